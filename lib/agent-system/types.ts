@@ -30,6 +30,7 @@ export interface Task {
   retryCount?: number;           // リトライ回数
   modelType?: 'gpt' | 'local' | 'gemini' | 'claude'; // 使用するLLMタイプ（オプション、AgentのmodelTypeを優先）
   selectedModel?: string;        // 選択されたモデル名（オプション、AgentのmodelTypeを優先）
+  promptVersion?: number;        // 実行に使うAgentのプロンプトのバージョン（固定はpin_task_prompt_version_commandでのみ変更）
   createdAt: number;             // 作成日時
   updatedAt: number;             // 更新日時
 }
@@ -43,6 +44,7 @@ export enum ExecutionStatus {
   COMPLETED = 'completed',       // 完了
   FAILED = 'failed',             // 失敗
   CANCELLED = 'cancelled',       // キャンセル
+  SKIPPED = 'skipped',           // スキップ（依存タスクが完了せず未実行）
 }
 
 /**
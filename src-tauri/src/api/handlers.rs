@@ -15,6 +15,7 @@ use crate::database::{
     get_all_themes, get_theme_by_id, save_theme as db_save_theme, create_theme as db_create_theme, delete_theme as db_delete_theme,
     Theme as DbTheme,
     get_doc, set_doc, update_doc, delete_doc, get_collection,
+    query_collection, CollectionQuery,
 };
 
 // ヘルスチェック
@@ -924,3 +925,36 @@ pub async fn delete_theme_handler(
         ))
     }
 }
+
+// コレクションクエリハンドラー
+// ボディは query_get と同じ構造化クエリ（where / orderBy / limit / offset / cursor）
+pub async fn query_collection_handler(
+    Path(collection): Path<String>,
+    body: Option<AxumJson<Value>>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let query = match body {
+        Some(AxumJson(value)) => CollectionQuery::from_value(&value).map_err(|e| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("クエリが不正です: {}", e) }))
+        ))?,
+        None => CollectionQuery::default(),
+    };
+    
+    match query_collection(&collection, &query) {
+        Ok(items) => Ok(Json(json!(items))),
+        Err(e) => {
+            let error_msg = format!("{}", e);
+            // テーブル名・フィールド名の検証エラーはクライアントエラーとして返す
+            let status = if error_msg.contains("無効なテーブル名") || error_msg.contains("無効なフィールド名")
+                || error_msg.contains("cursor") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            Err((
+                status,
+                Json(json!({ "error": format!("コレクションの取得に失敗しました: {}", e) }))
+            ))
+        }
+    }
+}
@@ -55,6 +55,9 @@ pub fn create_routes() -> Router {
         .route("/api/entities/:id", put(handlers::update_entity))
         .route("/api/entities/:id", delete(handlers::delete_entity))
         
+        // 汎用コレクションクエリAPI
+        .route("/api/collections/:collection/query", post(handlers::query_collection_handler))
+        
         // テーマ関連API
         .route("/api/themes", get(handlers::get_themes))
         .route("/api/themes", post(handlers::create_theme))
@@ -1,12 +1,58 @@
 use crate::database::{
     save_task, get_task, get_all_tasks, delete_task,
     save_task_execution, get_task_execution, get_task_executions, get_all_task_executions,
+    get_task_executions_by_chain_run,
     save_task_chain, get_task_chain, get_all_task_chains, delete_task_chain,
     save_agent, get_agent, get_all_agents, delete_agent,
     save_mcp_tool, get_mcp_tool_by_name, get_all_mcp_tools, get_enabled_mcp_tools, delete_mcp_tool,
     update_mcp_tool_enabled,
     Task, TaskExecution, TaskChain, Agent, MCPTool,
+    submit_task, cancel_task_execution, AgentTaskProgress, AgentTaskProgressCallback,
+    prepare_task_workflow, run_task_workflow, TaskWorkflowOptions,
+    start_task_chain_run, run_task_chain, cancel_task_chain_run, get_task_chain_run, list_task_chain_runs,
+    TaskChainRun,
+    send_a2a_message, get_a2a_message, wait_for_a2a_response, get_a2a_inbox, mark_a2a_messages_read,
+    get_a2a_task_threads, A2AMessage, A2AMessageDraft, A2AInboxOptions, A2AMessageThread, A2ABusEvent, A2ABusCallback,
+    list_agent_prompt_versions, get_agent_prompt_version, diff_agent_prompt_versions, rollback_agent_prompt,
+    pin_task_prompt_version, start_prompt_evaluation, run_prompt_evaluation, get_prompt_evaluation,
+    list_prompt_evaluations, AgentPromptVersion, AgentPromptDiff, PromptEvaluation, RevisionDiffMode,
 };
+use serde_json::Value;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+/// タスク実行の進捗イベント名（ペイロードはAgentTaskProgress）
+const AGENT_TASK_PROGRESS_EVENT: &str = "agent-task-progress";
+/// ワークフローの完了イベント名（ペイロードはTaskWorkflowReport）
+const TASK_WORKFLOW_FINISHED_EVENT: &str = "task-workflow-finished";
+/// タスクチェーンの実行の終了イベント名（ペイロードはTaskChainRun）
+const TASK_CHAIN_RUN_FINISHED_EVENT: &str = "task-chain-run-finished";
+/// プロンプトのA/B評価の終了イベント名（ペイロードはPromptEvaluation）
+const PROMPT_EVALUATION_FINISHED_EVENT: &str = "prompt-evaluation-finished";
+/// A2Aメッセージの送信イベント名（ペイロードはA2AMessage）
+const A2A_MESSAGE_EVENT: &str = "a2a-message";
+/// A2Aメッセージの応答期限切れイベント名（ペイロードは応答のなかったA2AMessage）
+const A2A_RESPONSE_TIMEOUT_EVENT: &str = "a2a-response-timeout";
+
+fn progress_emitter(app_handle: AppHandle) -> AgentTaskProgressCallback {
+    Arc::new(move |progress: AgentTaskProgress| {
+        if let Err(e) = app_handle.emit(AGENT_TASK_PROGRESS_EVENT, progress) {
+            eprintln!("⚠️ [agent_system] 進捗イベントの送信に失敗しました: {}", e);
+        }
+    })
+}
+
+fn a2a_emitter(app_handle: AppHandle) -> A2ABusCallback {
+    Arc::new(move |event: A2ABusEvent| {
+        let result = match event {
+            A2ABusEvent::Message(message) => app_handle.emit(A2A_MESSAGE_EVENT, message),
+            A2ABusEvent::ResponseTimeout(message) => app_handle.emit(A2A_RESPONSE_TIMEOUT_EVENT, message),
+        };
+        if let Err(e) = result {
+            eprintln!("⚠️ [agent_system] A2Aメッセージのイベントの送信に失敗しました: {}", e);
+        }
+    })
+}
 
 /// タスクを保存
 #[tauri::command]
@@ -56,6 +102,38 @@ pub async fn get_all_task_executions_command() -> Result<Vec<TaskExecution>, Str
     get_all_task_executions().map_err(|e| format!("実行履歴一覧の取得に失敗しました: {}", e))
 }
 
+/// タスクをRust側の実行エンジンで実行（キューに追加した実行をすぐに返し、進捗はagent-task-progressイベントで通知する）
+#[tauri::command]
+pub async fn execute_task_command(app_handle: AppHandle, task_id: String) -> Result<TaskExecution, String> {
+    submit_task(&task_id, progress_emitter(app_handle)).map_err(|e| format!("タスクの実行に失敗しました: {}", e))
+}
+
+/// タスクの実行をキャンセル（実行待ち・実行中でなかった場合はfalse）
+#[tauri::command]
+pub async fn cancel_task_execution_command(execution_id: String) -> Result<bool, String> {
+    cancel_task_execution(&execution_id).map_err(|e| format!("タスク実行のキャンセルに失敗しました: {}", e))
+}
+
+/// タスクを依存関係（dependencies）に従ってバックグラウンドで実行し、ワークフローIDを返す
+/// 各タスクの進捗はagent-task-progressイベント、結果はtask-workflow-finishedイベントで通知する
+#[tauri::command]
+pub async fn run_task_workflow_command(
+    app_handle: AppHandle,
+    task_ids: Vec<String>,
+    options: Option<TaskWorkflowOptions>,
+) -> Result<String, String> {
+    let workflow = prepare_task_workflow(&task_ids, &options.unwrap_or_default())
+        .map_err(|e| format!("ワークフローの開始に失敗しました: {}", e))?;
+    let workflow_id = workflow.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let report = run_task_workflow(workflow, progress_emitter(app_handle.clone())).await;
+        if let Err(e) = app_handle.emit(TASK_WORKFLOW_FINISHED_EVENT, report) {
+            eprintln!("⚠️ [run_task_workflow_command] 完了イベントの送信に失敗しました: {}", e);
+        }
+    });
+    Ok(workflow_id)
+}
+
 /// タスクチェーンを保存
 #[tauri::command]
 pub async fn save_task_chain_command(chain: TaskChain) -> Result<TaskChain, String> {
@@ -80,6 +158,50 @@ pub async fn delete_task_chain_command(chain_id: String) -> Result<(), String> {
     delete_task_chain(&chain_id).map_err(|e| format!("タスクチェーンの削除に失敗しました: {}", e))
 }
 
+/// タスクチェーンをバックグラウンドで実行し、チェーンの実行IDを返す
+/// ノードごとの進捗はagent-task-progressイベント、結果はtask-chain-run-finishedイベントで通知する
+#[tauri::command]
+pub async fn run_task_chain_command(
+    app_handle: AppHandle,
+    chain_id: String,
+    input: Option<Value>,
+) -> Result<String, String> {
+    let handle = start_task_chain_run(&chain_id, input)
+        .map_err(|e| format!("タスクチェーンの実行の開始に失敗しました: {}", e))?;
+    let run_id = handle.run.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let run = run_task_chain(handle, progress_emitter(app_handle.clone())).await;
+        if let Err(e) = app_handle.emit(TASK_CHAIN_RUN_FINISHED_EVENT, run) {
+            eprintln!("⚠️ [run_task_chain_command] 完了イベントの送信に失敗しました: {}", e);
+        }
+    });
+    Ok(run_id)
+}
+
+/// タスクチェーンの実行をキャンセル（実行中でなかった場合はfalse）
+#[tauri::command]
+pub async fn cancel_task_chain_run_command(run_id: String) -> Result<bool, String> {
+    Ok(cancel_task_chain_run(&run_id))
+}
+
+/// タスクチェーンの実行を取得
+#[tauri::command]
+pub async fn get_task_chain_run_command(run_id: String) -> Result<Option<TaskChainRun>, String> {
+    get_task_chain_run(&run_id).map_err(|e| format!("タスクチェーンの実行の取得に失敗しました: {}", e))
+}
+
+/// タスクチェーンの実行履歴を取得（新しい順）
+#[tauri::command]
+pub async fn list_task_chain_runs_command(chain_id: String) -> Result<Vec<TaskChainRun>, String> {
+    list_task_chain_runs(&chain_id).map_err(|e| format!("タスクチェーンの実行履歴の取得に失敗しました: {}", e))
+}
+
+/// タスクチェーンの実行でノードごとに記録した実行を取得（実行順）
+#[tauri::command]
+pub async fn get_task_chain_run_executions_command(run_id: String) -> Result<Vec<TaskExecution>, String> {
+    get_task_executions_by_chain_run(&run_id).map_err(|e| format!("実行履歴の取得に失敗しました: {}", e))
+}
+
 /// Agent定義を保存
 #[tauri::command]
 pub async fn save_agent_command(agent: Agent) -> Result<Agent, String> {
@@ -104,6 +226,117 @@ pub async fn delete_agent_command(agent_id: String) -> Result<(), String> {
     delete_agent(&agent_id).map_err(|e| format!("Agent定義の削除に失敗しました: {}", e))
 }
 
+/// Agentのプロンプトのバージョン一覧を取得（新しい順、先頭が現在のプロンプト）
+#[tauri::command]
+pub async fn list_agent_prompt_versions_command(agent_id: String) -> Result<Vec<AgentPromptVersion>, String> {
+    list_agent_prompt_versions(&agent_id).map_err(|e| format!("プロンプトのバージョン一覧の取得に失敗しました: {}", e))
+}
+
+/// Agentのプロンプトの指定したバージョンを取得
+#[tauri::command]
+pub async fn get_agent_prompt_version_command(agent_id: String, version: i64) -> Result<AgentPromptVersion, String> {
+    get_agent_prompt_version(&agent_id, version).map_err(|e| format!("プロンプトのバージョンの取得に失敗しました: {}", e))
+}
+
+/// 2つのバージョンのプロンプトの差分を取得（mode: 'unified'（既定）または 'word'）
+#[tauri::command]
+pub async fn diff_agent_prompt_versions_command(
+    agent_id: String,
+    from_version: i64,
+    to_version: i64,
+    mode: Option<String>,
+    context: Option<usize>,
+) -> Result<AgentPromptDiff, String> {
+    let mode = match mode.as_deref() {
+        Some(value) => RevisionDiffMode::parse(value)?,
+        None => RevisionDiffMode::default(),
+    };
+    diff_agent_prompt_versions(&agent_id, from_version, to_version, mode, context)
+        .map_err(|e| format!("プロンプトの差分の取得に失敗しました: {}", e))
+}
+
+/// Agentのプロンプトを指定したバージョンに戻す（新しいバージョンとして記録する）
+#[tauri::command]
+pub async fn rollback_agent_prompt_command(agent_id: String, version: i64) -> Result<Agent, String> {
+    rollback_agent_prompt(&agent_id, version).map_err(|e| format!("プロンプトのロールバックに失敗しました: {}", e))
+}
+
+/// タスクの実行に使うプロンプトのバージョンを固定（versionを省略すると固定を解除）
+#[tauri::command]
+pub async fn pin_task_prompt_version_command(task_id: String, version: Option<i64>) -> Result<Task, String> {
+    pin_task_prompt_version(&task_id, version).map_err(|e| format!("プロンプトのバージョンの固定に失敗しました: {}", e))
+}
+
+/// 同じタスクの組を2つのバージョンのプロンプトでバックグラウンドで実行し、評価IDを返す
+/// 各実行の進捗はagent-task-progressイベント、結果はprompt-evaluation-finishedイベントで通知する
+#[tauri::command]
+pub async fn run_prompt_evaluation_command(
+    app_handle: AppHandle,
+    agent_id: String,
+    version_a: i64,
+    version_b: i64,
+    task_ids: Vec<String>,
+) -> Result<String, String> {
+    let handle = start_prompt_evaluation(&agent_id, version_a, version_b, &task_ids)
+        .map_err(|e| format!("プロンプトの評価の開始に失敗しました: {}", e))?;
+    let evaluation_id = handle.evaluation.id.clone();
+    tauri::async_runtime::spawn(async move {
+        let evaluation = run_prompt_evaluation(handle, progress_emitter(app_handle.clone())).await;
+        if let Err(e) = app_handle.emit(PROMPT_EVALUATION_FINISHED_EVENT, evaluation) {
+            eprintln!("⚠️ [run_prompt_evaluation_command] 完了イベントの送信に失敗しました: {}", e);
+        }
+    });
+    Ok(evaluation_id)
+}
+
+/// プロンプトの評価を取得（タスクごとに2つのバージョンの実行を並べたもの）
+#[tauri::command]
+pub async fn get_prompt_evaluation_command(evaluation_id: String) -> Result<Option<PromptEvaluation>, String> {
+    get_prompt_evaluation(&evaluation_id).map_err(|e| format!("プロンプトの評価の取得に失敗しました: {}", e))
+}
+
+/// Agentのプロンプトの評価の一覧を取得（新しい順）
+#[tauri::command]
+pub async fn list_prompt_evaluations_command(agent_id: String) -> Result<Vec<PromptEvaluation>, String> {
+    list_prompt_evaluations(&agent_id).map_err(|e| format!("プロンプトの評価一覧の取得に失敗しました: {}", e))
+}
+
+/// A2Aメッセージを送信（送信先にはa2a-messageイベントで通知する）
+#[tauri::command]
+pub async fn send_a2a_message_command(app_handle: AppHandle, message: A2AMessageDraft) -> Result<A2AMessage, String> {
+    send_a2a_message(message, a2a_emitter(app_handle)).map_err(|e| format!("A2Aメッセージの送信に失敗しました: {}", e))
+}
+
+/// A2Aメッセージを取得
+#[tauri::command]
+pub async fn get_a2a_message_command(message_id: String) -> Result<Option<A2AMessage>, String> {
+    get_a2a_message(&message_id).map_err(|e| format!("A2Aメッセージの取得に失敗しました: {}", e))
+}
+
+/// 応答が必要なA2Aメッセージへの応答を待つ（応答期限が過ぎた場合はnull）
+#[tauri::command]
+pub async fn wait_for_a2a_response_command(message_id: String) -> Result<Option<A2AMessage>, String> {
+    wait_for_a2a_response(&message_id).await.map_err(|e| format!("A2Aメッセージの応答の取得に失敗しました: {}", e))
+}
+
+/// Agentの受信箱を取得（新しい順）
+#[tauri::command]
+pub async fn get_a2a_inbox_command(agent_id: String, options: Option<A2AInboxOptions>) -> Result<Vec<A2AMessage>, String> {
+    get_a2a_inbox(&agent_id, &options.unwrap_or_default()).map_err(|e| format!("受信箱の取得に失敗しました: {}", e))
+}
+
+/// Agent宛てのA2Aメッセージを既読にする（message_idsが空の場合は未読のメッセージすべて）
+#[tauri::command]
+pub async fn mark_a2a_messages_read_command(agent_id: String, message_ids: Vec<String>) -> Result<usize, String> {
+    mark_a2a_messages_read(&agent_id, &message_ids).map_err(|e| format!("A2Aメッセージの既読化に失敗しました: {}", e))
+}
+
+/// タスクに関するA2Aメッセージをスレッドとして取得
+#[tauri::command]
+pub async fn get_a2a_task_threads_command(task_id: String) -> Result<Vec<A2AMessageThread>, String> {
+    get_a2a_task_threads(&task_id).map_err(|e| format!("A2Aメッセージのスレッドの取得に失敗しました: {}", e))
+}
+
 /// MCPツールを保存
 #[tauri::command]
 pub async fn save_mcp_tool_command(tool: MCPTool) -> Result<MCPTool, String> {
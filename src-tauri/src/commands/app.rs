@@ -1,7 +1,8 @@
 use tauri::{AppHandle, Manager, State};
 use std::collections::HashMap;
 use std::fs;
-use crate::db::{WriteJob, WriteQueueState};
+use crate::db::{DeadWriteJob, WriteJob, WriteOutbox, WriteQueueState};
+use crate::database::{MigrationReport, MigrationStatus};
 
 #[tauri::command]
 pub async fn get_version() -> Result<String, String> {
@@ -231,6 +232,26 @@ pub async fn diagnose_database(app: AppHandle) -> Result<HashMap<String, String>
     Ok(diagnostics)
 }
 
+/// スキーマバージョンと適用済み・未適用のマイグレーション一覧を取得
+#[tauri::command]
+pub async fn get_schema_migration_status() -> Result<MigrationStatus, String> {
+    use crate::database::get_db;
+    
+    let db = get_db().ok_or("データベースが初期化されていません")?;
+    db.get_migration_status()
+        .map_err(|e| format!("マイグレーション状態の取得に失敗しました: {}", e))
+}
+
+/// 未適用のマイグレーションを実行（dry_run=trueの場合は実行結果のみ確認してロールバック）
+#[tauri::command]
+pub async fn run_schema_migrations(dry_run: Option<bool>) -> Result<MigrationReport, String> {
+    use crate::database::get_db;
+    
+    let db = get_db().ok_or("データベースが初期化されていません")?;
+    db.run_migrations(dry_run.unwrap_or(false))
+        .map_err(|e| format!("マイグレーションの実行に失敗しました: {}", e))
+}
+
 #[tauri::command]
 pub async fn update_chroma_sync_status(
     state: State<'_, WriteQueueState>,
@@ -239,17 +260,54 @@ pub async fn update_chroma_sync_status(
     synced: bool,
     error: Option<String>,
 ) -> Result<(), String> {
-    state.tx.send(WriteJob::UpdateChromaSyncStatus {
+    state.enqueue(WriteJob::UpdateChromaSyncStatus {
         entity_type,
         entity_id,
         synced,
         error,
-    }).await
-    .map_err(|e| format!("書き込みキューへの送信に失敗しました: {}", e))?;
+    }).await?;
     
     Ok(())
 }
 
+fn write_outbox(state: &WriteQueueState) -> Result<&WriteOutbox, String> {
+    state.outbox.as_deref().ok_or_else(|| "書き込みキューが初期化されていません".to_string())
+}
+
+/// 再試行の上限に達した書き込みジョブ（デッドレター）の一覧を取得
+#[tauri::command]
+pub async fn list_dead_write_jobs(
+    state: State<'_, WriteQueueState>,
+) -> Result<Vec<DeadWriteJob>, String> {
+    write_outbox(&state)?
+        .list_dead_letters()
+        .map_err(|e| format!("デッドレターの取得に失敗しました: {}", e))
+}
+
+/// デッドレターの書き込みジョブを再実行
+#[tauri::command]
+pub async fn retry_dead_write_job(
+    state: State<'_, WriteQueueState>,
+    id: i64,
+) -> Result<(), String> {
+    let outbox_id = write_outbox(&state)?
+        .retry_dead_letter(id)
+        .map_err(|e| format!("デッドレターの再実行に失敗しました: {}", e))?;
+    state.wake(outbox_id);
+    Ok(())
+}
+
+/// デッドレターの書き込みジョブを破棄
+#[tauri::command]
+pub async fn discard_dead_write_job(
+    state: State<'_, WriteQueueState>,
+    id: i64,
+) -> Result<(), String> {
+    write_outbox(&state)?
+        .discard_dead_letter(id)
+        .map_err(|e| format!("デッドレターの破棄に失敗しました: {}", e))
+}
+
 #[tauri::command]
 pub async fn get_table_schema(table_name: String) -> Result<HashMap<String, String>, String> {
     use crate::database::get_db;
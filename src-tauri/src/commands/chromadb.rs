@@ -3,22 +3,37 @@
  * JavaScript側からChromaDBを使用するためのAPI
  */
 
-use crate::database::chromadb;
-use serde_json::Value;
+use crate::database::{
+    self, chromadb, embedding_service_for, resolve_embedding, ChromaSyncOptions, ChromaSyncPendingCount,
+    ChromaSyncProgress, EmbeddingCollectionInfo, EmbeddingService, ReembedReport, TextEmbedder,
+};
+use crate::database::vector_search::{
+    org_collection_name, vector_store, EntitySearchFilter, RelationSearchFilter, TopicSearchFilter,
+    COLLECTION_ENTITIES, COLLECTION_RELATIONS, COLLECTION_TOPICS,
+};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
 use std::collections::HashMap;
 
 /// エンティティ埋め込みを保存
+/// combinedEmbeddingを省略した場合はmetadataのsearchableText（なければname）からサーバー側で生成する
 #[tauri::command]
 pub async fn chromadb_save_entity_embedding(
     entityId: String,
     organizationId: String,
-    combinedEmbedding: Vec<f32>,
+    combinedEmbedding: Option<Vec<f32>>,
     metadata: HashMap<String, Value>,
 ) -> Result<(), String> {
+    let embedding = resolve_embedding(
+        &org_collection_name(COLLECTION_ENTITIES, &organizationId),
+        combinedEmbedding,
+        &metadata,
+        &["name"],
+    ).await?;
     chromadb::save_entity_embedding(
         entityId,
         organizationId,
-        combinedEmbedding,
+        embedding,
         metadata,
     ).await
 }
@@ -32,14 +47,15 @@ pub async fn chromadb_get_entity_embedding(
     chromadb::get_entity_embedding(entityId, organizationId).await
 }
 
-/// 類似エンティティを検索
+/// 類似エンティティを検索（filterでエンティティの種類を絞り込み可能）
 #[tauri::command]
 pub async fn chromadb_find_similar_entities(
     queryEmbedding: Vec<f32>,
     limit: usize,
     organizationId: Option<String>,
+    filter: Option<EntitySearchFilter>,
 ) -> Result<Vec<(String, f32)>, String> {
-    chromadb::find_similar_entities(queryEmbedding, limit, organizationId).await
+    chromadb::find_similar_entities(queryEmbedding, limit, organizationId, filter).await
 }
 
 /// エンティティコレクションの件数を取得
@@ -51,17 +67,24 @@ pub async fn chromadb_count_entities(
 }
 
 /// リレーション埋め込みを保存
+/// combinedEmbeddingを省略した場合はmetadataのsearchableText（なければrelationTypeとdescription）からサーバー側で生成する
 #[tauri::command]
 pub async fn chromadb_save_relation_embedding(
     relationId: String,
     organizationId: String,
-    combinedEmbedding: Vec<f32>,
+    combinedEmbedding: Option<Vec<f32>>,
     metadata: HashMap<String, Value>,
 ) -> Result<(), String> {
+    let embedding = resolve_embedding(
+        &org_collection_name(COLLECTION_RELATIONS, &organizationId),
+        combinedEmbedding,
+        &metadata,
+        &["relationType", "description"],
+    ).await?;
     chromadb::save_relation_embedding(
         relationId,
         organizationId,
-        combinedEmbedding,
+        embedding,
         metadata,
     ).await
 }
@@ -75,31 +98,39 @@ pub async fn chromadb_get_relation_embedding(
     chromadb::get_relation_embedding(relationId, organizationId).await
 }
 
-/// 類似リレーションを検索
+/// 類似リレーションを検索（filterでリレーションの種類・トピックを絞り込み可能）
 #[tauri::command]
 pub async fn chromadb_find_similar_relations(
     queryEmbedding: Vec<f32>,
     limit: usize,
     organizationId: Option<String>,
+    filter: Option<RelationSearchFilter>,
 ) -> Result<Vec<(String, f32)>, String> {
-    chromadb::find_similar_relations(queryEmbedding, limit, organizationId).await
+    chromadb::find_similar_relations(queryEmbedding, limit, organizationId, filter).await
 }
 
 /// トピック埋め込みを保存
+/// combinedEmbeddingを省略した場合はmetadataのsearchableText（なければtitleとcontentSummary）からサーバー側で生成する
 #[tauri::command]
 pub async fn chromadb_save_topic_embedding(
     topicId: String,
     meetingNoteId: Option<String>,
     organizationId: String,
-    combinedEmbedding: Vec<f32>,
+    combinedEmbedding: Option<Vec<f32>>,
     metadata: HashMap<String, Value>,
     regulationId: Option<String>,
 ) -> Result<(), String> {
+    let embedding = resolve_embedding(
+        &org_collection_name(COLLECTION_TOPICS, &organizationId),
+        combinedEmbedding,
+        &metadata,
+        &["title", "contentSummary"],
+    ).await?;
     chromadb::save_topic_embedding(
         topicId,
         meetingNoteId,
         organizationId,
-        combinedEmbedding,
+        embedding,
         metadata,
         regulationId,
     ).await
@@ -114,14 +145,15 @@ pub async fn chromadb_get_topic_embedding(
     chromadb::get_topic_embedding(topicId, organizationId).await
 }
 
-/// 類似トピックを検索
+/// 類似トピックを検索（filterでsemanticCategory・議事録・topicDateの範囲を絞り込み可能）
 #[tauri::command]
 pub async fn chromadb_find_similar_topics(
     queryEmbedding: Vec<f32>,
     limit: usize,
     organizationId: Option<String>,
+    filter: Option<TopicSearchFilter>,
 ) -> Result<Vec<chromadb::TopicSearchResult>, String> {
-    chromadb::find_similar_topics(queryEmbedding, limit, organizationId).await
+    chromadb::find_similar_topics(queryEmbedding, limit, organizationId, filter).await
 }
 
 /// システム設計ドキュメント埋め込みを保存
@@ -199,3 +231,93 @@ pub async fn chromadb_delete_organization_collections(
 ) -> Result<(), String> {
     chromadb::delete_organization_collections(organizationId).await
 }
+
+/// ChromaDB同期の進捗イベント名
+const CHROMA_SYNC_PROGRESS_EVENT: &str = "chroma-sync-progress";
+/// ChromaDB同期の完了イベント名（ペイロードはChromaSyncReport、失敗時は{ "error": ... }）
+const CHROMA_SYNC_FINISHED_EVENT: &str = "chroma-sync-finished";
+
+/// 未同期（chromaSynced = 0）の行をバックグラウンドでベクトルストアに同期（deleteOrphansを指定した場合は孤立したベクトルも削除）
+/// 進捗はchroma-sync-progressイベント、結果はchroma-sync-finishedイベントで通知する
+#[tauri::command]
+pub async fn start_chroma_sync(
+    app: AppHandle,
+    options: Option<ChromaSyncOptions>,
+) -> Result<(), String> {
+    if database::is_chroma_sync_running() {
+        return Err("ChromaDB同期は既に実行中です".to_string());
+    }
+    let store = vector_store()?;
+    // コレクションごとに、埋め込みレジストリに登録されたプロバイダー・モデルを使う
+    let embedder_for = |collection: &str| -> Result<Box<dyn TextEmbedder>, String> {
+        Ok(Box::new(embedding_service_for(collection)?))
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let progress_app = app.clone();
+        let on_progress = move |progress: ChromaSyncProgress| {
+            if let Err(e) = progress_app.emit(CHROMA_SYNC_PROGRESS_EVENT, progress) {
+                eprintln!("⚠️ [start_chroma_sync] 進捗イベントの送信に失敗しました: {}", e);
+            }
+        };
+        let result = database::run_chroma_sync(&*store, &embedder_for, options.unwrap_or_default(), &on_progress).await;
+        let emitted = match result {
+            Ok(report) => app.emit(CHROMA_SYNC_FINISHED_EVENT, report),
+            Err(e) => {
+                eprintln!("❌ [start_chroma_sync] ChromaDB同期に失敗しました: {}", e);
+                app.emit(CHROMA_SYNC_FINISHED_EVENT, json!({ "error": e }))
+            }
+        };
+        if let Err(e) = emitted {
+            eprintln!("⚠️ [start_chroma_sync] 完了イベントの送信に失敗しました: {}", e);
+        }
+    });
+    Ok(())
+}
+
+/// 実行中のChromaDB同期を中断（実行中でなかった場合はfalse）
+#[tauri::command]
+pub async fn cancel_chroma_sync() -> Result<bool, String> {
+    Ok(database::cancel_chroma_sync())
+}
+
+/// 種別ごとの未同期件数を取得
+#[tauri::command]
+pub async fn get_chroma_sync_pending_counts() -> Result<Vec<ChromaSyncPendingCount>, String> {
+    database::get_chroma_sync_pending_counts()
+}
+
+/// 設定されたプロバイダーでテキストの埋め込みを生成
+/// collectionを指定した場合は、そのコレクションに登録されたプロバイダー・モデルを使う（検索クエリの埋め込み用）
+/// providerを省略した場合は環境変数EMBEDDING_PROVIDER（未設定の場合はopenai）
+#[tauri::command]
+pub async fn generate_embeddings(
+    texts: Vec<String>,
+    provider: Option<String>,
+    collection: Option<String>,
+) -> Result<Vec<Vec<f32>>, String> {
+    let service = match collection {
+        Some(collection) => embedding_service_for(&collection)?,
+        None => EmbeddingService::from_ai_settings(provider.as_deref())?,
+    };
+    service.embed_texts(texts).await
+}
+
+/// コレクションごとの埋め込みモデル・次元数の一覧
+#[tauri::command]
+pub async fn list_embedding_collections() -> Result<Vec<EmbeddingCollectionInfo>, String> {
+    database::list_embedding_collections()
+}
+
+/// コレクション全体を指定モデルで再埋め込みし、新しいコレクションに切り替える
+/// provider・modelを省略した場合はgenerate_embeddingsと同じ設定を使う
+#[tauri::command]
+pub async fn reembed_collection(
+    collection: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ReembedReport, String> {
+    database::reembed_collection(collection, provider, model)
+        .await
+        .map_err(|e| format!("再埋め込みに失敗しました: {}", e))
+}
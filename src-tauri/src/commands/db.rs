@@ -2,7 +2,17 @@ use crate::database::{sign_in as db_sign_in, sign_up as db_sign_up, sign_out as
                       get_current_user as db_get_current_user, get_doc, set_doc, update_doc, delete_doc, add_doc, get_collection,
                       export_to_file, import_from_file, export_organizations_and_members_to_file,
                       delete_meeting_note_with_relations as db_delete_meeting_note_with_relations,
-                      update_meeting_note_item_content as db_update_meeting_note_item_content};
+                      update_meeting_note_item_content as db_update_meeting_note_item_content,
+                      search_full_text, rebuild_full_text_index, FullTextSearchOptions, FullTextSearchHit,
+                      hybrid_search as db_hybrid_search, HybridSearchOptions, HybridSearchResult,
+                      graph_neighborhood, graph_shortest_path, graph_connected_components, graph_centrality,
+                      GraphResult, GraphNeighborhoodOptions, GraphShortestPathOptions, GraphComponentsOptions, GraphCentralityOptions,
+                      find_duplicate_entities as db_find_duplicate_entities, merge_entities as db_merge_entities,
+                      list_entity_merge_audit as db_list_entity_merge_audit,
+                      DuplicateEntityOptions, DuplicateEntityCandidate, MergeEntitiesOptions, EntityMergeReport, EntityMergeAuditEntry,
+                      export_knowledge_graph as db_export_knowledge_graph,
+                      export_knowledge_graph_to_file as db_export_knowledge_graph_to_file,
+                      GraphExportOptions, GraphExportSummary};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -374,6 +384,100 @@ pub async fn query_get(collection_name: String, conditions: Option<HashMap<Strin
     }
 }
 
+/// キーワードによる全文検索（FTS5、ベクトル検索が使えない環境でも動作）
+#[tauri::command]
+pub async fn full_text_search(options: FullTextSearchOptions) -> Result<Vec<FullTextSearchHit>, String> {
+    search_full_text(&options)
+        .map_err(|e| format!("全文検索に失敗しました: {}", e))
+}
+
+/// 全文検索の索引を元テーブルから再構築
+#[tauri::command]
+pub async fn rebuild_full_text_search_index() -> Result<usize, String> {
+    eprintln!("🔄 [rebuild_full_text_search_index] 全文検索の索引を再構築します");
+    let count = rebuild_full_text_index()
+        .map_err(|e| format!("全文検索の索引の再構築に失敗しました: {}", e))?;
+    eprintln!("✅ [rebuild_full_text_search_index] {}件を索引しました", count);
+    Ok(count)
+}
+
+/// キーワード検索とベクトル検索をRRFで統合したハイブリッド検索（エンティティ・リレーション・トピック）
+#[tauri::command]
+pub async fn hybrid_search(options: HybridSearchOptions) -> Result<Vec<HybridSearchResult>, String> {
+    db_hybrid_search(options).await
+        .map_err(|e| format!("ハイブリッド検索に失敗しました: {}", e))
+}
+
+/// エンティティからkホップ以内のナレッジグラフを取得
+#[tauri::command]
+pub async fn get_graph_neighborhood(options: GraphNeighborhoodOptions) -> Result<GraphResult, String> {
+    graph_neighborhood(&options)
+        .map_err(|e| format!("近傍グラフの取得に失敗しました: {}", e))
+}
+
+/// 2つのエンティティ間の最短経路を取得
+#[tauri::command]
+pub async fn find_graph_shortest_path(options: GraphShortestPathOptions) -> Result<GraphResult, String> {
+    graph_shortest_path(&options)
+        .map_err(|e| format!("最短経路の取得に失敗しました: {}", e))
+}
+
+/// ナレッジグラフの連結成分を取得
+#[tauri::command]
+pub async fn get_graph_connected_components(options: GraphComponentsOptions) -> Result<GraphResult, String> {
+    graph_connected_components(&options)
+        .map_err(|e| format!("連結成分の取得に失敗しました: {}", e))
+}
+
+/// 次数またはPageRankの中心性が高いエンティティを取得
+#[tauri::command]
+pub async fn get_graph_centrality(options: GraphCentralityOptions) -> Result<GraphResult, String> {
+    graph_centrality(&options)
+        .map_err(|e| format!("中心性の計算に失敗しました: {}", e))
+}
+
+/// 名前・エイリアス・（任意で）埋め込みの類似度から重複の可能性が高いエンティティを取得
+#[tauri::command]
+pub async fn find_duplicate_entities(options: DuplicateEntityOptions) -> Result<Vec<DuplicateEntityCandidate>, String> {
+    db_find_duplicate_entities(options).await
+        .map_err(|e| format!("重複エンティティの検出に失敗しました: {}", e))
+}
+
+/// 重複エンティティを1つに統合（リレーションの付け替え・エイリアスとメタデータのマージ・監査ログの記録）
+#[tauri::command]
+pub async fn merge_entities(options: MergeEntitiesOptions) -> Result<EntityMergeReport, String> {
+    db_merge_entities(options).await
+        .map_err(|e| format!("エンティティの統合に失敗しました: {}", e))
+}
+
+/// エンティティ統合の監査ログを取得
+#[tauri::command]
+pub async fn list_entity_merge_audit(entity_id: Option<String>) -> Result<Vec<EntityMergeAuditEntry>, String> {
+    db_list_entity_merge_audit(entity_id)
+        .map_err(|e| format!("統合の監査ログの取得に失敗しました: {}", e))
+}
+
+/// ナレッジグラフをGraphML・JSON-LD・Cypher・Turtleの文字列として取得
+#[tauri::command]
+pub async fn export_knowledge_graph(options: GraphExportOptions) -> Result<String, String> {
+    db_export_knowledge_graph(&options)
+        .map(|(content, _)| content)
+        .map_err(|e| format!("ナレッジグラフのエクスポートに失敗しました: {}", e))
+}
+
+/// ナレッジグラフをGraphML・JSON-LD・Cypher・Turtleのファイルに書き出す
+#[tauri::command]
+pub async fn export_knowledge_graph_to_file(export_path: String, options: GraphExportOptions) -> Result<GraphExportSummary, String> {
+    eprintln!("📤 [export_knowledge_graph_to_file] ナレッジグラフのエクスポートを開始します: {}", export_path);
+    let summary = db_export_knowledge_graph_to_file(&export_path, &options)
+        .map_err(|e| format!("ナレッジグラフのエクスポートに失敗しました: {}", e))?;
+    eprintln!(
+        "✅ [export_knowledge_graph_to_file] エクスポート成功: {} (エンティティ: {}件, リレーション: {}件)",
+        export_path, summary.entity_count, summary.relation_count
+    );
+    Ok(summary)
+}
+
 #[tauri::command]
 pub async fn export_database_data(export_path: String) -> Result<HashMap<String, Value>, String> {
     eprintln!("📤 [export_database_data] データベースのエクスポートを開始します: {}", export_path);
@@ -9,7 +9,18 @@ use crate::database::{
     create_design_doc_section_relation, update_design_doc_section_relation,
     get_design_doc_section_relation_by_id, get_design_doc_section_relations_by_section_id,
     get_all_design_doc_section_relations, delete_design_doc_section_relation,
+    list_design_doc_section_revisions, get_design_doc_section_revision,
+    diff_design_doc_section_revisions, restore_design_doc_section_revision, merge_design_doc_section,
+    RevisionContext, RevisionDiffMode,
+    export_design_doc_site, DesignDocSiteOptions, DesignDocSiteSummary,
+    check_design_doc_consistency,
 };
+use crate::commands::plantuml_worker::{render_plantuml_batch_cached, PlantUmlFormat};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// 静的サイトに同梱するMermaidのスクリプト
+const MERMAID_SCRIPT_FILE: &str = "mermaid.min.js";
 
 /// セクションを作成
 #[tauri::command]
@@ -59,6 +70,9 @@ pub fn update_design_doc_section_cmd(
     semantic_category: Option<String>,
     keywords: Option<Vec<String>>,
     summary: Option<String>,
+    author: Option<String>,
+    base_revision_id: Option<String>,
+    message: Option<String>,
 ) -> Result<serde_json::Value, String> {
     match update_design_doc_section(
         &id,
@@ -73,6 +87,7 @@ pub fn update_design_doc_section_cmd(
         semantic_category,
         keywords,
         summary,
+        RevisionContext { author, base_revision_id, message },
     ) {
         Ok(section) => Ok(serde_json::to_value(section).unwrap()),
         Err(e) => Err(format!("セクションの更新に失敗しました: {}", e)),
@@ -115,6 +130,127 @@ pub fn delete_design_doc_section_cmd(id: String) -> Result<(), String> {
     }
 }
 
+/// セクションのリビジョン一覧を取得（新しい順）
+#[tauri::command]
+pub fn list_design_doc_section_revisions_cmd(section_id: String) -> Result<serde_json::Value, String> {
+    match list_design_doc_section_revisions(&section_id) {
+        Ok(revisions) => Ok(serde_json::to_value(revisions).unwrap()),
+        Err(e) => Err(format!("リビジョン一覧の取得に失敗しました: {}", e)),
+    }
+}
+
+/// IDでリビジョンを取得
+#[tauri::command]
+pub fn get_design_doc_section_revision_cmd(revision_id: String) -> Result<serde_json::Value, String> {
+    match get_design_doc_section_revision(&revision_id) {
+        Ok(revision) => Ok(serde_json::to_value(revision).unwrap()),
+        Err(e) => Err(format!("リビジョンの取得に失敗しました: {}", e)),
+    }
+}
+
+/// 2つのリビジョンの差分を取得（mode: 'unified'（既定）または 'word'）
+#[tauri::command]
+pub fn diff_design_doc_section_revisions_cmd(
+    from_revision_id: String,
+    to_revision_id: String,
+    mode: Option<String>,
+    context: Option<usize>,
+) -> Result<serde_json::Value, String> {
+    let mode = match mode.as_deref() {
+        Some(value) => RevisionDiffMode::parse(value)?,
+        None => RevisionDiffMode::default(),
+    };
+    match diff_design_doc_section_revisions(&from_revision_id, &to_revision_id, mode, context) {
+        Ok(diff) => Ok(serde_json::to_value(diff).unwrap()),
+        Err(e) => Err(format!("リビジョンの差分の取得に失敗しました: {}", e)),
+    }
+}
+
+/// セクションを指定したリビジョンの内容に戻す
+#[tauri::command]
+pub fn restore_design_doc_section_revision_cmd(
+    section_id: String,
+    revision_id: String,
+    author: Option<String>,
+) -> Result<serde_json::Value, String> {
+    match restore_design_doc_section_revision(&section_id, &revision_id, author) {
+        Ok(section) => Ok(serde_json::to_value(section).unwrap()),
+        Err(e) => Err(format!("リビジョンの復元に失敗しました: {}", e)),
+    }
+}
+
+/// base_revision_idを元にした編集を最新のリビジョンと3-wayマージ（競合がなければ保存）
+#[tauri::command]
+pub fn merge_design_doc_section_cmd(
+    section_id: String,
+    base_revision_id: String,
+    title: Option<String>,
+    content: String,
+    author: Option<String>,
+) -> Result<serde_json::Value, String> {
+    match merge_design_doc_section(&section_id, &base_revision_id, title, content, author) {
+        Ok(result) => Ok(serde_json::to_value(result).unwrap()),
+        Err(e) => Err(format!("セクションのマージに失敗しました: {}", e)),
+    }
+}
+
+/// セクション間の参照（relatedSections・hierarchy・セクション関係）の整合性をチェック（auto_fixでリンク切れを取り除く）
+#[tauri::command]
+pub fn check_design_doc_consistency_cmd(auto_fix: Option<bool>) -> Result<serde_json::Value, String> {
+    match check_design_doc_consistency(auto_fix.unwrap_or(false)) {
+        Ok(report) => Ok(serde_json::to_value(report).unwrap()),
+        Err(e) => Err(format!("整合性チェックに失敗しました: {}", e)),
+    }
+}
+
+/// 静的サイトに同梱するmermaid.min.jsを探す（リソースディレクトリ → アプリデータディレクトリ → 開発環境のresources/）
+fn find_mermaid_script(app_handle: &AppHandle) -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Ok(resource_dir) = app_handle.path().resource_dir() {
+        candidates.push(resource_dir.join(MERMAID_SCRIPT_FILE));
+        candidates.push(resource_dir.join("resources").join(MERMAID_SCRIPT_FILE));
+    }
+    if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+        candidates.push(app_data_dir.join(MERMAID_SCRIPT_FILE));
+    }
+    candidates.push(PathBuf::from("src-tauri/resources").join(MERMAID_SCRIPT_FILE));
+    candidates.push(PathBuf::from("resources").join(MERMAID_SCRIPT_FILE));
+    let found = candidates.into_iter().find(|path| path.is_file());
+    if found.is_none() {
+        eprintln!("⚠️ [export_design_doc_site_cmd] {}が見つかりません（Mermaidの図はコードのまま出力します）", MERMAID_SCRIPT_FILE);
+    }
+    found
+}
+
+/// 設計ドキュメントを静的サイト（HTMLまたはMarkdown）としてoutput_dirに書き出す
+#[tauri::command]
+pub async fn export_design_doc_site_cmd(
+    app_handle: AppHandle,
+    output_dir: String,
+    options: DesignDocSiteOptions,
+) -> Result<DesignDocSiteSummary, String> {
+    eprintln!("📤 [export_design_doc_site_cmd] 設計ドキュメントの書き出しを開始します: {}", output_dir);
+    // PlantUMLの一括レンダリングの完了を待つため、ブロッキング用のスレッドで実行
+    let mermaid_script = find_mermaid_script(&app_handle);
+    let summary = tokio::task::spawn_blocking(move || {
+        let mut render = |codes: Vec<String>| {
+            tauri::async_runtime::block_on(render_plantuml_batch_cached(&app_handle, codes, PlantUmlFormat::Svg))
+                .into_iter()
+                .map(|result| result.data.ok_or_else(|| result.error.unwrap_or_default()))
+                .collect()
+        };
+        export_design_doc_site(&output_dir, &options, &mut render, mermaid_script.as_deref())
+    })
+    .await
+    .map_err(|e| format!("設計ドキュメントの書き出しタスクの実行に失敗しました: {}", e))?
+    .map_err(|e| format!("設計ドキュメントの書き出しに失敗しました: {}", e))?;
+    eprintln!(
+        "✅ [export_design_doc_site_cmd] 書き出し成功: {} (ページ: {}件, 図: {}件, 図のエラー: {}件)",
+        summary.output_dir, summary.page_count, summary.diagram_count, summary.diagram_errors.len()
+    );
+    Ok(summary)
+}
+
 /// セクション関係を作成
 #[tauri::command]
 pub fn create_design_doc_section_relation_cmd(
@@ -7,6 +7,9 @@ use crate::database::{
     create_graphviz_yaml_file, update_graphviz_yaml_file, get_graphviz_yaml_file_by_id,
     get_all_graphviz_yaml_files, delete_graphviz_yaml_file,
     create_graphviz_dot_file, get_graphviz_dot_file_by_yaml_file_id,
+    generate_graphviz_dot, generate_and_save_graphviz_dot, DotGenerationOptions, DotSaveOptions,
+    render_graphviz_dot, GraphvizRenderFormat, GraphvizRenderEngine,
+    list_graphviz_yaml_versions, diff_graphviz_yaml_versions, rollback_graphviz_yaml_file, validate_yaml,
 };
 
 /// YAMLファイルを作成
@@ -92,6 +95,45 @@ pub fn delete_graphviz_yaml_file_cmd(id: String) -> Result<(), String> {
     }
 }
 
+/// YAMLを構文チェックし、スキーマで検証する（保存はしない）
+#[tauri::command]
+pub fn validate_graphviz_yaml_cmd(
+    yaml_content: String,
+    yaml_schema: Option<String>,
+) -> Result<serde_json::Value, String> {
+    match validate_yaml(&yaml_content, yaml_schema.as_deref()) {
+        Ok(report) => Ok(serde_json::to_value(report).unwrap()),
+        Err(e) => Err(format!("YAMLの検証に失敗しました: {}", e)),
+    }
+}
+
+/// YAMLファイルのバージョン一覧を取得
+#[tauri::command]
+pub fn list_graphviz_yaml_versions_cmd(id: String) -> Result<Vec<serde_json::Value>, String> {
+    match list_graphviz_yaml_versions(&id) {
+        Ok(versions) => Ok(versions.into_iter().map(|v| serde_json::to_value(v).unwrap()).collect()),
+        Err(e) => Err(format!("YAMLファイルのバージョン一覧の取得に失敗しました: {}", e)),
+    }
+}
+
+/// YAMLファイルの2つのバージョンの差分を取得
+#[tauri::command]
+pub fn diff_graphviz_yaml_versions_cmd(from_id: String, to_id: String) -> Result<serde_json::Value, String> {
+    match diff_graphviz_yaml_versions(&from_id, &to_id) {
+        Ok(diff) => Ok(serde_json::to_value(diff).unwrap()),
+        Err(e) => Err(format!("YAMLファイルの差分の取得に失敗しました: {}", e)),
+    }
+}
+
+/// YAMLファイルを過去のバージョンに戻す
+#[tauri::command]
+pub fn rollback_graphviz_yaml_file_cmd(id: String, version_id: String) -> Result<serde_json::Value, String> {
+    match rollback_graphviz_yaml_file(&id, &version_id) {
+        Ok(yaml_file) => Ok(serde_json::to_value(yaml_file).unwrap()),
+        Err(e) => Err(format!("YAMLファイルのロールバックに失敗しました: {}", e)),
+    }
+}
+
 /// DOTファイルを作成
 #[tauri::command]
 pub fn create_graphviz_dot_file_cmd(
@@ -133,3 +175,50 @@ pub fn get_graphviz_dot_file_cmd(yaml_file_id: String) -> Result<Option<serde_js
     }
 }
 
+
+/// サブグラフまたはYAMLファイルからDOTを生成
+#[tauri::command]
+pub fn generate_graphviz_dot_cmd(options: DotGenerationOptions) -> Result<serde_json::Value, String> {
+    match generate_graphviz_dot(&options) {
+        Ok(result) => Ok(serde_json::to_value(result).unwrap()),
+        Err(e) => Err(format!("DOTの生成に失敗しました: {}", e)),
+    }
+}
+
+/// DOTを生成してDOTファイルとして保存
+#[tauri::command]
+pub fn generate_and_save_graphviz_dot_cmd(
+    options: DotGenerationOptions,
+    save: DotSaveOptions,
+) -> Result<serde_json::Value, String> {
+    match generate_and_save_graphviz_dot(&options, save) {
+        Ok(dot_file) => Ok(serde_json::to_value(dot_file).unwrap()),
+        Err(e) => Err(format!("DOTの生成・保存に失敗しました: {}", e)),
+    }
+}
+
+/// DOTをSVG/PNGにレンダリングする
+#[tauri::command]
+pub async fn render_graphviz(
+    dot_content: String,
+    format: String, // "svg" or "png"
+    engine: Option<String>, // "auto"（デフォルト）, "dot", "native"
+) -> Result<Vec<u8>, String> {
+    let format = GraphvizRenderFormat::parse(&format)?;
+    let engine = match engine {
+        Some(engine) => GraphvizRenderEngine::parse(&engine)?,
+        None => GraphvizRenderEngine::default(),
+    };
+    match render_graphviz_dot(&dot_content, format, engine).await {
+        Ok(rendered) => {
+            eprintln!(
+                "✅ [Graphviz] レンダリングしました: {} bytes（ハッシュ: {}、キャッシュ: {}）",
+                rendered.data.len(),
+                rendered.content_hash,
+                rendered.cached
+            );
+            Ok(rendered.data)
+        }
+        Err(e) => Err(format!("Graphvizのレンダリングに失敗しました: {}", e)),
+    }
+}
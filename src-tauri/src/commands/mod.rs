@@ -8,6 +8,7 @@ pub mod fs;
 pub mod chromadb;
 pub mod design_doc;
 pub mod plantuml;
+pub mod plantuml_worker;
 pub mod agent_system;
 pub mod system;
 pub mod graphviz;
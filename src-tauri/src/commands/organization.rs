@@ -57,11 +57,10 @@ pub async fn create_org(
     }
     
     // 書き込みキューに送信
-    state.tx.send(WriteJob::UpsertOrganization {
+    state.enqueue(WriteJob::UpsertOrganization {
         organization_id: organization_id.clone(),
         payload,
-    }).await
-    .map_err(|e| format!("書き込みキューへの送信に失敗しました: {}", e))?;
+    }).await?;
     
     // 作成された組織の情報を返す（IDと基本情報のみ）
     Ok(json!({
@@ -115,11 +114,10 @@ pub async fn update_org(
     }
     
     // 書き込みキューに送信
-    state.tx.send(WriteJob::UpsertOrganization {
+    state.enqueue(WriteJob::UpsertOrganization {
         organization_id: id.clone(),
         payload,
-    }).await
-    .map_err(|e| format!("書き込みキューへの送信に失敗しました: {}", e))?;
+    }).await?;
     
     // 更新後の組織情報を返す
     Ok(json!({
@@ -169,11 +167,10 @@ pub async fn update_org_parent(
     }
     
     // 書き込みキューに送信
-    state.tx.send(WriteJob::UpsertOrganization {
+    state.enqueue(WriteJob::UpsertOrganization {
         organization_id: id.clone(),
         payload,
-    }).await
-    .map_err(|e| format!("書き込みキューへの送信に失敗しました: {}", e))?;
+    }).await?;
     
     // 更新後の組織情報を返す
     Ok(json!({
@@ -1,12 +1,12 @@
-use std::process::{Command, Stdio};
+use std::process::Command;
 use std::path::PathBuf;
-use std::fs;
-use std::io::Write;
 use anyhow::Result;
 use tauri::{AppHandle, Manager};
 
+use super::plantuml_worker::{render_plantuml_batch_cached, render_plantuml_cached, PlantUmlBatchResult, PlantUmlFormat};
+
 /// Javaのパスを検出する
-fn detect_java() -> Result<PathBuf> {
+pub(crate) fn detect_java() -> Result<PathBuf> {
     // 1. JAVA_HOME環境変数を確認
     if let Ok(java_home) = std::env::var("JAVA_HOME") {
         let java_path = PathBuf::from(&java_home).join("bin").join("java");
@@ -215,7 +215,7 @@ fn detect_java() -> Result<PathBuf> {
 }
 
 /// PlantUML JARファイルのパスを取得
-fn get_plantuml_jar_path(app_handle: &AppHandle) -> Result<PathBuf> {
+pub(crate) fn get_plantuml_jar_path(app_handle: &AppHandle) -> Result<PathBuf> {
     // 1. リソースディレクトリからplantuml.jarを探す（本番環境で最も重要）
     if let Ok(resource_dir) = app_handle.path().resource_dir() {
         eprintln!("🔍 [PlantUML] リソースディレクトリを確認: {}", resource_dir.display());
@@ -323,159 +323,60 @@ fn get_plantuml_jar_path(app_handle: &AppHandle) -> Result<PathBuf> {
     );
 }
 
-/// PlantUMLコードをレンダリングする
+/// PlantUMLコードをレンダリングする（常駐ワーカーで処理し、結果はキャッシュする）
 #[tauri::command]
 pub async fn render_plantuml(
     app_handle: AppHandle,
     code: String,
     format: String, // "svg" or "png"
 ) -> Result<Vec<u8>, String> {
-    // フォーマットの検証
-    let format = format.to_lowercase();
-    if format != "svg" && format != "png" {
-        return Err(format!("無効なフォーマット: {}. 'svg' または 'png' を指定してください。", format));
-    }
-    
-    // 1. Javaのパスを検出
-    let java_path = detect_java().map_err(|e| e.to_string())?;
-    
-    // 2. PlantUML JARファイルのパスを取得
-    let jar_path = get_plantuml_jar_path(&app_handle).map_err(|e| e.to_string())?;
-    
-    // 3. PlantUMLコードを一時ファイルに保存
-    let temp_dir = std::env::temp_dir();
-    let temp_file = temp_dir.join(format!("plantuml_{}.puml", uuid::Uuid::new_v4()));
-    
-    fs::write(&temp_file, &code)
-        .map_err(|e| format!("一時ファイルの作成に失敗しました: {}", e))?;
-    
-    // 4. Javaプロセスを起動してPlantUMLを実行（標準出力に出力）
-    // PlantUMLは-pipeオプションで標準出力にSVG/PNGを出力できる
-    let mut child = Command::new(&java_path)
-        .arg("-jar")
-        .arg(&jar_path)
-        .arg("-pipe")
-        .arg(&format!("-t{}", format))
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("PlantUMLプロセスの起動に失敗しました: {}", e))?;
-    
-    // 5. 標準入力にPlantUMLコードを書き込む
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin.write_all(code.as_bytes())
-            .map_err(|e| format!("PlantUMLコードの書き込みに失敗しました: {}", e))?;
-        stdin.flush()
-            .map_err(|e| format!("標準入力のフラッシュに失敗しました: {}", e))?;
-        // stdinを閉じる（drop）
-    }
-    
-    // 6. プロセスの完了を待つ
-    let output = child.wait_with_output()
-        .map_err(|e| format!("PlantUMLの実行に失敗しました: {}", e))?;
-    
-    // 7. 一時ファイルを削除
-    let _ = fs::remove_file(&temp_file);
-    
-    // 8. エラーチェック
-    let stderr_text = String::from_utf8_lossy(&output.stderr);
-    let stdout_text = String::from_utf8_lossy(&output.stdout);
-    
-    // Graphviz関連のエラーを検出
-    let is_graphviz_error = stderr_text.contains("Cannot find Graphviz") ||
-                            stderr_text.contains("Dot executable does not exist") ||
-                            stderr_text.contains("Graphviz");
-    
-    if !output.status.success() {
-        let error_msg = if !stderr_text.is_empty() {
-            stderr_text.to_string()
-        } else if !stdout_text.is_empty() {
-            stdout_text.to_string()
-        } else {
-            "PlantUMLの実行に失敗しました（詳細不明）".to_string()
-        };
-        
-        // Graphvizエラーの場合、より詳細なメッセージを提供
-        if is_graphviz_error {
-            return Err(format!(
-                "PlantUMLエラー: Graphvizが見つかりません。\n\n\
-                エラー詳細: {}\n\n\
-                対処法:\n\
-                1. Graphvizをインストールしてください:\n\
-                   macOS (Homebrew): brew install graphviz\n\
-                   macOS (MacPorts): sudo port install graphviz\n\
-                   Linux (apt): sudo apt-get install graphviz\n\
-                   Linux (yum): sudo yum install graphviz\n\
-                2. インストール後、dotコマンドがPATHに含まれているか確認してください:\n\
-                   which dot\n\
-                3. アプリを再起動してください\n\n\
-                注意: 一部のPlantUML図タイプ（クラス図、シーケンス図など）はGraphvizが必要です。",
-                error_msg
-            ));
-        }
-        
-        return Err(format!("PlantUMLエラー: {}", error_msg));
-    }
-    
-    // 9. 標準出力が空の場合は、生成されたファイルを読み込む（フォールバック）
-    if output.stdout.is_empty() {
-        eprintln!("⚠️ [PlantUML] 標準出力が空です。ファイル出力を確認します。");
-        eprintln!("   一時ファイル: {:?}", temp_file);
-        eprintln!("   標準エラー: {}", stderr_text);
-        
-        // Graphvizエラーの場合
-        if is_graphviz_error {
-            return Err(format!(
-                "PlantUMLが空の出力を返しました。Graphvizが見つかりません。\n\n\
-                標準エラー: {}\n\n\
-                対処法:\n\
-                1. Graphvizをインストールしてください:\n\
-                   macOS (Homebrew): brew install graphviz\n\
-                   macOS (MacPorts): sudo port install graphviz\n\
-                2. インストール後、アプリを再起動してください",
-                stderr_text
-            ));
-        }
-        
-        // PlantUMLがファイルに出力した場合、一時ファイルと同じディレクトリに生成される
-        let output_file = temp_file.with_extension(format!("{}", format));
-        eprintln!("   出力ファイルパス: {:?}", output_file);
-        
-        if output_file.exists() {
-            eprintln!("✅ [PlantUML] 出力ファイルが見つかりました");
-            let file_data = fs::read(&output_file)
-                .map_err(|e| format!("生成されたファイルの読み込みに失敗しました: {}", e))?;
-            let _ = fs::remove_file(&output_file);
-            eprintln!("✅ [PlantUML] ファイルデータを読み込みました: {} bytes", file_data.len());
-            return Ok(file_data);
-        }
-        
-        // 一時ファイルのディレクトリ内のすべてのファイルを確認（デバッグ用）
-        if let Some(parent) = temp_file.parent() {
-            eprintln!("   ディレクトリ内のファイル:");
-            if let Ok(entries) = fs::read_dir(parent) {
-                for entry in entries.flatten() {
-                    eprintln!("     - {:?}", entry.path());
-                }
-            }
-        }
-        
-        return Err(format!(
-            "PlantUMLが空の出力を返しました。\n\n\
-            標準エラー: {}\n\n\
-            考えられる原因:\n\
-            1. PlantUMLコードに構文エラーがある\n\
-            2. Javaのバージョンが古い\n\
-            3. PlantUML JARファイルが破損している\n\
-            4. Graphvizがインストールされていない（一部の図タイプで必要）",
-            stderr_text
-        ));
+    let format = PlantUmlFormat::parse(&format)?;
+    let rendered = render_plantuml_cached(&app_handle, &code, format).await?;
+    if rendered.cached {
+        eprintln!("✅ [PlantUML] キャッシュからデータを取得しました: {} bytes", rendered.data.len());
+    } else {
+        eprintln!("✅ [PlantUML] レンダリングしました: {} bytes", rendered.data.len());
     }
-    
-    // 10. 生成された画像データを返す
-    eprintln!("✅ [PlantUML] 標準出力からデータを取得しました: {} bytes", output.stdout.len());
-    Ok(output.stdout)
+    Ok(rendered.data)
+}
+
+/// 複数のPlantUMLコードをまとめてレンダリングする（結果は入力と同じ順序で、図ごとに成否を返す）
+#[tauri::command]
+pub async fn render_plantuml_batch(
+    app_handle: AppHandle,
+    codes: Vec<String>,
+    format: String, // "svg" or "png"
+) -> Result<Vec<PlantUmlBatchResult>, String> {
+    let format = PlantUmlFormat::parse(&format)?;
+    let results = render_plantuml_batch_cached(&app_handle, codes, format).await;
+    let failed = results.iter().filter(|result| result.error.is_some()).count();
+    let cached = results.iter().filter(|result| result.cached).count();
+    eprintln!(
+        "✅ [PlantUML] 一括レンダリングが完了しました: {}件（キャッシュ: {}件, 失敗: {}件）",
+        results.len(),
+        cached,
+        failed
+    );
+    Ok(results)
+}
+
+/// Graphvizが見つからない場合のエラーメッセージ
+pub(crate) fn graphviz_missing_message(detail: &str) -> String {
+    format!(
+        "PlantUMLエラー: Graphvizが見つかりません。\n\n\
+        エラー詳細: {}\n\n\
+        対処法:\n\
+        1. Graphvizをインストールしてください:\n\
+           macOS (Homebrew): brew install graphviz\n\
+           macOS (MacPorts): sudo port install graphviz\n\
+           Linux (apt): sudo apt-get install graphviz\n\
+           Linux (yum): sudo yum install graphviz\n\
+        2. インストール後、dotコマンドがPATHに含まれているか確認してください:\n\
+           which dot\n\
+        3. アプリを再起動してください\n\n\
+        注意: 一部のPlantUML図タイプ（クラス図、シーケンス図など）はGraphvizが必要です。",
+        detail.trim()
+    )
 }
 
 /// Javaがインストールされているか確認する
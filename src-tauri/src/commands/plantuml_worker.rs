@@ -0,0 +1,461 @@
+/**
+ * PlantUMLのレンダリングワーカー
+ * PlantUMLを常駐プロセス（java -jar plantuml.jar -pipe）で実行し、図ごとにJVMを起動しないようにする
+ *
+ * - ワーカー（既定2つ、PLANTUML_WORKERSで変更可能）がキューからレンダリング要求を取り出して処理する
+ * - 各ワーカーは出力形式ごとにPlantUMLプロセスを1つ持ち、-pipedelimitorの区切りで図の出力の終わりを判定する
+ * - 図ごとにタイムアウトを設け、タイムアウト・プロセスの異常終了時はプロセスを作り直す（異常終了時は1回だけ再試行）
+ * - 標準エラーはレンダリング中に標準出力と並行して読み、区切りを読んだ後に読み残しを読み切ってから図に対応付ける
+ * - 結果はPlantUMLのコードと出力形式のハッシュをキーに、データディレクトリ/plantuml_renders/にキャッシュする
+ *   （標準エラーに出力があった図はエラー画像の可能性があるためキャッシュしない）
+ * - PLANTUML_SERVER_URLが設定されている場合は、Javaを使わずPlantUMLサーバーにPOSTしてレンダリングする
+ */
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command as TokioCommand};
+use tokio::sync::oneshot;
+
+use super::plantuml::{detect_java, get_plantuml_jar_path, graphviz_missing_message};
+
+/// 1図あたりのレンダリングのタイムアウト
+const RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// ワーカー数を指定する環境変数
+const WORKER_COUNT_ENV: &str = "PLANTUML_WORKERS";
+const DEFAULT_WORKER_COUNT: usize = 2;
+const MAX_WORKER_COUNT: usize = 8;
+
+/// PlantUMLサーバーのURLを指定する環境変数（例: http://localhost:8080/plantuml）
+const SERVER_URL_ENV: &str = "PLANTUML_SERVER_URL";
+
+/// 図の出力の区切り（-pipedelimitor）
+const PIPE_DELIMITER: &str = "___PLANTUML_PIPE_DELIMITER_6f1c2b___";
+
+/// 保持する標準エラーの最大バイト数
+const MAX_STDERR_BYTES: usize = 16_384;
+
+/// 区切りを読んだ後、標準エラーの出力が途切れたとみなすまでの時間
+/// （PlantUMLは区切りより前に標準エラーへ書き込むため、読み残しはパイプに届いている）
+const STDERR_QUIET_PERIOD: Duration = Duration::from_millis(20);
+
+static POOL: OnceLock<PlantUmlPool> = OnceLock::new();
+static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlantUmlFormat {
+    Svg,
+    Png,
+}
+
+impl PlantUmlFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "svg" => Ok(Self::Svg),
+            "png" => Ok(Self::Png),
+            _ => Err(format!("無効なフォーマット: {}. 'svg' または 'png' を指定してください。", value)),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// レンダリング結果
+#[derive(Debug, Clone)]
+pub struct RenderedPlantUml {
+    pub data: Vec<u8>,
+    /// キャッシュから返したか
+    pub cached: bool,
+}
+
+/// 一括レンダリングの1件の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct PlantUmlBatchResult {
+    pub index: usize,
+    pub data: Option<Vec<u8>>,
+    pub error: Option<String>,
+    pub cached: bool,
+}
+
+/// PlantUMLプロセスの出力
+struct PipeOutput {
+    data: Vec<u8>,
+    /// レンダリング中に標準エラーに出力された内容（構文エラー等）
+    stderr: String,
+}
+
+/// レンダリング要求
+struct RenderJob {
+    code: String,
+    format: PlantUmlFormat,
+    reply: oneshot::Sender<Result<PipeOutput, String>>,
+}
+
+struct PlantUmlPool {
+    tx: async_channel::Sender<RenderJob>,
+}
+
+/// 常駐しているPlantUMLプロセス
+struct PipeProcess {
+    // dropしたときにプロセスを終了させるために保持する
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+    stderr: ChildStderr,
+    /// 区切りの後に読み込んだ残りの出力
+    buffer: Vec<u8>,
+    /// 現在の図のレンダリング中に読み込んだ標準エラー
+    stderr_buffer: Vec<u8>,
+    /// 標準エラーがまだ開いているか
+    stderr_open: bool,
+}
+
+impl PipeProcess {
+    async fn spawn(app_handle: &AppHandle, format: PlantUmlFormat) -> Result<Self, String> {
+        // Java・JARの検出はプロセスを同期的に実行するため、ブロッキング用のスレッドで行う
+        let handle = app_handle.clone();
+        let (java_path, jar_path) = tokio::task::spawn_blocking(move || -> Result<(PathBuf, PathBuf), String> {
+            let java_path = detect_java().map_err(|e| e.to_string())?;
+            let jar_path = get_plantuml_jar_path(&handle).map_err(|e| e.to_string())?;
+            Ok((java_path, jar_path))
+        })
+        .await
+        .map_err(|e| format!("Javaの検出に失敗しました: {}", e))??;
+
+        let mut child = TokioCommand::new(&java_path)
+            .arg("-Djava.awt.headless=true")
+            .arg("-jar")
+            .arg(&jar_path)
+            .arg("-pipe")
+            .arg(format!("-t{}", format.extension()))
+            .arg("-charset")
+            .arg("UTF-8")
+            .arg("-pipedelimitor")
+            .arg(PIPE_DELIMITER)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("PlantUMLプロセスの起動に失敗しました: {}", e))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| "PlantUMLプロセスの標準入力を取得できませんでした".to_string())?;
+        let stdout = child.stdout.take().ok_or_else(|| "PlantUMLプロセスの標準出力を取得できませんでした".to_string())?;
+        let stderr = child.stderr.take().ok_or_else(|| "PlantUMLプロセスの標準エラーを取得できませんでした".to_string())?;
+
+        Ok(Self {
+            _child: child,
+            stdin,
+            stdout,
+            stderr,
+            buffer: Vec::new(),
+            stderr_buffer: Vec::new(),
+            stderr_open: true,
+        })
+    }
+
+    /// 読み込んだ標準エラーを追加（上限を超えた分は捨てる）
+    fn push_stderr(&mut self, bytes: &[u8]) {
+        let room = MAX_STDERR_BYTES.saturating_sub(self.stderr_buffer.len());
+        self.stderr_buffer.extend_from_slice(&bytes[..bytes.len().min(room)]);
+    }
+
+    /// 読み込んだ標準エラーを取り出す
+    fn take_stderr(&mut self) -> String {
+        let bytes = std::mem::take(&mut self.stderr_buffer);
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// パイプに届いている標準エラーを読み切る（quiet_periodの間に出力がなければ終了）
+    async fn drain_stderr(&mut self, quiet_period: Duration) {
+        let mut chunk = [0u8; 4096];
+        while self.stderr_open {
+            match tokio::time::timeout(quiet_period, self.stderr.read(&mut chunk)).await {
+                Ok(Ok(0)) | Ok(Err(_)) => self.stderr_open = false,
+                Ok(Ok(read)) => self.push_stderr(&chunk[..read]),
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// 1つの図をレンダリングし、区切りまでの出力を返す
+    async fn render(&mut self, code: &str) -> Result<PipeOutput, String> {
+        // 前の図の後に遅れて届いた標準エラーは、この図のものとして扱わずにログに残す
+        self.drain_stderr(Duration::ZERO).await;
+        let late_stderr = self.take_stderr();
+        if !late_stderr.trim().is_empty() {
+            eprintln!("⚠️ [PlantUML] 前の図のレンダリング後に出力された標準エラー: {}", late_stderr.trim());
+        }
+
+        let mut input = code.to_string();
+        if !input.ends_with('\n') {
+            input.push('\n');
+        }
+        self.stdin
+            .write_all(input.as_bytes())
+            .await
+            .map_err(|e| format!("PlantUMLコードの書き込みに失敗しました: {}", e))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| format!("標準入力のフラッシュに失敗しました: {}", e))?;
+
+        let delimiter = PIPE_DELIMITER.as_bytes();
+        let mut searched = 0;
+        let mut chunk = vec![0u8; 64 * 1024];
+        let mut stderr_chunk = [0u8; 4096];
+        loop {
+            if let Some(position) = find_bytes(&self.buffer[searched..], delimiter).map(|i| searched + i) {
+                let after = position + delimiter.len();
+                if let Some(line_end) = self.buffer[after..].iter().position(|&b| b == b'\n') {
+                    let mut data: Vec<u8> = self.buffer.drain(..after + line_end + 1).collect();
+                    data.truncate(position);
+                    self.drain_stderr(STDERR_QUIET_PERIOD).await;
+                    return Ok(PipeOutput { data, stderr: self.take_stderr() });
+                }
+            } else {
+                // 区切りが読み込みの境界をまたぐ場合に備えて、区切りの長さ分は再検索する
+                searched = self.buffer.len().saturating_sub(delimiter.len());
+            }
+
+            // 標準エラーがパイプを埋めてPlantUMLが止まらないよう、標準出力と並行して読む
+            tokio::select! {
+                read = self.stdout.read(&mut chunk) => {
+                    let read = read.map_err(|e| format!("PlantUMLの出力の読み込みに失敗しました: {}", e))?;
+                    if read == 0 {
+                        self.drain_stderr(STDERR_QUIET_PERIOD).await;
+                        return Err(format!("PlantUMLプロセスが終了しました: {}", self.take_stderr().trim()));
+                    }
+                    self.buffer.extend_from_slice(&chunk[..read]);
+                }
+                read = self.stderr.read(&mut stderr_chunk), if self.stderr_open => {
+                    match read {
+                        Ok(0) | Err(_) => self.stderr_open = false,
+                        Ok(read) => self.push_stderr(&stderr_chunk[..read]),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// キューから要求を取り出してレンダリングするワーカー
+struct PlantUmlWorker {
+    index: usize,
+    app_handle: AppHandle,
+    processes: HashMap<PlantUmlFormat, PipeProcess>,
+}
+
+impl PlantUmlWorker {
+    async fn run(mut self, rx: async_channel::Receiver<RenderJob>) {
+        while let Ok(job) = rx.recv().await {
+            let result = self.render(&job.code, job.format).await;
+            // 要求元がキャンセルされている場合は結果を捨てる
+            let _ = job.reply.send(result);
+        }
+    }
+
+    async fn render(&mut self, code: &str, format: PlantUmlFormat) -> Result<PipeOutput, String> {
+        for attempt in 0..2 {
+            let fresh = !self.processes.contains_key(&format);
+            if fresh {
+                let process = PipeProcess::spawn(&self.app_handle, format).await?;
+                eprintln!("✅ [PlantUML] ワーカー{}のPlantUMLプロセス（{}）を起動しました", self.index, format.extension());
+                self.processes.insert(format, process);
+            }
+            let Some(process) = self.processes.get_mut(&format) else { continue };
+
+            match tokio::time::timeout(RENDER_TIMEOUT, process.render(code)).await {
+                Ok(Ok(output)) => return Ok(output),
+                Ok(Err(e)) => {
+                    // プロセスを破棄し（kill_on_dropで終了）、次の要求で作り直す
+                    self.processes.remove(&format);
+                    eprintln!("⚠️ [PlantUML] ワーカー{}のPlantUMLプロセスが異常終了しました: {}", self.index, e);
+                    if fresh || attempt > 0 {
+                        return Err(e);
+                    }
+                }
+                Err(_) => {
+                    self.processes.remove(&format);
+                    return Err(format!("PlantUMLのレンダリングがタイムアウトしました（{}秒）", RENDER_TIMEOUT.as_secs()));
+                }
+            }
+        }
+        Err("PlantUMLのレンダリングに失敗しました".to_string())
+    }
+}
+
+fn worker_count() -> usize {
+    std::env::var(WORKER_COUNT_ENV)
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_WORKER_COUNT)
+        .clamp(1, MAX_WORKER_COUNT)
+}
+
+fn pool(app_handle: &AppHandle) -> &'static PlantUmlPool {
+    POOL.get_or_init(|| {
+        let (tx, rx) = async_channel::unbounded::<RenderJob>();
+        let count = worker_count();
+        for index in 0..count {
+            let worker = PlantUmlWorker { index, app_handle: app_handle.clone(), processes: HashMap::new() };
+            let rx = rx.clone();
+            tauri::async_runtime::spawn(async move {
+                worker.run(rx).await;
+            });
+        }
+        eprintln!("✅ [PlantUML] レンダリングワーカーを{}個起動しました", count);
+        PlantUmlPool { tx }
+    })
+}
+
+/// キャッシュの保存先（データベースディレクトリ/plantuml_renders）
+fn render_cache_dir() -> Option<PathBuf> {
+    let db_dir_name = if cfg!(debug_assertions) {
+        "network-mock-local-dev"
+    } else {
+        "network-mock-local"
+    };
+    dirs::data_dir().map(|dir| dir.join(db_dir_name).join("plantuml_renders"))
+}
+
+/// 図が1つだけになるように@startuml〜@endumlを補う（-pipeは@end〜ごとに1図として出力するため）
+fn normalize_code(code: &str) -> Result<String, String> {
+    let starts = code.lines().filter(|line| line.trim_start().starts_with("@start")).count();
+    match starts {
+        0 => Ok(format!("@startuml\n{}\n@enduml\n", code.trim_end())),
+        1 => Ok(code.to_string()),
+        _ => Err("1回のレンダリングで扱える図は1つです。@start〜@endの図ごとに分けてください。".to_string()),
+    }
+}
+
+/// PlantUMLサーバーでレンダリング
+async fn render_with_server(server_url: &str, code: &str, format: PlantUmlFormat) -> Result<Vec<u8>, String> {
+    let client = HTTP_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(RENDER_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    });
+    let url = format!("{}/{}", server_url.trim_end_matches('/'), format.extension());
+    let response = client
+        .post(&url)
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(code.to_string())
+        .send()
+        .await
+        .map_err(|e| format!("PlantUMLサーバーへの接続に失敗しました: {} ({})", e, url))?;
+    let status = response.status();
+    let data = response
+        .bytes()
+        .await
+        .map_err(|e| format!("PlantUMLサーバーの応答の読み込みに失敗しました: {}", e))?;
+    if !status.is_success() {
+        return Err(format!("PlantUMLサーバーエラー: HTTP {}", status));
+    }
+    Ok(data.to_vec())
+}
+
+/// PlantUMLコードをレンダリングする（同じコード・形式の結果はキャッシュから返す）
+pub async fn render_plantuml_cached(app_handle: &AppHandle, code: &str, format: PlantUmlFormat) -> Result<RenderedPlantUml, String> {
+    let code = normalize_code(code)?;
+    let hash = format!("{:x}", Sha256::digest(format!("{}\n{}", format.extension(), code).as_bytes()));
+    let cache_path = render_cache_dir().map(|dir| dir.join(format!("{}.{}", hash, format.extension())));
+    if let Some(path) = cache_path.as_ref() {
+        if let Ok(data) = tokio::fs::read(path).await {
+            return Ok(RenderedPlantUml { data, cached: true });
+        }
+    }
+
+    let (data, cacheable) = match std::env::var(SERVER_URL_ENV).ok().filter(|url| !url.trim().is_empty()) {
+        Some(server_url) => (render_with_server(&server_url, &code, format).await?, true),
+        None => {
+            let (reply, response) = oneshot::channel();
+            pool(app_handle)
+                .tx
+                .send(RenderJob { code, format, reply })
+                .await
+                .map_err(|_| "PlantUMLのレンダリングキューが停止しています".to_string())?;
+            let output = response
+                .await
+                .map_err(|_| "PlantUMLのレンダリングワーカーが応答しませんでした".to_string())??;
+
+            let is_graphviz_error = output.stderr.contains("Cannot find Graphviz")
+                || output.stderr.contains("Dot executable does not exist");
+            if is_graphviz_error {
+                return Err(graphviz_missing_message(&output.stderr));
+            }
+            if output.data.is_empty() {
+                return Err(format!("PlantUMLが空の出力を返しました: {}", output.stderr.trim()));
+            }
+            if !output.stderr.trim().is_empty() {
+                eprintln!("⚠️ [PlantUML] レンダリング時の警告・エラー: {}", output.stderr.trim());
+            }
+            // 構文エラー等のエラー画像は、環境によって結果が変わる可能性があるためキャッシュしない
+            let cacheable = output.stderr.trim().is_empty();
+            (output.data, cacheable)
+        }
+    };
+
+    // キャッシュの書き込みに失敗してもレンダリング結果は返す
+    // 同時に読み込む要求が書きかけのファイルを返さないよう、同じディレクトリの一時ファイルに書き出してからリネームする
+    if let Some(path) = cache_path.as_ref().filter(|_| cacheable) {
+        let tmp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let written = async {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::write(&tmp_path, &data).await?;
+            tokio::fs::rename(&tmp_path, path).await
+        }
+        .await;
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            eprintln!("⚠️ [PlantUML] レンダリング結果のキャッシュに失敗しました: {}", e);
+        }
+    }
+
+    Ok(RenderedPlantUml { data, cached: false })
+}
+
+/// 複数のPlantUMLコードをまとめてレンダリングする（ワーカー数まで並行して処理）
+pub async fn render_plantuml_batch_cached(app_handle: &AppHandle, codes: Vec<String>, format: PlantUmlFormat) -> Vec<PlantUmlBatchResult> {
+    let tasks: Vec<_> = codes
+        .into_iter()
+        .map(|code| {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move { render_plantuml_cached(&app_handle, &code, format).await })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for (index, task) in tasks.into_iter().enumerate() {
+        let result = task
+            .await
+            .map_err(|e| format!("レンダリングタスクの実行に失敗しました: {}", e))
+            .and_then(|result| result);
+        results.push(match result {
+            Ok(rendered) => PlantUmlBatchResult { index, data: Some(rendered.data), error: None, cached: rendered.cached },
+            Err(error) => PlantUmlBatchResult { index, data: None, error: Some(error), cached: false },
+        });
+    }
+    results
+}
@@ -0,0 +1,528 @@
+/**
+ * Agent間（A2A）メッセージバス
+ * a2aMessagesテーブルにメッセージを記録し、送信・受信箱・要求と応答の対応付けを行う
+ *
+ * - 送信したメッセージはすべてa2aMessagesに記録する（監査用のログとして削除しない）
+ * - type = responseのメッセージはresponseToで元のメッセージに対応付け、送信先は元のメッセージの送信元になる
+ * - requiresResponseのメッセージには応答期限（既定60秒）を設定し、期限までに応答がなければtimed_outにする
+ *   （アプリの再起動をまたいだメッセージは、次にバスを使ったときに期限切れとして記録する）
+ * - 新しいメッセージと応答の期限切れはコールバック（コマンドではTauriイベント）で通知する
+ * - タスクごとのメッセージは、responseToを辿ってスレッドとして再構成できる
+ */
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::watch;
+
+use super::agent_executor::now_millis;
+use super::get_db;
+
+/// 応答期限の既定値（ミリ秒）
+const DEFAULT_RESPONSE_TIMEOUT_MS: u64 = 60_000;
+/// 応答期限の上限（ミリ秒）
+const MAX_RESPONSE_TIMEOUT_MS: u64 = 24 * 60 * 60 * 1000;
+const DEFAULT_INBOX_LIMIT: usize = 100;
+const MAX_INBOX_LIMIT: usize = 1000;
+
+const RESPONSE_PENDING: &str = "pending";
+const RESPONSE_ANSWERED: &str = "answered";
+const RESPONSE_TIMED_OUT: &str = "timed_out";
+
+/// メッセージの種類（フロントエンドのA2AMessageTypeと同じ値）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum A2AMessageType {
+    Request,
+    Response,
+    Notification,
+    Confirmation,
+    StatusUpdate,
+}
+
+impl A2AMessageType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Request => "request",
+            Self::Response => "response",
+            Self::Notification => "notification",
+            Self::Confirmation => "confirmation",
+            Self::StatusUpdate => "status_update",
+        }
+    }
+}
+
+/// 記録されたA2Aメッセージ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct A2AMessage {
+    pub id: String,
+    pub from: String,
+    pub to: String,
+    #[serde(rename = "type")]
+    pub message_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "taskId")]
+    pub task_id: Option<String>,
+    pub payload: String, // JSON文字列
+    pub timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "responseTo")]
+    pub response_to: Option<String>,
+    #[serde(rename = "requiresResponse")]
+    pub requires_response: bool,
+    /// 応答の状態（pending / answered / timed_out。応答が不要なメッセージはNone）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "responseStatus")]
+    pub response_status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "responseDeadline")]
+    pub response_deadline: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "readAt")]
+    pub read_at: Option<i64>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+/// 送信するメッセージ
+#[derive(Debug, Clone, Deserialize)]
+pub struct A2AMessageDraft {
+    pub from: String,
+    /// 送信先（responseの場合は省略すると元のメッセージの送信元）
+    #[serde(default)]
+    pub to: String,
+    #[serde(rename = "type")]
+    pub message_type: A2AMessageType,
+    #[serde(rename = "taskId", default)]
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub payload: Value,
+    #[serde(rename = "responseTo", default)]
+    pub response_to: Option<String>,
+    #[serde(rename = "requiresResponse", default)]
+    pub requires_response: bool,
+    /// 応答期限（ミリ秒。既定60秒）
+    #[serde(rename = "responseTimeoutMs", default)]
+    pub response_timeout_ms: Option<u64>,
+}
+
+/// 受信箱の絞り込み
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct A2AInboxOptions {
+    /// 未読のメッセージのみ
+    #[serde(rename = "unreadOnly")]
+    pub unread_only: Option<bool>,
+    /// 応答待ちの（まだ応答していない）メッセージのみ
+    #[serde(rename = "awaitingResponseOnly")]
+    pub awaiting_response_only: Option<bool>,
+    #[serde(rename = "type")]
+    pub message_type: Option<A2AMessageType>,
+    #[serde(rename = "taskId")]
+    pub task_id: Option<String>,
+    /// 取得する件数（既定100、新しい順）
+    pub limit: Option<usize>,
+}
+
+/// メッセージと、それに対する応答・返信のスレッド
+#[derive(Debug, Clone, Serialize)]
+pub struct A2AMessageThread {
+    pub message: A2AMessage,
+    pub replies: Vec<A2AMessageThread>,
+}
+
+/// バスからの通知
+#[derive(Debug, Clone)]
+pub enum A2ABusEvent {
+    /// メッセージが送信された
+    Message(A2AMessage),
+    /// 応答が必要なメッセージの応答期限が過ぎた
+    ResponseTimeout(A2AMessage),
+}
+
+pub type A2ABusCallback = Arc<dyn Fn(A2ABusEvent) + Send + Sync>;
+
+/// 応答待ちのメッセージの状態
+#[derive(Debug, Clone)]
+enum ResponseState {
+    Pending,
+    Answered(A2AMessage),
+    TimedOut,
+}
+
+/// このプロセスで応答期限を監視しているメッセージ
+static PENDING_RESPONSES: OnceLock<Mutex<HashMap<String, watch::Sender<ResponseState>>>> = OnceLock::new();
+
+fn pending_responses() -> std::sync::MutexGuard<'static, HashMap<String, watch::Sender<ResponseState>>> {
+    PENDING_RESPONSES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+const MESSAGE_COLUMNS: &str = "id, fromAgent, toAgent, type, taskId, payload, timestamp, responseTo, requiresResponse, \
+    responseStatus, responseDeadline, readAt, createdAt";
+
+fn read_message(row: &rusqlite::Row) -> SqlResult<A2AMessage> {
+    Ok(A2AMessage {
+        id: row.get(0)?,
+        from: row.get(1)?,
+        to: row.get(2)?,
+        message_type: row.get(3)?,
+        task_id: row.get(4)?,
+        payload: row.get(5)?,
+        timestamp: row.get(6)?,
+        response_to: row.get(7)?,
+        requires_response: row.get::<_, Option<i64>>(8)?.unwrap_or(0) != 0,
+        response_status: row.get(9)?,
+        response_deadline: row.get(10)?,
+        read_at: row.get(11)?,
+        created_at: row.get(12)?,
+    })
+}
+
+fn query_messages(conn: &Connection, condition: &str, values: &[&dyn rusqlite::ToSql]) -> SqlResult<Vec<A2AMessage>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM a2aMessages WHERE {}", MESSAGE_COLUMNS, condition))?;
+    let messages = stmt.query_map(values, read_message)?.collect::<SqlResult<Vec<_>>>()?;
+    Ok(messages)
+}
+
+fn find_message(conn: &Connection, message_id: &str) -> SqlResult<Option<A2AMessage>> {
+    conn.query_row(
+        &format!("SELECT {} FROM a2aMessages WHERE id = ?1", MESSAGE_COLUMNS),
+        params![message_id],
+        read_message,
+    )
+    .optional()
+}
+
+/// メッセージへの（最初の）応答
+fn find_response(conn: &Connection, message_id: &str) -> SqlResult<Option<A2AMessage>> {
+    Ok(query_messages(
+        conn,
+        "responseTo = ?1 AND type = ?2 ORDER BY timestamp, rowid LIMIT 1",
+        &[&message_id, &A2AMessageType::Response.as_str()],
+    )?
+    .into_iter()
+    .next())
+}
+
+/// このプロセスで監視していない（再起動前に送信された）メッセージの応答期限切れを記録する
+fn expire_overdue_responses(conn: &Connection) -> SqlResult<()> {
+    let overdue: Vec<String> = {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM a2aMessages WHERE responseStatus = ?1 AND responseDeadline IS NOT NULL AND responseDeadline <= ?2",
+        )?;
+        let ids = stmt
+            .query_map(params![RESPONSE_PENDING, now_millis()], |row| row.get(0))?
+            .collect::<SqlResult<Vec<String>>>()?;
+        ids
+    };
+    let tracked = pending_responses();
+    for id in overdue.iter().filter(|id| !tracked.contains_key(*id)) {
+        conn.execute(
+            "UPDATE a2aMessages SET responseStatus = ?1 WHERE id = ?2 AND responseStatus = ?3",
+            params![RESPONSE_TIMED_OUT, id, RESPONSE_PENDING],
+        )?;
+    }
+    Ok(())
+}
+
+/// 応答期限まで待ち、応答がなければtimed_outとして記録する
+async fn watch_deadline(message_id: String, deadline: i64, on_event: Option<A2ABusCallback>) {
+    let wait = (deadline - now_millis()).max(0) as u64;
+    tokio::time::sleep(Duration::from_millis(wait)).await;
+
+    let Some(state_tx) = pending_responses().remove(&message_id) else {
+        return; // 応答済み
+    };
+    let timed_out = connection().and_then(|conn| {
+        let changed = conn
+            .execute(
+                "UPDATE a2aMessages SET responseStatus = ?1 WHERE id = ?2 AND responseStatus = ?3",
+                params![RESPONSE_TIMED_OUT, message_id, RESPONSE_PENDING],
+            )
+            .map_err(|e| e.to_string())?;
+        if changed == 0 {
+            // 期限の直前に応答が記録された
+            let response = find_response(&conn, &message_id).map_err(|e| e.to_string())?;
+            return Ok(response.map(ResponseState::Answered));
+        }
+        Ok(None)
+    });
+
+    match timed_out {
+        Ok(Some(answered)) => {
+            let _ = state_tx.send(answered);
+        }
+        Ok(None) => {
+            eprintln!("⏰ [a2a_bus] 応答期限が過ぎました: {}", message_id);
+            let _ = state_tx.send(ResponseState::TimedOut);
+            if let (Some(on_event), Ok(Some(message))) = (on_event, get_a2a_message(&message_id)) {
+                on_event(A2ABusEvent::ResponseTimeout(message));
+            }
+        }
+        Err(e) => {
+            eprintln!("⚠️ [a2a_bus] 応答期限切れの記録に失敗しました ({}): {}", message_id, e);
+            let _ = state_tx.send(ResponseState::TimedOut);
+        }
+    }
+}
+
+/// 応答の状態の通知を受け取る（監視していないメッセージは期限の監視を開始する）
+fn subscribe_response(message_id: &str, deadline: i64, on_event: Option<A2ABusCallback>) -> watch::Receiver<ResponseState> {
+    let mut pending = pending_responses();
+    if let Some(state_tx) = pending.get(message_id) {
+        return state_tx.subscribe();
+    }
+    let (state_tx, state_rx) = watch::channel(ResponseState::Pending);
+    pending.insert(message_id.to_string(), state_tx);
+    tauri::async_runtime::spawn(watch_deadline(message_id.to_string(), deadline, on_event));
+    state_rx
+}
+
+/// メッセージを送信（記録）する
+pub fn send_a2a_message(draft: A2AMessageDraft, on_event: A2ABusCallback) -> Result<A2AMessage, String> {
+    let from = draft.from.trim().to_string();
+    if from.is_empty() {
+        return Err("送信元のAgent（from）を指定してください".to_string());
+    }
+    let response_to = draft.response_to.filter(|id| !id.trim().is_empty());
+    let mut to = draft.to.trim().to_string();
+    let mut task_id = draft.task_id.filter(|id| !id.trim().is_empty());
+
+    let conn = connection()?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+
+    let original = match &response_to {
+        Some(id) => Some(
+            find_message(&tx, id)
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("応答先のメッセージが見つかりません: {}", id))?,
+        ),
+        None => None,
+    };
+    if draft.message_type == A2AMessageType::Response {
+        let original = original
+            .as_ref()
+            .ok_or_else(|| "応答（response）には応答先のメッセージ（responseTo）を指定してください".to_string())?;
+        if original.to != from {
+            return Err(format!("応答できるのはメッセージの送信先のAgent（{}）のみです", original.to));
+        }
+        if to.is_empty() {
+            to = original.from.clone();
+        } else if to != original.from {
+            return Err(format!("応答の送信先は元のメッセージの送信元（{}）です", original.from));
+        }
+        if task_id.is_none() {
+            task_id = original.task_id.clone();
+        }
+        if original.requires_response {
+            let changed = tx
+                .execute(
+                    "UPDATE a2aMessages SET responseStatus = ?1 WHERE id = ?2 AND responseStatus = ?3",
+                    params![RESPONSE_ANSWERED, original.id, RESPONSE_PENDING],
+                )
+                .map_err(|e| e.to_string())?;
+            if changed == 0 {
+                return Err(match original.response_status.as_deref() {
+                    Some(RESPONSE_ANSWERED) => format!("メッセージ {} には既に応答しています", original.id),
+                    Some(RESPONSE_TIMED_OUT) => format!("メッセージ {} の応答期限が過ぎています", original.id),
+                    _ => format!("メッセージ {} は応答を受け付けていません", original.id),
+                });
+            }
+        }
+    }
+    if to.is_empty() {
+        return Err("送信先のAgent（to）を指定してください".to_string());
+    }
+
+    let now = now_millis();
+    let deadline = draft.requires_response.then(|| {
+        let timeout = draft
+            .response_timeout_ms
+            .unwrap_or(DEFAULT_RESPONSE_TIMEOUT_MS)
+            .clamp(1, MAX_RESPONSE_TIMEOUT_MS);
+        now + timeout as i64
+    });
+    let message = A2AMessage {
+        id: format!("a2a_{}", uuid::Uuid::new_v4()),
+        from,
+        to,
+        message_type: draft.message_type.as_str().to_string(),
+        task_id,
+        payload: if draft.payload.is_null() { "{}".to_string() } else { draft.payload.to_string() },
+        timestamp: now,
+        response_to,
+        requires_response: draft.requires_response,
+        response_status: deadline.map(|_| RESPONSE_PENDING.to_string()),
+        response_deadline: deadline,
+        read_at: None,
+        created_at: now.to_string(),
+    };
+    tx.execute(
+        "INSERT INTO a2aMessages (id, fromAgent, toAgent, type, taskId, payload, timestamp, responseTo, requiresResponse,
+            responseStatus, responseDeadline, readAt, createdAt)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params![
+            message.id,
+            message.from,
+            message.to,
+            message.message_type,
+            message.task_id,
+            message.payload,
+            message.timestamp,
+            message.response_to,
+            message.requires_response as i64,
+            message.response_status,
+            message.response_deadline,
+            message.read_at,
+            message.created_at,
+        ],
+    )
+    .map_err(|e| format!("メッセージの保存に失敗しました: {}", e))?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    // 応答を待っている呼び出しに通知する
+    if let (A2AMessageType::Response, Some(original)) = (draft.message_type, &original) {
+        if let Some(state_tx) = pending_responses().remove(&original.id) {
+            let _ = state_tx.send(ResponseState::Answered(message.clone()));
+        }
+    }
+    if let Some(deadline) = deadline {
+        subscribe_response(&message.id, deadline, Some(on_event.clone()));
+    }
+    on_event(A2ABusEvent::Message(message.clone()));
+    Ok(message)
+}
+
+/// メッセージを取得
+pub fn get_a2a_message(message_id: &str) -> Result<Option<A2AMessage>, String> {
+    let conn = connection()?;
+    expire_overdue_responses(&conn).map_err(|e| e.to_string())?;
+    find_message(&conn, message_id).map_err(|e| e.to_string())
+}
+
+/// 応答が必要なメッセージへの応答を待つ（応答期限が過ぎた場合はNone）
+pub async fn wait_for_a2a_response(message_id: &str) -> Result<Option<A2AMessage>, String> {
+    let mut state_rx = {
+        let conn = connection()?;
+        expire_overdue_responses(&conn).map_err(|e| e.to_string())?;
+        let message = find_message(&conn, message_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("メッセージが見つかりません: {}", message_id))?;
+        if let Some(response) = find_response(&conn, message_id).map_err(|e| e.to_string())? {
+            return Ok(Some(response));
+        }
+        match (message.response_status.as_deref(), message.response_deadline) {
+            (Some(RESPONSE_PENDING), Some(deadline)) => subscribe_response(message_id, deadline, None),
+            (Some(RESPONSE_TIMED_OUT), _) => return Ok(None),
+            _ => return Err(format!("メッセージ {} は応答を必要としていません", message_id)),
+        }
+    };
+
+    let state = state_rx
+        .wait_for(|state| !matches!(state, ResponseState::Pending))
+        .await
+        .map(|state| state.clone());
+    match state {
+        Ok(ResponseState::Answered(response)) => Ok(Some(response)),
+        Ok(_) => Ok(None),
+        // 通知を受け取れなかった場合は記録された状態を返す
+        Err(_) => {
+            let conn = connection()?;
+            find_response(&conn, message_id).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Agentの受信箱（新しい順）
+pub fn get_a2a_inbox(agent_id: &str, options: &A2AInboxOptions) -> Result<Vec<A2AMessage>, String> {
+    let conn = connection()?;
+    expire_overdue_responses(&conn).map_err(|e| e.to_string())?;
+
+    let mut condition = "toAgent = ?1".to_string();
+    let mut values: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(agent_id.to_string())];
+    if options.unread_only.unwrap_or(false) {
+        condition.push_str(" AND readAt IS NULL");
+    }
+    if options.awaiting_response_only.unwrap_or(false) {
+        values.push(Box::new(RESPONSE_PENDING));
+        condition.push_str(&format!(" AND responseStatus = ?{}", values.len()));
+    }
+    if let Some(message_type) = options.message_type {
+        values.push(Box::new(message_type.as_str()));
+        condition.push_str(&format!(" AND type = ?{}", values.len()));
+    }
+    if let Some(task_id) = options.task_id.as_ref().filter(|id| !id.is_empty()) {
+        values.push(Box::new(task_id.clone()));
+        condition.push_str(&format!(" AND taskId = ?{}", values.len()));
+    }
+    let limit = options.limit.unwrap_or(DEFAULT_INBOX_LIMIT).clamp(1, MAX_INBOX_LIMIT);
+    condition.push_str(&format!(" ORDER BY timestamp DESC, rowid DESC LIMIT {}", limit));
+
+    let values: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+    query_messages(&conn, &condition, &values).map_err(|e| e.to_string())
+}
+
+/// Agent宛てのメッセージを既読にする（message_idsが空の場合は未読のメッセージすべて）
+pub fn mark_a2a_messages_read(agent_id: &str, message_ids: &[String]) -> Result<usize, String> {
+    let conn = connection()?;
+    let now = now_millis();
+    if message_ids.is_empty() {
+        return conn
+            .execute(
+                "UPDATE a2aMessages SET readAt = ?1 WHERE toAgent = ?2 AND readAt IS NULL",
+                params![now, agent_id],
+            )
+            .map_err(|e| e.to_string());
+    }
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut count = 0;
+    for message_id in message_ids {
+        count += tx
+            .execute(
+                "UPDATE a2aMessages SET readAt = ?1 WHERE id = ?2 AND toAgent = ?3 AND readAt IS NULL",
+                params![now, message_id, agent_id],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+fn build_thread(message: A2AMessage, children: &mut HashMap<String, Vec<A2AMessage>>) -> A2AMessageThread {
+    let replies = children
+        .remove(&message.id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|reply| build_thread(reply, children))
+        .collect();
+    A2AMessageThread { message, replies }
+}
+
+/// タスクに関するメッセージを、responseToを辿ったスレッドとして取得（送信順）
+pub fn get_a2a_task_threads(task_id: &str) -> Result<Vec<A2AMessageThread>, String> {
+    let conn = connection()?;
+    expire_overdue_responses(&conn).map_err(|e| e.to_string())?;
+    let messages = query_messages(&conn, "taskId = ?1 ORDER BY timestamp, rowid", &[&task_id])
+        .map_err(|e| e.to_string())?;
+
+    let ids: HashSet<String> = messages.iter().map(|m| m.id.clone()).collect();
+    let mut roots = Vec::new();
+    let mut children: HashMap<String, Vec<A2AMessage>> = HashMap::new();
+    for message in messages {
+        match message.response_to.clone().filter(|id| ids.contains(id)) {
+            Some(parent) => children.entry(parent).or_default().push(message),
+            None => roots.push(message),
+        }
+    }
+    Ok(roots.into_iter().map(|root| build_thread(root, &mut children)).collect())
+}
@@ -0,0 +1,761 @@
+/**
+ * Agentのタスク実行エンジン
+ * タスクをRust側のキューで実行し、ウィンドウを閉じても実行を継続できるようにする
+ *
+ * - priorityの大きい順（同じ優先度は投入順）に、同時実行数（AGENT_EXECUTOR_CONCURRENCY、既定3）まで実行する
+ * - 実行するAgentはagentId、なければrequiredAgentsのうち最初に存在するもの、
+ *   なければタスクタイプをcapabilitiesに持つAgent
+ * - モデルはタスクのmodelType / selectedModel、なければAgentの設定（フロントエンドのgetModelInfoと同じ優先順位）
+ * - タスクのpromptVersionを指定した場合は、Agentのそのバージョンのシステムプロンプトで実行する
+ * - timeout（ミリ秒、なければAgentのconfig.defaultTimeout、既定60秒）を試行ごとに適用し、
+ *   失敗時はretryCount回までAgentのconfig.retryPolicyに従って再試行する
+ * - 進捗はTaskExecution.logsに追記して保存し、コールバックでも通知する（Tauriコマンドからイベントとして送信）
+ * - 状態はpending → running → completed / failed / cancelled の順にのみ遷移する
+ *   （依存タスクが失敗したタスクはtask_dagのスケジューラーがskippedとして記録する）
+ */
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{oneshot, watch, Notify, Semaphore};
+
+use super::agent_system::{
+    get_agent, get_all_agents, get_all_task_executions, get_task, save_task_execution,
+    Agent, Task, TaskExecution,
+};
+use super::agent_prompt::apply_prompt_version;
+use super::llm_client::{LlmClient, LlmMessage, LlmOptions};
+
+/// 同時実行数を指定する環境変数
+const CONCURRENCY_ENV: &str = "AGENT_EXECUTOR_CONCURRENCY";
+const DEFAULT_CONCURRENCY: usize = 3;
+const MAX_CONCURRENCY: usize = 16;
+
+/// タスク・Agentのどちらにもタイムアウトがない場合（フロントエンドと同じ60秒）
+const DEFAULT_TIMEOUT_MS: i64 = 60_000;
+/// retryPolicyがない場合の再試行間隔と倍率
+const DEFAULT_RETRY_DELAY_MS: u64 = 1_000;
+const DEFAULT_BACKOFF_MULTIPLIER: f64 = 2.0;
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// この実行エンジンが作成した実行のIDの接頭辞（フロントエンドのAgentOrchestratorの`exec_...`と区別する）
+const EXECUTION_ID_PREFIX: &str = "agent_exec_";
+
+/// 実行状態（フロントエンドのExecutionStatusと同じ値）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    /// 依存タスクが完了しなかったため実行しなかった
+    Skipped,
+}
+
+impl ExecutionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::Skipped => "skipped",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "running" => Some(Self::Running),
+            "completed" => Some(Self::Completed),
+            "failed" => Some(Self::Failed),
+            "cancelled" => Some(Self::Cancelled),
+            "skipped" => Some(Self::Skipped),
+            _ => None,
+        }
+    }
+
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled | Self::Skipped)
+    }
+
+    /// pending → running → completed / failed / cancelled（pendingからの失敗・キャンセルも可）
+    fn can_transition_to(self, next: Self) -> bool {
+        match (self, next) {
+            (Self::Pending, Self::Running) => true,
+            (Self::Pending | Self::Running, Self::Failed | Self::Cancelled) => true,
+            (Self::Running, Self::Completed) => true,
+            _ => false,
+        }
+    }
+}
+
+/// 実行ログ（フロントエンドのExecutionLogと同じ形式）
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionLog {
+    pub timestamp: i64,
+    pub level: String, // "info" | "warn" | "error"
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// 実行の進捗
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentTaskProgress {
+    #[serde(rename = "executionId")]
+    pub execution_id: String,
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+    #[serde(rename = "agentId")]
+    pub agent_id: String,
+    pub status: ExecutionStatus,
+    /// 追加されたログ（状態の変化のみの場合はNone）
+    pub log: Option<ExecutionLog>,
+}
+
+pub type AgentTaskProgressCallback = Arc<dyn Fn(AgentTaskProgress) + Send + Sync>;
+
+pub(crate) fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// 実行の状態とログを保持し、変更のたびにtaskExecutionsへ保存する
+struct ExecutionRecorder {
+    execution: TaskExecution,
+    status: ExecutionStatus,
+    logs: Vec<Value>,
+    on_progress: AgentTaskProgressCallback,
+}
+
+impl ExecutionRecorder {
+    fn new(execution: TaskExecution, on_progress: AgentTaskProgressCallback) -> Self {
+        let status = ExecutionStatus::parse(&execution.status).unwrap_or(ExecutionStatus::Pending);
+        let logs = serde_json::from_str::<Vec<Value>>(&execution.logs).unwrap_or_default();
+        Self { execution, status, logs, on_progress }
+    }
+
+    fn save(&mut self) {
+        self.execution.status = self.status.as_str().to_string();
+        self.execution.logs = serde_json::to_string(&self.logs).unwrap_or_else(|_| "[]".to_string());
+        match save_task_execution(&self.execution) {
+            Ok(saved) => self.execution = saved,
+            Err(e) => eprintln!("⚠️ [agent_executor] 実行履歴の保存に失敗しました ({}): {}", self.execution.id, e),
+        }
+    }
+
+    fn notify(&self, log: Option<ExecutionLog>) {
+        (self.on_progress)(AgentTaskProgress {
+            execution_id: self.execution.id.clone(),
+            task_id: self.execution.task_id.clone(),
+            agent_id: self.execution.agent_id.clone(),
+            status: self.status,
+            log,
+        });
+    }
+
+    fn log(&mut self, level: &str, message: impl Into<String>, data: Option<Value>) {
+        let entry = ExecutionLog { timestamp: now_millis(), level: level.to_string(), message: message.into(), data };
+        self.logs.push(serde_json::to_value(&entry).unwrap_or(Value::Null));
+        self.save();
+        self.notify(Some(entry));
+    }
+
+    fn transition(&mut self, next: ExecutionStatus) -> bool {
+        if !self.status.can_transition_to(next) {
+            eprintln!(
+                "⚠️ [agent_executor] 無効な状態遷移です ({}): {} → {}",
+                self.execution.id,
+                self.status.as_str(),
+                next.as_str()
+            );
+            return false;
+        }
+        self.status = next;
+        match next {
+            ExecutionStatus::Running => self.execution.started_at = now_millis().to_string(),
+            _ if next.is_terminal() => self.execution.completed_at = Some(now_millis().to_string()),
+            _ => {}
+        }
+        self.save();
+        self.notify(None);
+        true
+    }
+
+    fn complete(&mut self, result: Value) {
+        self.execution.result = Some(result.to_string());
+        self.execution.error = None;
+        self.transition(ExecutionStatus::Completed);
+    }
+
+    fn fail(&mut self, status: ExecutionStatus, error: String) {
+        self.execution.error = Some(error);
+        self.transition(status);
+    }
+}
+
+/// キュー内の実行待ちタスク
+struct QueuedExecution {
+    priority: i32,
+    sequence: u64,
+    task: Task,
+    agent: Agent,
+    recorder: ExecutionRecorder,
+    /// 実行が終了したときに最終状態を通知する（スケジューラーから投入した場合）
+    done: Option<oneshot::Sender<TaskExecution>>,
+}
+
+impl PartialEq for QueuedExecution {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority && self.sequence == other.sequence
+    }
+}
+
+impl Eq for QueuedExecution {}
+
+impl PartialOrd for QueuedExecution {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedExecution {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // 優先度が高いほど先、同じ優先度は先に投入したほうが先
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[derive(Default)]
+struct ExecutorState {
+    queue: BinaryHeap<QueuedExecution>,
+    /// 実行中の実行ID → キャンセル通知
+    running: HashMap<String, watch::Sender<bool>>,
+    sequence: u64,
+}
+
+struct AgentExecutor {
+    state: Mutex<ExecutorState>,
+    notify: Notify,
+    semaphore: Arc<Semaphore>,
+}
+
+static EXECUTOR: OnceLock<Arc<AgentExecutor>> = OnceLock::new();
+
+fn concurrency() -> usize {
+    std::env::var(CONCURRENCY_ENV)
+        .ok()
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .clamp(1, MAX_CONCURRENCY)
+}
+
+fn executor() -> Arc<AgentExecutor> {
+    EXECUTOR
+        .get_or_init(|| {
+            let count = concurrency();
+            let executor = Arc::new(AgentExecutor {
+                state: Mutex::new(ExecutorState::default()),
+                notify: Notify::new(),
+                semaphore: Arc::new(Semaphore::new(count)),
+            });
+            let dispatcher = executor.clone();
+            tauri::async_runtime::spawn(async move {
+                dispatcher.dispatch().await;
+            });
+            eprintln!("✅ [agent_executor] タスク実行エンジンを起動しました（同時実行数: {}）", count);
+            executor
+        })
+        .clone()
+}
+
+fn new_execution_id() -> String {
+    format!("{}{}", EXECUTION_ID_PREFIX, uuid::Uuid::new_v4())
+}
+
+/// この実行エンジンが作成した実行か（接頭辞の導入前の`exec_<UUID>`を含む）
+fn is_executor_execution(execution_id: &str) -> bool {
+    execution_id.starts_with(EXECUTION_ID_PREFIX)
+        || execution_id
+            .strip_prefix("exec_")
+            .map(|rest| uuid::Uuid::parse_str(rest).is_ok())
+            .unwrap_or(false)
+}
+
+/// 前回の起動時に終了しなかったこの実行エンジンの実行（pending / running）を失敗にする（起動時に一度だけ呼び出す）
+///
+/// フロントエンドのAgentOrchestratorが実行中の実行は対象にしない。
+pub(crate) fn recover_interrupted_executions() {
+    let executions = match get_all_task_executions() {
+        Ok(executions) => executions,
+        Err(e) => {
+            eprintln!("⚠️ [agent_executor] 実行履歴の取得に失敗しました: {}", e);
+            return;
+        }
+    };
+    let noop: AgentTaskProgressCallback = Arc::new(|_| {});
+    for execution in executions {
+        if !is_executor_execution(&execution.id) {
+            continue;
+        }
+        if !matches!(ExecutionStatus::parse(&execution.status), Some(ExecutionStatus::Pending | ExecutionStatus::Running)) {
+            continue;
+        }
+        let mut recorder = ExecutionRecorder::new(execution, noop.clone());
+        recorder.log("error", "アプリの終了により実行が中断されました", None);
+        recorder.fail(ExecutionStatus::Failed, "アプリの終了により実行が中断されました".to_string());
+    }
+}
+
+impl AgentExecutor {
+    fn lock(&self) -> std::sync::MutexGuard<'_, ExecutorState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 空きができるたびに優先度の最も高いタスクを取り出して実行する
+    async fn dispatch(self: Arc<Self>) {
+        loop {
+            let permit = match self.semaphore.clone().acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+            let (queued, cancel_rx) = loop {
+                let next = {
+                    let mut state = self.lock();
+                    state.queue.pop().map(|queued| {
+                        let (cancel_tx, cancel_rx) = watch::channel(false);
+                        state.running.insert(queued.recorder.execution.id.clone(), cancel_tx);
+                        (queued, cancel_rx)
+                    })
+                };
+                match next {
+                    Some(next) => break next,
+                    None => self.notify.notified().await,
+                }
+            };
+            let executor = self.clone();
+            tauri::async_runtime::spawn(async move {
+                let QueuedExecution { task, agent, mut recorder, done, .. } = queued;
+                run_execution(&task, &agent, &mut recorder, cancel_rx).await;
+                drop(permit);
+                executor.lock().running.remove(&recorder.execution.id);
+                if let Some(done) = done {
+                    let _ = done.send(recorder.execution.clone());
+                }
+            });
+        }
+    }
+}
+
+/// Agentの設定（config）から数値を取得
+fn config_number(agent: &Agent, path: &[&str]) -> Option<f64> {
+    let config: Value = serde_json::from_str(&agent.config).ok()?;
+    path.iter().try_fold(&config, |value, key| value.get(*key))?.as_f64()
+}
+
+/// 再試行までの待ち時間（retryDelay * backoffMultiplier^(再試行回数-1)）
+fn retry_delay(agent: &Agent, retry: u32) -> Duration {
+    let delay_ms = config_number(agent, &["retryPolicy", "retryDelay"])
+        .filter(|d| *d >= 0.0)
+        .unwrap_or(DEFAULT_RETRY_DELAY_MS as f64);
+    let multiplier = config_number(agent, &["retryPolicy", "backoffMultiplier"])
+        .filter(|m| *m >= 1.0)
+        .unwrap_or(DEFAULT_BACKOFF_MULTIPLIER);
+    let delay = delay_ms * multiplier.powi(retry.saturating_sub(1) as i32);
+    Duration::from_millis(delay as u64).min(MAX_RETRY_DELAY)
+}
+
+/// 上流タスクの結果をparametersに格納するキー（task_dagのスケジューラーが設定する）
+pub(crate) const UPSTREAM_RESULTS_KEY: &str = "upstreamResults";
+
+/// タスクのパラメータからユーザープロンプトを作成（parameters.promptがあればそれを使う）
+/// 上流タスクの結果は`{{upstream.<タスクID>}}`の位置に埋め込み、埋め込まれなかったものは末尾に追加する
+fn build_prompt(task: &Task) -> String {
+    let mut parameters: Value = serde_json::from_str(&task.parameters).unwrap_or(Value::Null);
+    let upstream = parameters
+        .as_object_mut()
+        .and_then(|p| p.remove(UPSTREAM_RESULTS_KEY))
+        .and_then(|u| match u {
+            Value::Object(map) => Some(map),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    let mut prompt = match parameters.get("prompt").and_then(|p| p.as_str()).filter(|p| !p.trim().is_empty()) {
+        Some(prompt) => prompt.to_string(),
+        None => {
+            let mut prompt = format!("タスク: {}\nタイプ: {}\n", task.name, task.task_type);
+            if !task.description.trim().is_empty() {
+                prompt.push_str(&format!("\n{}\n", task.description.trim()));
+            }
+            if parameters.as_object().is_some_and(|p| !p.is_empty()) {
+                prompt.push_str(&format!(
+                    "\nパラメータ:\n{}\n",
+                    serde_json::to_string_pretty(&parameters).unwrap_or_default()
+                ));
+            }
+            prompt
+        }
+    };
+
+    let mut unreferenced = Vec::new();
+    for (task_id, result) in &upstream {
+        let content = result.get("content").and_then(|c| c.as_str()).map(str::to_string)
+            .unwrap_or_else(|| result.to_string());
+        let placeholder = format!("{{{{upstream.{}}}}}", task_id);
+        if prompt.contains(&placeholder) {
+            prompt = prompt.replace(&placeholder, &content);
+        } else {
+            let name = result.get("taskName").and_then(|n| n.as_str()).unwrap_or(task_id);
+            unreferenced.push(format!("## {}\n{}", name, content));
+        }
+    }
+    if !unreferenced.is_empty() {
+        prompt.push_str(&format!("\n\n前のタスクの結果:\n\n{}\n", unreferenced.join("\n\n")));
+    }
+    prompt
+}
+
+/// キャンセルが通知されるまで待つ
+pub(crate) async fn cancelled(cancel_rx: &mut watch::Receiver<bool>) {
+    while !*cancel_rx.borrow() {
+        if cancel_rx.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+async fn run_execution(task: &Task, agent: &Agent, recorder: &mut ExecutionRecorder, mut cancel_rx: watch::Receiver<bool>) {
+    if !recorder.transition(ExecutionStatus::Running) {
+        return;
+    }
+
+    let model_type = task.model_type.as_deref().or(Some(agent.model_type.as_str()));
+    let model = task.selected_model.as_deref().or(agent.selected_model.as_deref());
+    let client = match LlmClient::resolve(model_type, model) {
+        Ok(client) => client,
+        Err(e) => {
+            recorder.log("error", format!("LLMの設定に失敗しました: {}", e), None);
+            recorder.fail(ExecutionStatus::Failed, e);
+            return;
+        }
+    };
+
+    let timeout_ms = task
+        .timeout
+        .filter(|t| *t > 0)
+        .or_else(|| config_number(agent, &["defaultTimeout"]).map(|t| t as i64).filter(|t| *t > 0))
+        .unwrap_or(DEFAULT_TIMEOUT_MS);
+    let timeout = Duration::from_millis(timeout_ms as u64);
+    let max_attempts = task.retry_count.unwrap_or(0).max(0) as u32 + 1;
+
+    let mut messages = Vec::new();
+    if !agent.system_prompt.trim().is_empty() {
+        messages.push(LlmMessage::system(agent.system_prompt.clone()));
+    }
+    messages.push(LlmMessage::user(build_prompt(task)));
+    let options = LlmOptions::default();
+
+    recorder.log(
+        "info",
+        format!("タスクの実行を開始しました（Agent: {}）", agent.name),
+        Some(json!({
+            "provider": client.provider(),
+            "model": client.model(),
+            "timeout": timeout_ms,
+            "maxAttempts": max_attempts,
+        })),
+    );
+
+    let mut last_error = String::new();
+    for attempt in 1..=max_attempts {
+        recorder.log("info", format!("LLMを呼び出します（試行 {}/{}）", attempt, max_attempts), None);
+        let started = std::time::Instant::now();
+        let outcome = tokio::select! {
+            result = tokio::time::timeout(timeout, client.chat(&messages, &options)) => Some(result),
+            _ = cancelled(&mut cancel_rx) => None,
+        };
+
+        match outcome {
+            None => {
+                recorder.log("warn", "タスクがキャンセルされました", None);
+                recorder.fail(ExecutionStatus::Cancelled, "タスクがキャンセルされました".to_string());
+                return;
+            }
+            Some(Ok(Ok(response))) => {
+                let elapsed_ms = started.elapsed().as_millis() as u64;
+                recorder.log(
+                    "info",
+                    format!("LLMの応答を受信しました（{}文字, {}ms）", response.content.chars().count(), elapsed_ms),
+                    Some(json!({ "usage": response.usage })),
+                );
+                recorder.complete(json!({
+                    "content": response.content,
+                    "provider": response.provider,
+                    "model": response.model,
+                    "usage": response.usage,
+                    "attempts": attempt,
+                }));
+                return;
+            }
+            Some(Ok(Err(e))) => {
+                last_error = e;
+                recorder.log("error", format!("LLMの呼び出しに失敗しました: {}", last_error), None);
+            }
+            Some(Err(_)) => {
+                last_error = format!("タスクがタイムアウトしました（{}ms）", timeout_ms);
+                recorder.log("error", last_error.clone(), None);
+            }
+        }
+
+        if attempt < max_attempts {
+            let delay = retry_delay(agent, attempt);
+            recorder.log("warn", format!("{}ms後に再試行します", delay.as_millis()), None);
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancelled(&mut cancel_rx) => {
+                    recorder.log("warn", "タスクがキャンセルされました", None);
+                    recorder.fail(ExecutionStatus::Cancelled, "タスクがキャンセルされました".to_string());
+                    return;
+                }
+            }
+        }
+    }
+
+    recorder.fail(ExecutionStatus::Failed, last_error);
+}
+
+/// タスクを実行するAgentを決定（agentId → requiredAgents → タスクタイプをcapabilitiesに持つAgent）
+pub(crate) fn resolve_agent(task: &Task) -> Result<Agent, String> {
+    if let Some(agent_id) = task.agent_id.as_deref().filter(|id| !id.is_empty()) {
+        return get_agent(agent_id)
+            .map_err(|e| format!("Agentの取得に失敗しました: {}", e))?
+            .ok_or_else(|| format!("Agentが見つかりません: {}", agent_id));
+    }
+
+    let required: Vec<String> = task
+        .required_agents
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+    for agent_id in &required {
+        if let Some(agent) = get_agent(agent_id).map_err(|e| format!("Agentの取得に失敗しました: {}", e))? {
+            return Ok(agent);
+        }
+    }
+
+    let agents = get_all_agents().map_err(|e| format!("Agent一覧の取得に失敗しました: {}", e))?;
+    agents
+        .into_iter()
+        .find(|agent| {
+            serde_json::from_str::<Vec<String>>(&agent.capabilities)
+                .map(|capabilities| capabilities.iter().any(|c| c == &task.task_type))
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| {
+            if required.is_empty() {
+                format!("タスクタイプ {} を実行できるAgentが見つかりません", task.task_type)
+            } else {
+                format!("requiredAgentsのAgentが見つかりません: {}", required.join(", "))
+            }
+        })
+}
+
+/// タスクを実行キューに追加し、作成した実行（pending）を返す
+pub fn submit_task(task_id: &str, on_progress: AgentTaskProgressCallback) -> Result<TaskExecution, String> {
+    let task = get_task(task_id)
+        .map_err(|e| format!("タスクの取得に失敗しました: {}", e))?
+        .ok_or_else(|| format!("タスクが見つかりません: {}", task_id))?;
+    enqueue_task(task, ExecutionLink::default(), on_progress).map(|(execution, _)| execution)
+}
+
+/// タスクチェーンの実行・プロンプトの評価から投入した実行の参照
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExecutionLink {
+    pub chain_run_id: Option<String>,
+    pub node_id: Option<String>,
+    pub evaluation_id: Option<String>,
+}
+
+/// タスク（パラメータを差し替えたものでもよい）を実行キューに追加し、作成した実行と終了時の通知を返す
+pub(crate) fn enqueue_task(
+    task: Task,
+    link: ExecutionLink,
+    on_progress: AgentTaskProgressCallback,
+) -> Result<(TaskExecution, oneshot::Receiver<TaskExecution>), String> {
+    let mut agent = resolve_agent(&task)?;
+    // promptVersionを固定したタスクは、そのバージョンのプロンプトで実行する
+    let prompt_version = apply_prompt_version(&mut agent, task.prompt_version)?;
+    let executor = executor();
+
+    let now = now_millis().to_string();
+    let execution = TaskExecution {
+        id: new_execution_id(),
+        task_id: task.id.clone(),
+        agent_id: agent.id.clone(),
+        status: ExecutionStatus::Pending.as_str().to_string(),
+        started_at: now.clone(),
+        completed_at: None,
+        result: None,
+        error: None,
+        logs: "[]".to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+        chain_run_id: link.chain_run_id,
+        node_id: link.node_id,
+        prompt_version,
+        evaluation_id: link.evaluation_id,
+    };
+    let execution = save_task_execution(&execution).map_err(|e| format!("タスク実行の保存に失敗しました: {}", e))?;
+
+    let mut recorder = ExecutionRecorder::new(execution, on_progress);
+    recorder.log("info", format!("実行キューに追加しました（優先度: {}）", task.priority), None);
+    let execution = recorder.execution.clone();
+    let (done, done_rx) = oneshot::channel();
+
+    {
+        let mut state = executor.lock();
+        state.sequence += 1;
+        let sequence = state.sequence;
+        state.queue.push(QueuedExecution { priority: task.priority, sequence, task, agent, recorder, done: Some(done) });
+    }
+    executor.notify.notify_one();
+    Ok((execution, done_rx))
+}
+
+/// 実行しなかったタスクをskippedとして記録する
+pub(crate) fn record_skipped_execution(
+    task: &Task,
+    reason: &str,
+    on_progress: AgentTaskProgressCallback,
+) -> Result<TaskExecution, String> {
+    let mut recorder = record_finished_execution(
+        task,
+        ExecutionLink::default(),
+        ExecutionStatus::Skipped,
+        None,
+        None,
+        Some(reason.to_string()),
+        on_progress,
+    )?;
+    recorder.log("warn", reason, None);
+    Ok(recorder.execution)
+}
+
+/// 実行キューに追加できなかったタスクをfailedとして記録する（評価などで投入の失敗を実行として残すため）
+/// promptVersionはタスクに固定されたバージョンを記録する
+pub(crate) fn record_failed_execution(
+    task: &Task,
+    link: ExecutionLink,
+    error: &str,
+    on_progress: AgentTaskProgressCallback,
+) -> Result<TaskExecution, String> {
+    let mut recorder = record_finished_execution(
+        task,
+        link,
+        ExecutionStatus::Failed,
+        task.prompt_version,
+        None,
+        Some(error.to_string()),
+        on_progress,
+    )?;
+    recorder.log("error", error, None);
+    Ok(recorder.execution)
+}
+
+/// Agentを呼び出さないチェーンのノード（条件分岐・並列など）の通過を、終了済みの実行として記録する
+pub(crate) fn record_chain_node_execution(
+    task: &Task,
+    link: ExecutionLink,
+    status: ExecutionStatus,
+    result: Value,
+    message: &str,
+    on_progress: AgentTaskProgressCallback,
+) -> Result<TaskExecution, String> {
+    let error = (status != ExecutionStatus::Completed).then(|| message.to_string());
+    let mut recorder = record_finished_execution(task, link, status, None, Some(result), error, on_progress)?;
+    let level = if status == ExecutionStatus::Completed { "info" } else { "warn" };
+    recorder.log(level, message, None);
+    Ok(recorder.execution)
+}
+
+fn record_finished_execution(
+    task: &Task,
+    link: ExecutionLink,
+    status: ExecutionStatus,
+    prompt_version: Option<i64>,
+    result: Option<Value>,
+    error: Option<String>,
+    on_progress: AgentTaskProgressCallback,
+) -> Result<ExecutionRecorder, String> {
+    let now = now_millis().to_string();
+    let agent_id = task
+        .agent_id
+        .clone()
+        .or_else(|| resolve_agent(task).ok().map(|agent| agent.id))
+        .unwrap_or_default();
+    let execution = TaskExecution {
+        id: new_execution_id(),
+        task_id: task.id.clone(),
+        agent_id,
+        status: status.as_str().to_string(),
+        started_at: now.clone(),
+        completed_at: Some(now.clone()),
+        result: result.map(|r| r.to_string()),
+        error,
+        logs: "[]".to_string(),
+        created_at: now.clone(),
+        updated_at: now,
+        chain_run_id: link.chain_run_id,
+        node_id: link.node_id,
+        prompt_version,
+        evaluation_id: link.evaluation_id,
+    };
+    let execution = save_task_execution(&execution).map_err(|e| format!("タスク実行の保存に失敗しました: {}", e))?;
+    Ok(ExecutionRecorder::new(execution, on_progress))
+}
+
+/// 実行をキャンセル（実行待ち・実行中でなかった場合はfalse）
+pub fn cancel_task_execution(execution_id: &str) -> Result<bool, String> {
+    let Some(executor) = EXECUTOR.get() else {
+        return Ok(false);
+    };
+    let mut state = executor.lock();
+
+    // 実行中の場合は通知し、実行中のタスクがキャンセル状態にする
+    if let Some(cancel_tx) = state.running.get(execution_id) {
+        let _ = cancel_tx.send(true);
+        return Ok(true);
+    }
+
+    // 実行待ちの場合はキューから取り除く
+    let queue = std::mem::take(&mut state.queue);
+    let (mut removed, remaining): (Vec<_>, Vec<_>) = queue
+        .into_iter()
+        .partition(|queued| queued.recorder.execution.id == execution_id);
+    state.queue = remaining.into_iter().collect();
+    drop(state);
+
+    match removed.pop() {
+        Some(mut queued) => {
+            queued.recorder.log("warn", "タスクがキャンセルされました", None);
+            queued.recorder.fail(ExecutionStatus::Cancelled, "タスクがキャンセルされました".to_string());
+            if let Some(done) = queued.done.take() {
+                let _ = done.send(queued.recorder.execution.clone());
+            }
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,578 @@
+/**
+ * Agentのシステムプロンプトのバージョン管理とA/B評価
+ * プロンプトの変更をagent_prompt_versionsに自動で記録し（最新のバージョンが現在のプロンプト）、
+ * バージョン間の差分・ロールバック・タスクごとのバージョン固定と、2つのバージョンの比較評価を行う
+ *
+ * - バージョンはAgentごとの通し番号（1始まり）。ロールバックも新しいバージョンとして記録し、rolledBackFromに元のバージョンを残す
+ * - タスクのpromptVersionを指定すると、実行時にそのバージョンのプロンプトでAgentを呼び出す
+ * - 評価では同じタスクの組を2つのバージョンでそれぞれ実行し、taskExecutionsにevaluationIdと
+ *   promptVersionを付けて記録する（依存関係は解決せず、各タスクを単独で実行する）
+ */
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use super::agent_executor::{
+    enqueue_task, now_millis, record_failed_execution, AgentTaskProgressCallback, ExecutionLink, ExecutionStatus,
+};
+use super::agent_system::{
+    get_agent, get_task, get_task_executions_by_evaluation, Agent, Task, TaskExecution,
+};
+use super::design_doc_revision::RevisionDiffMode;
+use super::get_db;
+use super::task_dag::constraint_error;
+use super::text_diff::{diff_words, unified_diff, UnifiedDiff, WordDiff};
+
+/// unified形式の差分の前後に表示する行数の既定値
+const DEFAULT_DIFF_CONTEXT: usize = 3;
+
+const VERSION_COLUMNS: &str = "id, agentId, version, systemPrompt, rolledBackFrom, createdAt";
+const EVALUATION_COLUMNS: &str = "id, agentId, versionA, versionB, taskIds, createdAt";
+
+/// システムプロンプトのバージョン
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentPromptVersion {
+    pub id: String,
+    #[serde(rename = "agentId")]
+    pub agent_id: String,
+    /// Agent内の通し番号（1始まり）
+    pub version: i64,
+    #[serde(rename = "systemPrompt")]
+    pub system_prompt: String,
+    /// ロールバックで記録した場合の元のバージョン
+    #[serde(rename = "rolledBackFrom")]
+    pub rolled_back_from: Option<i64>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+/// バージョン間の差分
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentPromptDiff {
+    #[serde(rename = "agentId")]
+    pub agent_id: String,
+    #[serde(rename = "fromVersion")]
+    pub from_version: i64,
+    #[serde(rename = "toVersion")]
+    pub to_version: i64,
+    pub mode: RevisionDiffMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unified: Option<UnifiedDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<WordDiff>,
+}
+
+/// 評価のバージョンごとの集計
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptEvaluationSummary {
+    pub version: i64,
+    pub completed: usize,
+    /// 失敗・キャンセルされた実行
+    pub failed: usize,
+    /// 実行待ち・実行中の実行
+    pub running: usize,
+    /// 完了した実行の平均所要時間（ミリ秒）
+    #[serde(rename = "averageDurationMs")]
+    pub average_duration_ms: Option<i64>,
+}
+
+/// 1つのタスクの2つのバージョンでの実行
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptEvaluationPair {
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+    pub a: Option<TaskExecution>,
+    pub b: Option<TaskExecution>,
+}
+
+/// プロンプトのA/B評価
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptEvaluation {
+    pub id: String,
+    #[serde(rename = "agentId")]
+    pub agent_id: String,
+    #[serde(rename = "versionA")]
+    pub version_a: i64,
+    #[serde(rename = "versionB")]
+    pub version_b: i64,
+    #[serde(rename = "taskIds")]
+    pub task_ids: Vec<String>,
+    /// running / completed（すべての実行が終了したらcompleted）
+    pub status: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<i64>,
+    pub pairs: Vec<PromptEvaluationPair>,
+    #[serde(rename = "summaryA")]
+    pub summary_a: PromptEvaluationSummary,
+    #[serde(rename = "summaryB")]
+    pub summary_b: PromptEvaluationSummary,
+}
+
+/// promptEvaluationsテーブルの行
+struct EvaluationRecord {
+    id: String,
+    agent_id: String,
+    version_a: i64,
+    version_b: i64,
+    task_ids: Vec<String>,
+    created_at: i64,
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+fn read_version(row: &rusqlite::Row) -> SqlResult<AgentPromptVersion> {
+    Ok(AgentPromptVersion {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        version: row.get(2)?,
+        system_prompt: row.get(3)?,
+        rolled_back_from: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+fn find_version(conn: &Connection, agent_id: &str, version: i64) -> SqlResult<Option<AgentPromptVersion>> {
+    conn.query_row(
+        &format!("SELECT {} FROM agent_prompt_versions WHERE agentId = ?1 AND version = ?2", VERSION_COLUMNS),
+        params![agent_id, version],
+        read_version,
+    )
+    .optional()
+}
+
+fn latest_version(conn: &Connection, agent_id: &str) -> SqlResult<Option<AgentPromptVersion>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM agent_prompt_versions WHERE agentId = ?1 ORDER BY version DESC LIMIT 1",
+            VERSION_COLUMNS
+        ),
+        params![agent_id],
+        read_version,
+    )
+    .optional()
+}
+
+/// プロンプトが最新のバージョンと異なる場合に新しいバージョンとして記録し、最新のバージョンを返す
+pub(crate) fn record_prompt_version(
+    conn: &Connection,
+    agent_id: &str,
+    system_prompt: &str,
+    rolled_back_from: Option<i64>,
+) -> SqlResult<AgentPromptVersion> {
+    let latest = latest_version(conn, agent_id)?;
+    if let Some(latest) = latest.as_ref().filter(|latest| latest.system_prompt == system_prompt) {
+        return Ok(latest.clone());
+    }
+
+    let version = latest.map(|latest| latest.version).unwrap_or(0) + 1;
+    let record = AgentPromptVersion {
+        id: format!("{}-v{}", agent_id, version),
+        agent_id: agent_id.to_string(),
+        version,
+        system_prompt: system_prompt.to_string(),
+        rolled_back_from,
+        created_at: now_millis(),
+    };
+    conn.execute(
+        "INSERT INTO agent_prompt_versions (id, agentId, version, systemPrompt, rolledBackFrom, createdAt, updatedAt)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            record.id,
+            record.agent_id,
+            record.version,
+            record.system_prompt,
+            record.rolled_back_from,
+            record.created_at,
+            record.created_at,
+        ],
+    )?;
+    Ok(record)
+}
+
+/// タスクのpromptVersionが、タスクのAgent（agentId）に存在するバージョンかを検証する
+pub(crate) fn validate_prompt_pin(task: &Task) -> SqlResult<()> {
+    let Some(version) = task.prompt_version else {
+        return Ok(());
+    };
+    let Some(agent_id) = task.agent_id.as_deref().filter(|id| !id.is_empty()) else {
+        return Err(constraint_error("promptVersionを指定する場合はagentIdも指定してください".to_string()));
+    };
+    let conn = connection().map_err(constraint_error)?;
+    if find_version(&conn, agent_id, version)?.is_none() {
+        return Err(constraint_error(format!(
+            "Agent {} のプロンプトのバージョン {} が見つかりません",
+            agent_id, version
+        )));
+    }
+    Ok(())
+}
+
+/// 実行するAgentのプロンプトを固定したバージョンに差し替え、実行に使うバージョンを返す
+///
+/// 固定していない場合は現在のプロンプト（最新のバージョン）のまま実行する。
+pub(crate) fn apply_prompt_version(agent: &mut Agent, pinned: Option<i64>) -> Result<Option<i64>, String> {
+    let conn = connection()?;
+    let Some(version) = pinned else {
+        let latest = latest_version(&conn, &agent.id).map_err(|e| e.to_string())?;
+        return Ok(latest.filter(|latest| latest.system_prompt == agent.system_prompt).map(|latest| latest.version));
+    };
+    let record = find_version(&conn, &agent.id, version)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Agent {} のプロンプトのバージョン {} が見つかりません", agent.id, version))?;
+    agent.system_prompt = record.system_prompt;
+    Ok(Some(version))
+}
+
+/// Agentのプロンプトのバージョン一覧を取得（新しい順）
+pub fn list_agent_prompt_versions(agent_id: &str) -> Result<Vec<AgentPromptVersion>, String> {
+    let conn = connection()?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM agent_prompt_versions WHERE agentId = ?1 ORDER BY version DESC",
+            VERSION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let versions = stmt
+        .query_map(params![agent_id], read_version)
+        .and_then(|rows| rows.collect::<SqlResult<Vec<_>>>())
+        .map_err(|e| e.to_string())?;
+    Ok(versions)
+}
+
+/// Agentのプロンプトの指定したバージョンを取得
+pub fn get_agent_prompt_version(agent_id: &str, version: i64) -> Result<AgentPromptVersion, String> {
+    let conn = connection()?;
+    find_version(&conn, agent_id, version)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Agent {} のプロンプトのバージョン {} が見つかりません", agent_id, version))
+}
+
+/// 2つのバージョンのプロンプトの差分を取得
+pub fn diff_agent_prompt_versions(
+    agent_id: &str,
+    from_version: i64,
+    to_version: i64,
+    mode: RevisionDiffMode,
+    context: Option<usize>,
+) -> Result<AgentPromptDiff, String> {
+    let from = get_agent_prompt_version(agent_id, from_version)?;
+    let to = get_agent_prompt_version(agent_id, to_version)?;
+
+    let (unified, words) = match mode {
+        RevisionDiffMode::Unified => {
+            let unified = unified_diff(
+                &from.system_prompt,
+                &to.system_prompt,
+                &format!("v{}", from.version),
+                &format!("v{}", to.version),
+                context.unwrap_or(DEFAULT_DIFF_CONTEXT),
+            );
+            (Some(unified), None)
+        }
+        RevisionDiffMode::Word => (None, Some(diff_words(&from.system_prompt, &to.system_prompt))),
+    };
+
+    Ok(AgentPromptDiff {
+        agent_id: agent_id.to_string(),
+        from_version: from.version,
+        to_version: to.version,
+        mode,
+        unified,
+        words,
+    })
+}
+
+/// Agentのプロンプトを指定したバージョンに戻す（ロールバックも新しいバージョンとして記録する）
+pub fn rollback_agent_prompt(agent_id: &str, version: i64) -> Result<Agent, String> {
+    let target = get_agent_prompt_version(agent_id, version)?;
+    {
+        let conn = connection()?;
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        let updated = tx
+            .execute(
+                "UPDATE agents SET systemPrompt = ?1, updatedAt = ?2 WHERE id = ?3",
+                params![target.system_prompt, super::get_timestamp(), agent_id],
+            )
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err(format!("Agentが見つかりません: {}", agent_id));
+        }
+        record_prompt_version(&tx, agent_id, &target.system_prompt, Some(version)).map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+    eprintln!("⏪ [agent_prompt] Agent {} のプロンプトをバージョン {} に戻しました", agent_id, version);
+
+    get_agent(agent_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Agentが見つかりません: {}", agent_id))
+}
+
+/// タスクの実行に使うプロンプトのバージョンを固定する（Noneで固定を解除、タスクのAgentを変更した場合も解除される）
+pub fn pin_task_prompt_version(task_id: &str, version: Option<i64>) -> Result<Task, String> {
+    let mut task = get_task(task_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("タスクが見つかりません: {}", task_id))?;
+    task.prompt_version = version;
+    validate_prompt_pin(&task).map_err(|e| e.to_string())?;
+
+    // save_taskはpromptVersionを更新しないため、固定はここでのみ書き込む
+    connection()?
+        .execute(
+            "UPDATE tasks SET promptVersion = ?1, updatedAt = ?2 WHERE id = ?3",
+            params![version, super::get_timestamp(), task_id],
+        )
+        .map_err(|e| e.to_string())?;
+    get_task(task_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("タスクが見つかりません: {}", task_id))
+}
+
+fn read_evaluation(row: &rusqlite::Row) -> SqlResult<EvaluationRecord> {
+    let task_ids: String = row.get(4)?;
+    Ok(EvaluationRecord {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        version_a: row.get(2)?,
+        version_b: row.get(3)?,
+        task_ids: serde_json::from_str(&task_ids).unwrap_or_default(),
+        created_at: row.get(5)?,
+    })
+}
+
+fn summarize(version: i64, executions: &[&TaskExecution]) -> PromptEvaluationSummary {
+    let mut summary = PromptEvaluationSummary { version, completed: 0, failed: 0, running: 0, average_duration_ms: None };
+    let mut durations = Vec::new();
+    for execution in executions {
+        match ExecutionStatus::parse(&execution.status) {
+            Some(ExecutionStatus::Completed) => {
+                summary.completed += 1;
+                let started = execution.started_at.parse::<i64>().ok();
+                let completed = execution.completed_at.as_deref().and_then(|c| c.parse::<i64>().ok());
+                if let (Some(started), Some(completed)) = (started, completed) {
+                    durations.push(completed - started);
+                }
+            }
+            Some(status) if status.is_terminal() => summary.failed += 1,
+            _ => summary.running += 1,
+        }
+    }
+    if !durations.is_empty() {
+        summary.average_duration_ms = Some(durations.iter().sum::<i64>() / durations.len() as i64);
+    }
+    summary
+}
+
+/// 評価の実行をタスクごとの組にまとめる（同じタスク・バージョンの実行が複数ある場合は後のものを使う）
+fn build_evaluation(record: EvaluationRecord, executions: Vec<TaskExecution>) -> PromptEvaluation {
+    let pairs: Vec<PromptEvaluationPair> = record
+        .task_ids
+        .iter()
+        .map(|task_id| {
+            let find = |version: i64| {
+                executions
+                    .iter()
+                    .rfind(|e| &e.task_id == task_id && e.prompt_version == Some(version))
+                    .cloned()
+            };
+            PromptEvaluationPair { task_id: task_id.clone(), a: find(record.version_a), b: find(record.version_b) }
+        })
+        .collect();
+
+    let executions_a: Vec<&TaskExecution> = pairs.iter().filter_map(|pair| pair.a.as_ref()).collect();
+    let executions_b: Vec<&TaskExecution> = pairs.iter().filter_map(|pair| pair.b.as_ref()).collect();
+    let summary_a = summarize(record.version_a, &executions_a);
+    let summary_b = summarize(record.version_b, &executions_b);
+
+    // 実行の投入前（開始直後）は実行中として扱う（投入できなかった実行はfailedとして記録される）
+    let all_submitted = pairs.iter().all(|pair| pair.a.is_some() && pair.b.is_some());
+    let finished = all_submitted && summary_a.running == 0 && summary_b.running == 0;
+    let completed_at = finished
+        .then(|| {
+            executions
+                .iter()
+                .filter_map(|e| e.completed_at.as_deref().and_then(|c| c.parse::<i64>().ok()))
+                .max()
+        })
+        .flatten();
+
+    PromptEvaluation {
+        id: record.id,
+        agent_id: record.agent_id,
+        version_a: record.version_a,
+        version_b: record.version_b,
+        task_ids: record.task_ids,
+        status: if finished { "completed" } else { "running" }.to_string(),
+        created_at: record.created_at,
+        completed_at,
+        pairs,
+        summary_a,
+        summary_b,
+    }
+}
+
+/// 開始を記録した実行待ちの評価
+pub struct PromptEvaluationHandle {
+    pub evaluation: PromptEvaluation,
+    tasks: Vec<Task>,
+}
+
+/// 評価するバージョンとタスクを検証し、評価の開始を記録する
+pub fn start_prompt_evaluation(
+    agent_id: &str,
+    version_a: i64,
+    version_b: i64,
+    task_ids: &[String],
+) -> Result<PromptEvaluationHandle, String> {
+    if version_a == version_b {
+        return Err("比較する2つのバージョンには異なるバージョンを指定してください".to_string());
+    }
+    get_agent(agent_id)
+        .map_err(|e| format!("Agentの取得に失敗しました: {}", e))?
+        .ok_or_else(|| format!("Agentが見つかりません: {}", agent_id))?;
+    get_agent_prompt_version(agent_id, version_a)?;
+    get_agent_prompt_version(agent_id, version_b)?;
+
+    let mut seen = HashSet::new();
+    let task_ids: Vec<String> = task_ids.iter().filter(|id| seen.insert(id.as_str())).cloned().collect();
+    if task_ids.is_empty() {
+        return Err("評価するタスクを指定してください".to_string());
+    }
+    let tasks = task_ids
+        .iter()
+        .map(|task_id| {
+            get_task(task_id)
+                .map_err(|e| format!("タスクの取得に失敗しました: {}", e))?
+                .ok_or_else(|| format!("タスクが見つかりません: {}", task_id))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let record = EvaluationRecord {
+        id: format!("prompt_eval_{}", uuid::Uuid::new_v4()),
+        agent_id: agent_id.to_string(),
+        version_a,
+        version_b,
+        task_ids,
+        created_at: now_millis(),
+    };
+    connection()?
+        .execute(
+            &format!("INSERT INTO promptEvaluations ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6)", EVALUATION_COLUMNS),
+            params![
+                record.id,
+                record.agent_id,
+                record.version_a,
+                record.version_b,
+                serde_json::to_string(&record.task_ids).unwrap_or_else(|_| "[]".to_string()),
+                record.created_at,
+            ],
+        )
+        .map_err(|e| format!("評価の保存に失敗しました: {}", e))?;
+
+    Ok(PromptEvaluationHandle { evaluation: build_evaluation(record, Vec::new()), tasks })
+}
+
+/// 各タスクを2つのバージョンのプロンプトでそれぞれ実行キューに追加し、すべての実行が終了するまで待つ
+pub async fn run_prompt_evaluation(handle: PromptEvaluationHandle, on_progress: AgentTaskProgressCallback) -> PromptEvaluation {
+    let PromptEvaluationHandle { evaluation, tasks } = handle;
+    eprintln!(
+        "🚀 [agent_prompt] プロンプトの評価を開始します: {} (Agent: {}, v{} / v{}, {}タスク)",
+        evaluation.id,
+        evaluation.agent_id,
+        evaluation.version_a,
+        evaluation.version_b,
+        tasks.len()
+    );
+
+    // 同じタスクの2つの実行が近いタイミングで実行されるよう、タスクごとに交互に投入する
+    let mut waiting = Vec::new();
+    let mut failed = Vec::new();
+    for task in &tasks {
+        for version in [evaluation.version_a, evaluation.version_b] {
+            let mut variant = task.clone();
+            variant.agent_id = Some(evaluation.agent_id.clone());
+            variant.prompt_version = Some(version);
+            let link = ExecutionLink { evaluation_id: Some(evaluation.id.clone()), ..Default::default() };
+            match enqueue_task(variant.clone(), link.clone(), on_progress.clone()) {
+                Ok((_, done)) => waiting.push(done),
+                Err(e) => {
+                    // 投入できなかった実行もfailedとして記録し、評価が実行中のまま残らないようにする
+                    eprintln!("⚠️ [agent_prompt] 評価のタスクを実行できませんでした: {} (v{}): {}", task.id, version, e);
+                    let message = format!("評価のタスクを実行キューに追加できませんでした: {}", e);
+                    match record_failed_execution(&variant, link, &message, on_progress.clone()) {
+                        Ok(execution) => failed.push(execution),
+                        Err(e) => eprintln!("❌ [agent_prompt] 評価の失敗した実行の記録に失敗しました: {} (v{}): {}", task.id, version, e),
+                    }
+                }
+            }
+        }
+    }
+    let mut executions = failed;
+    for done in waiting {
+        if let Ok(execution) = done.await {
+            executions.push(execution);
+        }
+    }
+
+    let record = EvaluationRecord {
+        id: evaluation.id.clone(),
+        agent_id: evaluation.agent_id.clone(),
+        version_a: evaluation.version_a,
+        version_b: evaluation.version_b,
+        task_ids: evaluation.task_ids.clone(),
+        created_at: evaluation.created_at,
+    };
+    let evaluation = build_evaluation(record, executions);
+    eprintln!(
+        "✅ [agent_prompt] プロンプトの評価が終了しました: {} (v{}: {}件完了, v{}: {}件完了)",
+        evaluation.id, evaluation.version_a, evaluation.summary_a.completed, evaluation.version_b, evaluation.summary_b.completed
+    );
+    evaluation
+}
+
+/// 評価を取得（実行の状態はtaskExecutionsから集計する）
+pub fn get_prompt_evaluation(evaluation_id: &str) -> Result<Option<PromptEvaluation>, String> {
+    let record = {
+        let conn = connection()?;
+        conn.query_row(
+            &format!("SELECT {} FROM promptEvaluations WHERE id = ?1", EVALUATION_COLUMNS),
+            params![evaluation_id],
+            read_evaluation,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+    };
+    let Some(record) = record else {
+        return Ok(None);
+    };
+    let executions = get_task_executions_by_evaluation(evaluation_id).map_err(|e| e.to_string())?;
+    Ok(Some(build_evaluation(record, executions)))
+}
+
+/// Agentの評価の一覧を取得（新しい順）
+pub fn list_prompt_evaluations(agent_id: &str) -> Result<Vec<PromptEvaluation>, String> {
+    let records = {
+        let conn = connection()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM promptEvaluations WHERE agentId = ?1 ORDER BY createdAt DESC",
+                EVALUATION_COLUMNS
+            ))
+            .map_err(|e| e.to_string())?;
+        stmt.query_map(params![agent_id], read_evaluation)
+            .and_then(|rows| rows.collect::<SqlResult<Vec<_>>>())
+            .map_err(|e| e.to_string())?
+    };
+    records
+        .into_iter()
+        .map(|record| {
+            let executions = get_task_executions_by_evaluation(&record.id).map_err(|e| e.to_string())?;
+            Ok(build_evaluation(record, executions))
+        })
+        .collect()
+}
@@ -1,6 +1,9 @@
 use rusqlite::{params, Result as SqlResult};
 use serde::{Deserialize, Serialize};
 use crate::database::{get_db, get_timestamp};
+use super::task_dag::validate_task_graph;
+use super::task_chain::validate_task_chain;
+use super::agent_prompt::{record_prompt_version, validate_prompt_pin};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -30,6 +33,10 @@ pub struct Task {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "selectedModel")]
     pub selected_model: Option<String>,
+    /// 実行に使うAgentのプロンプトのバージョン（未指定の場合は最新のプロンプト）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "promptVersion", default)]
+    pub prompt_version: Option<i64>,
     #[serde(rename = "createdAt")]
     pub created_at: String,
     #[serde(rename = "updatedAt")]
@@ -58,10 +65,24 @@ pub struct TaskExecution {
     pub created_at: String,
     #[serde(rename = "updatedAt")]
     pub updated_at: String,
+    /// タスクチェーンの実行から実行された場合のチェーンの実行IDとノードID
+    #[serde(rename = "chainRunId", default)]
+    pub chain_run_id: Option<String>,
+    #[serde(rename = "nodeId", default)]
+    pub node_id: Option<String>,
+    /// 実行に使ったAgentのプロンプトのバージョン
+    #[serde(rename = "promptVersion", default)]
+    pub prompt_version: Option<i64>,
+    /// プロンプトのA/B評価から実行された場合の評価ID
+    #[serde(rename = "evaluationId", default)]
+    pub evaluation_id: Option<String>,
 }
 
 /// タスクを保存
 pub fn save_task(task: &Task) -> SqlResult<Task> {
+    // dependencies / requiredAgentsの形式と依存関係の循環を検証
+    validate_task_graph(task)?;
+
     let db = get_db().ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
             rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
@@ -77,10 +98,13 @@ pub fn save_task(task: &Task) -> SqlResult<Task> {
     let is_new = existing_task.is_none();
 
     if is_new {
+        // 固定したプロンプトのバージョンがAgentに存在するかを検証
+        validate_prompt_pin(task)?;
+
         // 新規作成
         conn.execute(
-            "INSERT INTO tasks (id, name, description, type, agentId, requiredAgents, dependencies, parameters, priority, timeout, retryCount, modelType, selectedModel, createdAt, updatedAt)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+            "INSERT INTO tasks (id, name, description, type, agentId, requiredAgents, dependencies, parameters, priority, timeout, retryCount, modelType, selectedModel, promptVersion, createdAt, updatedAt)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
             params![
                 task.id,
                 task.name,
@@ -95,14 +119,16 @@ pub fn save_task(task: &Task) -> SqlResult<Task> {
                 task.retry_count,
                 task.model_type,
                 task.selected_model,
+                task.prompt_version,
                 now,
                 now,
             ],
         )?;
     } else {
-        // 更新
+        // 更新（promptVersionはpin_task_prompt_versionでのみ変更し、Agentが変わった場合は固定を解除する）
         conn.execute(
-            "UPDATE tasks SET name = ?1, description = ?2, type = ?3, agentId = ?4, requiredAgents = ?5, dependencies = ?6, parameters = ?7, priority = ?8, timeout = ?9, retryCount = ?10, modelType = ?11, selectedModel = ?12, updatedAt = ?13
+            "UPDATE tasks SET name = ?1, description = ?2, type = ?3, agentId = ?4, requiredAgents = ?5, dependencies = ?6, parameters = ?7, priority = ?8, timeout = ?9, retryCount = ?10, modelType = ?11, selectedModel = ?12,
+                promptVersion = CASE WHEN agentId IS ?4 THEN promptVersion ELSE NULL END, updatedAt = ?13
              WHERE id = ?14",
             params![
                 task.name,
@@ -145,7 +171,7 @@ pub fn get_task(id: &str) -> SqlResult<Option<Task>> {
     let conn = db.get_connection()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, name, description, type, agentId, requiredAgents, dependencies, parameters, priority, timeout, retryCount, modelType, selectedModel, createdAt, updatedAt
+        "SELECT id, name, description, type, agentId, requiredAgents, dependencies, parameters, priority, timeout, retryCount, modelType, selectedModel, promptVersion, createdAt, updatedAt
          FROM tasks WHERE id = ?1"
     )?;
 
@@ -164,8 +190,9 @@ pub fn get_task(id: &str) -> SqlResult<Option<Task>> {
             retry_count: row.get(10)?,
             model_type: row.get(11)?,
             selected_model: row.get(12)?,
-            created_at: row.get(13)?,
-            updated_at: row.get(14)?,
+            prompt_version: row.get(13)?,
+            created_at: row.get(14)?,
+            updated_at: row.get(15)?,
         })
     });
 
@@ -176,7 +203,7 @@ pub fn get_task(id: &str) -> SqlResult<Option<Task>> {
     }
 }
 
-/// すべてのタスクを取得
+/// すべてのタスクを取得（タスクチェーン専用のタスクは除く）
 pub fn get_all_tasks() -> SqlResult<Vec<Task>> {
     let db = get_db().ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
@@ -188,8 +215,8 @@ pub fn get_all_tasks() -> SqlResult<Vec<Task>> {
     let conn = db.get_connection()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, name, description, type, agentId, requiredAgents, dependencies, parameters, priority, timeout, retryCount, modelType, selectedModel, createdAt, updatedAt
-         FROM tasks ORDER BY createdAt DESC"
+        "SELECT id, name, description, type, agentId, requiredAgents, dependencies, parameters, priority, timeout, retryCount, modelType, selectedModel, promptVersion, createdAt, updatedAt
+         FROM tasks WHERE chainId IS NULL ORDER BY createdAt DESC"
     )?;
 
     let task_iter = stmt.query_map([], |row| {
@@ -207,8 +234,9 @@ pub fn get_all_tasks() -> SqlResult<Vec<Task>> {
             retry_count: row.get(10)?,
             model_type: row.get(11)?,
             selected_model: row.get(12)?,
-            created_at: row.get(13)?,
-            updated_at: row.get(14)?,
+            prompt_version: row.get(13)?,
+            created_at: row.get(14)?,
+            updated_at: row.get(15)?,
         })
     })?;
 
@@ -258,8 +286,8 @@ pub fn save_task_execution(execution: &TaskExecution) -> SqlResult<TaskExecution
     if is_new {
         // 新規作成
         conn.execute(
-            "INSERT INTO taskExecutions (id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            "INSERT INTO taskExecutions (id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt, chainRunId, nodeId, promptVersion, evaluationId)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
             params![
                 execution.id,
                 execution.task_id,
@@ -272,6 +300,10 @@ pub fn save_task_execution(execution: &TaskExecution) -> SqlResult<TaskExecution
                 execution.logs,
                 now,
                 now,
+                execution.chain_run_id,
+                execution.node_id,
+                execution.prompt_version,
+                execution.evaluation_id,
             ],
         )?;
     } else {
@@ -316,7 +348,7 @@ pub fn get_task_execution(id: &str) -> SqlResult<Option<TaskExecution>> {
     let conn = db.get_connection()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt
+        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt, chainRunId, nodeId, promptVersion, evaluationId
          FROM taskExecutions WHERE id = ?1"
     )?;
 
@@ -333,6 +365,10 @@ pub fn get_task_execution(id: &str) -> SqlResult<Option<TaskExecution>> {
             logs: row.get(8)?,
             created_at: row.get(9)?,
             updated_at: row.get(10)?,
+            chain_run_id: row.get(11)?,
+            node_id: row.get(12)?,
+            prompt_version: row.get(13)?,
+            evaluation_id: row.get(14)?,
         })
     });
 
@@ -355,7 +391,7 @@ pub fn get_task_executions(task_id: &str) -> SqlResult<Vec<TaskExecution>> {
     let conn = db.get_connection()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt
+        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt, chainRunId, nodeId, promptVersion, evaluationId
          FROM taskExecutions WHERE taskId = ?1 ORDER BY createdAt DESC"
     )?;
 
@@ -372,6 +408,10 @@ pub fn get_task_executions(task_id: &str) -> SqlResult<Vec<TaskExecution>> {
             logs: row.get(8)?,
             created_at: row.get(9)?,
             updated_at: row.get(10)?,
+            chain_run_id: row.get(11)?,
+            node_id: row.get(12)?,
+            prompt_version: row.get(13)?,
+            evaluation_id: row.get(14)?,
         })
     })?;
 
@@ -395,7 +435,7 @@ pub fn get_all_task_executions() -> SqlResult<Vec<TaskExecution>> {
     let conn = db.get_connection()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt
+        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt, chainRunId, nodeId, promptVersion, evaluationId
          FROM taskExecutions ORDER BY createdAt DESC"
     )?;
 
@@ -412,6 +452,98 @@ pub fn get_all_task_executions() -> SqlResult<Vec<TaskExecution>> {
             logs: row.get(8)?,
             created_at: row.get(9)?,
             updated_at: row.get(10)?,
+            chain_run_id: row.get(11)?,
+            node_id: row.get(12)?,
+            prompt_version: row.get(13)?,
+            evaluation_id: row.get(14)?,
+        })
+    })?;
+
+    let mut executions = Vec::new();
+    for execution_result in execution_iter {
+        executions.push(execution_result?);
+    }
+
+    Ok(executions)
+}
+
+/// タスクチェーンの実行に紐づく実行履歴を取得（実行順）
+pub fn get_task_executions_by_chain_run(chain_run_id: &str) -> SqlResult<Vec<TaskExecution>> {
+    let db = get_db().ok_or_else(|| {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            Some("データベースが初期化されていません".to_string()),
+        )
+    })?;
+
+    let conn = db.get_connection()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt, chainRunId, nodeId, promptVersion, evaluationId
+         FROM taskExecutions WHERE chainRunId = ?1 ORDER BY CAST(startedAt AS INTEGER), rowid"
+    )?;
+
+    let execution_iter = stmt.query_map(params![chain_run_id], |row| {
+        Ok(TaskExecution {
+            id: row.get(0)?,
+            task_id: row.get(1)?,
+            agent_id: row.get(2)?,
+            status: row.get(3)?,
+            started_at: row.get(4)?,
+            completed_at: row.get(5)?,
+            result: row.get(6)?,
+            error: row.get(7)?,
+            logs: row.get(8)?,
+            created_at: row.get(9)?,
+            updated_at: row.get(10)?,
+            chain_run_id: row.get(11)?,
+            node_id: row.get(12)?,
+            prompt_version: row.get(13)?,
+            evaluation_id: row.get(14)?,
+        })
+    })?;
+
+    let mut executions = Vec::new();
+    for execution_result in execution_iter {
+        executions.push(execution_result?);
+    }
+
+    Ok(executions)
+}
+
+/// プロンプトのA/B評価に紐づく実行履歴を取得（投入順）
+pub fn get_task_executions_by_evaluation(evaluation_id: &str) -> SqlResult<Vec<TaskExecution>> {
+    let db = get_db().ok_or_else(|| {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            Some("データベースが初期化されていません".to_string()),
+        )
+    })?;
+
+    let conn = db.get_connection()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, taskId, agentId, status, startedAt, completedAt, result, error, logs, createdAt, updatedAt, chainRunId, nodeId, promptVersion, evaluationId
+         FROM taskExecutions WHERE evaluationId = ?1 ORDER BY rowid"
+    )?;
+
+    let execution_iter = stmt.query_map(params![evaluation_id], |row| {
+        Ok(TaskExecution {
+            id: row.get(0)?,
+            task_id: row.get(1)?,
+            agent_id: row.get(2)?,
+            status: row.get(3)?,
+            started_at: row.get(4)?,
+            completed_at: row.get(5)?,
+            result: row.get(6)?,
+            error: row.get(7)?,
+            logs: row.get(8)?,
+            created_at: row.get(9)?,
+            updated_at: row.get(10)?,
+            chain_run_id: row.get(11)?,
+            node_id: row.get(12)?,
+            prompt_version: row.get(13)?,
+            evaluation_id: row.get(14)?,
         })
     })?;
 
@@ -439,6 +571,8 @@ pub struct TaskChain {
 
 /// タスクチェーンを保存
 pub fn save_task_chain(chain: &TaskChain) -> SqlResult<TaskChain> {
+    validate_task_chain(chain)?;
+
     let db = get_db().ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
             rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
@@ -621,9 +755,12 @@ pub fn save_agent(agent: &Agent) -> SqlResult<Agent> {
     let existing_agent = get_agent(&agent.id).ok().flatten();
     let is_new = existing_agent.is_none();
 
+    // Agentの更新とプロンプトのバージョン記録は同じトランザクションで行う
+    let tx = conn.unchecked_transaction()?;
+
     if is_new {
         // 新規作成
-        match conn.execute(
+        match tx.execute(
             "INSERT INTO agents (id, name, description, role, capabilities, tools, modelType, selectedModel, systemPrompt, config, createdAt, updatedAt)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             params![
@@ -646,7 +783,7 @@ pub fn save_agent(agent: &Agent) -> SqlResult<Agent> {
                 // UNIQUE制約エラーの場合、既に存在する可能性があるので更新を試みる
                 if e.to_string().contains("UNIQUE constraint") {
                     // 更新を試みる
-                    conn.execute(
+                    tx.execute(
                         "UPDATE agents SET name = ?2, description = ?3, role = ?4, capabilities = ?5, tools = ?6, modelType = ?7, selectedModel = ?8, systemPrompt = ?9, config = ?10, updatedAt = ?11
                          WHERE id = ?1",
                         params![
@@ -669,40 +806,8 @@ pub fn save_agent(agent: &Agent) -> SqlResult<Agent> {
             }
         }
     } else {
-        // 更新 - システムプロンプトが変更された場合はバージョン履歴を保存
-        if let Some(existing) = existing_agent {
-            if existing.system_prompt != agent.system_prompt {
-                // 現在の最大バージョン番号を取得
-                let max_version: i32 = conn.query_row(
-                    "SELECT COALESCE(MAX(version), 0) FROM agent_prompt_versions WHERE agentId = ?1",
-                    params![agent.id],
-                    |row| row.get(0),
-                ).unwrap_or(0);
-
-                // 新しいバージョンとして保存
-                let version_id = format!("{}-v{}", agent.id, max_version + 1);
-                use std::time::{SystemTime, UNIX_EPOCH};
-                let now_timestamp = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as i64;
-                conn.execute(
-                    "INSERT INTO agent_prompt_versions (id, agentId, version, systemPrompt, createdAt, updatedAt)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                    params![
-                        version_id,
-                        agent.id,
-                        max_version + 1,
-                        existing.system_prompt, // 変更前のプロンプトを保存
-                        now_timestamp,
-                        now_timestamp,
-                    ],
-                )?;
-            }
-        }
-
-        // Agentを更新
-        conn.execute(
+        // 更新
+        tx.execute(
             "UPDATE agents SET name = ?2, description = ?3, role = ?4, capabilities = ?5, tools = ?6, modelType = ?7, selectedModel = ?8, systemPrompt = ?9, config = ?10, updatedAt = ?11
              WHERE id = ?1",
             params![
@@ -721,6 +826,10 @@ pub fn save_agent(agent: &Agent) -> SqlResult<Agent> {
         )?;
     }
 
+    // システムプロンプトが変更された場合（新規作成を含む）は新しいバージョンとして記録
+    record_prompt_version(&tx, &agent.id, &agent.system_prompt, None)?;
+    tx.commit()?;
+
     // 保存したAgentを取得して返す
     get_agent(&agent.id)?.ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
@@ -0,0 +1,779 @@
+/**
+ * ChromaDB同期のリコンサイラー
+ * chromaSyncedカラムが0の行（未同期・同期失敗）の埋め込みを再生成してベクトルストアに登録し、
+ * deleteOrphansを指定した場合はSQLiteの行が削除済みの孤立したベクトルを削除する
+ *
+ * - 対象はエンティティ・リレーション・トピック・議事録・Graphviz YAML/DOTファイル
+ * - ベクトルのIDは既存の保存処理と同じ（トピックはtopicsの行IDではなくtopicId）
+ * - バッチ単位で埋め込みを生成し、バッチ間の最小間隔でAPIのレート制限を避ける
+ * - 埋め込みはコレクションごとに、埋め込みレジストリに登録されたプロバイダー・モデルで生成する
+ * - 埋め込みAPIの失敗は指数バックオフで再試行し、それでも失敗した行はchromaSyncErrorに記録する
+ * - 同期中に行が更新された場合は同期済みにせず、次回の実行で再同期する
+ * - 進捗はコールバックで通知する（Tauriコマンドからイベントとして送信）
+ */
+
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use crate::database::{get_db, get_timestamp};
+use super::embedding::TextEmbedder;
+use super::vector_search::{self, VectorStore};
+
+const DEFAULT_BATCH_SIZE: usize = 32;
+const MAX_BATCH_SIZE: usize = 256;
+const DEFAULT_MIN_BATCH_INTERVAL_MS: u64 = 500;
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// 埋め込みAPIの再試行間隔（RETRY_BASE_DELAY * 2^(試行回数-1)、最大RETRY_MAX_DELAY）
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// 埋め込みに使うテキストの最大文字数（議事録本文などの長文を切り詰める）
+const MAX_TEXT_CHARS: usize = 8000;
+
+/// 孤立ベクトルの確認でSQLiteに一度に問い合わせるID数
+const ORPHAN_CHECK_CHUNK: usize = 500;
+
+/// 論理コレクション名から、そのコレクションの埋め込みを生成するTextEmbedderを作成する関数
+pub type EmbedderResolver = dyn Fn(&str) -> Result<Box<dyn TextEmbedder>, String> + Send + Sync;
+
+/// 同期対象の定義
+struct SyncSource {
+    source_type: &'static str,
+    table: &'static str,
+    /// ベクトルストアのコレクション名（組織ごとの場合は`{prefix}_{organizationId}`）
+    collection_prefix: &'static str,
+    /// コレクションを組織ごとに分けるか
+    per_organization: bool,
+    /// chromaSyncedカラムを持つか（持たない場合は孤立ベクトルの削除のみ）
+    tracks_sync: bool,
+    /// コレクションの組織ID（事業会社の場合はcompanyId）
+    scope_expr: &'static str,
+    /// 埋め込みに使うテキスト
+    text_expr: &'static str,
+    /// ベクトルのメタデータに含めるカラム
+    metadata_columns: &'static [&'static str],
+    /// メタデータに行IDを格納するキー
+    id_key: &'static str,
+    /// ベクトルのIDにするカラム（既存のChromaDBの保存処理と同じIDにする）
+    vector_id_column: &'static str,
+}
+
+static SOURCES: &[SyncSource] = &[
+    SyncSource {
+        source_type: "entity",
+        table: "entities",
+        collection_prefix: vector_search::COLLECTION_ENTITIES,
+        per_organization: true,
+        tracks_sync: true,
+        scope_expr: "COALESCE(organizationId, companyId, '')",
+        text_expr: "COALESCE(NULLIF(searchableText, ''), name || ' ' || type || ' ' || COALESCE(aliases, ''))",
+        metadata_columns: &["name", "type", "companyId"],
+        id_key: "entityId",
+        vector_id_column: "id",
+    },
+    SyncSource {
+        source_type: "relation",
+        table: "relations",
+        collection_prefix: vector_search::COLLECTION_RELATIONS,
+        per_organization: true,
+        tracks_sync: true,
+        scope_expr: "COALESCE(organizationId, companyId, '')",
+        text_expr: "COALESCE(NULLIF(searchableText, ''), relationType || ' ' || COALESCE(description, ''))",
+        metadata_columns: &["relationType", "topicId", "yamlFileId", "sourceEntityId", "targetEntityId", "description", "companyId"],
+        id_key: "relationId",
+        vector_id_column: "id",
+    },
+    SyncSource {
+        source_type: "topic",
+        table: "topics",
+        collection_prefix: vector_search::COLLECTION_TOPICS,
+        per_organization: true,
+        tracks_sync: true,
+        scope_expr: "COALESCE(organizationId, companyId, '')",
+        text_expr: "COALESCE(NULLIF(searchableText, ''), title || ' ' || COALESCE(contentSummary, content, ''))",
+        metadata_columns: &["title", "contentSummary", "semanticCategory", "topicDate", "meetingNoteId", "companyId"],
+        id_key: "topicId",
+        vector_id_column: "topicId",
+    },
+    SyncSource {
+        source_type: "meetingNote",
+        table: "meetingNotes",
+        collection_prefix: vector_search::COLLECTION_MEETING_NOTES,
+        per_organization: true,
+        tracks_sync: true,
+        scope_expr: "COALESCE(organizationId, companyId, '')",
+        text_expr: "title || ' ' || COALESCE(description, '') || ' ' || COALESCE(content, '')",
+        metadata_columns: &["title", "description", "companyId"],
+        id_key: "meetingNoteId",
+        vector_id_column: "id",
+    },
+    SyncSource {
+        source_type: "graphvizYamlFile",
+        table: "graphvizYamlFiles",
+        collection_prefix: vector_search::COLLECTION_GRAPHVIZ_YAML_FILES,
+        per_organization: true,
+        tracks_sync: true,
+        scope_expr: "COALESCE(organizationId, '')",
+        text_expr: "COALESCE(NULLIF(searchableText, ''), name || ' ' || COALESCE(description, '') || ' ' || COALESCE(contentSummary, ''))",
+        metadata_columns: &["name", "description", "yamlType", "semanticCategory"],
+        id_key: "yamlFileId",
+        vector_id_column: "id",
+    },
+    SyncSource {
+        source_type: "graphvizDotFile",
+        table: "graphvizDotFiles",
+        collection_prefix: vector_search::COLLECTION_GRAPHVIZ_DOT_FILES,
+        per_organization: true,
+        tracks_sync: true,
+        scope_expr: "COALESCE(organizationId, '')",
+        text_expr: "COALESCE(NULLIF(searchableText, ''), name || ' ' || COALESCE(description, ''))",
+        metadata_columns: &["name", "description", "graphType", "yamlFileId"],
+        id_key: "dotFileId",
+        vector_id_column: "id",
+    },
+    SyncSource {
+        source_type: "designDoc",
+        table: "designDocSections",
+        collection_prefix: vector_search::COLLECTION_DESIGN_DOCS,
+        per_organization: false,
+        tracks_sync: false,
+        scope_expr: "''",
+        text_expr: "title || ' ' || COALESCE(description, '') || ' ' || content",
+        metadata_columns: &[],
+        id_key: "sectionId",
+        vector_id_column: "id",
+    },
+];
+
+impl SyncSource {
+    fn collection_name(&self, scope_id: &str) -> String {
+        if self.per_organization {
+            vector_search::org_collection_name(self.collection_prefix, scope_id)
+        } else {
+            self.collection_prefix.to_string()
+        }
+    }
+
+    /// コレクション名がこの同期対象のものか
+    fn owns_collection(&self, name: &str) -> bool {
+        if self.per_organization {
+            name.strip_prefix(self.collection_prefix)
+                .map(|rest| rest.starts_with('_'))
+                .unwrap_or(false)
+        } else {
+            name == self.collection_prefix
+        }
+    }
+}
+
+/// リコンサイラーの実行オプション
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChromaSyncOptions {
+    /// 対象の種別（未指定の場合はすべて）
+    #[serde(rename = "sourceTypes")]
+    pub source_types: Option<Vec<String>>,
+    /// 1回の埋め込みAPI呼び出しで処理する行数
+    #[serde(rename = "batchSize")]
+    pub batch_size: Option<usize>,
+    /// バッチ間の最小間隔（ミリ秒）
+    #[serde(rename = "minBatchIntervalMs")]
+    pub min_batch_interval_ms: Option<u64>,
+    /// 埋め込みAPIの失敗時の再試行回数
+    #[serde(rename = "maxRetries")]
+    pub max_retries: Option<u32>,
+    /// 孤立したベクトルを削除するか（デフォルト: false）
+    #[serde(rename = "deleteOrphans")]
+    pub delete_orphans: Option<bool>,
+}
+
+/// 進捗通知（Tauriイベント`chroma-sync-progress`のペイロード）
+#[derive(Debug, Clone, Serialize)]
+pub struct ChromaSyncProgress {
+    /// sync / orphans / done / cancelled
+    pub phase: String,
+    #[serde(rename = "sourceType")]
+    pub source_type: Option<String>,
+    /// 対象種別の未同期件数（開始時点）
+    pub total: usize,
+    pub processed: usize,
+    pub synced: usize,
+    pub failed: usize,
+    #[serde(rename = "orphansDeleted")]
+    pub orphans_deleted: usize,
+    pub message: Option<String>,
+}
+
+/// 種別ごとの結果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChromaSyncSourceReport {
+    #[serde(rename = "sourceType")]
+    pub source_type: String,
+    pub pending: usize,
+    pub synced: usize,
+    pub failed: usize,
+    #[serde(rename = "orphansDeleted")]
+    pub orphans_deleted: usize,
+}
+
+/// リコンサイラーの実行結果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChromaSyncReport {
+    pub sources: Vec<ChromaSyncSourceReport>,
+    pub synced: usize,
+    pub failed: usize,
+    #[serde(rename = "orphansDeleted")]
+    pub orphans_deleted: usize,
+    pub cancelled: bool,
+}
+
+/// 種別ごとの未同期件数
+#[derive(Debug, Clone, Serialize)]
+pub struct ChromaSyncPendingCount {
+    #[serde(rename = "sourceType")]
+    pub source_type: String,
+    /// chromaSynced = 0 の件数
+    pub pending: usize,
+    /// そのうちchromaSyncErrorが記録されている件数
+    pub failed: usize,
+}
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+static CANCEL_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// 実行中フラグを解除するガード
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// リコンサイラーが実行中か
+pub fn is_chroma_sync_running() -> bool {
+    RUNNING.load(Ordering::SeqCst)
+}
+
+/// 実行中のリコンサイラーに中断を要求（現在のバッチの完了後に停止）
+pub fn cancel_chroma_sync() -> bool {
+    if !is_chroma_sync_running() {
+        return false;
+    }
+    CANCEL_REQUESTED.store(true, Ordering::SeqCst);
+    true
+}
+
+fn cancel_requested() -> bool {
+    CANCEL_REQUESTED.load(Ordering::SeqCst)
+}
+
+fn selected_sources(source_types: &Option<Vec<String>>) -> Result<Vec<&'static SyncSource>, String> {
+    match source_types {
+        None => Ok(SOURCES.iter().collect()),
+        Some(types) => types
+            .iter()
+            .map(|t| {
+                SOURCES.iter()
+                    .find(|s| s.source_type == t)
+                    .ok_or_else(|| format!("不明な同期対象です: {}", t))
+            })
+            .collect(),
+    }
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+/// 種別ごとの未同期件数を取得
+pub fn get_chroma_sync_pending_counts() -> Result<Vec<ChromaSyncPendingCount>, String> {
+    let conn = connection()?;
+    SOURCES.iter()
+        .filter(|s| s.tracks_sync)
+        .map(|source| {
+            conn.query_row(
+                &format!(
+                    "SELECT COUNT(*), COUNT(chromaSyncError) FROM {} WHERE COALESCE(chromaSynced, 0) = 0",
+                    source.table
+                ),
+                [],
+                |row| Ok(ChromaSyncPendingCount {
+                    source_type: source.source_type.to_string(),
+                    pending: row.get::<_, i64>(0)? as usize,
+                    failed: row.get::<_, i64>(1)? as usize,
+                }),
+            )
+            .map_err(|e| format!("未同期件数の取得に失敗しました ({}): {}", source.table, e))
+        })
+        .collect()
+}
+
+/// コレクションに対応するテーブルから、IDごとの埋め込み用テキストを取得（再埋め込みで使用）
+/// 同期対象に該当しないコレクションの場合は空
+pub(crate) fn load_source_texts(collection: &str, ids: &[String]) -> Result<HashMap<String, String>, String> {
+    let Some(source) = SOURCES.iter().find(|s| s.owns_collection(collection)) else {
+        return Ok(HashMap::new());
+    };
+    let conn = connection()?;
+    let mut texts = HashMap::new();
+    for chunk in ids.chunks(ORPHAN_CHECK_CHUNK) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {}, {} FROM {} WHERE {} IN ({})",
+            source.vector_id_column, source.text_expr, source.table, source.vector_id_column, placeholders
+        )).map_err(|e| format!("テキストの取得に失敗しました ({}): {}", source.table, e))?;
+        let rows: Vec<(String, Option<String>)> = stmt
+            .query_map(params_from_iter(chunk.iter()), |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect())
+            .map_err(|e| format!("テキストの取得に失敗しました ({}): {}", source.table, e))?;
+        for (id, text) in rows {
+            let text: String = text.unwrap_or_default().trim().chars().take(MAX_TEXT_CHARS).collect();
+            if !text.is_empty() {
+                texts.insert(id, text);
+            }
+        }
+    }
+    Ok(texts)
+}
+
+/// コレクションのベクトルIDに対応するSQLiteの行を未同期（chromaSynced = 0）に戻す
+/// （再埋め込み中に書き込まれたベクトルを次回の同期で作り直すため）
+pub(crate) fn mark_unsynced(collection: &str, ids: &[String]) -> Result<usize, String> {
+    let Some(source) = SOURCES.iter().find(|s| s.tracks_sync && s.owns_collection(collection)) else {
+        return Ok(0);
+    };
+    let conn = connection()?;
+    let mut updated = 0;
+    for chunk in ids.chunks(ORPHAN_CHECK_CHUNK) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        updated += conn.execute(
+            &format!(
+                "UPDATE {} SET chromaSynced = 0 WHERE {} IN ({})",
+                source.table, source.vector_id_column, placeholders
+            ),
+            params_from_iter(chunk.iter()),
+        ).map_err(|e| format!("同期状態の更新に失敗しました ({}): {}", source.table, e))?;
+    }
+    Ok(updated)
+}
+
+/// 同期対象の1行
+struct PendingRow {
+    id: String,
+    /// ベクトルストアでのID
+    vector_id: String,
+    scope_id: String,
+    updated_at: Option<String>,
+    text: String,
+    metadata: HashMap<String, Value>,
+}
+
+fn count_pending(conn: &Connection, source: &SyncSource) -> rusqlite::Result<usize> {
+    conn.query_row(
+        &format!("SELECT COUNT(*) FROM {} WHERE COALESCE(chromaSynced, 0) = 0", source.table),
+        [],
+        |row| row.get::<_, i64>(0),
+    ).map(|n| n as usize)
+}
+
+/// 未同期の行をIDの昇順に取得（after_idより後の行のみ）
+fn load_pending_rows(
+    conn: &Connection,
+    source: &SyncSource,
+    after_id: &str,
+    limit: usize,
+) -> rusqlite::Result<Vec<PendingRow>> {
+    let mut columns = vec![
+        "id".to_string(),
+        format!("{} AS scopeId", source.scope_expr),
+        "updatedAt".to_string(),
+        format!("{} AS syncText", source.text_expr),
+        format!("{} AS vectorId", source.vector_id_column),
+    ];
+    columns.extend(source.metadata_columns.iter().map(|c| c.to_string()));
+    let sql = format!(
+        "SELECT {} FROM {} WHERE COALESCE(chromaSynced, 0) = 0 AND id > ?1 ORDER BY id LIMIT ?2",
+        columns.join(", "),
+        source.table
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params![after_id, limit as i64], |row| {
+        let mut metadata = HashMap::new();
+        for (i, column) in source.metadata_columns.iter().enumerate() {
+            if let Some(value) = row.get::<_, Option<String>>(5 + i)? {
+                metadata.insert(column.to_string(), Value::String(value));
+            }
+        }
+        let text: Option<String> = row.get(3)?;
+        Ok(PendingRow {
+            id: row.get(0)?,
+            scope_id: row.get(1)?,
+            updated_at: row.get(2)?,
+            vector_id: row.get(4)?,
+            text: text.unwrap_or_default().trim().chars().take(MAX_TEXT_CHARS).collect(),
+            metadata,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 同期済みにする（読み込み後に行が更新されていた場合は何もしない）
+fn mark_synced(conn: &Connection, source: &SyncSource, row: &PendingRow) -> rusqlite::Result<bool> {
+    let updated = conn.execute(
+        &format!(
+            "UPDATE {} SET chromaSynced = 1, chromaSyncError = NULL, lastChromaSyncAttempt = ?1
+             WHERE id = ?2 AND updatedAt IS ?3",
+            source.table
+        ),
+        params![get_timestamp(), row.id, row.updated_at],
+    )?;
+    Ok(updated > 0)
+}
+
+/// 同期失敗を記録
+fn mark_failed(conn: &Connection, source: &SyncSource, id: &str, error: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "UPDATE {} SET chromaSynced = 0, chromaSyncError = ?1, lastChromaSyncAttempt = ?2 WHERE id = ?3",
+            source.table
+        ),
+        params![error, get_timestamp(), id],
+    )?;
+    Ok(())
+}
+
+/// 埋め込みを生成（失敗時は指数バックオフで再試行）
+async fn embed_with_retry(
+    embedder: &dyn TextEmbedder,
+    texts: Vec<String>,
+    max_retries: u32,
+) -> Result<Vec<Vec<f32>>, String> {
+    let mut attempt = 0;
+    loop {
+        match embedder.embed(texts.clone()).await {
+            Ok(embeddings) if embeddings.len() == texts.len() => return Ok(embeddings),
+            Ok(embeddings) => {
+                return Err(format!(
+                    "埋め込みの件数が一致しません: 期待値={}, 実際={}",
+                    texts.len(),
+                    embeddings.len()
+                ));
+            }
+            Err(e) if attempt < max_retries && !cancel_requested() => {
+                attempt += 1;
+                let delay = RETRY_BASE_DELAY
+                    .saturating_mul(2u32.saturating_pow(attempt - 1))
+                    .min(RETRY_MAX_DELAY);
+                eprintln!(
+                    "[chroma_sync] ⚠️ 埋め込みの生成に失敗しました（{}秒後に再試行 {}/{}）: {}",
+                    delay.as_secs(),
+                    attempt,
+                    max_retries,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 1行分の埋め込みをベクトルストアに登録
+async fn upsert_row(
+    store: &dyn VectorStore,
+    source: &SyncSource,
+    row: &PendingRow,
+    embedding: Vec<f32>,
+) -> Result<(), String> {
+    let metadata = row.metadata.clone();
+    match source.source_type {
+        "entity" => {
+            vector_search::save_entity_embedding(store, row.vector_id.clone(), row.scope_id.clone(), embedding, metadata).await
+        }
+        "relation" => {
+            vector_search::save_relation_embedding(store, row.vector_id.clone(), row.scope_id.clone(), embedding, metadata).await
+        }
+        "topic" => {
+            let meeting_note_id = metadata.get("meetingNoteId").and_then(|v| v.as_str()).map(|s| s.to_string());
+            vector_search::save_topic_embedding(
+                store,
+                row.vector_id.clone(),
+                meeting_note_id,
+                row.scope_id.clone(),
+                embedding,
+                metadata,
+                None,
+            ).await
+        }
+        _ => {
+            let mut metadata: Map<String, Value> = metadata.into_iter().collect();
+            metadata.insert(source.id_key.to_string(), Value::String(row.vector_id.clone()));
+            if source.per_organization {
+                metadata.insert("organizationId".to_string(), Value::String(row.scope_id.clone()));
+            }
+            store.upsert(source.collection_name(&row.scope_id), row.vector_id.clone(), embedding, metadata).await
+                .map_err(|e| format!("埋め込みの保存に失敗しました: {}", e))
+        }
+    }
+}
+
+/// 1種別分の未同期行を同期
+async fn sync_source(
+    store: &dyn VectorStore,
+    embedder_for: &EmbedderResolver,
+    source: &SyncSource,
+    options: &ChromaSyncOptions,
+    report: &mut ChromaSyncSourceReport,
+    on_progress: &(dyn Fn(ChromaSyncProgress) + Send + Sync),
+) -> Result<(), String> {
+    let batch_size = options.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).clamp(1, MAX_BATCH_SIZE);
+    let min_interval = Duration::from_millis(options.min_batch_interval_ms.unwrap_or(DEFAULT_MIN_BATCH_INTERVAL_MS));
+    let max_retries = options.max_retries.unwrap_or(DEFAULT_MAX_RETRIES);
+
+    report.pending = count_pending(&*connection()?, source)
+        .map_err(|e| format!("未同期件数の取得に失敗しました ({}): {}", source.table, e))?;
+    let progress = |report: &ChromaSyncSourceReport, message: Option<String>| ChromaSyncProgress {
+        phase: "sync".to_string(),
+        source_type: Some(source.source_type.to_string()),
+        total: report.pending,
+        processed: report.synced + report.failed,
+        synced: report.synced,
+        failed: report.failed,
+        orphans_deleted: 0,
+        message,
+    };
+    on_progress(progress(report, None));
+
+    // コレクションごとのTextEmbedder（作成に失敗した場合は次のバッチで再度作成する）
+    let mut embedders: HashMap<String, Box<dyn TextEmbedder>> = HashMap::new();
+
+    // IDの昇順に1回ずつ処理する（同期に失敗した行を同じ実行内で繰り返し処理しない）
+    let mut last_id = String::new();
+    let mut last_batch_started: Option<Instant> = None;
+    loop {
+        if cancel_requested() {
+            return Ok(());
+        }
+
+        let rows = load_pending_rows(&*connection()?, source, &last_id, batch_size)
+            .map_err(|e| format!("未同期行の取得に失敗しました ({}): {}", source.table, e))?;
+        let Some(last) = rows.last() else {
+            return Ok(());
+        };
+        last_id = last.id.clone();
+
+        if let Some(started) = last_batch_started {
+            let elapsed = started.elapsed();
+            if elapsed < min_interval {
+                tokio::time::sleep(min_interval - elapsed).await;
+            }
+        }
+        last_batch_started = Some(Instant::now());
+
+        let (rows, empty_rows): (Vec<PendingRow>, Vec<PendingRow>) =
+            rows.into_iter().partition(|row| !row.text.is_empty());
+        let mut failures: Vec<(String, String)> = empty_rows
+            .into_iter()
+            .map(|row| (row.id, "埋め込み対象のテキストがありません".to_string()))
+            .collect();
+        let mut synced_rows = Vec::new();
+
+        // 組織ごとのコレクションでモデルが異なる場合があるため、コレクション単位で埋め込みを生成する
+        let mut groups: Vec<(String, Vec<PendingRow>)> = Vec::new();
+        for row in rows {
+            let collection = source.collection_name(&row.scope_id);
+            match groups.iter_mut().find(|(name, _)| *name == collection) {
+                Some((_, group)) => group.push(row),
+                None => groups.push((collection, vec![row])),
+            }
+        }
+
+        for (collection, rows) in groups {
+            let texts = rows.iter().map(|row| row.text.clone()).collect();
+            let embedder = match embedders.entry(collection) {
+                Entry::Occupied(entry) => Ok(entry.into_mut()),
+                Entry::Vacant(entry) => {
+                    let embedder = embedder_for(entry.key());
+                    embedder.map(|embedder| entry.insert(embedder))
+                }
+            };
+            let embedded = match embedder {
+                Ok(embedder) => embed_with_retry(&**embedder, texts, max_retries).await,
+                Err(e) => Err(e),
+            };
+            match embedded {
+                Ok(embeddings) => {
+                    for (row, embedding) in rows.into_iter().zip(embeddings) {
+                        match upsert_row(store, source, &row, embedding).await {
+                            Ok(()) => synced_rows.push(row),
+                            Err(e) => failures.push((row.id, e)),
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("[chroma_sync] ❌ 埋め込みの生成に失敗しました ({}): {}", source.source_type, e);
+                    failures.extend(rows.into_iter().map(|row| (row.id, e.clone())));
+                }
+            }
+        }
+
+        let conn = connection()?;
+        for row in &synced_rows {
+            match mark_synced(&conn, source, row) {
+                Ok(true) => report.synced += 1,
+                // 同期中に更新された行は未同期のまま残し、次回の実行で再同期する
+                Ok(false) => {}
+                Err(e) => eprintln!("[chroma_sync] ⚠️ 同期状態の更新に失敗しました ({} {}): {}", source.table, row.id, e),
+            }
+        }
+        for (id, error) in &failures {
+            if let Err(e) = mark_failed(&conn, source, id, error) {
+                eprintln!("[chroma_sync] ⚠️ 同期エラーの記録に失敗しました ({} {}): {}", source.table, id, e);
+            }
+        }
+        report.failed += failures.len();
+        drop(conn);
+
+        let message = failures.first().map(|(id, error)| format!("{}: {}", id, error));
+        on_progress(progress(report, message));
+    }
+}
+
+/// SQLiteに存在しない行のベクトルを削除
+async fn delete_orphans(
+    store: &dyn VectorStore,
+    source: &SyncSource,
+    report: &mut ChromaSyncSourceReport,
+    on_progress: &(dyn Fn(ChromaSyncProgress) + Send + Sync),
+) -> Result<(), String> {
+    let collections: Vec<String> = store.list_collections().await?
+        .into_iter()
+        .filter(|name| source.owns_collection(name))
+        .collect();
+
+    for collection in collections {
+        if cancel_requested() {
+            return Ok(());
+        }
+
+        let ids = store.list_ids(collection.clone()).await?;
+        let mut orphan_ids = Vec::new();
+        {
+            let conn = connection()?;
+            for chunk in ids.chunks(ORPHAN_CHECK_CHUNK) {
+                let placeholders = vec!["?"; chunk.len()].join(", ");
+                let mut stmt = conn.prepare(&format!(
+                    "SELECT {} FROM {} WHERE {} IN ({})",
+                    source.vector_id_column, source.table, source.vector_id_column, placeholders
+                )).map_err(|e| format!("孤立ベクトルの確認に失敗しました ({}): {}", source.table, e))?;
+                let existing: HashSet<String> = stmt
+                    .query_map(params_from_iter(chunk.iter()), |row| row.get(0))
+                    .and_then(|rows| rows.collect())
+                    .map_err(|e| format!("孤立ベクトルの確認に失敗しました ({}): {}", source.table, e))?;
+                orphan_ids.extend(chunk.iter().filter(|id| !existing.contains(*id)).cloned());
+            }
+        }
+
+        if orphan_ids.is_empty() {
+            continue;
+        }
+        let deleted = orphan_ids.len();
+        store.delete(collection.clone(), orphan_ids).await?;
+        report.orphans_deleted += deleted;
+        eprintln!("[chroma_sync] 🗑️ 孤立したベクトルを削除しました: {} ({}件)", collection, deleted);
+        on_progress(ChromaSyncProgress {
+            phase: "orphans".to_string(),
+            source_type: Some(source.source_type.to_string()),
+            total: 0,
+            processed: 0,
+            synced: 0,
+            failed: 0,
+            orphans_deleted: report.orphans_deleted,
+            message: Some(collection),
+        });
+    }
+    Ok(())
+}
+
+/// リコンサイラーを実行
+/// 同時に実行できるのは1つだけ（実行中の場合はエラー）
+pub async fn run_chroma_sync(
+    store: &dyn VectorStore,
+    embedder_for: &EmbedderResolver,
+    options: ChromaSyncOptions,
+    on_progress: &(dyn Fn(ChromaSyncProgress) + Send + Sync),
+) -> Result<ChromaSyncReport, String> {
+    let sources = selected_sources(&options.source_types)?;
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("ChromaDB同期は既に実行中です".to_string());
+    }
+    let _guard = RunningGuard;
+    CANCEL_REQUESTED.store(false, Ordering::SeqCst);
+
+    eprintln!("[chroma_sync] 🔄 ChromaDB同期を開始します");
+    let mut report = ChromaSyncReport::default();
+
+    for source in sources {
+        let mut source_report = ChromaSyncSourceReport {
+            source_type: source.source_type.to_string(),
+            ..Default::default()
+        };
+
+        if source.tracks_sync {
+            if let Err(e) = sync_source(store, embedder_for, source, &options, &mut source_report, on_progress).await {
+                eprintln!("[chroma_sync] ❌ {}の同期に失敗しました: {}", source.source_type, e);
+                on_progress(ChromaSyncProgress {
+                    phase: "sync".to_string(),
+                    source_type: Some(source.source_type.to_string()),
+                    total: source_report.pending,
+                    processed: source_report.synced + source_report.failed,
+                    synced: source_report.synced,
+                    failed: source_report.failed,
+                    orphans_deleted: 0,
+                    message: Some(e),
+                });
+            }
+        }
+
+        if options.delete_orphans.unwrap_or(false) && !cancel_requested() {
+            if let Err(e) = delete_orphans(store, source, &mut source_report, on_progress).await {
+                eprintln!("[chroma_sync] ⚠️ {}の孤立ベクトルの削除に失敗しました: {}", source.source_type, e);
+            }
+        }
+
+        report.synced += source_report.synced;
+        report.failed += source_report.failed;
+        report.orphans_deleted += source_report.orphans_deleted;
+        report.sources.push(source_report);
+
+        if cancel_requested() {
+            report.cancelled = true;
+            break;
+        }
+    }
+
+    on_progress(ChromaSyncProgress {
+        phase: if report.cancelled { "cancelled" } else { "done" }.to_string(),
+        source_type: None,
+        total: report.sources.iter().map(|s| s.pending).sum(),
+        processed: report.synced + report.failed,
+        synced: report.synced,
+        failed: report.failed,
+        orphans_deleted: report.orphans_deleted,
+        message: None,
+    });
+    eprintln!(
+        "[chroma_sync] ✅ ChromaDB同期が完了しました（同期: {}件, 失敗: {}件, 孤立ベクトル削除: {}件{}）",
+        report.synced,
+        report.failed,
+        report.orphans_deleted,
+        if report.cancelled { ", 中断" } else { "" }
+    );
+    Ok(report)
+}
@@ -16,6 +16,11 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
+use super::embedding_registry;
+use super::vector_search::{
+    self, EntitySearchFilter, MetadataFilter, RelationSearchFilter, TopicSearchFilter,
+    VectorBackend, VectorMatch, VectorRecord, VectorStore, VectorStoreFuture,
+};
 
 // ChromaDB Serverの管理
 pub struct ChromaDBServer {
@@ -938,6 +943,15 @@ fn get_chromadb_client() -> Result<Arc<Mutex<Option<Arc<ChromaClient>>>>, String
         .ok_or("ChromaDBクライアントが初期化されていません".to_string())
 }
 
+/// 論理コレクション名に対応する物理コレクション（埋め込みレジストリで再埋め込み済みの場合は新しいコレクション）を取得または作成
+async fn get_or_create_registered_collection(
+    client: Arc<ChromaClient>,
+    collection_name: &str,
+) -> Result<ChromaCollection, String> {
+    let physical_name = embedding_registry::physical_collection_name(collection_name);
+    get_or_create_collection_with_error_handling(client, &physical_name).await
+}
+
 /// コレクションを取得または作成（エラーハンドリング付き）
 async fn get_or_create_collection_with_error_handling(
     client: Arc<ChromaClient>,
@@ -1183,6 +1197,11 @@ pub async fn save_entity_embedding(
     combined_embedding: Vec<f32>,
     metadata: HashMap<String, Value>,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::save_entity_embedding(store.as_ref(), entity_id, organization_id, combined_embedding, metadata).await;
+    }
+    
     // クライアントが初期化されていない場合、自動的に初期化を試みる
     let client_initialized = {
         if let Some(client_lock) = CHROMADB_CLIENT.get() {
@@ -1322,8 +1341,9 @@ pub async fn save_entity_embedding(
             .clone()
     };
     
+    embedding_registry::check_upsert_dimension(&collection_name, combined_embedding.len()).await?;
     // コレクションを取得または作成
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // メタデータにエンティティIDと組織IDを追加
     let mut embedding_metadata = metadata;
@@ -1355,6 +1375,11 @@ pub async fn get_entity_embedding(
     entity_id: String,
     organization_id: String,
 ) -> Result<Option<HashMap<String, Value>>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::get_entity_embedding(store.as_ref(), entity_id, organization_id).await;
+    }
+    
     // クライアントが初期化されていない場合、自動的に初期化を試みる
     if CHROMADB_CLIENT.get().is_none() {
         eprintln!("⚠️ ChromaDBクライアントが初期化されていません。自動初期化を試みます...");
@@ -1450,7 +1475,7 @@ pub async fn get_entity_embedding(
     };
     
     // コレクションを取得
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // IDから直接取得
     let get_options = GetOptions {
@@ -1527,9 +1552,10 @@ async fn search_entities_in_collection(
     collection_name: &str,
     query_embedding: Vec<f32>,
     limit: usize,
+    where_metadata: Option<Value>,
 ) -> Result<Vec<(String, f32)>, String> {
     // コレクションを取得
-    let collection = get_or_create_collection_with_error_handling(client, collection_name).await?;
+    let collection = get_or_create_registered_collection(client, collection_name).await?;
     
     // コレクションの件数を取得（デバッグ用）
     let collection_count = match collection.count().await {
@@ -1555,7 +1581,7 @@ async fn search_entities_in_collection(
     let query_options = QueryOptions {
         query_texts: None,
         query_embeddings: Some(vec![query_embedding]),
-        where_metadata: None,
+        where_metadata,
         where_document: None,
         n_results: Some(limit),
         include: Some(vec!["distances"]),
@@ -1597,7 +1623,19 @@ pub async fn find_similar_entities(
     query_embedding: Vec<f32>,
     limit: usize,
     organization_id: Option<String>,
+    filter: Option<EntitySearchFilter>,
 ) -> Result<Vec<(String, f32)>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::find_similar_entities(store.as_ref(), query_embedding, limit, organization_id, filter).await;
+    }
+    
+    // メタデータフィルタをChromaDBのwhere句に変換（検索側で絞り込むことでlimit件を正しく返す）
+    let where_metadata = match filter {
+        Some(filter) => filter.to_metadata_filter()?.to_chroma_where(),
+        None => None,
+    };
+    
     eprintln!("[find_similar_entities] 検索開始: organizationId={:?}, limit={}, embedding_dim={}", 
         organization_id, limit, query_embedding.len());
     
@@ -1649,12 +1687,14 @@ pub async fn find_similar_entities(
         } else {
             format!("entities_{}", org_id)
         };
+        embedding_registry::check_query_dimension(&collection_name, query_embedding.len()).await?;
         eprintln!("[find_similar_entities] 検索タスクを作成: 組織ID={}, コレクション名={}", org_id, collection_name);
         let client_clone = client.clone();
         let embedding_clone = query_embedding.clone();
+        let where_clone = where_metadata.clone();
         
         let task = tokio::spawn(async move {
-            search_entities_in_collection(client_clone, &collection_name, embedding_clone, limit).await
+            search_entities_in_collection(client_clone, &collection_name, embedding_clone, limit, where_clone).await
         });
         search_tasks.push((org_id, task));
     }
@@ -1693,6 +1733,11 @@ pub async fn find_similar_entities(
 
 /// エンティティコレクションの件数を取得
 pub async fn count_entities(organization_id: Option<String>) -> Result<usize, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::count_entities(store.as_ref(), organization_id).await;
+    }
+    
     let org_id = match organization_id {
         Some(id) if !id.is_empty() => id,
         _ => return Err("organizationIdが指定されていません".to_string()),
@@ -1708,7 +1753,7 @@ pub async fn count_entities(organization_id: Option<String>) -> Result<usize, St
             .clone()
     };
     
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     let count = collection.count().await
         .map_err(|e| format!("コレクションの件数取得に失敗しました: {}", e))?;
@@ -1723,6 +1768,11 @@ pub async fn save_relation_embedding(
     combined_embedding: Vec<f32>,
     metadata: HashMap<String, Value>,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::save_relation_embedding(store.as_ref(), relation_id, organization_id, combined_embedding, metadata).await;
+    }
+    
     // クライアントが初期化されていない場合、自動的に初期化を試みる
     let client_initialized = {
         if let Some(client_lock) = CHROMADB_CLIENT.get() {
@@ -1860,9 +1910,10 @@ pub async fn save_relation_embedding(
             .ok_or("ChromaDBクライアントが初期化されていません")?
             .clone()
     };
-    
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
-    
+
+    embedding_registry::check_upsert_dimension(&collection_name, combined_embedding.len()).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
+
     let mut embedding_metadata = metadata;
     embedding_metadata.insert("relationId".to_string(), Value::String(relation_id.clone()));
     embedding_metadata.insert("organizationId".to_string(), Value::String(organization_id.clone()));
@@ -1891,6 +1942,11 @@ pub async fn get_relation_embedding(
     relation_id: String,
     organization_id: String,
 ) -> Result<Option<HashMap<String, Value>>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::get_relation_embedding(store.as_ref(), relation_id, organization_id).await;
+    }
+    
     // クライアントが初期化されていない場合、自動的に初期化を試みる
     if CHROMADB_CLIENT.get().is_none() {
         eprintln!("⚠️ ChromaDBクライアントが初期化されていません。自動初期化を試みます...");
@@ -1986,7 +2042,7 @@ pub async fn get_relation_embedding(
     };
     
     // コレクションを取得
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // IDから直接取得
     let get_options = GetOptions {
@@ -2048,13 +2104,14 @@ async fn search_relations_in_collection(
     collection_name: &str,
     query_embedding: Vec<f32>,
     limit: usize,
+    where_metadata: Option<Value>,
 ) -> Result<Vec<(String, f32)>, String> {
-    let collection = get_or_create_collection_with_error_handling(client, collection_name).await?;
+    let collection = get_or_create_registered_collection(client, collection_name).await?;
     
     let query_options = QueryOptions {
         query_texts: None,
         query_embeddings: Some(vec![query_embedding]),
-        where_metadata: None,
+        where_metadata,
         where_document: None,
         n_results: Some(limit),
         include: Some(vec!["distances"]),
@@ -2094,7 +2151,19 @@ pub async fn find_similar_relations(
     query_embedding: Vec<f32>,
     limit: usize,
     organization_id: Option<String>,
+    filter: Option<RelationSearchFilter>,
 ) -> Result<Vec<(String, f32)>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::find_similar_relations(store.as_ref(), query_embedding, limit, organization_id, filter).await;
+    }
+    
+    // メタデータフィルタをChromaDBのwhere句に変換（検索側で絞り込むことでlimit件を正しく返す）
+    let where_metadata = match filter {
+        Some(filter) => filter.to_metadata_filter()?.to_chroma_where(),
+        None => None,
+    };
+    
     eprintln!("[find_similar_relations] 検索開始: organizationId={:?}, limit={}, embedding_dim={}", 
         organization_id, limit, query_embedding.len());
     
@@ -2142,11 +2211,13 @@ pub async fn find_similar_relations(
         } else {
             format!("relations_{}", org_id)
         };
+        embedding_registry::check_query_dimension(&collection_name, query_embedding.len()).await?;
         let client_clone = client.clone();
         let embedding_clone = query_embedding.clone();
+        let where_clone = where_metadata.clone();
         
         let task = tokio::spawn(async move {
-            search_relations_in_collection(client_clone, &collection_name, embedding_clone, limit).await
+            search_relations_in_collection(client_clone, &collection_name, embedding_clone, limit, where_clone).await
         });
         search_tasks.push((org_id, task));
     }
@@ -2184,6 +2255,11 @@ pub async fn save_topic_embedding(
     metadata: HashMap<String, Value>,
     regulation_id: Option<String>,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::save_topic_embedding(store.as_ref(), topic_id, meeting_note_id, organization_id, combined_embedding, metadata, regulation_id).await;
+    }
+    
     let _parent_id = meeting_note_id.as_ref().or(regulation_id.as_ref());
     eprintln!("[save_topic_embedding] 開始: topicId={}, meetingNoteId={:?}, regulationId={:?}, organizationId={}, embedding_dim={}", 
         topic_id, meeting_note_id, regulation_id, organization_id, combined_embedding.len());
@@ -2302,8 +2378,9 @@ pub async fn save_topic_embedding(
             .clone()
     };
     
+    embedding_registry::check_upsert_dimension(&collection_name, combined_embedding.len()).await?;
     eprintln!("[save_topic_embedding] コレクションを取得/作成中...");
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     eprintln!("[save_topic_embedding] コレクションを取得/作成しました");
     
     let mut embedding_metadata = metadata;
@@ -2347,6 +2424,11 @@ pub async fn save_topic_embedding(
         embedding_metadata.insert("regulationId".to_string(), Value::String(regulation_id));
     }
     
+    // topicDateの範囲検索用に数値のタイムスタンプを保存（ChromaDBの$gte/$lteは数値のみ対応）
+    if let Some(seconds) = vector_search::topic_date_timestamp(embedding_metadata.get("topicDate")) {
+        embedding_metadata.insert(vector_search::TOPIC_DATE_TIMESTAMP_KEY.to_string(), Value::from(seconds));
+    }
+    
     // メタデータをChromaDBの形式に変換（serde_json::Mapを使用）
     let mut chroma_metadata = serde_json::Map::new();
     for (k, v) in embedding_metadata {
@@ -2390,13 +2472,14 @@ async fn search_topics_in_collection(
     collection_name: &str,
     query_embedding: Vec<f32>,
     limit: usize,
+    where_metadata: Option<Value>,
 ) -> Result<Vec<TopicSearchResult>, String> {
-    let collection = get_or_create_collection_with_error_handling(client, collection_name).await?;
+    let collection = get_or_create_registered_collection(client, collection_name).await?;
     
     let query_options = QueryOptions {
         query_texts: None,
         query_embeddings: Some(vec![query_embedding]),
-        where_metadata: None,
+        where_metadata,
         where_document: None,
         n_results: Some(limit),
         include: Some(vec!["distances", "metadatas"]),
@@ -2493,6 +2576,11 @@ pub async fn get_topic_embedding(
     topic_id: String,
     organization_id: String,
 ) -> Result<Option<HashMap<String, Value>>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::get_topic_embedding(store.as_ref(), topic_id, organization_id).await;
+    }
+    
     // クライアントが初期化されていない場合、自動的に初期化を試みる
     if CHROMADB_CLIENT.get().is_none() {
         eprintln!("⚠️ ChromaDBクライアントが初期化されていません。自動初期化を試みます...");
@@ -2581,7 +2669,7 @@ pub async fn get_topic_embedding(
     };
     
     // コレクションを取得
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // IDから直接取得
     let get_options = GetOptions {
@@ -2642,7 +2730,19 @@ pub async fn find_similar_topics(
     query_embedding: Vec<f32>,
     limit: usize,
     organization_id: Option<String>,
+    filter: Option<TopicSearchFilter>,
 ) -> Result<Vec<TopicSearchResult>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::find_similar_topics(store.as_ref(), query_embedding, limit, organization_id, filter).await;
+    }
+    
+    // メタデータフィルタをChromaDBのwhere句に変換（検索側で絞り込むことでlimit件を正しく返す）
+    let where_metadata = match filter {
+        Some(filter) => filter.to_metadata_filter()?.to_chroma_where(),
+        None => None,
+    };
+    
     eprintln!("[find_similar_topics] 検索開始: organizationId={:?}, limit={}, embedding_dim={}", 
         organization_id, limit, query_embedding.len());
     
@@ -2690,11 +2790,13 @@ pub async fn find_similar_topics(
         } else {
             format!("topics_{}", org_id)
         };
+        embedding_registry::check_query_dimension(&collection_name, query_embedding.len()).await?;
         let client_clone = client.clone();
         let embedding_clone = query_embedding.clone();
+        let where_clone = where_metadata.clone();
         
         let task = tokio::spawn(async move {
-            search_topics_in_collection(client_clone, &collection_name, embedding_clone, limit).await
+            search_topics_in_collection(client_clone, &collection_name, embedding_clone, limit, where_clone).await
         });
         search_tasks.push((org_id, task));
     }
@@ -2729,6 +2831,11 @@ pub async fn save_design_doc_embedding(
     combined_embedding: Vec<f32>,
     metadata: HashMap<String, Value>,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::save_design_doc_embedding(store.as_ref(), section_id, combined_embedding, metadata).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     let collection_name = "design_docs";  // 組織ごとではなく、全体で1つのコレクション
     
@@ -2740,8 +2847,9 @@ pub async fn save_design_doc_embedding(
             .clone()
     };
     
+    embedding_registry::check_upsert_dimension(&collection_name, combined_embedding.len()).await?;
     // コレクションを取得または作成
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // メタデータにセクションIDを追加
     let mut embedding_metadata = metadata;
@@ -2803,6 +2911,11 @@ pub async fn find_similar_design_docs(
     section_id: Option<String>,
     tags: Option<Vec<String>>,
 ) -> Result<Vec<(String, f32)>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::find_similar_design_docs(store.as_ref(), query_embedding, limit, section_id, tags).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     let collection_name = "design_docs";
     
@@ -2814,7 +2927,8 @@ pub async fn find_similar_design_docs(
             .clone()
     };
     
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    embedding_registry::check_query_dimension(collection_name, query_embedding.len()).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // メタデータフィルターを構築
     let mut where_metadata: Option<serde_json::Map<String, Value>> = None;
@@ -2882,6 +2996,11 @@ pub async fn find_similar_design_docs(
 pub async fn get_design_doc_metadata(
     section_id: String,
 ) -> Result<HashMap<String, Value>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::get_design_doc_metadata(store.as_ref(), section_id).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     let collection_name = "design_docs";
     
@@ -2893,7 +3012,7 @@ pub async fn get_design_doc_metadata(
             .clone()
     };
     
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // getメソッドを使用して特定のIDのメタデータを取得
     // ChromaDBのドキュメントIDはsection_idそのもの
@@ -2932,6 +3051,11 @@ pub async fn get_design_doc_metadata(
 
 /// システム設計ドキュメントコレクション内の全セクションIDを取得（デバッグ用）
 pub async fn list_design_doc_section_ids() -> Result<Vec<String>, String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::list_design_doc_section_ids(store.as_ref()).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     let collection_name = "design_docs";
     
@@ -2943,7 +3067,7 @@ pub async fn list_design_doc_section_ids() -> Result<Vec<String>, String> {
             .clone()
     };
     
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // 全データを取得（getメソッドを使用）
     // idsを空のベクトルにすると全IDを取得できる
@@ -2974,6 +3098,11 @@ pub async fn delete_topic_embedding(
     topic_id: String,
     organization_id: String,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::delete_topic_embedding(store.as_ref(), topic_id, organization_id).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     // organizationIdが空文字列の場合は"topics_all"を使用（ChromaDBの命名規則に準拠）
     let collection_name = if organization_id.is_empty() {
@@ -2990,7 +3119,7 @@ pub async fn delete_topic_embedding(
             .clone()
     };
     
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // トピックIDで削除
     // ChromaDBのIDはtopicIdそのもの（save_topic_embeddingでtopic_idをそのままIDとして使用）
@@ -3009,6 +3138,11 @@ pub async fn delete_entity_embedding(
     entity_id: String,
     organization_id: String,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::delete_entity_embedding(store.as_ref(), entity_id, organization_id).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     // organizationIdが空文字列の場合は"entities_all"を使用（ChromaDBの命名規則に準拠）
     let collection_name = if organization_id.is_empty() {
@@ -3025,7 +3159,7 @@ pub async fn delete_entity_embedding(
             .clone()
     };
     
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // エンティティIDで削除
     collection.delete(
@@ -3043,6 +3177,11 @@ pub async fn delete_relation_embedding(
     relation_id: String,
     organization_id: String,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::delete_relation_embedding(store.as_ref(), relation_id, organization_id).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     // organizationIdが空文字列の場合は"relations_all"を使用（ChromaDBの命名規則に準拠）
     let collection_name = if organization_id.is_empty() {
@@ -3059,7 +3198,7 @@ pub async fn delete_relation_embedding(
             .clone()
     };
     
-    let collection = get_or_create_collection_with_error_handling(client, &collection_name).await?;
+    let collection = get_or_create_registered_collection(client, &collection_name).await?;
     
     // リレーションIDで削除
     collection.delete(
@@ -3076,6 +3215,11 @@ pub async fn delete_relation_embedding(
 pub async fn delete_organization_collections(
     organization_id: String,
 ) -> Result<(), String> {
+    // HNSWバックエンドが選択されている場合はChromaDBサーバーを使わない
+    if let Some(store) = vector_search::native_vector_store()? {
+        return vector_search::delete_organization_collections(store.as_ref(), organization_id).await;
+    }
+    
     let client_lock = get_chromadb_client()?;
     
     // MutexGuardをdropしてから.awaitする必要がある
@@ -3103,9 +3247,11 @@ pub async fn delete_organization_collections(
     
     // 各コレクションを削除
     for collection_name in collection_names {
-        match client.delete_collection(&collection_name).await {
+        let physical_name = embedding_registry::physical_collection_name(&collection_name);
+        match client.delete_collection(&physical_name).await {
             Ok(_) => {
-                eprintln!("✅ [delete_organization_collections] コレクション削除成功: {}", collection_name);
+                embedding_registry::remove_collection(&collection_name);
+                eprintln!("✅ [delete_organization_collections] コレクション削除成功: {}", physical_name);
             }
             Err(e) => {
                 let error_msg = format!("{}", e);
@@ -3121,3 +3267,175 @@ pub async fn delete_organization_collections(
     
     Ok(())
 }
+
+/// ChromaDBクライアントを取得（未初期化の場合はエラー）
+async fn chromadb_client() -> Result<Arc<ChromaClient>, String> {
+    let client_lock = get_chromadb_client()?;
+    let client_guard = client_lock.lock().await;
+    client_guard.as_ref()
+        .cloned()
+        .ok_or("ChromaDBクライアントが初期化されていません".to_string())
+}
+
+/// ChromaDBバックエンド（VectorStoreトレイトの実装）
+pub struct ChromaVectorStore;
+
+impl VectorStore for ChromaVectorStore {
+    fn backend(&self) -> VectorBackend {
+        VectorBackend::ChromaDB
+    }
+
+    fn upsert(
+        &self,
+        collection: String,
+        id: String,
+        embedding: Vec<f32>,
+        metadata: serde_json::Map<String, Value>,
+    ) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            let collection = get_or_create_collection_with_error_handling(client, &collection).await?;
+            let entries = CollectionEntries {
+                ids: vec![id.as_str()],
+                embeddings: Some(vec![embedding]),
+                metadatas: Some(vec![metadata]),
+                documents: None,
+            };
+            collection.upsert(entries, None).await
+                .map_err(|e| format!("埋め込みの保存に失敗しました: {}", e))?;
+            Ok(())
+        })
+    }
+
+    fn get(&self, collection: String, id: String) -> VectorStoreFuture<'_, Option<VectorRecord>> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            let collection = get_or_create_collection_with_error_handling(client, &collection).await?;
+            let get_options = GetOptions {
+                ids: vec![id.clone()],
+                where_metadata: None,
+                where_document: None,
+                limit: Some(1),
+                offset: None,
+                include: Some(vec!["embeddings".to_string(), "metadatas".to_string()]),
+            };
+            let results = collection.get(get_options).await
+                .map_err(|e| format!("埋め込みの取得に失敗しました: {}", e))?;
+            if results.ids.is_empty() {
+                return Ok(None);
+            }
+            let embedding = results.embeddings
+                .and_then(|e| e.into_iter().next())
+                .flatten()
+                .unwrap_or_default();
+            let metadata = results.metadatas
+                .and_then(|m| m.into_iter().next())
+                .flatten()
+                .unwrap_or_default();
+            Ok(Some(VectorRecord { id, embedding, metadata }))
+        })
+    }
+
+    fn query(
+        &self,
+        collection: String,
+        embedding: Vec<f32>,
+        limit: usize,
+        filter: Option<MetadataFilter>,
+    ) -> VectorStoreFuture<'_, Vec<VectorMatch>> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            let collection = get_or_create_collection_with_error_handling(client, &collection).await?;
+            let query_options = QueryOptions {
+                query_texts: None,
+                query_embeddings: Some(vec![embedding]),
+                where_metadata: filter.and_then(|f| f.to_chroma_where()),
+                where_document: None,
+                n_results: Some(limit),
+                include: Some(vec!["distances", "metadatas"]),
+            };
+            let results = collection.query(query_options, None).await
+                .map_err(|e| format!("類似検索に失敗しました: {}", e))?;
+
+            let ids = results.ids.into_iter().next().unwrap_or_default();
+            let distances = results.distances
+                .and_then(|d| d.into_iter().next())
+                .unwrap_or_default();
+            let metadatas = results.metadatas
+                .and_then(|m| m.into_iter().next())
+                .unwrap_or_default();
+            Ok(ids.into_iter().enumerate().filter_map(|(i, id)| {
+                let distance = *distances.get(i)?;
+                Some(VectorMatch {
+                    id,
+                    similarity: (1.0_f32 - distance).max(0.0_f32),
+                    metadata: metadatas.get(i).cloned().flatten().unwrap_or_default(),
+                })
+            }).collect())
+        })
+    }
+
+    fn delete(&self, collection: String, ids: Vec<String>) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            let collection = get_or_create_collection_with_error_handling(client, &collection).await?;
+            collection.delete(Some(ids.iter().map(|id| id.as_str()).collect()), None, None).await
+                .map_err(|e| format!("埋め込みの削除に失敗しました: {}", e))?;
+            Ok(())
+        })
+    }
+
+    fn count(&self, collection: String) -> VectorStoreFuture<'_, usize> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            let collection = get_or_create_collection_with_error_handling(client, &collection).await?;
+            collection.count().await
+                .map_err(|e| format!("コレクションの件数取得に失敗しました: {}", e))
+        })
+    }
+
+    fn list_ids(&self, collection: String) -> VectorStoreFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            let collection = get_or_create_collection_with_error_handling(client, &collection).await?;
+            let get_options = GetOptions {
+                ids: vec![],
+                where_metadata: None,
+                limit: None,
+                offset: None,
+                where_document: None,
+                include: None,
+            };
+            let results = collection.get(get_options).await
+                .map_err(|e| format!("ID一覧の取得に失敗しました: {}", e))?;
+            Ok(results.ids)
+        })
+    }
+
+    fn list_collections(&self) -> VectorStoreFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            let collections = client.list_collections().await
+                .map_err(|e| format!("コレクション一覧の取得に失敗しました: {}", e))?;
+            Ok(collections.iter().map(|c| c.name().to_string()).collect())
+        })
+    }
+
+    fn delete_collection(&self, collection: String) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            let client = chromadb_client().await?;
+            match client.delete_collection(&collection).await {
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    let error_msg = format!("{}", e);
+                    // コレクションが存在しない場合は削除済みとみなす
+                    if error_msg.contains("not found") || error_msg.contains("does not exist") {
+                        Ok(())
+                    } else {
+                        Err(format!("コレクション {} の削除に失敗しました: {}", collection, error_msg))
+                    }
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,593 @@
+/**
+ * コレクションクエリDSL
+ * store::get_collection / query_get / HTTP APIで共通に使う構造化クエリを定義し、
+ * テーブルの実カラムで検証したうえでパラメータ付きのSELECT文を組み立てる
+ *
+ * 形式:
+ * {
+ *   "where": { "and": [
+ *       { "field": "organizationId", "op": "==", "value": "org-1" },
+ *       { "or": [
+ *           { "field": "type", "op": "in", "value": ["a", "b"] },
+ *           { "field": "parentId", "op": "isNull" }
+ *       ] },
+ *       { "field": "createdAt", "op": ">=", "value": "2024-04-01", "type": "date" }
+ *   ] },
+ *   "orderBy": [{ "field": "position", "direction": "asc" }, "name"],
+ *   "limit": 50,
+ *   "offset": 0,               // offsetまたはcursorのどちらか
+ *   "cursor": "<前ページ最後のid>"
+ * }
+ */
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{Connection, OptionalExtension, Result as SqlResult};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// 旧形式（{ field: value } / field・operator・value）で予約されているキー
+const LEGACY_RESERVED_KEYS: &[&str] = &["orderBy", "orderDirection", "field", "operator", "value"];
+// 構造化クエリのトップレベルキー
+const QUERY_KEYS: &[&str] = &["where", "orderBy", "orderDirection", "limit", "offset", "cursor"];
+
+/// 比較演算子
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryOperator {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    In,
+    NotIn,
+    Like,
+    NotLike,
+    IsNull,
+    IsNotNull,
+}
+
+impl QueryOperator {
+    fn parse(op: &str) -> Option<Self> {
+        Some(match op {
+            "==" | "=" | "eq" => QueryOperator::Eq,
+            "!=" | "<>" | "ne" => QueryOperator::Ne,
+            "<" | "lt" => QueryOperator::Lt,
+            "<=" | "lte" => QueryOperator::Lte,
+            ">" | "gt" => QueryOperator::Gt,
+            ">=" | "gte" => QueryOperator::Gte,
+            "in" => QueryOperator::In,
+            "notIn" | "not-in" | "not in" => QueryOperator::NotIn,
+            "like" => QueryOperator::Like,
+            "notLike" | "not like" => QueryOperator::NotLike,
+            "isNull" | "is null" => QueryOperator::IsNull,
+            "isNotNull" | "is not null" => QueryOperator::IsNotNull,
+            _ => return None,
+        })
+    }
+
+    fn comparison_sql(self) -> &'static str {
+        match self {
+            QueryOperator::Eq => "=",
+            QueryOperator::Ne => "!=",
+            QueryOperator::Lt => "<",
+            QueryOperator::Lte => "<=",
+            QueryOperator::Gt => ">",
+            QueryOperator::Gte => ">=",
+            _ => "=",
+        }
+    }
+}
+
+/// 値の比較方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryValueType {
+    /// JSONの型から判断（数値は数値比較、それ以外はそのまま）
+    Auto,
+    String,
+    Number,
+    /// UnixタイムスタンプとISO 8601文字列のどちらで保存されていても秒単位で比較
+    Date,
+}
+
+/// 単一の条件
+#[derive(Debug, Clone)]
+pub struct QueryCondition {
+    pub field: String,
+    pub op: QueryOperator,
+    pub value: Value,
+    pub value_type: QueryValueType,
+}
+
+/// 条件ツリー（AND / ORのグループを入れ子にできる）
+#[derive(Debug, Clone)]
+pub enum QueryFilter {
+    And(Vec<QueryFilter>),
+    Or(Vec<QueryFilter>),
+    Condition(QueryCondition),
+}
+
+/// 並び順
+#[derive(Debug, Clone)]
+pub struct QueryOrder {
+    pub field: String,
+    pub descending: bool,
+}
+
+/// 構造化クエリ
+#[derive(Debug, Clone, Default)]
+pub struct CollectionQuery {
+    pub filter: Option<QueryFilter>,
+    pub order_by: Vec<QueryOrder>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// 前ページ最後のドキュメントID（このドキュメントより後ろから取得する）
+    pub cursor: Option<String>,
+}
+
+fn query_error(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+        Some(message),
+    )
+}
+
+impl CollectionQuery {
+    /// query_get / get_collectionの条件マップから生成
+    /// 構造化クエリのキー（where / limit / offset / cursor / 配列のorderBy）がない場合は旧形式として解釈する
+    pub fn from_conditions(conditions: &HashMap<String, Value>) -> SqlResult<Self> {
+        let is_structured = conditions.contains_key("where")
+            || conditions.contains_key("limit")
+            || conditions.contains_key("offset")
+            || conditions.contains_key("cursor")
+            || conditions.get("orderBy").map(|v| !v.is_string()).unwrap_or(false);
+
+        if is_structured {
+            Self::parse_structured(conditions)
+        } else {
+            Ok(Self::parse_legacy(conditions))
+        }
+    }
+
+    /// JSON値から生成（HTTP APIのリクエストボディなど）
+    pub fn from_value(value: &Value) -> SqlResult<Self> {
+        match value {
+            Value::Null => Ok(Self::default()),
+            Value::Object(map) => {
+                let conditions: HashMap<String, Value> = map.clone().into_iter().collect();
+                Self::from_conditions(&conditions)
+            }
+            _ => Err(query_error("クエリはオブジェクトで指定してください".to_string())),
+        }
+    }
+
+    fn parse_structured(conditions: &HashMap<String, Value>) -> SqlResult<Self> {
+        if let Some(unknown) = conditions.keys().find(|k| !QUERY_KEYS.contains(&k.as_str())) {
+            return Err(query_error(format!("不明なクエリキー: {}", unknown)));
+        }
+
+        let filter = match conditions.get("where") {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(parse_filter(value)?),
+        };
+
+        let order_by = match conditions.get("orderBy") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items.iter().map(parse_order).collect::<SqlResult<Vec<_>>>()?,
+            Some(Value::String(field)) => vec![QueryOrder {
+                field: field.clone(),
+                descending: conditions.get("orderDirection").and_then(|v| v.as_str()) == Some("desc"),
+            }],
+            Some(other) => vec![parse_order(other)?],
+        };
+
+        let limit = parse_non_negative(conditions.get("limit"), "limit")?;
+        let offset = parse_non_negative(conditions.get("offset"), "offset")?;
+        let cursor = match conditions.get("cursor") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(_) => return Err(query_error("cursorはドキュメントIDの文字列で指定してください".to_string())),
+        };
+        if cursor.is_some() && offset.is_some() {
+            return Err(query_error("offsetとcursorは同時に指定できません".to_string()));
+        }
+
+        Ok(CollectionQuery { filter, order_by, limit, offset, cursor })
+    }
+
+    /// 旧形式: { field: value } の等価条件（AND）、またはfield / operator / valueの単一条件
+    fn parse_legacy(conditions: &HashMap<String, Value>) -> Self {
+        let mut filters: Vec<QueryFilter> = conditions.iter()
+            .filter(|(field, _)| !LEGACY_RESERVED_KEYS.contains(&field.as_str()))
+            .map(|(field, value)| QueryFilter::Condition(QueryCondition {
+                field: field.clone(),
+                op: if value.is_null() { QueryOperator::IsNull } else { QueryOperator::Eq },
+                value: value.clone(),
+                value_type: QueryValueType::Auto,
+            }))
+            .collect();
+
+        if filters.is_empty() {
+            let field = conditions.get("field").and_then(|v| v.as_str());
+            let operator = conditions.get("operator").and_then(|v| v.as_str());
+            if let (Some(field), Some(operator), Some(value)) = (field, operator, conditions.get("value")) {
+                // 旧形式では未知の演算子は等価比較として扱っていた
+                let op = match QueryOperator::parse(operator) {
+                    Some(op @ (QueryOperator::Eq | QueryOperator::Ne | QueryOperator::Lt
+                        | QueryOperator::Lte | QueryOperator::Gt | QueryOperator::Gte)) => op,
+                    _ => QueryOperator::Eq,
+                };
+                filters.push(QueryFilter::Condition(QueryCondition {
+                    field: field.to_string(),
+                    op: if value.is_null() && op == QueryOperator::Eq { QueryOperator::IsNull } else { op },
+                    value: value.clone(),
+                    value_type: QueryValueType::Auto,
+                }));
+            }
+        }
+
+        let order_by = conditions.get("orderBy")
+            .and_then(|v| v.as_str())
+            .map(|field| vec![QueryOrder {
+                field: field.to_string(),
+                descending: conditions.get("orderDirection").and_then(|v| v.as_str()) == Some("desc"),
+            }])
+            .unwrap_or_default();
+
+        CollectionQuery {
+            filter: if filters.is_empty() { None } else { Some(QueryFilter::And(filters)) },
+            order_by,
+            ..Default::default()
+        }
+    }
+}
+
+fn parse_non_negative(value: Option<&Value>, key: &str) -> SqlResult<Option<i64>> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => match v.as_i64() {
+            Some(n) if n >= 0 => Ok(Some(n)),
+            _ => Err(query_error(format!("{}は0以上の整数で指定してください", key))),
+        },
+    }
+}
+
+fn parse_filter(value: &Value) -> SqlResult<QueryFilter> {
+    match value {
+        // 配列はANDとして扱う
+        Value::Array(items) => Ok(QueryFilter::And(items.iter().map(parse_filter).collect::<SqlResult<Vec<_>>>()?)),
+        Value::Object(map) => {
+            if let Some(items) = map.get("and") {
+                let items = items.as_array()
+                    .ok_or_else(|| query_error("andには条件の配列を指定してください".to_string()))?;
+                return Ok(QueryFilter::And(items.iter().map(parse_filter).collect::<SqlResult<Vec<_>>>()?));
+            }
+            if let Some(items) = map.get("or") {
+                let items = items.as_array()
+                    .ok_or_else(|| query_error("orには条件の配列を指定してください".to_string()))?;
+                return Ok(QueryFilter::Or(items.iter().map(parse_filter).collect::<SqlResult<Vec<_>>>()?));
+            }
+
+            let field = map.get("field")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| query_error(format!("条件にfieldがありません: {}", value)))?;
+            let op_str = map.get("op")
+                .or_else(|| map.get("operator"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("==");
+            let op = QueryOperator::parse(op_str)
+                .ok_or_else(|| query_error(format!("未対応の演算子: {}", op_str)))?;
+            let value_type = match map.get("type").and_then(|v| v.as_str()) {
+                None => QueryValueType::Auto,
+                Some("string") => QueryValueType::String,
+                Some("number") => QueryValueType::Number,
+                Some("date") => QueryValueType::Date,
+                Some(other) => return Err(query_error(format!("未対応の型: {}", other))),
+            };
+            let value = map.get("value").cloned().unwrap_or(Value::Null);
+
+            match op {
+                QueryOperator::In | QueryOperator::NotIn if !value.is_array() => {
+                    return Err(query_error(format!("{}の値は配列で指定してください: {}", op_str, field)));
+                }
+                QueryOperator::Like | QueryOperator::NotLike if !value.is_string() => {
+                    return Err(query_error(format!("{}の値は文字列で指定してください: {}", op_str, field)));
+                }
+                _ => {}
+            }
+
+            Ok(QueryFilter::Condition(QueryCondition {
+                field: field.to_string(),
+                // 値がnullの等価比較はIS NULLとして扱う
+                op: match (op, value.is_null()) {
+                    (QueryOperator::Eq, true) => QueryOperator::IsNull,
+                    (QueryOperator::Ne, true) => QueryOperator::IsNotNull,
+                    _ => op,
+                },
+                value,
+                value_type,
+            }))
+        }
+        _ => Err(query_error(format!("条件はオブジェクトまたは配列で指定してください: {}", value))),
+    }
+}
+
+fn parse_order(value: &Value) -> SqlResult<QueryOrder> {
+    match value {
+        Value::String(field) => Ok(QueryOrder { field: field.clone(), descending: false }),
+        Value::Object(map) => {
+            let field = map.get("field")
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| query_error(format!("orderByにfieldがありません: {}", value)))?;
+            let descending = match map.get("direction").and_then(|v| v.as_str()) {
+                None | Some("asc") => false,
+                Some("desc") => true,
+                Some(other) => return Err(query_error(format!("未対応の並び順: {}", other))),
+            };
+            Ok(QueryOrder { field: field.to_string(), descending })
+        }
+        _ => Err(query_error(format!("orderByの形式が不正です: {}", value))),
+    }
+}
+
+// ========== SQL生成 ==========
+
+fn quote_column(field: &str) -> String {
+    format!("\"{}\"", field)
+}
+
+/// UnixタイムスタンプでもISO 8601文字列でも秒に揃えるSQL式
+pub(crate) fn date_column_sql(column: &str) -> String {
+    format!(
+        "(CASE WHEN typeof({c}) IN ('integer', 'real') THEN {c} \
+         WHEN {c} GLOB '[0-9]*' AND {c} NOT GLOB '*[^0-9]*' THEN CAST({c} AS INTEGER) \
+         ELSE CAST(strftime('%s', {c}) AS INTEGER) END)",
+        c = column
+    )
+}
+
+/// 日付の値をUnix秒に変換（数値、数字のみの文字列、RFC 3339、YYYY-MM-DD、{ seconds }に対応）
+pub(crate) fn parse_date_value(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        Value::String(s) => {
+            let s = s.trim();
+            if let Ok(secs) = s.parse::<i64>() {
+                return Some(secs);
+            }
+            if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+                return Some(dt.timestamp());
+            }
+            for format in ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+                if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+                    return Some(dt.and_utc().timestamp());
+                }
+            }
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc().timestamp())
+        }
+        Value::Object(map) => map.get("seconds").and_then(|v| v.as_i64()),
+        _ => None,
+    }
+}
+
+fn number_to_sql(value: &Value) -> Option<SqlValue> {
+    match value {
+        Value::Number(n) => n.as_i64().map(SqlValue::Integer).or_else(|| n.as_f64().map(SqlValue::Real)),
+        Value::String(s) => s.trim().parse::<i64>().map(SqlValue::Integer).ok()
+            .or_else(|| s.trim().parse::<f64>().ok().map(SqlValue::Real)),
+        _ => None,
+    }
+}
+
+/// JSON値をバインド用の値に変換（型を保ったまま渡すことで数値が文字列比較されないようにする）
+fn json_to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Text(b.to_string()),
+        Value::Number(_) => number_to_sql(value).unwrap_or(SqlValue::Null),
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(_) | Value::Object(_) => SqlValue::Text(value.to_string()),
+    }
+}
+
+struct SqlBuilder<'a> {
+    columns: &'a [String],
+    params: Vec<SqlValue>,
+}
+
+impl SqlBuilder<'_> {
+    fn check_column(&self, field: &str) -> SqlResult<()> {
+        if self.columns.iter().any(|c| c == field) {
+            Ok(())
+        } else {
+            Err(query_error(format!("無効なフィールド名: {}", field)))
+        }
+    }
+
+    fn push(&mut self, value: SqlValue) -> &'static str {
+        self.params.push(value);
+        "?"
+    }
+
+    fn filter_sql(&mut self, filter: &QueryFilter) -> SqlResult<String> {
+        match filter {
+            QueryFilter::And(items) | QueryFilter::Or(items) => {
+                let (joiner, empty) = if matches!(filter, QueryFilter::And(_)) { (" AND ", "1") } else { (" OR ", "0") };
+                if items.is_empty() {
+                    return Ok(empty.to_string());
+                }
+                let parts = items.iter().map(|f| self.filter_sql(f)).collect::<SqlResult<Vec<_>>>()?;
+                Ok(format!("({})", parts.join(joiner)))
+            }
+            QueryFilter::Condition(cond) => self.condition_sql(cond),
+        }
+    }
+
+    fn condition_sql(&mut self, cond: &QueryCondition) -> SqlResult<String> {
+        self.check_column(&cond.field)?;
+        let column = quote_column(&cond.field);
+
+        match cond.op {
+            QueryOperator::IsNull => return Ok(format!("{} IS NULL", column)),
+            QueryOperator::IsNotNull => return Ok(format!("{} IS NOT NULL", column)),
+            QueryOperator::Like | QueryOperator::NotLike => {
+                let not = if cond.op == QueryOperator::NotLike { "NOT " } else { "" };
+                let placeholder = self.push(json_to_sql(&cond.value));
+                return Ok(format!("{} {}LIKE {}", column, not, placeholder));
+            }
+            _ => {}
+        }
+
+        let value_type = match cond.value_type {
+            QueryValueType::Auto if cond.value.is_number() => QueryValueType::Number,
+            QueryValueType::Auto if cond.value.as_array().map(|a| !a.is_empty() && a.iter().all(|v| v.is_number())).unwrap_or(false) => QueryValueType::Number,
+            other => other,
+        };
+        let convert = |value: &Value| -> SqlResult<SqlValue> {
+            match value_type {
+                QueryValueType::Date => parse_date_value(value)
+                    .map(SqlValue::Integer)
+                    .ok_or_else(|| query_error(format!("日付として解釈できません: {} ({})", value, cond.field))),
+                QueryValueType::Number => number_to_sql(value)
+                    .ok_or_else(|| query_error(format!("数値として解釈できません: {} ({})", value, cond.field))),
+                QueryValueType::String => Ok(match value {
+                    Value::String(s) => SqlValue::Text(s.clone()),
+                    other => SqlValue::Text(other.to_string()),
+                }),
+                QueryValueType::Auto => Ok(json_to_sql(value)),
+            }
+        };
+        let lhs = match value_type {
+            QueryValueType::Date => date_column_sql(&column),
+            // TEXTカラムに数字が入っている場合も数値として比較する
+            QueryValueType::Number => format!("CAST({} AS NUMERIC)", column),
+            _ => column.clone(),
+        };
+
+        match cond.op {
+            QueryOperator::In | QueryOperator::NotIn => {
+                let items = cond.value.as_array().cloned().unwrap_or_default();
+                let not = cond.op == QueryOperator::NotIn;
+                if items.is_empty() {
+                    return Ok(if not { "1" } else { "0" }.to_string());
+                }
+                let mut placeholders = Vec::new();
+                for item in &items {
+                    let v = convert(item)?;
+                    placeholders.push(self.push(v));
+                }
+                Ok(format!("{} {}IN ({})", lhs, if not { "NOT " } else { "" }, placeholders.join(", ")))
+            }
+            QueryOperator::Eq | QueryOperator::Ne if value_type == QueryValueType::Auto && cond.value.is_boolean() => {
+                // 真偽値は "true"/"false" の文字列と 1/0 の整数のどちらでも保存されている可能性がある
+                let b = cond.value.as_bool().unwrap_or(false);
+                let text = self.push(SqlValue::Text(b.to_string()));
+                let int = self.push(SqlValue::Integer(b as i64));
+                let not = if cond.op == QueryOperator::Ne { "NOT " } else { "" };
+                Ok(format!("{} {}IN ({}, {})", column, not, text, int))
+            }
+            op => {
+                let v = convert(&cond.value)?;
+                let placeholder = self.push(v);
+                Ok(format!("{} {} {}", lhs, op.comparison_sql(), placeholder))
+            }
+        }
+    }
+}
+
+/// クエリをテーブルの実カラムで検証し、SELECT文とバインド値を生成
+/// table_nameはALLOWED_TABLESで検証済みであること
+pub fn build_select(
+    conn: &Connection,
+    table_name: &str,
+    columns: &[String],
+    query: &CollectionQuery,
+) -> SqlResult<(String, Vec<SqlValue>)> {
+    let mut builder = SqlBuilder { columns, params: Vec::new() };
+    let mut where_clauses: Vec<String> = Vec::new();
+
+    if let Some(filter) = &query.filter {
+        where_clauses.push(builder.filter_sql(filter)?);
+    }
+
+    for order in &query.order_by {
+        builder.check_column(&order.field)?;
+    }
+    let mut order_by = query.order_by.clone();
+
+    // limit / cursor使用時は並びを一意にするためidを最後の並びキーにする
+    let has_id = columns.iter().any(|c| c == "id");
+    if (query.limit.is_some() || query.cursor.is_some()) && has_id && !order_by.iter().any(|o| o.field == "id") {
+        order_by.push(QueryOrder { field: "id".to_string(), descending: false });
+    }
+
+    if let Some(cursor) = &query.cursor {
+        if !has_id {
+            return Err(query_error(format!("{}はcursorによるページングに対応していません", table_name)));
+        }
+        where_clauses.push(cursor_clause(conn, table_name, &order_by, cursor, &mut builder)?);
+    }
+
+    let mut sql = format!("SELECT * FROM {}", table_name);
+    if !where_clauses.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&where_clauses.join(" AND "));
+    }
+    if !order_by.is_empty() {
+        let keys: Vec<String> = order_by.iter()
+            .map(|o| format!("{} {}", quote_column(&o.field), if o.descending { "DESC" } else { "ASC" }))
+            .collect();
+        sql.push_str(" ORDER BY ");
+        sql.push_str(&keys.join(", "));
+    }
+    match (query.limit, query.offset) {
+        (Some(limit), Some(offset)) => sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset)),
+        (Some(limit), None) => sql.push_str(&format!(" LIMIT {}", limit)),
+        (None, Some(offset)) => sql.push_str(&format!(" LIMIT -1 OFFSET {}", offset)),
+        (None, None) => {}
+    }
+
+    Ok((sql, builder.params))
+}
+
+/// cursorのドキュメントより後ろの行だけに絞るキーセット条件
+/// SQLiteの並び順（ASCではNULLが先頭、DESCでは末尾）に合わせてNULLを扱う
+fn cursor_clause(
+    conn: &Connection,
+    table_name: &str,
+    order_by: &[QueryOrder],
+    cursor: &str,
+    builder: &mut SqlBuilder,
+) -> SqlResult<String> {
+    let select_keys: Vec<String> = order_by.iter().map(|o| quote_column(&o.field)).collect();
+    let cursor_values: Option<Vec<SqlValue>> = conn.query_row(
+        &format!("SELECT {} FROM {} WHERE id = ?1", select_keys.join(", "), table_name),
+        [cursor],
+        |row| (0..order_by.len()).map(|i| row.get::<_, SqlValue>(i)).collect(),
+    ).optional()?;
+    let cursor_values = cursor_values
+        .ok_or_else(|| query_error(format!("cursorのドキュメントが見つかりません: {}", cursor)))?;
+
+    let mut alternatives = Vec::new();
+    for i in 0..order_by.len() {
+        let mut parts = Vec::new();
+        for (prev, prev_value) in order_by.iter().zip(&cursor_values).take(i) {
+            let placeholder = builder.push(prev_value.clone());
+            parts.push(format!("{} IS {}", quote_column(&prev.field), placeholder));
+        }
+        let column = quote_column(&order_by[i].field);
+        let after = match (&cursor_values[i], order_by[i].descending) {
+            (SqlValue::Null, false) => format!("{} IS NOT NULL", column),
+            (SqlValue::Null, true) => "0".to_string(),
+            (value, false) => format!("{} > {}", column, builder.push(value.clone())),
+            (value, true) => format!("({} < {} OR {} IS NULL)", column, builder.push(value.clone()), column),
+        };
+        parts.push(after);
+        alternatives.push(format!("({})", parts.join(" AND ")));
+    }
+    Ok(format!("({})", alternatives.join(" OR ")))
+}
@@ -6,8 +6,13 @@
 use rusqlite::{params, Result as SqlResult};
 use serde::{Deserialize, Serialize};
 use crate::database::{get_db, get_timestamp};
+use crate::database::design_doc_revision::{ensure_initial_revision, insert_revision, RevisionContext};
+use crate::database::design_doc_consistency::remove_section_references;
 use uuid::Uuid;
 
+/// セクションIDの接頭辞
+pub(crate) const SECTION_ID_PREFIX: &str = "section_";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DesignDocSection {
     pub id: String,
@@ -55,7 +60,7 @@ pub fn create_design_doc_section(
     })?;
 
     let conn = db.get_connection()?;
-    let id = format!("section_{}", Uuid::new_v4().to_string().replace("-", ""));
+    let id = format!("{}{}", SECTION_ID_PREFIX, Uuid::new_v4().to_string().replace("-", ""));
     let now = get_timestamp();
     let now_clone = now.clone();
 
@@ -65,7 +70,9 @@ pub fn create_design_doc_section(
     let keywords_json = keywords.as_ref().map(|k| serde_json::to_string(k).unwrap_or_default());
     let page_url_value = page_url.unwrap_or_else(|| "/design".to_string());
 
-    conn.execute(
+    // セクションと初版のリビジョンを同じトランザクションで保存
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
         "INSERT INTO designDocSections (
             id, title, description, content, tags, order_index, pageUrl,
             hierarchy, relatedSections, semanticCategory, keywords, summary,
@@ -90,7 +97,7 @@ pub fn create_design_doc_section(
         ],
     )?;
 
-    Ok(DesignDocSection {
+    let section = DesignDocSection {
         id,
         title,
         description,
@@ -105,7 +112,11 @@ pub fn create_design_doc_section(
         summary,
         created_at: get_timestamp(),
         updated_at: get_timestamp(),
-    })
+    };
+    insert_revision(&tx, &section, &RevisionContext::default(), None)?;
+    tx.commit()?;
+
+    Ok(section)
 }
 
 /// セクションを更新
@@ -122,6 +133,7 @@ pub fn update_design_doc_section(
     semantic_category: Option<String>,
     keywords: Option<Vec<String>>,
     summary: Option<String>,
+    revision: RevisionContext,
 ) -> SqlResult<DesignDocSection> {
     let db = get_db().ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
@@ -132,9 +144,10 @@ pub fn update_design_doc_section(
 
     let conn = db.get_connection()?;
     let now = get_timestamp();
+    let tx = conn.unchecked_transaction()?;
 
     // 現在の値を取得（デッドロックを防ぐため、直接クエリを実行）
-    let mut section = conn.query_row(
+    let mut section = tx.query_row(
         "SELECT id, title, description, content, tags, order_index, pageUrl,
                 hierarchy, relatedSections, semanticCategory, keywords, summary,
                 createdAt, updatedAt
@@ -161,6 +174,26 @@ pub fn update_design_doc_section(
         },
     )?;
 
+    // 編集の元にしたリビジョンが最新でなければ、他のユーザーの変更を上書きしないよう更新を拒否する
+    let tracks_revision = title.as_ref().is_some_and(|t| *t != section.title)
+        || content.as_ref().is_some_and(|c| *c != section.content);
+    let latest = if tracks_revision || revision.base_revision_id.is_some() {
+        Some(ensure_initial_revision(&tx, &section)?)
+    } else {
+        None
+    };
+    if let (Some(base_revision_id), Some(latest)) = (revision.base_revision_id.as_ref(), latest.as_ref()) {
+        if *base_revision_id != latest.id {
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+                Some(format!(
+                    "セクションは他のユーザーによって更新されています（最新リビジョン: {}）。マージしてから保存してください",
+                    latest.revision
+                )),
+            ));
+        }
+    }
+
     // 更新
     if let Some(title) = title {
         section.title = title;
@@ -203,7 +236,7 @@ pub fn update_design_doc_section(
     let related_sections_json = section.related_sections.clone();
     let keywords_json = section.keywords.clone();
 
-    conn.execute(
+    tx.execute(
         "UPDATE designDocSections SET
             title = ?2,
             description = ?3,
@@ -235,6 +268,13 @@ pub fn update_design_doc_section(
         ],
     )?;
 
+    // タイトル・本文が変わった場合はリビジョンを記録
+    if tracks_revision {
+        let base_revision_id = revision.base_revision_id.clone().or_else(|| latest.map(|l| l.id));
+        insert_revision(&tx, &section, &revision, base_revision_id)?;
+    }
+    tx.commit()?;
+
     Ok(section)
 }
 
@@ -376,10 +416,14 @@ pub fn delete_design_doc_section(id: &str) -> SqlResult<()> {
 
     let conn = db.get_connection()?;
 
-    conn.execute(
+    // 他のセクションのrelatedSections・hierarchyに残る参照も同じトランザクションで取り除く
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
         "DELETE FROM designDocSections WHERE id = ?1",
         params![id],
     )?;
+    remove_section_references(&tx, id)?;
+    tx.commit()?;
 
     Ok(())
 }
@@ -0,0 +1,355 @@
+/**
+ * システム設計ドキュメントセクションの整合性チェックモジュール
+ * セクション間の参照（relatedSections・hierarchy・designDocSectionRelations）を検査する
+ *
+ * - 存在しないセクションを指す参照（リンク切れ）
+ * - hierarchyの親子関係の循環
+ * - 同じページ内で重複しているorder_index
+ * - どこからもリンクされていないセクション
+ *
+ * hierarchyの要素は、既存のセクションIDまたはセクションIDの形式（section_〜）のものを参照として扱い、
+ * それ以外は階層の名前として扱う。自動修正ではリンク切れの参照だけを取り除く。
+ */
+
+use rusqlite::{params, Connection, Result as SqlResult};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::database::design_doc::{
+    get_all_design_doc_section_relations, get_all_design_doc_sections, DesignDocSection, DesignDocSectionRelation,
+    SECTION_ID_PREFIX,
+};
+use crate::database::{get_db, get_timestamp};
+
+/// リンク切れの参照（relatedSections・hierarchy）
+#[derive(Debug, Clone, Serialize)]
+pub struct DanglingSectionReference {
+    #[serde(rename = "sectionId")]
+    pub section_id: String,
+    #[serde(rename = "sectionTitle")]
+    pub section_title: String,
+    /// relatedSections または hierarchy
+    pub field: String,
+    #[serde(rename = "referenceId")]
+    pub reference_id: String,
+}
+
+/// 存在しないセクションを指す関係
+#[derive(Debug, Clone, Serialize)]
+pub struct DanglingSectionRelation {
+    #[serde(rename = "relationId")]
+    pub relation_id: String,
+    #[serde(rename = "sourceSectionId")]
+    pub source_section_id: String,
+    #[serde(rename = "targetSectionId")]
+    pub target_section_id: String,
+    #[serde(rename = "missingSource")]
+    pub missing_source: bool,
+    #[serde(rename = "missingTarget")]
+    pub missing_target: bool,
+}
+
+/// 同じページ内で重複しているorder_index
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateOrderIndex {
+    #[serde(rename = "pageUrl")]
+    pub page_url: String,
+    #[serde(rename = "order")]
+    pub order_index: i32,
+    #[serde(rename = "sectionIds")]
+    pub section_ids: Vec<String>,
+}
+
+/// セクションのIDとタイトル
+#[derive(Debug, Clone, Serialize)]
+pub struct SectionSummary {
+    pub id: String,
+    pub title: String,
+}
+
+/// 自動修正の結果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DesignDocConsistencyFix {
+    #[serde(rename = "prunedRelatedSections")]
+    pub pruned_related_sections: usize,
+    #[serde(rename = "prunedHierarchyEntries")]
+    pub pruned_hierarchy_entries: usize,
+    #[serde(rename = "deletedRelations")]
+    pub deleted_relations: usize,
+    #[serde(rename = "updatedSections")]
+    pub updated_sections: usize,
+}
+
+/// 整合性チェックの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct DesignDocConsistencyReport {
+    /// 問題がないか（どこからもリンクされていないセクションは問題に含めない）
+    pub valid: bool,
+    #[serde(rename = "sectionCount")]
+    pub section_count: usize,
+    #[serde(rename = "relationCount")]
+    pub relation_count: usize,
+    #[serde(rename = "danglingReferences")]
+    pub dangling_references: Vec<DanglingSectionReference>,
+    #[serde(rename = "danglingRelations")]
+    pub dangling_relations: Vec<DanglingSectionRelation>,
+    /// hierarchyの親子関係の循環（循環しているセクションIDの列）
+    #[serde(rename = "hierarchyCycles")]
+    pub hierarchy_cycles: Vec<Vec<String>>,
+    #[serde(rename = "duplicateOrderIndexes")]
+    pub duplicate_order_indexes: Vec<DuplicateOrderIndex>,
+    /// どこからもリンクされていないセクション（関係のターゲットにも他のセクションのrelatedSectionsにも含まれない）
+    #[serde(rename = "unlinkedSections")]
+    pub unlinked_sections: Vec<SectionSummary>,
+    /// 自動修正を行った場合の結果（修正後の状態を再チェックした結果を返す）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<DesignDocConsistencyFix>,
+}
+
+fn parse_json_list(value: Option<&String>) -> Vec<String> {
+    value
+        .and_then(|v| serde_json::from_str::<Vec<String>>(v).ok())
+        .unwrap_or_default()
+}
+
+/// hierarchyの要素がセクションへの参照か
+fn is_section_reference(entry: &str, section_ids: &HashSet<&str>) -> bool {
+    section_ids.contains(entry) || entry.starts_with(SECTION_ID_PREFIX)
+}
+
+/// hierarchyのうちセクションへの参照の最後の要素（直近の親）
+fn hierarchy_parent<'a>(section: &DesignDocSection, section_ids: &HashSet<&'a str>) -> Option<&'a str> {
+    parse_json_list(section.hierarchy.as_ref())
+        .iter()
+        .rev()
+        .find_map(|entry| section_ids.get(entry.as_str()).copied())
+}
+
+/// 親子関係をたどって循環を探す（循環は最小のIDから始まる列にそろえる）
+fn find_hierarchy_cycles(sections: &[DesignDocSection], section_ids: &HashSet<&str>) -> Vec<Vec<String>> {
+    let parents: HashMap<&str, &str> = sections
+        .iter()
+        .filter_map(|section| hierarchy_parent(section, section_ids).map(|parent| (section.id.as_str(), parent)))
+        .collect();
+
+    let mut finished: HashSet<&str> = HashSet::new();
+    let mut cycles: BTreeSet<Vec<String>> = BTreeSet::new();
+    for section in sections {
+        let mut path: Vec<&str> = Vec::new();
+        let mut position: HashMap<&str, usize> = HashMap::new();
+        let mut current = Some(section.id.as_str());
+        while let Some(id) = current {
+            if finished.contains(id) {
+                break;
+            }
+            if let Some(&start) = position.get(id) {
+                let mut cycle: Vec<String> = path[start..].iter().map(|s| s.to_string()).collect();
+                let min = cycle.iter().enumerate().min_by(|a, b| a.1.cmp(b.1)).map(|(i, _)| i).unwrap_or(0);
+                cycle.rotate_left(min);
+                cycles.insert(cycle);
+                break;
+            }
+            position.insert(id, path.len());
+            path.push(id);
+            current = parents.get(id).copied();
+        }
+        finished.extend(path);
+    }
+    cycles.into_iter().collect()
+}
+
+fn build_report(sections: &[DesignDocSection], relations: &[DesignDocSectionRelation]) -> DesignDocConsistencyReport {
+    let section_ids: HashSet<&str> = sections.iter().map(|s| s.id.as_str()).collect();
+
+    let mut dangling_references = Vec::new();
+    let mut linked: HashSet<&str> = HashSet::new();
+    for section in sections {
+        for related in parse_json_list(section.related_sections.as_ref()) {
+            if let Some(&target) = section_ids.get(related.as_str()) {
+                if target != section.id {
+                    linked.insert(target);
+                }
+            } else {
+                dangling_references.push(DanglingSectionReference {
+                    section_id: section.id.clone(),
+                    section_title: section.title.clone(),
+                    field: "relatedSections".to_string(),
+                    reference_id: related,
+                });
+            }
+        }
+        for entry in parse_json_list(section.hierarchy.as_ref()) {
+            if !section_ids.contains(entry.as_str()) && is_section_reference(&entry, &section_ids) {
+                dangling_references.push(DanglingSectionReference {
+                    section_id: section.id.clone(),
+                    section_title: section.title.clone(),
+                    field: "hierarchy".to_string(),
+                    reference_id: entry,
+                });
+            }
+        }
+    }
+
+    let mut dangling_relations = Vec::new();
+    for relation in relations {
+        let missing_source = !section_ids.contains(relation.source_section_id.as_str());
+        let missing_target = !section_ids.contains(relation.target_section_id.as_str());
+        if missing_source || missing_target {
+            dangling_relations.push(DanglingSectionRelation {
+                relation_id: relation.id.clone(),
+                source_section_id: relation.source_section_id.clone(),
+                target_section_id: relation.target_section_id.clone(),
+                missing_source,
+                missing_target,
+            });
+        } else if relation.source_section_id != relation.target_section_id {
+            linked.insert(relation.target_section_id.as_str());
+        }
+    }
+
+    let mut orders: BTreeMap<(&str, i32), Vec<String>> = BTreeMap::new();
+    for section in sections {
+        orders
+            .entry((section.page_url.as_str(), section.order_index))
+            .or_default()
+            .push(section.id.clone());
+    }
+    let duplicate_order_indexes: Vec<DuplicateOrderIndex> = orders
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .map(|((page_url, order_index), section_ids)| DuplicateOrderIndex {
+            page_url: page_url.to_string(),
+            order_index,
+            section_ids,
+        })
+        .collect();
+
+    let hierarchy_cycles = find_hierarchy_cycles(sections, &section_ids);
+    let unlinked_sections = sections
+        .iter()
+        .filter(|section| !linked.contains(section.id.as_str()))
+        .map(|section| SectionSummary { id: section.id.clone(), title: section.title.clone() })
+        .collect();
+
+    DesignDocConsistencyReport {
+        valid: dangling_references.is_empty()
+            && dangling_relations.is_empty()
+            && hierarchy_cycles.is_empty()
+            && duplicate_order_indexes.is_empty(),
+        section_count: sections.len(),
+        relation_count: relations.len(),
+        dangling_references,
+        dangling_relations,
+        hierarchy_cycles,
+        duplicate_order_indexes,
+        unlinked_sections,
+        fixed: None,
+    }
+}
+
+/// JSON配列からkeepがfalseの要素を取り除く（変更がなければNone、変更後のJSONと取り除いた数を返す）
+fn prune_json_list(value: Option<&String>, keep: impl Fn(&str) -> bool) -> Option<(String, usize)> {
+    let list = parse_json_list(value);
+    let kept: Vec<&String> = list.iter().filter(|entry| keep(entry)).collect();
+    let removed = list.len() - kept.len();
+    (removed > 0).then(|| (serde_json::to_string(&kept).unwrap_or_default(), removed))
+}
+
+/// 削除したセクションへの参照を他のセクションのrelatedSections・hierarchyから取り除く
+pub(crate) fn remove_section_references(conn: &Connection, section_id: &str) -> SqlResult<usize> {
+    let pattern = format!("%{}%", serde_json::to_string(section_id).unwrap_or_default());
+    let mut stmt = conn.prepare(
+        "SELECT id, relatedSections, hierarchy FROM designDocSections
+         WHERE id != ?1 AND (relatedSections LIKE ?2 OR hierarchy LIKE ?2)",
+    )?;
+    let rows = stmt
+        .query_map(params![section_id, pattern], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?, row.get::<_, Option<String>>(2)?))
+        })?
+        .collect::<SqlResult<Vec<_>>>()?;
+
+    let now = get_timestamp();
+    let mut updated = 0;
+    for (id, related_sections, hierarchy) in rows {
+        let related = prune_json_list(related_sections.as_ref(), |entry| entry != section_id);
+        let hierarchy = prune_json_list(hierarchy.as_ref(), |entry| entry != section_id);
+        if related.is_none() && hierarchy.is_none() {
+            continue;
+        }
+        conn.execute(
+            "UPDATE designDocSections SET
+                relatedSections = COALESCE(?2, relatedSections),
+                hierarchy = COALESCE(?3, hierarchy),
+                updatedAt = ?4
+             WHERE id = ?1",
+            params![id, related.map(|(json, _)| json), hierarchy.map(|(json, _)| json), now],
+        )?;
+        updated += 1;
+    }
+    Ok(updated)
+}
+
+/// リンク切れの参照と関係を取り除く
+fn prune_dangling_references(report: &DesignDocConsistencyReport, sections: &[DesignDocSection]) -> Result<DesignDocConsistencyFix, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    let conn = db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))?;
+    let section_ids: HashSet<&str> = sections.iter().map(|s| s.id.as_str()).collect();
+    let dangling_sections: HashSet<&str> = report.dangling_references.iter().map(|r| r.section_id.as_str()).collect();
+
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let now = get_timestamp();
+    let mut fix = DesignDocConsistencyFix::default();
+    for section in sections.iter().filter(|s| dangling_sections.contains(s.id.as_str())) {
+        let related = prune_json_list(section.related_sections.as_ref(), |entry| section_ids.contains(entry));
+        let hierarchy = prune_json_list(section.hierarchy.as_ref(), |entry| {
+            section_ids.contains(entry) || !is_section_reference(entry, &section_ids)
+        });
+        if related.is_none() && hierarchy.is_none() {
+            continue;
+        }
+        fix.pruned_related_sections += related.as_ref().map_or(0, |(_, removed)| *removed);
+        fix.pruned_hierarchy_entries += hierarchy.as_ref().map_or(0, |(_, removed)| *removed);
+        fix.updated_sections += 1;
+        tx.execute(
+            "UPDATE designDocSections SET
+                relatedSections = COALESCE(?2, relatedSections),
+                hierarchy = COALESCE(?3, hierarchy),
+                updatedAt = ?4
+             WHERE id = ?1",
+            params![section.id, related.map(|(json, _)| json), hierarchy.map(|(json, _)| json), now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for relation in &report.dangling_relations {
+        fix.deleted_relations += tx
+            .execute("DELETE FROM designDocSectionRelations WHERE id = ?1", params![relation.relation_id])
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(fix)
+}
+
+fn load() -> Result<(Vec<DesignDocSection>, Vec<DesignDocSectionRelation>), String> {
+    let sections = get_all_design_doc_sections().map_err(|e| format!("セクションの取得に失敗しました: {}", e))?;
+    let relations = get_all_design_doc_section_relations().map_err(|e| format!("セクション関係の取得に失敗しました: {}", e))?;
+    Ok((sections, relations))
+}
+
+/// セクション間の参照の整合性をチェックする（auto_fixの場合はリンク切れの参照を取り除いてから再チェック）
+pub fn check_design_doc_consistency(auto_fix: bool) -> Result<DesignDocConsistencyReport, String> {
+    let (sections, relations) = load()?;
+    let report = build_report(&sections, &relations);
+    if !auto_fix || (report.dangling_references.is_empty() && report.dangling_relations.is_empty()) {
+        return Ok(report);
+    }
+
+    let fix = prune_dangling_references(&report, &sections)?;
+    eprintln!(
+        "🔧 [DesignDoc] リンク切れの参照を取り除きました: relatedSections {}件, hierarchy {}件, 関係 {}件",
+        fix.pruned_related_sections, fix.pruned_hierarchy_entries, fix.deleted_relations
+    );
+    let (sections, relations) = load()?;
+    let mut report = build_report(&sections, &relations);
+    report.fixed = Some(fix);
+    Ok(report)
+}
@@ -0,0 +1,417 @@
+/**
+ * システム設計ドキュメントセクションのリビジョン管理モジュール
+ * セクションのタイトル・本文の変更をdesignDocSectionRevisionsに記録し、
+ * リビジョン間の差分（行単位のunified形式・単語単位）、リビジョンの復元、
+ * 別々のリビジョンを元にした編集の3-wayマージを行う
+ */
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::database::design_doc::{update_design_doc_section, DesignDocSection};
+use crate::database::text_diff::{diff_words, merge3, unified_diff, MergeConflict, MergeLabels, UnifiedDiff, WordDiff};
+use crate::database::{get_current_user, get_db, get_timestamp};
+
+/// unified形式の差分の前後に表示する行数の既定値
+const DEFAULT_DIFF_CONTEXT: usize = 3;
+
+const REVISION_COLUMNS: &str =
+    "id, sectionId, revision, title, content, baseRevisionId, author, authorId, message, createdAt";
+
+/// セクションのリビジョン
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesignDocSectionRevision {
+    pub id: String,
+    #[serde(rename = "sectionId")]
+    pub section_id: String,
+    /// セクション内の通し番号（1始まり）
+    pub revision: i64,
+    pub title: String,
+    pub content: String,
+    /// 編集の元にしたリビジョン
+    #[serde(rename = "baseRevisionId")]
+    pub base_revision_id: Option<String>,
+    pub author: Option<String>,
+    #[serde(rename = "authorId")]
+    pub author_id: Option<String>,
+    pub message: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+/// 更新時にリビジョンへ記録する情報
+#[derive(Debug, Clone, Default)]
+pub struct RevisionContext {
+    /// 編集者（未指定の場合はログイン中のユーザーのメールアドレス）
+    pub author: Option<String>,
+    /// 編集の元にしたリビジョン（指定した場合、最新のリビジョンでなければ更新を拒否する）
+    pub base_revision_id: Option<String>,
+    pub message: Option<String>,
+}
+
+/// 差分の形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevisionDiffMode {
+    /// 行単位（unified形式）
+    #[default]
+    Unified,
+    /// 単語単位
+    Word,
+}
+
+impl RevisionDiffMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "unified" | "line" => Ok(Self::Unified),
+            "word" => Ok(Self::Word),
+            _ => Err(format!("無効な差分形式: {}. 'unified' または 'word' を指定してください。", value)),
+        }
+    }
+}
+
+/// リビジョン間の差分
+#[derive(Debug, Clone, Serialize)]
+pub struct DesignDocRevisionDiff {
+    #[serde(rename = "fromRevisionId")]
+    pub from_revision_id: String,
+    #[serde(rename = "fromRevision")]
+    pub from_revision: i64,
+    #[serde(rename = "toRevisionId")]
+    pub to_revision_id: String,
+    #[serde(rename = "toRevision")]
+    pub to_revision: i64,
+    pub mode: RevisionDiffMode,
+    #[serde(rename = "titleChanged")]
+    pub title_changed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unified: Option<UnifiedDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub words: Option<WordDiff>,
+}
+
+/// 3-wayマージの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct DesignDocMergeResult {
+    /// マージ結果の本文（競合箇所は競合マーカー付き）
+    pub content: String,
+    pub title: String,
+    pub conflicts: Vec<MergeConflict>,
+    /// タイトルが両方で別々に変更されている（現在のタイトルを残す）
+    #[serde(rename = "titleConflict")]
+    pub title_conflict: bool,
+    #[serde(rename = "baseRevision")]
+    pub base_revision: i64,
+    #[serde(rename = "currentRevision")]
+    pub current_revision: i64,
+    /// 競合がなくセクションに保存したか
+    pub saved: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<DesignDocSection>,
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+fn read_revision(row: &rusqlite::Row) -> SqlResult<DesignDocSectionRevision> {
+    Ok(DesignDocSectionRevision {
+        id: row.get(0)?,
+        section_id: row.get(1)?,
+        revision: row.get(2)?,
+        title: row.get(3)?,
+        content: row.get(4)?,
+        base_revision_id: row.get(5)?,
+        author: row.get(6)?,
+        author_id: row.get(7)?,
+        message: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+fn find_revision(conn: &Connection, revision_id: &str) -> SqlResult<Option<DesignDocSectionRevision>> {
+    conn.query_row(
+        &format!("SELECT {} FROM designDocSectionRevisions WHERE id = ?1", REVISION_COLUMNS),
+        params![revision_id],
+        read_revision,
+    )
+    .optional()
+}
+
+fn write_revision(conn: &Connection, record: &DesignDocSectionRevision) -> SqlResult<()> {
+    conn.execute(
+        &format!(
+            "INSERT INTO designDocSectionRevisions ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            REVISION_COLUMNS
+        ),
+        params![
+            record.id,
+            record.section_id,
+            record.revision,
+            record.title,
+            record.content,
+            record.base_revision_id,
+            record.author,
+            record.author_id,
+            record.message,
+            record.created_at,
+        ],
+    )?;
+    Ok(())
+}
+
+/// セクションの最新のリビジョンを取得
+pub(crate) fn latest_revision(conn: &Connection, section_id: &str) -> SqlResult<Option<DesignDocSectionRevision>> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM designDocSectionRevisions WHERE sectionId = ?1 ORDER BY revision DESC LIMIT 1",
+            REVISION_COLUMNS
+        ),
+        params![section_id],
+        read_revision,
+    )
+    .optional()
+}
+
+/// リビジョンを記録する（編集者の指定がなければログイン中のユーザーを記録）
+pub(crate) fn insert_revision(
+    conn: &Connection,
+    section: &DesignDocSection,
+    context: &RevisionContext,
+    base_revision_id: Option<String>,
+) -> SqlResult<DesignDocSectionRevision> {
+    let revision: i64 = conn.query_row(
+        "SELECT COALESCE(MAX(revision), 0) + 1 FROM designDocSectionRevisions WHERE sectionId = ?1",
+        params![section.id],
+        |row| row.get(0),
+    )?;
+    let current_user = get_current_user();
+    let author = context
+        .author
+        .clone()
+        .filter(|a| !a.trim().is_empty())
+        .or_else(|| current_user.as_ref().map(|u| u.email.clone()));
+
+    let record = DesignDocSectionRevision {
+        id: format!("rev_{}", Uuid::new_v4().to_string().replace("-", "")),
+        section_id: section.id.clone(),
+        revision,
+        title: section.title.clone(),
+        content: section.content.clone(),
+        base_revision_id,
+        author,
+        author_id: current_user.map(|u| u.uid),
+        message: context.message.clone(),
+        created_at: get_timestamp(),
+    };
+    write_revision(conn, &record)?;
+    Ok(record)
+}
+
+/// リビジョンがまだないセクション（リビジョン管理の導入前に作成されたもの）の現在の状態を初版として記録し、最新のリビジョンを返す
+pub(crate) fn ensure_initial_revision(conn: &Connection, section: &DesignDocSection) -> SqlResult<DesignDocSectionRevision> {
+    if let Some(latest) = latest_revision(conn, &section.id)? {
+        return Ok(latest);
+    }
+    let record = DesignDocSectionRevision {
+        id: format!("rev_{}", Uuid::new_v4().to_string().replace("-", "")),
+        section_id: section.id.clone(),
+        revision: 1,
+        title: section.title.clone(),
+        content: section.content.clone(),
+        base_revision_id: None,
+        author: None,
+        author_id: None,
+        message: Some("リビジョン管理の導入前の内容".to_string()),
+        created_at: section.updated_at.clone(),
+    };
+    write_revision(conn, &record)?;
+    Ok(record)
+}
+
+/// セクションのリビジョン一覧を取得（新しい順）
+pub fn list_design_doc_section_revisions(section_id: &str) -> Result<Vec<DesignDocSectionRevision>, String> {
+    let conn = connection()?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM designDocSectionRevisions WHERE sectionId = ?1 ORDER BY revision DESC",
+            REVISION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let revisions = stmt
+        .query_map(params![section_id], read_revision)
+        .and_then(|rows| rows.collect::<SqlResult<Vec<_>>>())
+        .map_err(|e| e.to_string())?;
+    Ok(revisions)
+}
+
+/// IDでリビジョンを取得
+pub fn get_design_doc_section_revision(revision_id: &str) -> Result<DesignDocSectionRevision, String> {
+    let conn = connection()?;
+    find_revision(&conn, revision_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("リビジョンが見つかりません: {}", revision_id))
+}
+
+/// 2つのリビジョンの本文の差分を取得
+pub fn diff_design_doc_section_revisions(
+    from_revision_id: &str,
+    to_revision_id: &str,
+    mode: RevisionDiffMode,
+    context: Option<usize>,
+) -> Result<DesignDocRevisionDiff, String> {
+    let from = get_design_doc_section_revision(from_revision_id)?;
+    let to = get_design_doc_section_revision(to_revision_id)?;
+    if from.section_id != to.section_id {
+        return Err("異なるセクションのリビジョンは比較できません".to_string());
+    }
+
+    let (unified, words) = match mode {
+        RevisionDiffMode::Unified => {
+            let unified = unified_diff(
+                &from.content,
+                &to.content,
+                &format!("{} (rev {})", from.title, from.revision),
+                &format!("{} (rev {})", to.title, to.revision),
+                context.unwrap_or(DEFAULT_DIFF_CONTEXT),
+            );
+            (Some(unified), None)
+        }
+        RevisionDiffMode::Word => (None, Some(diff_words(&from.content, &to.content))),
+    };
+
+    Ok(DesignDocRevisionDiff {
+        from_revision_id: from.id,
+        from_revision: from.revision,
+        to_revision_id: to.id,
+        to_revision: to.revision,
+        mode,
+        title_changed: from.title != to.title,
+        unified,
+        words,
+    })
+}
+
+/// 指定したリビジョンのタイトル・本文に戻す（復元も新しいリビジョンとして記録する）
+pub fn restore_design_doc_section_revision(
+    section_id: &str,
+    revision_id: &str,
+    author: Option<String>,
+) -> Result<DesignDocSection, String> {
+    let revision = get_design_doc_section_revision(revision_id)?;
+    if revision.section_id != section_id {
+        return Err(format!("リビジョン {} はセクション {} のものではありません", revision_id, section_id));
+    }
+
+    update_design_doc_section(
+        section_id,
+        Some(revision.title),
+        None,
+        Some(revision.content),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        RevisionContext {
+            author,
+            base_revision_id: None,
+            message: Some(format!("リビジョン{}から復元", revision.revision)),
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// base_revision_idを元に編集した内容を、その後に保存された最新のリビジョンと3-wayマージする
+///
+/// 競合がなければマージ結果をセクションに保存し、競合がある場合は保存せずに競合マーカー付きの本文と競合箇所を返す。
+pub fn merge_design_doc_section(
+    section_id: &str,
+    base_revision_id: &str,
+    title: Option<String>,
+    content: String,
+    author: Option<String>,
+) -> Result<DesignDocMergeResult, String> {
+    let (base, current) = {
+        let conn = connection()?;
+        let base = find_revision(&conn, base_revision_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("ベースのリビジョンが見つかりません: {}", base_revision_id))?;
+        if base.section_id != section_id {
+            return Err(format!("リビジョン {} はセクション {} のものではありません", base_revision_id, section_id));
+        }
+        let current = latest_revision(&conn, section_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("セクションのリビジョンが見つかりません: {}", section_id))?;
+        (base, current)
+    };
+
+    let labels = MergeLabels {
+        current: &format!("現在の内容 (rev {})", current.revision),
+        base: &format!("ベース (rev {})", base.revision),
+        incoming: "編集内容",
+    };
+    let merge = merge3(&base.content, &current.content, &content, &labels);
+
+    // タイトルは一方だけが変更していればその変更を採用する
+    let incoming_title = title.unwrap_or_else(|| base.title.clone());
+    let title_conflict = current.title != base.title && incoming_title != base.title && current.title != incoming_title;
+    let merged_title = if current.title == base.title { incoming_title } else { current.title.clone() };
+
+    if !merge.conflicts.is_empty() || title_conflict {
+        eprintln!(
+            "⚠️ [DesignDoc] セクション {} のマージで競合が発生しました: 本文 {}件, タイトル {}",
+            section_id,
+            merge.conflicts.len(),
+            title_conflict
+        );
+        return Ok(DesignDocMergeResult {
+            content: merge.merged,
+            title: merged_title,
+            conflicts: merge.conflicts,
+            title_conflict,
+            base_revision: base.revision,
+            current_revision: current.revision,
+            saved: false,
+            section: None,
+        });
+    }
+
+    let section = update_design_doc_section(
+        section_id,
+        Some(merged_title.clone()),
+        None,
+        Some(merge.merged.clone()),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        RevisionContext {
+            author,
+            // マージ中に別の保存があった場合は更新を拒否する
+            base_revision_id: Some(current.id.clone()),
+            message: Some(format!("リビジョン{}を元にした編集をリビジョン{}とマージ", base.revision, current.revision)),
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(DesignDocMergeResult {
+        content: merge.merged,
+        title: merged_title,
+        conflicts: Vec::new(),
+        title_conflict: false,
+        base_revision: base.revision,
+        current_revision: current.revision,
+        saved: true,
+        section: Some(section),
+    })
+}
@@ -0,0 +1,830 @@
+/**
+ * システム設計ドキュメントの静的サイト出力モジュール
+ * designDocSectionsとdesignDocSectionRelationsを、アプリがなくても閲覧できる
+ * HTML（またはMarkdown）のファイル一式として書き出す
+ *
+ * - セクションはhierarchy（階層。要素がセクションIDの場合はそのセクションのタイトル）ごとにまとめ、order_index順に並べる
+ * - 目次、前後のセクションへのリンク、関係（designDocSectionRelations・relatedSections）からの相互リンクを付ける
+ * - PlantUMLのコードブロックは書き出し前にすべて集めてまとめてSVGへレンダリングし、diagrams/に保存する
+ * - MermaidのコードブロックはHTMLではassets/に同梱したmermaid.min.jsで描画するブロックに変換し、Markdownではそのまま残す
+ *   （スクリプトが見つからない場合はコードのまま出力する。CDNは使わない）
+ * - 本文中の生のHTMLはエスケープしてテキストとして出力する
+ * - 全文検索用のインデックス（search-index.json / search-index.js）を出力する
+ */
+
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::database::design_doc::{
+    get_all_design_doc_section_relations, get_all_design_doc_sections, DesignDocSection, SECTION_ID_PREFIX,
+};
+
+/// サイトのタイトルの既定値
+const DEFAULT_SITE_TITLE: &str = "システム設計ドキュメント";
+
+/// 検索インデックスに含める本文の最大文字数（セクションごと）
+const MAX_SEARCH_TEXT_CHARS: usize = 10_000;
+
+/// HTMLでMermaidを描画するスクリプトの出力先（assets/からの相対パス）
+const MERMAID_SCRIPT_FILE: &str = "mermaid.min.js";
+
+/// mermaid.min.jsが見つからない場合の図のエラー
+const MERMAID_SCRIPT_MISSING: &str = "mermaid.min.jsが見つからないため、Mermaidの図をコードのまま出力しました";
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DesignDocSiteFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+/// 出力の条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DesignDocSiteOptions {
+    #[serde(default)]
+    pub format: DesignDocSiteFormat,
+    /// 出力するセクション（未指定の場合はすべて）
+    #[serde(rename = "sectionIds")]
+    pub section_ids: Option<Vec<String>>,
+    /// サイトのタイトル（デフォルト「システム設計ドキュメント」）
+    pub title: Option<String>,
+    /// PlantUMLをSVGにレンダリングするか（デフォルトtrue）
+    #[serde(rename = "renderDiagrams")]
+    pub render_diagrams: Option<bool>,
+}
+
+/// レンダリングに失敗した図
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagramRenderError {
+    #[serde(rename = "sectionId")]
+    pub section_id: String,
+    pub message: String,
+}
+
+/// 出力の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct DesignDocSiteSummary {
+    pub format: DesignDocSiteFormat,
+    #[serde(rename = "outputDir")]
+    pub output_dir: String,
+    /// 目次のファイル（index.html または README.md）
+    #[serde(rename = "entryFile")]
+    pub entry_file: String,
+    #[serde(rename = "pageCount")]
+    pub page_count: usize,
+    #[serde(rename = "diagramCount")]
+    pub diagram_count: usize,
+    #[serde(rename = "mermaidCount")]
+    pub mermaid_count: usize,
+    #[serde(rename = "crossLinkCount")]
+    pub cross_link_count: usize,
+    /// レンダリングに失敗した図（該当のコードブロックはコードのまま出力する）
+    #[serde(rename = "diagramErrors")]
+    pub diagram_errors: Vec<DiagramRenderError>,
+}
+
+/// 検索インデックスの1件
+#[derive(Debug, Clone, Serialize)]
+struct SearchIndexEntry {
+    id: String,
+    title: String,
+    path: String,
+    hierarchy: Vec<String>,
+    summary: Option<String>,
+    keywords: Vec<String>,
+    text: String,
+}
+
+/// 出力するページ
+struct SitePage<'a> {
+    section: &'a DesignDocSection,
+    /// 目次上の階層（セクション自身のタイトルは含まない）
+    hierarchy: Vec<String>,
+    /// 出力ディレクトリからの相対パス
+    path: String,
+    links: Vec<CrossLink>,
+}
+
+/// 他のセクションへのリンク
+struct CrossLink {
+    target: usize,
+    label: String,
+    description: Option<String>,
+    outgoing: bool,
+}
+
+/// 目次の階層
+#[derive(Default)]
+struct TocNode {
+    name: String,
+    pages: Vec<usize>,
+    children: Vec<TocNode>,
+}
+
+impl TocNode {
+    fn insert(&mut self, path: &[String], page: usize) {
+        match path.split_first() {
+            None => self.pages.push(page),
+            Some((head, rest)) => {
+                let index = match self.children.iter().position(|child| child.name == *head) {
+                    Some(index) => index,
+                    None => {
+                        self.children.push(TocNode { name: head.clone(), ..Default::default() });
+                        self.children.len() - 1
+                    }
+                };
+                self.children[index].insert(rest, page);
+            }
+        }
+    }
+
+    /// 目次の並び（階層のページ、下位の階層の順）でページを列挙
+    fn flatten(&self, order: &mut Vec<usize>) {
+        order.extend(&self.pages);
+        for child in &self.children {
+            child.flatten(order);
+        }
+    }
+}
+
+/// PlantUMLのコードを1つの図として扱える形にする（@start〜がない場合は@startuml〜@endumlで囲む）
+fn normalize_plantuml(code: &str) -> String {
+    if code.contains("@start") {
+        code.to_string()
+    } else {
+        format!("@startuml\n{}\n@enduml\n", code.trim_end())
+    }
+}
+
+/// PlantUMLの図をまとめてレンダリングしてdiagrams/に保存した結果（同じコードは1回だけレンダリング）
+#[derive(Default)]
+struct DiagramRenderer {
+    /// 正規化したコードのハッシュごとの結果（図のファイル名、または失敗の理由）
+    rendered: HashMap<String, Result<String, String>>,
+}
+
+impl DiagramRenderer {
+    /// codesを1回の呼び出しでまとめてレンダリングし、dirに保存する
+    fn render_all(
+        dir: &Path,
+        codes: Vec<String>,
+        render: &mut dyn FnMut(Vec<String>) -> Vec<Result<Vec<u8>, String>>,
+    ) -> Self {
+        let mut seen = HashSet::new();
+        let mut unique: Vec<(String, String)> = Vec::new();
+        for code in codes {
+            let code = normalize_plantuml(&code);
+            let hash = format!("{:x}", Sha256::digest(code.as_bytes()));
+            if seen.insert(hash.clone()) {
+                unique.push((hash, code));
+            }
+        }
+        if unique.is_empty() {
+            return Self::default();
+        }
+
+        let mut results = render(unique.iter().map(|(_, code)| code.clone()).collect()).into_iter();
+        let mut rendered = HashMap::with_capacity(unique.len());
+        for (hash, _) in unique {
+            let file_name = format!("{}.svg", &hash[..16]);
+            let result = results
+                .next()
+                .unwrap_or_else(|| Err("レンダリング結果がありません".to_string()))
+                .and_then(|svg| {
+                    fs::create_dir_all(dir).map_err(|e| format!("ディレクトリの作成に失敗しました: {}", e))?;
+                    fs::write(dir.join(&file_name), svg).map_err(|e| format!("図の書き込みに失敗しました: {}", e))?;
+                    Ok(file_name)
+                });
+            rendered.insert(hash, result);
+        }
+        Self { rendered }
+    }
+
+    /// 図のファイル名を返す
+    fn file_name(&self, code: &str) -> Result<String, String> {
+        let hash = format!("{:x}", Sha256::digest(normalize_plantuml(code).as_bytes()));
+        self.rendered
+            .get(&hash)
+            .cloned()
+            .unwrap_or_else(|| Err("図がレンダリングされていません".to_string()))
+    }
+
+    fn diagram_count(&self) -> usize {
+        self.rendered.values().filter(|result| result.is_ok()).count()
+    }
+}
+
+fn markdown_options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS | Options::ENABLE_FOOTNOTES
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Markdownのリンクテキストに使えるようにエスケープ
+fn escape_link_text(value: &str) -> String {
+    value.replace('[', "\\[").replace(']', "\\]")
+}
+
+fn parse_json_list(value: Option<&String>) -> Vec<String> {
+    value
+        .and_then(|v| serde_json::from_str::<Vec<String>>(v).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// セクションIDをファイル名に使える形にする
+fn file_stem(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// 本文のフェンス付きコードブロック（言語、本文中の範囲、コード）を取り出す
+fn fenced_code_blocks(content: &str) -> Vec<(String, std::ops::Range<usize>, String)> {
+    let mut blocks = Vec::new();
+    let mut current: Option<(String, std::ops::Range<usize>, String)> = None;
+    for (event, range) in Parser::new_ext(content, markdown_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                let language = info.split_whitespace().next().unwrap_or("").to_lowercase();
+                current = Some((language, range, String::new()));
+            }
+            Event::Text(text) => {
+                if let Some((_, _, code)) = current.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                blocks.extend(current.take());
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+fn is_plantuml(language: &str) -> bool {
+    matches!(language, "plantuml" | "puml")
+}
+
+fn is_mermaid(info: &str) -> bool {
+    info.split_whitespace().next().is_some_and(|language| language.eq_ignore_ascii_case("mermaid"))
+}
+
+/// 本文のコードブロックのうち、PlantUMLを画像に置き換える
+fn prepare_content(
+    section: &DesignDocSection,
+    diagrams: Option<&DiagramRenderer>,
+    errors: &mut Vec<DiagramRenderError>,
+) -> String {
+    let content = &section.content;
+    let mut replacements: Vec<(std::ops::Range<usize>, String)> = Vec::new();
+
+    for (language, range, code) in fenced_code_blocks(content) {
+        if is_plantuml(&language) {
+            let Some(renderer) = diagrams else { continue };
+            match renderer.file_name(&code) {
+                Ok(file_name) => {
+                    replacements.push((range, format!("![PlantUML](../diagrams/{})\n", file_name)));
+                }
+                Err(message) => {
+                    eprintln!("⚠️ [DesignDocSite] セクション {} のPlantUMLのレンダリングに失敗しました: {}", section.id, message);
+                    errors.push(DiagramRenderError { section_id: section.id.clone(), message });
+                }
+            }
+        }
+    }
+
+    let mut prepared = String::with_capacity(content.len());
+    let mut position = 0;
+    for (range, replacement) in replacements {
+        prepared.push_str(&content[position..range.start]);
+        prepared.push_str(&replacement);
+        position = range.end;
+    }
+    prepared.push_str(&content[position..]);
+    prepared
+}
+
+/// 本文のMarkdownをHTMLに変換し、描画用のブロックにしたMermaidの図の数を返す
+/// 生のHTMLはエスケープしてテキストとして出力し、render_mermaidの場合はMermaidのコードブロックをmermaid.jsで描画するブロックにする
+fn markdown_html(content: &str, render_mermaid: bool) -> (String, usize) {
+    let mut events = Vec::new();
+    let mut mermaid: Option<String> = None;
+    let mut mermaid_count = 0;
+    for event in Parser::new_ext(content, markdown_options()) {
+        if let Some(code) = mermaid.as_mut() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    events.push(Event::Html(format!("<pre class=\"mermaid\">{}</pre>\n", escape_html(code)).into()));
+                    mermaid = None;
+                    mermaid_count += 1;
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if render_mermaid && is_mermaid(&info) => {
+                mermaid = Some(String::new());
+            }
+            // HTMLブロックは段落として、中身をエスケープして出力する
+            Event::Start(Tag::HtmlBlock) => events.push(Event::Start(Tag::Paragraph)),
+            Event::End(TagEnd::HtmlBlock) => events.push(Event::End(TagEnd::Paragraph)),
+            Event::Html(html) | Event::InlineHtml(html) => events.push(Event::Text(html)),
+            event => events.push(event),
+        }
+    }
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    (html, mermaid_count)
+}
+
+/// 検索インデックス用に本文からテキストだけを取り出す
+fn plain_text(content: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(content, markdown_options()) {
+        match event {
+            Event::Text(value) | Event::Code(value) => text.push_str(&value),
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(MAX_SEARCH_TEXT_CHARS).collect()
+}
+
+/// 出力するページを目次の順に並べ、相互リンクを付ける
+fn build_pages<'a>(sections: &'a [DesignDocSection], options: &DesignDocSiteOptions) -> Result<(Vec<SitePage<'a>>, TocNode), String> {
+    let extension = match options.format {
+        DesignDocSiteFormat::Html => "html",
+        DesignDocSiteFormat::Markdown => "md",
+    };
+    let selected: Option<HashSet<&String>> = options.section_ids.as_ref().map(|ids| ids.iter().collect());
+    let mut ordered: Vec<&DesignDocSection> = sections
+        .iter()
+        .filter(|section| selected.as_ref().is_none_or(|ids| ids.contains(&section.id)))
+        .collect();
+    ordered.sort_by(|a, b| a.order_index.cmp(&b.order_index).then_with(|| a.title.cmp(&b.title)));
+
+    // hierarchyの要素がセクションIDの場合はそのセクションのタイトルを階層名にする（存在しないセクションは除く）
+    let titles: HashMap<&str, &str> = sections.iter().map(|s| (s.id.as_str(), s.title.as_str())).collect();
+    let hierarchies: Vec<Vec<String>> = ordered
+        .iter()
+        .map(|section| {
+            let mut hierarchy: Vec<String> = parse_json_list(section.hierarchy.as_ref())
+                .into_iter()
+                .filter_map(|entry| match titles.get(entry.as_str()) {
+                    Some(title) => Some(title.to_string()),
+                    None if entry.starts_with(SECTION_ID_PREFIX) => None,
+                    None => Some(entry),
+                })
+                .collect();
+            // 階層の末尾がセクション自身のタイトルの場合は目次の階層に含めない
+            if hierarchy.last() == Some(&section.title) {
+                hierarchy.pop();
+            }
+            hierarchy
+        })
+        .collect();
+    let mut grouped = TocNode::default();
+    for (index, hierarchy) in hierarchies.iter().enumerate() {
+        grouped.insert(hierarchy, index);
+    }
+    let mut order = Vec::with_capacity(ordered.len());
+    grouped.flatten(&mut order);
+
+    // 目次の並び順でページ番号を振り直す
+    let mut page_index: HashMap<String, usize> = HashMap::new();
+    let mut pages: Vec<SitePage> = order
+        .iter()
+        .enumerate()
+        .map(|(index, &original)| {
+            let section = ordered[original];
+            page_index.insert(section.id.clone(), index);
+            SitePage {
+                section,
+                hierarchy: hierarchies[original].clone(),
+                path: format!("sections/{}.{}", file_stem(&section.id), extension),
+                links: Vec::new(),
+            }
+        })
+        .collect();
+    let mut toc = TocNode::default();
+    for (index, page) in pages.iter().enumerate() {
+        toc.insert(&page.hierarchy, index);
+    }
+
+    let relations = get_all_design_doc_section_relations().map_err(|e| format!("セクション関係の取得に失敗しました: {}", e))?;
+    let mut seen: HashSet<(usize, usize, String, bool)> = HashSet::new();
+    let mut add_link = |pages: &mut Vec<SitePage>, from: usize, link: CrossLink| {
+        if seen.insert((from, link.target, link.label.clone(), link.outgoing)) {
+            pages[from].links.push(link);
+        }
+    };
+    for relation in &relations {
+        let (Some(&source), Some(&target)) = (page_index.get(&relation.source_section_id), page_index.get(&relation.target_section_id)) else {
+            continue;
+        };
+        add_link(&mut pages, source, CrossLink {
+            target,
+            label: relation.relation_type.clone(),
+            description: relation.description.clone(),
+            outgoing: true,
+        });
+        add_link(&mut pages, target, CrossLink {
+            target: source,
+            label: relation.relation_type.clone(),
+            description: relation.description.clone(),
+            outgoing: false,
+        });
+    }
+    for index in 0..pages.len() {
+        for related_id in parse_json_list(pages[index].section.related_sections.as_ref()) {
+            if let Some(&target) = page_index.get(&related_id).filter(|&&target| target != index) {
+                add_link(&mut pages, index, CrossLink { target, label: "関連".to_string(), description: None, outgoing: true });
+            }
+        }
+    }
+
+    Ok((pages, toc))
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("ディレクトリの作成に失敗しました: {}", e))?;
+    }
+    fs::write(path, content).map_err(|e| format!("ファイルの書き込みに失敗しました: {} ({})", e, path.display()))
+}
+
+fn toc_html(node: &TocNode, pages: &[SitePage], prefix: &str, current: Option<usize>, html: &mut String) {
+    html.push_str("<ul>");
+    for &index in &node.pages {
+        let class = if current == Some(index) { " class=\"current\"" } else { "" };
+        html.push_str(&format!(
+            "<li><a{} href=\"{}{}\">{}</a></li>",
+            class,
+            prefix,
+            pages[index].path,
+            escape_html(&pages[index].section.title)
+        ));
+    }
+    for child in &node.children {
+        html.push_str(&format!("<li><span class=\"toc-group\">{}</span>", escape_html(&child.name)));
+        toc_html(child, pages, prefix, current, html);
+        html.push_str("</li>");
+    }
+    html.push_str("</ul>");
+}
+
+fn toc_markdown(node: &TocNode, pages: &[SitePage], depth: usize, markdown: &mut String) {
+    let indent = "  ".repeat(depth);
+    for &index in &node.pages {
+        markdown.push_str(&format!(
+            "{}- [{}]({})\n",
+            indent,
+            escape_link_text(&pages[index].section.title),
+            pages[index].path
+        ));
+    }
+    for child in &node.children {
+        markdown.push_str(&format!("{}- **{}**\n", indent, child.name));
+        toc_markdown(child, pages, depth + 1, markdown);
+    }
+}
+
+/// セクション以外からの相対パスでページ名を返す（sections/内のページ同士のリンク用）
+fn sibling_path<'a>(page: &'a SitePage) -> &'a str {
+    page.path.trim_start_matches("sections/")
+}
+
+fn page_layout(site_title: &str, title: &str, prefix: &str, sidebar: &str, main: &str, use_mermaid: bool) -> String {
+    let mermaid = if use_mermaid {
+        format!(
+            "<script src=\"{}assets/{}\"></script>\n<script>mermaid.initialize({{ startOnLoad: true }});</script>\n",
+            prefix, MERMAID_SCRIPT_FILE
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"ja\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{title}</title>\n<link rel=\"stylesheet\" href=\"{prefix}assets/style.css\">\n</head>\n<body>\n\
+         <nav class=\"sidebar\">\n<a class=\"site-title\" href=\"{prefix}index.html\">{site_title}</a>\n\
+         <input id=\"search\" type=\"search\" placeholder=\"検索\" autocomplete=\"off\">\n<ul id=\"search-results\"></ul>\n\
+         {sidebar}\n</nav>\n<main>\n{main}\n</main>\n\
+         <script src=\"{prefix}search-index.js\"></script>\n<script src=\"{prefix}assets/search.js\" data-base=\"{prefix}\"></script>\n\
+         {mermaid}</body>\n</html>\n",
+        title = escape_html(title),
+        site_title = escape_html(site_title),
+        prefix = prefix,
+        sidebar = sidebar,
+        main = main,
+        mermaid = mermaid,
+    )
+}
+
+/// bodyは本文を変換したHTML
+fn section_html(pages: &[SitePage], index: usize, body: &str, toc: &str, site_title: &str, use_mermaid: bool) -> String {
+    let page = &pages[index];
+    let section = page.section;
+    let mut main = String::new();
+    if !page.hierarchy.is_empty() {
+        main.push_str(&format!(
+            "<div class=\"breadcrumb\">{}</div>\n",
+            page.hierarchy.iter().map(|name| escape_html(name)).collect::<Vec<_>>().join(" / ")
+        ));
+    }
+    main.push_str(&format!("<h1>{}</h1>\n", escape_html(&section.title)));
+    if let Some(description) = section.description.as_ref().filter(|d| !d.trim().is_empty()) {
+        main.push_str(&format!("<p class=\"description\">{}</p>\n", escape_html(description)));
+    }
+
+    main.push_str(&format!("<article>\n{}</article>\n", body));
+
+    if !page.links.is_empty() {
+        main.push_str("<section class=\"related\">\n<h2>関連セクション</h2>\n<ul>\n");
+        for link in &page.links {
+            let target = &pages[link.target];
+            main.push_str(&format!(
+                "<li>{} <a href=\"{}\">{}</a> <span class=\"relation-type\">{}</span>{}</li>\n",
+                if link.outgoing { "→" } else { "←" },
+                sibling_path(target),
+                escape_html(&target.section.title),
+                escape_html(&link.label),
+                link.description
+                    .as_ref()
+                    .filter(|d| !d.trim().is_empty())
+                    .map(|d| format!(" <span class=\"relation-description\">{}</span>", escape_html(d)))
+                    .unwrap_or_default()
+            ));
+        }
+        main.push_str("</ul>\n</section>\n");
+    }
+
+    main.push_str("<nav class=\"pager\">");
+    if let Some(previous) = index.checked_sub(1).map(|i| &pages[i]) {
+        main.push_str(&format!(
+            "<a class=\"previous\" href=\"{}\">← {}</a>",
+            sibling_path(previous),
+            escape_html(&previous.section.title)
+        ));
+    }
+    if let Some(next) = pages.get(index + 1) {
+        main.push_str(&format!(
+            "<a class=\"next\" href=\"{}\">{} →</a>",
+            sibling_path(next),
+            escape_html(&next.section.title)
+        ));
+    }
+    main.push_str("</nav>\n");
+    main.push_str(&format!("<footer>最終更新: {}</footer>", escape_html(&section.updated_at)));
+
+    page_layout(site_title, &format!("{} - {}", section.title, site_title), "../", toc, &main, use_mermaid)
+}
+
+fn section_markdown(pages: &[SitePage], index: usize, content: &str) -> String {
+    let page = &pages[index];
+    let section = page.section;
+    let mut markdown = String::new();
+    if !page.hierarchy.is_empty() {
+        markdown.push_str(&format!("[目次](../README.md) / {}\n\n", page.hierarchy.join(" / ")));
+    } else {
+        markdown.push_str("[目次](../README.md)\n\n");
+    }
+    markdown.push_str(&format!("# {}\n\n", section.title));
+    if let Some(description) = section.description.as_ref().filter(|d| !d.trim().is_empty()) {
+        markdown.push_str(&format!("> {}\n\n", description.trim().replace('\n', "\n> ")));
+    }
+    markdown.push_str(content.trim_end());
+    markdown.push_str("\n\n");
+
+    if !page.links.is_empty() {
+        markdown.push_str("## 関連セクション\n\n");
+        for link in &page.links {
+            let target = &pages[link.target];
+            markdown.push_str(&format!(
+                "- {} [{}]({})（{}）{}\n",
+                if link.outgoing { "→" } else { "←" },
+                escape_link_text(&target.section.title),
+                sibling_path(target),
+                link.label,
+                link.description.as_ref().filter(|d| !d.trim().is_empty()).map(|d| format!(" {}", d)).unwrap_or_default()
+            ));
+        }
+        markdown.push('\n');
+    }
+
+    let mut pager = Vec::new();
+    if let Some(previous) = index.checked_sub(1).map(|i| &pages[i]) {
+        pager.push(format!("← [{}]({})", escape_link_text(&previous.section.title), sibling_path(previous)));
+    }
+    if let Some(next) = pages.get(index + 1) {
+        pager.push(format!("[{}]({}) →", escape_link_text(&next.section.title), sibling_path(next)));
+    }
+    if !pager.is_empty() {
+        markdown.push_str(&format!("---\n\n{}\n", pager.join(" | ")));
+    }
+    markdown
+}
+
+/// 設計ドキュメントを静的サイトとしてoutput_dirに書き出す
+///
+/// render_plantumlはPlantUMLのコードの一覧から、同じ順序で図ごとのSVGを返す関数（Javaで実行するためコマンド側から渡す）。
+/// 出力するページのPlantUMLをすべて集めてから1回だけ呼び出す。
+/// mermaid_scriptはassets/に同梱するmermaid.min.js（見つからない場合はNoneで、Mermaidの図はコードのまま出力する）。
+pub fn export_design_doc_site(
+    output_dir: &str,
+    options: &DesignDocSiteOptions,
+    render_plantuml: &mut dyn FnMut(Vec<String>) -> Vec<Result<Vec<u8>, String>>,
+    mermaid_script: Option<&Path>,
+) -> Result<DesignDocSiteSummary, String> {
+    let output = PathBuf::from(output_dir);
+    let site_title = options
+        .title
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_SITE_TITLE.to_string());
+
+    let sections = get_all_design_doc_sections().map_err(|e| format!("セクションの取得に失敗しました: {}", e))?;
+    let (pages, toc) = build_pages(&sections, options)?;
+    if pages.is_empty() {
+        return Err("出力するセクションがありません".to_string());
+    }
+
+    let render_diagrams = options.render_diagrams.unwrap_or(true);
+    let renderer = if render_diagrams {
+        let codes = pages
+            .iter()
+            .flat_map(|page| fenced_code_blocks(&page.section.content))
+            .filter(|(language, _, _)| is_plantuml(language))
+            .map(|(_, _, code)| code)
+            .collect();
+        DiagramRenderer::render_all(&output.join("diagrams"), codes, render_plantuml)
+    } else {
+        DiagramRenderer::default()
+    };
+    let mut diagram_errors = Vec::new();
+    let mut mermaid_count = 0;
+    let mut search_index = Vec::with_capacity(pages.len());
+
+    for (index, page) in pages.iter().enumerate() {
+        let content = prepare_content(page.section, render_diagrams.then_some(&renderer), &mut diagram_errors);
+        let document = match options.format {
+            DesignDocSiteFormat::Html => {
+                let (body, page_mermaid_count) = markdown_html(&content, mermaid_script.is_some());
+                mermaid_count += page_mermaid_count;
+                if mermaid_script.is_none() && fenced_code_blocks(&content).iter().any(|(language, _, _)| is_mermaid(language)) {
+                    eprintln!("⚠️ [DesignDocSite] セクション {}: {}", page.section.id, MERMAID_SCRIPT_MISSING);
+                    diagram_errors.push(DiagramRenderError {
+                        section_id: page.section.id.clone(),
+                        message: MERMAID_SCRIPT_MISSING.to_string(),
+                    });
+                }
+                let mut sidebar = String::new();
+                toc_html(&toc, &pages, "../", Some(index), &mut sidebar);
+                section_html(&pages, index, &body, &sidebar, &site_title, page_mermaid_count > 0)
+            }
+            DesignDocSiteFormat::Markdown => section_markdown(&pages, index, &content),
+        };
+        write_file(&output.join(&page.path), &document)?;
+
+        search_index.push(SearchIndexEntry {
+            id: page.section.id.clone(),
+            title: page.section.title.clone(),
+            path: page.path.clone(),
+            hierarchy: page.hierarchy.clone(),
+            summary: page.section.summary.clone().or_else(|| page.section.description.clone()),
+            keywords: parse_json_list(page.section.keywords.as_ref()),
+            text: plain_text(&page.section.content),
+        });
+    }
+
+    let entry_file = match options.format {
+        DesignDocSiteFormat::Html => {
+            let mut toc_list = String::new();
+            toc_html(&toc, &pages, "", None, &mut toc_list);
+            let main = format!("<h1>{}</h1>\n<section class=\"toc\">\n<h2>目次</h2>\n{}\n</section>", escape_html(&site_title), toc_list);
+            write_file(&output.join("index.html"), &page_layout(&site_title, &site_title, "", "", &main, false))?;
+            write_file(&output.join("assets/style.css"), SITE_STYLE)?;
+            write_file(&output.join("assets/search.js"), SEARCH_SCRIPT)?;
+            if let Some(script) = mermaid_script.filter(|_| mermaid_count > 0) {
+                fs::copy(script, output.join("assets").join(MERMAID_SCRIPT_FILE))
+                    .map_err(|e| format!("mermaid.min.jsのコピーに失敗しました: {}", e))?;
+            }
+            "index.html"
+        }
+        DesignDocSiteFormat::Markdown => {
+            let mut readme = format!("# {}\n\n## 目次\n\n", site_title);
+            toc_markdown(&toc, &pages, 0, &mut readme);
+            write_file(&output.join("README.md"), &readme)?;
+            "README.md"
+        }
+    };
+
+    let index_json = serde_json::to_string(&search_index).map_err(|e| format!("検索インデックスの作成に失敗しました: {}", e))?;
+    write_file(&output.join("search-index.json"), &index_json)?;
+    if options.format == DesignDocSiteFormat::Html {
+        // file://で開いた場合はfetchできないため、スクリプトとしても出力する
+        write_file(&output.join("search-index.js"), &format!("window.DESIGN_DOC_SEARCH_INDEX = {};\n", index_json))?;
+    }
+
+    Ok(DesignDocSiteSummary {
+        format: options.format,
+        output_dir: output_dir.to_string(),
+        entry_file: entry_file.to_string(),
+        page_count: pages.len(),
+        diagram_count: renderer.diagram_count(),
+        mermaid_count,
+        cross_link_count: pages.iter().map(|page| page.links.len()).sum(),
+        diagram_errors,
+    })
+}
+
+const SITE_STYLE: &str = r#"* { box-sizing: border-box; }
+body { margin: 0; display: flex; font-family: -apple-system, BlinkMacSystemFont, "Hiragino Sans", "Noto Sans JP", sans-serif; color: #1f2933; line-height: 1.7; }
+.sidebar { width: 300px; flex-shrink: 0; height: 100vh; position: sticky; top: 0; overflow-y: auto; padding: 20px; background: #f5f7fa; border-right: 1px solid #e4e7eb; font-size: 14px; }
+.sidebar ul { list-style: none; padding-left: 12px; margin: 4px 0; }
+.sidebar > ul { padding-left: 0; }
+.sidebar a { color: #334e68; text-decoration: none; }
+.sidebar a.current { font-weight: bold; color: #0b69a3; }
+.site-title { display: block; font-size: 16px; font-weight: bold; margin-bottom: 12px; }
+.toc-group { display: block; margin-top: 8px; font-weight: bold; color: #52606d; }
+#search { width: 100%; padding: 6px 8px; border: 1px solid #cbd2d9; border-radius: 4px; }
+#search-results { padding-left: 0; margin-bottom: 16px; }
+#search-results li { padding: 4px 0; border-bottom: 1px solid #e4e7eb; }
+main { flex: 1; min-width: 0; max-width: 960px; padding: 32px 48px; }
+.breadcrumb { font-size: 13px; color: #7b8794; }
+.description { color: #52606d; }
+article img { max-width: 100%; }
+pre { background: #f5f7fa; padding: 12px; overflow-x: auto; border-radius: 4px; }
+pre.mermaid { background: none; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #cbd2d9; padding: 6px 10px; }
+.related { margin-top: 40px; padding-top: 16px; border-top: 1px solid #e4e7eb; }
+.relation-type { font-size: 12px; color: #7b8794; }
+.relation-description { font-size: 13px; color: #52606d; }
+.pager { display: flex; justify-content: space-between; margin-top: 32px; }
+.pager .next { margin-left: auto; }
+footer { margin-top: 24px; font-size: 12px; color: #9aa5b1; }
+"#;
+
+const SEARCH_SCRIPT: &str = r#"(function () {
+  var base = document.currentScript.getAttribute('data-base') || '';
+  var index = window.DESIGN_DOC_SEARCH_INDEX || [];
+  var input = document.getElementById('search');
+  var results = document.getElementById('search-results');
+  if (!input || !results) return;
+
+  function escapeHtml(value) {
+    return value.replace(/[&<>"']/g, function (c) {
+      return { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c];
+    });
+  }
+
+  input.addEventListener('input', function () {
+    var terms = input.value.toLowerCase().split(/\s+/).filter(Boolean);
+    results.innerHTML = '';
+    if (terms.length === 0) return;
+    var matches = [];
+    index.forEach(function (entry) {
+      var title = entry.title.toLowerCase();
+      var body = [entry.summary || '', entry.keywords.join(' '), entry.hierarchy.join(' '), entry.text].join(' ').toLowerCase();
+      var score = 0;
+      for (var i = 0; i < terms.length; i++) {
+        if (title.indexOf(terms[i]) >= 0) score += 10;
+        else if (body.indexOf(terms[i]) >= 0) score += 1;
+        else return;
+      }
+      matches.push({ entry: entry, score: score });
+    });
+    matches.sort(function (a, b) { return b.score - a.score; });
+    results.innerHTML = matches.slice(0, 20).map(function (match) {
+      return '<li><a href="' + base + match.entry.path + '">' + escapeHtml(match.entry.title) + '</a></li>';
+    }).join('') || '<li>該当するセクションがありません</li>';
+  });
+})();
+"#;
@@ -0,0 +1,567 @@
+/**
+ * テキスト埋め込みの生成
+ * ai_settingsに設定されたプロバイダーのAPIを呼び出し、テキストを埋め込みベクトルに変換する
+ *
+ * - OpenAI / LMStudio: OpenAI互換の`/embeddings`エンドポイント（複数テキストをまとめて送信）
+ * - Ollama: `/api/embeddings`エンドポイント（1テキストずつ送信）
+ * - Anthropicは埋め込みAPIを提供していないため非対応
+ *
+ * 同じプロバイダー・モデル・テキストの結果はメモリ上にキャッシュし、
+ * 次元数が既知のモデルは生成した埋め込みの次元数を照合する（コレクションとの照合はembedding_registryで行う）
+ */
+
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use super::ai_settings::{get_ai_setting, AIProvider};
+use super::embedding_registry::embedding_service_for;
+use super::vector_search::{VectorStoreFuture, EMBEDDING_DIMENSION};
+
+/// 各プロバイダーの埋め込みモデルのデフォルト
+pub const DEFAULT_OPENAI_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+const DEFAULT_OLLAMA_EMBEDDING_MODEL: &str = "nomic-embed-text";
+const DEFAULT_LMSTUDIO_EMBEDDING_MODEL: &str = "text-embedding-nomic-embed-text-v1.5";
+
+/// 各プロバイダーのベースURLのデフォルト
+pub(super) const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+pub(super) const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434";
+pub(super) const DEFAULT_LMSTUDIO_BASE_URL: &str = "http://localhost:1234/v1";
+
+/// OpenAI互換APIに1回のリクエストで送るテキスト数
+const MAX_BATCH_SIZE: usize = 128;
+
+/// キャッシュする埋め込みの最大件数（超えた場合は古いものから削除）
+const CACHE_CAPACITY: usize = 4096;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// テキストを埋め込みベクトルに変換するインターフェース
+pub trait TextEmbedder: Send + Sync {
+    /// 複数のテキストをまとめて変換（戻り値はtextsと同じ順序）
+    fn embed(&self, texts: Vec<String>) -> VectorStoreFuture<'_, Vec<Vec<f32>>>;
+}
+
+/// 埋め込みAPIの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EmbeddingApi {
+    /// POST {baseUrl}/embeddings { model, input: [..] }
+    OpenAICompatible,
+    /// POST {baseUrl}/api/embeddings { model, prompt }
+    Ollama,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingResponse {
+    data: Vec<OpenAIEmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+/// 生成済み埋め込みのキャッシュ（キーはプロバイダー・モデル・テキストのハッシュ）
+struct EmbeddingCache {
+    entries: HashMap<String, Vec<f32>>,
+    order: VecDeque<String>,
+}
+
+impl EmbeddingCache {
+    fn get(&self, key: &str) -> Option<Vec<f32>> {
+        self.entries.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: String, embedding: Vec<f32>) {
+        if self.entries.insert(key.clone(), embedding).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > CACHE_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+static EMBEDDING_CACHE: OnceLock<Mutex<EmbeddingCache>> = OnceLock::new();
+
+fn embedding_cache() -> &'static Mutex<EmbeddingCache> {
+    EMBEDDING_CACHE.get_or_init(|| Mutex::new(EmbeddingCache {
+        entries: HashMap::new(),
+        order: VecDeque::new(),
+    }))
+}
+
+/// 設定されたプロバイダーで埋め込みを生成するサービス
+pub struct EmbeddingService {
+    client: reqwest::Client,
+    api: EmbeddingApi,
+    provider: String,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+    /// 期待する次元数（モデルが既知の場合）
+    dimension: Option<usize>,
+}
+
+/// 主な埋め込みモデルの出力次元数
+fn known_model_dimension(model: &str) -> Option<usize> {
+    // Ollamaのタグ（nomic-embed-text:latestなど）は除いて照合
+    let name = model.split(':').next().unwrap_or(model);
+    match name {
+        "text-embedding-3-small" | "text-embedding-ada-002" => Some(EMBEDDING_DIMENSION),
+        "text-embedding-3-large" => Some(3072),
+        "nomic-embed-text" | "text-embedding-nomic-embed-text-v1.5" => Some(768),
+        "mxbai-embed-large" => Some(1024),
+        "all-minilm" => Some(384),
+        _ => None,
+    }
+}
+
+/// 環境変数{PROVIDER}_EMBEDDING_MODELから埋め込みモデル名を取得
+fn env_embedding_model(provider: &str) -> Option<String> {
+    std::env::var(format!("{}_EMBEDDING_MODEL", provider.to_uppercase()))
+        .ok()
+        .filter(|s| !s.is_empty())
+}
+
+impl EmbeddingService {
+    /// ai_settings（環境変数またはaiSettingsテーブル）の設定から作成
+    /// providerを省略した場合は環境変数EMBEDDING_PROVIDER、未設定の場合はopenai
+    /// モデルは環境変数{PROVIDER}_EMBEDDING_MODEL、未設定の場合はプロバイダーごとのデフォルト
+    pub fn from_ai_settings(provider: Option<&str>) -> Result<Self, String> {
+        let provider_name = provider
+            .map(|p| p.to_string())
+            .or_else(|| std::env::var("EMBEDDING_PROVIDER").ok().filter(|s| !s.is_empty()))
+            .unwrap_or_else(|| "openai".to_string())
+            .to_lowercase();
+        let provider = AIProvider::from_str(&provider_name)
+            .ok_or_else(|| format!("不明な埋め込みプロバイダーです: {}", provider_name))?;
+        // データベース未初期化（Supabase専用）の場合は環境変数とデフォルト値のみを使う
+        let config = get_ai_setting(&provider_name).unwrap_or_else(|e| {
+            eprintln!("⚠️ [embedding] AI設定の取得に失敗しました（デフォルト設定を使用します）: {}", e);
+            None
+        });
+        let (api_key, base_url) = match config {
+            Some(config) => (config.api_key, config.base_url),
+            None => (None, None),
+        };
+
+        let (api, default_base_url, default_model) = match provider {
+            AIProvider::OpenAI => (EmbeddingApi::OpenAICompatible, DEFAULT_OPENAI_BASE_URL, DEFAULT_OPENAI_EMBEDDING_MODEL),
+            AIProvider::LMStudio => (EmbeddingApi::OpenAICompatible, DEFAULT_LMSTUDIO_BASE_URL, DEFAULT_LMSTUDIO_EMBEDDING_MODEL),
+            AIProvider::Ollama => (EmbeddingApi::Ollama, DEFAULT_OLLAMA_BASE_URL, DEFAULT_OLLAMA_EMBEDDING_MODEL),
+            AIProvider::Anthropic => {
+                return Err("Anthropicは埋め込みAPIに対応していません".to_string());
+            }
+        };
+        if matches!(provider, AIProvider::OpenAI) && api_key.is_none() {
+            return Err("OpenAIのAPIキーが設定されていません".to_string());
+        }
+
+        let model = env_embedding_model(&provider_name).unwrap_or_else(|| default_model.to_string());
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api,
+            base_url: base_url
+                .unwrap_or_else(|| default_base_url.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            api_key,
+            dimension: known_model_dimension(&model),
+            model,
+            provider: provider_name,
+        })
+    }
+
+    /// 埋め込みモデルを変更
+    pub fn with_model(mut self, model: String) -> Self {
+        self.dimension = known_model_dimension(&model);
+        self.model = model;
+        self
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn cache_key(&self, text: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.provider.as_bytes());
+        hasher.update([0]);
+        hasher.update(self.model.as_bytes());
+        hasher.update([0]);
+        hasher.update(text.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn check_dimension(&self, embedding: &[f32]) -> Result<(), String> {
+        match self.dimension {
+            Some(dimension) if embedding.len() != dimension => Err(format!(
+                "埋め込みの次元数が一致しません: モデル={}, 期待値={}, 実際={}",
+                self.model,
+                dimension,
+                embedding.len()
+            )),
+            _ if embedding.is_empty() => Err(format!("埋め込みが空です: モデル={}", self.model)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 1件のテキストを変換
+    pub async fn embed_text(&self, text: &str) -> Result<Vec<f32>, String> {
+        let mut embeddings = self.embed_texts(vec![text.to_string()]).await?;
+        embeddings.pop().ok_or_else(|| "埋め込みの生成結果が空です".to_string())
+    }
+
+    /// 複数のテキストを変換（キャッシュ済みのテキストはAPIを呼び出さない）
+    pub async fn embed_texts(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+        if let Some(index) = texts.iter().position(|t| t.trim().is_empty()) {
+            return Err(format!("空のテキストは埋め込みできません (index={})", index));
+        }
+
+        let keys: Vec<String> = texts.iter().map(|t| self.cache_key(t)).collect();
+        let mut results: Vec<Option<Vec<f32>>> = {
+            let cache = embedding_cache().lock().map_err(|e| format!("キャッシュのロックに失敗しました: {}", e))?;
+            keys.iter().map(|key| cache.get(key)).collect()
+        };
+
+        // 未キャッシュのテキストのみ（重複は1回だけ）APIに送信
+        let mut missing: Vec<usize> = Vec::new();
+        let mut first_index: HashMap<&str, usize> = HashMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            if results[i].is_none() && !first_index.contains_key(key.as_str()) {
+                first_index.insert(key, i);
+                missing.push(i);
+            }
+        }
+
+        for chunk in missing.chunks(self.batch_size()) {
+            let chunk_texts: Vec<String> = chunk.iter().map(|&i| texts[i].clone()).collect();
+            let embeddings = self.request(chunk_texts).await?;
+            for embedding in &embeddings {
+                self.check_dimension(embedding)?;
+            }
+            let mut cache = embedding_cache().lock().map_err(|e| format!("キャッシュのロックに失敗しました: {}", e))?;
+            for (&i, embedding) in chunk.iter().zip(embeddings) {
+                cache.insert(keys[i].clone(), embedding.clone());
+                results[i] = Some(embedding);
+            }
+        }
+
+        // 重複していたテキストは最初の結果を使う
+        for (i, key) in keys.iter().enumerate() {
+            if results[i].is_none() {
+                results[i] = results[first_index[key.as_str()]].clone();
+            }
+        }
+        let embeddings = results
+            .into_iter()
+            .map(|r| r.ok_or_else(|| "埋め込みの生成結果が不足しています".to_string()))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(embeddings)
+    }
+
+    fn batch_size(&self) -> usize {
+        match self.api {
+            EmbeddingApi::OpenAICompatible => MAX_BATCH_SIZE,
+            EmbeddingApi::Ollama => 1,
+        }
+    }
+
+    async fn request(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+        match self.api {
+            EmbeddingApi::OpenAICompatible => self.request_openai(texts).await,
+            EmbeddingApi::Ollama => {
+                let mut embeddings = Vec::with_capacity(texts.len());
+                for text in texts {
+                    embeddings.push(self.request_ollama(text).await?);
+                }
+                Ok(embeddings)
+            }
+        }
+    }
+
+    async fn send(&self, url: String, body: serde_json::Value) -> Result<reqwest::Response, String> {
+        let mut request = self.client.post(url).timeout(REQUEST_TIMEOUT).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await
+            .map_err(|e| format!("埋め込みAPIへの接続に失敗しました ({}): {}", self.provider, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("埋め込みAPIがエラーを返しました ({} {}): {}", self.provider, status, body));
+        }
+        Ok(response)
+    }
+
+    async fn request_openai(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+        let expected = texts.len();
+        let response = self.send(
+            format!("{}/embeddings", self.base_url),
+            json!({ "model": self.model, "input": texts }),
+        ).await?;
+        let mut parsed: OpenAIEmbeddingResponse = response.json().await
+            .map_err(|e| format!("埋め込みAPIのレスポンスの解析に失敗しました: {}", e))?;
+        if parsed.data.len() != expected {
+            return Err(format!(
+                "埋め込みAPIの結果件数が一致しません: 期待値={}, 実際={}",
+                expected,
+                parsed.data.len()
+            ));
+        }
+        parsed.data.sort_by_key(|d| d.index);
+        Ok(parsed.data.into_iter().map(|d| d.embedding).collect())
+    }
+
+    async fn request_ollama(&self, text: String) -> Result<Vec<f32>, String> {
+        let response = self.send(
+            format!("{}/api/embeddings", self.base_url),
+            json!({ "model": self.model, "prompt": text }),
+        ).await?;
+        let parsed: OllamaEmbeddingResponse = response.json().await
+            .map_err(|e| format!("埋め込みAPIのレスポンスの解析に失敗しました: {}", e))?;
+        if parsed.embedding.is_empty() {
+            return Err(format!("埋め込みAPIが空の埋め込みを返しました (model={})", self.model));
+        }
+        Ok(parsed.embedding)
+    }
+}
+
+impl TextEmbedder for EmbeddingService {
+    fn embed(&self, texts: Vec<String>) -> VectorStoreFuture<'_, Vec<Vec<f32>>> {
+        Box::pin(self.embed_texts(texts))
+    }
+}
+
+/// メタデータから埋め込み対象のテキストを取得
+/// searchableTextがあればそれを、なければfallback_keysの値を空白区切りで連結する
+pub fn searchable_text_from_metadata(
+    metadata: &HashMap<String, serde_json::Value>,
+    fallback_keys: &[&str],
+) -> Option<String> {
+    let text_of = |key: &str| {
+        metadata.get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+    };
+    if let Some(text) = text_of("searchableText") {
+        return Some(text.to_string());
+    }
+    let parts: Vec<&str> = fallback_keys.iter().filter_map(|key| text_of(key)).collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+/// 埋め込みが渡されなかった場合に、メタデータのテキストからサーバー側で生成する
+/// 保存先の論理コレクションに登録されたプロバイダー・モデルを使う（再埋め込み後も同じモデルで揃える）
+pub async fn resolve_embedding(
+    collection: &str,
+    embedding: Option<Vec<f32>>,
+    metadata: &HashMap<String, serde_json::Value>,
+    fallback_keys: &[&str],
+) -> Result<Vec<f32>, String> {
+    if let Some(embedding) = embedding {
+        return Ok(embedding);
+    }
+    let text = searchable_text_from_metadata(metadata, fallback_keys)
+        .ok_or_else(|| "埋め込みを生成するテキスト（searchableText）がありません".to_string())?;
+    embedding_service_for(collection)?.embed_text(&text).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::Arc;
+    use std::thread;
+
+    /// 埋め込みAPIのモックサーバー（リクエストごとのテキスト数を記録する）
+    struct MockServer {
+        base_url: String,
+        batches: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl MockServer {
+        fn batches(&self) -> Vec<usize> {
+            self.batches.lock().unwrap().clone()
+        }
+    }
+
+    /// 先頭の要素をテキストの長さにした埋め込み（結果の順序の確認用）
+    fn fake_embedding(text: &str, dimension: usize) -> Vec<f32> {
+        let mut embedding = vec![0.5; dimension];
+        embedding[0] = text.chars().count() as f32;
+        embedding
+    }
+
+    fn read_request_body(stream: &TcpStream) -> Vec<u8> {
+        let mut reader = BufReader::new(stream);
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                break;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        let _ = reader.read_exact(&mut body);
+        body
+    }
+
+    /// OpenAI互換（input）とOllama（prompt）の両方に、指定した次元数の埋め込みを返すサーバーを起動
+    fn start_mock_server(dimension: usize) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let recorded = batches.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let request: Value = serde_json::from_slice(&read_request_body(&stream)).unwrap_or(Value::Null);
+                let response = match request["input"].as_array() {
+                    Some(input) => {
+                        recorded.lock().unwrap().push(input.len());
+                        // indexで並べ替えていることを確認するため逆順で返す
+                        let data: Vec<Value> = input
+                            .iter()
+                            .enumerate()
+                            .rev()
+                            .map(|(i, text)| json!({ "index": i, "embedding": fake_embedding(text.as_str().unwrap_or(""), dimension) }))
+                            .collect();
+                        json!({ "data": data })
+                    }
+                    None => {
+                        recorded.lock().unwrap().push(1);
+                        json!({ "embedding": fake_embedding(request["prompt"].as_str().unwrap_or(""), dimension) })
+                    }
+                };
+                let body = response.to_string();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        MockServer { base_url, batches }
+    }
+
+    fn service(api: EmbeddingApi, server: &MockServer, model: &str) -> EmbeddingService {
+        EmbeddingService {
+            client: reqwest::Client::new(),
+            api,
+            provider: "mock".to_string(),
+            base_url: server.base_url.clone(),
+            api_key: None,
+            model: model.to_string(),
+            dimension: known_model_dimension(model),
+        }
+    }
+
+    /// キャッシュはプロセス全体で共有されるため、テストごとに異なるテキストを使う
+    fn texts(prefix: &str, count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("{}-{}", prefix, "x".repeat(i))).collect()
+    }
+
+    #[tokio::test]
+    async fn openai_requests_are_batched_and_deduplicated() {
+        let server = start_mock_server(EMBEDDING_DIMENSION);
+        let service = service(EmbeddingApi::OpenAICompatible, &server, DEFAULT_OPENAI_EMBEDDING_MODEL);
+        let mut input = texts("batch", MAX_BATCH_SIZE + 2);
+        input.push(input[0].clone());
+
+        let embeddings = service.embed_texts(input.clone()).await.unwrap();
+
+        assert_eq!(server.batches(), vec![MAX_BATCH_SIZE, 2]);
+        assert_eq!(embeddings.len(), input.len());
+        for (text, embedding) in input.iter().zip(&embeddings) {
+            assert_eq!(embedding.len(), EMBEDDING_DIMENSION);
+            assert_eq!(embedding[0], text.chars().count() as f32);
+        }
+    }
+
+    #[tokio::test]
+    async fn ollama_sends_one_text_per_request() {
+        let server = start_mock_server(768);
+        let service = service(EmbeddingApi::Ollama, &server, DEFAULT_OLLAMA_EMBEDDING_MODEL);
+        let input = texts("ollama", 3);
+
+        let embeddings = service.embed_texts(input.clone()).await.unwrap();
+
+        assert_eq!(server.batches(), vec![1, 1, 1]);
+        assert_eq!(embeddings[2][0], input[2].chars().count() as f32);
+    }
+
+    #[tokio::test]
+    async fn cached_texts_do_not_call_the_api() {
+        let server = start_mock_server(EMBEDDING_DIMENSION);
+        let service = service(EmbeddingApi::OpenAICompatible, &server, DEFAULT_OPENAI_EMBEDDING_MODEL);
+        let input = texts("cache", 3);
+
+        let first = service.embed_texts(input[..2].to_vec()).await.unwrap();
+        let second = service.embed_texts(input.clone()).await.unwrap();
+
+        // 2回目は未キャッシュの1件だけを送信する
+        assert_eq!(server.batches(), vec![2, 1]);
+        assert_eq!(&second[..2], &first[..]);
+    }
+
+    #[tokio::test]
+    async fn dimension_mismatch_is_rejected_and_not_cached() {
+        let server = start_mock_server(8);
+        let service = service(EmbeddingApi::OpenAICompatible, &server, DEFAULT_OPENAI_EMBEDDING_MODEL);
+        let input = texts("mismatch", 1);
+
+        let error = service.embed_texts(input.clone()).await.unwrap_err();
+        assert!(error.contains("次元数が一致しません"), "{}", error);
+
+        // 不正な結果はキャッシュされず、再度APIを呼び出す
+        assert!(service.embed_texts(input).await.is_err());
+        assert_eq!(server.batches(), vec![1, 1]);
+    }
+
+    #[tokio::test]
+    async fn unknown_models_accept_any_dimension() {
+        let server = start_mock_server(8);
+        let service = service(EmbeddingApi::OpenAICompatible, &server, "custom-embedding-model");
+
+        let embeddings = service.embed_texts(texts("custom", 1)).await.unwrap();
+
+        assert_eq!(embeddings[0].len(), 8);
+    }
+}
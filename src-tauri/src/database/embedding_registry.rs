@@ -0,0 +1,690 @@
+/**
+ * 埋め込みモデルのレジストリ
+ * コレクション（entities_{org}、topics_{org}、design_docsなど）ごとに、ベクトルを生成したモデルと次元数を記録する
+ *
+ * - 呼び出し側が使うコレクション名（論理名）と、実際にベクトルを保存しているコレクション（物理名）を対応付ける
+ *   （vector_store()が返すRegistryVectorStoreが論理名を物理名に変換してバックエンドに渡す）
+ *   再埋め込みでは`{論理名}__v{バージョン}`の新しいコレクションに書き込み、完了後にレジストリの対応を切り替える
+ * - 次元数が登録と異なる保存・検索はエラーにする
+ *   （未登録のコレクションは既存のベクトルから次元数を登録し、空の場合は最初の保存時に登録）
+ * - レジストリはベクトルインデックスと同じディレクトリのembedding_registry.jsonに保存する
+ *   （一時ファイルに書き出してからリネームするため、切り替えは途中で失敗しても前後どちらかの状態になる）
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use super::chroma_sync;
+use super::embedding::{searchable_text_from_metadata, EmbeddingService};
+use super::get_timestamp;
+use super::vector_search::{
+    raw_vector_store, vector_store_dir, MetadataFilter, VectorBackend, VectorMatch,
+    VectorRecord, VectorStore, VectorStoreFuture,
+};
+
+const REGISTRY_FILE_NAME: &str = "embedding_registry.json";
+
+/// バージョン付きの物理コレクション名の区切り
+const VERSION_SEPARATOR: &str = "__v";
+
+/// 再埋め込みで一度に処理する件数
+const REEMBED_BATCH_SIZE: usize = 64;
+
+/// 切り替え後に旧コレクションへの書き込みを反映する最大回数
+const MAX_CATCH_UP_ROUNDS: usize = 3;
+
+/// メタデータから再埋め込みのテキストを組み立てる場合に使うキー
+const METADATA_TEXT_KEYS: &[&str] = &["title", "name", "relationType", "contentSummary", "description"];
+
+/// コレクションの埋め込みモデル情報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingCollectionInfo {
+    /// 論理コレクション名
+    pub collection: String,
+    /// ベクトルを保存している物理コレクション名
+    #[serde(rename = "physicalCollection")]
+    pub physical_collection: String,
+    pub provider: Option<String>,
+    /// 埋め込みモデル（フロントエンドで生成された埋め込みのみの場合は不明）
+    pub model: Option<String>,
+    pub dimension: usize,
+    pub version: u32,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    collections: BTreeMap<String, EmbeddingCollectionInfo>,
+}
+
+struct Registry {
+    path: PathBuf,
+    collections: BTreeMap<String, EmbeddingCollectionInfo>,
+    /// 既存のベクトルから次元数を登録済み、または確認済みのコレクション（プロセス内のみ）
+    seeded: HashSet<String>,
+}
+
+impl Registry {
+    fn load(path: PathBuf) -> Self {
+        let collections = match fs::read_to_string(&path) {
+            Ok(content) => match serde_json::from_str::<RegistryFile>(&content) {
+                Ok(file) => file.collections,
+                Err(e) => {
+                    eprintln!("⚠️ [embedding_registry] {} の読み込みに失敗しました（空のレジストリで開始します）: {}", path.display(), e);
+                    BTreeMap::new()
+                }
+            },
+            Err(_) => BTreeMap::new(),
+        };
+        Registry { path, collections, seeded: HashSet::new() }
+    }
+
+    /// 一時ファイルに書き出してからリネーム
+    fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("レジストリの保存先ディレクトリの作成に失敗しました: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&RegistryFile { collections: self.collections.clone() })
+            .map_err(|e| format!("レジストリのシリアライズに失敗しました: {}", e))?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, content)
+            .map_err(|e| format!("レジストリの書き込みに失敗しました: {}", e))?;
+        fs::rename(&tmp_path, &self.path)
+            .map_err(|e| format!("レジストリの書き込みに失敗しました: {}", e))
+    }
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+fn with_registry<T>(f: impl FnOnce(&mut Registry) -> Result<T, String>) -> Result<T, String> {
+    let registry = match REGISTRY.get() {
+        Some(registry) => registry,
+        None => {
+            let path = vector_store_dir()?.join(REGISTRY_FILE_NAME);
+            REGISTRY.get_or_init(|| Mutex::new(Registry::load(path)))
+        }
+    };
+    let mut guard = registry.lock()
+        .map_err(|e| format!("レジストリのロック取得に失敗しました: {}", e))?;
+    f(&mut guard)
+}
+
+/// `{論理名}__v{数字}`の形式か
+fn is_versioned_name(name: &str) -> bool {
+    name.rsplit_once(VERSION_SEPARATOR)
+        .map(|(base, version)| !base.is_empty() && !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false)
+}
+
+/// 論理コレクション名から物理コレクション名を取得（未登録の場合は同じ名前）
+pub fn physical_collection_name(collection: &str) -> String {
+    with_registry(|registry| {
+        Ok(registry.collections
+            .get(collection)
+            .map(|info| info.physical_collection.clone()))
+    })
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| collection.to_string())
+}
+
+/// バックエンドのコレクション一覧（物理名）を論理名に変換
+/// 切り替え前後の使われていない物理コレクションは除外する
+pub fn logical_collection_names(physical_names: Vec<String>) -> Vec<String> {
+    let collections = with_registry(|registry| Ok(registry.collections.clone())).unwrap_or_default();
+    let by_physical: HashMap<&str, &str> = collections
+        .values()
+        .map(|info| (info.physical_collection.as_str(), info.collection.as_str()))
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut names = Vec::new();
+    for name in physical_names {
+        let logical = match by_physical.get(name.as_str()) {
+            Some(logical) => logical.to_string(),
+            // 別の物理コレクションに切り替え済みの旧コレクション、または再埋め込み途中のコレクション
+            None if collections.contains_key(&name) || is_versioned_name(&name) => continue,
+            None => name,
+        };
+        if seen.insert(logical.clone()) {
+            names.push(logical);
+        }
+    }
+    names
+}
+
+/// モデル不明のコレクションとして登録する情報
+fn unversioned_info(collection: &str, dimension: usize) -> EmbeddingCollectionInfo {
+    EmbeddingCollectionInfo {
+        collection: collection.to_string(),
+        physical_collection: collection.to_string(),
+        provider: None,
+        model: None,
+        dimension,
+        version: 1,
+        updated_at: get_timestamp(),
+    }
+}
+
+/// コレクションの先頭のベクトルの次元数（空の場合はNone）
+async fn sample_dimension(store: &dyn VectorStore, collection: &str) -> Result<Option<usize>, String> {
+    let Some(id) = store.list_ids(collection.to_string()).await?.into_iter().next() else {
+        return Ok(None);
+    };
+    Ok(store.get(collection.to_string(), id).await?
+        .map(|record| record.embedding.len())
+        .filter(|dimension| *dimension > 0))
+}
+
+/// 未登録のコレクションに既存のベクトルがあれば、その次元数で登録する
+/// （レジストリ導入前に作られたコレクションにも、最初の保存を待たずに次元数の確認を適用する）
+async fn seed_existing_collection(collection: &str) -> Result<(), String> {
+    let needs_seed = with_registry(|registry| {
+        Ok(!registry.collections.contains_key(collection) && registry.seeded.insert(collection.to_string()))
+    })?;
+    if !needs_seed {
+        return Ok(());
+    }
+
+    let dimension = match sample_dimension(&*raw_vector_store()?, collection).await {
+        Ok(Some(dimension)) => dimension,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            // 次回の確認で再試行する
+            eprintln!("⚠️ [embedding_registry] {} の既存ベクトルの確認に失敗しました: {}", collection, e);
+            return with_registry(|registry| {
+                registry.seeded.remove(collection);
+                Ok(())
+            });
+        }
+    };
+    with_registry(|registry| {
+        if registry.collections.contains_key(collection) {
+            return Ok(());
+        }
+        eprintln!("📝 [embedding_registry] 既存のコレクション {} を{}次元で登録しました", collection, dimension);
+        registry.collections.insert(collection.to_string(), unversioned_info(collection, dimension));
+        registry.save()
+    })
+}
+
+/// 保存する埋め込みの次元数を確認（未登録で空のコレクションは次元数を登録）
+pub async fn check_upsert_dimension(collection: &str, dimension: usize) -> Result<(), String> {
+    if is_versioned_name(collection) {
+        return Ok(());
+    }
+    seed_existing_collection(collection).await?;
+    with_registry(|registry| {
+        if let Some(info) = registry.collections.get(collection) {
+            return dimension_matches(info, dimension, "保存");
+        }
+        registry.collections.insert(collection.to_string(), unversioned_info(collection, dimension));
+        registry.save()
+    })
+}
+
+/// 検索に使う埋め込みの次元数を確認
+pub async fn check_query_dimension(collection: &str, dimension: usize) -> Result<(), String> {
+    if is_versioned_name(collection) {
+        return Ok(());
+    }
+    seed_existing_collection(collection).await?;
+    with_registry(|registry| match registry.collections.get(collection) {
+        Some(info) => dimension_matches(info, dimension, "検索"),
+        None => Ok(()),
+    })
+}
+
+fn dimension_matches(info: &EmbeddingCollectionInfo, dimension: usize, operation: &str) -> Result<(), String> {
+    if info.dimension == dimension {
+        return Ok(());
+    }
+    Err(format!(
+        "コレクション '{}' の埋め込みは{}（{}次元）で作成されていますが、{}に{}次元の埋め込みが指定されました。同じモデルで埋め込みを生成するか、reembed_collectionでコレクションを再埋め込みしてください",
+        info.collection,
+        info.model.as_deref().unwrap_or("不明なモデル"),
+        info.dimension,
+        operation,
+        dimension
+    ))
+}
+
+/// コレクションの登録を削除（コレクション削除時）
+pub fn remove_collection(collection: &str) {
+    let result = with_registry(|registry| {
+        registry.seeded.remove(collection);
+        if registry.collections.remove(collection).is_some() {
+            registry.save()?;
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("⚠️ [embedding_registry] {} の登録削除に失敗しました: {}", collection, e);
+    }
+}
+
+/// 登録済みのコレクション一覧
+pub fn list_embedding_collections() -> Result<Vec<EmbeddingCollectionInfo>, String> {
+    with_registry(|registry| Ok(registry.collections.values().cloned().collect()))
+}
+
+/// 論理コレクションの埋め込みを生成するサービスを取得
+/// 再埋め込みで登録されたプロバイダー・モデルを使い、未登録（または不明）の場合はai_settingsのデフォルト
+pub fn embedding_service_for(collection: &str) -> Result<EmbeddingService, String> {
+    let info = with_registry(|registry| Ok(registry.collections.get(collection).cloned()))?;
+    let (provider, model) = match info {
+        Some(info) => (info.provider, info.model),
+        None => (None, None),
+    };
+    let service = EmbeddingService::from_ai_settings(provider.as_deref())?;
+    Ok(match model {
+        Some(model) => service.with_model(model),
+        None => service,
+    })
+}
+
+/// 論理コレクション名で操作するベクトルストア
+/// 保存・検索時に次元数を確認し、コレクション名をレジストリの物理名に変換してバックエンドに渡す
+pub struct RegistryVectorStore {
+    inner: Arc<dyn VectorStore>,
+}
+
+impl RegistryVectorStore {
+    pub fn new(inner: Arc<dyn VectorStore>) -> Self {
+        Self { inner }
+    }
+}
+
+impl VectorStore for RegistryVectorStore {
+    fn backend(&self) -> VectorBackend {
+        self.inner.backend()
+    }
+
+    fn upsert(
+        &self,
+        collection: String,
+        id: String,
+        embedding: Vec<f32>,
+        metadata: serde_json::Map<String, Value>,
+    ) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            check_upsert_dimension(&collection, embedding.len()).await?;
+            self.inner.upsert(physical_collection_name(&collection), id, embedding, metadata).await
+        })
+    }
+
+    fn get(&self, collection: String, id: String) -> VectorStoreFuture<'_, Option<VectorRecord>> {
+        self.inner.get(physical_collection_name(&collection), id)
+    }
+
+    fn query(
+        &self,
+        collection: String,
+        embedding: Vec<f32>,
+        limit: usize,
+        filter: Option<MetadataFilter>,
+    ) -> VectorStoreFuture<'_, Vec<VectorMatch>> {
+        Box::pin(async move {
+            check_query_dimension(&collection, embedding.len()).await?;
+            self.inner.query(physical_collection_name(&collection), embedding, limit, filter).await
+        })
+    }
+
+    fn delete(&self, collection: String, ids: Vec<String>) -> VectorStoreFuture<'_, ()> {
+        self.inner.delete(physical_collection_name(&collection), ids)
+    }
+
+    fn count(&self, collection: String) -> VectorStoreFuture<'_, usize> {
+        self.inner.count(physical_collection_name(&collection))
+    }
+
+    fn list_ids(&self, collection: String) -> VectorStoreFuture<'_, Vec<String>> {
+        self.inner.list_ids(physical_collection_name(&collection))
+    }
+
+    fn list_collections(&self) -> VectorStoreFuture<'_, Vec<String>> {
+        Box::pin(async move { Ok(logical_collection_names(self.inner.list_collections().await?)) })
+    }
+
+    fn delete_collection(&self, collection: String) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            self.inner.delete_collection(physical_collection_name(&collection)).await?;
+            remove_collection(&collection);
+            Ok(())
+        })
+    }
+}
+
+/// 再埋め込みの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct ReembedReport {
+    pub collection: String,
+    #[serde(rename = "previousPhysicalCollection")]
+    pub previous_physical_collection: String,
+    #[serde(rename = "physicalCollection")]
+    pub physical_collection: String,
+    pub provider: String,
+    pub model: String,
+    pub dimension: usize,
+    pub version: u32,
+    pub reembedded: usize,
+    /// 埋め込み対象のテキストが見つからず、旧コレクションのベクトルをそのままコピーしたID
+    #[serde(rename = "copiedIds")]
+    pub copied_ids: Vec<String>,
+    /// 再埋め込み中に旧コレクションへ書き込まれたため、SQLiteの行を未同期に戻したID
+    #[serde(rename = "resyncIds")]
+    pub resync_ids: Vec<String>,
+    /// 旧コレクションを削除したか（新しいコレクションの検証に失敗した場合は残す）
+    #[serde(rename = "previousCollectionDeleted")]
+    pub previous_collection_deleted: bool,
+}
+
+/// 再埋め込みの途中経過
+#[derive(Default)]
+struct ReembedState {
+    dimension: Option<usize>,
+    /// 読み取った時点の旧コレクションのベクトル（IDごとの埋め込みとメタデータのハッシュ）
+    fingerprints: HashMap<String, u64>,
+    /// 埋め込み対象のテキストがなく、旧ベクトルをそのままコピーするレコード
+    pending_copies: Vec<VectorRecord>,
+    reembedded: HashSet<String>,
+    copied: HashSet<String>,
+}
+
+/// 旧コレクションのベクトルが読み取り後に書き換えられたかを判定するためのハッシュ
+fn record_fingerprint(record: &VectorRecord) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for value in &record.embedding {
+        value.to_bits().hash(&mut hasher);
+    }
+    serde_json::to_string(&record.metadata).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// IDごとの再埋め込み用テキストとレコードを取得（テキストはSQLiteの元データを優先し、なければメタデータから組み立てる）
+async fn load_reembed_texts(
+    raw_store: &dyn VectorStore,
+    collection: &str,
+    source: &str,
+    ids: &[String],
+) -> Result<(HashMap<String, String>, HashMap<String, VectorRecord>), String> {
+    let source_texts = chroma_sync::load_source_texts(collection, ids).unwrap_or_else(|e| {
+        eprintln!("⚠️ [reembed_collection] SQLiteからのテキスト取得に失敗しました（メタデータを使用します）: {}", e);
+        HashMap::new()
+    });
+
+    let mut texts = HashMap::new();
+    let mut records = HashMap::new();
+    for id in ids {
+        let Some(record) = raw_store.get(source.to_string(), id.clone()).await? else {
+            continue;
+        };
+        let text = source_texts.get(id).cloned().or_else(|| {
+            let metadata: HashMap<String, Value> = record.metadata.clone().into_iter().collect();
+            searchable_text_from_metadata(&metadata, METADATA_TEXT_KEYS)
+        });
+        if let Some(text) = text {
+            texts.insert(id.clone(), text);
+        }
+        records.insert(id.clone(), record);
+    }
+    Ok((texts, records))
+}
+
+/// 指定IDを旧コレクション（source）から新しい物理コレクション（target）へ再埋め込み
+/// テキストのないIDはpending_copiesに積み、copy_pendingで次元数を確認してからコピーする
+async fn reembed_ids(
+    raw_store: &dyn VectorStore,
+    service: &EmbeddingService,
+    collection: &str,
+    source: &str,
+    target: &str,
+    ids: &[String],
+    state: &mut ReembedState,
+) -> Result<(), String> {
+    for chunk in ids.chunks(REEMBED_BATCH_SIZE) {
+        let (texts, mut records) = load_reembed_texts(raw_store, collection, source, chunk).await?;
+        for (id, record) in &records {
+            state.fingerprints.insert(id.clone(), record_fingerprint(record));
+        }
+        let targets: Vec<&String> = chunk.iter().filter(|id| texts.contains_key(*id)).collect();
+        state.pending_copies.extend(
+            chunk.iter().filter(|id| !texts.contains_key(*id)).filter_map(|id| records.remove(id)),
+        );
+        if targets.is_empty() {
+            continue;
+        }
+
+        let embeddings = service.embed_texts(targets.iter().map(|id| texts[*id].clone()).collect()).await?;
+        for (id, embedding) in targets.into_iter().zip(embeddings) {
+            match state.dimension {
+                Some(d) if d != embedding.len() => {
+                    return Err(format!("モデルの出力次元数が一定ではありません: {} / {}", d, embedding.len()));
+                }
+                _ => state.dimension = Some(embedding.len()),
+            }
+            let metadata = records.remove(id).map(|record| record.metadata).unwrap_or_default();
+            raw_store.upsert(target.to_string(), id.clone(), embedding, metadata).await?;
+            state.copied.remove(id);
+            state.reembedded.insert(id.clone());
+        }
+    }
+    Ok(())
+}
+
+/// テキストのないIDの旧ベクトルを新しいコレクションにそのままコピー
+/// 旧ベクトルの次元数が新しいモデルと異なる場合は、次元数の混在を避けるためエラーにする
+async fn copy_pending(
+    raw_store: &dyn VectorStore,
+    target: &str,
+    dimension: usize,
+    state: &mut ReembedState,
+) -> Result<(), String> {
+    let mismatched: Vec<&str> = state.pending_copies
+        .iter()
+        .filter(|record| record.embedding.len() != dimension)
+        .map(|record| record.id.as_str())
+        .collect();
+    if !mismatched.is_empty() {
+        return Err(format!(
+            "埋め込み対象のテキストがないID（{}件: {}）の旧ベクトルは新しいモデルの埋め込み（{}次元）と次元数が異なるため、コピーできません",
+            mismatched.len(),
+            mismatched.join(", "),
+            dimension
+        ));
+    }
+    for record in std::mem::take(&mut state.pending_copies) {
+        state.reembedded.remove(&record.id);
+        state.copied.insert(record.id.clone());
+        raw_store.upsert(target.to_string(), record.id, record.embedding, record.metadata).await?;
+    }
+    Ok(())
+}
+
+/// 読み取り後に旧コレクションで追加・更新・削除されたIDを新しいコレクションに反映し、変更のあったIDを返す
+async fn catch_up(
+    raw_store: &dyn VectorStore,
+    service: &EmbeddingService,
+    collection: &str,
+    source: &str,
+    target: &str,
+    state: &mut ReembedState,
+) -> Result<Vec<String>, String> {
+    let current_ids = raw_store.list_ids(source.to_string()).await?;
+    let mut changed = Vec::new();
+    for id in &current_ids {
+        let Some(record) = raw_store.get(source.to_string(), id.clone()).await? else {
+            continue;
+        };
+        if state.fingerprints.get(id) != Some(&record_fingerprint(&record)) {
+            changed.push(id.clone());
+        }
+    }
+    if !changed.is_empty() {
+        reembed_ids(raw_store, service, collection, source, target, &changed, state).await?;
+    }
+
+    let current: HashSet<&String> = current_ids.iter().collect();
+    let removed: Vec<String> = state.fingerprints.keys().filter(|id| !current.contains(id)).cloned().collect();
+    if !removed.is_empty() {
+        raw_store.delete(target.to_string(), removed.clone()).await?;
+        for id in &removed {
+            state.fingerprints.remove(id);
+            state.reembedded.remove(id);
+            state.copied.remove(id);
+        }
+    }
+    changed.extend(removed);
+    Ok(changed)
+}
+
+/// コレクション全体を指定モデルで再埋め込みし、新しい物理コレクションに切り替える
+///
+/// - テキストが見つからないIDは旧ベクトルをそのままコピーする（次元数が異なる場合は切り替えずに中止）
+/// - 再埋め込み中に旧コレクションへ書き込まれたIDは、切り替えの前後で新しいコレクションに反映し、
+///   SQLiteの行を未同期に戻して次回のChromaDB同期でも作り直す
+/// - 旧コレクションは、新しいコレクションに旧コレクションの全IDが揃ったことを確認してから削除する
+pub async fn reembed_collection(
+    collection: String,
+    provider: Option<String>,
+    model: Option<String>,
+) -> Result<ReembedReport, String> {
+    if is_versioned_name(&collection) {
+        return Err(format!("論理コレクション名を指定してください: {}", collection));
+    }
+    // 旧・新どちらの物理コレクションも物理名で直接操作する
+    let raw_store = raw_vector_store()?;
+    let mut service = EmbeddingService::from_ai_settings(provider.as_deref())?;
+    if let Some(model) = model {
+        service = service.with_model(model);
+    }
+
+    let current = with_registry(|registry| Ok(registry.collections.get(&collection).cloned()))?;
+    let previous_physical = current
+        .as_ref()
+        .map(|info| info.physical_collection.clone())
+        .unwrap_or_else(|| collection.clone());
+    let version = current.as_ref().map(|info| info.version).unwrap_or(1) + 1;
+    let target = format!("{}{}{}", collection, VERSION_SEPARATOR, version);
+
+    eprintln!(
+        "🔄 [reembed_collection] {} を再埋め込みします: {} → {} (provider={}, model={})",
+        collection, previous_physical, target, service.provider(), service.model()
+    );
+
+    // 前回失敗した再埋め込みの残りを削除してから開始
+    raw_store.delete_collection(target.clone()).await?;
+
+    let mut state = ReembedState::default();
+    let mut resync_ids: HashSet<String> = HashSet::new();
+    let prepared: Result<usize, String> = async {
+        let ids = raw_store.list_ids(previous_physical.clone()).await?;
+        reembed_ids(&*raw_store, &service, &collection, &previous_physical, &target, &ids, &mut state).await?;
+        resync_ids.extend(catch_up(&*raw_store, &service, &collection, &previous_physical, &target, &mut state).await?);
+        let dimension = state.dimension
+            .ok_or_else(|| format!("コレクション '{}' に再埋め込みできるデータがありません", collection))?;
+        copy_pending(&*raw_store, &target, dimension, &mut state).await?;
+        Ok(dimension)
+    }.await;
+    let dimension = match prepared {
+        Ok(dimension) => dimension,
+        Err(e) => {
+            if let Err(cleanup) = raw_store.delete_collection(target.clone()).await {
+                eprintln!("⚠️ [reembed_collection] 再埋め込み途中のコレクション {} の削除に失敗しました: {}", target, cleanup);
+            }
+            return Err(e);
+        }
+    };
+
+    // レジストリを切り替え（以降の保存・検索は新しいコレクションを使う）
+    let info = EmbeddingCollectionInfo {
+        collection: collection.clone(),
+        physical_collection: target.clone(),
+        provider: Some(service.provider().to_string()),
+        model: Some(service.model().to_string()),
+        dimension,
+        version,
+        updated_at: get_timestamp(),
+    };
+    with_registry(|registry| {
+        registry.collections.insert(collection.clone(), info);
+        registry.save()
+    })?;
+
+    // 切り替え直前に旧コレクションへ書き込まれた分を反映（変更がなくなるまで）
+    let mut verified = false;
+    for _ in 0..MAX_CATCH_UP_ROUNDS {
+        match catch_up(&*raw_store, &service, &collection, &previous_physical, &target, &mut state).await {
+            Ok(changed) if changed.is_empty() => {
+                verified = true;
+                break;
+            }
+            Ok(changed) => resync_ids.extend(changed),
+            Err(e) => {
+                eprintln!("⚠️ [reembed_collection] 切り替え後の差分の反映に失敗しました: {}", e);
+                break;
+            }
+        }
+    }
+    if let Err(e) = copy_pending(&*raw_store, &target, dimension, &mut state).await {
+        eprintln!("⚠️ [reembed_collection] {}", e);
+        verified = false;
+    }
+
+    let mut resync_ids: Vec<String> = resync_ids.into_iter().collect();
+    resync_ids.sort();
+    if !resync_ids.is_empty() {
+        match chroma_sync::mark_unsynced(&collection, &resync_ids) {
+            Ok(count) => eprintln!("🔁 [reembed_collection] 再埋め込み中に書き込まれた{}件を未同期に戻しました", count),
+            Err(e) => eprintln!("⚠️ [reembed_collection] 同期状態の更新に失敗しました: {}", e),
+        }
+    }
+
+    // 新しいコレクションに旧コレクションの全IDが揃っている場合のみ旧コレクションを削除
+    if verified {
+        let previous_ids = raw_store.list_ids(previous_physical.clone()).await?;
+        let target_ids: HashSet<String> = raw_store.list_ids(target.clone()).await?.into_iter().collect();
+        let missing = previous_ids.iter().filter(|id| !target_ids.contains(*id)).count();
+        if missing > 0 {
+            eprintln!("⚠️ [reembed_collection] 新しいコレクションに{}件のIDがありません", missing);
+            verified = false;
+        }
+    }
+    let previous_collection_deleted = verified && match raw_store.delete_collection(previous_physical.clone()).await {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("⚠️ [reembed_collection] 旧コレクション {} の削除に失敗しました: {}", previous_physical, e);
+            false
+        }
+    };
+    if !verified {
+        eprintln!("⚠️ [reembed_collection] 検証できなかったため、旧コレクション {} を残しました", previous_physical);
+    }
+
+    let mut copied_ids: Vec<String> = state.copied.into_iter().collect();
+    copied_ids.sort();
+    eprintln!(
+        "✅ [reembed_collection] {} の再埋め込みが完了しました: {}件（コピー: {}件）, {}次元",
+        collection, state.reembedded.len(), copied_ids.len(), dimension
+    );
+    Ok(ReembedReport {
+        collection,
+        previous_physical_collection: previous_physical,
+        physical_collection: target,
+        provider: service.provider().to_string(),
+        model: service.model().to_string(),
+        dimension,
+        version,
+        reembedded: state.reembedded.len(),
+        copied_ids,
+        resync_ids,
+        previous_collection_deleted,
+    })
+}
@@ -0,0 +1,709 @@
+/**
+ * エンティティの名寄せ（重複候補の検出と統合）
+ * 議事録からのトピック抽出で「ITOCHU」と「伊藤忠商事」のような同一実体のエンティティが別々に作られるため、
+ * 重複候補を検出し、1つのエンティティに統合する
+ *
+ * - 重複候補: 正規化した名前の一致、名前とエイリアス（aliases）の一致、（任意で）埋め込みの類似度を組み合わせてスコア付け
+ * - 統合: リレーションの付け替え、エイリアスの和集合、メタデータのマージ、統合されたエンティティとベクトルの削除を行い、
+ *   統合前のエンティティをentityMergeAuditテーブルに記録する
+ */
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use crate::database::{get_db, get_timestamp};
+use super::vector_search::{self, vector_store, VectorStore, COLLECTION_ENTITIES, COLLECTION_RELATIONS};
+
+/// 重複候補のデフォルト件数・最大件数
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+/// 重複候補として返す最低スコア
+const DEFAULT_MIN_SCORE: f64 = 0.8;
+
+/// 埋め込みの類似度で重複とみなす下限
+const DEFAULT_EMBEDDING_THRESHOLD: f64 = 0.92;
+
+/// 埋め込みで比較する近傍の件数・エンティティ数の上限
+const EMBEDDING_NEIGHBORS: usize = 5;
+const MAX_EMBEDDING_ENTITIES: usize = 2000;
+
+/// 同じキーを持つエンティティがこれより多い場合は一般的すぎる語として比較しない
+const MAX_BUCKET_SIZE: usize = 50;
+
+/// シグナルごとのスコア
+const SCORE_SAME_NAME: f64 = 1.0;
+const SCORE_NAME_IN_ALIASES: f64 = 0.95;
+const SCORE_ALIAS_OVERLAP_BASE: f64 = 0.8;
+
+/// 正規化時に取り除く法人格の表記
+const JA_CORPORATE_AFFIXES: &[&str] = &["株式会社", "有限会社", "合同会社", "(株)", "(有)", "㈱", "㈲"];
+const EN_CORPORATE_SUFFIXES: &[&str] = &["inc", "incorporated", "corp", "corporation", "co", "ltd", "limited", "llc", "plc", "gmbh"];
+
+/// 重複候補の検索条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DuplicateEntityOptions {
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+    /// 同じtypeのエンティティ同士のみ比較するか（デフォルトtrue）
+    #[serde(rename = "sameTypeOnly")]
+    pub same_type_only: Option<bool>,
+    /// エンティティコレクションの埋め込みの類似度も使うか（デフォルトfalse）
+    #[serde(rename = "useEmbeddings")]
+    pub use_embeddings: Option<bool>,
+    /// 埋め込みの類似度で重複とみなす下限（デフォルト0.92）
+    #[serde(rename = "embeddingThreshold")]
+    pub embedding_threshold: Option<f64>,
+    /// 重複候補として返す最低スコア（デフォルト0.8）
+    #[serde(rename = "minScore")]
+    pub min_score: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+/// 重複候補のエンティティの概要
+#[derive(Debug, Clone, Serialize)]
+pub struct EntitySummary {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "displayName")]
+    pub display_name: Option<String>,
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    pub aliases: Vec<String>,
+    #[serde(rename = "relationCount")]
+    pub relation_count: usize,
+}
+
+/// 重複候補のペア
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateEntityCandidate {
+    pub entity: EntitySummary,
+    pub duplicate: EntitySummary,
+    /// 0〜1のスコア（シグナルごとのスコアの最大値）
+    pub score: f64,
+    /// 一致したシグナル（sameName, nameInAliases, aliasOverlap, embedding）
+    pub reasons: Vec<String>,
+    #[serde(rename = "embeddingSimilarity")]
+    pub embedding_similarity: Option<f64>,
+}
+
+/// エンティティの統合条件
+#[derive(Debug, Clone, Deserialize)]
+pub struct MergeEntitiesOptions {
+    /// 残すエンティティ
+    #[serde(rename = "survivorEntityId")]
+    pub survivor_entity_id: String,
+    /// 統合して削除するエンティティ
+    #[serde(rename = "mergedEntityIds")]
+    pub merged_entity_ids: Vec<String>,
+    /// 監査ログに残す統合理由
+    pub reason: Option<String>,
+}
+
+/// エンティティの統合結果
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityMergeReport {
+    #[serde(rename = "survivorEntityId")]
+    pub survivor_entity_id: String,
+    #[serde(rename = "mergedEntityIds")]
+    pub merged_entity_ids: Vec<String>,
+    /// 付け替えたリレーション数
+    #[serde(rename = "relationsRewired")]
+    pub relations_rewired: usize,
+    /// 統合で自己ループになったため削除したリレーション数
+    #[serde(rename = "relationsRemoved")]
+    pub relations_removed: usize,
+    /// 統合後のエイリアス
+    pub aliases: Vec<String>,
+    #[serde(rename = "auditIds")]
+    pub audit_ids: Vec<i64>,
+}
+
+/// 統合の監査ログ
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityMergeAuditEntry {
+    pub id: i64,
+    #[serde(rename = "survivorEntityId")]
+    pub survivor_entity_id: String,
+    #[serde(rename = "mergedEntityId")]
+    pub merged_entity_id: String,
+    /// 統合前のエンティティ（JSON）
+    #[serde(rename = "mergedEntity")]
+    pub merged_entity: Value,
+    #[serde(rename = "rewiredRelationIds")]
+    pub rewired_relation_ids: Vec<String>,
+    #[serde(rename = "removedRelationIds")]
+    pub removed_relation_ids: Vec<String>,
+    pub reason: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+/// SQLiteから読み込んだエンティティ
+#[derive(Debug, Clone)]
+struct EntityRow {
+    id: String,
+    name: String,
+    entity_type: String,
+    aliases: Option<String>,
+    metadata: Option<String>,
+    organization_id: Option<String>,
+    company_id: Option<String>,
+    searchable_text: Option<String>,
+    display_name: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+const ENTITY_COLUMNS: &str =
+    "id, name, type, aliases, metadata, organizationId, companyId, searchableText, displayName, createdAt, updatedAt";
+
+fn read_entity(row: &rusqlite::Row) -> SqlResult<EntityRow> {
+    Ok(EntityRow {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        entity_type: row.get(2)?,
+        aliases: row.get(3)?,
+        metadata: row.get(4)?,
+        organization_id: row.get(5)?,
+        company_id: row.get(6)?,
+        searchable_text: row.get(7)?,
+        display_name: row.get(8)?,
+        created_at: row.get(9)?,
+        updated_at: row.get(10)?,
+    })
+}
+
+impl EntityRow {
+    fn alias_list(&self) -> Vec<String> {
+        parse_aliases(self.aliases.as_deref())
+    }
+
+    /// ベクトルストアのコレクションの組織ID（事業会社の場合はcompanyId）
+    fn scope_id(&self) -> String {
+        self.organization_id.clone().or_else(|| self.company_id.clone()).unwrap_or_default()
+    }
+
+    fn to_json(&self) -> Value {
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "type": self.entity_type,
+            "aliases": self.aliases,
+            "metadata": self.metadata,
+            "organizationId": self.organization_id,
+            "companyId": self.company_id,
+            "searchableText": self.searchable_text,
+            "displayName": self.display_name,
+            "createdAt": self.created_at,
+            "updatedAt": self.updated_at,
+        })
+    }
+}
+
+/// aliasesカラム（JSON配列の文字列）を読み込む（JSONでない場合は1つのエイリアスとして扱う）
+fn parse_aliases(aliases: Option<&str>) -> Vec<String> {
+    let Some(raw) = aliases.map(str::trim).filter(|s| !s.is_empty()) else {
+        return Vec::new();
+    };
+    match serde_json::from_str::<Value>(raw) {
+        Ok(Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|v| v.as_str().map(|s| s.trim().to_string()))
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Ok(Value::String(s)) if !s.trim().is_empty() => vec![s.trim().to_string()],
+        Ok(_) => Vec::new(),
+        Err(_) => vec![raw.to_string()],
+    }
+}
+
+/// 名前を比較用に正規化（全角英数の半角化・小文字化・法人格と記号の除去）
+fn normalize_entity_name(name: &str) -> String {
+    let mut text: String = name
+        .chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .flat_map(char::to_lowercase)
+        .collect();
+    for affix in JA_CORPORATE_AFFIXES {
+        text = text.replace(affix, " ");
+    }
+
+    let tokens: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect();
+    // 英語の法人格は末尾の語のみ除去（名前全体が法人格の語の場合は残す）
+    let mut end = tokens.len();
+    while end > 1 && EN_CORPORATE_SUFFIXES.contains(&tokens[end - 1].as_str()) {
+        end -= 1;
+    }
+    tokens[..end].concat()
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+fn load_scope_entities(conn: &Connection, options: &DuplicateEntityOptions) -> SqlResult<Vec<EntityRow>> {
+    let mut conditions = vec!["1 = 1".to_string()];
+    let mut params = Vec::new();
+    if let Some(organization_id) = options.organization_id.as_ref().filter(|s| !s.is_empty()) {
+        conditions.push("organizationId = ?".to_string());
+        params.push(SqlValue::Text(organization_id.clone()));
+    }
+    if let Some(company_id) = options.company_id.as_ref().filter(|s| !s.is_empty()) {
+        conditions.push("companyId = ?".to_string());
+        params.push(SqlValue::Text(company_id.clone()));
+    }
+    let sql = format!("SELECT {} FROM entities WHERE {} ORDER BY id", ENTITY_COLUMNS, conditions.join(" AND "));
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(params), read_entity)?;
+    rows.collect()
+}
+
+/// エンティティごとのリレーション数
+fn load_relation_counts(conn: &Connection) -> SqlResult<HashMap<String, usize>> {
+    let mut stmt = conn.prepare(
+        "SELECT entityId, COUNT(*) FROM (
+            SELECT sourceEntityId AS entityId FROM relations WHERE sourceEntityId IS NOT NULL
+            UNION ALL
+            SELECT targetEntityId AS entityId FROM relations WHERE targetEntityId IS NOT NULL
+         ) GROUP BY entityId",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))?;
+    rows.collect()
+}
+
+/// 比較に使うエンティティの正規化済みの名前・エイリアス
+struct EntityKeys {
+    names: HashSet<String>,
+    aliases: HashSet<String>,
+}
+
+fn entity_keys(entity: &EntityRow) -> EntityKeys {
+    let names: HashSet<String> = std::iter::once(entity.name.as_str())
+        .chain(entity.display_name.as_deref())
+        .map(normalize_entity_name)
+        .filter(|k| !k.is_empty())
+        .collect();
+    let aliases: HashSet<String> = entity
+        .alias_list()
+        .iter()
+        .map(|a| normalize_entity_name(a))
+        .filter(|k| !k.is_empty())
+        .collect();
+    EntityKeys { names, aliases }
+}
+
+/// 候補ペアのシグナル
+#[derive(Default)]
+struct PairSignals {
+    score: f64,
+    reasons: Vec<String>,
+    embedding_similarity: Option<f64>,
+}
+
+impl PairSignals {
+    fn add(&mut self, reason: &str, score: f64) {
+        if !self.reasons.iter().any(|r| r == reason) {
+            self.reasons.push(reason.to_string());
+        }
+        self.score = self.score.max(score);
+    }
+}
+
+/// 名前とエイリアスのシグナルを計算
+fn name_signals(a: &EntityKeys, b: &EntityKeys, signals: &mut PairSignals) {
+    if !a.names.is_disjoint(&b.names) {
+        signals.add("sameName", SCORE_SAME_NAME);
+    }
+    if !a.names.is_disjoint(&b.aliases) || !b.names.is_disjoint(&a.aliases) {
+        signals.add("nameInAliases", SCORE_NAME_IN_ALIASES);
+    }
+    let shared = a.aliases.intersection(&b.aliases).count();
+    if shared > 0 {
+        let union = a.aliases.union(&b.aliases).count();
+        let jaccard = shared as f64 / union as f64;
+        signals.add("aliasOverlap", SCORE_ALIAS_OVERLAP_BASE + (1.0 - SCORE_ALIAS_OVERLAP_BASE) * jaccard * 0.5);
+    }
+}
+
+/// エンティティコレクションの埋め込みで近傍を検索し、類似度がしきい値以上のペアを返す
+async fn embedding_pairs(
+    store: &dyn VectorStore,
+    entities: &[EntityRow],
+    index: &HashMap<String, usize>,
+    threshold: f64,
+) -> Result<HashMap<(usize, usize), f64>, String> {
+    let mut pairs = HashMap::new();
+    for (i, entity) in entities.iter().enumerate().take(MAX_EMBEDDING_ENTITIES) {
+        let collection = vector_search::org_collection_name(COLLECTION_ENTITIES, &entity.scope_id());
+        let Some(record) = store.get(collection.clone(), entity.id.clone()).await? else {
+            continue;
+        };
+        let matches = store.query(collection, record.embedding, EMBEDDING_NEIGHBORS + 1, None).await?;
+        for m in matches {
+            let similarity = m.similarity as f64;
+            if similarity < threshold {
+                continue;
+            }
+            let Some(&j) = index.get(&m.id) else { continue };
+            if i == j {
+                continue;
+            }
+            let key = (i.min(j), i.max(j));
+            let entry = pairs.entry(key).or_insert(similarity);
+            *entry = entry.max(similarity);
+        }
+    }
+    Ok(pairs)
+}
+
+fn summary(entity: &EntityRow, relation_counts: &HashMap<String, usize>) -> EntitySummary {
+    EntitySummary {
+        id: entity.id.clone(),
+        name: entity.name.clone(),
+        display_name: entity.display_name.clone(),
+        entity_type: entity.entity_type.clone(),
+        aliases: entity.alias_list(),
+        relation_count: relation_counts.get(&entity.id).copied().unwrap_or(0),
+    }
+}
+
+/// 重複の可能性が高いエンティティのペアをスコアの高い順に取得
+/// 各ペアのentityはリレーション数が多い方（統合先の候補）
+pub async fn find_duplicate_entities(options: DuplicateEntityOptions) -> Result<Vec<DuplicateEntityCandidate>, String> {
+    let same_type_only = options.same_type_only.unwrap_or(true);
+    let min_score = options.min_score.unwrap_or(DEFAULT_MIN_SCORE);
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    // 注意: DB接続を保持したまま.awaitしないよう、必要なデータを先に読み込む
+    let (entities, relation_counts) = {
+        let conn = connection()?;
+        let entities = load_scope_entities(&conn, &options)
+            .map_err(|e| format!("エンティティの取得に失敗しました: {}", e))?;
+        let counts = load_relation_counts(&conn)
+            .map_err(|e| format!("リレーション数の取得に失敗しました: {}", e))?;
+        (entities, counts)
+    };
+    let keys: Vec<EntityKeys> = entities.iter().map(entity_keys).collect();
+    let index: HashMap<String, usize> = entities.iter().enumerate().map(|(i, e)| (e.id.clone(), i)).collect();
+
+    // 名前・エイリアスのキーが共通するエンティティ同士のみ比較する
+    let mut buckets: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        for k in key.names.iter().chain(key.aliases.iter()) {
+            buckets.entry(k.as_str()).or_default().push(i);
+        }
+    }
+    let mut signals: HashMap<(usize, usize), PairSignals> = HashMap::new();
+    for members in buckets.values() {
+        if members.len() < 2 || members.len() > MAX_BUCKET_SIZE {
+            continue;
+        }
+        for (n, &a) in members.iter().enumerate() {
+            for &b in &members[n + 1..] {
+                if a == b {
+                    continue;
+                }
+                let key = (a.min(b), a.max(b));
+                if signals.contains_key(&key) {
+                    continue;
+                }
+                let mut pair = PairSignals::default();
+                name_signals(&keys[key.0], &keys[key.1], &mut pair);
+                signals.insert(key, pair);
+            }
+        }
+    }
+
+    if options.use_embeddings.unwrap_or(false) {
+        let threshold = options.embedding_threshold.unwrap_or(DEFAULT_EMBEDDING_THRESHOLD);
+        let store = vector_store()?;
+        match embedding_pairs(store.as_ref(), &entities, &index, threshold).await {
+            Ok(pairs) => {
+                for (key, similarity) in pairs {
+                    let pair = signals.entry(key).or_default();
+                    pair.embedding_similarity = Some(similarity);
+                    pair.add("embedding", similarity);
+                }
+            }
+            Err(e) => eprintln!("⚠️ [find_duplicate_entities] 埋め込みによる比較に失敗しました（名前・エイリアスのみで判定します）: {}", e),
+        }
+    }
+
+    let mut candidates: Vec<DuplicateEntityCandidate> = signals
+        .into_iter()
+        .filter(|((a, b), pair)| {
+            pair.score >= min_score && (!same_type_only || entities[*a].entity_type == entities[*b].entity_type)
+        })
+        .map(|((a, b), pair)| {
+            let count = |i: usize| relation_counts.get(&entities[i].id).copied().unwrap_or(0);
+            let (first, second) = if count(b) > count(a) { (b, a) } else { (a, b) };
+            DuplicateEntityCandidate {
+                entity: summary(&entities[first], &relation_counts),
+                duplicate: summary(&entities[second], &relation_counts),
+                score: pair.score,
+                reasons: pair.reasons,
+                embedding_similarity: pair.embedding_similarity,
+            }
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.entity.id.cmp(&b.entity.id))
+            .then_with(|| a.duplicate.id.cmp(&b.duplicate.id))
+    });
+    candidates.truncate(limit);
+    Ok(candidates)
+}
+
+fn load_entity(conn: &Connection, id: &str) -> Result<EntityRow, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM entities WHERE id = ?1", ENTITY_COLUMNS),
+        params![id],
+        read_entity,
+    )
+    .optional()
+    .map_err(|e| format!("エンティティの取得に失敗しました: {}", e))?
+    .ok_or_else(|| format!("エンティティが見つかりません: {}", id))
+}
+
+/// エイリアスの和集合（正規化して重複を除き、残すエンティティの名前自体は含めない）
+fn merge_aliases(survivor: &EntityRow, merged: &[EntityRow]) -> Vec<String> {
+    let mut seen: HashSet<String> = HashSet::from([normalize_entity_name(&survivor.name)]);
+    let mut aliases = Vec::new();
+    let candidates = survivor.alias_list().into_iter().chain(merged.iter().flat_map(|entity| {
+        std::iter::once(entity.name.clone())
+            .chain(entity.display_name.clone())
+            .chain(entity.alias_list())
+    }));
+    for alias in candidates {
+        let key = normalize_entity_name(&alias);
+        if key.is_empty() || !seen.insert(key) {
+            continue;
+        }
+        aliases.push(alias);
+    }
+    aliases
+}
+
+/// メタデータのマージ（残すエンティティの値を優先し、無いキーのみ追加。オブジェクトは再帰的にマージ）
+fn merge_metadata_value(target: &mut Map<String, Value>, source: Map<String, Value>) {
+    for (key, value) in source {
+        match (target.get_mut(&key), value) {
+            (Some(Value::Object(existing)), Value::Object(incoming)) => merge_metadata_value(existing, incoming),
+            (Some(Value::Null), value) => {
+                target.insert(key, value);
+            }
+            (Some(_), _) => {}
+            (None, value) => {
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge_metadata(survivor: &EntityRow, merged: &[EntityRow]) -> Option<String> {
+    let parse = |raw: Option<&str>| -> Map<String, Value> {
+        raw.and_then(|s| serde_json::from_str::<Value>(s).ok())
+            .and_then(|v| match v {
+                Value::Object(map) => Some(map),
+                _ => None,
+            })
+            .unwrap_or_default()
+    };
+    let mut metadata = parse(survivor.metadata.as_deref());
+    for entity in merged {
+        merge_metadata_value(&mut metadata, parse(entity.metadata.as_deref()));
+    }
+    if metadata.is_empty() && survivor.metadata.is_none() {
+        return None;
+    }
+    Some(Value::Object(metadata).to_string())
+}
+
+/// 指定したエンティティを参照するリレーションのID
+fn relation_ids_referencing(conn: &Connection, entity_id: &str) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM relations WHERE sourceEntityId = ?1 OR targetEntityId = ?1 ORDER BY id",
+    )?;
+    let rows = stmt.query_map(params![entity_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// エンティティを統合する（1つのトランザクションで実行し、ベクトルの削除はコミット後に行う）
+pub async fn merge_entities(options: MergeEntitiesOptions) -> Result<EntityMergeReport, String> {
+    let survivor_id = options.survivor_entity_id.clone();
+    let mut merged_ids: Vec<String> = Vec::new();
+    for id in &options.merged_entity_ids {
+        if *id == survivor_id {
+            return Err("統合先のエンティティを統合対象に含めることはできません".to_string());
+        }
+        if !merged_ids.contains(id) {
+            merged_ids.push(id.clone());
+        }
+    }
+    if merged_ids.is_empty() {
+        return Err("統合するエンティティを指定してください".to_string());
+    }
+
+    let (report, scope_id, removed_relation_ids, removed_relation_scopes) = {
+        let conn = connection()?;
+        let survivor = load_entity(&conn, &survivor_id)?;
+        let mut merged = Vec::new();
+        for id in &merged_ids {
+            let entity = load_entity(&conn, id)?;
+            if entity.organization_id != survivor.organization_id || entity.company_id != survivor.company_id {
+                return Err(format!(
+                    "組織・事業会社が異なるエンティティは統合できません: {} と {}",
+                    survivor.id, entity.id
+                ));
+            }
+            merged.push(entity);
+        }
+
+        let aliases = merge_aliases(&survivor, &merged);
+        let metadata = merge_metadata(&survivor, &merged);
+        let now = get_timestamp();
+
+        let tx = conn.unchecked_transaction()
+            .map_err(|e| format!("トランザクションの開始に失敗しました: {}", e))?;
+        let sql_err = |e: rusqlite::Error| format!("エンティティの統合に失敗しました: {}", e);
+
+        let mut relations_rewired = 0;
+        let mut removed_relation_ids = Vec::new();
+        let mut removed_relation_scopes = Vec::new();
+        let mut audit_ids = Vec::new();
+        for entity in &merged {
+            let rewired = relation_ids_referencing(&tx, &entity.id).map_err(sql_err)?;
+            tx.execute(
+                "UPDATE relations SET sourceEntityId = ?1, chromaSynced = 0, updatedAt = ?3 WHERE sourceEntityId = ?2",
+                params![survivor.id, entity.id, now],
+            ).map_err(sql_err)?;
+            tx.execute(
+                "UPDATE relations SET targetEntityId = ?1, chromaSynced = 0, updatedAt = ?3 WHERE targetEntityId = ?2",
+                params![survivor.id, entity.id, now],
+            ).map_err(sql_err)?;
+
+            // 重複エンティティ同士のリレーションは統合で自己ループになるため削除
+            let mut removed = Vec::new();
+            for relation_id in &rewired {
+                let scope: Option<String> = tx.query_row(
+                    "SELECT COALESCE(organizationId, companyId, '') FROM relations
+                     WHERE id = ?1 AND sourceEntityId = ?2 AND targetEntityId = ?2",
+                    params![relation_id, survivor.id],
+                    |row| row.get(0),
+                ).optional().map_err(sql_err)?;
+                if let Some(scope) = scope {
+                    tx.execute("DELETE FROM relations WHERE id = ?1", params![relation_id]).map_err(sql_err)?;
+                    removed.push(relation_id.clone());
+                    removed_relation_scopes.push(scope);
+                }
+            }
+            let rewired_kept: Vec<String> = rewired.into_iter().filter(|id| !removed.contains(id)).collect();
+            relations_rewired += rewired_kept.len();
+
+            tx.execute("DELETE FROM entities WHERE id = ?1", params![entity.id]).map_err(sql_err)?;
+            tx.execute(
+                "INSERT INTO entityMergeAudit
+                    (survivorEntityId, mergedEntityId, mergedEntity, rewiredRelationIds, removedRelationIds, reason, createdAt)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    survivor.id,
+                    entity.id,
+                    entity.to_json().to_string(),
+                    Value::from(rewired_kept).to_string(),
+                    Value::from(removed.clone()).to_string(),
+                    options.reason,
+                    now,
+                ],
+            ).map_err(sql_err)?;
+            audit_ids.push(tx.last_insert_rowid());
+            removed_relation_ids.extend(removed);
+        }
+
+        // エイリアスが変わるため、残すエンティティは再埋め込みの対象にする
+        tx.execute(
+            "UPDATE entities SET aliases = ?1, metadata = ?2, chromaSynced = 0, updatedAt = ?3 WHERE id = ?4",
+            params![Value::from(aliases.clone()).to_string(), metadata, now, survivor.id],
+        ).map_err(sql_err)?;
+        tx.commit().map_err(|e| format!("トランザクションのコミットに失敗しました: {}", e))?;
+
+        let report = EntityMergeReport {
+            survivor_entity_id: survivor.id.clone(),
+            merged_entity_ids: merged_ids.clone(),
+            relations_rewired,
+            relations_removed: removed_relation_ids.len(),
+            aliases,
+            audit_ids,
+        };
+        (report, survivor.scope_id(), removed_relation_ids, removed_relation_scopes)
+    };
+
+    // ベクトルの削除に失敗しても統合は完了しているため続行（ChromaDB同期の孤立ベクトル削除で後から消える）
+    match vector_store() {
+        Ok(store) => {
+            let collection = vector_search::org_collection_name(COLLECTION_ENTITIES, &scope_id);
+            if let Err(e) = store.delete(collection, merged_ids.clone()).await {
+                eprintln!("⚠️ [merge_entities] 統合したエンティティのベクトル削除に失敗しました: {}", e);
+            }
+            let mut relations_by_scope: HashMap<String, Vec<String>> = HashMap::new();
+            for (id, scope) in removed_relation_ids.into_iter().zip(removed_relation_scopes) {
+                relations_by_scope.entry(scope).or_default().push(id);
+            }
+            for (scope, ids) in relations_by_scope {
+                let collection = vector_search::org_collection_name(COLLECTION_RELATIONS, &scope);
+                if let Err(e) = store.delete(collection, ids).await {
+                    eprintln!("⚠️ [merge_entities] 削除したリレーションのベクトル削除に失敗しました: {}", e);
+                }
+            }
+        }
+        Err(e) => eprintln!("⚠️ [merge_entities] ベクトルストアを取得できないため、ベクトルの削除をスキップしました: {}", e),
+    }
+
+    eprintln!(
+        "✅ [merge_entities] {}件のエンティティを {} に統合しました（リレーション付け替え: {}件, 削除: {}件）",
+        report.merged_entity_ids.len(), report.survivor_entity_id, report.relations_rewired, report.relations_removed
+    );
+    Ok(report)
+}
+
+/// 統合の監査ログを新しい順に取得（entityIdを指定した場合は統合先・統合元のどちらかが一致するもの）
+pub fn list_entity_merge_audit(entity_id: Option<String>) -> Result<Vec<EntityMergeAuditEntry>, String> {
+    let conn = connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, survivorEntityId, mergedEntityId, mergedEntity, rewiredRelationIds, removedRelationIds, reason, createdAt
+         FROM entityMergeAudit
+         WHERE ?1 IS NULL OR survivorEntityId = ?1 OR mergedEntityId = ?1
+         ORDER BY id DESC",
+    ).map_err(|e| format!("監査ログの取得に失敗しました: {}", e))?;
+    let parse_ids = |raw: String| -> Vec<String> { serde_json::from_str(&raw).unwrap_or_default() };
+    let rows = stmt.query_map(params![entity_id], |row| {
+        let merged_entity: String = row.get(3)?;
+        Ok(EntityMergeAuditEntry {
+            id: row.get(0)?,
+            survivor_entity_id: row.get(1)?,
+            merged_entity_id: row.get(2)?,
+            merged_entity: serde_json::from_str(&merged_entity).unwrap_or(Value::String(merged_entity)),
+            rewired_relation_ids: parse_ids(row.get(4)?),
+            removed_relation_ids: parse_ids(row.get(5)?),
+            reason: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    }).map_err(|e| format!("監査ログの取得に失敗しました: {}", e))?;
+    rows.collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| format!("監査ログの取得に失敗しました: {}", e))
+}
@@ -0,0 +1,483 @@
+/**
+ * 全文検索モジュール（SQLite FTS5）
+ * トピック・議事録・エンティティ・リレーション・Graphviz YAML・設計ドキュメントを
+ * 1つのFTS5仮想テーブル（fullTextSearch）に索引し、ベクトル検索が使えない環境でもキーワード検索を提供する
+ *
+ * - 索引はトリガーで元テーブルと同期される（INSERT/UPDATE/DELETE、Graphviz YAMLの履歴行は索引しない）
+ * - 日本語は単語の区切りが無いため、trigramトークナイザーで部分一致検索を行う
+ * - 3文字未満のキーワードはtrigramで検索できないため、LIKEによる部分一致にフォールバックする
+ * - 検索でヒットした行はsearchCount/lastSearchDateを更新する（カラムを持つテーブルのみ）
+ */
+
+use rusqlite::{params_from_iter, Connection, Result as SqlResult};
+use rusqlite::types::Value as SqlValue;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::database::{get_db, get_timestamp};
+
+/// FTS5仮想テーブル名
+const FTS_TABLE: &str = "fullTextSearch";
+
+/// ハイライトの開始・終了タグ
+const HIGHLIGHT_OPEN: &str = "<mark>";
+const HIGHLIGHT_CLOSE: &str = "</mark>";
+
+/// スニペットの前後に付ける省略記号
+const SNIPPET_ELLIPSIS: &str = "…";
+
+/// スニペットのトークン数（trigramでは1トークン≒1文字）
+const SNIPPET_TOKENS: i64 = 48;
+
+/// 検索結果のデフォルト件数・最大件数
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+
+/// 全文検索の索引対象テーブル定義
+/// 同期トリガーはマイグレーションで作成するため、式を変更する場合はトリガーを作り直すマイグレーションも追加する
+struct FullTextSource {
+    /// 検索結果に返す種別（topic, meetingNote, entity, relation, graphvizYamlFile, designDoc）
+    source_type: &'static str,
+    table: &'static str,
+    /// タイトルとして索引する式
+    title_expr: &'static str,
+    /// 本文として索引する式
+    body_expr: &'static str,
+    /// organizationId / companyId カラムの式（カラムが無いテーブルはNULL）
+    organization_expr: &'static str,
+    company_expr: &'static str,
+    /// searchCount / lastSearchDate カラムを持つか
+    tracks_search_stats: bool,
+    /// 索引する行の条件（履歴行など検索対象にしない行を除外する）
+    row_filter: Option<&'static str>,
+}
+
+static SOURCES: &[FullTextSource] = &[
+    FullTextSource {
+        source_type: "topic",
+        table: "topics",
+        title_expr: "title",
+        body_expr: "COALESCE(searchableText, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(content, '')",
+        organization_expr: "organizationId",
+        company_expr: "companyId",
+        tracks_search_stats: true,
+        row_filter: None,
+    },
+    FullTextSource {
+        source_type: "meetingNote",
+        table: "meetingNotes",
+        title_expr: "title",
+        body_expr: "COALESCE(description, '') || ' ' || COALESCE(content, '')",
+        organization_expr: "organizationId",
+        company_expr: "companyId",
+        tracks_search_stats: false,
+        row_filter: None,
+    },
+    FullTextSource {
+        source_type: "entity",
+        table: "entities",
+        title_expr: "COALESCE(displayName, name)",
+        body_expr: "COALESCE(searchableText, '') || ' ' || type",
+        organization_expr: "organizationId",
+        company_expr: "companyId",
+        tracks_search_stats: true,
+        row_filter: None,
+    },
+    FullTextSource {
+        source_type: "relation",
+        table: "relations",
+        title_expr: "relationType",
+        body_expr: "COALESCE(searchableText, '')",
+        organization_expr: "organizationId",
+        company_expr: "companyId",
+        tracks_search_stats: true,
+        row_filter: None,
+    },
+    FullTextSource {
+        source_type: "graphvizYamlFile",
+        table: "graphvizYamlFiles",
+        title_expr: "name",
+        body_expr: "COALESCE(searchableText, '') || ' ' || COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(contentSummary, '')",
+        organization_expr: "organizationId",
+        company_expr: "NULL",
+        tracks_search_stats: true,
+        // 履歴行（parentYamlFileIdが現在の行を指す）は索引しない
+        row_filter: Some("parentYamlFileId IS NULL"),
+    },
+    FullTextSource {
+        source_type: "designDoc",
+        table: "designDocSections",
+        title_expr: "title",
+        body_expr: "COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(summary, '') || ' ' || COALESCE(content, '')",
+        organization_expr: "NULL",
+        company_expr: "NULL",
+        tracks_search_stats: false,
+        row_filter: None,
+    },
+];
+
+/// 全文検索の条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FullTextSearchOptions {
+    pub query: String,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+    /// 検索対象の種別（未指定の場合は全種別）
+    #[serde(rename = "sourceTypes")]
+    pub source_types: Option<Vec<String>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
+
+/// 全文検索のヒット
+#[derive(Debug, Clone, Serialize)]
+pub struct FullTextSearchHit {
+    #[serde(rename = "sourceType")]
+    pub source_type: String,
+    pub id: String,
+    /// キーワードを<mark>で囲んだタイトル
+    pub title: String,
+    /// キーワード周辺を切り出し、<mark>で囲んだ本文
+    pub snippet: String,
+    /// 関連度スコア（bm25の符号を反転した値。大きいほど関連度が高い）
+    pub score: f64,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+}
+
+fn misuse(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+        Some(message),
+    )
+}
+
+/// 索引を元テーブルから作り直す（索引した行数を返す）
+fn rebuild_index(conn: &Connection) -> SqlResult<usize> {
+    conn.execute(&format!("DELETE FROM {}", FTS_TABLE), [])?;
+
+    let mut total = 0;
+    for source in SOURCES {
+        total += conn.execute(
+            &format!(
+                "INSERT INTO {fts} (sourceType, sourceId, organizationId, companyId, title, body)
+                 SELECT '{source_type}', id, {org}, {company}, {title}, {body} FROM {table}{filter}",
+                fts = FTS_TABLE,
+                source_type = source.source_type,
+                org = source.organization_expr,
+                company = source.company_expr,
+                title = source.title_expr,
+                body = source.body_expr,
+                table = source.table,
+                filter = source.row_filter.map(|f| format!(" WHERE {}", f)).unwrap_or_default(),
+            ),
+            [],
+        )?;
+    }
+    conn.execute(&format!("INSERT INTO {fts}({fts}) VALUES('optimize')", fts = FTS_TABLE), [])?;
+    Ok(total)
+}
+
+/// 全文検索の索引を再構築（索引した行数を返す）
+pub fn rebuild_full_text_index() -> SqlResult<usize> {
+    let db = get_db().ok_or_else(|| misuse("データベースが初期化されていません".to_string()))?;
+    let conn = db.get_connection()?;
+    let tx = conn.unchecked_transaction()?;
+    let total = rebuild_index(&tx)?;
+    tx.commit()?;
+    Ok(total)
+}
+
+/// 検索キーワードを分解したもの
+struct ParsedQuery {
+    /// trigramで検索できるキーワード（3文字以上）
+    match_terms: Vec<String>,
+    /// LIKEで検索するキーワード（3文字未満）
+    like_terms: Vec<String>,
+}
+
+/// 空白区切りのキーワードを分解（すべてのキーワードを含む行がヒットする）
+fn parse_query(query: &str) -> ParsedQuery {
+    let mut parsed = ParsedQuery { match_terms: Vec::new(), like_terms: Vec::new() };
+    for term in query.split_whitespace() {
+        // FTS5のフレーズ記法を使うため、ダブルクォートは検索語から除く
+        let term = term.replace('"', "");
+        if term.is_empty() {
+            continue;
+        }
+        if term.chars().count() >= 3 {
+            parsed.match_terms.push(term);
+        } else {
+            parsed.like_terms.push(term);
+        }
+    }
+    parsed
+}
+
+/// FTS5のMATCH式を作成（各キーワードをフレーズとして扱い、AND条件で結合）
+fn match_expression(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|t| format!("\"{}\"", t))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// LIKEのワイルドカードをエスケープ
+fn like_pattern(term: &str) -> String {
+    let escaped = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// キーワードを<mark>で囲む（MATCHを使わない検索のハイライト用、大文字小文字は区別しない）
+fn highlight_terms(text: &str, terms: &[String]) -> String {
+    let lower: Vec<char> = text.chars().flat_map(|c| c.to_lowercase()).collect();
+    let chars: Vec<char> = text.chars().collect();
+    // to_lowercaseで文字数が変わる場合は位置がずれるため、ハイライトしない
+    if lower.len() != chars.len() {
+        return text.to_string();
+    }
+    let needles: Vec<Vec<char>> = terms
+        .iter()
+        .map(|t| t.chars().flat_map(|c| c.to_lowercase()).collect::<Vec<char>>())
+        .filter(|n| !n.is_empty())
+        .collect();
+
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let hit = needles
+            .iter()
+            .filter(|n| lower[i..].starts_with(n))
+            .map(|n| n.len())
+            .max();
+        match hit {
+            Some(len) => {
+                result.push_str(HIGHLIGHT_OPEN);
+                result.extend(&chars[i..i + len]);
+                result.push_str(HIGHLIGHT_CLOSE);
+                i += len;
+            }
+            None => {
+                result.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    result
+}
+
+/// FTS5のhighlight()/snippet()で<mark>済みのテキストにキーワードのハイライトを追加
+/// 既存の<mark>…</mark>はそのまま残し、その外側のテキストだけをハイライトする（タグの中の文字に一致させない）
+fn highlight_marked_terms(text: &str, terms: &[String]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find(HIGHLIGHT_OPEN) {
+        result.push_str(&highlight_terms(&rest[..open], terms));
+        let marked = &rest[open..];
+        let end = marked
+            .find(HIGHLIGHT_CLOSE)
+            .map(|close| close + HIGHLIGHT_CLOSE.len())
+            .unwrap_or(marked.len());
+        result.push_str(&marked[..end]);
+        rest = &marked[end..];
+    }
+    result.push_str(&highlight_terms(rest, terms));
+    result
+}
+
+/// 最初にキーワードが現れる位置の周辺を切り出してハイライト
+fn snippet_terms(text: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: String = text.to_lowercase();
+    let first = terms
+        .iter()
+        .filter_map(|t| lower.find(&t.to_lowercase()))
+        .min()
+        .map(|byte_pos| lower[..byte_pos].chars().count())
+        .unwrap_or(0);
+
+    let window = SNIPPET_TOKENS as usize;
+    let start = first.saturating_sub(window / 4).min(chars.len());
+    let end = (start + window).min(chars.len());
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push_str(SNIPPET_ELLIPSIS);
+    }
+    snippet.push_str(&highlight_terms(&chars[start..end].iter().collect::<String>(), terms));
+    if end < chars.len() {
+        snippet.push_str(SNIPPET_ELLIPSIS);
+    }
+    snippet
+}
+
+/// 全文検索を実行（関連度順）
+///
+/// organizationId / companyId を指定した場合はその範囲に絞り込む。
+/// 組織に属さない設計ドキュメントは、範囲を指定した場合も検索対象に含める。
+pub fn search_full_text(options: &FullTextSearchOptions) -> SqlResult<Vec<FullTextSearchHit>> {
+    let db = get_db().ok_or_else(|| misuse("データベースが初期化されていません".to_string()))?;
+    let conn = db.get_connection()?;
+    let hits = query_full_text(&conn, options)?;
+    record_search_hits(&conn, hits.iter().map(|h| (h.source_type.as_str(), h.id.as_str())))?;
+    Ok(hits)
+}
+
+/// 全文検索を実行する（searchCountは更新しない。ハイブリッド検索の候補取得にも使う）
+pub(crate) fn query_full_text(conn: &Connection, options: &FullTextSearchOptions) -> SqlResult<Vec<FullTextSearchHit>> {
+    let parsed = parse_query(&options.query);
+    if parsed.match_terms.is_empty() && parsed.like_terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let sources: Vec<&FullTextSource> = match &options.source_types {
+        Some(types) if !types.is_empty() => {
+            let mut selected = Vec::new();
+            for t in types {
+                let source = SOURCES
+                    .iter()
+                    .find(|s| s.source_type == t)
+                    .ok_or_else(|| misuse(format!("不明な検索対象です: {}", t)))?;
+                selected.push(source);
+            }
+            selected
+        }
+        _ => SOURCES.iter().collect(),
+    };
+
+    let use_match = !parsed.match_terms.is_empty();
+    let mut conditions: Vec<String> = Vec::new();
+    let mut values: Vec<SqlValue> = Vec::new();
+
+    if use_match {
+        conditions.push(format!("{} MATCH ?", FTS_TABLE));
+        values.push(SqlValue::Text(match_expression(&parsed.match_terms)));
+    }
+    for term in &parsed.like_terms {
+        conditions.push("(title LIKE ? ESCAPE '\\' OR body LIKE ? ESCAPE '\\')".to_string());
+        let pattern = like_pattern(term);
+        values.push(SqlValue::Text(pattern.clone()));
+        values.push(SqlValue::Text(pattern));
+    }
+
+    conditions.push(format!(
+        "sourceType IN ({})",
+        sources.iter().map(|_| "?").collect::<Vec<_>>().join(", ")
+    ));
+    values.extend(sources.iter().map(|s| SqlValue::Text(s.source_type.to_string())));
+
+    if options.organization_id.is_some() || options.company_id.is_some() {
+        let mut scope = Vec::new();
+        if let Some(org_id) = &options.organization_id {
+            scope.push("organizationId = ?");
+            values.push(SqlValue::Text(org_id.clone()));
+        }
+        if let Some(company_id) = &options.company_id {
+            scope.push("companyId = ?");
+            values.push(SqlValue::Text(company_id.clone()));
+        }
+        scope.push("(organizationId IS NULL AND companyId IS NULL)");
+        conditions.push(format!("({})", scope.join(" OR ")));
+    }
+
+    // タイトルの一致を本文より重く評価する
+    let (title_col, snippet_col, score_col, order_by) = if use_match {
+        (
+            format!("highlight({fts}, 4, '{o}', '{c}')", fts = FTS_TABLE, o = HIGHLIGHT_OPEN, c = HIGHLIGHT_CLOSE),
+            format!(
+                "snippet({fts}, 5, '{o}', '{c}', '{e}', {n})",
+                fts = FTS_TABLE, o = HIGHLIGHT_OPEN, c = HIGHLIGHT_CLOSE, e = SNIPPET_ELLIPSIS, n = SNIPPET_TOKENS
+            ),
+            format!("-bm25({}, 0.0, 0.0, 0.0, 0.0, 5.0, 1.0)", FTS_TABLE),
+            "score DESC",
+        )
+    } else {
+        ("title".to_string(), "body".to_string(), "0.0".to_string(), "rowid DESC")
+    };
+
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let offset = options.offset.unwrap_or(0);
+    let sql = format!(
+        "SELECT sourceType, sourceId, {title_col}, {snippet_col}, {score_col} AS score, organizationId, companyId
+         FROM {fts}
+         WHERE {conditions}
+         ORDER BY {order_by}
+         LIMIT {limit} OFFSET {offset}",
+        title_col = title_col,
+        snippet_col = snippet_col,
+        score_col = score_col,
+        fts = FTS_TABLE,
+        conditions = conditions.join(" AND "),
+        order_by = order_by,
+        limit = limit,
+        offset = offset,
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(values.iter()), |row| {
+        Ok(FullTextSearchHit {
+            source_type: row.get(0)?,
+            id: row.get(1)?,
+            title: row.get::<_, Option<String>>(2)?.unwrap_or_default(),
+            snippet: row.get::<_, Option<String>>(3)?.unwrap_or_default(),
+            score: row.get(4)?,
+            organization_id: row.get(5)?,
+            company_id: row.get(6)?,
+        })
+    })?;
+
+    let mut hits = Vec::new();
+    for row in rows {
+        hits.push(row?);
+    }
+
+    // 3文字未満のキーワードはFTS5のハイライト対象にならないため、Rust側でハイライトする
+    if !parsed.like_terms.is_empty() {
+        for hit in hits.iter_mut() {
+            if use_match {
+                hit.title = highlight_marked_terms(&hit.title, &parsed.like_terms);
+                hit.snippet = highlight_marked_terms(&hit.snippet, &parsed.like_terms);
+            } else {
+                hit.title = highlight_terms(&hit.title, &parsed.like_terms);
+                hit.snippet = snippet_terms(hit.snippet.trim(), &parsed.like_terms);
+            }
+        }
+    }
+
+    Ok(hits)
+}
+
+/// ヒットした行のsearchCount / lastSearchDateを更新（(種別, ID)の組を受け取る）
+pub(crate) fn record_search_hits<'a>(
+    conn: &Connection,
+    hits: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> SqlResult<()> {
+    let mut ids_by_type: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (source_type, id) in hits {
+        ids_by_type.entry(source_type).or_default().push(id);
+    }
+    if ids_by_type.is_empty() {
+        return Ok(());
+    }
+
+    let now = get_timestamp();
+    let tx = conn.unchecked_transaction()?;
+    for source in SOURCES.iter().filter(|s| s.tracks_search_stats) {
+        let Some(ids) = ids_by_type.get(source.source_type) else {
+            continue;
+        };
+        let sql = format!(
+            "UPDATE {} SET searchCount = COALESCE(searchCount, 0) + 1, lastSearchDate = ? WHERE id IN ({})",
+            source.table,
+            ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ")
+        );
+        let mut values: Vec<SqlValue> = vec![SqlValue::Text(now.clone())];
+        values.extend(ids.iter().map(|id| SqlValue::Text(id.to_string())));
+        tx.execute(&sql, params_from_iter(values.iter()))?;
+    }
+    tx.commit()?;
+    Ok(())
+}
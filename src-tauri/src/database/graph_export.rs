@@ -0,0 +1,879 @@
+/**
+ * ナレッジグラフのエクスポート
+ * entities / relations テーブルのグラフを外部ツール向けの形式で書き出す
+ *
+ * - GraphML（yEd・Gephiなど）、JSON-LD、Neo4jのCypher（CREATE文）、RDF/Turtle に対応
+ * - organizationId・companyId・yamlFileId・topicIdでリレーションを絞り込める
+ *   （yamlFileId・topicIdを指定した場合、エンティティはそのリレーションが参照するもののみ）
+ * - includeProvenanceを指定すると、リレーションの出典のトピックと議事録も含める
+ */
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::fs;
+use crate::database::{get_db, get_timestamp};
+
+/// IRIのデフォルトの接頭辞（JSON-LD・Turtle）
+const DEFAULT_BASE_IRI: &str = "urn:network-kg:";
+
+/// IDをまとめて読み込む件数（SQLiteのパラメータ数上限対策）
+const ID_CHUNK_SIZE: usize = 500;
+
+/// エクスポート形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphExportFormat {
+    GraphML,
+    JsonLd,
+    Cypher,
+    Turtle,
+}
+
+impl GraphExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GraphExportFormat::GraphML => "graphml",
+            GraphExportFormat::JsonLd => "jsonld",
+            GraphExportFormat::Cypher => "cypher",
+            GraphExportFormat::Turtle => "turtle",
+        }
+    }
+}
+
+/// エクスポートの条件
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphExportOptions {
+    pub format: GraphExportFormat,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+    #[serde(rename = "yamlFileId")]
+    pub yaml_file_id: Option<String>,
+    /// relations.topicId（topics.topicIdと同じ値）
+    #[serde(rename = "topicId")]
+    pub topic_id: Option<String>,
+    /// 出典のトピックと議事録を含めるか（デフォルトfalse）
+    #[serde(rename = "includeProvenance")]
+    pub include_provenance: Option<bool>,
+    /// JSON-LD・TurtleのIRIの接頭辞（デフォルトurn:network-kg:）
+    #[serde(rename = "baseIri")]
+    pub base_iri: Option<String>,
+}
+
+/// エクスポートの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphExportSummary {
+    pub format: String,
+    pub path: Option<String>,
+    #[serde(rename = "entityCount")]
+    pub entity_count: usize,
+    #[serde(rename = "relationCount")]
+    pub relation_count: usize,
+    #[serde(rename = "topicCount")]
+    pub topic_count: usize,
+    #[serde(rename = "meetingNoteCount")]
+    pub meeting_note_count: usize,
+    #[serde(rename = "exportedAt")]
+    pub exported_at: String,
+}
+
+struct ExportEntity {
+    id: String,
+    name: String,
+    display_name: Option<String>,
+    entity_type: String,
+    aliases: Vec<String>,
+    metadata: Option<String>,
+    organization_id: Option<String>,
+    company_id: Option<String>,
+}
+
+impl ExportEntity {
+    fn label(&self) -> &str {
+        self.display_name.as_deref().filter(|s| !s.is_empty()).unwrap_or(&self.name)
+    }
+}
+
+struct ExportRelation {
+    id: String,
+    source: String,
+    target: String,
+    relation_type: String,
+    description: Option<String>,
+    confidence: Option<f64>,
+    topic_id: Option<String>,
+    yaml_file_id: Option<String>,
+}
+
+struct ExportTopic {
+    /// topics.topicId（relations.topicIdが参照する値）
+    topic_id: String,
+    title: String,
+    meeting_note_id: String,
+    topic_date: Option<String>,
+}
+
+struct ExportMeetingNote {
+    id: String,
+    title: String,
+}
+
+/// エクスポート対象のグラフ
+struct ExportGraph {
+    entities: Vec<ExportEntity>,
+    relations: Vec<ExportRelation>,
+    topics: Vec<ExportTopic>,
+    meeting_notes: Vec<ExportMeetingNote>,
+}
+
+impl ExportGraph {
+    /// トピックに登場するエンティティ（トピック → エンティティIDの組）
+    fn mentions(&self) -> BTreeSet<(String, String)> {
+        let topics: HashSet<&str> = self.topics.iter().map(|t| t.topic_id.as_str()).collect();
+        self.relations
+            .iter()
+            .filter_map(|r| r.topic_id.as_deref().filter(|t| topics.contains(t)).map(|t| (t, r)))
+            .flat_map(|(t, r)| [(t.to_string(), r.source.clone()), (t.to_string(), r.target.clone())])
+            .collect()
+    }
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+fn parse_aliases(raw: Option<String>) -> Vec<String> {
+    match raw.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        None => Vec::new(),
+        Some(raw) => match serde_json::from_str::<Value>(raw) {
+            Ok(Value::Array(items)) => items.into_iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+            _ => vec![raw.to_string()],
+        },
+    }
+}
+
+fn load_relations(conn: &Connection, options: &GraphExportOptions) -> SqlResult<Vec<ExportRelation>> {
+    let mut conditions = vec![
+        "r.sourceEntityId IS NOT NULL".to_string(),
+        "r.targetEntityId IS NOT NULL".to_string(),
+        "EXISTS (SELECT 1 FROM entities s WHERE s.id = r.sourceEntityId)".to_string(),
+        "EXISTS (SELECT 1 FROM entities t WHERE t.id = r.targetEntityId)".to_string(),
+    ];
+    let mut params = Vec::new();
+    for (column, value) in [
+        ("organizationId", &options.organization_id),
+        ("companyId", &options.company_id),
+        ("yamlFileId", &options.yaml_file_id),
+        ("topicId", &options.topic_id),
+    ] {
+        if let Some(value) = value.as_ref().filter(|s| !s.is_empty()) {
+            conditions.push(format!("r.{} = ?", column));
+            params.push(SqlValue::Text(value.clone()));
+        }
+    }
+    let sql = format!(
+        "SELECT r.id, r.sourceEntityId, r.targetEntityId, r.relationType, r.description, r.confidence, r.topicId, r.yamlFileId
+         FROM relations r WHERE {} ORDER BY r.id",
+        conditions.join(" AND ")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(params), |row| {
+        Ok(ExportRelation {
+            id: row.get(0)?,
+            source: row.get(1)?,
+            target: row.get(2)?,
+            relation_type: row.get(3)?,
+            description: row.get(4)?,
+            confidence: row.get(5)?,
+            topic_id: row.get(6)?,
+            yaml_file_id: row.get(7)?,
+        })
+    })?;
+    rows.collect()
+}
+
+const ENTITY_COLUMNS: &str = "id, name, displayName, type, aliases, metadata, organizationId, companyId";
+
+fn read_entity(row: &rusqlite::Row) -> SqlResult<ExportEntity> {
+    Ok(ExportEntity {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        display_name: row.get(2)?,
+        entity_type: row.get(3)?,
+        aliases: parse_aliases(row.get(4)?),
+        metadata: row.get(5)?,
+        organization_id: row.get(6)?,
+        company_id: row.get(7)?,
+    })
+}
+
+fn load_entities(conn: &Connection, options: &GraphExportOptions, relations: &[ExportRelation]) -> SqlResult<Vec<ExportEntity>> {
+    let mut entities: Vec<ExportEntity> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    // yamlFileId・topicIdで絞り込まない場合は、リレーションの無いエンティティも含める
+    if options.yaml_file_id.is_none() && options.topic_id.is_none() {
+        let mut conditions = vec!["1 = 1".to_string()];
+        let mut params = Vec::new();
+        for (column, value) in [("organizationId", &options.organization_id), ("companyId", &options.company_id)] {
+            if let Some(value) = value.as_ref().filter(|s| !s.is_empty()) {
+                conditions.push(format!("{} = ?", column));
+                params.push(SqlValue::Text(value.clone()));
+            }
+        }
+        let sql = format!("SELECT {} FROM entities WHERE {} ORDER BY id", ENTITY_COLUMNS, conditions.join(" AND "));
+        let mut stmt = conn.prepare(&sql)?;
+        for entity in stmt.query_map(params_from_iter(params), read_entity)? {
+            let entity = entity?;
+            seen.insert(entity.id.clone());
+            entities.push(entity);
+        }
+    }
+
+    // リレーションが参照するエンティティ（組織をまたぐリレーションの相手も含む）
+    let missing: Vec<String> = relations
+        .iter()
+        .flat_map(|r| [r.source.clone(), r.target.clone()])
+        .filter(|id| !seen.contains(id))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    for chunk in missing.chunks(ID_CHUNK_SIZE) {
+        let sql = format!("SELECT {} FROM entities WHERE id IN ({}) ORDER BY id", ENTITY_COLUMNS, placeholders(chunk.len()));
+        let mut stmt = conn.prepare(&sql)?;
+        for entity in stmt.query_map(params_from_iter(chunk.iter()), read_entity)? {
+            entities.push(entity?);
+        }
+    }
+    Ok(entities)
+}
+
+fn load_provenance(conn: &Connection, relations: &[ExportRelation]) -> SqlResult<(Vec<ExportTopic>, Vec<ExportMeetingNote>)> {
+    let topic_ids: Vec<String> = relations
+        .iter()
+        .filter_map(|r| r.topic_id.clone().filter(|t| !t.is_empty()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut topics: Vec<ExportTopic> = Vec::new();
+    let mut seen_topics: HashSet<String> = HashSet::new();
+    for chunk in topic_ids.chunks(ID_CHUNK_SIZE) {
+        let sql = format!(
+            "SELECT topicId, title, meetingNoteId, topicDate FROM topics WHERE topicId IN ({}) ORDER BY topicId, id",
+            placeholders(chunk.len())
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(chunk.iter()), |row| {
+            Ok(ExportTopic {
+                topic_id: row.get(0)?,
+                title: row.get(1)?,
+                meeting_note_id: row.get(2)?,
+                topic_date: row.get(3)?,
+            })
+        })?;
+        for topic in rows {
+            let topic = topic?;
+            if seen_topics.insert(topic.topic_id.clone()) {
+                topics.push(topic);
+            }
+        }
+    }
+
+    let note_ids: Vec<String> = topics
+        .iter()
+        .map(|t| t.meeting_note_id.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let mut meeting_notes = Vec::new();
+    for chunk in note_ids.chunks(ID_CHUNK_SIZE) {
+        let sql = format!("SELECT id, title FROM meetingNotes WHERE id IN ({}) ORDER BY id", placeholders(chunk.len()));
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(chunk.iter()), |row| {
+            Ok(ExportMeetingNote { id: row.get(0)?, title: row.get(1)? })
+        })?;
+        for note in rows {
+            meeting_notes.push(note?);
+        }
+    }
+    Ok((topics, meeting_notes))
+}
+
+fn load_graph(options: &GraphExportOptions) -> Result<ExportGraph, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    let conn = db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))?;
+    let relations = load_relations(&conn, options).map_err(|e| format!("リレーションの取得に失敗しました: {}", e))?;
+    let entities = load_entities(&conn, options, &relations).map_err(|e| format!("エンティティの取得に失敗しました: {}", e))?;
+    let (topics, meeting_notes) = if options.include_provenance.unwrap_or(false) {
+        load_provenance(&conn, &relations).map_err(|e| format!("出典の取得に失敗しました: {}", e))?
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    Ok(ExportGraph { entities, relations, topics, meeting_notes })
+}
+
+// ---------------------------------------------------------------------------
+// GraphML
+// ---------------------------------------------------------------------------
+
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML 1.0で使えない制御文字は除去
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn graphml_data(out: &mut String, key: &str, value: Option<&str>) {
+    if let Some(value) = value.filter(|v| !v.is_empty()) {
+        let _ = writeln!(out, "      <data key=\"{}\">{}</data>", key, xml_escape(value));
+    }
+}
+
+fn write_graphml(graph: &ExportGraph) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n");
+    for (id, target, name, kind) in [
+        ("d_kind", "node", "kind", "string"),
+        ("d_label", "node", "label", "string"),
+        ("d_type", "node", "type", "string"),
+        ("d_aliases", "node", "aliases", "string"),
+        ("d_metadata", "node", "metadata", "string"),
+        ("d_organizationId", "node", "organizationId", "string"),
+        ("d_companyId", "node", "companyId", "string"),
+        ("d_topicDate", "node", "topicDate", "string"),
+        ("e_relationType", "edge", "relationType", "string"),
+        ("e_description", "edge", "description", "string"),
+        ("e_confidence", "edge", "confidence", "double"),
+        ("e_topicId", "edge", "topicId", "string"),
+        ("e_yamlFileId", "edge", "yamlFileId", "string"),
+    ] {
+        let _ = writeln!(out, "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>", id, target, name, kind);
+    }
+    out.push_str("  <graph id=\"knowledge-graph\" edgedefault=\"directed\">\n");
+
+    for entity in &graph.entities {
+        let _ = writeln!(out, "    <node id=\"entity:{}\">", xml_escape(&entity.id));
+        graphml_data(&mut out, "d_kind", Some("entity"));
+        graphml_data(&mut out, "d_label", Some(entity.label()));
+        graphml_data(&mut out, "d_type", Some(&entity.entity_type));
+        let aliases = (!entity.aliases.is_empty()).then(|| entity.aliases.join("|"));
+        graphml_data(&mut out, "d_aliases", aliases.as_deref());
+        graphml_data(&mut out, "d_metadata", entity.metadata.as_deref());
+        graphml_data(&mut out, "d_organizationId", entity.organization_id.as_deref());
+        graphml_data(&mut out, "d_companyId", entity.company_id.as_deref());
+        out.push_str("    </node>\n");
+    }
+    for topic in &graph.topics {
+        let _ = writeln!(out, "    <node id=\"topic:{}\">", xml_escape(&topic.topic_id));
+        graphml_data(&mut out, "d_kind", Some("topic"));
+        graphml_data(&mut out, "d_label", Some(&topic.title));
+        graphml_data(&mut out, "d_topicDate", topic.topic_date.as_deref());
+        out.push_str("    </node>\n");
+    }
+    for note in &graph.meeting_notes {
+        let _ = writeln!(out, "    <node id=\"meetingNote:{}\">", xml_escape(&note.id));
+        graphml_data(&mut out, "d_kind", Some("meetingNote"));
+        graphml_data(&mut out, "d_label", Some(&note.title));
+        out.push_str("    </node>\n");
+    }
+
+    for relation in &graph.relations {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"relation:{}\" source=\"entity:{}\" target=\"entity:{}\">",
+            xml_escape(&relation.id), xml_escape(&relation.source), xml_escape(&relation.target)
+        );
+        graphml_data(&mut out, "e_relationType", Some(&relation.relation_type));
+        graphml_data(&mut out, "e_description", relation.description.as_deref());
+        let confidence = relation.confidence.map(|c| c.to_string());
+        graphml_data(&mut out, "e_confidence", confidence.as_deref());
+        graphml_data(&mut out, "e_topicId", relation.topic_id.as_deref());
+        graphml_data(&mut out, "e_yamlFileId", relation.yaml_file_id.as_deref());
+        out.push_str("    </edge>\n");
+    }
+    let notes: HashSet<&str> = graph.meeting_notes.iter().map(|n| n.id.as_str()).collect();
+    for topic in graph.topics.iter().filter(|t| notes.contains(t.meeting_note_id.as_str())) {
+        let _ = writeln!(
+            out,
+            "    <edge source=\"topic:{}\" target=\"meetingNote:{}\">",
+            xml_escape(&topic.topic_id), xml_escape(&topic.meeting_note_id)
+        );
+        graphml_data(&mut out, "e_relationType", Some("partOf"));
+        out.push_str("    </edge>\n");
+    }
+    for (topic_id, entity_id) in graph.mentions() {
+        let _ = writeln!(
+            out,
+            "    <edge source=\"entity:{}\" target=\"topic:{}\">",
+            xml_escape(&entity_id), xml_escape(&topic_id)
+        );
+        graphml_data(&mut out, "e_relationType", Some("mentionedIn"));
+        out.push_str("    </edge>\n");
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+// ---------------------------------------------------------------------------
+// JSON-LD / Turtle 共通
+// ---------------------------------------------------------------------------
+
+/// IRIに使えない文字をパーセントエンコード
+fn iri_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+    encoded
+}
+
+struct Iris {
+    base: String,
+}
+
+impl Iris {
+    fn new(options: &GraphExportOptions) -> Self {
+        Iris { base: options.base_iri.clone().filter(|b| !b.trim().is_empty()).unwrap_or_else(|| DEFAULT_BASE_IRI.to_string()) }
+    }
+
+    /// `urn:...:`形式の接頭辞は`{接頭辞}{種別}:{ID}`、それ以外は`{接頭辞}/{種別}/{ID}`
+    fn join(&self, kind: &str, id: &str) -> String {
+        if self.base.ends_with(':') {
+            format!("{}{}:{}", self.base, kind, iri_segment(id))
+        } else {
+            format!("{}/{}/{}", self.base.trim_end_matches('/'), kind, iri_segment(id))
+        }
+    }
+
+    fn entity(&self, id: &str) -> String {
+        self.join("entity", id)
+    }
+
+    fn relation(&self, id: &str) -> String {
+        self.join("relation", id)
+    }
+
+    fn relation_type(&self, relation_type: &str) -> String {
+        self.join("relationType", relation_type)
+    }
+
+    fn topic(&self, id: &str) -> String {
+        self.join("topic", id)
+    }
+
+    fn meeting_note(&self, id: &str) -> String {
+        self.join("meetingNote", id)
+    }
+
+    fn vocab(&self) -> String {
+        self.join("vocab", "")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// JSON-LD
+// ---------------------------------------------------------------------------
+
+fn write_json_ld(graph: &ExportGraph, iris: &Iris) -> String {
+    let context = json!({
+        "@vocab": iris.vocab(),
+        "rdfs": "http://www.w3.org/2000/01/rdf-schema#",
+        "prov": "http://www.w3.org/ns/prov#",
+        "xsd": "http://www.w3.org/2001/XMLSchema#",
+        "label": "rdfs:label",
+        "source": { "@type": "@id" },
+        "target": { "@type": "@id" },
+        "relationTypeIri": { "@type": "@id" },
+        "wasDerivedFrom": { "@id": "prov:wasDerivedFrom", "@type": "@id" },
+        "partOf": { "@type": "@id" },
+        "mentionedIn": { "@type": "@id", "@container": "@set" },
+        "confidence": { "@type": "xsd:double" },
+    });
+
+    let mut mentions: HashMap<String, Vec<String>> = HashMap::new();
+    for (topic_id, entity_id) in graph.mentions() {
+        mentions.entry(entity_id).or_default().push(iris.topic(&topic_id));
+    }
+
+    let mut nodes: Vec<Value> = Vec::new();
+    for entity in &graph.entities {
+        let mut node = Map::new();
+        node.insert("@id".to_string(), json!(iris.entity(&entity.id)));
+        node.insert("@type".to_string(), json!("Entity"));
+        node.insert("identifier".to_string(), json!(entity.id));
+        node.insert("label".to_string(), json!(entity.label()));
+        node.insert("name".to_string(), json!(entity.name));
+        node.insert("entityType".to_string(), json!(entity.entity_type));
+        if !entity.aliases.is_empty() {
+            node.insert("alias".to_string(), json!(entity.aliases));
+        }
+        if let Some(metadata) = entity.metadata.as_deref().filter(|m| !m.is_empty()) {
+            node.insert("metadata".to_string(), json!(metadata));
+        }
+        if let Some(organization_id) = &entity.organization_id {
+            node.insert("organizationId".to_string(), json!(organization_id));
+        }
+        if let Some(company_id) = &entity.company_id {
+            node.insert("companyId".to_string(), json!(company_id));
+        }
+        if let Some(topics) = mentions.remove(&entity.id) {
+            node.insert("mentionedIn".to_string(), json!(topics));
+        }
+        nodes.push(Value::Object(node));
+    }
+
+    let topics: HashSet<&str> = graph.topics.iter().map(|t| t.topic_id.as_str()).collect();
+    for relation in &graph.relations {
+        let mut node = Map::new();
+        node.insert("@id".to_string(), json!(iris.relation(&relation.id)));
+        node.insert("@type".to_string(), json!("Relation"));
+        node.insert("identifier".to_string(), json!(relation.id));
+        node.insert("source".to_string(), json!(iris.entity(&relation.source)));
+        node.insert("target".to_string(), json!(iris.entity(&relation.target)));
+        node.insert("relationType".to_string(), json!(relation.relation_type));
+        node.insert("relationTypeIri".to_string(), json!(iris.relation_type(&relation.relation_type)));
+        if let Some(description) = relation.description.as_deref().filter(|d| !d.is_empty()) {
+            node.insert("description".to_string(), json!(description));
+        }
+        if let Some(confidence) = relation.confidence {
+            node.insert("confidence".to_string(), json!(confidence));
+        }
+        if let Some(topic_id) = relation.topic_id.as_deref().filter(|t| !t.is_empty()) {
+            node.insert("topicId".to_string(), json!(topic_id));
+            if topics.contains(topic_id) {
+                node.insert("wasDerivedFrom".to_string(), json!(iris.topic(topic_id)));
+            }
+        }
+        if let Some(yaml_file_id) = relation.yaml_file_id.as_deref().filter(|y| !y.is_empty()) {
+            node.insert("yamlFileId".to_string(), json!(yaml_file_id));
+        }
+        nodes.push(Value::Object(node));
+    }
+
+    let notes: HashSet<&str> = graph.meeting_notes.iter().map(|n| n.id.as_str()).collect();
+    for topic in &graph.topics {
+        let mut node = Map::new();
+        node.insert("@id".to_string(), json!(iris.topic(&topic.topic_id)));
+        node.insert("@type".to_string(), json!("Topic"));
+        node.insert("identifier".to_string(), json!(topic.topic_id));
+        node.insert("label".to_string(), json!(topic.title));
+        if let Some(date) = &topic.topic_date {
+            node.insert("topicDate".to_string(), json!(date));
+        }
+        if notes.contains(topic.meeting_note_id.as_str()) {
+            node.insert("partOf".to_string(), json!(iris.meeting_note(&topic.meeting_note_id)));
+        }
+        nodes.push(Value::Object(node));
+    }
+    for note in &graph.meeting_notes {
+        nodes.push(json!({
+            "@id": iris.meeting_note(&note.id),
+            "@type": "MeetingNote",
+            "identifier": note.id,
+            "label": note.title,
+        }));
+    }
+
+    let document = json!({ "@context": context, "@graph": nodes });
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Turtle
+// ---------------------------------------------------------------------------
+
+fn turtle_literal(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// 主語ごとの述語と目的語を書き出す
+fn turtle_subject(out: &mut String, subject: &str, predicates: Vec<(String, String)>) {
+    let _ = write!(out, "<{}>", subject);
+    for (i, (predicate, object)) in predicates.iter().enumerate() {
+        let separator = if i == 0 { "\n    " } else { " ;\n    " };
+        let _ = write!(out, "{}{} {}", separator, predicate, object);
+    }
+    out.push_str(" .\n\n");
+}
+
+fn write_turtle(graph: &ExportGraph, iris: &Iris) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "@prefix kg: <{}> .", iris.vocab());
+    out.push_str("@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n");
+    out.push_str("@prefix prov: <http://www.w3.org/ns/prov#> .\n");
+    out.push_str("@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\n\n");
+
+    let mut mentions: HashMap<String, Vec<String>> = HashMap::new();
+    for (topic_id, entity_id) in graph.mentions() {
+        mentions.entry(entity_id).or_default().push(iris.topic(&topic_id));
+    }
+
+    for entity in &graph.entities {
+        let mut predicates = vec![
+            ("a".to_string(), "kg:Entity".to_string()),
+            ("kg:identifier".to_string(), turtle_literal(&entity.id)),
+            ("rdfs:label".to_string(), turtle_literal(entity.label())),
+            ("kg:name".to_string(), turtle_literal(&entity.name)),
+            ("kg:entityType".to_string(), turtle_literal(&entity.entity_type)),
+        ];
+        for alias in &entity.aliases {
+            predicates.push(("kg:alias".to_string(), turtle_literal(alias)));
+        }
+        if let Some(organization_id) = &entity.organization_id {
+            predicates.push(("kg:organizationId".to_string(), turtle_literal(organization_id)));
+        }
+        if let Some(company_id) = &entity.company_id {
+            predicates.push(("kg:companyId".to_string(), turtle_literal(company_id)));
+        }
+        for topic in mentions.remove(&entity.id).unwrap_or_default() {
+            predicates.push(("kg:mentionedIn".to_string(), format!("<{}>", topic)));
+        }
+        turtle_subject(&mut out, &iris.entity(&entity.id), predicates);
+    }
+
+    // リレーションは直接のトリプルと、属性を持たせるためのリレーションノードの両方で表す
+    let topics: HashSet<&str> = graph.topics.iter().map(|t| t.topic_id.as_str()).collect();
+    for relation in &graph.relations {
+        let _ = writeln!(
+            out,
+            "<{}> <{}> <{}> .\n",
+            iris.entity(&relation.source),
+            iris.relation_type(&relation.relation_type),
+            iris.entity(&relation.target)
+        );
+        let mut predicates = vec![
+            ("a".to_string(), "kg:Relation".to_string()),
+            ("kg:identifier".to_string(), turtle_literal(&relation.id)),
+            ("kg:source".to_string(), format!("<{}>", iris.entity(&relation.source))),
+            ("kg:target".to_string(), format!("<{}>", iris.entity(&relation.target))),
+            ("kg:relationType".to_string(), turtle_literal(&relation.relation_type)),
+        ];
+        if let Some(description) = relation.description.as_deref().filter(|d| !d.is_empty()) {
+            predicates.push(("rdfs:comment".to_string(), turtle_literal(description)));
+        }
+        if let Some(confidence) = relation.confidence.filter(|c| c.is_finite()) {
+            predicates.push(("kg:confidence".to_string(), format!("\"{}\"^^xsd:double", confidence)));
+        }
+        if let Some(topic_id) = relation.topic_id.as_deref().filter(|t| !t.is_empty()) {
+            predicates.push(("kg:topicId".to_string(), turtle_literal(topic_id)));
+            if topics.contains(topic_id) {
+                predicates.push(("prov:wasDerivedFrom".to_string(), format!("<{}>", iris.topic(topic_id))));
+            }
+        }
+        if let Some(yaml_file_id) = relation.yaml_file_id.as_deref().filter(|y| !y.is_empty()) {
+            predicates.push(("kg:yamlFileId".to_string(), turtle_literal(yaml_file_id)));
+        }
+        turtle_subject(&mut out, &iris.relation(&relation.id), predicates);
+    }
+
+    let notes: HashSet<&str> = graph.meeting_notes.iter().map(|n| n.id.as_str()).collect();
+    for topic in &graph.topics {
+        let mut predicates = vec![
+            ("a".to_string(), "kg:Topic".to_string()),
+            ("kg:identifier".to_string(), turtle_literal(&topic.topic_id)),
+            ("rdfs:label".to_string(), turtle_literal(&topic.title)),
+        ];
+        if let Some(date) = &topic.topic_date {
+            predicates.push(("kg:topicDate".to_string(), turtle_literal(date)));
+        }
+        if notes.contains(topic.meeting_note_id.as_str()) {
+            predicates.push(("kg:partOf".to_string(), format!("<{}>", iris.meeting_note(&topic.meeting_note_id))));
+        }
+        turtle_subject(&mut out, &iris.topic(&topic.topic_id), predicates);
+    }
+    for note in &graph.meeting_notes {
+        turtle_subject(&mut out, &iris.meeting_note(&note.id), vec![
+            ("a".to_string(), "kg:MeetingNote".to_string()),
+            ("kg:identifier".to_string(), turtle_literal(&note.id)),
+            ("rdfs:label".to_string(), turtle_literal(&note.title)),
+        ]);
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Cypher
+// ---------------------------------------------------------------------------
+
+fn cypher_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('\'');
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('\'');
+    escaped
+}
+
+/// ラベル・リレーションシップタイプはバッククォートで囲む（日本語のrelationTypeもそのまま使える）
+fn cypher_identifier(value: &str) -> String {
+    format!("`{}`", value.replace('`', "``"))
+}
+
+fn cypher_properties(properties: Vec<(&str, Option<String>)>) -> String {
+    let items: Vec<String> = properties
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|v| format!("{}: {}", key, v)))
+        .collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+fn write_cypher(graph: &ExportGraph) -> String {
+    let mut out = String::new();
+    out.push_str("// Knowledge graph export (Neo4j Cypher)\n");
+    out.push_str("CREATE CONSTRAINT entity_id IF NOT EXISTS FOR (n:Entity) REQUIRE n.id IS UNIQUE;\n");
+    if !graph.topics.is_empty() {
+        out.push_str("CREATE CONSTRAINT topic_id IF NOT EXISTS FOR (n:Topic) REQUIRE n.id IS UNIQUE;\n");
+        out.push_str("CREATE CONSTRAINT meeting_note_id IF NOT EXISTS FOR (n:MeetingNote) REQUIRE n.id IS UNIQUE;\n");
+    }
+    out.push('\n');
+
+    for entity in &graph.entities {
+        let aliases = (!entity.aliases.is_empty()).then(|| {
+            format!("[{}]", entity.aliases.iter().map(|a| cypher_string(a)).collect::<Vec<_>>().join(", "))
+        });
+        let _ = writeln!(
+            out,
+            "CREATE (:Entity:{} {});",
+            cypher_identifier(&entity.entity_type),
+            cypher_properties(vec![
+                ("id", Some(cypher_string(&entity.id))),
+                ("name", Some(cypher_string(&entity.name))),
+                ("label", Some(cypher_string(entity.label()))),
+                ("type", Some(cypher_string(&entity.entity_type))),
+                ("aliases", aliases),
+                ("metadata", entity.metadata.as_deref().filter(|m| !m.is_empty()).map(cypher_string)),
+                ("organizationId", entity.organization_id.as_deref().map(cypher_string)),
+                ("companyId", entity.company_id.as_deref().map(cypher_string)),
+            ])
+        );
+    }
+    for topic in &graph.topics {
+        let _ = writeln!(
+            out,
+            "CREATE (:Topic {});",
+            cypher_properties(vec![
+                ("id", Some(cypher_string(&topic.topic_id))),
+                ("title", Some(cypher_string(&topic.title))),
+                ("topicDate", topic.topic_date.as_deref().map(cypher_string)),
+            ])
+        );
+    }
+    for note in &graph.meeting_notes {
+        let _ = writeln!(
+            out,
+            "CREATE (:MeetingNote {});",
+            cypher_properties(vec![("id", Some(cypher_string(&note.id))), ("title", Some(cypher_string(&note.title)))])
+        );
+    }
+    out.push('\n');
+
+    for relation in &graph.relations {
+        let _ = writeln!(
+            out,
+            "MATCH (a:Entity {{id: {}}}), (b:Entity {{id: {}}}) CREATE (a)-[:{} {}]->(b);",
+            cypher_string(&relation.source),
+            cypher_string(&relation.target),
+            cypher_identifier(&relation.relation_type),
+            cypher_properties(vec![
+                ("id", Some(cypher_string(&relation.id))),
+                ("relationType", Some(cypher_string(&relation.relation_type))),
+                ("description", relation.description.as_deref().filter(|d| !d.is_empty()).map(cypher_string)),
+                ("confidence", relation.confidence.filter(|c| c.is_finite()).map(|c| format!("{:?}", c))),
+                ("topicId", relation.topic_id.as_deref().filter(|t| !t.is_empty()).map(cypher_string)),
+                ("yamlFileId", relation.yaml_file_id.as_deref().filter(|y| !y.is_empty()).map(cypher_string)),
+            ])
+        );
+    }
+    let notes: HashSet<&str> = graph.meeting_notes.iter().map(|n| n.id.as_str()).collect();
+    for topic in graph.topics.iter().filter(|t| notes.contains(t.meeting_note_id.as_str())) {
+        let _ = writeln!(
+            out,
+            "MATCH (t:Topic {{id: {}}}), (m:MeetingNote {{id: {}}}) CREATE (t)-[:PART_OF]->(m);",
+            cypher_string(&topic.topic_id),
+            cypher_string(&topic.meeting_note_id)
+        );
+    }
+    for (topic_id, entity_id) in graph.mentions() {
+        let _ = writeln!(
+            out,
+            "MATCH (e:Entity {{id: {}}}), (t:Topic {{id: {}}}) CREATE (e)-[:MENTIONED_IN]->(t);",
+            cypher_string(&entity_id),
+            cypher_string(&topic_id)
+        );
+    }
+    out
+}
+
+/// ナレッジグラフを指定形式の文字列として書き出す
+pub fn export_knowledge_graph(options: &GraphExportOptions) -> Result<(String, GraphExportSummary), String> {
+    let graph = load_graph(options)?;
+    let iris = Iris::new(options);
+    let content = match options.format {
+        GraphExportFormat::GraphML => write_graphml(&graph),
+        GraphExportFormat::JsonLd => write_json_ld(&graph, &iris),
+        GraphExportFormat::Cypher => write_cypher(&graph),
+        GraphExportFormat::Turtle => write_turtle(&graph, &iris),
+    };
+    let summary = GraphExportSummary {
+        format: options.format.as_str().to_string(),
+        path: None,
+        entity_count: graph.entities.len(),
+        relation_count: graph.relations.len(),
+        topic_count: graph.topics.len(),
+        meeting_note_count: graph.meeting_notes.len(),
+        exported_at: get_timestamp(),
+    };
+    Ok((content, summary))
+}
+
+/// ナレッジグラフを指定形式でファイルに書き出す
+pub fn export_knowledge_graph_to_file(export_path: &str, options: &GraphExportOptions) -> Result<GraphExportSummary, String> {
+    let (content, mut summary) = export_knowledge_graph(options)?;
+    fs::write(export_path, content).map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+    summary.path = Some(export_path.to_string());
+    Ok(summary)
+}
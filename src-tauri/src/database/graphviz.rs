@@ -6,6 +6,8 @@
 use rusqlite::{params, Result as SqlResult};
 use serde::{Deserialize, Serialize};
 use crate::database::{get_db, get_timestamp};
+use crate::database::text_diff::{diff_lines, TextDiff};
+use crate::database::yaml_schema::ensure_valid_yaml;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,7 +91,7 @@ pub struct GraphvizDotFile {
     pub updated_at: String,
 }
 
-/// YAMLファイルを作成
+/// YAMLファイルを作成（保存前にyamlSchemaで検証）
 pub fn create_graphviz_yaml_file(
     name: String,
     description: Option<String>,
@@ -98,6 +100,20 @@ pub fn create_graphviz_yaml_file(
     yaml_type: Option<String>,
     organization_id: Option<String>,
     tags: Option<Vec<String>>,
+) -> Result<GraphvizYamlFile, String> {
+    ensure_valid_yaml(&yaml_content, yaml_schema.as_deref())?;
+    insert_graphviz_yaml_file(name, description, yaml_content, yaml_schema, yaml_type, organization_id, tags)
+        .map_err(|e| e.to_string())
+}
+
+fn insert_graphviz_yaml_file(
+    name: String,
+    description: Option<String>,
+    yaml_content: String,
+    yaml_schema: Option<String>,
+    yaml_type: Option<String>,
+    organization_id: Option<String>,
+    tags: Option<Vec<String>>,
 ) -> SqlResult<GraphvizYamlFile> {
     let db = get_db().ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
@@ -171,6 +187,8 @@ pub fn create_graphviz_yaml_file(
 }
 
 /// YAMLファイルを更新
+/// yamlContent / yamlSchemaを変更する場合は保存前に検証し、
+/// 内容（name, description, yamlContent, yamlSchema, yamlType）が変わる場合は更新前の状態を履歴行として残す
 pub fn update_graphviz_yaml_file(
     id: &str,
     name: Option<String>,
@@ -182,41 +200,112 @@ pub fn update_graphviz_yaml_file(
     semantic_category: Option<String>,
     keywords: Option<String>,
     content_summary: Option<String>,
-) -> SqlResult<GraphvizYamlFile> {
-    let db = get_db().ok_or_else(|| {
-        rusqlite::Error::SqliteFailure(
-            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
-            Some("データベースが初期化されていません".to_string()),
-        )
-    })?;
-
-    let conn = db.get_connection()?;
-    let now = get_timestamp();
-
+) -> Result<GraphvizYamlFile, String> {
     // 既存のデータを取得
-    let existing: GraphvizYamlFile = get_graphviz_yaml_file_by_id(id)?;
+    let existing: GraphvizYamlFile = get_graphviz_yaml_file_by_id(id).map_err(|e| e.to_string())?;
+    if existing.parent_yaml_file_id.is_some() {
+        return Err("過去のバージョンは更新できません。ロールバックを使用してください".to_string());
+    }
 
-    let name = name.unwrap_or(existing.name);
-    let description = description.or(existing.description);
-    let yaml_content = yaml_content.unwrap_or(existing.yaml_content);
-    let yaml_schema = yaml_schema.or(existing.yaml_schema);
-    let yaml_type = yaml_type.or(existing.yaml_type);
-    let tags_json = tags.as_ref().map(|t| serde_json::to_string(t).unwrap_or_default()).or(existing.tags);
+    let needs_validation = yaml_content.is_some() || yaml_schema.is_some();
+    let tags_json = tags.as_ref().map(|t| serde_json::to_string(t).unwrap_or_default()).or(existing.tags.clone());
+    let updated = GraphvizYamlFile {
+        name: name.unwrap_or(existing.name.clone()),
+        description: description.or(existing.description.clone()),
+        yaml_content: yaml_content.unwrap_or(existing.yaml_content.clone()),
+        yaml_schema: yaml_schema.or(existing.yaml_schema.clone()),
+        yaml_type: yaml_type.or(existing.yaml_type.clone()),
+        tags: tags_json,
+        // メタデータフィールドを更新（指定された場合のみ）
+        semantic_category: semantic_category.or(existing.semantic_category.clone()),
+        keywords: keywords.or(existing.keywords.clone()),
+        content_summary: content_summary.or(existing.content_summary.clone()),
+        ..existing.clone()
+    };
+    write_graphviz_yaml_file_update(&existing, updated, needs_validation)
+}
+
+/// 各フィールドを解決済みの新しい状態を検証し、searchableText・バージョンを更新して書き込む
+fn write_graphviz_yaml_file_update(
+    existing: &GraphvizYamlFile,
+    merged: GraphvizYamlFile,
+    needs_validation: bool,
+) -> Result<GraphvizYamlFile, String> {
+    if needs_validation {
+        ensure_valid_yaml(&merged.yaml_content, merged.yaml_schema.as_deref())?;
+    }
 
     // searchableTextを更新
     let searchable_text = format!(
         "{} {} {}",
-        name,
-        description.as_ref().unwrap_or(&String::new()),
-        yaml_content.chars().take(500).collect::<String>()
+        merged.name,
+        merged.description.as_ref().unwrap_or(&String::new()),
+        merged.yaml_content.chars().take(500).collect::<String>()
     );
 
-    // メタデータフィールドを更新（指定された場合のみ）
-    let semantic_category = semantic_category.or(existing.semantic_category.clone());
-    let keywords = keywords.or(existing.keywords.clone());
-    let content_summary = content_summary.or(existing.content_summary.clone());
+    // 内容が変わる場合のみバージョンを進める（検索用メタデータだけの更新では履歴を作らない）
+    let content_changed = merged.name != existing.name
+        || merged.description != existing.description
+        || merged.yaml_content != existing.yaml_content
+        || merged.yaml_schema != existing.yaml_schema
+        || merged.yaml_type != existing.yaml_type;
 
-    conn.execute(
+    let updated = GraphvizYamlFile {
+        version: if content_changed { existing.version + 1 } else { existing.version },
+        parent_yaml_file_id: None,
+        searchable_text: Some(searchable_text),
+        updated_at: get_timestamp(),
+        ..merged
+    };
+
+    save_graphviz_yaml_file_update(existing, &updated, content_changed).map_err(|e| e.to_string())?;
+    Ok(updated)
+}
+
+/// 更新を書き込む（archiveの場合は更新前の状態を履歴行として同じトランザクションで保存）
+fn save_graphviz_yaml_file_update(existing: &GraphvizYamlFile, updated: &GraphvizYamlFile, archive: bool) -> SqlResult<()> {
+    let db = get_db().ok_or_else(|| {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            Some("データベースが初期化されていません".to_string()),
+        )
+    })?;
+
+    let conn = db.get_connection()?;
+    let tx = conn.unchecked_transaction()?;
+
+    if archive {
+        // 履歴行はparentYamlFileIdで現在の行を指す。検索・ベクトル同期の対象にしないため
+        // searchableTextは空にし、chromaSyncedは同期済みとして扱う
+        let history_id = format!("yaml_{}", Uuid::new_v4().to_string().replace("-", ""));
+        tx.execute(
+            "INSERT INTO graphvizYamlFiles (
+                id, name, description, yamlContent, yamlSchema, yamlType,
+                organizationId, tags, version, parentYamlFileId, searchableText,
+                semanticCategory, keywords, contentSummary,
+                chromaSynced, searchCount, createdAt, updatedAt
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, NULL, ?11, ?12, ?13, 1, 0, ?14, ?14)",
+            params![
+                history_id,
+                existing.name,
+                existing.description,
+                existing.yaml_content,
+                existing.yaml_schema,
+                existing.yaml_type,
+                existing.organization_id,
+                existing.tags,
+                existing.version,
+                existing.id,
+                existing.semantic_category,
+                existing.keywords,
+                existing.content_summary,
+                existing.updated_at,
+            ],
+        )?;
+    }
+
+    tx.execute(
         "UPDATE graphvizYamlFiles SET
             name = ?1,
             description = ?2,
@@ -228,47 +317,27 @@ pub fn update_graphviz_yaml_file(
             semanticCategory = ?8,
             keywords = ?9,
             contentSummary = ?10,
-            updatedAt = ?11
-        WHERE id = ?12",
+            version = ?11,
+            updatedAt = ?12
+        WHERE id = ?13",
         params![
-            name.clone(),
-            description.clone(),
-            yaml_content.clone(),
-            yaml_schema.clone(),
-            yaml_type.clone(),
-            tags_json.clone(),
-            searchable_text.clone(),
-            semantic_category.clone(),
-            keywords.clone(),
-            content_summary.clone(),
-            now,
-            id
+            updated.name,
+            updated.description,
+            updated.yaml_content,
+            updated.yaml_schema,
+            updated.yaml_type,
+            updated.tags,
+            updated.searchable_text,
+            updated.semantic_category,
+            updated.keywords,
+            updated.content_summary,
+            updated.version,
+            updated.updated_at,
+            updated.id
         ],
     )?;
 
-    Ok(GraphvizYamlFile {
-        id: id.to_string(),
-        name,
-        description,
-        yaml_content,
-        yaml_schema,
-        yaml_type,
-        organization_id: existing.organization_id,
-        tags: tags_json,
-        version: existing.version,
-        parent_yaml_file_id: existing.parent_yaml_file_id,
-        searchable_text: Some(searchable_text),
-        semantic_category,
-        keywords,
-        content_summary,
-        chroma_synced: existing.chroma_synced,
-        chroma_sync_error: existing.chroma_sync_error,
-        last_chroma_sync_attempt: existing.last_chroma_sync_attempt,
-        last_search_date: existing.last_search_date,
-        search_count: existing.search_count,
-        created_at: existing.created_at,
-        updated_at: now,
-    })
+    tx.commit()
 }
 
 /// IDでYAMLファイルを取得
@@ -320,7 +389,7 @@ pub fn get_graphviz_yaml_file_by_id(id: &str) -> SqlResult<GraphvizYamlFile> {
     Ok(yaml_file)
 }
 
-/// すべてのYAMLファイルを取得
+/// すべてのYAMLファイルを取得（過去のバージョンの履歴行は除く）
 pub fn get_all_graphviz_yaml_files(organization_id: Option<String>) -> SqlResult<Vec<GraphvizYamlFile>> {
     let db = get_db().ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
@@ -337,14 +406,14 @@ pub fn get_all_graphviz_yaml_files(organization_id: Option<String>) -> SqlResult
                 semanticCategory, keywords, contentSummary,
                 chromaSynced, chromaSyncError, lastChromaSyncAttempt,
                 lastSearchDate, searchCount, createdAt, updatedAt
-         FROM graphvizYamlFiles WHERE organizationId = ?1 ORDER BY createdAt DESC"
+         FROM graphvizYamlFiles WHERE organizationId = ?1 AND parentYamlFileId IS NULL ORDER BY createdAt DESC"
     } else {
         "SELECT id, name, description, yamlContent, yamlSchema, yamlType,
                 organizationId, tags, version, parentYamlFileId, searchableText,
                 semanticCategory, keywords, contentSummary,
                 chromaSynced, chromaSyncError, lastChromaSyncAttempt,
                 lastSearchDate, searchCount, createdAt, updatedAt
-         FROM graphvizYamlFiles ORDER BY createdAt DESC"
+         FROM graphvizYamlFiles WHERE parentYamlFileId IS NULL ORDER BY createdAt DESC"
     };
 
     let mut stmt = conn.prepare(query)?;
@@ -406,7 +475,7 @@ pub fn get_all_graphviz_yaml_files(organization_id: Option<String>) -> SqlResult
     Ok(yaml_files)
 }
 
-/// YAMLファイルを削除
+/// YAMLファイルを削除（過去のバージョンも削除）
 pub fn delete_graphviz_yaml_file(id: &str) -> SqlResult<()> {
     let db = get_db().ok_or_else(|| {
         rusqlite::Error::SqliteFailure(
@@ -416,11 +485,136 @@ pub fn delete_graphviz_yaml_file(id: &str) -> SqlResult<()> {
     })?;
 
     let conn = db.get_connection()?;
-    conn.execute("DELETE FROM graphvizYamlFiles WHERE id = ?1", params![id])?;
+    let tx = conn.unchecked_transaction()?;
+    // 履歴行はparentYamlFileIdで参照しているため先に削除する
+    tx.execute("DELETE FROM graphvizYamlFiles WHERE parentYamlFileId = ?1", params![id])?;
+    tx.execute("DELETE FROM graphvizYamlFiles WHERE id = ?1", params![id])?;
+    tx.commit()?;
 
     Ok(())
 }
 
+const YAML_FILE_COLUMNS: &str = "id, name, description, yamlContent, yamlSchema, yamlType,
+                organizationId, tags, version, parentYamlFileId, searchableText,
+                semanticCategory, keywords, contentSummary,
+                chromaSynced, chromaSyncError, lastChromaSyncAttempt,
+                lastSearchDate, searchCount, createdAt, updatedAt";
+
+fn read_yaml_file(row: &rusqlite::Row) -> SqlResult<GraphvizYamlFile> {
+    Ok(GraphvizYamlFile {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        yaml_content: row.get(3)?,
+        yaml_schema: row.get(4)?,
+        yaml_type: row.get(5)?,
+        organization_id: row.get(6)?,
+        tags: row.get(7)?,
+        version: row.get(8)?,
+        parent_yaml_file_id: row.get(9)?,
+        searchable_text: row.get(10)?,
+        semantic_category: row.get(11)?,
+        keywords: row.get(12)?,
+        content_summary: row.get(13)?,
+        chroma_synced: row.get(14)?,
+        chroma_sync_error: row.get(15)?,
+        last_chroma_sync_attempt: row.get(16)?,
+        last_search_date: row.get(17)?,
+        search_count: row.get(18)?,
+        created_at: row.get(19)?,
+        updated_at: row.get(20)?,
+    })
+}
+
+/// YAMLファイルのバージョン間の差分
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphvizYamlVersionDiff {
+    #[serde(rename = "fromId")]
+    pub from_id: String,
+    #[serde(rename = "fromVersion")]
+    pub from_version: i32,
+    #[serde(rename = "toId")]
+    pub to_id: String,
+    #[serde(rename = "toVersion")]
+    pub to_version: i32,
+    /// yamlSchemaが変わったか
+    #[serde(rename = "schemaChanged")]
+    pub schema_changed: bool,
+    #[serde(flatten)]
+    pub diff: TextDiff,
+}
+
+/// YAMLファイルのすべてのバージョンを新しい順に取得（現在の行 + 履歴行）
+/// idには現在の行・履歴行のどちらのIDも指定できる
+pub fn list_graphviz_yaml_versions(id: &str) -> SqlResult<Vec<GraphvizYamlFile>> {
+    let current = get_graphviz_yaml_file_by_id(id)?;
+    let root_id = current.parent_yaml_file_id.clone().unwrap_or_else(|| current.id.clone());
+
+    let db = get_db().ok_or_else(|| {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
+            Some("データベースが初期化されていません".to_string()),
+        )
+    })?;
+
+    let conn = db.get_connection()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM graphvizYamlFiles
+         WHERE id = ?1 OR parentYamlFileId = ?1
+         ORDER BY version DESC, createdAt DESC",
+        YAML_FILE_COLUMNS
+    ))?;
+    let versions = stmt.query_map(params![root_id], read_yaml_file)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(versions)
+}
+
+/// 同じYAMLファイルの2つのバージョンの行単位の差分を取得
+pub fn diff_graphviz_yaml_versions(from_id: &str, to_id: &str) -> Result<GraphvizYamlVersionDiff, String> {
+    let from = get_graphviz_yaml_file_by_id(from_id).map_err(|e| format!("バージョンの取得に失敗しました: {}", e))?;
+    let to = get_graphviz_yaml_file_by_id(to_id).map_err(|e| format!("バージョンの取得に失敗しました: {}", e))?;
+    let root_of = |file: &GraphvizYamlFile| file.parent_yaml_file_id.clone().unwrap_or_else(|| file.id.clone());
+    if root_of(&from) != root_of(&to) {
+        return Err("異なるYAMLファイルのバージョンは比較できません".to_string());
+    }
+
+    Ok(GraphvizYamlVersionDiff {
+        schema_changed: from.yaml_schema != to.yaml_schema,
+        diff: diff_lines(&from.yaml_content, &to.yaml_content),
+        from_id: from.id,
+        from_version: from.version,
+        to_id: to.id,
+        to_version: to.version,
+    })
+}
+
+/// 過去のバージョンの内容に戻す
+/// 履歴は削除せず、現在の状態を履歴行として残したうえで新しいバージョンとして保存する
+pub fn rollback_graphviz_yaml_file(id: &str, version_id: &str) -> Result<GraphvizYamlFile, String> {
+    let version = get_graphviz_yaml_file_by_id(version_id).map_err(|e| format!("バージョンの取得に失敗しました: {}", e))?;
+    if version.parent_yaml_file_id.as_deref() != Some(id) {
+        return Err(format!("バージョン {} はYAMLファイル {} の履歴ではありません", version_id, id));
+    }
+
+    let existing = get_graphviz_yaml_file_by_id(id).map_err(|e| e.to_string())?;
+    if existing.parent_yaml_file_id.is_some() {
+        return Err("過去のバージョンは更新できません。ロールバックを使用してください".to_string());
+    }
+
+    // 説明・スキーマなどが空のバージョンに戻す場合も現在の値を引き継がないよう、スナップショットの値をそのまま書き込む
+    let restored = GraphvizYamlFile {
+        name: version.name,
+        description: version.description,
+        yaml_content: version.yaml_content,
+        yaml_schema: version.yaml_schema,
+        yaml_type: version.yaml_type,
+        semantic_category: version.semantic_category,
+        keywords: version.keywords,
+        content_summary: version.content_summary,
+        ..existing.clone()
+    };
+    write_graphviz_yaml_file_update(&existing, restored, true)
+}
+
 /// DOTファイルを作成
 pub fn create_graphviz_dot_file(
     yaml_file_id: String,
@@ -0,0 +1,1183 @@
+/**
+ * Graphviz DOT生成モジュール
+ * エンティティ/リレーションのサブグラフ、またはgraphvizYamlFilesのYAML定義からDOTを生成する
+ *
+ * - サブグラフ: entityIds / relationIds で選択（未指定の場合は組織/事業会社のエンティティ全体）
+ * - YAML: アプリのYAML種別（topology / device / links / intent / site-topology / site-equipment / rack-servers / server-details）を
+ *   フロントエンド（yamlToDotAdvanced.ts）と同じ表示に変換する。graph.nodes / graph.edges 形式にも対応
+ * - エンティティ種別・リレーション種別ごとのスタイルルールを適用できる
+ * - organizationIdごとにclusterサブグラフでまとめられる
+ * - 生成結果にはnodeCount / edgeCountを含め、そのままgraphvizDotFilesに保存できる
+ */
+
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use yaml_rust2::{Yaml, YamlLoader};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::database::get_db;
+use crate::database::graphviz::{create_graphviz_dot_file, get_graphviz_yaml_file_by_id, GraphvizDotFile};
+use crate::database::knowledge_graph::{edges_within, load_edges, load_nodes, load_scope_entity_ids, GraphFilter};
+
+/// 生成するノード数のデフォルト・上限
+const DEFAULT_MAX_NODES: usize = 500;
+const MAX_NODES_LIMIT: usize = 5000;
+
+/// 種別ごとのスタイルが見つからない場合に使うキー
+const FALLBACK_STYLE_KEY: &str = "*";
+
+/// YAMLのノード/エッジからDOT属性としてそのまま引き継ぐキー
+const PASSTHROUGH_ATTRIBUTES: &[&str] = &[
+    "shape", "color", "style", "fillcolor", "fontcolor", "fontname", "fontsize", "penwidth",
+    "tooltip", "URL", "width", "height", "arrowhead", "arrowtail", "dir", "weight", "constraint",
+];
+
+/// DOT属性（属性名 → 値）
+pub type DotAttributes = BTreeMap<String, String>;
+
+/// DOT生成の条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DotGenerationOptions {
+    /// 対象のエンティティID（サブグラフ）
+    #[serde(rename = "entityIds")]
+    pub entity_ids: Option<Vec<String>>,
+    /// 対象のリレーションID（両端のエンティティも含める）
+    #[serde(rename = "relationIds")]
+    pub relation_ids: Option<Vec<String>>,
+    /// YAMLファイルID（指定した場合はYAML定義から生成）
+    #[serde(rename = "yamlFileId")]
+    pub yaml_file_id: Option<String>,
+    /// サブグラフのエッジ条件（組織/事業会社・relationType・confidence）
+    #[serde(default, flatten)]
+    pub filter: GraphFilter,
+    /// エンティティ種別ごとのノード属性。YAMLでは layer・port・server・rack などの変換後の種別に対応する。"*" は種別が一致しない場合に使う
+    #[serde(rename = "entityTypeStyles", default)]
+    pub entity_type_styles: HashMap<String, DotAttributes>,
+    /// リレーション種別（YAMLではエッジのtype）ごとのエッジ属性。"*" は種別が一致しない場合に使う
+    #[serde(rename = "relationTypeStyles", default)]
+    pub relation_type_styles: HashMap<String, DotAttributes>,
+    /// organizationIdごとにclusterサブグラフでまとめるか
+    #[serde(rename = "clusterByOrganization", default)]
+    pub cluster_by_organization: bool,
+    /// グラフ名（未指定の場合はYAMLのgraph.name、なければ "knowledge_graph"）
+    #[serde(rename = "graphName")]
+    pub graph_name: Option<String>,
+    /// レイアウト方向（TB / LR / BT / RL、デフォルトはLR）
+    pub rankdir: Option<String>,
+    /// 有向グラフにするか（未指定の場合はYAMLのgraph.type、なければ有向）
+    pub directed: Option<bool>,
+    /// リレーション種別をエッジのラベルにするか（デフォルトはtrue）
+    #[serde(rename = "showRelationLabels")]
+    pub show_relation_labels: Option<bool>,
+    /// サブグラフのノード数の上限
+    #[serde(rename = "maxNodes")]
+    pub max_nodes: Option<usize>,
+}
+
+/// DOTの保存先の情報
+#[derive(Debug, Clone, Deserialize)]
+pub struct DotSaveOptions {
+    /// 紐づけるYAMLファイルID（YAMLから生成した場合は省略可）
+    #[serde(rename = "yamlFileId")]
+    pub yaml_file_id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "viewType")]
+    pub view_type: Option<String>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// DOT生成の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct DotGenerationResult {
+    #[serde(rename = "dotContent")]
+    pub dot_content: String,
+    /// digraph / graph
+    #[serde(rename = "graphType")]
+    pub graph_type: String,
+    #[serde(rename = "nodeCount")]
+    pub node_count: usize,
+    #[serde(rename = "edgeCount")]
+    pub edge_count: usize,
+    /// ノード数の上限でサブグラフを打ち切ったか
+    pub truncated: bool,
+}
+
+struct DotNode {
+    id: String,
+    label: String,
+    node_type: Option<String>,
+    /// 組織ID（clusterByOrganizationでまとめる）
+    cluster: Option<String>,
+    /// YAMLの構造上のまとまり（ネットワーク・ラックなど、常にclusterサブグラフで表示）
+    group: Option<String>,
+    /// 表示の既定の属性（種別ごとのスタイルルールで上書きできる）
+    base: DotAttributes,
+    attributes: DotAttributes,
+}
+
+struct DotEdge {
+    source: String,
+    target: String,
+    edge_type: Option<String>,
+    label: Option<String>,
+    base: DotAttributes,
+    attributes: DotAttributes,
+}
+
+/// YAMLの構造上のまとまり（clusterサブグラフ）
+struct DotGroup {
+    id: String,
+    label: String,
+}
+
+struct DotGraph {
+    name: String,
+    directed: bool,
+    nodes: Vec<DotNode>,
+    edges: Vec<DotEdge>,
+    groups: Vec<DotGroup>,
+    truncated: bool,
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+/// DOTの引用符付き文字列にエスケープ
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => {}
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn format_attributes(attributes: &DotAttributes) -> String {
+    attributes
+        .iter()
+        .map(|(key, value)| format!("{}={}", quote(key), quote(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 種別のスタイル（なければ "*" のスタイル）
+fn style_for<'a>(styles: &'a HashMap<String, DotAttributes>, style_type: Option<&str>) -> Option<&'a DotAttributes> {
+    style_type
+        .and_then(|t| styles.get(t))
+        .or_else(|| styles.get(FALLBACK_STYLE_KEY))
+}
+
+/// 既定の属性・スタイルルール・個別の属性の順に重ねる
+fn merge_attributes(base: &DotAttributes, style: Option<&DotAttributes>, own: &DotAttributes) -> DotAttributes {
+    let mut merged = base.clone();
+    if let Some(style) = style {
+        merged.extend(style.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+    merged.extend(own.iter().map(|(k, v)| (k.clone(), v.clone())));
+    merged
+}
+
+/// 表示されるエッジか（並び順のための不可視エッジは数えない）
+fn is_visible_edge(edge: &DotEdge) -> bool {
+    merge_attributes(&edge.base, None, &edge.attributes).get("style").map(|s| s.as_str()) != Some("invis")
+}
+
+/// 組織IDから組織名を取得（見つからない組織はIDのまま表示）
+fn load_organization_names(conn: &Connection, ids: &[String]) -> Result<HashMap<String, String>, String> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let sql = format!(
+        "SELECT id, name FROM organizations WHERE id IN ({})",
+        vec!["?"; ids.len()].join(", ")
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| format!("組織の取得に失敗しました: {}", e))?;
+    let rows = stmt
+        .query_map(params_from_iter(ids.iter()), |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("組織の取得に失敗しました: {}", e))?;
+    rows.collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("組織の取得に失敗しました: {}", e))
+}
+
+fn render(graph: &DotGraph, options: &DotGenerationOptions, cluster_labels: &HashMap<String, String>) -> String {
+    let (keyword, connector) = if graph.directed { ("digraph", "->") } else { ("graph", "--") };
+    let rankdir = options.rankdir.as_deref().filter(|r| !r.is_empty()).unwrap_or("LR");
+
+    let mut lines = vec![
+        format!("{} {} {{", keyword, quote(&graph.name)),
+        format!("  graph [rankdir={}];", quote(rankdir)),
+        "  node [shape=\"box\", style=\"rounded\"];".to_string(),
+    ];
+
+    let node_line = |node: &DotNode, indent: &str| {
+        let mut attributes = merge_attributes(
+            &node.base,
+            style_for(&options.entity_type_styles, node.node_type.as_deref()),
+            &node.attributes,
+        );
+        attributes.insert("label".to_string(), node.label.clone());
+        format!("{}{} [{}];", indent, quote(&node.id), format_attributes(&attributes))
+    };
+
+    // YAMLの構造上のまとまりは定義順に出力する
+    for group in &graph.groups {
+        let nodes: Vec<&DotNode> = graph.nodes.iter().filter(|n| n.group.as_ref() == Some(&group.id)).collect();
+        if nodes.is_empty() {
+            continue;
+        }
+        lines.push(String::new());
+        lines.push(format!("  subgraph {} {{", quote(&format!("cluster_{}", group.id))));
+        lines.push(format!("    label={};", quote(&group.label)));
+        lines.push("    style=\"rounded\";".to_string());
+        lines.extend(nodes.iter().map(|node| node_line(node, "    ")));
+        lines.push("  }".to_string());
+    }
+
+    // 組織のclusterは組織名順に出力し、生成結果を安定させる
+    let mut clusters: BTreeMap<(String, String), Vec<&DotNode>> = BTreeMap::new();
+    let mut loose_nodes = Vec::new();
+    for node in graph.nodes.iter().filter(|n| n.group.is_none()) {
+        match node.cluster.as_ref().filter(|_| options.cluster_by_organization) {
+            Some(cluster) => {
+                let label = cluster_labels.get(cluster).cloned().unwrap_or_else(|| cluster.clone());
+                clusters.entry((label, cluster.clone())).or_default().push(node);
+            }
+            None => loose_nodes.push(node),
+        }
+    }
+    for (index, ((label, _), nodes)) in clusters.iter().enumerate() {
+        lines.push(String::new());
+        lines.push(format!("  subgraph {} {{", quote(&format!("cluster_{}", index))));
+        lines.push(format!("    label={};", quote(label)));
+        lines.push("    style=\"rounded\";".to_string());
+        lines.extend(nodes.iter().map(|node| node_line(node, "    ")));
+        lines.push("  }".to_string());
+    }
+    if !loose_nodes.is_empty() {
+        lines.push(String::new());
+        lines.extend(loose_nodes.iter().map(|node| node_line(node, "  ")));
+    }
+
+    if !graph.edges.is_empty() {
+        lines.push(String::new());
+    }
+    for edge in &graph.edges {
+        let mut attributes = merge_attributes(
+            &edge.base,
+            style_for(&options.relation_type_styles, edge.edge_type.as_deref()),
+            &edge.attributes,
+        );
+        if let Some(label) = edge.label.as_ref().filter(|l| !l.is_empty()) {
+            attributes.insert("label".to_string(), label.clone());
+        }
+        let attributes = format_attributes(&attributes);
+        if attributes.is_empty() {
+            lines.push(format!("  {} {} {};", quote(&edge.source), connector, quote(&edge.target)));
+        } else {
+            lines.push(format!("  {} {} {} [{}];", quote(&edge.source), connector, quote(&edge.target), attributes));
+        }
+    }
+
+    lines.push("}".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// エンティティ/リレーションのサブグラフを読み込む
+fn build_subgraph(conn: &Connection, options: &DotGenerationOptions) -> Result<DotGraph, String> {
+    let max_nodes = options.max_nodes.unwrap_or(DEFAULT_MAX_NODES).clamp(1, MAX_NODES_LIMIT);
+    let edges = load_edges(conn, &options.filter).map_err(|e| format!("リレーションの取得に失敗しました: {}", e))?;
+
+    let entity_ids = options.entity_ids.as_ref().filter(|ids| !ids.is_empty());
+    let relation_ids = options.relation_ids.as_ref().filter(|ids| !ids.is_empty());
+
+    let mut node_ids: Vec<String> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut selected_edges = Vec::new();
+    if entity_ids.is_none() && relation_ids.is_none() {
+        let scope_ids = load_scope_entity_ids(conn, &options.filter)
+            .map_err(|e| format!("エンティティの取得に失敗しました: {}", e))?;
+        node_ids.extend(scope_ids.into_iter().filter(|id| seen.insert(id.clone())));
+    } else {
+        if let Some(ids) = entity_ids {
+            node_ids.extend(ids.iter().filter(|id| seen.insert((*id).clone())).cloned());
+        }
+        if let Some(ids) = relation_ids {
+            let relation_set: HashSet<&String> = ids.iter().collect();
+            for edge in edges.iter().filter(|e| relation_set.contains(&e.id)) {
+                for id in [&edge.source, &edge.target] {
+                    if seen.insert(id.clone()) {
+                        node_ids.push(id.clone());
+                    }
+                }
+                selected_edges.push(edge.clone());
+            }
+        }
+    }
+
+    let truncated = node_ids.len() > max_nodes;
+    node_ids.truncate(max_nodes);
+    let mut entities = load_nodes(conn, &node_ids).map_err(|e| format!("エンティティの取得に失敗しました: {}", e))?;
+    let node_ids: Vec<String> = node_ids.into_iter().filter(|id| entities.contains_key(id)).collect();
+    let node_set: HashSet<String> = node_ids.iter().cloned().collect();
+
+    // entityIdsを指定した場合や範囲全体の場合は、ノード間のリレーションをすべて含める
+    let graph_edges = if entity_ids.is_some() || relation_ids.is_none() {
+        let mut edge_ids: HashSet<String> = selected_edges.iter().map(|e| e.id.clone()).collect();
+        let mut combined = edges_within(&selected_edges, &node_set);
+        combined.extend(edges_within(&edges, &node_set).into_iter().filter(|e| edge_ids.insert(e.id.clone())));
+        combined
+    } else {
+        edges_within(&selected_edges, &node_set)
+    };
+
+    let show_labels = options.show_relation_labels.unwrap_or(true);
+    let nodes = node_ids
+        .iter()
+        .filter_map(|id| entities.remove(id))
+        .map(|node| DotNode {
+            id: node.id,
+            label: node.label,
+            node_type: Some(node.entity_type),
+            cluster: node.organization_id.filter(|o| !o.is_empty()),
+            group: None,
+            base: DotAttributes::new(),
+            attributes: DotAttributes::new(),
+        })
+        .collect();
+    let edges = graph_edges
+        .into_iter()
+        .map(|edge| {
+            let mut attributes = DotAttributes::new();
+            if let Some(description) = edge.description.filter(|d| !d.is_empty()) {
+                attributes.insert("tooltip".to_string(), description);
+            }
+            DotEdge {
+                source: edge.source,
+                target: edge.target,
+                label: if show_labels { Some(edge.relation_type.clone()) } else { None },
+                edge_type: Some(edge.relation_type),
+                base: DotAttributes::new(),
+                attributes,
+            }
+        })
+        .collect();
+
+    Ok(DotGraph {
+        name: options.graph_name.clone().unwrap_or_else(|| "knowledge_graph".to_string()),
+        directed: options.directed.unwrap_or(true),
+        nodes,
+        edges,
+        groups: Vec::new(),
+        truncated,
+    })
+}
+
+/// YAMLのスカラー値を文字列に変換
+fn yaml_scalar(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// マッピングのキーの値（マッピング以外・キーが無い場合はNone）
+fn yaml_get<'a>(value: &'a Yaml, key: &str) -> Option<&'a Yaml> {
+    Some(&value[key]).filter(|v| !v.is_badvalue())
+}
+
+fn yaml_field(value: &Yaml, key: &str) -> Option<String> {
+    yaml_get(value, key).and_then(yaml_scalar).filter(|s| !s.is_empty())
+}
+
+/// 配列のキーの要素（配列以外・キーが無い場合は空）
+fn yaml_list<'a>(value: &'a Yaml, key: &str) -> &'a [Yaml] {
+    yaml_get(value, key).and_then(|v| v.as_vec()).map(|v| v.as_slice()).unwrap_or(&[])
+}
+
+/// JavaScriptの真偽判定と同じく、値が設定されているか（null・false・空文字・0は未設定）
+fn yaml_truthy(value: &Yaml, key: &str) -> bool {
+    match yaml_get(value, key) {
+        None | Some(Yaml::Null) | Some(Yaml::Boolean(false)) | Some(Yaml::Integer(0)) => false,
+        Some(Yaml::String(s)) => !s.is_empty(),
+        Some(_) => true,
+    }
+}
+
+fn yaml_attributes(value: &Yaml) -> DotAttributes {
+    PASSTHROUGH_ATTRIBUTES
+        .iter()
+        .filter_map(|key| yaml_field(value, key).map(|v| (key.to_string(), v)))
+        .collect()
+}
+
+fn attributes(pairs: &[(&str, &str)]) -> DotAttributes {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+/// 立体の箱で表示するノードのスタイル
+fn box_style(fill: &str, color: &str, penwidth: &str) -> DotAttributes {
+    attributes(&[("shape", "box3d"), ("style", "rounded,filled"), ("fillcolor", fill), ("color", color), ("penwidth", penwidth)])
+}
+
+/// ポートのノードのスタイル
+fn port_style(style: &str, fill: &str, color: &str, penwidth: &str) -> DotAttributes {
+    attributes(&[("shape", "tab"), ("style", style), ("fillcolor", fill), ("color", color), ("penwidth", penwidth)])
+}
+
+/// 縦に並べるための不可視エッジ
+fn invisible_style() -> DotAttributes {
+    attributes(&[("style", "invis")])
+}
+
+/// 機器からポートへの補助線
+fn port_link_style() -> DotAttributes {
+    attributes(&[("style", "dashed"), ("color", "gray"), ("arrowhead", "none")])
+}
+
+/// ラベルの行を改行でつなぐ（空の行は除く）
+fn label_lines(lines: impl IntoIterator<Item = Option<String>>) -> Option<String> {
+    let lines: Vec<String> = lines.into_iter().flatten().filter(|l| !l.is_empty()).collect();
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// graphvizYamlFilesのYAMLの種別（app/graphviz/components/utils/yamlSchemas.tsのdetectYamlTypeと同じ順序で判定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum YamlDocumentType {
+    SiteTopology,
+    SiteEquipment,
+    RackServers,
+    ServerDetails,
+    Topology,
+    Device,
+    Links,
+    Intent,
+    /// graph.nodes / graph.edges 形式
+    Graph,
+}
+
+fn detect_yaml_type(root: &Yaml) -> Option<YamlDocumentType> {
+    root.as_hash()?;
+    let declared = yaml_field(root, "type");
+    let is = |name: &str| declared.as_deref() == Some(name);
+
+    let detected = if is("site-topology") || yaml_truthy(root, "sites") {
+        YamlDocumentType::SiteTopology
+    } else if is("site-equipment") || (yaml_truthy(root, "racks") && yaml_truthy(root, "siteId")) {
+        YamlDocumentType::SiteEquipment
+    } else if is("rack-servers") || (yaml_truthy(root, "servers") && yaml_truthy(root, "rackId")) {
+        YamlDocumentType::RackServers
+    } else if is("server-details") || (yaml_truthy(root, "serverId") && (yaml_truthy(root, "os") || yaml_truthy(root, "sequences"))) {
+        YamlDocumentType::ServerDetails
+    } else if is("topology") || yaml_truthy(root, "layers") {
+        YamlDocumentType::Topology
+    } else if is("device") || yaml_truthy(root, "ports") {
+        YamlDocumentType::Device
+    } else if is("links") || yaml_truthy(root, "connections") {
+        YamlDocumentType::Links
+    } else if is("intent") || yaml_truthy(root, "rules") {
+        YamlDocumentType::Intent
+    } else {
+        let graph = yaml_get(root, "graph").filter(|g| g.as_hash().is_some()).unwrap_or(root);
+        if yaml_get(graph, "nodes").and_then(|v| v.as_vec()).is_some() || yaml_get(graph, "edges").and_then(|v| v.as_vec()).is_some() {
+            YamlDocumentType::Graph
+        } else {
+            return None;
+        }
+    };
+    Some(detected)
+}
+
+/// YAMLからグラフを組み立てる（同じIDのノードは最初の定義を使い、クラスター・ノード・エッジの順序を保つ）
+#[derive(Default)]
+struct YamlGraphBuilder {
+    nodes: Vec<DotNode>,
+    node_ids: HashSet<String>,
+    edges: Vec<DotEdge>,
+    groups: Vec<DotGroup>,
+}
+
+impl YamlGraphBuilder {
+    fn group(&mut self, id: &str, label: &str) -> String {
+        if !self.groups.iter().any(|g| g.id == id) {
+            self.groups.push(DotGroup { id: id.to_string(), label: label.to_string() });
+        }
+        id.to_string()
+    }
+
+    fn node(&mut self, id: &str, label: &str, node_type: Option<&str>, group: Option<&str>, base: DotAttributes) {
+        if !self.node_ids.insert(id.to_string()) {
+            return;
+        }
+        self.nodes.push(DotNode {
+            id: id.to_string(),
+            label: label.to_string(),
+            node_type: node_type.map(|t| t.to_string()),
+            cluster: None,
+            group: group.map(|g| g.to_string()),
+            base,
+            attributes: DotAttributes::new(),
+        });
+    }
+
+    /// エッジを追加（ノードとして定義されていない端点も、Graphvizと同様に暗黙のノードとして数える）
+    fn edge(&mut self, source: &str, target: &str, label: Option<String>, edge_type: Option<&str>, base: DotAttributes) {
+        for id in [source, target] {
+            self.node(id, id, None, None, DotAttributes::new());
+        }
+        self.edges.push(DotEdge {
+            source: source.to_string(),
+            target: target.to_string(),
+            edge_type: edge_type.map(|t| t.to_string()),
+            label,
+            base,
+            attributes: DotAttributes::new(),
+        });
+    }
+
+    /// ノードを並び順に不可視エッジでつなぐ（クラスター内で縦に並べる）
+    fn chain(&mut self, ids: &[String]) {
+        for pair in ids.windows(2) {
+            self.edge(&pair[0], &pair[1], None, None, invisible_style());
+        }
+    }
+
+    fn finish(self, name: String, directed: bool) -> DotGraph {
+        DotGraph { name, directed, nodes: self.nodes, edges: self.edges, groups: self.groups, truncated: false }
+    }
+}
+
+/// topology: ネットワークごとのレイヤー（旧形式はトップレベルのlayers）を階層順につなぐ
+fn build_topology_view(b: &mut YamlGraphBuilder, root: &Yaml) {
+    let networks = yaml_list(root, "networks");
+    if networks.is_empty() {
+        let mut layer_ids = Vec::new();
+        for layer in yaml_list(root, "layers") {
+            let Some(id) = yaml_field(layer, "id") else { continue };
+            let node_id = format!("layer_{}", id);
+            let label = yaml_field(layer, "label").unwrap_or(id);
+            b.node(&node_id, &label, Some("layer"), None, box_style("lightblue", "blue", "2"));
+            layer_ids.push(node_id);
+        }
+        for pair in layer_ids.windows(2) {
+            b.edge(&pair[0], &pair[1], None, None, attributes(&[("style", "dashed")]));
+        }
+        return;
+    }
+
+    for network in networks {
+        let layers = yaml_list(network, "layers");
+        if layers.is_empty() {
+            continue;
+        }
+        let prefix = yaml_field(network, "id")
+            .or_else(|| yaml_field(network, "label"))
+            .unwrap_or_else(|| "network".to_string());
+        let label = yaml_field(network, "label").unwrap_or_else(|| prefix.clone());
+        let group = b.group(&prefix, &label);
+
+        let mut layer_ids = Vec::new();
+        for layer in layers {
+            let Some(id) = yaml_field(layer, "id") else { continue };
+            let layer_id = format!("{}_{}", prefix, id);
+            let layer_label = yaml_field(layer, "label").unwrap_or(id);
+            b.node(&layer_id, &layer_label, Some("layer"), Some(&group), box_style("lightblue", "blue", "2"));
+            for variant in yaml_list(layer, "variants") {
+                let Some(variant_id) = yaml_field(variant, "id") else { continue };
+                let node_id = format!("{}_{}", prefix, variant_id);
+                let variant_label = yaml_field(variant, "label").unwrap_or(variant_id);
+                let mut style = box_style("lightgray", "gray", "1.5");
+                style.insert("style".to_string(), "rounded,filled,dashed".to_string());
+                b.node(&node_id, &variant_label, Some("variant"), Some(&group), style);
+                b.edge(&layer_id, &node_id, None, None, attributes(&[("style", "dotted")]));
+            }
+            layer_ids.push(layer_id);
+        }
+        for pair in layer_ids.windows(2) {
+            b.edge(&pair[0], &pair[1], None, None, attributes(&[("style", "dashed")]));
+        }
+    }
+}
+
+/// device: 機器とそのポート
+fn build_device_view(b: &mut YamlGraphBuilder, root: &Yaml) -> Result<(), String> {
+    let device_id = yaml_field(root, "id").ok_or_else(|| "機器のidがありません".to_string())?;
+    let label = yaml_field(root, "label").unwrap_or_else(|| device_id.clone());
+    let device_type = yaml_field(root, "type").unwrap_or_else(|| "device".to_string());
+    b.node(&device_id, &label, Some(&device_type), None, box_style("lightcyan", "cyan", "2"));
+    for port in yaml_list(root, "ports") {
+        let Some(port_id) = yaml_field(port, "id") else { continue };
+        let node_id = format!("{}_{}", device_id, port_id);
+        let port_label = yaml_field(port, "label").unwrap_or(port_id);
+        b.node(&node_id, &port_label, Some("port"), None, port_style("filled", "lightgray", "gray", "1"));
+        b.edge(&device_id, &node_id, None, None, port_link_style());
+    }
+    Ok(())
+}
+
+/// links: 機器間の接続（from.device → to.device、ポートとケーブルをラベルにする）
+fn build_links_view(b: &mut YamlGraphBuilder, root: &Yaml, show_labels: bool) {
+    let connections = yaml_list(root, "connections");
+    for connection in connections {
+        for end in ["from", "to"] {
+            if let Some(device) = yaml_get(connection, end).and_then(|e| yaml_field(e, "device")) {
+                b.node(&device, &device, Some("device"), None, box_style("lightcyan", "cyan", "2"));
+            }
+        }
+    }
+
+    for connection in connections {
+        let (Some(from), Some(to)) = (yaml_get(connection, "from"), yaml_get(connection, "to")) else { continue };
+        let (Some(from_device), Some(to_device)) = (yaml_field(from, "device"), yaml_field(to, "device")) else { continue };
+
+        let ports = match (yaml_field(from, "port"), yaml_field(to, "port")) {
+            (Some(from_port), Some(to_port)) => Some(format!("{} → {}", from_port, to_port)),
+            (Some(from_port), None) => Some(from_port),
+            _ => None,
+        };
+        let cable = yaml_field(connection, "cable_type").map(|cable_type| match yaml_field(connection, "cable_spec") {
+            Some(spec) => format!("{} ({})", cable_type, spec),
+            None => cable_type,
+        });
+
+        let mut style = DotAttributes::new();
+        if yaml_field(connection, "status").as_deref() == Some("inactive") {
+            style.extend(attributes(&[("style", "dashed"), ("color", "gray")]));
+        }
+        match yaml_field(connection, "location").as_deref() {
+            Some("other_floor") => style.extend(attributes(&[("color", "red"), ("style", "dashed")])),
+            Some("other_rack") => style.extend(attributes(&[("color", "orange"), ("style", "dashed")])),
+            _ => {}
+        }
+        let label = label_lines([ports, cable]).filter(|_| show_labels);
+        let edge_type = yaml_field(connection, "network").or_else(|| yaml_field(root, "network"));
+        b.edge(&from_device, &to_device, label, edge_type.as_deref(), style);
+    }
+}
+
+/// intent: ルールを注記として表示
+fn build_intent_view(b: &mut YamlGraphBuilder, root: &Yaml) {
+    for (i, rule) in yaml_list(root, "rules").iter().enumerate() {
+        let name = yaml_field(rule, "name").unwrap_or_else(|| format!("rule_{}", i + 1));
+        let label = label_lines([
+            Some(name.clone()),
+            yaml_field(rule, "description"),
+            yaml_field(rule, "applies_to").map(|a| format!("対象: {}", a)),
+        ])
+        .unwrap_or_else(|| name.clone());
+        b.node(
+            &format!("rule_{}", name),
+            &label,
+            Some("rule"),
+            None,
+            attributes(&[("shape", "note"), ("style", "filled"), ("fillcolor", "lightyellow"), ("color", "orange")]),
+        );
+    }
+}
+
+/// site-topology: 棟と棟間の接続
+fn build_site_topology_view(b: &mut YamlGraphBuilder, root: &Yaml, show_labels: bool) {
+    for site in yaml_list(root, "sites") {
+        let Some(id) = yaml_field(site, "id") else { continue };
+        let address = yaml_get(site, "location").map(|l| yaml_field(l, "address").unwrap_or_default());
+        let label = label_lines([yaml_field(site, "label").or(Some(id.clone())), address]).unwrap_or_else(|| id.clone());
+        b.node(&id, &label, Some("site"), None, box_style("lightblue", "blue", "2"));
+    }
+    for connection in yaml_list(root, "connections") {
+        let (Some(from), Some(to)) = (yaml_field(connection, "from"), yaml_field(connection, "to")) else { continue };
+        let connection_type = yaml_field(connection, "type");
+        let label = label_lines([connection_type.clone(), yaml_field(connection, "bandwidth")]).filter(|_| show_labels);
+        let style = if yaml_truthy(connection, "provider") { attributes(&[("color", "blue")]) } else { DotAttributes::new() };
+        b.edge(&from, &to, label, connection_type.as_deref(), style);
+    }
+}
+
+/// ラック内の機器の開始U位置（position_u: [30, 41] / position.unit: 25 / "1-4"、不明な場合は末尾）
+fn equipment_u_start(equipment: &Yaml) -> i64 {
+    if let Some(start) = yaml_list(equipment, "position_u").first().and_then(|u| u.as_i64()) {
+        return start;
+    }
+    match yaml_get(equipment, "position").and_then(|p| yaml_get(p, "unit")) {
+        Some(Yaml::Integer(unit)) => *unit,
+        Some(Yaml::String(unit)) => unit
+            .trim()
+            .split('-')
+            .next()
+            .and_then(|start| start.parse().ok())
+            .unwrap_or(9999),
+        _ => 9999,
+    }
+}
+
+/// ラック内の機器（devices、なければequipment）
+fn rack_equipment(rack: &Yaml) -> &[Yaml] {
+    let devices = yaml_list(rack, "devices");
+    if yaml_get(rack, "devices").and_then(|v| v.as_vec()).is_some() {
+        devices
+    } else {
+        yaml_list(rack, "equipment")
+    }
+}
+
+/// ケーブル種別の定義（cable_types）からラベルの部品を作る
+fn cable_label_parts(root: &Yaml, connection: &Yaml) -> Vec<String> {
+    let mut parts = Vec::new();
+    let cable = yaml_field(connection, "cable");
+    match cable.as_deref().and_then(|c| yaml_get(root, "cable_types").and_then(|types| yaml_get(types, c))) {
+        Some(cable_type) => {
+            parts.extend(yaml_field(cable_type, "spec"));
+            parts.extend(yaml_field(cable_type, "speed"));
+        }
+        None => parts.extend(cable),
+    }
+    if let Some(count) = yaml_get(connection, "count").and_then(|c| c.as_i64()).filter(|c| *c > 1) {
+        parts.push(format!("({}本)", count));
+    }
+    parts
+}
+
+/// site-equipment: ラックごとの機器（U位置の高い順）と、機器間・電源・データの接続
+fn build_site_equipment_view(b: &mut YamlGraphBuilder, root: &Yaml, show_labels: bool) {
+    let racks: Vec<&Yaml> = match yaml_get(root, "racks").and_then(|v| v.as_vec()) {
+        Some(racks) => racks.iter().collect(),
+        None => yaml_get(root, "rack").filter(|r| r.as_hash().is_some()).into_iter().collect(),
+    };
+
+    // 機器IDと機器種別から機器IDを引く（電源・データの接続は種別で指定されることがある）
+    let mut device_ids: HashMap<String, String> = HashMap::new();
+    for rack in &racks {
+        let Some(rack_id) = yaml_field(rack, "id") else { continue };
+        let rack_label = yaml_field(rack, "label").unwrap_or_else(|| rack_id.clone());
+        let group = b.group(&rack_id, &rack_label);
+        let mut rack_style = box_style("lightgray", "gray", "2");
+        rack_style.insert("fontcolor".to_string(), "white".to_string());
+        b.node(&rack_id, &rack_label, Some("rack"), Some(&group), rack_style);
+
+        let mut equipment: Vec<&Yaml> = rack_equipment(rack).iter().collect();
+        equipment.sort_by_key(|e| std::cmp::Reverse(equipment_u_start(e)));
+        let mut ordered = Vec::new();
+        for item in equipment {
+            let Some(id) = yaml_field(item, "id") else { continue };
+            let equipment_type = yaml_field(item, "type").unwrap_or_else(|| "unknown".to_string());
+            device_ids.entry(id.clone()).or_insert_with(|| id.clone());
+            device_ids.entry(equipment_type.clone()).or_insert_with(|| id.clone());
+
+            let (label, style) = if equipment_type == "server_group" {
+                let label = yaml_field(item, "label").unwrap_or_else(|| {
+                    format!(
+                        "{} ({}台)",
+                        yaml_field(item, "model").unwrap_or_else(|| "Server Group".to_string()),
+                        yaml_field(item, "count").unwrap_or_else(|| "0".to_string())
+                    )
+                });
+                (label, box_style("lightyellow", "orange", "2"))
+            } else {
+                let (fill, color) = match equipment_type.as_str() {
+                    "server" => ("lightyellow", "orange"),
+                    "switch" | "spine" | "server_leaf" | "oob_leaf" => ("lightcyan", "cyan"),
+                    "router" => ("lightpink", "pink"),
+                    "pdu" => ("lightgreen", "green"),
+                    _ => ("lightgray", "gray"),
+                };
+                (yaml_field(item, "label").unwrap_or_else(|| id.clone()), box_style(fill, color, "1.5"))
+            };
+            b.node(&id, &label, Some(&equipment_type), Some(&group), style);
+            ordered.push(id);
+        }
+        b.chain(&ordered);
+    }
+
+    for connection in yaml_list(root, "connections") {
+        let (Some(from), Some(to)) = (yaml_get(connection, "from"), yaml_get(connection, "to")) else { continue };
+        let (from_device, to_device, ports) = match (from, to) {
+            (Yaml::String(from), Yaml::String(to)) => (from.clone(), to.clone(), None),
+            (Yaml::Hash(_), Yaml::Hash(_)) => {
+                let (Some(from_device), Some(to_device)) = (yaml_field(from, "device"), yaml_field(to, "device")) else { continue };
+                let ports = match (yaml_field(from, "port"), yaml_field(to, "port")) {
+                    (Some(from_port), Some(to_port)) => Some(format!("{} → {}", from_port, to_port)),
+                    _ => None,
+                };
+                (from_device, to_device, ports)
+            }
+            _ => continue,
+        };
+        let connection_type = yaml_field(connection, "type");
+        let label = label_lines([
+            ports,
+            connection_type.clone(),
+            yaml_field(connection, "bandwidth"),
+            yaml_field(connection, "network"),
+            yaml_field(connection, "description"),
+        ])
+        .filter(|_| show_labels);
+        let color = if connection_type.as_deref() == Some("fiber") { "orange" } else { "blue" };
+        b.edge(&from_device, &to_device, label, connection_type.as_deref(), attributes(&[("color", color)]));
+    }
+
+    let resolve = |device: &str| device_ids.get(device).cloned().unwrap_or_else(|| device.to_string());
+    for connection in yaml_list(root, "power_connections") {
+        let (Some(from), Some(to)) = (yaml_field(connection, "from"), yaml_field(connection, "to")) else { continue };
+        let cable = yaml_field(connection, "cable");
+        let spec = cable
+            .as_deref()
+            .and_then(|c| yaml_get(root, "cable_types").and_then(|types| yaml_get(types, c)))
+            .and_then(|t| yaml_field(t, "spec"))
+            .or(cable)
+            .unwrap_or_default();
+        let count = yaml_get(connection, "count").and_then(|c| c.as_i64()).unwrap_or(1);
+        let label = Some(format!("{} ({}本)", spec, count)).filter(|_| show_labels);
+        b.edge(&resolve(&from), &resolve(&to), label, Some("power"), attributes(&[("style", "dashed")]));
+    }
+    for connection in yaml_list(root, "data_connections") {
+        let (Some(from), Some(to)) = (yaml_field(connection, "from"), yaml_field(connection, "to")) else { continue };
+        let mut parts = cable_label_parts(root, connection);
+        parts.extend(yaml_field(connection, "purpose").map(|p| format!("[{}]", p)));
+        parts.extend(yaml_field(connection, "range").map(|r| format!("({})", r)));
+        let label = Some(parts.join(" ")).filter(|l| show_labels && !l.is_empty());
+        b.edge(&resolve(&from), &resolve(&to), label, Some("data"), DotAttributes::new());
+    }
+    if let Some(optional) = yaml_get(root, "optional_connections") {
+        let condition = yaml_field(optional, "condition").unwrap_or_else(|| "optional".to_string());
+        for connection in yaml_list(optional, "links") {
+            let (Some(from), Some(to)) = (yaml_field(connection, "from"), yaml_field(connection, "to")) else { continue };
+            let mut parts = cable_label_parts(root, connection);
+            parts.push(format!("[{}]", condition));
+            let label = Some(parts.join(" ")).filter(|_| show_labels);
+            b.edge(&resolve(&from), &resolve(&to), label, Some("optional"), attributes(&[("style", "dotted")]));
+        }
+    }
+}
+
+/// ポートのラベル（種別・速度・役割を2行目に付ける）
+fn port_label(port: &Yaml, fallback_id: &str) -> String {
+    let mut details = Vec::new();
+    details.extend(yaml_field(port, "type"));
+    details.extend(yaml_field(port, "speed"));
+    details.extend(yaml_field(port, "role").map(|r| format!("[{}]", r)));
+    label_lines([
+        Some(yaml_field(port, "label").unwrap_or_else(|| fallback_id.to_string())),
+        Some(details.join(" ")),
+    ])
+    .unwrap_or_else(|| fallback_id.to_string())
+}
+
+/// rack-servers: ラック内のサーバーとポート、サーバーのポートから接続先のポートへの接続
+fn build_rack_servers_view(b: &mut YamlGraphBuilder, root: &Yaml, show_labels: bool) {
+    let servers = yaml_list(root, "servers");
+    if servers.is_empty() {
+        return;
+    }
+    let rack_id = yaml_field(root, "rackId")
+        .unwrap_or_else(|| format!("rack_{}", yaml_field(root, "id").unwrap_or_else(|| "unknown".to_string())));
+    let rack_label = yaml_field(root, "label")
+        .or_else(|| yaml_field(root, "rackId"))
+        .unwrap_or_else(|| "ラック".to_string());
+    let group = b.group(&rack_id, &rack_label);
+    let mut rack_style = box_style("lightgray", "gray", "2");
+    rack_style.insert("fontcolor".to_string(), "white".to_string());
+    b.node(&rack_id, &rack_label, Some("rack"), Some(&group), rack_style);
+
+    let mut server_ids = Vec::new();
+    for server in servers {
+        let Some(server_id) = yaml_field(server, "id") else { continue };
+        let label = label_lines([yaml_field(server, "label").or(Some(server_id.clone())), yaml_field(server, "model")])
+            .unwrap_or_else(|| server_id.clone());
+        b.node(&server_id, &label, Some("server"), Some(&group), box_style("lightyellow", "orange", "2"));
+        for port in yaml_list(server, "ports") {
+            let Some(port_id) = yaml_field(port, "id") else { continue };
+            let node_id = format!("{}_{}", server_id, port_id);
+            b.node(&node_id, &port_label(port, &port_id), Some("port"), Some(&group), port_style("filled", "lightgray", "gray", "1"));
+            b.edge(&server_id, &node_id, None, None, port_link_style());
+        }
+        server_ids.push(server_id);
+    }
+    b.chain(&server_ids);
+
+    for server in servers {
+        let Some(server_id) = yaml_field(server, "id") else { continue };
+        for connection in yaml_list(server, "connections") {
+            let (Some(from), Some(to)) = (yaml_get(connection, "from"), yaml_get(connection, "to")) else { continue };
+            let (Some(from_port), Some(to_device), Some(to_port)) =
+                (yaml_field(from, "port"), yaml_field(to, "device"), yaml_field(to, "port"))
+            else {
+                continue;
+            };
+            let connection_type = yaml_field(connection, "type");
+            b.edge(
+                &format!("{}_{}", server_id, from_port),
+                &format!("{}_{}", to_device, to_port),
+                connection_type.clone().filter(|_| show_labels),
+                connection_type.as_deref(),
+                DotAttributes::new(),
+            );
+        }
+    }
+}
+
+/// server-details: 機器のドライブベイ・フロントパネル・ネットワークポートと、シーケンス（なければアプリケーション構成）
+fn build_server_details_view(b: &mut YamlGraphBuilder, root: &Yaml, show_labels: bool) {
+    let id = yaml_field(root, "id").unwrap_or_else(|| "details".to_string());
+    let server_id = format!("server_{}", id);
+    let hardware = yaml_get(root, "hardware");
+    let label = label_lines([
+        Some(yaml_field(root, "label").or_else(|| yaml_field(root, "id")).unwrap_or_else(|| "機器詳細".to_string())),
+        hardware.and_then(|h| yaml_field(h, "model")),
+        hardware.and_then(|h| yaml_field(h, "serialNumber")).map(|s| format!("S/N: {}", s)),
+    ])
+    .unwrap_or_default();
+    b.node(&server_id, &label, Some("server"), None, box_style("lightyellow", "orange", "2"));
+
+    let slots = yaml_list(root, "slots");
+    if !slots.is_empty() {
+        let group = b.group(&format!("slots_{}", id), "ドライブベイ");
+        let mut slot_ids = Vec::new();
+        for slot in slots {
+            let slot_label = yaml_field(slot, "label");
+            let Some(slot_id) = yaml_field(slot, "id").or_else(|| slot_label.as_ref().map(|l| format!("slot_{}", l))) else { continue };
+            let status = yaml_field(slot, "status");
+            let label = label_lines([
+                slot_label.or(Some(slot_id.clone())),
+                status.clone().filter(|s| s != "empty").map(|s| format!("[{}]", s)),
+                yaml_field(slot, "capacity"),
+            ])
+            .unwrap_or_else(|| slot_id.clone());
+            let (fill, color) = match status.as_deref() {
+                Some("failed") => ("lightcoral", "red"),
+                Some("installed") => ("lightgreen", "green"),
+                _ => ("lightgray", "gray"),
+            };
+            b.node(&slot_id, &label, Some("slot"), Some(&group), box_style(fill, color, "1.5"));
+            slot_ids.push(slot_id);
+        }
+        b.chain(&slot_ids);
+    }
+
+    let front_ports = yaml_list(root, "frontPanelPorts");
+    if !front_ports.is_empty() {
+        let group = b.group(&format!("front_ports_{}", id), "フロントパネル");
+        let mut port_ids = Vec::new();
+        for port in front_ports {
+            let port_label = yaml_field(port, "label");
+            let Some(port_id) = yaml_field(port, "id").or_else(|| port_label.as_ref().map(|l| format!("port_{}", l))) else { continue };
+            let (fill, color) = match yaml_field(port, "type").as_deref() {
+                Some("VGA") => ("lightblue", "blue"),
+                Some("USB") => ("lightcyan", "cyan"),
+                Some("button") => ("lightpink", "pink"),
+                _ => ("lightgray", "gray"),
+            };
+            let label = port_label.unwrap_or_else(|| port_id.clone());
+            b.node(&port_id, &label, Some("frontPanelPort"), Some(&group), port_style("rounded,filled", fill, color, "1.5"));
+            port_ids.push(port_id);
+        }
+        b.chain(&port_ids);
+    }
+
+    let ports = yaml_list(root, "ports");
+    if !ports.is_empty() {
+        let group = b.group(&format!("ports_{}", id), "ネットワークポート");
+        let mut port_ids = Vec::new();
+        for port in ports {
+            let Some(port_id) = yaml_field(port, "id").or_else(|| yaml_field(port, "label").map(|l| format!("port_{}", l))) else { continue };
+            let label = label_lines([Some(port_label(port, &port_id)), yaml_field(port, "ip")]).unwrap_or_else(|| port_id.clone());
+            let (fill, color) = match yaml_field(port, "role").as_deref() {
+                Some("management") => ("lightgreen", "green"),
+                Some("public") => ("lightyellow", "orange"),
+                Some("storage") => ("lavender", "purple"),
+                Some("backup") => ("lightcoral", "red"),
+                Some("unused") => ("lightgray", "gray"),
+                _ => ("lightcyan", "cyan"),
+            };
+            b.node(&port_id, &label, Some("port"), Some(&group), port_style("rounded,filled", fill, color, "1.5"));
+            port_ids.push(port_id);
+        }
+        b.chain(&port_ids);
+    }
+
+    let sequences = yaml_list(root, "sequences");
+    if !sequences.is_empty() {
+        for sequence in sequences {
+            let (Some(participants), Some(steps)) = (
+                yaml_get(sequence, "participants").and_then(|v| v.as_vec()),
+                yaml_get(sequence, "steps").and_then(|v| v.as_vec()),
+            ) else {
+                continue;
+            };
+            for participant in participants.iter().filter_map(yaml_scalar) {
+                b.node(&participant, &participant, Some("participant"), None, box_style("lightblue", "blue", "2"));
+            }
+            for step in steps {
+                let (Some(from), Some(to)) = (yaml_field(step, "from"), yaml_field(step, "to")) else { continue };
+                let message = yaml_field(step, "message");
+                let description = message.as_ref().and(yaml_field(step, "description"));
+                let label = label_lines([message, description]).filter(|_| show_labels);
+                b.edge(&from, &to, label, Some("step"), DotAttributes::new());
+            }
+        }
+    } else {
+        for application in yaml_list(root, "applications") {
+            let Some(name) = yaml_field(application, "name") else { continue };
+            let label = match yaml_field(application, "port") {
+                Some(port) => format!("{}:{}", name, port),
+                None => name.clone(),
+            };
+            b.node(&name, &label, Some("application"), None, box_style("lightgreen", "green", "2"));
+            b.edge(&server_id, &name, None, None, attributes(&[("style", "dashed"), ("color", "gray")]));
+        }
+    }
+}
+
+/// graph.nodes / graph.edges 形式（graphキーを省略したトップレベル形式も可）
+fn build_generic_view(b: &mut YamlGraphBuilder, graph: &Yaml, show_labels: bool) -> Result<(), String> {
+    for (i, value) in yaml_list(graph, "nodes").iter().enumerate() {
+        let id = yaml_field(value, "id").ok_or_else(|| format!("nodes[{}]にidがありません", i))?;
+        if !b.node_ids.insert(id.clone()) {
+            continue;
+        }
+        b.nodes.push(DotNode {
+            label: yaml_field(value, "label").unwrap_or_else(|| id.clone()),
+            node_type: yaml_field(value, "type"),
+            cluster: yaml_field(value, "organizationId"),
+            group: None,
+            base: DotAttributes::new(),
+            attributes: yaml_attributes(value),
+            id,
+        });
+    }
+
+    for (i, value) in yaml_list(graph, "edges").iter().enumerate() {
+        let source = yaml_field(value, "from").ok_or_else(|| format!("edges[{}]にfromがありません", i))?;
+        let target = yaml_field(value, "to").ok_or_else(|| format!("edges[{}]にtoがありません", i))?;
+        let edge_type = yaml_field(value, "type").or_else(|| yaml_field(value, "relationType"));
+        let label = yaml_field(value, "label").or_else(|| edge_type.clone().filter(|_| show_labels));
+        b.edge(&source, &target, label, edge_type.as_deref(), DotAttributes::new());
+        if let Some(edge) = b.edges.last_mut() {
+            edge.attributes = yaml_attributes(value);
+        }
+    }
+    Ok(())
+}
+
+/// graphvizYamlFilesのYAML定義を読み込む（種別ごとの表示はapp/graphviz/components/utils/yamlToDotAdvanced.tsに合わせる）
+fn build_yaml_graph(yaml_content: &str, options: &DotGenerationOptions) -> Result<DotGraph, String> {
+    let root = YamlLoader::load_from_str(yaml_content)
+        .map_err(|e| format!("YAMLの解析に失敗しました: {}", e))?
+        .into_iter()
+        .next()
+        .unwrap_or(Yaml::Null);
+    let document_type = detect_yaml_type(&root).ok_or_else(|| {
+        "YAMLの種別を判定できません（topology / device / links / intent / site-topology / site-equipment / rack-servers / server-details、またはgraph.nodes / graph.edges 形式に対応しています）".to_string()
+    })?;
+
+    let show_labels = options.show_relation_labels.unwrap_or(true);
+    let mut builder = YamlGraphBuilder::default();
+    let graph = yaml_get(&root, "graph").filter(|g| g.as_hash().is_some()).unwrap_or(&root);
+    match document_type {
+        YamlDocumentType::SiteTopology => build_site_topology_view(&mut builder, &root, show_labels),
+        YamlDocumentType::SiteEquipment => build_site_equipment_view(&mut builder, &root, show_labels),
+        YamlDocumentType::RackServers => build_rack_servers_view(&mut builder, &root, show_labels),
+        YamlDocumentType::ServerDetails => build_server_details_view(&mut builder, &root, show_labels),
+        YamlDocumentType::Topology => build_topology_view(&mut builder, &root),
+        YamlDocumentType::Device => build_device_view(&mut builder, &root)?,
+        YamlDocumentType::Links => build_links_view(&mut builder, &root, show_labels),
+        YamlDocumentType::Intent => build_intent_view(&mut builder, &root),
+        YamlDocumentType::Graph => build_generic_view(&mut builder, graph, show_labels)?,
+    }
+
+    let directed = options.directed.unwrap_or_else(|| {
+        document_type != YamlDocumentType::Graph || yaml_field(graph, "type").as_deref() != Some("graph")
+    });
+    let name = options
+        .graph_name
+        .clone()
+        .or_else(|| match document_type {
+            YamlDocumentType::Graph => yaml_field(graph, "name"),
+            _ => yaml_field(&root, "id"),
+        })
+        .unwrap_or_else(|| "knowledge_graph".to_string());
+
+    Ok(builder.finish(name, directed))
+}
+
+/// サブグラフまたはYAML定義からDOTを生成
+pub fn generate_graphviz_dot(options: &DotGenerationOptions) -> Result<DotGenerationResult, String> {
+    let yaml_file_id = options.yaml_file_id.as_ref().filter(|id| !id.is_empty());
+    let has_selection = options.entity_ids.as_ref().is_some_and(|ids| !ids.is_empty())
+        || options.relation_ids.as_ref().is_some_and(|ids| !ids.is_empty());
+    if yaml_file_id.is_some() && has_selection {
+        return Err("yamlFileIdとentityIds/relationIdsは同時に指定できません".to_string());
+    }
+
+    let graph = match yaml_file_id {
+        Some(id) => {
+            let yaml_file = get_graphviz_yaml_file_by_id(id)
+                .map_err(|e| format!("YAMLファイルの取得に失敗しました: {}", e))?;
+            build_yaml_graph(&yaml_file.yaml_content, options)?
+        }
+        None => {
+            let conn = connection()?;
+            build_subgraph(&conn, options)?
+        }
+    };
+
+    let cluster_labels = if options.cluster_by_organization {
+        let mut organization_ids: Vec<String> = graph.nodes.iter().filter_map(|n| n.cluster.clone()).collect();
+        organization_ids.sort();
+        organization_ids.dedup();
+        match connection() {
+            Ok(conn) => load_organization_names(&conn, &organization_ids)?,
+            Err(_) => HashMap::new(),
+        }
+    } else {
+        HashMap::new()
+    };
+
+    Ok(DotGenerationResult {
+        dot_content: render(&graph, options, &cluster_labels),
+        graph_type: if graph.directed { "digraph" } else { "graph" }.to_string(),
+        node_count: graph.nodes.len(),
+        edge_count: graph.edges.iter().filter(|e| is_visible_edge(e)).count(),
+        truncated: graph.truncated,
+    })
+}
+
+/// DOTを生成してgraphvizDotFilesに保存
+pub fn generate_and_save_graphviz_dot(options: &DotGenerationOptions, save: DotSaveOptions) -> Result<GraphvizDotFile, String> {
+    let yaml_file_id = save
+        .yaml_file_id
+        .clone()
+        .or_else(|| options.yaml_file_id.clone())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| "保存先のyamlFileIdを指定してください".to_string())?;
+    let result = generate_graphviz_dot(options)?;
+
+    let organization_id = save.organization_id.or_else(|| options.filter.organization_id.clone());
+    create_graphviz_dot_file(
+        yaml_file_id,
+        save.name,
+        save.description,
+        result.dot_content,
+        result.graph_type,
+        save.view_type,
+        Some(result.node_count as i32),
+        Some(result.edge_count as i32),
+        organization_id,
+        save.tags,
+    )
+    .map_err(|e| format!("DOTファイルの保存に失敗しました: {}", e))
+}
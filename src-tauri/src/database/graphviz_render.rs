@@ -0,0 +1,292 @@
+/**
+ * Graphviz DOTのレンダリングモジュール
+ * DOTをSVG/PNGに変換し、使用したエンジンとDOTの内容のハッシュをキーにキャッシュする
+ *
+ * - dotコマンドがある場合はdotでレンダリング（タイムアウト付き）
+ * - dotコマンドがない場合はlayout-rs（Rust実装のレイアウトエンジン）でSVGを生成
+ * - layout-rsのSVGからのPNG変換はresvgで行う
+ * - キャッシュはデータディレクトリ/graphviz_renders/{ハッシュ}.{dot|native}.{svg|png}
+ *   （dotとlayout-rsでは出力が異なるため、autoは実際に使うエンジンで解決してからキーにする）
+ */
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command as TokioCommand;
+
+/// dotコマンド・layout-rsのレンダリングのタイムアウト
+const RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// layout-rsのSVGをPNGに変換するときの拡大率
+const PNG_SCALE: f32 = 2.0;
+
+/// dotコマンドのパスを指定する環境変数
+const DOT_PATH_ENV: &str = "GRAPHVIZ_DOT";
+
+static DOT_COMMAND: OnceLock<Option<PathBuf>> = OnceLock::new();
+static FONT_DATABASE: OnceLock<Arc<resvg::usvg::fontdb::Database>> = OnceLock::new();
+
+/// 出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphvizRenderFormat {
+    Svg,
+    Png,
+}
+
+impl GraphvizRenderFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "svg" => Ok(Self::Svg),
+            "png" => Ok(Self::Png),
+            _ => Err(format!("無効なフォーマット: {}. 'svg' または 'png' を指定してください。", value)),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// レンダリングエンジン
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphvizRenderEngine {
+    /// dotコマンドがあればdot、なければnative
+    #[default]
+    Auto,
+    /// dotコマンド（Graphviz）
+    Dot,
+    /// layout-rs + resvg
+    Native,
+}
+
+impl GraphvizRenderEngine {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "dot" => Ok(Self::Dot),
+            "native" => Ok(Self::Native),
+            _ => Err(format!("無効なエンジン: {}. 'auto'、'dot'、'native' のいずれかを指定してください。", value)),
+        }
+    }
+}
+
+/// レンダリング結果
+#[derive(Debug, Clone)]
+pub struct RenderedGraphviz {
+    pub data: Vec<u8>,
+    /// 使用したエンジンとDOTの内容のSHA-256（キャッシュキー）
+    pub content_hash: String,
+    /// キャッシュから返したか
+    pub cached: bool,
+}
+
+/// キャッシュの保存先（データベースディレクトリ/graphviz_renders）
+fn render_cache_dir() -> Result<PathBuf, String> {
+    let db_dir_name = if cfg!(debug_assertions) {
+        "network-mock-local-dev"
+    } else {
+        "network-mock-local"
+    };
+    dirs::data_dir()
+        .map(|dir| dir.join(db_dir_name).join("graphviz_renders"))
+        .ok_or_else(|| "データディレクトリを取得できませんでした".to_string())
+}
+
+fn content_hash(engine: &str, dot_content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(engine.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(dot_content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// dotコマンドを検出する（GUIアプリから起動した場合にPATHにないHomebrew等のパスも確認）
+fn detect_dot_command() -> Option<PathBuf> {
+    DOT_COMMAND
+        .get_or_init(|| {
+            let mut candidates = Vec::new();
+            if let Ok(path) = std::env::var(DOT_PATH_ENV) {
+                candidates.push(PathBuf::from(path));
+            }
+            candidates.push(PathBuf::from(if cfg!(target_os = "windows") { "dot.exe" } else { "dot" }));
+            candidates.extend(
+                ["/opt/homebrew/bin/dot", "/usr/local/bin/dot", "/usr/bin/dot"]
+                    .iter()
+                    .map(PathBuf::from),
+            );
+            let found = candidates.into_iter().find(|path| {
+                std::process::Command::new(path)
+                    .arg("-V")
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .map(|status| status.success())
+                    .unwrap_or(false)
+            });
+            match &found {
+                Some(path) => eprintln!("✅ [Graphviz] dotコマンドを検出しました: {}", path.display()),
+                None => eprintln!("ℹ️ [Graphviz] dotコマンドが見つからないため、layout-rsでレンダリングします"),
+            }
+            found
+        })
+        .clone()
+}
+
+/// dotコマンドでレンダリング
+async fn render_with_dot(dot_path: &PathBuf, dot_content: &str, format: GraphvizRenderFormat) -> Result<Vec<u8>, String> {
+    let mut child = TokioCommand::new(dot_path)
+        .arg(format!("-T{}", format.extension()))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("dotプロセスの起動に失敗しました: {}", e))?;
+
+    let mut stdin = child.stdin.take().ok_or_else(|| "dotプロセスの標準入力を取得できませんでした".to_string())?;
+    let input = dot_content.as_bytes().to_vec();
+    let run = async move {
+        stdin
+            .write_all(&input)
+            .await
+            .map_err(|e| format!("DOTの書き込みに失敗しました: {}", e))?;
+        // 標準入力を閉じてdotに入力の終わりを伝える
+        drop(stdin);
+        child
+            .wait_with_output()
+            .await
+            .map_err(|e| format!("dotの実行に失敗しました: {}", e))
+    };
+    // タイムアウトした場合はkill_on_dropでプロセスを終了する
+    let output = tokio::time::timeout(RENDER_TIMEOUT, run)
+        .await
+        .map_err(|_| format!("dotのレンダリングがタイムアウトしました（{}秒）", RENDER_TIMEOUT.as_secs()))??;
+
+    if !output.status.success() {
+        return Err(format!("dotエラー: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    if output.stdout.is_empty() {
+        return Err("dotが空の出力を返しました".to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// layout-rsでSVGを生成
+fn layout_svg(dot_content: &str) -> Result<String, String> {
+    let mut parser = layout::gv::DotParser::new(dot_content);
+    let graph = parser.process().map_err(|e| format!("DOTの解析に失敗しました: {}", e))?;
+    let mut builder = layout::gv::GraphBuilder::new();
+    builder.visit_graph(&graph);
+    let mut visual_graph = builder.get();
+    let mut writer = layout::backends::svg::SVGWriter::new();
+    visual_graph.do_it(false, false, false, &mut writer);
+    Ok(writer.finalize())
+}
+
+fn font_database() -> Arc<resvg::usvg::fontdb::Database> {
+    FONT_DATABASE
+        .get_or_init(|| {
+            let mut database = resvg::usvg::fontdb::Database::new();
+            database.load_system_fonts();
+            Arc::new(database)
+        })
+        .clone()
+}
+
+/// SVGをPNGに変換
+fn svg_to_png(svg: &str) -> Result<Vec<u8>, String> {
+    let options = resvg::usvg::Options {
+        fontdb: font_database(),
+        ..Default::default()
+    };
+    let tree = resvg::usvg::Tree::from_str(svg, &options).map_err(|e| format!("SVGの解析に失敗しました: {}", e))?;
+    let size = tree.size().to_int_size().scale_by(PNG_SCALE).ok_or_else(|| "PNGのサイズが不正です".to_string())?;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| "PNGの描画領域を確保できませんでした".to_string())?;
+    pixmap.fill(resvg::tiny_skia::Color::WHITE);
+    resvg::render(&tree, resvg::tiny_skia::Transform::from_scale(PNG_SCALE, PNG_SCALE), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| format!("PNGのエンコードに失敗しました: {}", e))
+}
+
+/// layout-rs + resvgでレンダリング（別スレッドで実行し、タイムアウト・パニックをエラーにする）
+async fn render_native(dot_content: &str, format: GraphvizRenderFormat) -> Result<Vec<u8>, String> {
+    let dot_content = dot_content.to_string();
+    let task = tokio::task::spawn_blocking(move || {
+        std::panic::catch_unwind(|| -> Result<Vec<u8>, String> {
+            let svg = layout_svg(&dot_content)?;
+            match format {
+                GraphvizRenderFormat::Svg => Ok(svg.into_bytes()),
+                GraphvizRenderFormat::Png => svg_to_png(&svg),
+            }
+        })
+        .unwrap_or_else(|_| Err("layout-rsでのレンダリング中にエラーが発生しました（このDOTには未対応の記法が含まれている可能性があります）".to_string()))
+    });
+    tokio::time::timeout(RENDER_TIMEOUT, task)
+        .await
+        .map_err(|_| format!("レンダリングがタイムアウトしました（{}秒）", RENDER_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("レンダリングタスクの実行に失敗しました: {}", e))?
+}
+
+/// DOTをSVG/PNGにレンダリング（同じエンジン・同じ内容のDOTはキャッシュから返す）
+pub async fn render_graphviz_dot(
+    dot_content: &str,
+    format: GraphvizRenderFormat,
+    engine: GraphvizRenderEngine,
+) -> Result<RenderedGraphviz, String> {
+    if dot_content.trim().is_empty() {
+        return Err("DOTが空です".to_string());
+    }
+    let dot_path = match engine {
+        GraphvizRenderEngine::Native => None,
+        GraphvizRenderEngine::Dot | GraphvizRenderEngine::Auto => detect_dot_command(),
+    };
+    if engine == GraphvizRenderEngine::Dot && dot_path.is_none() {
+        return Err("dotコマンドが見つかりません。Graphvizをインストールするか、GRAPHVIZ_DOT環境変数でパスを指定してください。".to_string());
+    }
+    let engine_name = if dot_path.is_some() { "dot" } else { "native" };
+
+    let hash = content_hash(engine_name, dot_content);
+    let cache_path = render_cache_dir()
+        .ok()
+        .map(|dir| dir.join(format!("{}.{}.{}", hash, engine_name, format.extension())));
+
+    if let Some(path) = cache_path.as_ref() {
+        if let Ok(data) = tokio::fs::read(path).await {
+            return Ok(RenderedGraphviz { data, content_hash: hash, cached: true });
+        }
+    }
+
+    let data = match dot_path {
+        Some(dot_path) => render_with_dot(&dot_path, dot_content, format).await?,
+        None => render_native(dot_content, format).await?,
+    };
+
+    // キャッシュの書き込みに失敗してもレンダリング結果は返す
+    // 同時に読み込む要求が書きかけのファイルを返さないよう、同じディレクトリの一時ファイルに書き出してからリネームする
+    if let Some(path) = cache_path.as_ref() {
+        let tmp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        let written = async {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::write(&tmp_path, &data).await?;
+            tokio::fs::rename(&tmp_path, path).await
+        }
+        .await;
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            eprintln!("⚠️ [Graphviz] レンダリング結果のキャッシュに失敗しました: {}", e);
+        }
+    }
+
+    Ok(RenderedGraphviz { data, content_hash: hash, cached: false })
+}
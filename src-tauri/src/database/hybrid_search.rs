@@ -0,0 +1,630 @@
+/**
+ * ハイブリッド検索モジュール（キーワード検索 + ベクトル検索）
+ * FTS5の全文検索結果とベクトル類似度検索の結果をReciprocal Rank Fusion（RRF）で1つのランキングに統合する
+ *
+ * - 対象はエンティティ・リレーション・トピック（種別が混在した1つのリストを返す）
+ * - 製品コードやメンバー名のような完全一致が必要な語はキーワード検索で、言い換えはベクトル検索で拾う
+ * - 片方の検索が失敗しても（ベクトルDB停止中など）、もう片方の結果だけで検索を続ける
+ * - トピックのベクトル検索はtopicIdで返るため、topics.id（キーワード検索と同じID）に変換してから統合する
+ * - メタデータフィルタ（semanticCategory・日付範囲・組織/事業会社）はSQLiteの元テーブルを参照して両方の候補に同じ条件で適用する
+ *   （ベクトル検索でも、ベクトルストアのメタデータで絞り込める条件は検索時に渡し、フィルタで候補が減らないようにする）
+ */
+
+use rusqlite::{params_from_iter, Connection, Result as SqlResult};
+use rusqlite::types::Value as SqlValue;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use crate::database::get_db;
+use super::chromadb;
+use super::collection_query::{date_column_sql, parse_date_value};
+use super::fulltext_search::{self, FullTextSearchHit, FullTextSearchOptions};
+use super::vector_search::TopicSearchFilter;
+
+/// ハイブリッド検索の対象種別
+const HYBRID_SOURCE_TYPES: &[&str] = &["entity", "relation", "topic"];
+
+/// RRFの定数k（大きいほど上位と下位の差が小さくなる）
+const DEFAULT_RRF_K: f64 = 60.0;
+
+/// 検索結果のデフォルト件数・最大件数
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+/// 各検索から取得する候補数（フィルタで除外される分を見込んでlimitより多めに取得する）
+const MIN_CANDIDATES: usize = 50;
+const CANDIDATE_MULTIPLIER: usize = 3;
+
+/// ベクトル検索のみでヒットした結果のスニペット文字数
+const SNIPPET_CHARS: usize = 120;
+
+/// ハイブリッド検索のメタデータフィルタ
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HybridSearchFilters {
+    /// トピックのsemanticCategory（指定した場合、このカラムを持たないエンティティ・リレーションは除外される）
+    #[serde(rename = "semanticCategory")]
+    pub semantic_category: Option<String>,
+    /// 日付範囲の開始（トピックはtopicDate、未設定の場合とエンティティ・リレーションはcreatedAt）
+    #[serde(rename = "dateFrom")]
+    pub date_from: Option<Value>,
+    /// 日付範囲の終了（この日時を含む）
+    #[serde(rename = "dateTo")]
+    pub date_to: Option<Value>,
+}
+
+/// ハイブリッド検索の条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct HybridSearchOptions {
+    /// キーワード検索に使う検索文字列
+    pub query: String,
+    /// ベクトル検索に使うクエリの埋め込み（未指定の場合はキーワード検索のみ）
+    #[serde(rename = "queryEmbedding")]
+    pub query_embedding: Option<Vec<f32>>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+    /// 検索対象の種別（entity, relation, topic。未指定の場合は全種別）
+    #[serde(rename = "sourceTypes")]
+    pub source_types: Option<Vec<String>>,
+    pub filters: Option<HybridSearchFilters>,
+    pub limit: Option<usize>,
+    /// RRFの定数k（デフォルト60）
+    #[serde(rename = "rrfK")]
+    pub rrf_k: Option<f64>,
+}
+
+/// ハイブリッド検索の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct HybridSearchResult {
+    #[serde(rename = "sourceType")]
+    pub source_type: String,
+    pub id: String,
+    pub title: String,
+    pub snippet: String,
+    /// RRFで統合したスコア
+    pub score: f64,
+    /// キーワード検索での順位（1始まり、ヒットしなかった場合はNone）
+    #[serde(rename = "keywordRank")]
+    pub keyword_rank: Option<usize>,
+    /// キーワード検索のスコア（bm25の符号を反転した値）
+    #[serde(rename = "keywordScore")]
+    pub keyword_score: Option<f64>,
+    /// ベクトル検索での順位（1始まり、ヒットしなかった場合はNone）
+    #[serde(rename = "vectorRank")]
+    pub vector_rank: Option<usize>,
+    /// ベクトル検索のコサイン類似度
+    #[serde(rename = "vectorSimilarity")]
+    pub vector_similarity: Option<f32>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+}
+
+/// SQLiteの元テーブルから読み込んだ候補の情報
+struct CandidateRow {
+    title: String,
+    text: String,
+    semantic_category: Option<String>,
+    date: Option<i64>,
+    organization_id: Option<String>,
+    company_id: Option<String>,
+}
+
+/// 種別ごとの元テーブルと、候補の情報を読み込むための式
+fn candidate_columns(source_type: &str) -> Option<(&'static str, &'static str, &'static str, &'static str)> {
+    // (テーブル, タイトル, semanticCategory, 日付)
+    match source_type {
+        "topic" => Some(("topics", "title", "semanticCategory", "COALESCE(topicDate, createdAt)")),
+        "entity" => Some(("entities", "COALESCE(displayName, name)", "NULL", "createdAt")),
+        "relation" => Some(("relations", "relationType", "NULL", "createdAt")),
+        _ => None,
+    }
+}
+
+/// 候補のIDから元テーブルの行を読み込む
+fn load_candidates(conn: &Connection, source_type: &str, ids: &[String]) -> SqlResult<HashMap<String, CandidateRow>> {
+    let mut rows = HashMap::new();
+    let Some((table, title, semantic_category, date)) = candidate_columns(source_type) else {
+        return Ok(rows);
+    };
+    if ids.is_empty() {
+        return Ok(rows);
+    }
+
+    let sql = format!(
+        "SELECT id, {title}, COALESCE(searchableText, ''), {semantic_category}, {date}, organizationId, companyId
+         FROM {table} WHERE id IN ({placeholders})",
+        title = title,
+        semantic_category = semantic_category,
+        date = date_column_sql(date),
+        table = table,
+        placeholders = ids.iter().map(|_| "?").collect::<Vec<_>>().join(", "),
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mapped = stmt.query_map(params_from_iter(ids.iter().map(|id| SqlValue::Text(id.clone()))), |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            CandidateRow {
+                title: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                text: row.get(2)?,
+                semantic_category: row.get(3)?,
+                date: row.get(4)?,
+                organization_id: row.get(5)?,
+                company_id: row.get(6)?,
+            },
+        ))
+    })?;
+    for row in mapped {
+        let (id, candidate) = row?;
+        rows.insert(id, candidate);
+    }
+    Ok(rows)
+}
+
+/// フィルタ条件（日付は秒に変換済み）
+struct ResolvedFilters {
+    organization_id: Option<String>,
+    company_id: Option<String>,
+    semantic_category: Option<String>,
+    date_from: Option<i64>,
+    date_to: Option<i64>,
+}
+
+impl ResolvedFilters {
+    fn resolve(options: &HybridSearchOptions) -> Result<Self, String> {
+        let filters = options.filters.clone().unwrap_or_default();
+        let parse = |value: &Option<Value>, key: &str| -> Result<Option<i64>, String> {
+            match value {
+                None | Some(Value::Null) => Ok(None),
+                Some(v) => parse_date_value(v)
+                    .map(Some)
+                    .ok_or_else(|| format!("{}の日付を解釈できません: {}", key, v)),
+            }
+        };
+        Ok(ResolvedFilters {
+            organization_id: options.organization_id.clone().filter(|s| !s.is_empty()),
+            company_id: options.company_id.clone().filter(|s| !s.is_empty()),
+            semantic_category: filters.semantic_category.filter(|s| !s.is_empty()),
+            date_from: parse(&filters.date_from, "dateFrom")?,
+            date_to: parse(&filters.date_to, "dateTo")?,
+        })
+    }
+
+    /// トピックのベクトル検索に渡すフィルタ
+    /// 日付範囲はtopicDateTimestampで絞り込むため、topicDateのないトピックはベクトル検索の候補にならない（キーワード検索では対象）
+    fn topic_vector_filter(&self) -> Option<TopicSearchFilter> {
+        if self.semantic_category.is_none() && self.date_from.is_none() && self.date_to.is_none() {
+            return None;
+        }
+        Some(TopicSearchFilter {
+            semantic_category: self.semantic_category.clone(),
+            meeting_note_id: None,
+            topic_date_from: self.date_from.map(Value::from),
+            topic_date_to: self.date_to.map(Value::from),
+        })
+    }
+
+    /// 元テーブルの値が必要な条件があるか（組織IDはベクトル検索のコレクションで絞り込まれる）
+    fn needs_row(&self) -> bool {
+        self.company_id.is_some()
+            || self.semantic_category.is_some()
+            || self.date_from.is_some()
+            || self.date_to.is_some()
+    }
+
+    fn matches(&self, row: Option<&CandidateRow>) -> bool {
+        let Some(row) = row else {
+            // SQLiteに行が無い候補（Supabase利用時など）は、条件を確認できないため条件がある場合のみ除外する
+            return !self.needs_row();
+        };
+        if let Some(org_id) = &self.organization_id {
+            if row.organization_id.as_ref() != Some(org_id) {
+                return false;
+            }
+        }
+        if let Some(company_id) = &self.company_id {
+            if row.company_id.as_ref() != Some(company_id) {
+                return false;
+            }
+        }
+        if let Some(category) = &self.semantic_category {
+            if row.semantic_category.as_ref() != Some(category) {
+                return false;
+            }
+        }
+        if self.date_from.is_some() || self.date_to.is_some() {
+            let Some(date) = row.date else {
+                return false;
+            };
+            if self.date_from.map(|from| date < from).unwrap_or(false) {
+                return false;
+            }
+            if self.date_to.map(|to| date > to).unwrap_or(false) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 統合中の候補
+struct FusedCandidate {
+    source_type: String,
+    id: String,
+    keyword: Option<(usize, f64, String, String)>,
+    vector: Option<(usize, f32)>,
+    organization_id: Option<String>,
+}
+
+/// ベクトル検索の候補
+struct VectorHit {
+    source_type: String,
+    id: String,
+    similarity: f32,
+    organization_id: Option<String>,
+    /// トピックの議事録ID（topicIdからtopics.idへの変換に使う）
+    meeting_note_id: Option<String>,
+}
+
+/// ベクトル検索で候補を取得（種別ごとの結果を類似度順に1つのリストへまとめる）
+async fn vector_candidates(
+    embedding: Vec<f32>,
+    source_types: &[&str],
+    candidate_limit: usize,
+    filters: &ResolvedFilters,
+) -> Result<Vec<VectorHit>, String> {
+    let mut results: Vec<VectorHit> = Vec::new();
+    let mut errors = Vec::new();
+    let organization_id = filters.organization_id.clone();
+    // semanticCategoryはトピックのみが持つため、指定された場合はエンティティ・リレーションを検索しない
+    let source_types: Vec<&str> = source_types
+        .iter()
+        .copied()
+        .filter(|source_type| filters.semantic_category.is_none() || *source_type == "topic")
+        .collect();
+
+    for source_type in &source_types {
+        let found = match *source_type {
+            "entity" => chromadb::find_similar_entities(embedding.clone(), candidate_limit, organization_id.clone(), None).await
+                .map(|r| r.into_iter().map(|(id, sim)| (id, sim, None, None)).collect::<Vec<_>>()),
+            "relation" => chromadb::find_similar_relations(embedding.clone(), candidate_limit, organization_id.clone(), None).await
+                .map(|r| r.into_iter().map(|(id, sim)| (id, sim, None, None)).collect::<Vec<_>>()),
+            "topic" => chromadb::find_similar_topics(embedding.clone(), candidate_limit, organization_id.clone(), filters.topic_vector_filter()).await
+                .map(|r| r.into_iter().map(|t| (t.topic_id, t.similarity, t.organization_id, t.meeting_note_id)).collect::<Vec<_>>()),
+            _ => continue,
+        };
+        match found {
+            Ok(found) => results.extend(found.into_iter().map(|(id, similarity, organization_id, meeting_note_id)| VectorHit {
+                source_type: source_type.to_string(),
+                id,
+                similarity,
+                organization_id,
+                meeting_note_id,
+            })),
+            Err(e) => {
+                eprintln!("⚠️ [hybrid_search] {}のベクトル検索に失敗しました: {}", source_type, e);
+                errors.push(e);
+            }
+        }
+    }
+
+    if results.is_empty() && errors.len() == source_types.len() && !errors.is_empty() {
+        return Err(errors.join("; "));
+    }
+    results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(results)
+}
+
+/// トピックのベクトル検索の結果（topicId）を、topics.id（`{meetingNoteId}-topic-{topicId}`）に置き換える
+/// 議事録IDが分かる場合は同じ議事録の行を選ぶ。SQLiteに行が無いトピックはtopicIdのまま残す
+fn resolve_topic_row_ids(conn: &Connection, hits: &mut [VectorHit]) -> SqlResult<()> {
+    let topic_ids: Vec<String> = hits
+        .iter()
+        .filter(|hit| hit.source_type == "topic")
+        .map(|hit| hit.id.clone())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    if topic_ids.is_empty() {
+        return Ok(());
+    }
+
+    let sql = format!(
+        "SELECT id, topicId, meetingNoteId FROM topics WHERE topicId IN ({})",
+        topic_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ")
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let mapped = stmt.query_map(params_from_iter(topic_ids.iter().map(|id| SqlValue::Text(id.clone()))), |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?))
+    })?;
+    let mut row_ids: HashMap<String, Vec<(String, Option<String>)>> = HashMap::new();
+    for row in mapped {
+        let (id, topic_id, meeting_note_id) = row?;
+        row_ids.entry(topic_id).or_default().push((id, meeting_note_id));
+    }
+
+    for hit in hits.iter_mut().filter(|hit| hit.source_type == "topic") {
+        let Some(rows) = row_ids.get(&hit.id) else {
+            continue;
+        };
+        let row = rows
+            .iter()
+            .find(|(_, meeting_note_id)| hit.meeting_note_id.is_some() && *meeting_note_id == hit.meeting_note_id)
+            .or_else(|| rows.first());
+        if let Some((id, _)) = row {
+            hit.id = id.clone();
+        }
+    }
+    Ok(())
+}
+
+/// キーワード検索とベクトル検索の候補を (種別, ID) ごとにまとめる（順位はそれぞれのリストの並び順）
+fn fuse_candidates(keyword_hits: Vec<FullTextSearchHit>, vector_hits: Vec<VectorHit>) -> Vec<FusedCandidate> {
+    let mut fused: Vec<FusedCandidate> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+
+    for (rank, hit) in keyword_hits.into_iter().enumerate() {
+        index.insert((hit.source_type.clone(), hit.id.clone()), fused.len());
+        fused.push(FusedCandidate {
+            source_type: hit.source_type,
+            id: hit.id,
+            keyword: Some((rank + 1, hit.score, hit.title, hit.snippet)),
+            vector: None,
+            organization_id: hit.organization_id,
+        });
+    }
+
+    for (rank, hit) in vector_hits.into_iter().enumerate() {
+        let key = (hit.source_type.clone(), hit.id.clone());
+        match index.get(&key) {
+            // 同じ行が複数ヒットした場合（同じトピックが複数の組織のコレクションにある場合など）は上位の順位を使う
+            Some(&i) => {
+                if fused[i].vector.is_none() {
+                    fused[i].vector = Some((rank + 1, hit.similarity));
+                }
+            }
+            None => {
+                index.insert(key, fused.len());
+                fused.push(FusedCandidate {
+                    source_type: hit.source_type,
+                    id: hit.id,
+                    keyword: None,
+                    vector: Some((rank + 1, hit.similarity)),
+                    organization_id: hit.organization_id,
+                });
+            }
+        }
+    }
+    fused
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    let trimmed = text.trim();
+    if trimmed.chars().count() > max_chars {
+        format!("{}…", trimmed.chars().take(max_chars).collect::<String>())
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// キーワード検索とベクトル検索をRRFで統合したハイブリッド検索を実行
+pub async fn hybrid_search(options: HybridSearchOptions) -> Result<Vec<HybridSearchResult>, String> {
+    let filters = ResolvedFilters::resolve(&options)?;
+
+    let source_types: Vec<&str> = match &options.source_types {
+        Some(types) if !types.is_empty() => {
+            let mut selected = Vec::new();
+            for t in types {
+                let source_type = HYBRID_SOURCE_TYPES
+                    .iter()
+                    .find(|s| **s == t.as_str())
+                    .ok_or_else(|| format!("ハイブリッド検索に対応していない種別です: {}", t))?;
+                selected.push(*source_type);
+            }
+            selected
+        }
+        _ => HYBRID_SOURCE_TYPES.to_vec(),
+    };
+
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let candidate_limit = (limit * CANDIDATE_MULTIPLIER).max(MIN_CANDIDATES);
+    let rrf_k = options.rrf_k.filter(|k| *k > 0.0).unwrap_or(DEFAULT_RRF_K);
+
+    // ベクトル検索（コサイン類似度）
+    // 注意: DB接続を保持したまま.awaitしないよう、ベクトル検索を先に実行する
+    let mut vector_matches = None;
+    if let Some(embedding) = options.query_embedding.clone().filter(|e| !e.is_empty()) {
+        match vector_candidates(embedding, &source_types, candidate_limit, &filters).await {
+            Ok(matches) => vector_matches = Some(matches),
+            Err(e) => eprintln!("⚠️ [hybrid_search] ベクトル検索に失敗しました: {}", e),
+        }
+    }
+    let vector_available = vector_matches.is_some();
+
+    let conn = match get_db() {
+        Some(db) => Some(db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))?),
+        None => None,
+    };
+
+    // キーワード検索（FTS5 / bm25）
+    let mut keyword_hits = Vec::new();
+    let mut keyword_available = false;
+    if let Some(conn) = &conn {
+        let keyword_options = FullTextSearchOptions {
+            query: options.query.clone(),
+            organization_id: filters.organization_id.clone(),
+            company_id: filters.company_id.clone(),
+            source_types: Some(source_types.iter().map(|s| s.to_string()).collect()),
+            limit: Some(candidate_limit),
+            offset: None,
+        };
+        match fulltext_search::query_full_text(conn, &keyword_options) {
+            Ok(hits) => {
+                keyword_available = true;
+                keyword_hits = hits;
+            }
+            Err(e) => eprintln!("⚠️ [hybrid_search] キーワード検索に失敗しました: {}", e),
+        }
+    } else {
+        eprintln!("⚠️ [hybrid_search] データベースが初期化されていないため、キーワード検索をスキップします");
+    }
+
+    let mut vector_hits: Vec<VectorHit> = vector_matches.unwrap_or_default().into_iter().take(candidate_limit).collect();
+    if let Some(conn) = &conn {
+        resolve_topic_row_ids(conn, &mut vector_hits)
+            .map_err(|e| format!("トピックIDの解決に失敗しました: {}", e))?;
+    }
+
+    // 候補を (種別, ID) ごとにまとめる
+    let fused = fuse_candidates(keyword_hits, vector_hits);
+
+    if !keyword_available && !vector_available {
+        return Err("キーワード検索・ベクトル検索のどちらも実行できませんでした".to_string());
+    }
+
+    // 元テーブルの行を読み込み、フィルタとタイトル・スニペットの補完に使う
+    let mut rows: HashMap<(String, String), CandidateRow> = HashMap::new();
+    if let Some(conn) = &conn {
+        for source_type in &source_types {
+            let ids: Vec<String> = fused
+                .iter()
+                .filter(|c| c.source_type == *source_type)
+                .map(|c| c.id.clone())
+                .collect();
+            let loaded = load_candidates(conn, source_type, &ids)
+                .map_err(|e| format!("検索候補の読み込みに失敗しました: {}", e))?;
+            rows.extend(loaded.into_iter().map(|(id, row)| ((source_type.to_string(), id), row)));
+        }
+    }
+
+    let mut results: Vec<HybridSearchResult> = fused
+        .into_iter()
+        .filter_map(|candidate| {
+            let row = rows.get(&(candidate.source_type.clone(), candidate.id.clone()));
+            if !filters.matches(row) {
+                return None;
+            }
+
+            let score = candidate.keyword.as_ref().map(|k| 1.0 / (rrf_k + k.0 as f64)).unwrap_or(0.0)
+                + candidate.vector.map(|v| 1.0 / (rrf_k + v.0 as f64)).unwrap_or(0.0);
+            let (title, snippet) = match &candidate.keyword {
+                Some((_, _, title, snippet)) => (title.clone(), snippet.clone()),
+                None => row
+                    .map(|r| (r.title.clone(), truncate_chars(&r.text, SNIPPET_CHARS)))
+                    .unwrap_or_default(),
+            };
+
+            Some(HybridSearchResult {
+                keyword_rank: candidate.keyword.as_ref().map(|k| k.0),
+                keyword_score: candidate.keyword.as_ref().map(|k| k.1),
+                vector_rank: candidate.vector.map(|v| v.0),
+                vector_similarity: candidate.vector.map(|v| v.1),
+                organization_id: row.and_then(|r| r.organization_id.clone()).or(candidate.organization_id),
+                company_id: row.and_then(|r| r.company_id.clone()),
+                source_type: candidate.source_type,
+                id: candidate.id,
+                title,
+                snippet,
+                score,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(limit);
+
+    if let Some(conn) = &conn {
+        if let Err(e) = fulltext_search::record_search_hits(conn, results.iter().map(|r| (r.source_type.as_str(), r.id.as_str()))) {
+            eprintln!("⚠️ [hybrid_search] 検索回数の更新に失敗しました: {}", e);
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topics_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE topics (
+                id TEXT PRIMARY KEY,
+                topicId TEXT NOT NULL,
+                meetingNoteId TEXT,
+                title TEXT,
+                searchableText TEXT,
+                semanticCategory TEXT,
+                topicDate TEXT,
+                createdAt TEXT,
+                organizationId TEXT,
+                companyId TEXT
+            );
+            INSERT INTO topics VALUES
+                ('mn-1-topic-t-1', 't-1', 'mn-1', '予算の見直し', '来期の予算を見直す', 'decision', NULL, '2026-01-01', 'org-1', NULL),
+                ('mn-2-topic-t-1', 't-1', 'mn-2', '別の議事録の同名トピック', '', 'discussion', NULL, '2026-01-02', 'org-1', NULL);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn keyword_hit(id: &str) -> FullTextSearchHit {
+        FullTextSearchHit {
+            source_type: "topic".to_string(),
+            id: id.to_string(),
+            title: "<mark>予算</mark>の見直し".to_string(),
+            snippet: "来期の<mark>予算</mark>を見直す".to_string(),
+            score: 1.5,
+            organization_id: Some("org-1".to_string()),
+            company_id: None,
+        }
+    }
+
+    fn topic_vector_hit(topic_id: &str, meeting_note_id: Option<&str>, similarity: f32) -> VectorHit {
+        VectorHit {
+            source_type: "topic".to_string(),
+            id: topic_id.to_string(),
+            similarity,
+            organization_id: Some("org-1".to_string()),
+            meeting_note_id: meeting_note_id.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn topic_found_by_keyword_and_vector_is_fused_into_one_result() {
+        let conn = topics_connection();
+        let mut vector_hits = vec![topic_vector_hit("t-1", Some("mn-1"), 0.9)];
+        resolve_topic_row_ids(&conn, &mut vector_hits).unwrap();
+
+        let fused = fuse_candidates(vec![keyword_hit("mn-1-topic-t-1")], vector_hits);
+
+        assert_eq!(fused.len(), 1);
+        assert_eq!(fused[0].id, "mn-1-topic-t-1");
+        assert_eq!(fused[0].keyword.as_ref().map(|k| k.0), Some(1));
+        assert_eq!(fused[0].vector, Some((1, 0.9)));
+    }
+
+    #[test]
+    fn vector_only_topic_is_loaded_from_its_row() {
+        let conn = topics_connection();
+        let mut vector_hits = vec![topic_vector_hit("t-1", Some("mn-2"), 0.8)];
+        resolve_topic_row_ids(&conn, &mut vector_hits).unwrap();
+        assert_eq!(vector_hits[0].id, "mn-2-topic-t-1");
+
+        let rows = load_candidates(&conn, "topic", &[vector_hits[0].id.clone()]).unwrap();
+        let row = rows.get("mn-2-topic-t-1").unwrap();
+        assert_eq!(row.title, "別の議事録の同名トピック");
+        assert_eq!(row.semantic_category.as_deref(), Some("discussion"));
+    }
+
+    #[test]
+    fn unknown_topic_ids_are_kept() {
+        let conn = topics_connection();
+        let mut vector_hits = vec![topic_vector_hit("t-missing", None, 0.7)];
+        resolve_topic_row_ids(&conn, &mut vector_hits).unwrap();
+        assert_eq!(vector_hits[0].id, "t-missing");
+    }
+}
@@ -0,0 +1,649 @@
+/**
+ * ナレッジグラフの探索モジュール
+ * entities（ノード）とrelations（sourceEntityId → targetEntityId のエッジ）をグラフとして探索する
+ *
+ * - k-hop近傍: 再帰CTEでエンティティからkホップ以内のエンティティを取得
+ * - 最短経路・連結成分・中心性（次数 / PageRank）: 条件に合うエッジをメモリに読み込んで計算
+ * - すべての探索でrelationType・confidence・組織/事業会社の条件を指定できる
+ * - 結果はノードとエッジのリスト（可視化ライブラリにそのまま渡せる形）で返す
+ */
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::database::get_db;
+
+/// k-hop近傍のデフォルト・最大ホップ数
+const DEFAULT_HOPS: usize = 2;
+const MAX_HOPS: usize = 6;
+
+/// 結果に含めるノード数のデフォルト・上限
+const DEFAULT_MAX_NODES: usize = 200;
+const MAX_NODES_LIMIT: usize = 5000;
+
+/// 最短経路の探索を打ち切る深さ
+const DEFAULT_MAX_PATH_DEPTH: usize = 10;
+
+/// PageRankのデフォルト値
+const DEFAULT_DAMPING: f64 = 0.85;
+const DEFAULT_PAGERANK_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-8;
+
+/// ノードIDをまとめて読み込む件数（SQLiteのパラメータ数上限対策）
+const ID_CHUNK_SIZE: usize = 500;
+
+/// エッジの向き
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphDirection {
+    /// sourceEntityId → targetEntityId の向きのみ
+    Out,
+    /// targetEntityId → sourceEntityId の向きのみ
+    In,
+    /// 向きを区別しない
+    #[default]
+    Both,
+}
+
+/// 探索対象のエッジの条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GraphFilter {
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+    /// 対象のrelationType（未指定の場合は全種別）
+    #[serde(rename = "relationTypes")]
+    pub relation_types: Option<Vec<String>>,
+    /// confidenceの下限（指定した場合、confidenceが未設定のリレーションは除外）
+    #[serde(rename = "minConfidence")]
+    pub min_confidence: Option<f64>,
+    #[serde(default)]
+    pub direction: GraphDirection,
+}
+
+/// グラフのノード（エンティティ）
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    /// 表示名（displayNameが未設定の場合はname）
+    pub label: String,
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "companyId")]
+    pub company_id: Option<String>,
+    /// 起点からのホップ数（k-hop近傍・最短経路）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depth: Option<usize>,
+    /// 連結成分の番号（連結成分）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component: Option<usize>,
+    /// 中心性のスコア（中心性）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+/// グラフのエッジ（リレーション）
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    #[serde(rename = "relationType")]
+    pub relation_type: String,
+    pub confidence: Option<f64>,
+    pub description: Option<String>,
+}
+
+/// 連結成分の概要
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphComponent {
+    pub index: usize,
+    pub size: usize,
+    #[serde(rename = "entityIds")]
+    pub entity_ids: Vec<String>,
+}
+
+/// 探索結果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GraphResult {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// 連結成分の一覧（連結成分の探索のみ）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<GraphComponent>>,
+    /// ノード数の上限で結果を打ち切ったか
+    pub truncated: bool,
+}
+
+/// k-hop近傍の条件
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphNeighborhoodOptions {
+    #[serde(rename = "entityId")]
+    pub entity_id: String,
+    pub hops: Option<usize>,
+    #[serde(rename = "maxNodes")]
+    pub max_nodes: Option<usize>,
+    #[serde(default, flatten)]
+    pub filter: GraphFilter,
+}
+
+/// 最短経路の条件
+#[derive(Debug, Clone, Deserialize)]
+pub struct GraphShortestPathOptions {
+    #[serde(rename = "sourceEntityId")]
+    pub source_entity_id: String,
+    #[serde(rename = "targetEntityId")]
+    pub target_entity_id: String,
+    /// 探索する最大ホップ数（デフォルト10）
+    #[serde(rename = "maxDepth")]
+    pub max_depth: Option<usize>,
+    #[serde(default, flatten)]
+    pub filter: GraphFilter,
+}
+
+/// 連結成分の条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GraphComponentsOptions {
+    /// この件数未満の連結成分は除外（デフォルト2。1を指定するとリレーションの無いエンティティも含む）
+    #[serde(rename = "minSize")]
+    pub min_size: Option<usize>,
+    #[serde(rename = "maxNodes")]
+    pub max_nodes: Option<usize>,
+    #[serde(default, flatten)]
+    pub filter: GraphFilter,
+}
+
+/// 中心性の計算方法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CentralityAlgorithm {
+    /// 次数（directionに応じて入次数・出次数・合計）
+    #[default]
+    Degree,
+    PageRank,
+}
+
+/// 中心性の条件
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GraphCentralityOptions {
+    #[serde(default)]
+    pub algorithm: CentralityAlgorithm,
+    /// 上位何件のノードを返すか
+    pub limit: Option<usize>,
+    /// PageRankの減衰係数（デフォルト0.85）
+    pub damping: Option<f64>,
+    /// PageRankの最大反復回数（デフォルト100）
+    pub iterations: Option<usize>,
+    #[serde(default, flatten)]
+    pub filter: GraphFilter,
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// エッジの条件をWHERE句に変換（relationsの別名はr）
+fn edge_conditions(filter: &GraphFilter) -> (String, Vec<SqlValue>) {
+    let mut conditions = vec![
+        "r.sourceEntityId IS NOT NULL".to_string(),
+        "r.targetEntityId IS NOT NULL".to_string(),
+        // 削除済みのエンティティを指すリレーションは除外
+        "EXISTS (SELECT 1 FROM entities s WHERE s.id = r.sourceEntityId)".to_string(),
+        "EXISTS (SELECT 1 FROM entities t WHERE t.id = r.targetEntityId)".to_string(),
+    ];
+    let mut params = Vec::new();
+    if let Some(organization_id) = filter.organization_id.as_ref().filter(|s| !s.is_empty()) {
+        conditions.push("r.organizationId = ?".to_string());
+        params.push(SqlValue::Text(organization_id.clone()));
+    }
+    if let Some(company_id) = filter.company_id.as_ref().filter(|s| !s.is_empty()) {
+        conditions.push("r.companyId = ?".to_string());
+        params.push(SqlValue::Text(company_id.clone()));
+    }
+    if let Some(types) = filter.relation_types.as_ref().filter(|t| !t.is_empty()) {
+        conditions.push(format!("r.relationType IN ({})", placeholders(types.len())));
+        params.extend(types.iter().map(|t| SqlValue::Text(t.clone())));
+    }
+    if let Some(min_confidence) = filter.min_confidence {
+        conditions.push("r.confidence >= ?".to_string());
+        params.push(SqlValue::Real(min_confidence));
+    }
+    (conditions.join(" AND "), params)
+}
+
+fn read_edge(row: &rusqlite::Row) -> SqlResult<GraphEdge> {
+    Ok(GraphEdge {
+        id: row.get(0)?,
+        source: row.get(1)?,
+        target: row.get(2)?,
+        relation_type: row.get(3)?,
+        confidence: row.get(4)?,
+        description: row.get(5)?,
+    })
+}
+
+const EDGE_COLUMNS: &str = "r.id, r.sourceEntityId, r.targetEntityId, r.relationType, r.confidence, r.description";
+
+/// 条件に合うエッジをすべて読み込む
+pub(crate) fn load_edges(conn: &Connection, filter: &GraphFilter) -> SqlResult<Vec<GraphEdge>> {
+    let (conditions, params) = edge_conditions(filter);
+    let sql = format!("SELECT {} FROM relations r WHERE {} ORDER BY r.id", EDGE_COLUMNS, conditions);
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(params), read_edge)?;
+    rows.collect()
+}
+
+/// 両端が指定ノードに含まれるエッジを取得
+pub(crate) fn edges_within<'a>(edges: impl IntoIterator<Item = &'a GraphEdge>, node_ids: &HashSet<String>) -> Vec<GraphEdge> {
+    edges
+        .into_iter()
+        .filter(|e| node_ids.contains(&e.source) && node_ids.contains(&e.target))
+        .cloned()
+        .collect()
+}
+
+/// エンティティをノードとして読み込む
+pub(crate) fn load_nodes(conn: &Connection, ids: &[String]) -> SqlResult<HashMap<String, GraphNode>> {
+    let mut nodes = HashMap::new();
+    for chunk in ids.chunks(ID_CHUNK_SIZE) {
+        let sql = format!(
+            "SELECT id, COALESCE(NULLIF(displayName, ''), name), type, organizationId, companyId
+             FROM entities WHERE id IN ({})",
+            placeholders(chunk.len())
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(chunk.iter()), |row| {
+            Ok(GraphNode {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                entity_type: row.get(2)?,
+                organization_id: row.get(3)?,
+                company_id: row.get(4)?,
+                depth: None,
+                component: None,
+                score: None,
+            })
+        })?;
+        for row in rows {
+            let node = row?;
+            nodes.insert(node.id.clone(), node);
+        }
+    }
+    Ok(nodes)
+}
+
+/// IDの順にノードを並べる（エンティティが見つからないIDは除外）
+fn ordered_nodes(mut nodes: HashMap<String, GraphNode>, ids: &[String]) -> Vec<GraphNode> {
+    ids.iter().filter_map(|id| nodes.remove(id)).collect()
+}
+
+/// 隣接リスト（directionに従って辿れる向きのみ）
+fn adjacency<'a>(edges: &'a [GraphEdge], direction: GraphDirection) -> HashMap<&'a str, Vec<(&'a str, usize)>> {
+    let mut adjacency: HashMap<&str, Vec<(&str, usize)>> = HashMap::new();
+    for (i, edge) in edges.iter().enumerate() {
+        if direction != GraphDirection::In {
+            adjacency.entry(edge.source.as_str()).or_default().push((edge.target.as_str(), i));
+        }
+        if direction != GraphDirection::Out {
+            adjacency.entry(edge.target.as_str()).or_default().push((edge.source.as_str(), i));
+        }
+    }
+    adjacency
+}
+
+fn ensure_entity_exists(conn: &Connection, entity_id: &str) -> Result<(), String> {
+    let exists: bool = conn
+        .query_row("SELECT EXISTS (SELECT 1 FROM entities WHERE id = ?1)", [entity_id], |row| row.get(0))
+        .map_err(|e| format!("エンティティの確認に失敗しました: {}", e))?;
+    if !exists {
+        return Err(format!("エンティティが見つかりません: {}", entity_id));
+    }
+    Ok(())
+}
+
+/// エンティティからkホップ以内の近傍を取得
+pub fn graph_neighborhood(options: &GraphNeighborhoodOptions) -> Result<GraphResult, String> {
+    let hops = options.hops.unwrap_or(DEFAULT_HOPS).min(MAX_HOPS);
+    let max_nodes = options.max_nodes.unwrap_or(DEFAULT_MAX_NODES).clamp(1, MAX_NODES_LIMIT);
+    let conn = connection()?;
+    ensure_entity_exists(&conn, &options.entity_id)?;
+
+    let (conditions, edge_params) = edge_conditions(&options.filter);
+    let step = match options.filter.direction {
+        GraphDirection::Out => "SELECT f.tgt, w.depth + 1 FROM walk w JOIN filtered f ON f.src = w.entityId WHERE w.depth < ?",
+        GraphDirection::In => "SELECT f.src, w.depth + 1 FROM walk w JOIN filtered f ON f.tgt = w.entityId WHERE w.depth < ?",
+        GraphDirection::Both => {
+            "SELECT CASE WHEN f.src = w.entityId THEN f.tgt ELSE f.src END, w.depth + 1
+             FROM walk w JOIN filtered f ON f.src = w.entityId OR f.tgt = w.entityId WHERE w.depth < ?"
+        }
+    };
+    // (エンティティ, 深さ)の組でUNIONするため、同じエンティティは深さごとに1回だけ展開される
+    let sql = format!(
+        "WITH RECURSIVE
+            filtered(src, tgt) AS (SELECT r.sourceEntityId, r.targetEntityId FROM relations r WHERE {conditions}),
+            walk(entityId, depth) AS (
+                SELECT ?, 0
+                UNION
+                {step}
+            )
+         SELECT entityId, MIN(depth) AS depth FROM walk GROUP BY entityId ORDER BY depth, entityId LIMIT ?",
+        conditions = conditions,
+        step = step,
+    );
+    let mut params = edge_params;
+    params.push(SqlValue::Text(options.entity_id.clone()));
+    params.push(SqlValue::Integer(hops as i64));
+    params.push(SqlValue::Integer(max_nodes as i64 + 1));
+
+    let mut reached: Vec<(String, usize)> = {
+        let mut stmt = conn.prepare(&sql).map_err(|e| format!("近傍の探索に失敗しました: {}", e))?;
+        let rows = stmt
+            .query_map(params_from_iter(params), |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))
+            .and_then(|rows| rows.collect::<SqlResult<Vec<_>>>())
+            .map_err(|e| format!("近傍の探索に失敗しました: {}", e))?;
+        rows
+    };
+    let truncated = reached.len() > max_nodes;
+    reached.truncate(max_nodes);
+
+    let ids: Vec<String> = reached.iter().map(|(id, _)| id.clone()).collect();
+    let depths: HashMap<String, usize> = reached.into_iter().collect();
+    let mut nodes = load_nodes(&conn, &ids).map_err(|e| format!("ノードの取得に失敗しました: {}", e))?;
+    for (id, node) in nodes.iter_mut() {
+        node.depth = depths.get(id).copied();
+    }
+
+    let node_ids: HashSet<String> = ids.iter().cloned().collect();
+    let edges = load_edges(&conn, &options.filter).map_err(|e| format!("エッジの取得に失敗しました: {}", e))?;
+    Ok(GraphResult {
+        nodes: ordered_nodes(nodes, &ids),
+        edges: edges_within(&edges, &node_ids),
+        components: None,
+        truncated,
+    })
+}
+
+/// 2つのエンティティ間の最短経路（ホップ数が最小の経路）を取得
+/// 経路が見つからない場合はノード・エッジが空の結果を返す
+pub fn graph_shortest_path(options: &GraphShortestPathOptions) -> Result<GraphResult, String> {
+    let max_depth = options.max_depth.unwrap_or(DEFAULT_MAX_PATH_DEPTH).max(1);
+    let conn = connection()?;
+    ensure_entity_exists(&conn, &options.source_entity_id)?;
+    ensure_entity_exists(&conn, &options.target_entity_id)?;
+
+    let edges = load_edges(&conn, &options.filter).map_err(|e| format!("エッジの取得に失敗しました: {}", e))?;
+    let adjacency = adjacency(&edges, options.filter.direction);
+
+    // 幅優先探索（到達したノードの直前のノードとエッジを記録）
+    let source = options.source_entity_id.as_str();
+    let target = options.target_entity_id.as_str();
+    let mut previous: HashMap<&str, (&str, usize)> = HashMap::new();
+    let mut depths: HashMap<&str, usize> = HashMap::from([(source, 0)]);
+    let mut queue = VecDeque::from([source]);
+    while let Some(current) = queue.pop_front() {
+        if current == target {
+            break;
+        }
+        let depth = depths[current];
+        if depth >= max_depth {
+            continue;
+        }
+        for &(next, edge_index) in adjacency.get(current).into_iter().flatten() {
+            if depths.contains_key(next) {
+                continue;
+            }
+            depths.insert(next, depth + 1);
+            previous.insert(next, (current, edge_index));
+            queue.push_back(next);
+        }
+    }
+
+    if !depths.contains_key(target) {
+        return Ok(GraphResult::default());
+    }
+
+    let mut path_ids = vec![target.to_string()];
+    let mut path_edges = Vec::new();
+    let mut current = target;
+    while let Some(&(prev, edge_index)) = previous.get(current) {
+        path_edges.push(edges[edge_index].clone());
+        path_ids.push(prev.to_string());
+        current = prev;
+    }
+    path_ids.reverse();
+    path_edges.reverse();
+
+    let mut nodes = load_nodes(&conn, &path_ids).map_err(|e| format!("ノードの取得に失敗しました: {}", e))?;
+    for (depth, id) in path_ids.iter().enumerate() {
+        if let Some(node) = nodes.get_mut(id) {
+            node.depth = Some(depth);
+        }
+    }
+    Ok(GraphResult {
+        nodes: ordered_nodes(nodes, &path_ids),
+        edges: path_edges,
+        components: None,
+        truncated: false,
+    })
+}
+
+/// Union-Findの代表元を取得
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// リレーションの向きを区別しない連結成分を取得（大きい順）
+pub fn graph_connected_components(options: &GraphComponentsOptions) -> Result<GraphResult, String> {
+    let min_size = options.min_size.unwrap_or(2).max(1);
+    let max_nodes = options.max_nodes.unwrap_or(DEFAULT_MAX_NODES).clamp(1, MAX_NODES_LIMIT);
+    let conn = connection()?;
+    let edges = load_edges(&conn, &options.filter).map_err(|e| format!("エッジの取得に失敗しました: {}", e))?;
+
+    let mut ids: Vec<String> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut intern = |id: &str, ids: &mut Vec<String>| -> usize {
+        *index.entry(id.to_string()).or_insert_with(|| {
+            ids.push(id.to_string());
+            ids.len() - 1
+        })
+    };
+    let edge_pairs: Vec<(usize, usize)> = edges
+        .iter()
+        .map(|e| (intern(&e.source, &mut ids), intern(&e.target, &mut ids)))
+        .collect();
+
+    // 孤立したエンティティも含める場合は、組織/事業会社のエンティティをすべて追加
+    if min_size == 1 {
+        for id in load_scope_entity_ids(&conn, &options.filter).map_err(|e| format!("エンティティの取得に失敗しました: {}", e))? {
+            intern(&id, &mut ids);
+        }
+    }
+
+    let mut parent: Vec<usize> = (0..ids.len()).collect();
+    for (a, b) in edge_pairs {
+        let (root_a, root_b) = (find_root(&mut parent, a), find_root(&mut parent, b));
+        if root_a != root_b {
+            parent[root_a.max(root_b)] = root_a.min(root_b);
+        }
+    }
+    let mut groups: HashMap<usize, Vec<String>> = HashMap::new();
+    for i in 0..ids.len() {
+        let root = find_root(&mut parent, i);
+        groups.entry(root).or_default().push(ids[i].clone());
+    }
+    let mut groups: Vec<Vec<String>> = groups.into_values().filter(|g| g.len() >= min_size).collect();
+    for group in groups.iter_mut() {
+        group.sort();
+    }
+    groups.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+
+    // ノード数の上限に達するまで大きい連結成分から順に含める
+    let mut components = Vec::new();
+    let mut node_ids: Vec<String> = Vec::new();
+    let mut truncated = false;
+    for group in groups {
+        if node_ids.len() + group.len() > max_nodes {
+            truncated = true;
+            break;
+        }
+        node_ids.extend(group.iter().cloned());
+        components.push(GraphComponent {
+            index: components.len(),
+            size: group.len(),
+            entity_ids: group,
+        });
+    }
+
+    let mut nodes = load_nodes(&conn, &node_ids).map_err(|e| format!("ノードの取得に失敗しました: {}", e))?;
+    for component in &components {
+        for id in &component.entity_ids {
+            if let Some(node) = nodes.get_mut(id) {
+                node.component = Some(component.index);
+            }
+        }
+    }
+    let node_set: HashSet<String> = node_ids.iter().cloned().collect();
+    Ok(GraphResult {
+        nodes: ordered_nodes(nodes, &node_ids),
+        edges: edges_within(&edges, &node_set),
+        components: Some(components),
+        truncated,
+    })
+}
+
+/// 組織/事業会社のエンティティID（孤立したエンティティを含める場合）
+pub(crate) fn load_scope_entity_ids(conn: &Connection, filter: &GraphFilter) -> SqlResult<Vec<String>> {
+    let mut conditions = vec!["1 = 1".to_string()];
+    let mut params = Vec::new();
+    if let Some(organization_id) = filter.organization_id.as_ref().filter(|s| !s.is_empty()) {
+        conditions.push("organizationId = ?".to_string());
+        params.push(SqlValue::Text(organization_id.clone()));
+    }
+    if let Some(company_id) = filter.company_id.as_ref().filter(|s| !s.is_empty()) {
+        conditions.push("companyId = ?".to_string());
+        params.push(SqlValue::Text(company_id.clone()));
+    }
+    let sql = format!("SELECT id FROM entities WHERE {} ORDER BY id", conditions.join(" AND "));
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(params), |row| row.get(0))?;
+    rows.collect()
+}
+
+/// 次数中心性（directionに従って出次数・入次数・合計を数える）
+fn degree_scores(edges: &[GraphEdge], direction: GraphDirection) -> HashMap<String, f64> {
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for edge in edges {
+        if direction != GraphDirection::In {
+            *scores.entry(edge.source.clone()).or_default() += 1.0;
+        }
+        if direction != GraphDirection::Out {
+            *scores.entry(edge.target.clone()).or_default() += 1.0;
+        }
+        // 片方向のみ数える場合も、もう一方の端点はスコア0のノードとして含める
+        scores.entry(edge.source.clone()).or_default();
+        scores.entry(edge.target.clone()).or_default();
+    }
+    scores
+}
+
+/// PageRank（direction=bothの場合は各リレーションを双方向のリンクとして扱う）
+fn pagerank_scores(edges: &[GraphEdge], direction: GraphDirection, damping: f64, iterations: usize) -> HashMap<String, f64> {
+    let mut ids: Vec<&str> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for edge in edges {
+        for id in [edge.source.as_str(), edge.target.as_str()] {
+            index.entry(id).or_insert_with(|| {
+                ids.push(id);
+                ids.len() - 1
+            });
+        }
+    }
+    let n = ids.len();
+    if n == 0 {
+        return HashMap::new();
+    }
+
+    // リンク（リンク元 → リンク先）
+    let mut out_links: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for edge in edges {
+        let (source, target) = (index[edge.source.as_str()], index[edge.target.as_str()]);
+        if direction != GraphDirection::In {
+            out_links[source].push(target);
+        }
+        if direction != GraphDirection::Out {
+            out_links[target].push(source);
+        }
+    }
+
+    let base = (1.0 - damping) / n as f64;
+    let mut ranks = vec![1.0 / n as f64; n];
+    for _ in 0..iterations {
+        // リンクを持たないノードのスコアは全ノードに均等に分配
+        let dangling: f64 = (0..n).filter(|&i| out_links[i].is_empty()).map(|i| ranks[i]).sum();
+        let mut next = vec![base + damping * dangling / n as f64; n];
+        for (i, links) in out_links.iter().enumerate() {
+            if links.is_empty() {
+                continue;
+            }
+            let share = damping * ranks[i] / links.len() as f64;
+            for &j in links {
+                next[j] += share;
+            }
+        }
+        let delta: f64 = next.iter().zip(&ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+    ids.into_iter().map(|id| id.to_string()).zip(ranks).collect()
+}
+
+/// 次数またはPageRankの中心性が高い順にノードを取得
+pub fn graph_centrality(options: &GraphCentralityOptions) -> Result<GraphResult, String> {
+    let limit = options.limit.unwrap_or(DEFAULT_MAX_NODES).clamp(1, MAX_NODES_LIMIT);
+    let conn = connection()?;
+    let edges = load_edges(&conn, &options.filter).map_err(|e| format!("エッジの取得に失敗しました: {}", e))?;
+
+    let scores = match options.algorithm {
+        CentralityAlgorithm::Degree => degree_scores(&edges, options.filter.direction),
+        CentralityAlgorithm::PageRank => {
+            let damping = options.damping.filter(|d| *d > 0.0 && *d < 1.0).unwrap_or(DEFAULT_DAMPING);
+            let iterations = options.iterations.unwrap_or(DEFAULT_PAGERANK_ITERATIONS).max(1);
+            pagerank_scores(&edges, options.filter.direction, damping, iterations)
+        }
+    };
+
+    let mut ranked: Vec<(String, f64)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let truncated = ranked.len() > limit;
+    ranked.truncate(limit);
+
+    let ids: Vec<String> = ranked.iter().map(|(id, _)| id.clone()).collect();
+    let mut nodes = load_nodes(&conn, &ids).map_err(|e| format!("ノードの取得に失敗しました: {}", e))?;
+    for (id, score) in &ranked {
+        if let Some(node) = nodes.get_mut(id) {
+            node.score = Some(*score);
+        }
+    }
+    let node_set: HashSet<String> = ids.iter().cloned().collect();
+    Ok(GraphResult {
+        nodes: ordered_nodes(nodes, &ids),
+        edges: edges_within(&edges, &node_set),
+        components: None,
+        truncated,
+    })
+}
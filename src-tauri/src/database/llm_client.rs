@@ -0,0 +1,318 @@
+/**
+ * LLMの呼び出し（チャット補完）
+ * Agent・タスクのmodelType / selectedModelからプロバイダーを判定し、ai_settingsの設定でAPIを呼び出す
+ *
+ * - gpt（OpenAI）/ lmstudio: OpenAI互換の`/chat/completions`エンドポイント
+ * - claude（Anthropic）: `/messages`エンドポイント
+ * - local（Ollama）: `/api/chat`エンドポイント
+ * - gemini: `/models/{model}:generateContent`エンドポイント（APIキーは環境変数GEMINI_API_KEY）
+ *
+ * プロバイダーの判定はフロントエンドのcallLLMAPIと同じ規則（モデル名が優先）
+ */
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::ai_settings::{get_ai_setting, get_default_model};
+use super::embedding::{DEFAULT_LMSTUDIO_BASE_URL, DEFAULT_OLLAMA_BASE_URL, DEFAULT_OPENAI_BASE_URL};
+
+const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+const DEFAULT_GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+const DEFAULT_GEMINI_MODEL: &str = "gemini-1.5-flash";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// 呼び出し側でタイムアウトを指定しない場合のリクエストのタイムアウト
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// LLMのメッセージ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmMessage {
+    pub role: String, // "system" | "user" | "assistant"
+    pub content: String,
+}
+
+impl LlmMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self { role: "system".to_string(), content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self { role: "user".to_string(), content: content.into() }
+    }
+}
+
+/// 生成のオプション
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmOptions {
+    pub temperature: f32,
+    #[serde(rename = "maxTokens")]
+    pub max_tokens: u32,
+}
+
+impl Default for LlmOptions {
+    fn default() -> Self {
+        // フロントエンドのcallLLMAPIと同じデフォルト
+        Self { temperature: 0.7, max_tokens: 4000 }
+    }
+}
+
+/// トークン使用量（プロバイダーが返した場合のみ）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LlmUsage {
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: Option<u64>,
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: Option<u64>,
+}
+
+/// LLMの応答
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmResponse {
+    pub content: String,
+    pub provider: String,
+    pub model: String,
+    pub usage: LlmUsage,
+}
+
+/// LLM APIの形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LlmApi {
+    OpenAICompatible,
+    Anthropic,
+    Ollama,
+    Gemini,
+}
+
+/// 設定されたプロバイダーでLLMを呼び出すクライアント
+#[derive(Clone)]
+pub struct LlmClient {
+    client: reqwest::Client,
+    api: LlmApi,
+    provider: String,
+    base_url: String,
+    api_key: Option<String>,
+    model: String,
+}
+
+/// フロントエンドと同じ規則でローカルモデル（Ollama）か判定
+fn is_local_model(model: &str) -> bool {
+    model.starts_with("qwen")
+        || model.starts_with("llama")
+        || model.starts_with("mistral")
+        || model.contains(":latest")
+        || model.contains(":instruct")
+}
+
+impl LlmClient {
+    /// modelType（gpt / local / claude / gemini / lmstudio）とモデル名からクライアントを作成
+    /// モデル名を省略した場合はプロバイダーのデフォルトモデル（ai_settingsの設定があればそれ）を使う
+    pub fn resolve(model_type: Option<&str>, model: Option<&str>) -> Result<Self, String> {
+        let model = model.map(str::trim).filter(|m| !m.is_empty());
+        let model_type = model_type.map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty());
+
+        let provider = match (model, model_type.as_deref()) {
+            (Some(m), _) if is_local_model(m) => "ollama",
+            (Some(m), _) if m.starts_with("claude") => "anthropic",
+            (Some(m), _) if m.starts_with("gemini") => "gemini",
+            (_, Some("local")) | (_, Some("ollama")) => "ollama",
+            (_, Some("claude")) | (_, Some("anthropic")) => "anthropic",
+            (_, Some("gemini")) => "gemini",
+            (_, Some("lmstudio")) => "lmstudio",
+            _ => "openai",
+        };
+
+        let (api, default_base_url) = match provider {
+            "ollama" => (LlmApi::Ollama, DEFAULT_OLLAMA_BASE_URL),
+            "anthropic" => (LlmApi::Anthropic, DEFAULT_ANTHROPIC_BASE_URL),
+            "gemini" => (LlmApi::Gemini, DEFAULT_GEMINI_BASE_URL),
+            "lmstudio" => (LlmApi::OpenAICompatible, DEFAULT_LMSTUDIO_BASE_URL),
+            _ => (LlmApi::OpenAICompatible, DEFAULT_OPENAI_BASE_URL),
+        };
+
+        let (api_key, base_url, configured_model) = if api == LlmApi::Gemini {
+            (
+                std::env::var("GEMINI_API_KEY").ok().filter(|s| !s.is_empty()),
+                std::env::var("GEMINI_BASE_URL").ok().filter(|s| !s.is_empty()),
+                None,
+            )
+        } else {
+            // データベース未初期化（Supabase専用）の場合は環境変数とデフォルト値のみを使う
+            match get_ai_setting(provider) {
+                Ok(Some(config)) => (config.api_key, config.base_url, Some(config.model).filter(|m| !m.is_empty())),
+                Ok(None) => (None, None, None),
+                Err(e) => {
+                    eprintln!("⚠️ [llm_client] AI設定の取得に失敗しました（デフォルト設定を使用します）: {}", e);
+                    (None, None, None)
+                }
+            }
+        };
+
+        match api {
+            LlmApi::OpenAICompatible if provider == "openai" && api_key.is_none() => {
+                return Err("OpenAIのAPIキーが設定されていません".to_string());
+            }
+            LlmApi::Anthropic if api_key.is_none() => {
+                return Err("AnthropicのAPIキーが設定されていません".to_string());
+            }
+            LlmApi::Gemini if api_key.is_none() => {
+                return Err("GeminiのAPIキーが設定されていません（環境変数GEMINI_API_KEY）".to_string());
+            }
+            _ => {}
+        }
+
+        let model = model
+            .map(str::to_string)
+            .or(configured_model)
+            .unwrap_or_else(|| match api {
+                LlmApi::Gemini => DEFAULT_GEMINI_MODEL.to_string(),
+                _ => get_default_model(provider),
+            });
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            api,
+            provider: provider.to_string(),
+            base_url: base_url
+                .unwrap_or_else(|| default_base_url.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            api_key,
+            model,
+        })
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// メッセージを送信して応答テキストを取得
+    pub async fn chat(&self, messages: &[LlmMessage], options: &LlmOptions) -> Result<LlmResponse, String> {
+        let system: Vec<&str> = messages.iter().filter(|m| m.role == "system").map(|m| m.content.as_str()).collect();
+        let conversation: Vec<&LlmMessage> = messages.iter().filter(|m| m.role != "system").collect();
+
+        let (url, body) = match self.api {
+            LlmApi::OpenAICompatible => (
+                format!("{}/chat/completions", self.base_url),
+                json!({
+                    "model": self.model,
+                    "messages": messages,
+                    "temperature": options.temperature,
+                    "max_tokens": options.max_tokens,
+                }),
+            ),
+            LlmApi::Anthropic => {
+                let mut body = json!({
+                    "model": self.model,
+                    "messages": conversation.iter().map(|m| json!({
+                        "role": if m.role == "assistant" { "assistant" } else { "user" },
+                        "content": m.content,
+                    })).collect::<Vec<_>>(),
+                    "max_tokens": options.max_tokens,
+                    "temperature": options.temperature,
+                });
+                if !system.is_empty() {
+                    body["system"] = json!(system.join("\n\n"));
+                }
+                (format!("{}/messages", self.base_url), body)
+            }
+            LlmApi::Ollama => (
+                format!("{}/api/chat", self.base_url),
+                json!({
+                    "model": self.model,
+                    "messages": messages,
+                    "stream": false,
+                    "options": {
+                        "temperature": options.temperature,
+                        "num_predict": options.max_tokens,
+                    },
+                }),
+            ),
+            LlmApi::Gemini => {
+                let mut body = json!({
+                    "contents": conversation.iter().map(|m| json!({
+                        "role": if m.role == "assistant" { "model" } else { "user" },
+                        "parts": [{ "text": m.content }],
+                    })).collect::<Vec<_>>(),
+                    "generationConfig": {
+                        "temperature": options.temperature,
+                        "maxOutputTokens": options.max_tokens,
+                    },
+                });
+                if !system.is_empty() {
+                    body["systemInstruction"] = json!({ "parts": [{ "text": system.join("\n\n") }] });
+                }
+                (format!("{}/models/{}:generateContent", self.base_url, self.model), body)
+            }
+        };
+
+        let mut request = self.client.post(url).timeout(DEFAULT_REQUEST_TIMEOUT).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = match self.api {
+                LlmApi::Anthropic => request
+                    .header("x-api-key", api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION),
+                LlmApi::Gemini => request.header("x-goog-api-key", api_key),
+                _ => request.bearer_auth(api_key),
+            };
+        }
+
+        let response = request.send().await
+            .map_err(|e| format!("LLM APIへの接続に失敗しました ({}): {}", self.provider, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("LLM APIがエラーを返しました ({} {}): {}", self.provider, status, body));
+        }
+        let data: Value = response.json().await
+            .map_err(|e| format!("LLM APIのレスポンスの解析に失敗しました: {}", e))?;
+
+        let (content, usage) = match self.api {
+            LlmApi::OpenAICompatible => (
+                data["choices"][0]["message"]["content"].as_str().map(str::to_string),
+                LlmUsage {
+                    input_tokens: data["usage"]["prompt_tokens"].as_u64(),
+                    output_tokens: data["usage"]["completion_tokens"].as_u64(),
+                },
+            ),
+            LlmApi::Anthropic => (
+                data["content"].as_array().map(|blocks| {
+                    blocks.iter().filter_map(|b| b["text"].as_str()).collect::<Vec<_>>().join("")
+                }),
+                LlmUsage {
+                    input_tokens: data["usage"]["input_tokens"].as_u64(),
+                    output_tokens: data["usage"]["output_tokens"].as_u64(),
+                },
+            ),
+            LlmApi::Ollama => (
+                data["message"]["content"].as_str().map(str::to_string),
+                LlmUsage {
+                    input_tokens: data["prompt_eval_count"].as_u64(),
+                    output_tokens: data["eval_count"].as_u64(),
+                },
+            ),
+            LlmApi::Gemini => (
+                data["candidates"][0]["content"]["parts"].as_array().map(|parts| {
+                    parts.iter().filter_map(|p| p["text"].as_str()).collect::<Vec<_>>().join("")
+                }),
+                LlmUsage {
+                    input_tokens: data["usageMetadata"]["promptTokenCount"].as_u64(),
+                    output_tokens: data["usageMetadata"]["candidatesTokenCount"].as_u64(),
+                },
+            ),
+        };
+
+        let content = content
+            .ok_or_else(|| format!("LLM APIのレスポンスに応答テキストがありません ({}): {}", self.provider, data))?;
+        Ok(LlmResponse {
+            content: content.trim().to_string(),
+            provider: self.provider.clone(),
+            model: self.model.clone(),
+            usage,
+        })
+    }
+}
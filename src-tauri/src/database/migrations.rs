@@ -0,0 +1,1246 @@
+/**
+ * スキーマ移行（マイグレーション）管理モジュール
+ * 番号付きマイグレーションを順番に適用し、schema_migrationsテーブルに記録する
+ *
+ * - 各マイグレーションは1つのトランザクション内で実行される（途中で失敗した場合はロールバック）
+ * - 適用済みのバージョンはschema_migrationsに記録され、再実行されない
+ * - dry_runモードでは全マイグレーションを実行した後にロールバックし、結果のみを返す
+ * - 既存データベース（schema_migrationsが無い）でも安全に再適用できるよう、各マイグレーションは冪等に書く
+ */
+
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use serde::Serialize;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use crate::database::get_timestamp;
+
+/// マイグレーション定義
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    up: fn(&Connection) -> SqlResult<()>,
+}
+
+/// マイグレーションの適用記録
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationRecord {
+    pub version: i64,
+    pub name: String,
+    #[serde(rename = "appliedAt")]
+    pub applied_at: Option<String>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: Option<i64>,
+}
+
+/// マイグレーション失敗情報
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationFailure {
+    pub version: i64,
+    pub name: String,
+    pub error: String,
+}
+
+/// マイグレーション実行結果
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    #[serde(rename = "dryRun")]
+    pub dry_run: bool,
+    #[serde(rename = "startVersion")]
+    pub start_version: i64,
+    #[serde(rename = "endVersion")]
+    pub end_version: i64,
+    #[serde(rename = "latestVersion")]
+    pub latest_version: i64,
+    /// 今回適用された（dry_runの場合は適用される予定の）マイグレーション
+    pub applied: Vec<MigrationRecord>,
+    /// 失敗したマイグレーション（失敗した場合、それ以降のマイグレーションは実行されない）
+    pub failure: Option<MigrationFailure>,
+}
+
+/// スキーマバージョンの状態
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    #[serde(rename = "currentVersion")]
+    pub current_version: i64,
+    #[serde(rename = "latestVersion")]
+    pub latest_version: i64,
+    pub applied: Vec<MigrationRecord>,
+    pub pending: Vec<MigrationRecord>,
+}
+
+/// 全マイグレーション（バージョン順に並べること。一度リリースしたマイグレーションは変更しない）
+static MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "recover_stray_backup_tables", up: recover_stray_backup_tables },
+    Migration { version: 2, name: "users_add_role", up: users_add_role },
+    Migration { version: 3, name: "organizations_add_type", up: organizations_add_type },
+    Migration { version: 4, name: "organization_members_add_profile_columns", up: organization_members_add_profile_columns },
+    Migration { version: 5, name: "meeting_notes_nullable_organization_id", up: meeting_notes_nullable_organization_id },
+    Migration { version: 6, name: "startups_add_columns", up: startups_add_columns },
+    Migration { version: 7, name: "focus_initiatives_nullable_organization_id", up: focus_initiatives_nullable_organization_id },
+    Migration { version: 8, name: "company_contents_add_capital_structure", up: company_contents_add_capital_structure },
+    Migration { version: 9, name: "themes_add_position", up: themes_add_position },
+    Migration { version: 10, name: "categories_add_position_and_parent", up: categories_add_position_and_parent },
+    Migration { version: 11, name: "entities_add_company_id_and_search_columns", up: entities_add_company_id_and_search_columns },
+    Migration { version: 12, name: "relations_add_company_id_and_nullable_topic_id", up: relations_add_company_id_and_nullable_topic_id },
+    Migration { version: 13, name: "topics_add_company_id_and_search_columns", up: topics_add_company_id_and_search_columns },
+    Migration { version: 14, name: "tasks_and_agents_add_model_columns", up: tasks_and_agents_add_model_columns },
+    Migration { version: 15, name: "create_full_text_search_index", up: create_full_text_search_index },
+    Migration { version: 16, name: "create_write_outbox_tables", up: create_write_outbox_tables },
+    Migration { version: 17, name: "create_entity_merge_audit_table", up: create_entity_merge_audit_table },
+    Migration { version: 18, name: "graphviz_yaml_files_add_version_index", up: graphviz_yaml_files_add_version_index },
+    Migration { version: 19, name: "create_design_doc_section_revisions_table", up: create_design_doc_section_revisions_table },
+    Migration { version: 20, name: "create_task_chain_runs_table", up: create_task_chain_runs_table },
+    Migration { version: 21, name: "a2a_messages_add_delivery_columns", up: a2a_messages_add_delivery_columns },
+    Migration { version: 22, name: "agent_prompt_versioning", up: agent_prompt_versioning },
+    Migration { version: 23, name: "full_text_search_exclude_yaml_history", up: full_text_search_exclude_yaml_history },
+    Migration { version: 24, name: "create_write_outbox_keys_tables", up: create_write_outbox_keys_tables },
+    Migration { version: 25, name: "tasks_add_chain_id", up: tasks_add_chain_id },
+];
+
+/// 最新のスキーマバージョン
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+/// schema_migrationsテーブルを作成
+fn ensure_migrations_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            appliedAt TEXT NOT NULL,
+            durationMs INTEGER
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 適用済みのマイグレーション一覧を取得（schema_migrationsが存在しない場合は空）
+fn applied_migrations(conn: &Connection) -> SqlResult<Vec<MigrationRecord>> {
+    if !table_exists(conn, "schema_migrations")? {
+        return Ok(Vec::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT version, name, appliedAt, durationMs FROM schema_migrations ORDER BY version",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(MigrationRecord {
+            version: row.get(0)?,
+            name: row.get(1)?,
+            applied_at: row.get(2)?,
+            duration_ms: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// 現在のスキーマバージョンを取得（未管理のデータベースは0）
+pub fn current_version(conn: &Connection) -> SqlResult<i64> {
+    if !table_exists(conn, "schema_migrations")? {
+        return Ok(0);
+    }
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_migrations", [], |row| row.get(0))
+}
+
+/// スキーマバージョンの状態を取得
+pub fn get_migration_status(conn: &Connection) -> SqlResult<MigrationStatus> {
+    let applied = applied_migrations(conn)?;
+    let pending = MIGRATIONS
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+        .map(|m| MigrationRecord {
+            version: m.version,
+            name: m.name.to_string(),
+            applied_at: None,
+            duration_ms: None,
+        })
+        .collect();
+
+    Ok(MigrationStatus {
+        current_version: current_version(conn)?,
+        latest_version: latest_version(),
+        applied,
+        pending,
+    })
+}
+
+/// 未適用のマイグレーションを順番に実行
+///
+/// マイグレーション自体の失敗はErrではなくMigrationReport::failureとして返す。
+/// Errはschema_migrationsテーブルの作成失敗など、実行基盤のエラーのみ。
+pub fn run_migrations(conn: &Connection, dry_run: bool) -> SqlResult<MigrationReport> {
+    let applied_versions: Vec<i64> = applied_migrations(conn)?.iter().map(|m| m.version).collect();
+    let start_version = current_version(conn)?;
+    let pending: Vec<&Migration> = MIGRATIONS
+        .iter()
+        .filter(|m| !applied_versions.contains(&m.version))
+        .collect();
+
+    let mut report = MigrationReport {
+        dry_run,
+        start_version,
+        end_version: start_version,
+        latest_version: latest_version(),
+        applied: Vec::new(),
+        failure: None,
+    };
+
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    // テーブル再作成を伴うマイグレーションのため外部キー制約を無効化する
+    // 注意: PRAGMA foreign_keysはトランザクション内では変更できないため、トランザクション開始前に設定する
+    conn.execute("PRAGMA foreign_keys = OFF", [])?;
+    let result = if dry_run {
+        run_pending_dry(conn, &pending, &mut report)
+    } else {
+        run_pending(conn, &pending, &mut report)
+    };
+    conn.execute("PRAGMA foreign_keys = ON", [])?;
+    result?;
+
+    Ok(report)
+}
+
+/// 各マイグレーションを個別のトランザクションで適用
+fn run_pending(conn: &Connection, pending: &[&Migration], report: &mut MigrationReport) -> SqlResult<()> {
+    ensure_migrations_table(conn)?;
+
+    for migration in pending {
+        eprintln!("🔧 マイグレーションを適用します: {:04}_{}", migration.version, migration.name);
+        let started = Instant::now();
+        let tx = conn.unchecked_transaction()?;
+
+        let result = (migration.up)(&tx).and_then(|_| {
+            let duration_ms = started.elapsed().as_millis() as i64;
+            let now = get_timestamp();
+            tx.execute(
+                "INSERT INTO schema_migrations (version, name, appliedAt, durationMs) VALUES (?1, ?2, ?3, ?4)",
+                params![migration.version, migration.name, now, duration_ms],
+            )?;
+            Ok((now, duration_ms))
+        });
+
+        match result {
+            Ok((applied_at, duration_ms)) => {
+                tx.commit()?;
+                report.end_version = migration.version;
+                report.applied.push(MigrationRecord {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    applied_at: Some(applied_at),
+                    duration_ms: Some(duration_ms),
+                });
+            }
+            Err(e) => {
+                // トランザクションはロールバックされ、データベースは直前のバージョンのまま残る
+                tx.rollback()?;
+                eprintln!("❌ マイグレーションに失敗しました: {:04}_{} - {}", migration.version, migration.name, e);
+                report.failure = Some(MigrationFailure {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    error: e.to_string(),
+                });
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 全マイグレーションを1つのトランザクションで実行し、最後にロールバックする
+fn run_pending_dry(conn: &Connection, pending: &[&Migration], report: &mut MigrationReport) -> SqlResult<()> {
+    let tx = conn.unchecked_transaction()?;
+
+    for migration in pending {
+        let started = Instant::now();
+        match (migration.up)(&tx) {
+            Ok(_) => {
+                report.end_version = migration.version;
+                report.applied.push(MigrationRecord {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    applied_at: None,
+                    duration_ms: Some(started.elapsed().as_millis() as i64),
+                });
+            }
+            Err(e) => {
+                report.failure = Some(MigrationFailure {
+                    version: migration.version,
+                    name: migration.name.to_string(),
+                    error: e.to_string(),
+                });
+                break;
+            }
+        }
+    }
+
+    tx.rollback()
+}
+
+// ============================================================================
+// ヘルパー
+// ============================================================================
+
+fn table_exists(conn: &Connection, table: &str) -> SqlResult<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name = ?1",
+        params![table],
+        |row| Ok(row.get::<_, i64>(0)? > 0),
+    )
+}
+
+fn table_columns(conn: &Connection, table: &str) -> SqlResult<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?1)")?;
+    let rows = stmt.query_map(params![table], |row| row.get::<_, String>(0))?;
+    rows.collect()
+}
+
+fn column_is_not_null(conn: &Connection, table: &str, column: &str) -> SqlResult<bool> {
+    let not_null: Option<i64> = conn
+        .query_row(
+            "SELECT \"notnull\" FROM pragma_table_info(?1) WHERE name = ?2",
+            params![table, column],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(not_null.unwrap_or(0) != 0)
+}
+
+/// 存在しないカラムのみ追加する（テーブルが存在しない場合は何もしない）
+fn add_missing_columns(conn: &Connection, table: &str, columns: &[(&str, &str)]) -> SqlResult<()> {
+    if !table_exists(conn, table)? {
+        return Ok(());
+    }
+
+    let existing = table_columns(conn, table)?;
+    for (column_name, column_type) in columns {
+        if !existing.iter().any(|c| c == column_name) {
+            eprintln!("📝 {}テーブルにカラムを追加: {}", table, column_name);
+            conn.execute(
+                &format!("ALTER TABLE \"{}\" ADD COLUMN \"{}\" {}", table, column_name, column_type),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 共通カラムのみを対象に、sourceテーブルの行をtargetテーブルへコピー
+fn copy_common_columns(conn: &Connection, source: &str, target: &str, or_ignore: bool) -> SqlResult<usize> {
+    let source_columns = table_columns(conn, source)?;
+    let common: Vec<String> = table_columns(conn, target)?
+        .into_iter()
+        .filter(|c| source_columns.contains(c))
+        .map(|c| format!("\"{}\"", c))
+        .collect();
+
+    if common.is_empty() {
+        return Ok(0);
+    }
+
+    let columns = common.join(", ");
+    conn.execute(
+        &format!(
+            "INSERT {} INTO \"{}\" ({}) SELECT {} FROM \"{}\"",
+            if or_ignore { "OR IGNORE" } else { "" },
+            target,
+            columns,
+            columns,
+            source
+        ),
+        [],
+    )
+}
+
+/// テーブルを新しい定義で再作成し、共通カラムのデータを引き継ぐ
+///
+/// SQLiteはALTER TABLEでNOT NULL制約やCHECK制約を変更できないため、
+/// バックアップ→削除→再作成→コピー→バックアップ削除 の手順で行う。
+/// トランザクション内で呼び出されるため、途中で失敗してもバックアップテーブルは残らない。
+fn rebuild_table(conn: &Connection, table: &str, create_sql: &str) -> SqlResult<()> {
+    let backup = format!("{}_migration_backup", table);
+    eprintln!("📝 {}テーブルを再作成します", table);
+
+    conn.execute(&format!("DROP TABLE IF EXISTS \"{}\"", backup), [])?;
+    conn.execute(&format!("CREATE TABLE \"{}\" AS SELECT * FROM \"{}\"", backup, table), [])?;
+    conn.execute(&format!("DROP TABLE \"{}\"", table), [])?;
+    conn.execute(create_sql, [])?;
+    copy_common_columns(conn, &backup, table, false)?;
+    conn.execute(&format!("DROP TABLE \"{}\"", backup), [])?;
+
+    eprintln!("✅ {}テーブルの再作成が完了しました", table);
+    Ok(())
+}
+
+// ============================================================================
+// マイグレーション本体
+// ============================================================================
+
+/// 0001: 旧実装のテーブル再作成が途中で失敗した際に残ったバックアップテーブルを回収する
+///
+/// 旧実装では「バックアップ作成→元テーブル削除→再作成→コピー」をトランザクション外で行っていたため、
+/// 途中で失敗するとバックアップテーブルだけが残り、次回起動時に元テーブルが空で再作成されることがあった。
+/// 元テーブルに存在しない行をバックアップから復元した上で、バックアップテーブルを削除する。
+fn recover_stray_backup_tables(conn: &Connection) -> SqlResult<()> {
+    let backups = [
+        ("meetingNotes_backup", "meetingNotes"),
+        ("focusInitiatives_backup", "focusInitiatives"),
+        ("entities_backup", "entities"),
+        ("relations_backup", "relations"),
+        ("relations_backup_topicid", "relations"),
+        ("topics_backup", "topics"),
+    ];
+
+    for (backup, target) in backups {
+        if !table_exists(conn, backup)? {
+            continue;
+        }
+
+        if table_exists(conn, target)? {
+            let restored = copy_common_columns(conn, backup, target, true)?;
+            eprintln!("📝 残存していた{}から{}件を{}へ復元しました", backup, restored, target);
+        }
+        conn.execute(&format!("DROP TABLE \"{}\"", backup), [])?;
+    }
+    Ok(())
+}
+
+/// 0002: usersテーブルにroleカラムを追加
+fn users_add_role(conn: &Connection) -> SqlResult<()> {
+    add_missing_columns(conn, "users", &[("role", "TEXT DEFAULT 'user'")])
+}
+
+/// 0003: organizationsテーブルにtypeカラムを追加
+fn organizations_add_type(conn: &Connection) -> SqlResult<()> {
+    add_missing_columns(conn, "organizations", &[("type", "TEXT DEFAULT 'organization'")])
+}
+
+/// 0004: organizationMembersテーブルに不足しているカラムを追加
+fn organization_members_add_profile_columns(conn: &Connection) -> SqlResult<()> {
+    add_missing_columns(
+        conn,
+        "organizationMembers",
+        &[
+            ("nameRomaji", "TEXT"),
+            ("department", "TEXT"),
+            ("extension", "TEXT"),
+            ("companyPhone", "TEXT"),
+            ("mobilePhone", "TEXT"),
+            ("email", "TEXT"),
+            ("itochuEmail", "TEXT"),
+            ("teams", "TEXT"),
+            ("employeeType", "TEXT"),
+            ("roleName", "TEXT"),
+            ("indicator", "TEXT"),
+            ("location", "TEXT"),
+            ("floorDoorNo", "TEXT"),
+            ("previousName", "TEXT"),
+        ],
+    )
+}
+
+/// 0005: meetingNotesのorganizationIdをNULL可能にし、companyIdカラムを追加
+fn meeting_notes_nullable_organization_id(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "meetingNotes")? {
+        return Ok(());
+    }
+
+    if column_is_not_null(conn, "meetingNotes", "organizationId")? {
+        rebuild_table(
+            conn,
+            "meetingNotes",
+            "CREATE TABLE meetingNotes (
+                id TEXT PRIMARY KEY,
+                organizationId TEXT,
+                companyId TEXT,
+                title TEXT NOT NULL,
+                description TEXT,
+                content TEXT,
+                chromaSynced INTEGER DEFAULT 0,
+                chromaSyncError TEXT,
+                lastChromaSyncAttempt TEXT,
+                createdAt TEXT,
+                updatedAt TEXT,
+                FOREIGN KEY (organizationId) REFERENCES organizations(id),
+                CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR
+                       (organizationId IS NULL AND companyId IS NOT NULL))
+            )",
+        )?;
+    }
+
+    add_missing_columns(conn, "meetingNotes", &[("companyId", "TEXT")])
+}
+
+/// 0006: startupsテーブルに評価・管理用カラムを追加
+fn startups_add_columns(conn: &Connection) -> SqlResult<()> {
+    add_missing_columns(
+        conn,
+        "startups",
+        &[
+            ("evaluationChart", "TEXT"),
+            ("evaluationChartSnapshots", "TEXT"),
+            ("competitorComparison", "TEXT"),
+            ("deepSearch", "TEXT"),
+            ("assignee", "TEXT"),
+            ("method", "TEXT"),
+            ("methodOther", "TEXT"),
+            ("methodDetails", "TEXT"),
+            ("means", "TEXT"),
+            ("meansOther", "TEXT"),
+            ("objective", "TEXT"),
+            ("evaluation", "TEXT"),
+            ("considerationPeriod", "TEXT"),
+            ("executionPeriod", "TEXT"),
+            ("monetizationPeriod", "TEXT"),
+            ("relatedOrganizations", "TEXT"),
+            ("relatedGroupCompanies", "TEXT"),
+            ("monetizationDiagram", "TEXT"),
+            ("monetizationDiagramId", "TEXT"),
+            ("relationDiagram", "TEXT"),
+            ("relationDiagramId", "TEXT"),
+            ("causeEffectDiagramId", "TEXT"),
+            ("themeId", "TEXT"),
+            ("themeIds", "TEXT"),
+            ("topicIds", "TEXT"),
+            ("categoryIds", "TEXT"),
+            ("relatedVCS", "TEXT"),
+            ("responsibleDepartments", "TEXT"),
+            ("status", "TEXT"),
+            ("agencyContractMonth", "TEXT"),
+            ("engagementLevel", "TEXT"),
+            ("bizDevPhase", "TEXT"),
+            ("hpUrl", "TEXT"),
+            ("asanaUrl", "TEXT"),
+            ("boxUrl", "TEXT"),
+            ("monetizationRenewalNotRequired", "INTEGER"),
+        ],
+    )
+}
+
+/// 0007: focusInitiativesのorganizationIdをNULL可能にし、companyId/themeIds/topicIdsカラムを追加
+fn focus_initiatives_nullable_organization_id(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "focusInitiatives")? {
+        return Ok(());
+    }
+
+    if column_is_not_null(conn, "focusInitiatives", "organizationId")? {
+        rebuild_table(
+            conn,
+            "focusInitiatives",
+            "CREATE TABLE focusInitiatives (
+                id TEXT PRIMARY KEY,
+                organizationId TEXT,
+                companyId TEXT,
+                title TEXT NOT NULL,
+                description TEXT,
+                content TEXT,
+                themeIds TEXT,
+                topicIds TEXT,
+                createdAt TEXT,
+                updatedAt TEXT,
+                FOREIGN KEY (organizationId) REFERENCES organizations(id),
+                CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR
+                       (organizationId IS NULL AND companyId IS NOT NULL))
+            )",
+        )?;
+    }
+
+    add_missing_columns(
+        conn,
+        "focusInitiatives",
+        &[("companyId", "TEXT"), ("themeIds", "TEXT"), ("topicIds", "TEXT")],
+    )
+}
+
+/// 0008: companyContentsテーブルに資本構成カラムを追加
+fn company_contents_add_capital_structure(conn: &Connection) -> SqlResult<()> {
+    add_missing_columns(
+        conn,
+        "companyContents",
+        &[("capitalStructure", "TEXT"), ("capitalStructureDiagram", "TEXT")],
+    )
+}
+
+/// 0009: themesテーブルにpositionカラムを追加し、作成日時順に連番を割り当てる
+fn themes_add_position(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "themes")? {
+        return Ok(());
+    }
+
+    if !table_columns(conn, "themes")?.iter().any(|c| c == "position") {
+        conn.execute("ALTER TABLE themes ADD COLUMN position INTEGER", [])?;
+        conn.execute(
+            "UPDATE themes SET position = (
+                SELECT COUNT(*) + 1 FROM themes t2
+                WHERE (t2.createdAt < themes.createdAt)
+                OR (t2.createdAt = themes.createdAt AND t2.title < themes.title)
+                OR (t2.createdAt = themes.createdAt AND t2.title = themes.title AND t2.id < themes.id)
+            )",
+            [],
+        )?;
+    }
+
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_themes_position ON themes(position)", [])?;
+    Ok(())
+}
+
+/// 0010: categoriesテーブルにpositionとparentCategoryIdカラムを追加
+fn categories_add_position_and_parent(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "categories")? {
+        return Ok(());
+    }
+
+    if !table_columns(conn, "categories")?.iter().any(|c| c == "position") {
+        conn.execute("ALTER TABLE categories ADD COLUMN position INTEGER", [])?;
+        conn.execute(
+            "UPDATE categories SET position = (
+                SELECT COUNT(*) + 1 FROM categories c2
+                WHERE (c2.createdAt < categories.createdAt)
+                OR (c2.createdAt = categories.createdAt AND c2.title < categories.title)
+                OR (c2.createdAt = categories.createdAt AND c2.title = categories.title AND c2.id < categories.id)
+            )",
+            [],
+        )?;
+    }
+
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_categories_position ON categories(position)", [])?;
+    add_missing_columns(conn, "categories", &[("parentCategoryId", "TEXT")])
+}
+
+/// 0011: entitiesテーブルにcompanyIdカラムとCHECK制約、RAG検索用カラムを追加
+fn entities_add_company_id_and_search_columns(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "entities")? {
+        return Ok(());
+    }
+
+    if !table_columns(conn, "entities")?.iter().any(|c| c == "companyId") {
+        rebuild_table(
+            conn,
+            "entities",
+            "CREATE TABLE entities (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                type TEXT NOT NULL,
+                aliases TEXT,
+                metadata TEXT,
+                organizationId TEXT,
+                companyId TEXT,
+                searchableText TEXT,
+                displayName TEXT,
+                chromaSynced INTEGER DEFAULT 0,
+                chromaSyncError TEXT,
+                lastChromaSyncAttempt TEXT,
+                lastSearchDate TEXT,
+                searchCount INTEGER DEFAULT 0,
+                createdAt TEXT NOT NULL,
+                updatedAt TEXT NOT NULL,
+                FOREIGN KEY (organizationId) REFERENCES organizations(id),
+                CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR
+                       (organizationId IS NULL AND companyId IS NOT NULL))
+            )",
+        )?;
+    }
+
+    add_missing_columns(
+        conn,
+        "entities",
+        &[
+            ("searchableText", "TEXT"),
+            ("displayName", "TEXT"),
+            ("lastSearchDate", "TEXT"),
+            ("searchCount", "INTEGER DEFAULT 0"),
+        ],
+    )
+}
+
+/// 0012: relationsテーブルにcompanyId/yamlFileIdカラムとCHECK制約を追加し、topicIdをNULL可能にする
+fn relations_add_company_id_and_nullable_topic_id(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "relations")? {
+        return Ok(());
+    }
+
+    let has_company_id = table_columns(conn, "relations")?.iter().any(|c| c == "companyId");
+    if !has_company_id || column_is_not_null(conn, "relations", "topicId")? {
+        rebuild_table(
+            conn,
+            "relations",
+            "CREATE TABLE relations (
+                id TEXT PRIMARY KEY,
+                topicId TEXT,
+                yamlFileId TEXT,
+                sourceEntityId TEXT,
+                targetEntityId TEXT,
+                relationType TEXT NOT NULL,
+                description TEXT,
+                confidence REAL,
+                metadata TEXT,
+                organizationId TEXT,
+                companyId TEXT,
+                searchableText TEXT,
+                chromaSynced INTEGER DEFAULT 0,
+                chromaSyncError TEXT,
+                lastChromaSyncAttempt TEXT,
+                lastSearchDate TEXT,
+                searchCount INTEGER DEFAULT 0,
+                createdAt TEXT NOT NULL,
+                updatedAt TEXT NOT NULL,
+                FOREIGN KEY (sourceEntityId) REFERENCES entities(id),
+                FOREIGN KEY (targetEntityId) REFERENCES entities(id),
+                FOREIGN KEY (organizationId) REFERENCES organizations(id),
+                FOREIGN KEY (yamlFileId) REFERENCES graphvizYamlFiles(id) ON DELETE CASCADE,
+                CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR
+                       (organizationId IS NULL AND companyId IS NOT NULL)),
+                CHECK ((topicId IS NOT NULL AND yamlFileId IS NULL) OR
+                       (topicId IS NULL AND yamlFileId IS NOT NULL) OR
+                       (topicId IS NULL AND yamlFileId IS NULL))
+            )",
+        )?;
+    }
+
+    add_missing_columns(
+        conn,
+        "relations",
+        &[
+            ("yamlFileId", "TEXT"),
+            ("searchableText", "TEXT"),
+            ("lastSearchDate", "TEXT"),
+            ("searchCount", "INTEGER DEFAULT 0"),
+        ],
+    )
+}
+
+/// 0013: topicsテーブルにcompanyIdカラムとCHECK制約、検索・ファイル管理用カラムを追加
+fn topics_add_company_id_and_search_columns(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "topics")? {
+        return Ok(());
+    }
+
+    if !table_columns(conn, "topics")?.iter().any(|c| c == "companyId") {
+        rebuild_table(
+            conn,
+            "topics",
+            "CREATE TABLE topics (
+                id TEXT PRIMARY KEY,
+                topicId TEXT NOT NULL,
+                meetingNoteId TEXT NOT NULL,
+                organizationId TEXT,
+                companyId TEXT,
+                title TEXT NOT NULL,
+                description TEXT,
+                content TEXT,
+                semanticCategory TEXT,
+                keywords TEXT,
+                tags TEXT,
+                contentSummary TEXT,
+                searchableText TEXT,
+                imagePaths TEXT,
+                chromaSynced INTEGER DEFAULT 0,
+                chromaSyncError TEXT,
+                lastChromaSyncAttempt TEXT,
+                lastSearchDate TEXT,
+                searchCount INTEGER DEFAULT 0,
+                topicDate TEXT,
+                parentTopicId TEXT,
+                createdAt TEXT NOT NULL,
+                updatedAt TEXT NOT NULL,
+                FOREIGN KEY (meetingNoteId) REFERENCES meetingNotes(id),
+                FOREIGN KEY (organizationId) REFERENCES organizations(id),
+                CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR
+                       (organizationId IS NULL AND companyId IS NOT NULL))
+            )",
+        )?;
+    }
+
+    add_missing_columns(
+        conn,
+        "topics",
+        &[
+            ("contentSummary", "TEXT"),
+            ("searchableText", "TEXT"),
+            ("imagePaths", "TEXT"),
+            ("lastSearchDate", "TEXT"),
+            ("searchCount", "INTEGER DEFAULT 0"),
+            ("topicDate", "TEXT"),
+            ("parentTopicId", "TEXT"),
+        ],
+    )
+}
+
+/// 0014: tasks/agentsテーブルにモデル選択カラムを追加
+fn tasks_and_agents_add_model_columns(conn: &Connection) -> SqlResult<()> {
+    add_missing_columns(conn, "tasks", &[("modelType", "TEXT"), ("selectedModel", "TEXT")])?;
+    add_missing_columns(conn, "agents", &[("selectedModel", "TEXT")])
+}
+
+/// 0015: 全文検索用のFTS5仮想テーブルと同期トリガーを作成し、既存データを索引
+/// （リリース時点の定義を固定するため、fulltext_searchの索引定義は参照しない）
+fn create_full_text_search_index(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS fullTextSearch USING fts5(
+            sourceType UNINDEXED,
+            sourceId UNINDEXED,
+            organizationId UNINDEXED,
+            companyId UNINDEXED,
+            title,
+            body,
+            tokenize = 'trigram'
+        );
+
+        CREATE TRIGGER IF NOT EXISTS fts_topics_insert
+        AFTER INSERT ON topics
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'topic' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'topic', id, organizationId, companyId, title,
+                       COALESCE(searchableText, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(content, '')
+                FROM topics WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_topics_update
+        AFTER UPDATE ON topics
+        WHEN OLD.title IS NOT NEW.title OR OLD.searchableText IS NOT NEW.searchableText
+            OR OLD.keywords IS NOT NEW.keywords OR OLD.content IS NOT NEW.content
+            OR OLD.organizationId IS NOT NEW.organizationId OR OLD.companyId IS NOT NEW.companyId
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'topic' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'topic', id, organizationId, companyId, title,
+                       COALESCE(searchableText, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(content, '')
+                FROM topics WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_topics_delete
+        AFTER DELETE ON topics
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'topic' AND sourceId = OLD.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_meetingNotes_insert
+        AFTER INSERT ON meetingNotes
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'meetingNote' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'meetingNote', id, organizationId, companyId, title,
+                       COALESCE(description, '') || ' ' || COALESCE(content, '')
+                FROM meetingNotes WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_meetingNotes_update
+        AFTER UPDATE ON meetingNotes
+        WHEN OLD.title IS NOT NEW.title OR OLD.description IS NOT NEW.description
+            OR OLD.content IS NOT NEW.content OR OLD.organizationId IS NOT NEW.organizationId
+            OR OLD.companyId IS NOT NEW.companyId
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'meetingNote' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'meetingNote', id, organizationId, companyId, title,
+                       COALESCE(description, '') || ' ' || COALESCE(content, '')
+                FROM meetingNotes WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_meetingNotes_delete
+        AFTER DELETE ON meetingNotes
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'meetingNote' AND sourceId = OLD.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_entities_insert
+        AFTER INSERT ON entities
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'entity' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'entity', id, organizationId, companyId, COALESCE(displayName, name),
+                       COALESCE(searchableText, '') || ' ' || type
+                FROM entities WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_entities_update
+        AFTER UPDATE ON entities
+        WHEN OLD.name IS NOT NEW.name OR OLD.displayName IS NOT NEW.displayName OR OLD.type IS NOT NEW.type
+            OR OLD.searchableText IS NOT NEW.searchableText OR OLD.organizationId IS NOT NEW.organizationId
+            OR OLD.companyId IS NOT NEW.companyId
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'entity' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'entity', id, organizationId, companyId, COALESCE(displayName, name),
+                       COALESCE(searchableText, '') || ' ' || type
+                FROM entities WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_entities_delete
+        AFTER DELETE ON entities
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'entity' AND sourceId = OLD.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_relations_insert
+        AFTER INSERT ON relations
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'relation' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'relation', id, organizationId, companyId, relationType,
+                       COALESCE(searchableText, '')
+                FROM relations WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_relations_update
+        AFTER UPDATE ON relations
+        WHEN OLD.relationType IS NOT NEW.relationType OR OLD.searchableText IS NOT NEW.searchableText
+            OR OLD.organizationId IS NOT NEW.organizationId OR OLD.companyId IS NOT NEW.companyId
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'relation' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'relation', id, organizationId, companyId, relationType,
+                       COALESCE(searchableText, '')
+                FROM relations WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_relations_delete
+        AFTER DELETE ON relations
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'relation' AND sourceId = OLD.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_graphvizYamlFiles_insert
+        AFTER INSERT ON graphvizYamlFiles
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'graphvizYamlFile' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'graphvizYamlFile', id, organizationId, NULL, name,
+                       COALESCE(searchableText, '') || ' ' || COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(contentSummary, '')
+                FROM graphvizYamlFiles WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_graphvizYamlFiles_update
+        AFTER UPDATE ON graphvizYamlFiles
+        WHEN OLD.name IS NOT NEW.name OR OLD.searchableText IS NOT NEW.searchableText
+            OR OLD.description IS NOT NEW.description OR OLD.keywords IS NOT NEW.keywords
+            OR OLD.contentSummary IS NOT NEW.contentSummary OR OLD.organizationId IS NOT NEW.organizationId
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'graphvizYamlFile' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'graphvizYamlFile', id, organizationId, NULL, name,
+                       COALESCE(searchableText, '') || ' ' || COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(contentSummary, '')
+                FROM graphvizYamlFiles WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_graphvizYamlFiles_delete
+        AFTER DELETE ON graphvizYamlFiles
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'graphvizYamlFile' AND sourceId = OLD.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_designDocSections_insert
+        AFTER INSERT ON designDocSections
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'designDoc' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'designDoc', id, NULL, NULL, title,
+                       COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(summary, '') || ' ' || COALESCE(content, '')
+                FROM designDocSections WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_designDocSections_update
+        AFTER UPDATE ON designDocSections
+        WHEN OLD.title IS NOT NEW.title OR OLD.description IS NOT NEW.description
+            OR OLD.keywords IS NOT NEW.keywords OR OLD.summary IS NOT NEW.summary OR OLD.content IS NOT NEW.content
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'designDoc' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'designDoc', id, NULL, NULL, title,
+                       COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(summary, '') || ' ' || COALESCE(content, '')
+                FROM designDocSections WHERE id = NEW.id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fts_designDocSections_delete
+        AFTER DELETE ON designDocSections
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'designDoc' AND sourceId = OLD.id;
+        END;
+
+        DELETE FROM fullTextSearch;
+        INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+            SELECT 'topic', id, organizationId, companyId, title,
+                   COALESCE(searchableText, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(content, '')
+            FROM topics;
+        INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+            SELECT 'meetingNote', id, organizationId, companyId, title,
+                   COALESCE(description, '') || ' ' || COALESCE(content, '')
+            FROM meetingNotes;
+        INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+            SELECT 'entity', id, organizationId, companyId, COALESCE(displayName, name),
+                   COALESCE(searchableText, '') || ' ' || type
+            FROM entities;
+        INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+            SELECT 'relation', id, organizationId, companyId, relationType,
+                   COALESCE(searchableText, '')
+            FROM relations;
+        INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+            SELECT 'graphvizYamlFile', id, organizationId, NULL, name,
+                   COALESCE(searchableText, '') || ' ' || COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(contentSummary, '')
+            FROM graphvizYamlFiles;
+        INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+            SELECT 'designDoc', id, NULL, NULL, title,
+                   COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(summary, '') || ' ' || COALESCE(content, '')
+            FROM designDocSections;
+        INSERT INTO fullTextSearch(fullTextSearch) VALUES('optimize');",
+    )
+}
+
+/// 0016: 書き込みジョブのアウトボックスとデッドレターのテーブルを作成
+fn create_write_outbox_tables(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS writeOutbox (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            jobType TEXT NOT NULL,
+            jobKey TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            nextAttemptAt INTEGER NOT NULL,
+            lastError TEXT,
+            createdAt TEXT NOT NULL,
+            updatedAt TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_writeOutbox_jobKey ON writeOutbox(jobKey, id);
+        CREATE INDEX IF NOT EXISTS idx_writeOutbox_nextAttemptAt ON writeOutbox(nextAttemptAt);
+        CREATE TABLE IF NOT EXISTS writeDeadLetters (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            outboxId INTEGER,
+            jobType TEXT NOT NULL,
+            jobKey TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            attempts INTEGER NOT NULL,
+            lastError TEXT,
+            createdAt TEXT NOT NULL,
+            failedAt TEXT NOT NULL
+        );",
+    )
+}
+
+/// 0017: エンティティ統合の監査ログテーブルを作成
+fn create_entity_merge_audit_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS entityMergeAudit (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            survivorEntityId TEXT NOT NULL,
+            mergedEntityId TEXT NOT NULL,
+            mergedEntity TEXT NOT NULL,
+            rewiredRelationIds TEXT NOT NULL,
+            removedRelationIds TEXT NOT NULL,
+            reason TEXT,
+            createdAt TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_entityMergeAudit_survivorEntityId ON entityMergeAudit(survivorEntityId);
+        CREATE INDEX IF NOT EXISTS idx_entityMergeAudit_mergedEntityId ON entityMergeAudit(mergedEntityId);",
+    )
+}
+
+/// 0018: YAMLファイルの履歴行（parentYamlFileId）を引くためのインデックスを作成
+fn graphviz_yaml_files_add_version_index(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "graphvizYamlFiles")? {
+        return Ok(());
+    }
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_graphvizYamlFiles_parentYamlFileId ON graphvizYamlFiles(parentYamlFileId, version)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 0019: 設計ドキュメントセクションのリビジョン（変更履歴）テーブルを作成
+fn create_design_doc_section_revisions_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS designDocSectionRevisions (
+            id TEXT PRIMARY KEY,
+            sectionId TEXT NOT NULL,
+            revision INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            content TEXT NOT NULL,
+            baseRevisionId TEXT,
+            author TEXT,
+            authorId TEXT,
+            message TEXT,
+            createdAt TEXT NOT NULL,
+            UNIQUE (sectionId, revision),
+            FOREIGN KEY (sectionId) REFERENCES designDocSections(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_designDocSectionRevisions_sectionId ON designDocSectionRevisions(sectionId, revision);",
+    )
+}
+
+/// 0020: タスクチェーンの実行（ラン）テーブルを作成し、タスク実行にチェーンの実行・ノードへの参照を追加
+fn create_task_chain_runs_table(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS taskChainRuns (
+            id TEXT PRIMARY KEY,
+            chainId TEXT NOT NULL,
+            status TEXT NOT NULL,
+            input TEXT,
+            variables TEXT,
+            executionPath TEXT NOT NULL DEFAULT '[]',
+            error TEXT,
+            startedAt INTEGER NOT NULL,
+            completedAt INTEGER,
+            FOREIGN KEY (chainId) REFERENCES taskChains(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_taskChainRuns_chainId ON taskChainRuns(chainId, startedAt);",
+    )?;
+    add_missing_columns(conn, "taskExecutions", &[("chainRunId", "TEXT"), ("nodeId", "TEXT")])?;
+    if table_exists(conn, "taskExecutions")? {
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_taskExecutions_chainRunId ON taskExecutions(chainRunId)",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// 0021: A2Aメッセージに既読日時・応答の状態と期限を追加
+fn a2a_messages_add_delivery_columns(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "a2aMessages")? {
+        return Ok(());
+    }
+    add_missing_columns(
+        conn,
+        "a2aMessages",
+        &[("readAt", "INTEGER"), ("responseStatus", "TEXT"), ("responseDeadline", "INTEGER")],
+    )?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_a2aMessages_responseTo ON a2aMessages(responseTo);
+        CREATE INDEX IF NOT EXISTS idx_a2aMessages_responseStatus ON a2aMessages(responseStatus, responseDeadline);",
+    )
+}
+
+/// 0022: プロンプトのバージョン固定・A/B評価用のカラムと評価テーブルを追加し、各Agentの現在のプロンプトを最新バージョンとして記録
+fn agent_prompt_versioning(conn: &Connection) -> SqlResult<()> {
+    add_missing_columns(conn, "agent_prompt_versions", &[("rolledBackFrom", "INTEGER")])?;
+    add_missing_columns(conn, "tasks", &[("promptVersion", "INTEGER")])?;
+    add_missing_columns(conn, "taskExecutions", &[("promptVersion", "INTEGER"), ("evaluationId", "TEXT")])?;
+    if table_exists(conn, "taskExecutions")? {
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_taskExecutions_evaluationId ON taskExecutions(evaluationId)",
+            [],
+        )?;
+    }
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS promptEvaluations (
+            id TEXT PRIMARY KEY,
+            agentId TEXT NOT NULL,
+            versionA INTEGER NOT NULL,
+            versionB INTEGER NOT NULL,
+            taskIds TEXT NOT NULL,
+            createdAt INTEGER NOT NULL,
+            FOREIGN KEY (agentId) REFERENCES agents(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_promptEvaluations_agentId ON promptEvaluations(agentId, createdAt);",
+    )?;
+
+    // これまでは変更前のプロンプトのみを記録していたため、現在のプロンプトが最新バージョンになるようにする
+    if !table_exists(conn, "agents")? || !table_exists(conn, "agent_prompt_versions")? {
+        return Ok(());
+    }
+    // 最新バージョンとプロンプトが異なる（またはバージョンが無い）Agentに、次のバージョンを追加する
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    conn.execute(
+        "INSERT INTO agent_prompt_versions (id, agentId, version, systemPrompt, rolledBackFrom, createdAt, updatedAt)
+         SELECT a.id || '-v' || (COALESCE(latest.version, 0) + 1), a.id, COALESCE(latest.version, 0) + 1, a.systemPrompt, NULL, ?1, ?1
+         FROM agents a
+         LEFT JOIN agent_prompt_versions latest
+             ON latest.agentId = a.id
+            AND latest.version = (SELECT MAX(version) FROM agent_prompt_versions WHERE agentId = a.id)
+         WHERE latest.systemPrompt IS NOT a.systemPrompt",
+        params![now],
+    )?;
+    Ok(())
+}
+
+/// 0023: Graphviz YAMLファイルの履歴行を全文検索の索引から除外（同期トリガーを作り直し、索引済みの履歴行を削除）
+fn full_text_search_exclude_yaml_history(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "fullTextSearch")? || !table_exists(conn, "graphvizYamlFiles")? {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "DROP TRIGGER IF EXISTS fts_graphvizYamlFiles_insert;
+        DROP TRIGGER IF EXISTS fts_graphvizYamlFiles_update;
+
+        CREATE TRIGGER fts_graphvizYamlFiles_insert
+        AFTER INSERT ON graphvizYamlFiles
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'graphvizYamlFile' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'graphvizYamlFile', id, organizationId, NULL, name,
+                       COALESCE(searchableText, '') || ' ' || COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(contentSummary, '')
+                FROM graphvizYamlFiles WHERE id = NEW.id AND parentYamlFileId IS NULL;
+        END;
+
+        CREATE TRIGGER fts_graphvizYamlFiles_update
+        AFTER UPDATE ON graphvizYamlFiles
+        WHEN OLD.name IS NOT NEW.name OR OLD.searchableText IS NOT NEW.searchableText
+            OR OLD.description IS NOT NEW.description OR OLD.keywords IS NOT NEW.keywords
+            OR OLD.contentSummary IS NOT NEW.contentSummary OR OLD.organizationId IS NOT NEW.organizationId
+        BEGIN
+            DELETE FROM fullTextSearch WHERE sourceType = 'graphvizYamlFile' AND sourceId = NEW.id;
+            INSERT INTO fullTextSearch (sourceType, sourceId, organizationId, companyId, title, body)
+                SELECT 'graphvizYamlFile', id, organizationId, NULL, name,
+                       COALESCE(searchableText, '') || ' ' || COALESCE(description, '') || ' ' || COALESCE(keywords, '') || ' ' || COALESCE(contentSummary, '')
+                FROM graphvizYamlFiles WHERE id = NEW.id AND parentYamlFileId IS NULL;
+        END;
+
+        DELETE FROM fullTextSearch
+        WHERE sourceType = 'graphvizYamlFile'
+          AND sourceId IN (SELECT id FROM graphvizYamlFiles WHERE parentYamlFileId IS NOT NULL);",
+    )
+}
+
+/// 0024: 書き込みジョブの順序キー（レコード単位）と、キーごとの処理済みアウトボックスIDのテーブルを作成
+/// 既存のジョブは保存時のjobKey（データの書き込みはすべて"data"）を排他キーとして引き継ぐ
+fn create_write_outbox_keys_tables(conn: &Connection) -> SqlResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS writeOutboxKeys (
+            outboxId INTEGER NOT NULL,
+            jobKey TEXT NOT NULL,
+            exclusive INTEGER NOT NULL,
+            PRIMARY KEY (outboxId, jobKey)
+        );
+        CREATE INDEX IF NOT EXISTS idx_writeOutboxKeys_jobKey ON writeOutboxKeys(jobKey, outboxId);
+        CREATE TABLE IF NOT EXISTS writeKeyWatermarks (
+            jobKey TEXT PRIMARY KEY,
+            lastOutboxId INTEGER NOT NULL
+        );",
+    )?;
+    if table_exists(conn, "writeOutbox")? {
+        conn.execute(
+            "INSERT OR IGNORE INTO writeOutboxKeys (outboxId, jobKey, exclusive) SELECT id, jobKey, 1 FROM writeOutbox",
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// 0025: タスクチェーン専用のタスク（インラインのタスク・制御用タスク）を通常のタスクと区別するchainIdを追加
+/// これまでに`<チェーンID>_<ノードID>` / `<チェーンID>_coordinator`として保存されたタスクにもchainIdを設定する
+fn tasks_add_chain_id(conn: &Connection) -> SqlResult<()> {
+    if !table_exists(conn, "tasks")? {
+        return Ok(());
+    }
+    add_missing_columns(conn, "tasks", &[("chainId", "TEXT")])?;
+    if table_exists(conn, "taskChains")? {
+        conn.execute(
+            "UPDATE tasks SET chainId = (
+                SELECT c.id FROM taskChains c WHERE substr(tasks.id, 1, length(c.id) + 1) = c.id || '_'
+                ORDER BY length(c.id) DESC LIMIT 1
+            )
+            WHERE chainId IS NULL
+              AND EXISTS (SELECT 1 FROM taskChains c WHERE substr(tasks.id, 1, length(c.id) + 1) = c.id || '_')",
+            [],
+        )?;
+    }
+    Ok(())
+}
@@ -1,10 +1,19 @@
 mod auth;
 mod store;
+mod collection_query;
+mod fulltext_search;
+mod hybrid_search;
+mod knowledge_graph;
+mod entity_resolution;
+mod graph_export;
 mod ai_settings;
+mod embedding;
+mod embedding_registry;
+mod chroma_sync;
 mod backup;
 mod export;
 mod organization;
-mod vector_search;
+pub mod vector_search;
 mod design_doc;
 mod themes;
 pub mod chromadb;
@@ -37,7 +46,27 @@ macro_rules! init_log_always {
 }
 
 pub use auth::{sign_up, sign_in, sign_out};
-pub use store::{get_doc, set_doc, update_doc, delete_doc, add_doc, get_collection, delete_meeting_note_with_relations, update_meeting_note_item_content};
+pub use store::{get_doc, set_doc, update_doc, delete_doc, add_doc, get_collection, query_collection, delete_meeting_note_with_relations, update_meeting_note_item_content};
+pub use collection_query::CollectionQuery;
+pub use fulltext_search::{search_full_text, rebuild_full_text_index, FullTextSearchOptions, FullTextSearchHit};
+pub use hybrid_search::{hybrid_search, HybridSearchOptions, HybridSearchResult};
+pub use knowledge_graph::{
+    graph_neighborhood, graph_shortest_path, graph_connected_components, graph_centrality,
+    GraphResult, GraphNeighborhoodOptions, GraphShortestPathOptions, GraphComponentsOptions, GraphCentralityOptions,
+};
+pub use entity_resolution::{
+    find_duplicate_entities, merge_entities, list_entity_merge_audit,
+    DuplicateEntityOptions, DuplicateEntityCandidate, MergeEntitiesOptions, EntityMergeReport, EntityMergeAuditEntry,
+};
+pub use graph_export::{export_knowledge_graph, export_knowledge_graph_to_file, GraphExportOptions, GraphExportSummary};
+pub use embedding::{EmbeddingService, TextEmbedder, resolve_embedding};
+pub use embedding_registry::{
+    embedding_service_for, list_embedding_collections, reembed_collection, EmbeddingCollectionInfo, ReembedReport,
+};
+pub use chroma_sync::{
+    run_chroma_sync, cancel_chroma_sync, is_chroma_sync_running, get_chroma_sync_pending_counts,
+    ChromaSyncOptions, ChromaSyncProgress, ChromaSyncPendingCount,
+};
 pub use export::{
     export_to_file, import_from_file, import_template_data_if_empty,
     export_organizations_and_members_to_file,
@@ -56,6 +85,16 @@ pub use design_doc::{
     get_design_doc_section_relation_by_id, get_design_doc_section_relations_by_section_id,
     get_all_design_doc_section_relations, delete_design_doc_section_relation,
 };
+mod design_doc_revision;
+pub use design_doc_revision::{
+    list_design_doc_section_revisions, get_design_doc_section_revision,
+    diff_design_doc_section_revisions, restore_design_doc_section_revision, merge_design_doc_section,
+    RevisionContext, RevisionDiffMode,
+};
+mod design_doc_consistency;
+pub use design_doc_consistency::check_design_doc_consistency;
+mod design_doc_site;
+pub use design_doc_site::{export_design_doc_site, DesignDocSiteOptions, DesignDocSiteSummary};
 pub use themes::{
     get_all_themes, get_theme_by_id, save_theme, create_theme, delete_theme,
     update_theme_positions,
@@ -65,15 +104,51 @@ mod agent_system;
 pub use agent_system::{
     save_task, get_task, get_all_tasks, delete_task,
     save_task_execution, get_task_execution, get_task_executions, get_all_task_executions,
+    get_task_executions_by_chain_run,
     save_task_chain, get_task_chain, get_all_task_chains, delete_task_chain,
     save_agent, get_agent, get_all_agents, delete_agent,
     Task, TaskExecution, TaskChain, Agent,
 };
+mod llm_client;
+mod agent_executor;
+pub use agent_executor::{submit_task, cancel_task_execution, AgentTaskProgress, AgentTaskProgressCallback};
+mod task_dag;
+pub use task_dag::{prepare_task_workflow, run_task_workflow, TaskWorkflowOptions};
+mod task_chain;
+pub use task_chain::{
+    start_task_chain_run, run_task_chain, cancel_task_chain_run, get_task_chain_run, list_task_chain_runs,
+    TaskChainRun,
+};
+mod agent_prompt;
+pub use agent_prompt::{
+    list_agent_prompt_versions, get_agent_prompt_version, diff_agent_prompt_versions, rollback_agent_prompt,
+    pin_task_prompt_version, start_prompt_evaluation, run_prompt_evaluation, get_prompt_evaluation,
+    list_prompt_evaluations, AgentPromptVersion, AgentPromptDiff, PromptEvaluation,
+};
+mod a2a_bus;
+pub use a2a_bus::{
+    send_a2a_message, get_a2a_message, wait_for_a2a_response, get_a2a_inbox, mark_a2a_messages_read,
+    get_a2a_task_threads, A2AMessage, A2AMessageDraft, A2AInboxOptions, A2AMessageThread, A2ABusEvent, A2ABusCallback,
+};
 mod graphviz;
 pub use graphviz::{
     create_graphviz_yaml_file, update_graphviz_yaml_file, get_graphviz_yaml_file_by_id,
     get_all_graphviz_yaml_files, delete_graphviz_yaml_file,
     create_graphviz_dot_file, get_graphviz_dot_file_by_yaml_file_id,
+    list_graphviz_yaml_versions, diff_graphviz_yaml_versions, rollback_graphviz_yaml_file,
+};
+mod yaml_schema;
+pub use yaml_schema::validate_yaml;
+mod text_diff;
+mod graphviz_generator;
+pub use graphviz_generator::{
+    generate_graphviz_dot, generate_and_save_graphviz_dot,
+    DotGenerationOptions, DotSaveOptions,
+};
+mod graphviz_render;
+pub use graphviz_render::{
+    render_graphviz_dot,
+    GraphvizRenderFormat, GraphvizRenderEngine,
 };
 mod mcp_tools;
 pub use mcp_tools::{
@@ -81,6 +156,8 @@ pub use mcp_tools::{
     update_mcp_tool_enabled,
     MCPTool,
 };
+mod migrations;
+pub use migrations::{MigrationReport, MigrationStatus};
 
 pub struct Database {
     pool: DatabasePool,
@@ -160,6 +237,18 @@ impl Database {
         Ok(())
     }
 
+    /// 未適用のスキーママイグレーションを実行（dry_runの場合は実行後にロールバック）
+    pub fn run_migrations(&self, dry_run: bool) -> SqlResult<MigrationReport> {
+        let conn = self.get_connection()?;
+        migrations::run_migrations(&conn, dry_run)
+    }
+
+    /// スキーマバージョンと適用済み・未適用のマイグレーション一覧を取得
+    pub fn get_migration_status(&self) -> SqlResult<MigrationStatus> {
+        let conn = self.get_connection()?;
+        migrations::get_migration_status(&conn)
+    }
+
     pub fn init_tables(&self) -> SqlResult<()> {
         let conn = self.get_connection()?;
         
@@ -179,23 +268,6 @@ impl Database {
             [],
         )?;
 
-        // usersテーブルにroleカラムを追加（既存テーブル用マイグレーション）
-        let _ = (|| -> rusqlite::Result<()> {
-            let role_exists = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name='role'",
-                [],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-
-            if !role_exists {
-                init_log!("📝 usersテーブルにroleカラムを追加します");
-                conn.execute("ALTER TABLE users ADD COLUMN role TEXT DEFAULT 'user'", [])?;
-                init_log!("✅ roleカラムを追加しました");
-            }
-
-            Ok(())
-        })();
-
         // 承認リクエストテーブル
         conn.execute(
             "CREATE TABLE IF NOT EXISTS approvalRequests (
@@ -253,24 +325,6 @@ impl Database {
             [],
         )?;
 
-        // 既存のorganizationsテーブルにtypeカラムを追加（マイグレーション）
-        let _ = (|| -> rusqlite::Result<()> {
-            // typeカラムの存在確認
-            let type_exists = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('organizations') WHERE name='type'",
-                [],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-
-            if !type_exists {
-                init_log!("📝 organizationsテーブルにtypeカラムを追加します");
-                conn.execute("ALTER TABLE organizations ADD COLUMN type TEXT DEFAULT 'organization'", [])?;
-                init_log!("✅ typeカラムを追加しました");
-            }
-
-            Ok(())
-        })();
-
         // 組織メンバーテーブル（新規追加）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS organizationMembers (
@@ -299,44 +353,6 @@ impl Database {
             [],
         )?;
 
-        // 既存のorganizationMembersテーブルに不足しているカラムを追加（マイグレーション）
-        // SQLiteではALTER TABLE ADD COLUMN IF NOT EXISTSは使えないため、エラーハンドリングで対応
-        let columns_to_add = vec![
-            ("nameRomaji", "TEXT"),
-            ("department", "TEXT"),
-            ("extension", "TEXT"),
-            ("companyPhone", "TEXT"),
-            ("mobilePhone", "TEXT"),
-            ("email", "TEXT"),
-            ("itochuEmail", "TEXT"),
-            ("teams", "TEXT"),
-            ("employeeType", "TEXT"),
-            ("roleName", "TEXT"),
-            ("indicator", "TEXT"),
-            ("location", "TEXT"),
-            ("floorDoorNo", "TEXT"),
-            ("previousName", "TEXT"),
-        ];
-
-        for (column_name, column_type) in columns_to_add {
-            // カラムが存在するかチェック
-            let column_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('organizationMembers') WHERE name = ?1",
-                params![column_name],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-
-            if !column_exists {
-                init_log!("📝 organizationMembersテーブルにカラムを追加: {}", column_name);
-                if let Err(e) = conn.execute(
-                    &format!("ALTER TABLE organizationMembers ADD COLUMN {} {}", column_name, column_type),
-                    [],
-                ) {
-                    init_log!("⚠️  カラム追加エラー（既に存在する可能性があります）: {} - {}", column_name, e);
-                }
-            }
-        }
-
         // 組織コンテンツテーブル（新規追加）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS organizationContents (
@@ -374,34 +390,6 @@ impl Database {
             [],
         )?;
         
-        // focusInitiativesテーブルにthemeIdsとtopicIdsカラムを追加（既存のテーブル用）
-        // SQLiteではALTER TABLE ADD COLUMN IF NOT EXISTSは使えないため、エラーハンドリングで対応
-        let columns_to_add = vec![
-            ("themeIds", "TEXT"),
-            ("topicIds", "TEXT"),
-        ];
-        
-        for (column_name, column_type) in columns_to_add {
-            // カラムが存在するかチェック
-            let column_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('focusInitiatives') WHERE name = ?1",
-                params![column_name],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-            
-            if !column_exists {
-                init_log!("📝 focusInitiativesテーブルにカラムを追加: {}", column_name);
-                if let Err(e) = conn.execute(
-                    &format!("ALTER TABLE focusInitiatives ADD COLUMN {} {}", column_name, column_type),
-                    [],
-                ) {
-                    init_log!("⚠️  カラム追加エラー（既に存在する可能性があります）: {} - {}", column_name, e);
-                }
-            } else {
-                init_log!("ℹ️  focusInitiativesテーブルのカラム '{}' は既に存在します", column_name);
-            }
-        }
-
         // 議事録テーブル（ChromaDB同期状態カラムを含む）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS meetingNotes (
@@ -423,160 +411,6 @@ impl Database {
             [],
         )?;
         
-        // meetingNotesテーブルのマイグレーション（organizationIdをNULL可能に）
-        // CREATE TABLE IF NOT EXISTSの後に実行することで、既存テーブルが古いスキーマの場合にマイグレーションを実行
-        init_log!("🔍 meetingNotesテーブルのマイグレーションを開始します...");
-        let meeting_notes_table_exists: bool = conn.query_row(
-            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='meetingNotes'",
-            [],
-            |row| row.get(0),
-        ).unwrap_or(false);
-        
-        init_log!("📊 meetingNotesテーブルの存在確認: {}", meeting_notes_table_exists);
-        
-        if meeting_notes_table_exists {
-            // organizationIdカラムが存在するかどうかを確認
-            let org_id_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('meetingNotes') WHERE name='organizationId'",
-                [],
-                |row| row.get::<_, i32>(0).map(|n| n > 0),
-            ).unwrap_or(false);
-            
-            init_log!("📊 meetingNotesテーブルのorganizationIdカラムの存在確認: {}", org_id_exists);
-            
-            if org_id_exists {
-                // organizationIdカラムがNOT NULLかどうかを確認
-                let org_id_not_null: bool = conn.query_row(
-                    "SELECT \"notnull\" FROM pragma_table_info('meetingNotes') WHERE name='organizationId'",
-                    [],
-                    |row| row.get::<_, i32>(0).map(|n| n != 0),
-                ).unwrap_or(false);
-                
-                init_log!("📊 meetingNotesテーブルのorganizationIdカラムのNOT NULL確認: {}", org_id_not_null);
-                
-                if org_id_not_null {
-                    init_log_always!("📝 meetingNotesテーブルを再作成します（organizationIdをNULL可能に）");
-                    
-                    // 外部キー制約を一時的に無効化（topicsテーブルがmeetingNotesを参照しているため）
-                    if let Err(e) = conn.execute("PRAGMA foreign_keys = OFF", []) {
-                        init_log_always!("❌ 外部キー制約の無効化に失敗しました: {}", e);
-                    } else {
-                        init_log!("✅ 外部キー制約を無効化しました");
-                        
-                        // 既存データをバックアップテーブルにコピー
-                        if let Err(e) = conn.execute("CREATE TABLE IF NOT EXISTS meetingNotes_backup AS SELECT * FROM meetingNotes", []) {
-                            init_log_always!("❌ バックアップテーブルの作成に失敗しました: {}", e);
-                        } else {
-                            init_log!("✅ バックアップテーブルを作成しました");
-                            
-                            // 古いテーブルを削除
-                            if let Err(e) = conn.execute("DROP TABLE meetingNotes", []) {
-                                init_log_always!("❌ 古いテーブルの削除に失敗しました: {}", e);
-                            } else {
-                                init_log!("✅ 古いテーブルを削除しました");
-                                
-                                // 新しいテーブルを作成（organizationIdをNULL可能に）
-                                if let Err(e) = conn.execute(
-                                    "CREATE TABLE meetingNotes (
-                                        id TEXT PRIMARY KEY,
-                                        organizationId TEXT,
-                                        companyId TEXT,
-                                        title TEXT NOT NULL,
-                                        description TEXT,
-                                        content TEXT,
-                                        chromaSynced INTEGER DEFAULT 0,
-                                        chromaSyncError TEXT,
-                                        lastChromaSyncAttempt TEXT,
-                                        createdAt TEXT,
-                                        updatedAt TEXT,
-                                        FOREIGN KEY (organizationId) REFERENCES organizations(id),
-                                        CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR 
-                                               (organizationId IS NULL AND companyId IS NOT NULL))
-                                    )",
-                                    [],
-                                ) {
-                                    init_log_always!("❌ 新しいテーブルの作成に失敗しました: {}", e);
-                                } else {
-                                    init_log!("✅ 新しいテーブルを作成しました");
-                                    
-                                    // バックアップテーブルからデータをコピー
-                                    let backup_has_company_id: bool = conn.query_row(
-                                        "SELECT COUNT(*) FROM pragma_table_info('meetingNotes_backup') WHERE name='companyId'",
-                                        [],
-                                        |row| row.get::<_, i32>(0).map(|n| n > 0),
-                                    ).unwrap_or(false);
-                                    
-                                    init_log!("📊 バックアップテーブルのcompanyIdカラムの存在確認: {}", backup_has_company_id);
-                                    
-                                    if backup_has_company_id {
-                                        if let Err(e) = conn.execute(
-                                            "INSERT INTO meetingNotes (id, organizationId, companyId, title, description, content, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt) 
-                                             SELECT id, organizationId, companyId, title, description, content, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt 
-                                             FROM meetingNotes_backup",
-                                            [],
-                                        ) {
-                                            init_log_always!("❌ データのコピーに失敗しました: {}", e);
-                                        } else {
-                                            init_log!("✅ データをコピーしました（companyIdあり）");
-                                        }
-                                    } else {
-                                        if let Err(e) = conn.execute(
-                                            "INSERT INTO meetingNotes (id, organizationId, companyId, title, description, content, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt) 
-                                             SELECT id, organizationId, NULL, title, description, content, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt 
-                                             FROM meetingNotes_backup",
-                                            [],
-                                        ) {
-                                            init_log_always!("❌ データのコピーに失敗しました: {}", e);
-                                        } else {
-                                            init_log!("✅ データをコピーしました（companyIdなし）");
-                                        }
-                                    }
-                                    
-                                    // バックアップテーブルを削除
-                                    let _ = conn.execute("DROP TABLE meetingNotes_backup", []);
-                                    
-                                    init_log_always!("✅ meetingNotesテーブルの再作成が完了しました");
-                                }
-                            }
-                            
-                            // 外部キー制約を再度有効化
-                            if let Err(e) = conn.execute("PRAGMA foreign_keys = ON", []) {
-                                init_log_always!("❌ 外部キー制約の再有効化に失敗しました: {}", e);
-                            } else {
-                                init_log!("✅ 外部キー制約を再有効化しました");
-                            }
-                        }
-                    }
-                } else {
-                    init_log!("ℹ️  meetingNotesテーブルのorganizationIdは既にNULL可能です");
-                }
-            } else {
-                init_log!("ℹ️  meetingNotesテーブルにorganizationIdカラムが存在しません（新規テーブルの可能性）");
-            }
-        }
-        
-        // meetingNotesテーブルにcompanyIdカラムを追加（既存のテーブル用）
-        let meeting_notes_columns_to_add = vec![("companyId", "TEXT")];
-        for (column_name, column_type) in meeting_notes_columns_to_add {
-            let column_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('meetingNotes') WHERE name = ?1",
-                params![column_name],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-            
-            if !column_exists {
-                init_log!("📝 meetingNotesテーブルにカラムを追加: {}", column_name);
-                if let Err(e) = conn.execute(
-                    &format!("ALTER TABLE meetingNotes ADD COLUMN {} {}", column_name, column_type),
-                    [],
-                ) {
-                    init_log!("⚠️  カラム追加エラー（既に存在する可能性があります）: {} - {}", column_name, e);
-                }
-            } else {
-                init_log!("ℹ️  meetingNotesテーブルのカラム '{}' は既に存在します", column_name);
-            }
-        }
-        
         // 制度テーブル
         conn.execute(
             "CREATE TABLE IF NOT EXISTS regulations (
@@ -609,205 +443,6 @@ impl Database {
         )?;
         init_log!("✅ startupsテーブルを作成しました");
         
-        // startupsテーブルにevaluationChartとevaluationChartSnapshotsカラムを追加（マイグレーション）
-        let startups_columns_to_add = vec![
-            ("evaluationChart", "TEXT"),
-            ("evaluationChartSnapshots", "TEXT"),
-            ("competitorComparison", "TEXT"),
-            ("deepSearch", "TEXT"),
-            ("assignee", "TEXT"),
-            ("method", "TEXT"),
-            ("methodOther", "TEXT"),
-            ("methodDetails", "TEXT"),
-            ("means", "TEXT"),
-            ("meansOther", "TEXT"),
-            ("objective", "TEXT"),
-            ("evaluation", "TEXT"),
-            ("considerationPeriod", "TEXT"),
-            ("executionPeriod", "TEXT"),
-            ("monetizationPeriod", "TEXT"),
-            ("relatedOrganizations", "TEXT"),
-            ("relatedGroupCompanies", "TEXT"),
-            ("monetizationDiagram", "TEXT"),
-            ("monetizationDiagramId", "TEXT"),
-            ("relationDiagram", "TEXT"),
-            ("relationDiagramId", "TEXT"),
-            ("causeEffectDiagramId", "TEXT"),
-            ("themeId", "TEXT"),
-            ("themeIds", "TEXT"),
-            ("topicIds", "TEXT"),
-            ("categoryIds", "TEXT"),
-            ("relatedVCS", "TEXT"),
-            ("responsibleDepartments", "TEXT"),
-            ("status", "TEXT"),
-            ("agencyContractMonth", "TEXT"),
-            ("engagementLevel", "TEXT"),
-            ("bizDevPhase", "TEXT"),
-            ("hpUrl", "TEXT"),
-            ("asanaUrl", "TEXT"),
-            ("boxUrl", "TEXT"),
-            ("monetizationRenewalNotRequired", "INTEGER"),
-        ];
-        for (column_name, column_type) in startups_columns_to_add {
-            let column_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('startups') WHERE name = ?1",
-                params![column_name],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-            
-            if !column_exists {
-                init_log!("📝 startupsテーブルにカラムを追加: {}", column_name);
-                if let Err(e) = conn.execute(
-                    &format!("ALTER TABLE startups ADD COLUMN {} {}", column_name, column_type),
-                    [],
-                ) {
-                    init_log!("⚠️  カラム追加エラー（既に存在する可能性があります）: {} - {}", column_name, e);
-                }
-            } else {
-                init_log!("ℹ️  startupsテーブルのカラム '{}' は既に存在します", column_name);
-            }
-        }
-        
-        // focusInitiativesテーブルにcompanyIdカラムを追加（既存のテーブル用）
-        let focus_initiatives_columns_to_add = vec![("companyId", "TEXT")];
-        for (column_name, column_type) in focus_initiatives_columns_to_add {
-            let column_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('focusInitiatives') WHERE name = ?1",
-                params![column_name],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-            
-            if !column_exists {
-                init_log!("📝 focusInitiativesテーブルにカラムを追加: {}", column_name);
-                if let Err(e) = conn.execute(
-                    &format!("ALTER TABLE focusInitiatives ADD COLUMN {} {}", column_name, column_type),
-                    [],
-                ) {
-                    init_log!("⚠️  カラム追加エラー（既に存在する可能性があります）: {} - {}", column_name, e);
-                }
-            } else {
-                init_log!("ℹ️  focusInitiativesテーブルのカラム '{}' は既に存在します", column_name);
-            }
-        }
-        
-        // focusInitiativesテーブルの外部キー制約を更新（organizationIdをNULL可能に）
-        // SQLiteではALTER TABLEでNOT NULL制約を削除できないため、テーブル再作成が必要
-        // 既存テーブルがorganizationIdをNOT NULLとして持っている場合は、テーブルを再作成
-        // エラーが発生しても初期化を続行できるようにエラーハンドリングを追加
-        if let Err(e) = (|| -> SqlResult<()> {
-            let table_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='focusInitiatives'",
-                [],
-                |row| row.get(0),
-            ).unwrap_or(false);
-            
-            if table_exists {
-                // organizationIdカラムが存在するかどうかを確認
-                let org_id_exists: bool = conn.query_row(
-                    "SELECT COUNT(*) FROM pragma_table_info('focusInitiatives') WHERE name='organizationId'",
-                    [],
-                    |row| row.get::<_, i32>(0).map(|n| n > 0),
-                ).unwrap_or(false);
-                
-                if !org_id_exists {
-                    init_log!("ℹ️  focusInitiativesテーブルにorganizationIdカラムが存在しません（新規テーブルの可能性）");
-                    return Ok(());
-                }
-                
-                // organizationIdカラムがNOT NULLかどうかを確認
-                let org_id_not_null: bool = conn.query_row(
-                    "SELECT \"notnull\" FROM pragma_table_info('focusInitiatives') WHERE name='organizationId'",
-                    [],
-                    |row| row.get::<_, i32>(0).map(|n| n != 0),
-                ).unwrap_or(false);
-                
-                if org_id_not_null {
-                    init_log!("📝 focusInitiativesテーブルを再作成します（organizationIdをNULL可能に）");
-                    
-                    // 既存データをバックアップテーブルにコピー
-                    conn.execute("CREATE TABLE IF NOT EXISTS focusInitiatives_backup AS SELECT * FROM focusInitiatives", [])?;
-                    
-                    // 古いテーブルを削除
-                    conn.execute("DROP TABLE focusInitiatives", [])?;
-                    
-                    // 新しいテーブルを作成（organizationIdをNULL可能に）
-                    conn.execute(
-                        "CREATE TABLE focusInitiatives (
-                            id TEXT PRIMARY KEY,
-                            organizationId TEXT,
-                            companyId TEXT,
-                            title TEXT NOT NULL,
-                            description TEXT,
-                            content TEXT,
-                            themeIds TEXT,
-                            topicIds TEXT,
-                            createdAt TEXT,
-                            updatedAt TEXT,
-                            FOREIGN KEY (organizationId) REFERENCES organizations(id),
-                            CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR 
-                                   (organizationId IS NULL AND companyId IS NOT NULL))
-                        )",
-                        [],
-                    )?;
-                    
-                    // バックアップテーブルからデータをコピー（カラム名を明示的に指定）
-                    // companyIdカラムが存在しない場合はNULLを設定
-                    let backup_has_company_id: bool = conn.query_row(
-                        "SELECT COUNT(*) FROM pragma_table_info('focusInitiatives_backup') WHERE name='companyId'",
-                        [],
-                        |row| row.get::<_, i32>(0).map(|n| n > 0),
-                    ).unwrap_or(false);
-                    
-                    // themeIdsとtopicIdsカラムの存在も確認
-                    let backup_has_theme_ids: bool = conn.query_row(
-                        "SELECT COUNT(*) FROM pragma_table_info('focusInitiatives_backup') WHERE name='themeIds'",
-                        [],
-                        |row| row.get::<_, i32>(0).map(|n| n > 0),
-                    ).unwrap_or(false);
-                    
-                    let backup_has_topic_ids: bool = conn.query_row(
-                        "SELECT COUNT(*) FROM pragma_table_info('focusInitiatives_backup') WHERE name='topicIds'",
-                        [],
-                        |row| row.get::<_, i32>(0).map(|n| n > 0),
-                    ).unwrap_or(false);
-                    
-                    if backup_has_company_id && backup_has_theme_ids && backup_has_topic_ids {
-                        conn.execute(
-                            "INSERT INTO focusInitiatives (id, organizationId, companyId, title, description, content, themeIds, topicIds, createdAt, updatedAt) 
-                             SELECT id, organizationId, companyId, title, description, content, themeIds, topicIds, createdAt, updatedAt 
-                             FROM focusInitiatives_backup",
-                            [],
-                        )?;
-                    } else {
-                        // 古いテーブル構造の場合、不足しているカラムはNULLを設定
-                        let company_id_col = if backup_has_company_id { "companyId" } else { "NULL" };
-                        let theme_ids_col = if backup_has_theme_ids { "themeIds" } else { "NULL" };
-                        let topic_ids_col = if backup_has_topic_ids { "topicIds" } else { "NULL" };
-                        
-                        conn.execute(
-                            &format!(
-                                "INSERT INTO focusInitiatives (id, organizationId, companyId, title, description, content, themeIds, topicIds, createdAt, updatedAt) 
-                                 SELECT id, organizationId, {}, title, description, content, {}, {}, createdAt, updatedAt 
-                                 FROM focusInitiatives_backup",
-                                company_id_col, theme_ids_col, topic_ids_col
-                            ),
-                            [],
-                        )?;
-                    }
-                    
-                    // バックアップテーブルを削除
-                    conn.execute("DROP TABLE focusInitiatives_backup", [])?;
-                    
-                    init_log!("✅ focusInitiativesテーブルの再作成が完了しました");
-                } else {
-                    init_log!("ℹ️  focusInitiativesテーブルのorganizationIdは既にNULL可能です");
-                }
-            }
-            Ok(())
-        })() {
-            init_log!("⚠️  focusInitiativesテーブルのマイグレーションでエラーが発生しました（続行します）: {}", e);
-        }
-        
         // 事業会社コンテンツテーブル（新規追加）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS companyContents (
@@ -823,31 +458,6 @@ impl Database {
             [],
         )?;
         
-        // companyContentsテーブルにcapitalStructureとcapitalStructureDiagramカラムを追加（マイグレーション）
-        if let Err(e) = (|| -> SqlResult<()> {
-            // capitalStructureカラムの存在確認
-            let mut stmt = conn.prepare("PRAGMA table_info(companyContents)")?;
-            let columns: Vec<String> = stmt.query_map([], |row| {
-                Ok(row.get::<_, String>(1)?)
-            })?.collect::<Result<Vec<_>, _>>()?;
-            
-            if !columns.contains(&"capitalStructure".to_string()) {
-                init_log!("📝 companyContentsテーブルにcapitalStructureカラムを追加します");
-                conn.execute("ALTER TABLE companyContents ADD COLUMN capitalStructure TEXT", [])?;
-                init_log!("✅ capitalStructureカラムを追加しました");
-            }
-            
-            if !columns.contains(&"capitalStructureDiagram".to_string()) {
-                init_log!("📝 companyContentsテーブルにcapitalStructureDiagramカラムを追加します");
-                conn.execute("ALTER TABLE companyContents ADD COLUMN capitalStructureDiagram TEXT", [])?;
-                init_log!("✅ capitalStructureDiagramカラムを追加しました");
-            }
-            
-            Ok(())
-        })() {
-            init_log!("⚠️  companyContentsテーブルのマイグレーションでエラーが発生しました（続行します）: {}", e);
-        }
-
         // テーマテーブル（新規追加）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS themes (
@@ -862,47 +472,6 @@ impl Database {
             [],
         )?;
         
-        // positionカラムのマイグレーション（既存テーブルにカラムが存在しない場合）
-        let position_exists: bool = conn.query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('themes') WHERE name = 'position'",
-            [],
-            |row| Ok(row.get::<_, i32>(0)? > 0),
-        ).unwrap_or(false);
-
-        if !position_exists {
-            init_log!("🔧 themesテーブルにpositionカラムを追加します...");
-            
-            // トランザクション内で実行（安全性のため）
-            let tx = conn.unchecked_transaction()?;
-            
-            // カラム追加
-            tx.execute(
-                "ALTER TABLE themes ADD COLUMN position INTEGER",
-                [],
-            )?;
-            
-            // 既存データにpositionを設定（createdAt順に連番を割り当て）
-            // サブクエリを使用してより安全に実装（ROW_NUMBER()の代わりにCOUNTを使用）
-            tx.execute(
-                "UPDATE themes SET position = (
-                    SELECT COUNT(*) + 1 FROM themes t2 
-                    WHERE (t2.createdAt < themes.createdAt) 
-                    OR (t2.createdAt = themes.createdAt AND t2.title < themes.title)
-                    OR (t2.createdAt = themes.createdAt AND t2.title = themes.title AND t2.id < themes.id)
-                )",
-                [],
-            )?;
-            
-            // positionカラムにインデックスを追加（パフォーマンス向上）
-            tx.execute(
-                "CREATE INDEX IF NOT EXISTS idx_themes_position ON themes(position)",
-                [],
-            )?;
-            
-            tx.commit()?;
-            init_log!("✅ positionカラムの追加と初期値設定が完了しました");
-        }
-
         // カテゴリーテーブル
         conn.execute(
             "CREATE TABLE IF NOT EXISTS categories (
@@ -1020,62 +589,6 @@ impl Database {
             [],
         )?;
         
-        // positionカラムのマイグレーション（既存テーブルにカラムが存在しない場合）
-        let category_position_exists: bool = conn.query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('categories') WHERE name = 'position'",
-            [],
-            |row| Ok(row.get::<_, i32>(0)? > 0),
-        ).unwrap_or(false);
-
-        if !category_position_exists {
-            init_log!("🔧 categoriesテーブルにpositionカラムを追加します...");
-            
-            // トランザクション内で実行（安全性のため）
-            let tx = conn.unchecked_transaction()?;
-            
-            // カラム追加
-            tx.execute(
-                "ALTER TABLE categories ADD COLUMN position INTEGER",
-                [],
-            )?;
-            
-            // 既存データにpositionを設定（createdAt順に連番を割り当て）
-            tx.execute(
-                "UPDATE categories SET position = (
-                    SELECT COUNT(*) + 1 FROM categories c2 
-                    WHERE (c2.createdAt < categories.createdAt) 
-                    OR (c2.createdAt = categories.createdAt AND c2.title < categories.title)
-                    OR (c2.createdAt = categories.createdAt AND c2.title = categories.title AND c2.id < categories.id)
-                )",
-                [],
-            )?;
-            
-            // positionカラムにインデックスを追加（パフォーマンス向上）
-            tx.execute(
-                "CREATE INDEX IF NOT EXISTS idx_categories_position ON categories(position)",
-                [],
-            )?;
-            
-            tx.commit()?;
-            init_log!("✅ categoriesテーブルのpositionカラムの追加と初期値設定が完了しました");
-        }
-
-        // parentCategoryIdカラムのマイグレーション（既存テーブルにカラムが存在しない場合）
-        let category_parent_exists: bool = conn.query_row(
-            "SELECT COUNT(*) FROM pragma_table_info('categories') WHERE name = 'parentCategoryId'",
-            [],
-            |row| Ok(row.get::<_, i32>(0)? > 0),
-        ).unwrap_or(false);
-
-        if !category_parent_exists {
-            init_log!("🔧 categoriesテーブルにparentCategoryIdカラムを追加します...");
-            conn.execute(
-                "ALTER TABLE categories ADD COLUMN parentCategoryId TEXT",
-                [],
-            )?;
-            init_log!("✅ categoriesテーブルのparentCategoryIdカラムの追加が完了しました");
-        }
-
         // テーマ階層設定テーブル（A2C100用）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS themeHierarchyConfigs (
@@ -1149,300 +662,6 @@ impl Database {
             [],
         )?;
         
-        // entitiesテーブルのマイグレーション（companyIdカラムとCHECK制約を追加）
-        init_log!("🔍 entitiesテーブルのマイグレーションを開始します...");
-        if let Err(e) = (|| -> SqlResult<()> {
-            let entities_table_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='entities'",
-                [],
-                |row| row.get(0),
-            ).unwrap_or(false);
-            
-            init_log!("📊 entitiesテーブルの存在確認: {}", entities_table_exists);
-            
-            if entities_table_exists {
-                // カラムの存在確認
-                let mut stmt = conn.prepare("PRAGMA table_info(entities)")?;
-                let columns: Vec<String> = stmt.query_map([], |row| {
-                    Ok(row.get::<_, String>(1)?)
-                })?.collect::<Result<Vec<_>, _>>()?;
-                
-                let company_id_exists = columns.contains(&"companyId".to_string());
-                let last_search_date_exists = columns.contains(&"lastSearchDate".to_string());
-                let search_count_exists = columns.contains(&"searchCount".to_string());
-                
-                init_log!("📊 entitiesテーブルのカラム存在確認: companyId={}, lastSearchDate={}, searchCount={}", 
-                    company_id_exists, last_search_date_exists, search_count_exists);
-                
-                if !company_id_exists {
-                    init_log_always!("📝 entitiesテーブルを再作成します（companyIdカラムとCHECK制約を追加）");
-                    
-                    // 外部キー制約を一時的に無効化（relationsテーブルがentitiesを参照しているため）
-                    conn.execute("PRAGMA foreign_keys = OFF", [])?;
-                    init_log!("✅ 外部キー制約を無効化しました");
-                    
-                    // 既存データをバックアップテーブルにコピー
-                    conn.execute("CREATE TABLE IF NOT EXISTS entities_backup AS SELECT * FROM entities", [])?;
-                    init_log!("✅ バックアップテーブルを作成しました");
-                    
-                    // 古いテーブルを削除
-                    conn.execute("DROP TABLE entities", [])?;
-                    init_log!("✅ 古いテーブルを削除しました");
-                    
-                    // 新しいテーブルを作成（companyIdカラムとCHECK制約を追加）
-                    conn.execute(
-                        "CREATE TABLE entities (
-                            id TEXT PRIMARY KEY,
-                            name TEXT NOT NULL,
-                            type TEXT NOT NULL,
-                            aliases TEXT,
-                            metadata TEXT,
-                            organizationId TEXT,
-                            companyId TEXT,
-                            chromaSynced INTEGER DEFAULT 0,
-                            chromaSyncError TEXT,
-                            lastChromaSyncAttempt TEXT,
-                            createdAt TEXT NOT NULL,
-                            updatedAt TEXT NOT NULL,
-                            FOREIGN KEY (organizationId) REFERENCES organizations(id),
-                            CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR 
-                                   (organizationId IS NULL AND companyId IS NOT NULL))
-                        )",
-                        [],
-                    )?;
-                    init_log!("✅ 新しいテーブルを作成しました");
-                    
-                    // バックアップテーブルからデータをコピー
-                    conn.execute(
-                        "INSERT INTO entities (id, name, type, aliases, metadata, organizationId, companyId, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt) 
-                         SELECT id, name, type, aliases, metadata, organizationId, NULL, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt 
-                         FROM entities_backup",
-                        [],
-                    )?;
-                    init_log!("✅ データをコピーしました");
-                    
-                    // バックアップテーブルを削除
-                    let _ = conn.execute("DROP TABLE entities_backup", []);
-                    
-                    // 外部キー制約を再度有効化
-                    conn.execute("PRAGMA foreign_keys = ON", [])?;
-                    init_log!("✅ 外部キー制約を再有効化しました");
-                    
-                    init_log_always!("✅ entitiesテーブルの再作成が完了しました");
-                } else {
-                    init_log!("ℹ️  entitiesテーブルにcompanyIdカラムは既に存在します");
-                }
-                
-                // 検索頻度追跡カラムの追加（非破壊的マイグレーション）
-                if !last_search_date_exists {
-                    init_log!("📝 entitiesテーブルにlastSearchDateカラムを追加します");
-                    conn.execute("ALTER TABLE entities ADD COLUMN lastSearchDate TEXT", [])?;
-                    init_log!("✅ lastSearchDateカラムを追加しました");
-                }
-                
-                if !search_count_exists {
-                    init_log!("📝 entitiesテーブルにsearchCountカラムを追加します");
-                    conn.execute("ALTER TABLE entities ADD COLUMN searchCount INTEGER DEFAULT 0", [])?;
-                    init_log!("✅ searchCountカラムを追加しました");
-                }
-            } else {
-                init_log!("ℹ️  entitiesテーブルが存在しません（新規テーブルの可能性）");
-            }
-            Ok(())
-        })() {
-            init_log_always!("❌ entitiesテーブルのマイグレーション中にエラーが発生しました: {}", e);
-        }
-        
-        // relationsテーブルのマイグレーション（companyIdカラムとCHECK制約を追加）
-        init_log!("🔍 relationsテーブルのマイグレーションを開始します...");
-        if let Err(e) = (|| -> SqlResult<()> {
-            let relations_table_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='relations'",
-                [],
-                |row| row.get(0),
-            ).unwrap_or(false);
-            
-            init_log!("📊 relationsテーブルの存在確認: {}", relations_table_exists);
-            
-            if relations_table_exists {
-                // カラムの存在確認
-                let mut stmt = conn.prepare("PRAGMA table_info(relations)")?;
-                let columns: Vec<String> = stmt.query_map([], |row| {
-                    Ok(row.get::<_, String>(1)?)
-                })?.collect::<Result<Vec<_>, _>>()?;
-                
-                let company_id_exists = columns.contains(&"companyId".to_string());
-                let last_search_date_exists = columns.contains(&"lastSearchDate".to_string());
-                let search_count_exists = columns.contains(&"searchCount".to_string());
-                
-                init_log!("📊 relationsテーブルのカラム存在確認: companyId={}, lastSearchDate={}, searchCount={}", 
-                    company_id_exists, last_search_date_exists, search_count_exists);
-                
-                if !company_id_exists {
-                    init_log_always!("📝 relationsテーブルを再作成します（companyIdカラムとCHECK制約を追加）");
-                    
-                    // 外部キー制約を一時的に無効化
-                    conn.execute("PRAGMA foreign_keys = OFF", [])?;
-                    init_log!("✅ 外部キー制約を無効化しました");
-                    
-                    // 既存データをバックアップテーブルにコピー
-                    conn.execute("CREATE TABLE IF NOT EXISTS relations_backup AS SELECT * FROM relations", [])?;
-                    init_log!("✅ バックアップテーブルを作成しました");
-                    
-                    // 古いテーブルを削除
-                    conn.execute("DROP TABLE relations", [])?;
-                    init_log!("✅ 古いテーブルを削除しました");
-                    
-                    // 新しいテーブルを作成（companyIdカラムとCHECK制約を追加）
-                    conn.execute(
-                        "CREATE TABLE relations (
-                            id TEXT PRIMARY KEY,
-                            topicId TEXT NOT NULL,
-                            sourceEntityId TEXT,
-                            targetEntityId TEXT,
-                            relationType TEXT NOT NULL,
-                            description TEXT,
-                            confidence REAL,
-                            metadata TEXT,
-                            organizationId TEXT,
-                            companyId TEXT,
-                            chromaSynced INTEGER DEFAULT 0,
-                            chromaSyncError TEXT,
-                            lastChromaSyncAttempt TEXT,
-                            createdAt TEXT NOT NULL,
-                            updatedAt TEXT NOT NULL,
-                            FOREIGN KEY (sourceEntityId) REFERENCES entities(id),
-                            FOREIGN KEY (targetEntityId) REFERENCES entities(id),
-                            FOREIGN KEY (organizationId) REFERENCES organizations(id),
-                            CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR 
-                                   (organizationId IS NULL AND companyId IS NOT NULL))
-                        )",
-                        [],
-                    )?;
-                    init_log!("✅ 新しいテーブルを作成しました");
-                    
-                    // バックアップテーブルからデータをコピー
-                    conn.execute(
-                        "INSERT INTO relations (id, topicId, sourceEntityId, targetEntityId, relationType, description, confidence, metadata, organizationId, companyId, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt) 
-                         SELECT id, topicId, sourceEntityId, targetEntityId, relationType, description, confidence, metadata, organizationId, NULL, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt 
-                         FROM relations_backup",
-                        [],
-                    )?;
-                    init_log!("✅ データをコピーしました");
-                    
-                    // バックアップテーブルを削除
-                    let _ = conn.execute("DROP TABLE relations_backup", []);
-                    
-                    // 外部キー制約を再度有効化
-                    conn.execute("PRAGMA foreign_keys = ON", [])?;
-                    init_log!("✅ 外部キー制約を再有効化しました");
-                    
-                    init_log_always!("✅ relationsテーブルの再作成が完了しました");
-                } else {
-                    init_log!("ℹ️  relationsテーブルにcompanyIdカラムは既に存在します");
-                }
-                
-                // 検索頻度追跡カラムの追加（非破壊的マイグレーション）
-                if !last_search_date_exists {
-                    init_log!("📝 relationsテーブルにlastSearchDateカラムを追加します");
-                    conn.execute("ALTER TABLE relations ADD COLUMN lastSearchDate TEXT", [])?;
-                    init_log!("✅ lastSearchDateカラムを追加しました");
-                }
-                
-                if !search_count_exists {
-                    init_log!("📝 relationsテーブルにsearchCountカラムを追加します");
-                    conn.execute("ALTER TABLE relations ADD COLUMN searchCount INTEGER DEFAULT 0", [])?;
-                    init_log!("✅ searchCountカラムを追加しました");
-                }
-                
-                // yamlFileIdカラムの追加（YAMLファイル対応）
-                let yaml_file_id_exists = columns.contains(&"yamlFileId".to_string());
-                if !yaml_file_id_exists {
-                    init_log!("📝 relationsテーブルにyamlFileIdカラムを追加します");
-                    conn.execute("ALTER TABLE relations ADD COLUMN yamlFileId TEXT", [])?;
-                    init_log!("✅ yamlFileIdカラムを追加しました");
-                }
-                
-                // topicIdをNULL可能にする（マイグレーション）
-                // SQLiteではALTER COLUMNができないため、テーブル再作成が必要
-                // topicIdがNOT NULL制約になっているかチェック
-                let topic_id_is_not_null = conn.query_row(
-                    "SELECT COUNT(*) FROM pragma_table_info('relations') WHERE name='topicId' AND \"notnull\"=1",
-                    [],
-                    |row| row.get::<_, i32>(0).map(|n| n > 0),
-                ).unwrap_or(false);
-                
-                if topic_id_is_not_null {
-                    init_log!("📝 relationsテーブルのtopicIdをNULL可能にするため、テーブルを再作成します");
-                    
-                    // バックアップテーブルを作成
-                    conn.execute("CREATE TABLE IF NOT EXISTS relations_backup_topicid AS SELECT * FROM relations", [])?;
-                    init_log!("✅ バックアップテーブルを作成しました");
-                    
-                    // 古いテーブルを削除
-                    conn.execute("DROP TABLE relations", [])?;
-                    init_log!("✅ 古いテーブルを削除しました");
-                    
-                    // 新しいテーブルを作成（topicIdをNULL可能に、yamlFileId対応、CHECK制約追加）
-                    conn.execute(
-                        "CREATE TABLE relations (
-                            id TEXT PRIMARY KEY,
-                            topicId TEXT,
-                            yamlFileId TEXT,
-                            sourceEntityId TEXT,
-                            targetEntityId TEXT,
-                            relationType TEXT NOT NULL,
-                            description TEXT,
-                            confidence REAL,
-                            metadata TEXT,
-                            organizationId TEXT,
-                            companyId TEXT,
-                            searchableText TEXT,
-                            chromaSynced INTEGER DEFAULT 0,
-                            chromaSyncError TEXT,
-                            lastChromaSyncAttempt TEXT,
-                            lastSearchDate TEXT,
-                            searchCount INTEGER DEFAULT 0,
-                            createdAt TEXT NOT NULL,
-                            updatedAt TEXT NOT NULL,
-                            FOREIGN KEY (sourceEntityId) REFERENCES entities(id),
-                            FOREIGN KEY (targetEntityId) REFERENCES entities(id),
-                            FOREIGN KEY (organizationId) REFERENCES organizations(id),
-                            FOREIGN KEY (yamlFileId) REFERENCES graphvizYamlFiles(id) ON DELETE CASCADE,
-                            CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR 
-                                   (organizationId IS NULL AND companyId IS NOT NULL)),
-                            CHECK ((topicId IS NOT NULL AND yamlFileId IS NULL) OR 
-                                   (topicId IS NULL AND yamlFileId IS NOT NULL) OR
-                                   (topicId IS NULL AND yamlFileId IS NULL))
-                        )",
-                        [],
-                    )?;
-                    init_log!("✅ 新しいテーブルを作成しました（topicId NULL可能）");
-                    
-                    // バックアップテーブルからデータをコピー
-                    conn.execute(
-                        "INSERT INTO relations (id, topicId, yamlFileId, sourceEntityId, targetEntityId, relationType, description, confidence, metadata, organizationId, companyId, searchableText, chromaSynced, chromaSyncError, lastChromaSyncAttempt, lastSearchDate, searchCount, createdAt, updatedAt) 
-                         SELECT id, topicId, yamlFileId, sourceEntityId, targetEntityId, relationType, description, confidence, metadata, organizationId, companyId, searchableText, chromaSynced, chromaSyncError, lastChromaSyncAttempt, lastSearchDate, searchCount, createdAt, updatedAt 
-                         FROM relations_backup_topicid",
-                        [],
-                    )?;
-                    init_log!("✅ データをコピーしました");
-                    
-                    // バックアップテーブルを削除
-                    let _ = conn.execute("DROP TABLE relations_backup_topicid", []);
-                    
-                    init_log!("✅ topicIdをNULL可能にするマイグレーションが完了しました");
-                } else {
-                    init_log!("ℹ️  relationsテーブルのtopicIdは既にNULL可能です");
-                }
-            } else {
-                init_log!("ℹ️  relationsテーブルが存在しません（新規テーブルの可能性）");
-            }
-            Ok(())
-        })() {
-            init_log_always!("❌ relationsテーブルのマイグレーション中にエラーが発生しました: {}", e);
-        }
-        
         // トピックテーブル（ChromaDB同期状態カラムを含む）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS topics (
@@ -1476,132 +695,6 @@ impl Database {
             [],
         )?;
         
-        // topicsテーブルのマイグレーション（companyIdカラム、CHECK制約、検索頻度追跡カラムを追加）
-        init_log!("🔍 topicsテーブルのマイグレーションを開始します...");
-        if let Err(e) = (|| -> SqlResult<()> {
-            let topics_table_exists: bool = conn.query_row(
-                "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='topics'",
-                [],
-                |row| row.get(0),
-            ).unwrap_or(false);
-            
-            init_log!("📊 topicsテーブルの存在確認: {}", topics_table_exists);
-            
-            if topics_table_exists {
-                // カラムの存在確認
-                let mut stmt = conn.prepare("PRAGMA table_info(topics)")?;
-                let columns: Vec<String> = stmt.query_map([], |row| {
-                    Ok(row.get::<_, String>(1)?)
-                })?.collect::<Result<Vec<_>, _>>()?;
-                
-                let company_id_exists = columns.contains(&"companyId".to_string());
-                let last_search_date_exists = columns.contains(&"lastSearchDate".to_string());
-                let search_count_exists = columns.contains(&"searchCount".to_string());
-                
-                init_log!("📊 topicsテーブルのカラム存在確認: companyId={}, lastSearchDate={}, searchCount={}", 
-                    company_id_exists, last_search_date_exists, search_count_exists);
-                
-                if !company_id_exists {
-                    init_log_always!("📝 topicsテーブルを再作成します（companyIdカラムとCHECK制約を追加）");
-                    
-                    // 外部キー制約を一時的に無効化
-                    conn.execute("PRAGMA foreign_keys = OFF", [])?;
-                    init_log!("✅ 外部キー制約を無効化しました");
-                    
-                    // 既存データをバックアップテーブルにコピー
-                    conn.execute("CREATE TABLE IF NOT EXISTS topics_backup AS SELECT * FROM topics", [])?;
-                    init_log!("✅ バックアップテーブルを作成しました");
-                    
-                    // 古いテーブルを削除
-                    conn.execute("DROP TABLE topics", [])?;
-                    init_log!("✅ 古いテーブルを削除しました");
-                    
-                    // 新しいテーブルを作成（companyIdカラムとCHECK制約を追加）
-                    conn.execute(
-                        "CREATE TABLE topics (
-                            id TEXT PRIMARY KEY,
-                            topicId TEXT NOT NULL,
-                            meetingNoteId TEXT NOT NULL,
-                            organizationId TEXT,
-                            companyId TEXT,
-                            title TEXT NOT NULL,
-                            description TEXT,
-                            content TEXT,
-                            semanticCategory TEXT,
-                            keywords TEXT,
-                            tags TEXT,
-                            chromaSynced INTEGER DEFAULT 0,
-                            chromaSyncError TEXT,
-                            lastChromaSyncAttempt TEXT,
-                            topicDate TEXT,
-                            createdAt TEXT NOT NULL,
-                            updatedAt TEXT NOT NULL,
-                            FOREIGN KEY (meetingNoteId) REFERENCES meetingNotes(id),
-                            FOREIGN KEY (organizationId) REFERENCES organizations(id),
-                            CHECK ((organizationId IS NOT NULL AND companyId IS NULL) OR 
-                                   (organizationId IS NULL AND companyId IS NOT NULL))
-                        )",
-                        [],
-                    )?;
-                    init_log!("✅ 新しいテーブルを作成しました");
-                    
-                    // バックアップテーブルからデータをコピー
-                    conn.execute(
-                        "INSERT INTO topics (id, topicId, meetingNoteId, organizationId, companyId, title, description, content, semanticCategory, keywords, tags, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt) 
-                         SELECT id, topicId, meetingNoteId, organizationId, NULL, title, description, content, semanticCategory, keywords, tags, chromaSynced, chromaSyncError, lastChromaSyncAttempt, createdAt, updatedAt FROM topics_backup", 
-                        []
-                    )?;
-                    init_log!("✅ バックアップテーブルからデータをコピーしました");
-                    
-                    // バックアップテーブルを削除
-                    conn.execute("DROP TABLE topics_backup", [])?;
-                    init_log!("✅ バックアップテーブルを削除しました");
-                    
-                    // 外部キー制約を再有効化
-                    conn.execute("PRAGMA foreign_keys = ON", [])?;
-                    init_log!("✅ 外部キー制約を再有効化しました");
-                } else {
-                    init_log!("ℹ️  topicsテーブルにcompanyIdカラムは既に存在します");
-                }
-                
-                // 検索頻度追跡カラムの追加（非破壊的マイグレーション）
-                if !last_search_date_exists {
-                    init_log!("📝 topicsテーブルにlastSearchDateカラムを追加します");
-                    conn.execute("ALTER TABLE topics ADD COLUMN lastSearchDate TEXT", [])?;
-                    init_log!("✅ lastSearchDateカラムを追加しました");
-                }
-                
-                if !search_count_exists {
-                    init_log!("📝 topicsテーブルにsearchCountカラムを追加します");
-                    conn.execute("ALTER TABLE topics ADD COLUMN searchCount INTEGER DEFAULT 0", [])?;
-                    init_log!("✅ searchCountカラムを追加しました");
-                }
-                
-                // imagePathsカラムの追加（非破壊的マイグレーション）
-                let image_paths_exists = columns.contains(&"imagePaths".to_string());
-                if !image_paths_exists {
-                    init_log!("📝 topicsテーブルにimagePathsカラムを追加します");
-                    conn.execute("ALTER TABLE topics ADD COLUMN imagePaths TEXT", [])?;
-                    init_log!("✅ imagePathsカラムを追加しました");
-                } else {
-                    init_log!("ℹ️  topicsテーブルのimagePathsカラムは既に存在します");
-                }
-                
-                // topicDateカラムの追加（非破壊的マイグレーション）
-                let topic_date_exists = columns.contains(&"topicDate".to_string());
-                if !topic_date_exists {
-                    init_log!("📝 topicsテーブルにtopicDateカラムを追加します");
-                    conn.execute("ALTER TABLE topics ADD COLUMN topicDate TEXT", [])?;
-                    init_log!("✅ topicDateカラムを追加しました");
-                } else {
-                    init_log!("ℹ️  topicsテーブルのtopicDateカラムは既に存在します");
-                }
-            }
-            Ok(())
-        })() {
-            init_log_always!("❌ topicsテーブルのマイグレーション中にエラーが発生しました: {}", e);
-        }
-
         // topicFilesテーブル（ファイル管理用）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS topicFiles (
@@ -1629,251 +722,35 @@ impl Database {
             [],
         )?;
 
-        // topicsテーブルにparentTopicIdカラムを追加（マイグレーション）
-        let _ = (|| -> rusqlite::Result<()> {
-            let columns: Vec<String> = conn
-                .prepare("SELECT name FROM pragma_table_info('topics')")?
-                .query_map([], |row| Ok(row.get::<_, String>(0)?))?
-                .collect::<Result<_, _>>()?;
-            
-            let parent_topic_id_exists = columns.contains(&"parentTopicId".to_string());
-            if !parent_topic_id_exists {
-                init_log!("📝 topicsテーブルにparentTopicIdカラムを追加します");
-                conn.execute("ALTER TABLE topics ADD COLUMN parentTopicId TEXT", [])?;
-                init_log!("✅ parentTopicIdカラムを追加しました");
-            } else {
-                init_log!("ℹ️  topicsテーブルのparentTopicIdカラムは既に存在します");
-            }
-            Ok(())
-        })();
-
         // 注意: entityEmbeddings、relationEmbeddingsテーブルは廃止されました（ChromaDBに統一）
         // 注意: companiesテーブルとorganizationCompanyDisplayテーブルは削除されました（organizationsテーブルに統合済み）
 
-        // インデックスを作成
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizationContents_organizationId ON organizationContents(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_focusInitiatives_organizationId ON focusInitiatives(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_focusInitiatives_companyId ON focusInitiatives(companyId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_organizationId ON meetingNotes(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_companyId ON meetingNotes(companyId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_companyContents_companyId ON companyContents(companyId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_themes_id ON themes(id)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_categories_id ON categories(id)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_organizationId ON entities(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_companyId ON entities(companyId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_type ON entities(type)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_name ON entities(name)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_chromaSynced ON entities(chromaSynced)", [])?;
-        // RAG検索最適化: searchableTextインデックス
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_searchable_text ON entities(searchableText)", [])?;
-        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_org_chroma ON entities(organizationId, chromaSynced)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_topicId ON relations(topicId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_yamlFileId ON relations(yamlFileId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_sourceEntityId ON relations(sourceEntityId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_targetEntityId ON relations(targetEntityId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_companyId ON relations(companyId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_relationType ON relations(relationType)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_organizationId ON relations(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_chromaSynced ON relations(chromaSynced)", [])?;
-        // RAG検索最適化: searchableTextインデックス
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_searchable_text ON relations(searchableText)", [])?;
-        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_org_chroma ON relations(organizationId, chromaSynced)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_meetingNoteId ON topics(meetingNoteId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_organizationId ON topics(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_companyId ON topics(companyId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_chromaSynced ON topics(chromaSynced)", [])?;
-        // RAG検索最適化: searchableTextとsemanticCategoryインデックス
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_searchable_text ON topics(searchableText)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_semanticCategory ON topics(semanticCategory)", [])?;
-        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_org_chroma ON topics(organizationId, chromaSynced)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_chromaSynced ON meetingNotes(chromaSynced)", [])?;
-        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_org_chroma ON meetingNotes(organizationId, chromaSynced)", [])?;
-        // 注意: companiesテーブルとorganizationCompanyDisplayテーブルは削除されました（organizationsテーブルに統合済み）
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizations_parentId ON organizations(parentId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizations_level ON organizations(level)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizations_levelName ON organizations(levelName)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizationMembers_organizationId ON organizationMembers(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_topicId ON topicFiles(topicId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_parentTopicId ON topicFiles(parentTopicId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_organizationId ON topicFiles(organizationId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_meetingNoteId ON topicFiles(meetingNoteId)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_parentTopicId ON topics(parentTopicId)", [])?;
-
-        // RAG検索最適化: 自動更新トリガーを作成
-        // topicsテーブルのcontentSummaryとsearchableTextを自動生成
+        // システム設計ドキュメントセクションテーブル（新規追加）
         conn.execute(
-            r#"
-            CREATE TRIGGER IF NOT EXISTS update_topics_searchable_fields
-            AFTER INSERT ON topics
-            BEGIN
-                UPDATE topics SET
-                    contentSummary = CASE
-                        WHEN content IS NOT NULL AND LENGTH(content) > 0
-                        THEN SUBSTR(content, 1, 200)
-                        ELSE NULL
-                    END,
-                    searchableText = TRIM(
-                        COALESCE(title, '') || ' ' ||
-                        COALESCE(description, '') || ' ' ||
-                        COALESCE(SUBSTR(content, 1, 200), '')
-                    )
-                WHERE id = NEW.id;
-            END
-            "#,
+            "CREATE TABLE IF NOT EXISTS designDocSections (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT,
+                content TEXT NOT NULL,
+                tags TEXT,
+                order_index INTEGER DEFAULT 0,
+                pageUrl TEXT DEFAULT '/design',
+                hierarchy TEXT,
+                relatedSections TEXT,
+                semanticCategory TEXT,
+                keywords TEXT,
+                summary TEXT,
+                createdAt TEXT NOT NULL,
+                updatedAt TEXT NOT NULL
+            )",
             [],
         )?;
 
-        conn.execute(
-            r#"
-            CREATE TRIGGER IF NOT EXISTS update_topics_searchable_fields_on_update
-            AFTER UPDATE ON topics
-            BEGIN
-                UPDATE topics SET
-                    contentSummary = CASE
-                        WHEN NEW.content IS NOT NULL AND LENGTH(NEW.content) > 0
-                        THEN SUBSTR(NEW.content, 1, 200)
-                        ELSE NULL
-                    END,
-                    searchableText = TRIM(
-                        COALESCE(NEW.title, '') || ' ' ||
-                        COALESCE(NEW.description, '') || ' ' ||
-                        COALESCE(SUBSTR(NEW.content, 1, 200), '')
-                    )
-                WHERE id = NEW.id;
-            END
-            "#,
-            [],
-        )?;
-
-        // entitiesテーブルのsearchableTextとdisplayNameを自動生成
-        conn.execute(
-            r#"
-            CREATE TRIGGER IF NOT EXISTS update_entities_searchable_fields
-            AFTER INSERT ON entities
-            BEGIN
-                UPDATE entities SET
-                    searchableText = TRIM(
-                        COALESCE(name, '') || ' ' ||
-                        COALESCE(aliases, '') || ' ' ||
-                        CASE
-                            WHEN metadata IS NOT NULL AND json_extract(metadata, '$.role') IS NOT NULL
-                            THEN json_extract(metadata, '$.role') || ' '
-                            ELSE ''
-                        END ||
-                        CASE
-                            WHEN metadata IS NOT NULL AND json_extract(metadata, '$.department') IS NOT NULL
-                            THEN json_extract(metadata, '$.department')
-                            ELSE ''
-                        END
-                    ),
-                    displayName = name ||
-                        CASE
-                            WHEN metadata IS NOT NULL AND json_extract(metadata, '$.role') IS NOT NULL
-                            THEN ' (' || json_extract(metadata, '$.role') || ')'
-                            ELSE ''
-                        END
-                WHERE id = NEW.id;
-            END
-            "#,
-            [],
-        )?;
-
-        conn.execute(
-            r#"
-            CREATE TRIGGER IF NOT EXISTS update_entities_searchable_fields_on_update
-            AFTER UPDATE ON entities
-            BEGIN
-                UPDATE entities SET
-                    searchableText = TRIM(
-                        COALESCE(NEW.name, '') || ' ' ||
-                        COALESCE(NEW.aliases, '') || ' ' ||
-                        CASE
-                            WHEN NEW.metadata IS NOT NULL AND json_extract(NEW.metadata, '$.role') IS NOT NULL
-                            THEN json_extract(NEW.metadata, '$.role') || ' '
-                            ELSE ''
-                        END ||
-                        CASE
-                            WHEN NEW.metadata IS NOT NULL AND json_extract(NEW.metadata, '$.department') IS NOT NULL
-                            THEN json_extract(NEW.metadata, '$.department')
-                            ELSE ''
-                        END
-                    ),
-                    displayName = NEW.name ||
-                        CASE
-                            WHEN NEW.metadata IS NOT NULL AND json_extract(NEW.metadata, '$.role') IS NOT NULL
-                            THEN ' (' || json_extract(NEW.metadata, '$.role') || ')'
-                            ELSE ''
-                        END
-                WHERE id = NEW.id;
-            END
-            "#,
-            [],
-        )?;
-
-        // relationsテーブルのsearchableTextを自動生成
-        conn.execute(
-            r#"
-            CREATE TRIGGER IF NOT EXISTS update_relations_searchable_fields
-            AFTER INSERT ON relations
-            BEGIN
-                UPDATE relations SET
-                    searchableText = TRIM(
-                        COALESCE(relationType, '') || ' ' ||
-                        COALESCE(description, '')
-                    )
-                WHERE id = NEW.id;
-            END
-            "#,
-            [],
-        )?;
-
-        conn.execute(
-            r#"
-            CREATE TRIGGER IF NOT EXISTS update_relations_searchable_fields_on_update
-            AFTER UPDATE ON relations
-            BEGIN
-                UPDATE relations SET
-                    searchableText = TRIM(
-                        COALESCE(NEW.relationType, '') || ' ' ||
-                        COALESCE(NEW.description, '')
-                    )
-                WHERE id = NEW.id;
-            END
-            "#,
-            [],
-        )?;
-
-        // システム設計ドキュメントセクションテーブル（新規追加）
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS designDocSections (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                description TEXT,
-                content TEXT NOT NULL,
-                tags TEXT,
-                order_index INTEGER DEFAULT 0,
-                pageUrl TEXT DEFAULT '/design',
-                hierarchy TEXT,
-                relatedSections TEXT,
-                semanticCategory TEXT,
-                keywords TEXT,
-                summary TEXT,
-                createdAt TEXT NOT NULL,
-                updatedAt TEXT NOT NULL
-            )",
-            [],
-        )?;
-
-        // インデックスを作成
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_designDocSections_order ON designDocSections(order_index)", [])?;
-        conn.execute("CREATE INDEX IF NOT EXISTS idx_designDocSections_semanticCategory ON designDocSections(semanticCategory)", [])?;
-
-        // システム設計ドキュメントセクション関係テーブル（新規追加）
+        // インデックスを作成
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_designDocSections_order ON designDocSections(order_index)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_designDocSections_semanticCategory ON designDocSections(semanticCategory)", [])?;
+
+        // システム設計ドキュメントセクション関係テーブル（新規追加）
         conn.execute(
             "CREATE TABLE IF NOT EXISTS designDocSectionRelations (
                 id TEXT PRIMARY KEY,
@@ -1917,37 +794,6 @@ impl Database {
             [],
         )?;
 
-        // tasksテーブルにmodelTypeとselectedModelカラムを追加（マイグレーション）
-        let _ = (|| -> rusqlite::Result<()> {
-            // modelTypeカラムの存在確認
-            let model_type_exists = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('tasks') WHERE name='modelType'",
-                [],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-
-            if !model_type_exists {
-                init_log!("📝 tasksテーブルにmodelTypeカラムを追加します");
-                conn.execute("ALTER TABLE tasks ADD COLUMN modelType TEXT", [])?;
-                init_log!("✅ modelTypeカラムを追加しました");
-            }
-
-            // selectedModelカラムの存在確認
-            let selected_model_exists = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('tasks') WHERE name='selectedModel'",
-                [],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-
-            if !selected_model_exists {
-                init_log!("📝 tasksテーブルにselectedModelカラムを追加します");
-                conn.execute("ALTER TABLE tasks ADD COLUMN selectedModel TEXT", [])?;
-                init_log!("✅ selectedModelカラムを追加しました");
-            }
-
-            Ok(())
-        })();
-
         // タスク実行テーブル
         conn.execute(
             "CREATE TABLE IF NOT EXISTS taskExecutions (
@@ -1986,24 +832,6 @@ impl Database {
             [],
         )?;
 
-        // agentsテーブルにselectedModelカラムを追加（マイグレーション）
-        let _ = (|| -> rusqlite::Result<()> {
-            // selectedModelカラムの存在確認
-            let selected_model_exists = conn.query_row(
-                "SELECT COUNT(*) FROM pragma_table_info('agents') WHERE name='selectedModel'",
-                [],
-                |row| Ok(row.get::<_, i32>(0)? > 0),
-            ).unwrap_or(false);
-
-            if !selected_model_exists {
-                init_log!("📝 agentsテーブルにselectedModelカラムを追加します");
-                conn.execute("ALTER TABLE agents ADD COLUMN selectedModel TEXT", [])?;
-                init_log!("✅ selectedModelカラムを追加しました");
-            }
-
-            Ok(())
-        })();
-
         // A2Aメッセージ履歴テーブル
         conn.execute(
             "CREATE TABLE IF NOT EXISTS a2aMessages (
@@ -2173,6 +1001,221 @@ impl Database {
         conn.execute("CREATE INDEX IF NOT EXISTS idx_graphvizDotFiles_chromaSynced ON graphvizDotFiles(chromaSynced)", [])?;
         conn.execute("CREATE INDEX IF NOT EXISTS idx_graphvizDotFiles_searchableText ON graphvizDotFiles(searchableText)", [])?;
 
+        // スキーマ移行（番号付きマイグレーションをschema_migrationsに記録しながら順番に適用）
+        // 注意: 以降のインデックス・トリガーは移行後のカラムを参照するため、必ずこの後に作成する
+        let report = migrations::run_migrations(&conn, false)?;
+        if !report.applied.is_empty() {
+            init_log!("✅ スキーマを移行しました: v{} → v{}", report.start_version, report.end_version);
+        }
+        if let Some(failure) = report.failure {
+            init_log_always!("❌ マイグレーション {:04}_{} に失敗しました: {}", failure.version, failure.name, failure.error);
+            return Err(rusqlite::Error::SqliteFailure(
+                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_ERROR),
+                Some(format!(
+                    "マイグレーション {:04}_{} に失敗しました（スキーマバージョン v{} のままです）: {}",
+                    failure.version, failure.name, report.end_version, failure.error
+                )),
+            ));
+        }
+
+        // インデックスを作成
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizationContents_organizationId ON organizationContents(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_focusInitiatives_organizationId ON focusInitiatives(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_focusInitiatives_companyId ON focusInitiatives(companyId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_organizationId ON meetingNotes(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_companyId ON meetingNotes(companyId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_companyContents_companyId ON companyContents(companyId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_themes_id ON themes(id)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_categories_id ON categories(id)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_organizationId ON entities(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_companyId ON entities(companyId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_type ON entities(type)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_name ON entities(name)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_chromaSynced ON entities(chromaSynced)", [])?;
+        // RAG検索最適化: searchableTextインデックス
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_searchable_text ON entities(searchableText)", [])?;
+        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_entities_org_chroma ON entities(organizationId, chromaSynced)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_topicId ON relations(topicId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_yamlFileId ON relations(yamlFileId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_sourceEntityId ON relations(sourceEntityId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_targetEntityId ON relations(targetEntityId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_companyId ON relations(companyId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_relationType ON relations(relationType)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_organizationId ON relations(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_chromaSynced ON relations(chromaSynced)", [])?;
+        // RAG検索最適化: searchableTextインデックス
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_searchable_text ON relations(searchableText)", [])?;
+        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_relations_org_chroma ON relations(organizationId, chromaSynced)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_meetingNoteId ON topics(meetingNoteId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_organizationId ON topics(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_companyId ON topics(companyId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_chromaSynced ON topics(chromaSynced)", [])?;
+        // RAG検索最適化: searchableTextとsemanticCategoryインデックス
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_searchable_text ON topics(searchableText)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_semanticCategory ON topics(semanticCategory)", [])?;
+        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_org_chroma ON topics(organizationId, chromaSynced)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_chromaSynced ON meetingNotes(chromaSynced)", [])?;
+        // 複合インデックス: organizationId + chromaSynced（RAG検索のパフォーマンス向上）
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_meetingNotes_org_chroma ON meetingNotes(organizationId, chromaSynced)", [])?;
+        // 注意: companiesテーブルとorganizationCompanyDisplayテーブルは削除されました（organizationsテーブルに統合済み）
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizations_parentId ON organizations(parentId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizations_level ON organizations(level)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizations_levelName ON organizations(levelName)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_organizationMembers_organizationId ON organizationMembers(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_topicId ON topicFiles(topicId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_parentTopicId ON topicFiles(parentTopicId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_organizationId ON topicFiles(organizationId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topicFiles_meetingNoteId ON topicFiles(meetingNoteId)", [])?;
+        conn.execute("CREATE INDEX IF NOT EXISTS idx_topics_parentTopicId ON topics(parentTopicId)", [])?;
+
+        // RAG検索最適化: 自動更新トリガーを作成
+        // topicsテーブルのcontentSummaryとsearchableTextを自動生成
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS update_topics_searchable_fields
+            AFTER INSERT ON topics
+            BEGIN
+                UPDATE topics SET
+                    contentSummary = CASE
+                        WHEN content IS NOT NULL AND LENGTH(content) > 0
+                        THEN SUBSTR(content, 1, 200)
+                        ELSE NULL
+                    END,
+                    searchableText = TRIM(
+                        COALESCE(title, '') || ' ' ||
+                        COALESCE(description, '') || ' ' ||
+                        COALESCE(SUBSTR(content, 1, 200), '')
+                    )
+                WHERE id = NEW.id;
+            END
+            "#,
+            [],
+        )?;
+
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS update_topics_searchable_fields_on_update
+            AFTER UPDATE ON topics
+            BEGIN
+                UPDATE topics SET
+                    contentSummary = CASE
+                        WHEN NEW.content IS NOT NULL AND LENGTH(NEW.content) > 0
+                        THEN SUBSTR(NEW.content, 1, 200)
+                        ELSE NULL
+                    END,
+                    searchableText = TRIM(
+                        COALESCE(NEW.title, '') || ' ' ||
+                        COALESCE(NEW.description, '') || ' ' ||
+                        COALESCE(SUBSTR(NEW.content, 1, 200), '')
+                    )
+                WHERE id = NEW.id;
+            END
+            "#,
+            [],
+        )?;
+
+        // entitiesテーブルのsearchableTextとdisplayNameを自動生成
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS update_entities_searchable_fields
+            AFTER INSERT ON entities
+            BEGIN
+                UPDATE entities SET
+                    searchableText = TRIM(
+                        COALESCE(name, '') || ' ' ||
+                        COALESCE(aliases, '') || ' ' ||
+                        CASE
+                            WHEN metadata IS NOT NULL AND json_extract(metadata, '$.role') IS NOT NULL
+                            THEN json_extract(metadata, '$.role') || ' '
+                            ELSE ''
+                        END ||
+                        CASE
+                            WHEN metadata IS NOT NULL AND json_extract(metadata, '$.department') IS NOT NULL
+                            THEN json_extract(metadata, '$.department')
+                            ELSE ''
+                        END
+                    ),
+                    displayName = name ||
+                        CASE
+                            WHEN metadata IS NOT NULL AND json_extract(metadata, '$.role') IS NOT NULL
+                            THEN ' (' || json_extract(metadata, '$.role') || ')'
+                            ELSE ''
+                        END
+                WHERE id = NEW.id;
+            END
+            "#,
+            [],
+        )?;
+
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS update_entities_searchable_fields_on_update
+            AFTER UPDATE ON entities
+            BEGIN
+                UPDATE entities SET
+                    searchableText = TRIM(
+                        COALESCE(NEW.name, '') || ' ' ||
+                        COALESCE(NEW.aliases, '') || ' ' ||
+                        CASE
+                            WHEN NEW.metadata IS NOT NULL AND json_extract(NEW.metadata, '$.role') IS NOT NULL
+                            THEN json_extract(NEW.metadata, '$.role') || ' '
+                            ELSE ''
+                        END ||
+                        CASE
+                            WHEN NEW.metadata IS NOT NULL AND json_extract(NEW.metadata, '$.department') IS NOT NULL
+                            THEN json_extract(NEW.metadata, '$.department')
+                            ELSE ''
+                        END
+                    ),
+                    displayName = NEW.name ||
+                        CASE
+                            WHEN NEW.metadata IS NOT NULL AND json_extract(NEW.metadata, '$.role') IS NOT NULL
+                            THEN ' (' || json_extract(NEW.metadata, '$.role') || ')'
+                            ELSE ''
+                        END
+                WHERE id = NEW.id;
+            END
+            "#,
+            [],
+        )?;
+
+        // relationsテーブルのsearchableTextを自動生成
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS update_relations_searchable_fields
+            AFTER INSERT ON relations
+            BEGIN
+                UPDATE relations SET
+                    searchableText = TRIM(
+                        COALESCE(relationType, '') || ' ' ||
+                        COALESCE(description, '')
+                    )
+                WHERE id = NEW.id;
+            END
+            "#,
+            [],
+        )?;
+
+        conn.execute(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS update_relations_searchable_fields_on_update
+            AFTER UPDATE ON relations
+            BEGIN
+                UPDATE relations SET
+                    searchableText = TRIM(
+                        COALESCE(NEW.relationType, '') || ' ' ||
+                        COALESCE(NEW.description, '')
+                    )
+                WHERE id = NEW.id;
+            END
+            "#,
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -2266,6 +1309,9 @@ pub fn init_database(app: &AppHandle) -> SqlResult<()> {
     
     init_log!("📁 データベースパス: {}", db_path_display);
     
+    // HNSWベクトルインデックスはapp.dbと同じディレクトリに保存
+    vector_search::init_vector_store(db_dir.join("vector_indexes"));
+    
     // データベースの作成
     let db = match Database::new(db_path.clone()) {
         Ok(db) => {
@@ -2320,6 +1366,9 @@ pub fn init_database(app: &AppHandle) -> SqlResult<()> {
         }
     }
     
+    // 前回の起動時に終了しなかったRust側のタスク実行を失敗として記録
+    agent_executor::recover_interrupted_executions();
+    
     // 雛形データのインポート（データベースが新規作成された場合のみ）
     let template_path = app.path().resource_dir()
         .map(|dir| dir.join("template-data.json"))
@@ -2338,6 +1387,11 @@ pub fn init_database(app: &AppHandle) -> SqlResult<()> {
 
 /// ChromaDB Serverを初期化（非同期）
 pub async fn init_chromadb(app: &AppHandle) -> Result<(), String> {
+    if vector_search::vector_backend() == vector_search::VectorBackend::Hnsw {
+        init_log!("ℹ️ ベクトルストアにHNSWを使用するため、ChromaDB Serverは起動しません");
+        return Ok(());
+    }
+    
     init_log!("🔧 ChromaDB Serverの初期化を開始します...");
     
     // データベースディレクトリを取得
@@ -1,4 +1,5 @@
 use crate::database::{get_db, get_timestamp, to_firestore_timestamp, get_current_user};
+use crate::database::collection_query::{build_select, CollectionQuery};
 use rusqlite::Result as SqlResult;
 use serde_json::{Value, json};
 use std::collections::HashMap;
@@ -979,6 +980,16 @@ pub fn add_doc(collection_name: &str, data: HashMap<String, Value>) -> SqlResult
 }
 
 pub fn get_collection(collection_name: &str, conditions: Option<HashMap<String, Value>>) -> SqlResult<Vec<HashMap<String, Value>>> {
+    // 旧形式（{ field: value } / field・operator・value）と構造化クエリのどちらも受け付ける
+    let query = match conditions {
+        Some(conds) => CollectionQuery::from_conditions(&conds)?,
+        None => CollectionQuery::default(),
+    };
+    query_collection(collection_name, &query)
+}
+
+/// 構造化クエリでコレクションを取得
+pub fn query_collection(collection_name: &str, query: &CollectionQuery) -> SqlResult<Vec<HashMap<String, Value>>> {
     // テーブル名の検証（SQLインジェクション対策）
     validate_table_name(collection_name)?;
     
@@ -988,92 +999,12 @@ pub fn get_collection(collection_name: &str, conditions: Option<HashMap<String,
     ))?;
     let conn = db.get_connection()?;
     
-    let mut query = format!("SELECT * FROM {}", collection_name);
-    let mut param_values: Vec<String> = Vec::new();
-    let mut where_clauses: Vec<String> = Vec::new();
-    
-    if let Some(conds) = conditions {
-        // 新しい形式: { field: value } の形式をサポート
-        // 例: { topicId: "some-value" } -> WHERE topicId = ?
-        for (field, value) in conds.iter() {
-            // 特殊キー（orderBy, orderDirection, field, operator, value）はスキップ
-            if field == "orderBy" || field == "orderDirection" || field == "field" || field == "operator" || field == "value" {
-                continue;
-            }
-            
-            // フィールド名の検証（SQLインジェクション対策）
-            if !field.chars().all(|c| c.is_alphanumeric() || c == '_') {
-                return Err(rusqlite::Error::SqliteFailure(
-                    rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
-                    Some(format!("無効なフィールド名: {}", field))
-                ));
-            }
-            
-            where_clauses.push(format!("{} = ?", field));
-            // valueを文字列に変換（ライフタイムの問題を回避）
-            let param_str = match value {
-                Value::String(s) => s.clone(),
-                Value::Number(n) => n.to_string(),
-                Value::Bool(b) => b.to_string(),
-                Value::Null => "NULL".to_string(),
-                _ => value.to_string(),
-            };
-            param_values.push(param_str);
-        }
-        
-        // 後方互換性のため、古い形式（field, operator, value）もサポート
-        if where_clauses.is_empty() {
-            if let Some(field) = conds.get("field").and_then(|v| v.as_str()) {
-                if let Some(operator) = conds.get("operator").and_then(|v| v.as_str()) {
-                    if let Some(value) = conds.get("value") {
-                        let sql_op = match operator {
-                            "==" => "=",
-                            "!=" => "!=",
-                            "<" => "<",
-                            "<=" => "<=",
-                            ">" => ">",
-                            ">=" => ">=",
-                            _ => "=",
-                        };
-                        where_clauses.push(format!("{} {} ?", field, sql_op));
-                        // valueを文字列に変換（ライフタイムの問題を回避）
-                        let param_str = match value {
-                            Value::String(s) => s.clone(),
-                            Value::Number(n) => n.to_string(),
-                            Value::Bool(b) => b.to_string(),
-                            _ => value.to_string(),
-                        };
-                        param_values.push(param_str);
-                    }
-                }
-            }
-        }
-        
-        // WHERE句を追加
-        if !where_clauses.is_empty() {
-            query.push_str(" WHERE ");
-            query.push_str(&where_clauses.join(" AND "));
-        }
-        
-        // ORDER BY句を追加
-        if let Some(order_by) = conds.get("orderBy").and_then(|v| v.as_str()) {
-            let direction = if conds.get("orderDirection")
-                .and_then(|v| v.as_str())
-                .map(|d| d == "desc")
-                .unwrap_or(false) {
-                "DESC"
-            } else {
-                "ASC"
-            };
-            query.push_str(&format!(" ORDER BY {} {}", order_by, direction));
-        }
-    }
-    
-    // paramsを構築（param_valuesのライフタイムを保持）
-    let params: Vec<&dyn rusqlite::ToSql> = param_values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+    // フィールド名は実在するカラムのみ許可（SQLインジェクション対策）
+    let columns = get_table_columns(&conn, collection_name)?;
+    let (query, params) = build_select(&conn, collection_name, &columns, query)?;
     
     let mut stmt = conn.prepare(&query)?;
-    let rows = stmt.query_map(params.as_slice(), |row| {
+    let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
         let mut map = HashMap::new();
         for i in 0..row.as_ref().column_count() {
             let col_name = row.as_ref().column_name(i)
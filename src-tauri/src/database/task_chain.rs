@@ -0,0 +1,1187 @@
+/**
+ * タスクチェーンのノードモデルとインタープリター
+ * TaskChain.nodes（ノードIDをキーとするChainNodeのJSONオブジェクト）を保存時に検証し、Rust側でチェーンを実行する
+ *
+ * ノードの種類（type）:
+ * - task: タスク（task: インラインのタスク定義 / taskId: 保存済みのタスク）を実行し、nextNodeIdへ進む
+ * - condition: 直前のノード（condition.nodeIdで指定も可）の出力や変数を評価し、trueBranch / falseBranchへ進む
+ * - loop: タスクをloopCount回（最大100回）繰り返す。loopConditionがある場合は各回の後に評価し、偽になったら終了する
+ * - parallel: branchesの各ノードから並行して実行し、joinNodeIdのjoinノードで合流する
+ * - join: 並列実行の合流点。各ブランチの最後の出力をまとめてnextNodeIdへ進む
+ * - end: チェーンをstatus（completed / failed）で終了する（次のノードがない場合もcompletedで終了する）
+ *
+ * - タスクのparametersの`{{vars.<変数>}}` / `{{nodes.<ノードID>.content}}` / `{{input.<キー>}}` / `{{loop.index}}`は
+ *   実行時の値で置き換える（解決できないものはそのまま残す）
+ * - outputVariableを指定したノードの出力（JSONとして解釈できる場合はその値）は、変数として後続のノードで参照できる
+ * - ノードの通過はすべてチェーンの実行（taskChainRuns）に紐づくTaskExecutionとして記録する
+ *   （Agentを呼び出さないノードは、チェーンごとの制御用タスク`<チェーンID>_coordinator`の実行として記録する）
+ * - インラインのタスクと制御用タスクはchainIdを設定したチェーン専用のタスクとして保存し、通常のタスク一覧には含めない
+ *   （定義が変わった場合のみ書き込み、毎回の実行では保存しない）
+ * - task / loopノードのタスクが失敗した場合、errorNodeIdがあればそこへ進み、なければチェーンを失敗として終了する
+ */
+
+use rusqlite::{params, OptionalExtension, Result as SqlResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Once, OnceLock};
+use tokio::sync::watch;
+
+use super::agent_executor::{
+    cancel_task_execution, cancelled, enqueue_task, now_millis, record_chain_node_execution,
+    AgentTaskProgressCallback, ExecutionLink, ExecutionStatus,
+};
+use super::agent_system::{get_task, get_task_chain, Task, TaskChain, TaskExecution};
+use super::get_db;
+use super::task_dag::{constraint_error, validate_task_graph};
+
+/// loopノードの繰り返し回数の上限
+const MAX_LOOP_ITERATIONS: u32 = 100;
+/// 1回の実行で通過できるノード数の上限（条件分岐による無限ループの防止）
+const MAX_CHAIN_STEPS: usize = 1000;
+
+/// ノードの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ChainNodeType {
+    Task,
+    Condition,
+    Loop,
+    Parallel,
+    Join,
+    End,
+}
+
+impl ChainNodeType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Task => "task",
+            Self::Condition => "condition",
+            Self::Loop => "loop",
+            Self::Parallel => "parallel",
+            Self::Join => "join",
+            Self::End => "end",
+        }
+    }
+}
+
+/// 条件の種類（フロントエンドのChainCondition.typeと同じ値）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ChainConditionType {
+    Equals,
+    NotEquals,
+    GreaterThan,
+    LessThan,
+    Contains,
+    Exists,
+}
+
+/// 条件分岐・ループ継続の条件
+#[derive(Debug, Clone, Deserialize)]
+struct ChainCondition {
+    #[serde(rename = "type")]
+    condition_type: ChainConditionType,
+    /// 評価する値のパス（例: "status", "json.score", "vars.count", "nodes.<ノードID>.content"）
+    field: String,
+    #[serde(default)]
+    value: Value,
+    /// 評価する出力のノード（省略時は直前のノード）
+    #[serde(rename = "nodeId", default)]
+    node_id: Option<String>,
+}
+
+/// ノードにインラインで定義したタスク（フロントエンドのTask。dependencies等のほかの項目は使わない）
+#[derive(Debug, Clone, Deserialize)]
+struct ChainTaskSpec {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(rename = "type", default)]
+    task_type: String,
+    #[serde(rename = "agentId", default)]
+    agent_id: Option<String>,
+    #[serde(rename = "requiredAgents", default)]
+    required_agents: Option<Vec<String>>,
+    #[serde(default)]
+    parameters: Value,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    timeout: Option<i64>,
+    #[serde(rename = "retryCount", default)]
+    retry_count: Option<i32>,
+    #[serde(rename = "modelType", default)]
+    model_type: Option<String>,
+    #[serde(rename = "selectedModel", default)]
+    selected_model: Option<String>,
+}
+
+/// チェーンのノード（フロントエンドのChainNodeと同じ形式。種類ごとに使う項目が異なる）
+#[derive(Debug, Clone, Deserialize)]
+struct ChainNode {
+    #[serde(default)]
+    id: String,
+    #[serde(rename = "type")]
+    node_type: ChainNodeType,
+    #[serde(default)]
+    task: Option<ChainTaskSpec>,
+    #[serde(rename = "taskId", default)]
+    task_id: Option<String>,
+    #[serde(default)]
+    condition: Option<ChainCondition>,
+    #[serde(rename = "trueBranch", default)]
+    true_branch: Option<String>,
+    #[serde(rename = "falseBranch", default)]
+    false_branch: Option<String>,
+    #[serde(rename = "loopCount", default)]
+    loop_count: Option<u32>,
+    #[serde(rename = "loopCondition", default)]
+    loop_condition: Option<ChainCondition>,
+    #[serde(default)]
+    branches: Vec<String>,
+    #[serde(rename = "joinNodeId", default)]
+    join_node_id: Option<String>,
+    #[serde(rename = "nextNodeId", default)]
+    next_node_id: Option<String>,
+    #[serde(rename = "errorNodeId", default)]
+    error_node_id: Option<String>,
+    #[serde(rename = "outputVariable", default)]
+    output_variable: Option<String>,
+    /// endノードの終了状態（completed / failed）
+    #[serde(default)]
+    status: Option<String>,
+    /// endノードのメッセージ
+    #[serde(default)]
+    message: Option<String>,
+}
+
+type ChainNodes = HashMap<String, ChainNode>;
+
+fn non_empty(value: &mut Option<String>) {
+    if value.as_deref().is_some_and(|v| v.trim().is_empty()) {
+        *value = None;
+    }
+}
+
+impl ChainNode {
+    /// エディタが未設定の参照を空文字列で保存する場合があるため、空文字列は未設定として扱う
+    fn normalize(&mut self) {
+        for value in [
+            &mut self.task_id,
+            &mut self.true_branch,
+            &mut self.false_branch,
+            &mut self.join_node_id,
+            &mut self.next_node_id,
+            &mut self.error_node_id,
+            &mut self.output_variable,
+            &mut self.status,
+        ] {
+            non_empty(value);
+        }
+        for condition in [&mut self.condition, &mut self.loop_condition].into_iter().flatten() {
+            non_empty(&mut condition.node_id);
+        }
+        self.branches.retain(|b| !b.trim().is_empty());
+    }
+
+    /// 参照しているノードID（項目名, ノードID）
+    fn references(&self) -> Vec<(&'static str, &str)> {
+        let mut references: Vec<(&'static str, &str)> = [
+            ("nextNodeId", &self.next_node_id),
+            ("errorNodeId", &self.error_node_id),
+            ("trueBranch", &self.true_branch),
+            ("falseBranch", &self.false_branch),
+            ("joinNodeId", &self.join_node_id),
+        ]
+        .into_iter()
+        .filter_map(|(field, id)| id.as_deref().map(|id| (field, id)))
+        .collect();
+        references.extend(self.branches.iter().map(|b| ("branches", b.as_str())));
+        if let Some(id) = self.condition.as_ref().and_then(|c| c.node_id.as_deref()) {
+            references.push(("condition.nodeId", id));
+        }
+        if let Some(id) = self.loop_condition.as_ref().and_then(|c| c.node_id.as_deref()) {
+            references.push(("loopCondition.nodeId", id));
+        }
+        references
+    }
+
+    /// 実行時に次に進む可能性のあるノードID
+    fn successors(&self) -> Vec<&str> {
+        self.references()
+            .into_iter()
+            .filter(|(field, _)| !field.ends_with(".nodeId"))
+            .map(|(_, id)| id)
+            .collect()
+    }
+}
+
+fn parse_chain_nodes(chain: &TaskChain) -> Result<ChainNodes, String> {
+    let raw: Map<String, Value> = serde_json::from_str(&chain.nodes)
+        .map_err(|e| format!("nodesはノードIDをキーとするオブジェクト（JSON）で指定してください: {}", e))?;
+    let mut nodes = HashMap::new();
+    for (key, value) in raw {
+        let mut node: ChainNode = serde_json::from_value(value)
+            .map_err(|e| format!("ノード {} の形式が正しくありません: {}", key, e))?;
+        node.normalize();
+        if node.id.is_empty() {
+            node.id = key.clone();
+        } else if node.id != key {
+            return Err(format!("ノードのID（{}）がキー（{}）と一致しません", node.id, key));
+        }
+        nodes.insert(key, node);
+    }
+    Ok(nodes)
+}
+
+/// startから辿れるノードのうち、stop_atを通らずに到達できるendノード
+fn find_reachable_end<'a>(nodes: &'a ChainNodes, start: &'a str, stop_at: &str) -> Option<&'a str> {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(id) = queue.pop_front() {
+        if id == stop_at || !visited.insert(id) {
+            continue;
+        }
+        let Some(node) = nodes.get(id) else { continue };
+        if node.node_type == ChainNodeType::End {
+            return Some(id);
+        }
+        queue.extend(node.successors());
+    }
+    None
+}
+
+fn validate_condition(condition: &ChainCondition, field: &str) -> Result<(), String> {
+    if condition.field.trim().is_empty() {
+        return Err(format!("{}の評価する値（field）を指定してください", field));
+    }
+    Ok(())
+}
+
+fn validate_node(node: &ChainNode, nodes: &ChainNodes) -> Result<(), String> {
+    for (field, target) in node.references() {
+        if !nodes.contains_key(target) {
+            return Err(format!("{}が存在しないノードを参照しています: {}", field, target));
+        }
+    }
+    if let Some(variable) = &node.output_variable {
+        if !variable.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("outputVariableには英数字と_のみ使用できます: {}", variable));
+        }
+    }
+
+    match node.node_type {
+        ChainNodeType::Task | ChainNodeType::Loop => {
+            match (&node.task, &node.task_id) {
+                (Some(_), Some(_)) => return Err("taskとtaskIdはどちらか一方を指定してください".to_string()),
+                (None, None) => return Err("実行するタスク（taskまたはtaskId）を指定してください".to_string()),
+                (None, Some(task_id)) => {
+                    get_task(task_id)
+                        .map_err(|e| format!("タスクの取得に失敗しました: {}", e))?
+                        .ok_or_else(|| format!("taskIdのタスクが見つかりません: {}", task_id))?;
+                }
+                (Some(_), None) => {}
+            }
+            if node.node_type == ChainNodeType::Loop {
+                if node.loop_count.is_none() && node.loop_condition.is_none() {
+                    return Err("loopCountまたはloopConditionを指定してください".to_string());
+                }
+                if let Some(count) = node.loop_count.filter(|n| *n == 0 || *n > MAX_LOOP_ITERATIONS) {
+                    return Err(format!("loopCountは1〜{}で指定してください: {}", MAX_LOOP_ITERATIONS, count));
+                }
+                if let Some(condition) = &node.loop_condition {
+                    validate_condition(condition, "loopCondition")?;
+                }
+            }
+        }
+        ChainNodeType::Condition => {
+            let condition = node.condition.as_ref().ok_or_else(|| "条件（condition）を指定してください".to_string())?;
+            validate_condition(condition, "condition")?;
+        }
+        ChainNodeType::Parallel => {
+            if node.branches.is_empty() {
+                return Err("並列実行するブランチ（branches）を指定してください".to_string());
+            }
+            let join_id = node.join_node_id.as_deref()
+                .ok_or_else(|| "合流するノード（joinNodeId）を指定してください".to_string())?;
+            if nodes[join_id].node_type != ChainNodeType::Join {
+                return Err(format!("joinNodeIdにはjoinノードを指定してください: {}", join_id));
+            }
+            for branch in &node.branches {
+                if branch == join_id {
+                    return Err("ブランチにjoinノードは指定できません".to_string());
+                }
+                if let Some(end) = find_reachable_end(nodes, branch, join_id) {
+                    return Err(format!("並列実行のブランチ内でendノードは使用できません: {}", end));
+                }
+            }
+        }
+        ChainNodeType::Join => {}
+        ChainNodeType::End => {
+            if let Some(status) = node.status.as_deref().filter(|s| *s != "completed" && *s != "failed") {
+                return Err(format!("endノードのstatusはcompletedまたはfailedで指定してください: {}", status));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_chain_nodes(chain: &TaskChain) -> Result<ChainNodes, String> {
+    let nodes = parse_chain_nodes(chain)?;
+    if nodes.is_empty() {
+        return Err("チェーンにノードがありません".to_string());
+    }
+    if !nodes.contains_key(&chain.start_node_id) {
+        return Err(format!("開始ノードが見つかりません: {}", chain.start_node_id));
+    }
+    let mut ids: Vec<&String> = nodes.keys().collect();
+    ids.sort();
+    for id in ids {
+        let node = &nodes[id];
+        validate_node(node, &nodes).map_err(|e| format!("ノード {}（{}）: {}", id, node.node_type.as_str(), e))?;
+    }
+    Ok(nodes)
+}
+
+/// 保存するタスクチェーンのノードを検証する
+pub(crate) fn validate_task_chain(chain: &TaskChain) -> SqlResult<()> {
+    validate_chain_nodes(chain).map(|_| ()).map_err(constraint_error)
+}
+
+/// タスクチェーンの実行（ラン）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskChainRun {
+    pub id: String,
+    #[serde(rename = "chainId")]
+    pub chain_id: String,
+    pub status: String, // "running" | "completed" | "failed" | "cancelled"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>, // JSON文字列
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables: Option<String>, // JSON文字列（outputVariableで設定した変数）
+    #[serde(rename = "executionPath")]
+    pub execution_path: String, // JSON文字列（通過したノードIDの配列）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(rename = "startedAt")]
+    pub started_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<i64>,
+}
+
+fn connection() -> Result<r2d2::PooledConnection<r2d2_sqlite::SqliteConnectionManager>, String> {
+    let db = get_db().ok_or_else(|| "データベースが初期化されていません".to_string())?;
+    db.get_connection().map_err(|e| format!("データベース接続の取得に失敗しました: {}", e))
+}
+
+fn read_run(row: &rusqlite::Row) -> SqlResult<TaskChainRun> {
+    Ok(TaskChainRun {
+        id: row.get(0)?,
+        chain_id: row.get(1)?,
+        status: row.get(2)?,
+        input: row.get(3)?,
+        variables: row.get(4)?,
+        execution_path: row.get(5)?,
+        error: row.get(6)?,
+        started_at: row.get(7)?,
+        completed_at: row.get(8)?,
+    })
+}
+
+const RUN_COLUMNS: &str = "id, chainId, status, input, variables, executionPath, error, startedAt, completedAt";
+
+fn save_run(run: &TaskChainRun) -> Result<(), String> {
+    let conn = connection()?;
+    conn.execute(
+        "INSERT INTO taskChainRuns (id, chainId, status, input, variables, executionPath, error, startedAt, completedAt)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET status = excluded.status, variables = excluded.variables,
+            executionPath = excluded.executionPath, error = excluded.error, completedAt = excluded.completedAt",
+        params![
+            run.id,
+            run.chain_id,
+            run.status,
+            run.input,
+            run.variables,
+            run.execution_path,
+            run.error,
+            run.started_at,
+            run.completed_at,
+        ],
+    )
+    .map_err(|e| format!("チェーンの実行の保存に失敗しました: {}", e))?;
+    Ok(())
+}
+
+/// チェーンの実行を取得
+pub fn get_task_chain_run(run_id: &str) -> Result<Option<TaskChainRun>, String> {
+    let conn = connection()?;
+    conn.query_row(
+        &format!("SELECT {} FROM taskChainRuns WHERE id = ?1", RUN_COLUMNS),
+        params![run_id],
+        read_run,
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// チェーンの実行履歴を取得（新しい順）
+pub fn list_task_chain_runs(chain_id: &str) -> Result<Vec<TaskChainRun>, String> {
+    let conn = connection()?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM taskChainRuns WHERE chainId = ?1 ORDER BY startedAt DESC",
+            RUN_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let runs = stmt
+        .query_map(params![chain_id], read_run)
+        .map_err(|e| e.to_string())?
+        .collect::<SqlResult<Vec<_>>>()
+        .map_err(|e| e.to_string())?;
+    Ok(runs)
+}
+
+/// 前回の起動時に実行中のまま終了したチェーンの実行を失敗として記録する（起動後の最初の実行時に一度だけ）
+fn recover_interrupted_runs() {
+    static RECOVERED: Once = Once::new();
+    RECOVERED.call_once(|| {
+        let result = connection().and_then(|conn| {
+            conn.execute(
+                "UPDATE taskChainRuns SET status = ?1, error = ?2, completedAt = ?3 WHERE status = ?4",
+                params![
+                    ExecutionStatus::Failed.as_str(),
+                    "アプリの終了により実行が中断されました",
+                    now_millis(),
+                    ExecutionStatus::Running.as_str(),
+                ],
+            )
+            .map_err(|e| e.to_string())
+        });
+        match result {
+            Ok(0) => {}
+            Ok(count) => eprintln!("⚠️ [task_chain] 中断されたチェーンの実行を失敗として記録しました: {}件", count),
+            Err(e) => eprintln!("⚠️ [task_chain] 中断されたチェーンの実行の記録に失敗しました: {}", e),
+        }
+    });
+}
+
+/// 実行中のチェーンのキャンセル通知
+static RUNNING_CHAIN_RUNS: OnceLock<Mutex<HashMap<String, watch::Sender<bool>>>> = OnceLock::new();
+
+fn running_chain_runs() -> std::sync::MutexGuard<'static, HashMap<String, watch::Sender<bool>>> {
+    RUNNING_CHAIN_RUNS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// チェーンの実行をキャンセル（実行中でなかった場合はfalse）
+pub fn cancel_task_chain_run(run_id: &str) -> bool {
+    match running_chain_runs().get(run_id) {
+        Some(cancel_tx) => {
+            let _ = cancel_tx.send(true);
+            true
+        }
+        None => false,
+    }
+}
+
+/// 作成・更新日時を除いてタスクの定義が同じか
+fn same_task_definition(a: &Task, b: &Task) -> bool {
+    let definition = |task: &Task| {
+        serde_json::to_value(Task { created_at: String::new(), updated_at: String::new(), ..task.clone() }).ok()
+    };
+    definition(a) == definition(b)
+}
+
+/// チェーン専用のタスクを、未保存または定義が変わった場合のみ保存する
+/// （taskExecutionsがtasksを参照するため行は必要だが、chainIdを設定してget_all_tasksの一覧から除外する）
+fn ensure_chain_task(chain_id: &str, task: Task) -> Result<Task, String> {
+    if let Some(existing) = get_task(&task.id).map_err(|e| format!("タスクの取得に失敗しました: {}", e))? {
+        if same_task_definition(&existing, &task) {
+            return Ok(existing);
+        }
+    }
+    validate_task_graph(&task).map_err(|e| e.to_string())?;
+
+    let conn = connection()?;
+    let now = now_millis().to_string();
+    conn.execute(
+        "INSERT INTO tasks (id, name, description, type, agentId, requiredAgents, dependencies, parameters, priority, timeout, retryCount, modelType, selectedModel, promptVersion, chainId, createdAt, updatedAt)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, NULL, ?14, ?15, ?15)
+         ON CONFLICT(id) DO UPDATE SET
+            name = excluded.name, description = excluded.description, type = excluded.type,
+            agentId = excluded.agentId, requiredAgents = excluded.requiredAgents, dependencies = excluded.dependencies,
+            parameters = excluded.parameters, priority = excluded.priority, timeout = excluded.timeout,
+            retryCount = excluded.retryCount, modelType = excluded.modelType, selectedModel = excluded.selectedModel,
+            promptVersion = NULL, chainId = excluded.chainId, updatedAt = excluded.updatedAt",
+        params![
+            task.id,
+            task.name,
+            task.description,
+            task.task_type,
+            task.agent_id,
+            task.required_agents,
+            task.dependencies,
+            task.parameters,
+            task.priority,
+            task.timeout,
+            task.retry_count,
+            task.model_type,
+            task.selected_model,
+            chain_id,
+            now,
+        ],
+    ).map_err(|e| format!("タスク {} の保存に失敗しました: {}", task.id, e))?;
+
+    get_task(&task.id)
+        .map_err(|e| format!("タスクの取得に失敗しました: {}", e))?
+        .ok_or_else(|| format!("タスクの保存後に取得に失敗しました: {}", task.id))
+}
+
+/// 条件分岐などのノードの記録に使うチェーンごとの制御用タスク
+fn coordinator_task(chain: &TaskChain) -> Result<Task, String> {
+    let task = Task {
+        id: format!("{}_coordinator", chain.id),
+        name: format!("{}（チェーン制御）", chain.name),
+        description: format!("タスクチェーン「{}」の条件分岐・並列実行などのノードの記録", chain.name),
+        task_type: "coordination".to_string(),
+        agent_id: None,
+        required_agents: None,
+        dependencies: None,
+        parameters: "{}".to_string(),
+        priority: 0,
+        timeout: None,
+        retry_count: None,
+        model_type: None,
+        selected_model: None,
+        prompt_version: None,
+        created_at: String::new(),
+        updated_at: String::new(),
+    };
+    ensure_chain_task(&chain.id, task)
+}
+
+/// インラインのタスク定義を`<チェーンID>_<ノードID>`のタスクとして保存する（実行履歴の参照先）
+fn save_inline_task(chain: &TaskChain, node_id: &str, spec: &ChainTaskSpec) -> Result<Task, String> {
+    let id = format!("{}_{}", chain.id, node_id);
+    let required_agents = spec
+        .required_agents
+        .as_ref()
+        .filter(|agents| !agents.is_empty())
+        .map(|agents| serde_json::to_string(agents).unwrap_or_else(|_| "[]".to_string()));
+    let task = Task {
+        id,
+        name: if spec.name.trim().is_empty() { node_id.to_string() } else { spec.name.clone() },
+        description: spec.description.clone(),
+        task_type: if spec.task_type.trim().is_empty() { "generation".to_string() } else { spec.task_type.clone() },
+        agent_id: spec.agent_id.clone().filter(|id| !id.trim().is_empty()),
+        required_agents,
+        dependencies: None,
+        parameters: if spec.parameters.is_null() { "{}".to_string() } else { spec.parameters.to_string() },
+        priority: spec.priority,
+        timeout: spec.timeout,
+        retry_count: spec.retry_count,
+        model_type: spec.model_type.clone(),
+        selected_model: spec.selected_model.clone(),
+        prompt_version: None,
+        created_at: String::new(),
+        updated_at: String::new(),
+    };
+    ensure_chain_task(&chain.id, task).map_err(|e| format!("ノード {} のタスクの準備に失敗しました: {}", node_id, e))
+}
+
+/// 開始を記録した実行待ちのチェーンの実行
+pub struct TaskChainRunHandle {
+    pub run: TaskChainRun,
+    start_node_id: String,
+    nodes: ChainNodes,
+    tasks: HashMap<String, Task>,
+    coordinator: Task,
+    input: Value,
+    cancel_rx: watch::Receiver<bool>,
+}
+
+/// チェーンを検証し、ノードのタスクを準備して実行の開始を記録する
+pub fn start_task_chain_run(chain_id: &str, input: Option<Value>) -> Result<TaskChainRunHandle, String> {
+    let chain = get_task_chain(chain_id)
+        .map_err(|e| format!("タスクチェーンの取得に失敗しました: {}", e))?
+        .ok_or_else(|| format!("タスクチェーンが見つかりません: {}", chain_id))?;
+    let nodes = validate_chain_nodes(&chain)?;
+
+    let mut tasks = HashMap::new();
+    for node in nodes.values() {
+        let task = match (&node.task, &node.task_id) {
+            (Some(spec), _) => save_inline_task(&chain, &node.id, spec)?,
+            (None, Some(task_id)) => get_task(task_id)
+                .map_err(|e| format!("タスクの取得に失敗しました: {}", e))?
+                .ok_or_else(|| format!("タスクが見つかりません: {}", task_id))?,
+            (None, None) => continue,
+        };
+        tasks.insert(node.id.clone(), task);
+    }
+    let coordinator = coordinator_task(&chain)?;
+
+    recover_interrupted_runs();
+    let input = input.filter(|v| !v.is_null()).unwrap_or_else(|| Value::Object(Map::new()));
+    let run = TaskChainRun {
+        id: format!("chainrun_{}", uuid::Uuid::new_v4()),
+        chain_id: chain.id.clone(),
+        status: ExecutionStatus::Running.as_str().to_string(),
+        input: Some(input.to_string()),
+        variables: Some("{}".to_string()),
+        execution_path: "[]".to_string(),
+        error: None,
+        started_at: now_millis(),
+        completed_at: None,
+    };
+    save_run(&run)?;
+
+    let (cancel_tx, cancel_rx) = watch::channel(false);
+    running_chain_runs().insert(run.id.clone(), cancel_tx);
+    Ok(TaskChainRunHandle {
+        run,
+        start_node_id: chain.start_node_id,
+        nodes,
+        tasks,
+        coordinator,
+        input,
+        cancel_rx,
+    })
+}
+
+/// 実行中の変数とノードの出力
+#[derive(Debug, Clone, Default)]
+struct ChainState {
+    /// outputVariableで設定した変数
+    vars: Map<String, Value>,
+    /// ノードID → ノードの出力
+    outputs: Map<String, Value>,
+    /// 最後に出力したノード
+    last_node_id: Option<String>,
+    /// loopノードの繰り返し中の回数（0始まり）
+    loop_index: Option<u32>,
+}
+
+impl ChainState {
+    fn last_output(&self) -> Option<&Value> {
+        self.last_node_id.as_ref().and_then(|id| self.outputs.get(id))
+    }
+
+    fn set_output(&mut self, node_id: &str, output: Value) {
+        self.outputs.insert(node_id.to_string(), output);
+        self.last_node_id = Some(node_id.to_string());
+    }
+}
+
+/// ノードを実行した後の進み先
+enum Step {
+    Next(Option<String>),
+    End(ExecutionStatus, Option<String>),
+}
+
+/// 実行の中断
+enum ChainStop {
+    Failed(String),
+    Cancelled,
+}
+
+/// 経路の実行の終わり方（Noneは次のノードがないか合流点に到達した）
+type PathResult = Result<Option<(ExecutionStatus, Option<String>)>, ChainStop>;
+
+struct ChainRunContext {
+    run: Mutex<TaskChainRun>,
+    nodes: ChainNodes,
+    tasks: HashMap<String, Task>,
+    coordinator: Task,
+    input: Value,
+    cancel_rx: watch::Receiver<bool>,
+    on_progress: AgentTaskProgressCallback,
+    path: Mutex<Vec<String>>,
+    steps: AtomicUsize,
+}
+
+fn is_completed(output: &Value) -> bool {
+    output.get("status").and_then(|s| s.as_str()) == Some(ExecutionStatus::Completed.as_str())
+}
+
+/// LLMの応答をJSONとして解釈する（```json のコードブロックも可）
+fn parse_content_json(content: &str) -> Value {
+    let trimmed = content.trim();
+    let body = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|b| b.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    serde_json::from_str(body.trim()).unwrap_or(Value::Null)
+}
+
+/// タスクの実行からノードの出力を作成
+fn task_output(execution: &TaskExecution) -> Value {
+    let result = execution
+        .result
+        .as_deref()
+        .and_then(|r| serde_json::from_str::<Value>(r).ok())
+        .unwrap_or(Value::Null);
+    let content = result.get("content").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+    json!({
+        "status": execution.status,
+        "executionId": execution.id,
+        "json": parse_content_json(&content),
+        "content": content,
+        "result": result,
+        "error": execution.error,
+    })
+}
+
+fn output_error(output: &Value) -> String {
+    output.get("error").and_then(|e| e.as_str()).unwrap_or("不明なエラー").to_string()
+}
+
+fn failed_output(execution_id: Option<&str>, error: String) -> Value {
+    json!({
+        "status": ExecutionStatus::Failed.as_str(),
+        "executionId": execution_id,
+        "json": Value::Null,
+        "content": "",
+        "result": Value::Null,
+        "error": error,
+    })
+}
+
+/// 変数に設定する値（JSONとして解釈できた場合はその値、それ以外は応答テキスト）
+fn output_value(output: &Value) -> Value {
+    match output.get("json") {
+        Some(json) if !json.is_null() => json.clone(),
+        _ => output.get("content").cloned().unwrap_or(Value::Null),
+    }
+}
+
+fn lookup<'a>(value: &'a Value, segments: &[&str]) -> Option<&'a Value> {
+    segments.iter().try_fold(value, |current, segment| match current {
+        Value::Object(map) => map.get(*segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+/// 条件を評価する（値が存在しない場合はフロントエンドと同じくすべて偽）
+fn compare(condition_type: ChainConditionType, actual: Option<&Value>, expected: &Value) -> bool {
+    let Some(actual) = actual.filter(|v| !v.is_null()) else {
+        return false;
+    };
+    match condition_type {
+        ChainConditionType::Exists => true,
+        ChainConditionType::Equals => values_equal(actual, expected),
+        ChainConditionType::NotEquals => !values_equal(actual, expected),
+        ChainConditionType::GreaterThan => matches!((as_number(actual), as_number(expected)), (Some(a), Some(b)) if a > b),
+        ChainConditionType::LessThan => matches!((as_number(actual), as_number(expected)), (Some(a), Some(b)) if a < b),
+        ChainConditionType::Contains => match actual {
+            Value::String(s) => s.contains(&value_text(expected)),
+            Value::Array(items) => items.iter().any(|item| values_equal(item, expected)),
+            _ => false,
+        },
+    }
+}
+
+impl ChainRunContext {
+    /// vars / nodes / input / loopで始まるパスの値（それ以外のパスはNone）
+    fn resolve_scoped(&self, state: &ChainState, path: &str) -> Option<Option<Value>> {
+        let segments: Vec<&str> = path.split('.').map(str::trim).collect();
+        let (scope, rest) = segments.split_first()?;
+        let value = match *scope {
+            "vars" => lookup_map(&state.vars, rest),
+            "nodes" => lookup_map(&state.outputs, rest),
+            "input" => lookup(&self.input, rest).cloned(),
+            "loop" => state.loop_index.and_then(|index| lookup(&json!({ "index": index }), rest).cloned()),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    fn evaluate(&self, condition: &ChainCondition, state: &ChainState) -> bool {
+        let actual = match self.resolve_scoped(state, &condition.field) {
+            Some(value) => value,
+            None => {
+                let target = match &condition.node_id {
+                    Some(node_id) => state.outputs.get(node_id),
+                    None => state.last_output(),
+                };
+                let segments: Vec<&str> = condition.field.split('.').map(str::trim).collect();
+                target.and_then(|t| lookup(t, &segments)).cloned()
+            }
+        };
+        compare(condition.condition_type, actual.as_ref(), &condition.value)
+    }
+
+    /// `{{...}}`のプレースホルダーを置き換える（解決できないものはそのまま残す）
+    fn render(&self, text: &str, state: &ChainState) -> String {
+        let mut rendered = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let Some(length) = rest[start + 2..].find("}}") else { break };
+            let end = start + 2 + length + 2;
+            rendered.push_str(&rest[..start]);
+            match self.resolve_scoped(state, &rest[start + 2..end - 2]) {
+                Some(Some(value)) => rendered.push_str(&value_text(&value)),
+                _ => rendered.push_str(&rest[start..end]),
+            }
+            rest = &rest[end..];
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+
+    fn render_value(&self, value: &Value, state: &ChainState) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.render(s, state)),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.render_value(v, state)).collect()),
+            Value::Object(map) => Value::Object(
+                map.iter().map(|(k, v)| (k.clone(), self.render_value(v, state))).collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn link(&self, node_id: &str) -> ExecutionLink {
+        ExecutionLink {
+            chain_run_id: Some(self.run.lock().unwrap_or_else(|e| e.into_inner()).id.clone()),
+            node_id: Some(node_id.to_string()),
+            evaluation_id: None,
+        }
+    }
+
+    /// Agentを呼び出さないノードの通過を記録する
+    fn record_node(&self, node: &ChainNode, status: ExecutionStatus, result: Value, message: &str) {
+        let recorded = record_chain_node_execution(
+            &self.coordinator,
+            self.link(&node.id),
+            status,
+            result,
+            message,
+            self.on_progress.clone(),
+        );
+        if let Err(e) = recorded {
+            eprintln!("⚠️ [task_chain] ノードの記録に失敗しました ({}): {}", node.id, e);
+        }
+    }
+
+    /// 通過したノードと変数を保存する
+    fn save_progress(&self, state: &ChainState) {
+        let path = self.path.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut run = self.run.lock().unwrap_or_else(|e| e.into_inner());
+        run.execution_path = serde_json::to_string(&path).unwrap_or_else(|_| "[]".to_string());
+        run.variables = Some(Value::Object(state.vars.clone()).to_string());
+        if let Err(e) = save_run(&run) {
+            eprintln!("⚠️ [task_chain] 実行状況の保存に失敗しました ({}): {}", run.id, e);
+        }
+    }
+
+    /// ノードのタスクを（parametersのプレースホルダーを置き換えて）実行し、終了まで待つ
+    async fn run_task(&self, node: &ChainNode, state: &ChainState) -> Result<Value, ChainStop> {
+        let mut task = self
+            .tasks
+            .get(&node.id)
+            .cloned()
+            .ok_or_else(|| ChainStop::Failed(format!("ノード {} のタスクが見つかりません", node.id)))?;
+        task.parameters = match serde_json::from_str::<Value>(&task.parameters) {
+            Ok(parameters) => self.render_value(&parameters, state).to_string(),
+            Err(_) => self.render(&task.parameters, state),
+        };
+
+        let (execution, mut done) = match enqueue_task(task, self.link(&node.id), self.on_progress.clone()) {
+            Ok(queued) => queued,
+            Err(e) => return Ok(failed_output(None, e)),
+        };
+        let mut cancel_rx = self.cancel_rx.clone();
+        tokio::select! {
+            result = &mut done => Ok(match result {
+                Ok(execution) => task_output(&execution),
+                Err(_) => failed_output(Some(&execution.id), "実行エンジンから結果を受け取れませんでした".to_string()),
+            }),
+            _ = cancelled(&mut cancel_rx) => {
+                if let Err(e) = cancel_task_execution(&execution.id) {
+                    eprintln!("⚠️ [task_chain] タスク実行のキャンセルに失敗しました ({}): {}", execution.id, e);
+                }
+                let _ = done.await;
+                Err(ChainStop::Cancelled)
+            }
+        }
+    }
+
+    /// タスクが失敗した場合の進み先（errorNodeIdがなければチェーンを失敗として終了する）
+    fn on_task_failure(&self, node: &ChainNode, error: String) -> Result<Step, ChainStop> {
+        match &node.error_node_id {
+            Some(error_node_id) => {
+                eprintln!(
+                    "⚠️ [task_chain] ノード {} のタスクが失敗したため、{} へ進みます: {}",
+                    node.id, error_node_id, error
+                );
+                Ok(Step::Next(Some(error_node_id.clone())))
+            }
+            None => Err(ChainStop::Failed(format!("ノード {} のタスク実行が失敗しました: {}", node.id, error))),
+        }
+    }
+
+    async fn visit(self: &Arc<Self>, node_id: &str, state: &mut ChainState) -> Result<Step, ChainStop> {
+        if *self.cancel_rx.borrow() {
+            return Err(ChainStop::Cancelled);
+        }
+        if self.steps.fetch_add(1, Ordering::SeqCst) >= MAX_CHAIN_STEPS {
+            return Err(ChainStop::Failed(format!(
+                "通過したノード数が上限（{}）を超えました（無限ループの可能性があります）",
+                MAX_CHAIN_STEPS
+            )));
+        }
+        let node = self
+            .nodes
+            .get(node_id)
+            .ok_or_else(|| ChainStop::Failed(format!("ノードが見つかりません: {}", node_id)))?;
+        self.path.lock().unwrap_or_else(|e| e.into_inner()).push(node_id.to_string());
+
+        match node.node_type {
+            ChainNodeType::Task => {
+                let output = self.run_task(node, state).await?;
+                let completed = is_completed(&output);
+                if let (true, Some(variable)) = (completed, &node.output_variable) {
+                    state.vars.insert(variable.clone(), output_value(&output));
+                }
+                let error = output_error(&output);
+                state.set_output(&node.id, output);
+                if !completed {
+                    return self.on_task_failure(node, error);
+                }
+                Ok(Step::Next(node.next_node_id.clone()))
+            }
+            ChainNodeType::Loop => {
+                let iterations = node.loop_count.unwrap_or(MAX_LOOP_ITERATIONS).min(MAX_LOOP_ITERATIONS);
+                let mut results = Vec::new();
+                let mut output = Value::Null;
+                let mut failure = None;
+                for index in 0..iterations {
+                    if *self.cancel_rx.borrow() {
+                        return Err(ChainStop::Cancelled);
+                    }
+                    state.loop_index = Some(index);
+                    output = self.run_task(node, state).await?;
+                    results.push(output_value(&output));
+                    state.set_output(&node.id, output.clone());
+                    if !is_completed(&output) {
+                        failure = Some(format!("{}回目: {}", index + 1, output_error(&output)));
+                        break;
+                    }
+                    // 継続条件は今回の出力（loop.indexは今回の回数）で評価する
+                    if node.loop_condition.as_ref().is_some_and(|c| !self.evaluate(c, state)) {
+                        break;
+                    }
+                }
+                state.loop_index = None;
+
+                if let Value::Object(map) = &mut output {
+                    map.insert("iterations".to_string(), json!(results.len()));
+                    map.insert("results".to_string(), Value::Array(results.clone()));
+                }
+                state.set_output(&node.id, output);
+                if let Some(error) = failure {
+                    return self.on_task_failure(node, error);
+                }
+                if let Some(variable) = &node.output_variable {
+                    state.vars.insert(variable.clone(), Value::Array(results));
+                }
+                Ok(Step::Next(node.next_node_id.clone()))
+            }
+            ChainNodeType::Condition => {
+                let condition = node
+                    .condition
+                    .as_ref()
+                    .ok_or_else(|| ChainStop::Failed(format!("ノード {} に条件が定義されていません", node.id)))?;
+                let matched = self.evaluate(condition, state);
+                let next = if matched { node.true_branch.clone() } else { node.false_branch.clone() };
+                self.record_node(
+                    node,
+                    ExecutionStatus::Completed,
+                    json!({ "result": matched, "field": condition.field, "nextNodeId": next }),
+                    &format!("条件 {} は{}でした", condition.field, if matched { "真" } else { "偽" }),
+                );
+                Ok(Step::Next(next))
+            }
+            ChainNodeType::Parallel => {
+                let join_id = node
+                    .join_node_id
+                    .clone()
+                    .ok_or_else(|| ChainStop::Failed(format!("ノード {} に合流するノードがありません", node.id)))?;
+                self.record_node(
+                    node,
+                    ExecutionStatus::Completed,
+                    json!({ "branches": node.branches, "joinNodeId": join_id }),
+                    &format!("{}個のブランチを並列実行します", node.branches.len()),
+                );
+
+                let handles: Vec<_> = node
+                    .branches
+                    .iter()
+                    .map(|branch| {
+                        tauri::async_runtime::spawn(run_path(
+                            self.clone(),
+                            branch.clone(),
+                            Some(join_id.clone()),
+                            state.clone(),
+                        ))
+                    })
+                    .collect();
+
+                // すべてのブランチの終了を待ってから、変数と出力をブランチの順にまとめる
+                let mut branch_outputs = Map::new();
+                let mut stop = None;
+                for (branch, handle) in node.branches.iter().zip(handles) {
+                    match handle.await {
+                        Ok((branch_state, result)) => {
+                            branch_outputs.insert(branch.clone(), branch_state.last_output().cloned().unwrap_or(Value::Null));
+                            state.vars.extend(branch_state.vars);
+                            state.outputs.extend(branch_state.outputs);
+                            if let Err(e) = result {
+                                if stop.is_none() || matches!(e, ChainStop::Cancelled) {
+                                    stop = Some(e);
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            stop.get_or_insert(ChainStop::Failed(format!("ブランチ {} の実行が異常終了しました: {}", branch, e)));
+                        }
+                    }
+                }
+                if let Some(stop) = stop {
+                    return Err(stop);
+                }
+                state.set_output(
+                    &join_id,
+                    json!({ "status": ExecutionStatus::Completed.as_str(), "branches": branch_outputs }),
+                );
+                Ok(Step::Next(Some(join_id)))
+            }
+            ChainNodeType::Join => {
+                let branches = state.outputs.get(&node.id).and_then(|o| o.get("branches")).cloned();
+                self.record_node(
+                    node,
+                    ExecutionStatus::Completed,
+                    json!({ "branches": branches }),
+                    "並列実行のブランチが合流しました",
+                );
+                Ok(Step::Next(node.next_node_id.clone()))
+            }
+            ChainNodeType::End => {
+                let status = match node.status.as_deref() {
+                    Some("failed") => ExecutionStatus::Failed,
+                    _ => ExecutionStatus::Completed,
+                };
+                let message = node.message.clone().filter(|m| !m.trim().is_empty());
+                self.record_node(
+                    node,
+                    status,
+                    json!({ "status": status.as_str(), "message": message }),
+                    message.as_deref().unwrap_or("チェーンを終了しました"),
+                );
+                Ok(Step::End(status, message))
+            }
+        }
+    }
+}
+
+fn lookup_map(map: &Map<String, Value>, segments: &[&str]) -> Option<Value> {
+    let (key, rest) = segments.split_first()?;
+    map.get(*key).and_then(|value| lookup(value, rest)).cloned()
+}
+
+type PathFuture = Pin<Box<dyn Future<Output = (ChainState, PathResult)> + Send>>;
+
+/// startから、次のノードがなくなるかstop_at（並列実行の合流点）に到達するまで実行する
+fn run_path(ctx: Arc<ChainRunContext>, start: String, stop_at: Option<String>, mut state: ChainState) -> PathFuture {
+    Box::pin(async move {
+        let mut current = Some(start);
+        while let Some(node_id) = current.take() {
+            if stop_at.as_deref() == Some(node_id.as_str()) {
+                break;
+            }
+            let step = ctx.visit(&node_id, &mut state).await;
+            ctx.save_progress(&state);
+            match step {
+                Ok(Step::Next(next)) => current = next,
+                Ok(Step::End(status, message)) => return (state, Ok(Some((status, message)))),
+                Err(stop) => return (state, Err(stop)),
+            }
+        }
+        (state, Ok(None))
+    })
+}
+
+/// チェーンを開始ノードから実行し、終了した実行を返す
+pub async fn run_task_chain(handle: TaskChainRunHandle, on_progress: AgentTaskProgressCallback) -> TaskChainRun {
+    let TaskChainRunHandle { run, start_node_id, nodes, tasks, coordinator, input, cancel_rx } = handle;
+    let run_id = run.id.clone();
+    eprintln!("🚀 [task_chain] チェーンの実行を開始します: {} ({})", run.chain_id, run_id);
+
+    let ctx = Arc::new(ChainRunContext {
+        run: Mutex::new(run),
+        nodes,
+        tasks,
+        coordinator,
+        input,
+        cancel_rx,
+        on_progress,
+        path: Mutex::new(Vec::new()),
+        steps: AtomicUsize::new(0),
+    });
+    let (state, result) = run_path(ctx.clone(), start_node_id, None, ChainState::default()).await;
+    ctx.save_progress(&state);
+    running_chain_runs().remove(&run_id);
+
+    let (status, error) = match result {
+        Ok(None) => (ExecutionStatus::Completed, None),
+        Ok(Some((ExecutionStatus::Completed, _))) => (ExecutionStatus::Completed, None),
+        Ok(Some((status, message))) => (status, Some(message.unwrap_or_else(|| "endノードで失敗として終了しました".to_string()))),
+        Err(ChainStop::Failed(e)) => (ExecutionStatus::Failed, Some(e)),
+        Err(ChainStop::Cancelled) => (ExecutionStatus::Cancelled, Some("チェーンの実行がキャンセルされました".to_string())),
+    };
+
+    let mut run = ctx.run.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    run.status = status.as_str().to_string();
+    run.error = error;
+    run.completed_at = Some(now_millis());
+    if let Err(e) = save_run(&run) {
+        eprintln!("⚠️ [task_chain] チェーンの実行結果の保存に失敗しました ({}): {}", run.id, e);
+    }
+    match &run.error {
+        Some(error) => eprintln!("⚠️ [task_chain] チェーンの実行が終了しました: {} ({}): {}", run.id, run.status, error),
+        None => eprintln!("✅ [task_chain] チェーンの実行が完了しました: {}", run.id),
+    }
+    run
+}
@@ -0,0 +1,432 @@
+/**
+ * タスクの依存関係（DAG）とスケジューラー
+ * Task.dependencies（依存タスクIDのJSON配列）を解釈し、依存関係に従ってタスクを実行する
+ *
+ * - タスクの保存時にdependencies / requiredAgentsの形式と、依存関係の循環を検証する
+ * - 依存タスクがすべて完了したタスクから、同時実行数の上限まで並行して実行する（実行はagent_executor）
+ * - 上流タスクの結果（TaskExecution.result）は下流タスクのparameters.upstreamResultsに渡す
+ * - 失敗・キャンセルしたタスクに（推移的に）依存するタスクはskippedとして記録し、
+ *   依存関係のない他のタスクの実行は継続する
+ * - includeDependencies = falseの場合、対象外の依存タスクは最後に完了した実行の結果を使う（失敗したタスク以降の再実行用）
+ */
+
+use rusqlite::Result as SqlResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tokio::sync::mpsc;
+
+use super::agent_executor::{
+    enqueue_task, record_skipped_execution, AgentTaskProgressCallback, ExecutionLink, ExecutionStatus,
+    UPSTREAM_RESULTS_KEY,
+};
+use super::agent_system::{get_all_tasks, get_task_executions, Task, TaskExecution};
+
+const DEFAULT_MAX_CONCURRENCY: usize = 3;
+const MAX_CONCURRENCY: usize = 16;
+
+pub(crate) fn constraint_error(message: String) -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CONSTRAINT),
+        Some(message),
+    )
+}
+
+/// IDのJSON配列（dependencies / requiredAgents）を解釈する（NULL・空文字列は空）
+pub(crate) fn parse_id_list(value: Option<&str>, field: &str) -> Result<Vec<String>, String> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty() && *v != "null") else {
+        return Ok(Vec::new());
+    };
+    let ids: Vec<String> = serde_json::from_str(value)
+        .map_err(|e| format!("{}はIDの配列（JSON）で指定してください: {}", field, e))?;
+    let mut seen = HashSet::new();
+    Ok(ids
+        .into_iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty() && seen.insert(id.clone()))
+        .collect())
+}
+
+fn task_dependencies(task: &Task) -> Result<Vec<String>, String> {
+    parse_id_list(task.dependencies.as_deref(), "dependencies")
+}
+
+/// 保存するタスクのdependencies / requiredAgentsを検証し、依存関係が循環する場合はエラーにする
+/// （まだ保存されていない依存タスクは、保存の順序に依存しないよう許容する）
+pub(crate) fn validate_task_graph(task: &Task) -> SqlResult<()> {
+    parse_id_list(task.required_agents.as_deref(), "requiredAgents").map_err(constraint_error)?;
+    let dependencies = task_dependencies(task).map_err(constraint_error)?;
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+    if dependencies.contains(&task.id) {
+        return Err(constraint_error(format!("タスクは自分自身に依存できません: {}", task.id)));
+    }
+
+    // 保存済みのタスクの依存関係（保存するタスクは新しい内容で置き換える）
+    let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+    for other in get_all_tasks()? {
+        if other.id != task.id {
+            // 既存の不正な値は、このタスクの検証では依存なしとして扱う
+            graph.insert(other.id.clone(), task_dependencies(&other).unwrap_or_default());
+        }
+    }
+    graph.insert(task.id.clone(), dependencies);
+
+    // 新たにできる循環は必ずこのタスクを通るため、このタスクから辿って戻ってくる経路を探す
+    if let Some(cycle) = find_path_back(&graph, &task.id) {
+        return Err(constraint_error(format!("タスクの依存関係が循環しています: {}", cycle.join(" → "))));
+    }
+    Ok(())
+}
+
+/// startから依存関係を辿ってstartに戻る経路（start → … → start）
+fn find_path_back(graph: &HashMap<String, Vec<String>>, start: &str) -> Option<Vec<String>> {
+    let mut visited = HashSet::new();
+    let mut path = vec![start.to_string()];
+    // (ノード, 次に調べる依存のインデックス)
+    let mut stack: Vec<(&str, usize)> = vec![(start, 0)];
+    while let Some((node, index)) = stack.last_mut() {
+        let deps = graph.get(*node).map(Vec::as_slice).unwrap_or(&[]);
+        if *index >= deps.len() {
+            stack.pop();
+            path.pop();
+            continue;
+        }
+        let next = deps[*index].as_str();
+        *index += 1;
+        if next == start {
+            path.push(start.to_string());
+            return Some(path);
+        }
+        if visited.insert(next.to_string()) {
+            path.push(next.to_string());
+            stack.push((next, 0));
+        }
+    }
+    None
+}
+
+/// ワークフロー実行のオプション
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TaskWorkflowOptions {
+    /// 同時に実行するタスク数の上限（既定3）
+    #[serde(rename = "maxConcurrency")]
+    pub max_concurrency: Option<usize>,
+    /// 指定したタスクの依存タスクも（推移的に）実行するか（既定true）
+    #[serde(rename = "includeDependencies")]
+    pub include_dependencies: Option<bool>,
+}
+
+/// ワークフローのタスクごとの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskWorkflowTaskResult {
+    #[serde(rename = "taskId")]
+    pub task_id: String,
+    #[serde(rename = "taskName")]
+    pub task_name: String,
+    #[serde(rename = "executionId")]
+    pub execution_id: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+/// ワークフローの実行結果
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskWorkflowReport {
+    #[serde(rename = "workflowId")]
+    pub workflow_id: String,
+    /// completed（すべて完了）/ partial（一部が失敗・スキップ）/ failed（完了したタスクなし）
+    pub status: String,
+    /// 実行した順序（完了順）のタスクの結果
+    pub tasks: Vec<TaskWorkflowTaskResult>,
+    #[serde(rename = "completedCount")]
+    pub completed_count: usize,
+    #[serde(rename = "failedCount")]
+    pub failed_count: usize,
+    #[serde(rename = "skippedCount")]
+    pub skipped_count: usize,
+}
+
+/// 実行対象のタスクの依存関係
+struct TaskDag {
+    tasks: HashMap<String, Task>,
+    /// タスクID → 対象内の依存タスクID
+    dependencies: HashMap<String, Vec<String>>,
+    /// タスクID → 対象内の依存されているタスクID
+    dependents: HashMap<String, Vec<String>>,
+    /// 対象外の依存タスクの結果（includeDependencies = falseの場合）
+    external_results: HashMap<String, Value>,
+}
+
+/// 実行の結果（resultのJSON）を下流に渡す形に変換
+fn upstream_entry(task: &Task, execution: &TaskExecution) -> Value {
+    let result = execution
+        .result
+        .as_deref()
+        .and_then(|r| serde_json::from_str::<Value>(r).ok())
+        .unwrap_or(Value::Null);
+    let content = result
+        .get("content")
+        .and_then(|c| c.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| result.to_string());
+    json!({
+        "taskName": task.name,
+        "executionId": execution.id,
+        "content": content,
+        "result": result,
+    })
+}
+
+/// 最後に完了した実行
+fn latest_completed_execution(task_id: &str) -> Result<Option<TaskExecution>, String> {
+    let executions = get_task_executions(task_id).map_err(|e| format!("実行履歴の取得に失敗しました: {}", e))?;
+    Ok(executions
+        .into_iter()
+        .filter(|e| e.status == ExecutionStatus::Completed.as_str())
+        .max_by_key(|e| {
+            e.completed_at
+                .as_deref()
+                .and_then(|c| c.parse::<i64>().ok())
+                .unwrap_or(0)
+        }))
+}
+
+fn build_dag(task_ids: &[String], include_dependencies: bool) -> Result<TaskDag, String> {
+    let all: HashMap<String, Task> = get_all_tasks()
+        .map_err(|e| format!("タスク一覧の取得に失敗しました: {}", e))?
+        .into_iter()
+        .map(|task| (task.id.clone(), task))
+        .collect();
+
+    let mut tasks = HashMap::new();
+    let mut external = HashSet::new();
+    let mut queue: VecDeque<String> = task_ids.iter().cloned().collect();
+    for task_id in task_ids {
+        if !all.contains_key(task_id) {
+            return Err(format!("タスクが見つかりません: {}", task_id));
+        }
+    }
+    while let Some(task_id) = queue.pop_front() {
+        if tasks.contains_key(&task_id) {
+            continue;
+        }
+        let task = all
+            .get(&task_id)
+            .ok_or_else(|| format!("依存タスクが見つかりません: {}", task_id))?
+            .clone();
+        for dependency in task_dependencies(&task).map_err(|e| format!("{} ({})", e, task.id))? {
+            if include_dependencies || task_ids.contains(&dependency) {
+                queue.push_back(dependency);
+            } else {
+                external.insert(dependency);
+            }
+        }
+        tasks.insert(task_id, task);
+    }
+
+    let mut external_results = HashMap::new();
+    for task_id in external {
+        let task = all
+            .get(&task_id)
+            .ok_or_else(|| format!("依存タスクが見つかりません: {}", task_id))?;
+        let execution = latest_completed_execution(&task_id)?
+            .ok_or_else(|| format!("依存タスク {} に完了した実行がありません", task.name))?;
+        external_results.insert(task_id, upstream_entry(task, &execution));
+    }
+
+    let mut dependencies = HashMap::new();
+    let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+    for task in tasks.values() {
+        let deps: Vec<String> = task_dependencies(task)?
+            .into_iter()
+            .filter(|d| tasks.contains_key(d))
+            .collect();
+        for dep in &deps {
+            dependents.entry(dep.clone()).or_default().push(task.id.clone());
+        }
+        dependencies.insert(task.id.clone(), deps);
+    }
+
+    // 循環の確認（Kahnのアルゴリズムで全タスクを並べられるか）
+    let mut remaining: HashMap<&str, usize> = dependencies.iter().map(|(id, deps)| (id.as_str(), deps.len())).collect();
+    let mut ready: Vec<&str> = remaining.iter().filter(|(_, n)| **n == 0).map(|(id, _)| *id).collect();
+    let mut ordered = 0;
+    while let Some(id) = ready.pop() {
+        ordered += 1;
+        for dependent in dependents.get(id).map(Vec::as_slice).unwrap_or(&[]) {
+            if let Some(n) = remaining.get_mut(dependent.as_str()) {
+                *n -= 1;
+                if *n == 0 {
+                    ready.push(dependent.as_str());
+                }
+            }
+        }
+    }
+    if ordered != tasks.len() {
+        let mut cyclic: Vec<&str> = remaining.iter().filter(|(_, n)| **n > 0).map(|(id, _)| *id).collect();
+        cyclic.sort();
+        return Err(format!("タスクの依存関係が循環しています: {}", cyclic.join(", ")));
+    }
+
+    Ok(TaskDag { tasks, dependencies, dependents, external_results })
+}
+
+/// 下流タスクのparametersに上流タスクの結果を追加したタスク
+fn with_upstream_results(task: &Task, upstream: Map<String, Value>) -> Task {
+    if upstream.is_empty() {
+        return task.clone();
+    }
+    let mut parameters = match serde_json::from_str::<Value>(&task.parameters) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    parameters.insert(UPSTREAM_RESULTS_KEY.to_string(), Value::Object(upstream));
+    let mut task = task.clone();
+    task.parameters = Value::Object(parameters).to_string();
+    task
+}
+
+/// 依存関係を検証済みの実行待ちワークフロー
+pub struct TaskWorkflow {
+    pub id: String,
+    dag: TaskDag,
+    max_concurrency: usize,
+}
+
+/// 実行するタスクの依存関係を解決・検証する（循環・存在しない依存タスクはエラー）
+pub fn prepare_task_workflow(task_ids: &[String], options: &TaskWorkflowOptions) -> Result<TaskWorkflow, String> {
+    if task_ids.is_empty() {
+        return Err("実行するタスクを指定してください".to_string());
+    }
+    Ok(TaskWorkflow {
+        id: format!("workflow_{}", uuid::Uuid::new_v4()),
+        dag: build_dag(task_ids, options.include_dependencies.unwrap_or(true))?,
+        max_concurrency: options.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY).clamp(1, MAX_CONCURRENCY),
+    })
+}
+
+/// タスクを依存関係に従って実行し、すべてのタスクが終了するまで待つ
+pub async fn run_task_workflow(workflow: TaskWorkflow, on_progress: AgentTaskProgressCallback) -> TaskWorkflowReport {
+    let TaskWorkflow { id: workflow_id, dag, max_concurrency } = workflow;
+    eprintln!(
+        "🚀 [task_dag] ワークフローを開始します: {} ({}タスク, 同時実行数: {})",
+        workflow_id,
+        dag.tasks.len(),
+        max_concurrency
+    );
+
+    let mut waiting: HashMap<String, usize> = dag.dependencies.iter().map(|(id, deps)| (id.clone(), deps.len())).collect();
+    // 優先度の高い順に実行キューへ投入する
+    let mut ready: Vec<String> = waiting.iter().filter(|(_, n)| **n == 0).map(|(id, _)| id.clone()).collect();
+    let mut upstream_results: HashMap<String, Value> = dag.external_results.clone();
+    let mut finished: HashSet<String> = HashSet::new();
+    let mut results = Vec::new();
+    let mut running = 0;
+    let (done_tx, mut done_rx) = mpsc::unbounded_channel::<(String, Result<TaskExecution, String>)>();
+
+    while finished.len() < dag.tasks.len() {
+        ready.sort_by_key(|id| (dag.tasks[id].priority, std::cmp::Reverse(id.clone())));
+        while running < max_concurrency {
+            let Some(task_id) = ready.pop() else { break };
+            let task = &dag.tasks[&task_id];
+            let upstream: Map<String, Value> = task_dependencies(task)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|dep| upstream_results.get(&dep).map(|r| (dep, r.clone())))
+                .collect();
+            let done_tx = done_tx.clone();
+            match enqueue_task(with_upstream_results(task, upstream), ExecutionLink::default(), on_progress.clone()) {
+                Ok((_, done)) => {
+                    tauri::async_runtime::spawn(async move {
+                        let result = done.await.map_err(|_| "実行エンジンから結果を受け取れませんでした".to_string());
+                        let _ = done_tx.send((task_id, result));
+                    });
+                }
+                Err(e) => {
+                    let _ = done_tx.send((task_id, Err(e)));
+                }
+            }
+            running += 1;
+        }
+
+        let Some((task_id, result)) = done_rx.recv().await else { break };
+        running -= 1;
+        finished.insert(task_id.clone());
+        let task = &dag.tasks[&task_id];
+
+        let (execution_id, status, error) = match &result {
+            Ok(execution) => (Some(execution.id.clone()), execution.status.clone(), execution.error.clone()),
+            Err(e) => (None, ExecutionStatus::Failed.as_str().to_string(), Some(e.clone())),
+        };
+        results.push(TaskWorkflowTaskResult {
+            task_id: task_id.clone(),
+            task_name: task.name.clone(),
+            execution_id,
+            status: status.clone(),
+            error,
+        });
+
+        if let (Ok(execution), true) = (&result, status == ExecutionStatus::Completed.as_str()) {
+            upstream_results.insert(task_id.clone(), upstream_entry(task, execution));
+            for dependent in dag.dependents.get(&task_id).map(Vec::as_slice).unwrap_or(&[]) {
+                if let Some(n) = waiting.get_mut(dependent) {
+                    *n -= 1;
+                    if *n == 0 {
+                        ready.push(dependent.clone());
+                    }
+                }
+            }
+            continue;
+        }
+
+        // 失敗・キャンセルしたタスクに推移的に依存するタスクをスキップする
+        eprintln!("⚠️ [task_dag] タスク {} が完了しなかったため、依存するタスクをスキップします", task.name);
+        let mut skip_queue: VecDeque<String> = dag.dependents.get(&task_id).cloned().unwrap_or_default().into();
+        while let Some(dependent_id) = skip_queue.pop_front() {
+            if !finished.insert(dependent_id.clone()) {
+                continue;
+            }
+            let dependent = &dag.tasks[&dependent_id];
+            let reason = format!("依存タスク {} が完了しなかったためスキップしました", task.name);
+            let execution = record_skipped_execution(dependent, &reason, on_progress.clone());
+            if let Err(e) = &execution {
+                eprintln!("⚠️ [task_dag] スキップの記録に失敗しました ({}): {}", dependent_id, e);
+            }
+            results.push(TaskWorkflowTaskResult {
+                task_id: dependent_id.clone(),
+                task_name: dependent.name.clone(),
+                execution_id: execution.ok().map(|e| e.id),
+                status: ExecutionStatus::Skipped.as_str().to_string(),
+                error: Some(reason),
+            });
+            skip_queue.extend(dag.dependents.get(&dependent_id).cloned().unwrap_or_default());
+        }
+    }
+
+    let count = |status: ExecutionStatus| results.iter().filter(|r| r.status == status.as_str()).count();
+    let completed_count = count(ExecutionStatus::Completed);
+    let skipped_count = count(ExecutionStatus::Skipped);
+    let failed_count = results.len() - completed_count - skipped_count;
+    let status = if completed_count == results.len() {
+        "completed"
+    } else if completed_count > 0 {
+        "partial"
+    } else {
+        "failed"
+    };
+    eprintln!(
+        "✅ [task_dag] ワークフローが終了しました: {} ({}, 完了: {}, 失敗: {}, スキップ: {})",
+        workflow_id, status, completed_count, failed_count, skipped_count
+    );
+
+    TaskWorkflowReport {
+        workflow_id,
+        status: status.to_string(),
+        tasks: results,
+        completed_count,
+        failed_count,
+        skipped_count,
+    }
+}
@@ -0,0 +1,380 @@
+/**
+ * 行単位のテキスト差分モジュール
+ * 2つのテキストの最長共通部分列（LCS）から、追加・削除・一致の行リストを作る
+ */
+
+use serde::Serialize;
+
+/// LCSの表を作る行数の積の上限（超える場合は共通の先頭・末尾以外をすべて置き換えとして扱う）
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// 差分の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// 差分の1行
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    /// 変更前の行番号（1始まり、追加行はnull）
+    #[serde(rename = "oldLine")]
+    pub old_line: Option<usize>,
+    /// 変更後の行番号（1始まり、削除行はnull）
+    #[serde(rename = "newLine")]
+    pub new_line: Option<usize>,
+    pub text: String,
+}
+
+/// 差分の結果
+#[derive(Debug, Clone, Serialize)]
+pub struct TextDiff {
+    pub lines: Vec<DiffLine>,
+    #[serde(rename = "addedLines")]
+    pub added_lines: usize,
+    #[serde(rename = "removedLines")]
+    pub removed_lines: usize,
+}
+
+/// 2つの行リストの差分（一致・削除・追加の操作列）
+pub(crate) fn diff_ops<S: AsRef<str>>(old: &[S], new: &[S]) -> Vec<(DiffOp, usize, usize)> {
+    // 共通の先頭・末尾はLCSの計算から除く
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a.as_ref() == b.as_ref()).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.as_ref() == b.as_ref())
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(DiffOp, usize, usize)> = (0..prefix).map(|i| (DiffOp::Equal, i, i)).collect();
+    let (n, m) = (old_mid.len(), new_mid.len());
+    if n.saturating_mul(m) > MAX_LCS_CELLS {
+        ops.extend((0..n).map(|i| (DiffOp::Delete, prefix + i, prefix)));
+        ops.extend((0..m).map(|j| (DiffOp::Insert, prefix + n, prefix + j)));
+    } else {
+        // lcs[i][j] = old_mid[i..] と new_mid[j..] のLCS長
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * (m + 1) + j] = if old_mid[i].as_ref() == new_mid[j].as_ref() {
+                    lcs[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i].as_ref() == new_mid[j].as_ref() {
+                ops.push((DiffOp::Equal, prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j < m && (i == n || lcs[i * (m + 1) + j + 1] > lcs[(i + 1) * (m + 1) + j]) {
+                ops.push((DiffOp::Insert, prefix + i, prefix + j));
+                j += 1;
+            } else {
+                ops.push((DiffOp::Delete, prefix + i, prefix + j));
+                i += 1;
+            }
+        }
+    }
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    ops.extend((0..suffix).map(|k| (DiffOp::Equal, old_end + k, new_end + k)));
+    ops
+}
+
+/// 行単位の差分を計算
+pub fn diff_lines(old_text: &str, new_text: &str) -> TextDiff {
+    let old: Vec<&str> = old_text.lines().collect();
+    let new: Vec<&str> = new_text.lines().collect();
+
+    let mut added_lines = 0;
+    let mut removed_lines = 0;
+    let lines = diff_ops(&old, &new)
+        .into_iter()
+        .map(|(op, i, j)| match op {
+            DiffOp::Equal => DiffLine { op, old_line: Some(i + 1), new_line: Some(j + 1), text: old[i].to_string() },
+            DiffOp::Delete => {
+                removed_lines += 1;
+                DiffLine { op, old_line: Some(i + 1), new_line: None, text: old[i].to_string() }
+            }
+            DiffOp::Insert => {
+                added_lines += 1;
+                DiffLine { op, old_line: None, new_line: Some(j + 1), text: new[j].to_string() }
+            }
+        })
+        .collect();
+
+    TextDiff { lines, added_lines, removed_lines }
+}
+
+/// unified形式の差分のハンク
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffHunk {
+    #[serde(rename = "oldStart")]
+    pub old_start: usize,
+    #[serde(rename = "oldLines")]
+    pub old_lines: usize,
+    #[serde(rename = "newStart")]
+    pub new_start: usize,
+    #[serde(rename = "newLines")]
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+/// unified形式の差分
+#[derive(Debug, Clone, Serialize)]
+pub struct UnifiedDiff {
+    pub hunks: Vec<DiffHunk>,
+    /// `diff -u` 形式のテキスト
+    pub unified: String,
+    #[serde(rename = "addedLines")]
+    pub added_lines: usize,
+    #[serde(rename = "removedLines")]
+    pub removed_lines: usize,
+}
+
+/// 行単位の差分をunified形式（前後context行のハンク）にまとめる
+pub fn unified_diff(old_text: &str, new_text: &str, old_label: &str, new_label: &str, context: usize) -> UnifiedDiff {
+    let TextDiff { lines, added_lines, removed_lines } = diff_lines(old_text, new_text);
+
+    // 変更行の前後context行を1つの範囲とし、重なる・隣接する範囲をつなげる
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.op == DiffOp::Equal {
+            continue;
+        }
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(lines.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    let mut unified = String::new();
+    if !ranges.is_empty() {
+        unified.push_str(&format!("--- {}\n+++ {}\n", old_label, new_label));
+    }
+    let hunks: Vec<DiffHunk> = ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let old_before = lines[..start].iter().filter(|l| l.old_line.is_some()).count();
+            let new_before = lines[..start].iter().filter(|l| l.new_line.is_some()).count();
+            let hunk_lines = lines[start..end].to_vec();
+            let old_lines = hunk_lines.iter().filter(|l| l.old_line.is_some()).count();
+            let new_lines = hunk_lines.iter().filter(|l| l.new_line.is_some()).count();
+            // diff -u と同じく、行数が0のハンクの開始行は直前の行番号にする
+            let old_start = if old_lines == 0 { old_before } else { old_before + 1 };
+            let new_start = if new_lines == 0 { new_before } else { new_before + 1 };
+
+            unified.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_lines, new_start, new_lines));
+            for line in &hunk_lines {
+                let marker = match line.op {
+                    DiffOp::Equal => ' ',
+                    DiffOp::Delete => '-',
+                    DiffOp::Insert => '+',
+                };
+                unified.push(marker);
+                unified.push_str(&line.text);
+                unified.push('\n');
+            }
+            DiffHunk { old_start, old_lines, new_start, new_lines, lines: hunk_lines }
+        })
+        .collect();
+
+    UnifiedDiff { hunks, unified, added_lines, removed_lines }
+}
+
+/// 単語単位の差分の区間（同じ種類の連続したトークンをまとめたもの）
+#[derive(Debug, Clone, Serialize)]
+pub struct WordDiffSegment {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// 単語単位の差分
+#[derive(Debug, Clone, Serialize)]
+pub struct WordDiff {
+    pub segments: Vec<WordDiffSegment>,
+    #[serde(rename = "addedWords")]
+    pub added_words: usize,
+    #[serde(rename = "removedWords")]
+    pub removed_words: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Word,
+    Kanji,
+    Hiragana,
+    Katakana,
+    Other,
+}
+
+fn char_class(c: char) -> CharClass {
+    match c {
+        '\n' => CharClass::Other,
+        c if c.is_whitespace() => CharClass::Space,
+        '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '々' => CharClass::Kanji,
+        '\u{3040}'..='\u{309F}' => CharClass::Hiragana,
+        '\u{30A0}'..='\u{30FF}' => CharClass::Katakana,
+        c if c.is_alphanumeric() || c == '_' => CharClass::Word,
+        _ => CharClass::Other,
+    }
+}
+
+/// テキストを単語に分割する（英数字・空白・漢字・ひらがな・カタカナの連続をそれぞれ1語、記号と改行は1文字ずつ）
+fn tokenize_words(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut current: Option<CharClass> = None;
+    for (index, c) in text.char_indices() {
+        let class = char_class(c);
+        if let Some(previous) = current {
+            if previous != class || class == CharClass::Other {
+                tokens.push(&text[start..index]);
+                start = index;
+            }
+        }
+        current = Some(class);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// 単語単位の差分を計算
+pub fn diff_words(old_text: &str, new_text: &str) -> WordDiff {
+    let old = tokenize_words(old_text);
+    let new = tokenize_words(new_text);
+
+    let mut segments: Vec<WordDiffSegment> = Vec::new();
+    let mut added_words = 0;
+    let mut removed_words = 0;
+    for (op, i, j) in diff_ops(&old, &new) {
+        let token = match op {
+            DiffOp::Equal | DiffOp::Delete => old[i],
+            DiffOp::Insert => new[j],
+        };
+        if !token.trim().is_empty() {
+            match op {
+                DiffOp::Insert => added_words += 1,
+                DiffOp::Delete => removed_words += 1,
+                DiffOp::Equal => {}
+            }
+        }
+        match segments.last_mut() {
+            Some(last) if last.op == op => last.text.push_str(token),
+            _ => segments.push(WordDiffSegment { op, text: token.to_string() }),
+        }
+    }
+
+    WordDiff { segments, added_words, removed_words }
+}
+
+/// 3-wayマージの競合箇所
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeConflict {
+    /// マージ結果で競合マーカーが始まる行（1始まり）
+    #[serde(rename = "mergedLine")]
+    pub merged_line: usize,
+    /// ベースでの開始行（1始まり）
+    #[serde(rename = "baseLine")]
+    pub base_line: usize,
+    pub base: Vec<String>,
+    pub current: Vec<String>,
+    pub incoming: Vec<String>,
+}
+
+/// 3-wayマージの結果
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeResult {
+    /// マージ結果（競合箇所は競合マーカー付き）
+    pub merged: String,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// 競合マーカーに表示するラベル
+pub(crate) struct MergeLabels<'a> {
+    pub current: &'a str,
+    pub base: &'a str,
+    pub incoming: &'a str,
+}
+
+/// ベースの各行が変更後のどの行と一致するか
+fn base_matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+    for (op, i, j) in diff_ops(base, other) {
+        if op == DiffOp::Equal {
+            matches[i] = Some(j);
+        }
+    }
+    matches
+}
+
+/// 共通のベースから別々に編集された2つのテキストを行単位でマージする（diff3と同じ方式）
+pub(crate) fn merge3(base_text: &str, current_text: &str, incoming_text: &str, labels: &MergeLabels) -> MergeResult {
+    let base: Vec<&str> = base_text.lines().collect();
+    let current: Vec<&str> = current_text.lines().collect();
+    let incoming: Vec<&str> = incoming_text.lines().collect();
+    let current_matches = base_matches(&base, &current);
+    let incoming_matches = base_matches(&base, &incoming);
+
+    let mut merged: Vec<String> = Vec::new();
+    let mut conflicts = Vec::new();
+    let (mut i, mut j, mut k) = (0, 0, 0);
+    loop {
+        // 両方の編集で変わっていないベースの次の行（安定行）を探す
+        let stable = (i..base.len()).find_map(|b| match (current_matches[b], incoming_matches[b]) {
+            (Some(cj), Some(ik)) if cj >= j && ik >= k => Some((b, cj, ik)),
+            _ => None,
+        });
+        let (b_end, c_end, i_end) = stable.unwrap_or((base.len(), current.len(), incoming.len()));
+
+        let base_chunk = &base[i..b_end];
+        let current_chunk = &current[j..c_end];
+        let incoming_chunk = &incoming[k..i_end];
+        if current_chunk == base_chunk || current_chunk == incoming_chunk {
+            merged.extend(incoming_chunk.iter().map(|s| s.to_string()));
+        } else if incoming_chunk == base_chunk {
+            merged.extend(current_chunk.iter().map(|s| s.to_string()));
+        } else {
+            conflicts.push(MergeConflict {
+                merged_line: merged.len() + 1,
+                base_line: i + 1,
+                base: base_chunk.iter().map(|s| s.to_string()).collect(),
+                current: current_chunk.iter().map(|s| s.to_string()).collect(),
+                incoming: incoming_chunk.iter().map(|s| s.to_string()).collect(),
+            });
+            merged.push(format!("<<<<<<< {}", labels.current));
+            merged.extend(current_chunk.iter().map(|s| s.to_string()));
+            merged.push(format!("||||||| {}", labels.base));
+            merged.extend(base_chunk.iter().map(|s| s.to_string()));
+            merged.push("=======".to_string());
+            merged.extend(incoming_chunk.iter().map(|s| s.to_string()));
+            merged.push(format!(">>>>>>> {}", labels.incoming));
+        }
+
+        let Some((b, cj, ik)) = stable else { break };
+        merged.push(base[b].to_string());
+        i = b + 1;
+        j = cj + 1;
+        k = ik + 1;
+    }
+
+    let mut merged = merged.join("\n");
+    if !merged.is_empty() && (current_text.ends_with('\n') || incoming_text.ends_with('\n')) {
+        merged.push('\n');
+    }
+    MergeResult { merged, conflicts }
+}
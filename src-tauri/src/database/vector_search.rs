@@ -1,40 +1,44 @@
 /**
  * ベクトル検索モジュール
  * RustネイティブのHNSWアルゴリズムを使用した高速ベクトル検索機能を提供
- * 
+ *
  * 使用ライブラリ: hnsw_rs
  * - Rustネイティブで動作（サーバー不要）
  * - ローカルファイルに保存可能（シリアライゼーション対応）
  * - 高速な近似最近傍検索（HNSWアルゴリズム）
  * - コサイン類似度、ユークリッド距離など複数の距離指標をサポート
+ *
+ * ChromaDBとHNSWは共通の`VectorStore`トレイトを実装する。
+ * 使用するバックエンドは環境変数`VECTOR_STORE_BACKEND`（hnsw / chromadb）で選択し、
+ * 未設定の場合は既存のベクトルが保存されているChromaDBを使用する（HNSWは明示的に指定した場合のみ。既存のベクトルは引き継がない）。
  */
 
-// 注意: 現在はChromaDBを使用するため、hnsw_rsは使用されていません
-// use hnsw_rs::dist::DistCosine;
-// use hnsw_rs::hnsw::Hnsw;
+use hnsw_rs::prelude::{DistCosine, Hnsw};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::future::Future;
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
-use serde_json::Value;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
 
-// ベクトル検索インデックスのラッパー
-// 注意: 現在はChromaDBを使用するため、この実装は使用されていません
-#[allow(dead_code)]
-struct VectorIndex {
-    // hnsw: Hnsw<f32, DistCosine>, // コメントアウト
-    id_to_index: HashMap<String, usize>, // エンティティID -> インデックス番号
-    index_to_id: HashMap<usize, String>, // インデックス番号 -> エンティティID
-    metadata: HashMap<String, HashMap<String, Value>>, // エンティティID -> メタデータ
-    next_index: usize,
-}
+use super::chromadb::{ChromaVectorStore, TopicSearchResult};
+use super::embedding_registry::{self, RegistryVectorStore};
 
 // コレクション名の定義
 pub const COLLECTION_ENTITIES: &str = "entities";
 pub const COLLECTION_RELATIONS: &str = "relations";
 pub const COLLECTION_TOPICS: &str = "topics";
 pub const COLLECTION_PAGES: &str = "pages";
+pub const COLLECTION_DESIGN_DOCS: &str = "design_docs";
+pub const COLLECTION_MEETING_NOTES: &str = "meeting_notes";
+pub const COLLECTION_GRAPHVIZ_YAML_FILES: &str = "graphviz_yaml_files";
+pub const COLLECTION_GRAPHVIZ_DOT_FILES: &str = "graphviz_dot_files";
 
-// 埋め込み次元数（text-embedding-3-smallの場合）
+// 埋め込み次元数のデフォルト（text-embedding-3-smallの場合）
+// コレクションごとの実際の次元数はembedding_registryに記録される
 pub const EMBEDDING_DIMENSION: usize = 1536;
 
 // HNSWパラメータ
@@ -43,124 +47,1176 @@ const NB_LAYERS: usize = 12; // レイヤー数
 const EF_CONSTRUCTION: usize = 200; // 構築時の動的リストサイズ
 const EF_SEARCH: usize = 50; // 検索時の動的リストサイズ
 
-// インデックスのストレージ
-// 注意: 現在はChromaDBを使用するため、この実装は使用されていません
-#[allow(dead_code)]
-static VECTOR_INDICES: Mutex<Option<HashMap<String, Arc<Mutex<VectorIndex>>>>> = Mutex::new(None);
+// メタデータフィルタに一致する点がこの件数以下の場合は、HNSWを使わず全件の類似度を計算する
+// （絞り込みが強いとHNSWのグラフ探索では近傍に一致する点が見つからず、limit件に満たないことがあるため）
+const EXACT_SEARCH_THRESHOLD: usize = 2048;
 
-/**
- * ベクトル検索インデックスを初期化
- * 
- * @param collection_name コレクション名
- * @param data_dir データディレクトリパス
- */
-// 注意: 現在はChromaDBを使用するため、この実装は使用されていません
-// コンパイルエラーを回避するための空実装
-#[allow(dead_code)]
-pub async fn init_vector_index(
-    _collection_name: &str,
-    _data_dir: PathBuf,
-) -> Result<(), String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(())
+// 削除済みノードがこの件数を超え、かつ有効件数より多くなったらインデックスを再構築
+const REBUILD_TOMBSTONE_THRESHOLD: usize = 256;
+
+/// ベクトルストアの非同期処理の戻り値
+pub type VectorStoreFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, String>> + Send + 'a>>;
+
+/// ベクトルストアのバックエンド種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VectorBackend {
+    #[serde(rename = "hnsw")]
+    Hnsw,
+    #[serde(rename = "chromadb")]
+    ChromaDB,
 }
 
-/**
- * エンティティ埋め込みを保存
- * 注意: 現在はChromaDBを使用するため、この実装は使用されていません
- */
-#[allow(dead_code)]
+/// 保存済みのベクトル（埋め込み + メタデータ）
+#[derive(Debug, Clone)]
+pub struct VectorRecord {
+    pub id: String,
+    pub embedding: Vec<f32>,
+    pub metadata: Map<String, Value>,
+}
+
+/// 類似検索の1件分の結果
+#[derive(Debug, Clone)]
+pub struct VectorMatch {
+    pub id: String,
+    pub similarity: f32,
+    pub metadata: Map<String, Value>,
+}
+
+/// メタデータの比較条件
+#[derive(Debug, Clone)]
+pub enum MetadataCondition {
+    /// 値が等しい
+    Eq { key: String, value: Value },
+    /// 数値が指定値以上
+    Gte { key: String, value: f64 },
+    /// 数値が指定値以下
+    Lte { key: String, value: f64 },
+}
+
+/// 類似検索のメタデータフィルタ（すべての条件をANDで結合）
+/// ChromaDBでは`where`句に変換し、HNSWでは検索時に各点のメタデータと照合する
+#[derive(Debug, Clone, Default)]
+pub struct MetadataFilter {
+    pub conditions: Vec<MetadataCondition>,
+}
+
+impl MetadataFilter {
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    fn push_eq(&mut self, key: &str, value: &Option<String>) {
+        if let Some(value) = value.as_ref().filter(|v| !v.is_empty()) {
+            self.conditions.push(MetadataCondition::Eq { key: key.to_string(), value: Value::String(value.clone()) });
+        }
+    }
+
+    /// ChromaDBの`where`句に変換（条件が無い場合はNone）
+    pub fn to_chroma_where(&self) -> Option<Value> {
+        let mut clauses: Vec<Value> = self.conditions.iter().map(|condition| {
+            let (key, op, value) = match condition {
+                MetadataCondition::Eq { key, value } => (key, "$eq", value.clone()),
+                MetadataCondition::Gte { key, value } => (key, "$gte", serde_json::json!(value)),
+                MetadataCondition::Lte { key, value } => (key, "$lte", serde_json::json!(value)),
+            };
+            serde_json::json!({ key.as_str(): { op: value } })
+        }).collect();
+        match clauses.len() {
+            0 => None,
+            1 => clauses.pop(),
+            _ => Some(serde_json::json!({ "$and": clauses })),
+        }
+    }
+
+    /// メタデータが条件を満たすか（キーが無い場合は満たさない）
+    pub fn matches(&self, metadata: &Map<String, Value>) -> bool {
+        self.conditions.iter().all(|condition| match condition {
+            MetadataCondition::Eq { key, value } => metadata.get(key).map(|v| metadata_value_eq(v, value)).unwrap_or(false),
+            MetadataCondition::Gte { key, value } => metadata.get(key).and_then(metadata_number).map(|n| n >= *value).unwrap_or(false),
+            MetadataCondition::Lte { key, value } => metadata.get(key).and_then(metadata_number).map(|n| n <= *value).unwrap_or(false),
+        })
+    }
+}
+
+fn metadata_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+fn metadata_value_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(_), Value::Number(_)) => metadata_number(a) == metadata_number(b),
+        _ => a == b,
+    }
+}
+
+/// トピックのtopicDateを数値で保存するメタデータキー（ChromaDBの範囲検索は数値のみ対応のため）
+pub const TOPIC_DATE_TIMESTAMP_KEY: &str = "topicDateTimestamp";
+
+/// topicDateのメタデータ値をUnix秒に変換
+pub fn topic_date_timestamp(value: Option<&Value>) -> Option<i64> {
+    value.and_then(super::collection_query::parse_date_value)
+}
+
+/// エンティティ類似検索のフィルタ
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntitySearchFilter {
+    /// エンティティの種類（メタデータの`type`）
+    #[serde(rename = "type")]
+    pub entity_type: Option<String>,
+}
+
+impl EntitySearchFilter {
+    pub fn to_metadata_filter(&self) -> Result<MetadataFilter, String> {
+        let mut filter = MetadataFilter::default();
+        filter.push_eq("type", &self.entity_type);
+        Ok(filter)
+    }
+}
+
+/// リレーション類似検索のフィルタ
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelationSearchFilter {
+    #[serde(rename = "relationType")]
+    pub relation_type: Option<String>,
+    #[serde(rename = "topicId")]
+    pub topic_id: Option<String>,
+}
+
+impl RelationSearchFilter {
+    pub fn to_metadata_filter(&self) -> Result<MetadataFilter, String> {
+        let mut filter = MetadataFilter::default();
+        filter.push_eq("relationType", &self.relation_type);
+        filter.push_eq("topicId", &self.topic_id);
+        Ok(filter)
+    }
+}
+
+/// トピック類似検索のフィルタ
+/// 日付範囲は保存時に付与されるtopicDateTimestampで絞り込むため、付与前に保存された埋め込みは範囲検索の対象外になる
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TopicSearchFilter {
+    #[serde(rename = "semanticCategory")]
+    pub semantic_category: Option<String>,
+    #[serde(rename = "meetingNoteId")]
+    pub meeting_note_id: Option<String>,
+    /// topicDateの範囲の開始（ISO 8601文字列・YYYY-MM-DD・Unix秒）
+    #[serde(rename = "topicDateFrom")]
+    pub topic_date_from: Option<Value>,
+    /// topicDateの範囲の終了（この日時を含む）
+    #[serde(rename = "topicDateTo")]
+    pub topic_date_to: Option<Value>,
+}
+
+impl TopicSearchFilter {
+    pub fn to_metadata_filter(&self) -> Result<MetadataFilter, String> {
+        let mut filter = MetadataFilter::default();
+        filter.push_eq("semanticCategory", &self.semantic_category);
+        filter.push_eq("meetingNoteId", &self.meeting_note_id);
+        for (value, is_from) in [(&self.topic_date_from, true), (&self.topic_date_to, false)] {
+            let Some(value) = value.as_ref().filter(|v| !v.is_null()) else {
+                continue;
+            };
+            let seconds = topic_date_timestamp(Some(value))
+                .ok_or_else(|| format!("topicDateの範囲を日付として解釈できません: {}", value))? as f64;
+            let key = TOPIC_DATE_TIMESTAMP_KEY.to_string();
+            filter.conditions.push(if is_from {
+                MetadataCondition::Gte { key, value: seconds }
+            } else {
+                MetadataCondition::Lte { key, value: seconds }
+            });
+        }
+        Ok(filter)
+    }
+}
+
+/// ベクトルストアの共通インターフェース
+/// ChromaDB（chromadb.rs）とHNSW（このモジュール）の両方が実装する
+pub trait VectorStore: Send + Sync {
+    /// バックエンド種別
+    fn backend(&self) -> VectorBackend;
+
+    /// 埋め込みを追加または更新
+    fn upsert(
+        &self,
+        collection: String,
+        id: String,
+        embedding: Vec<f32>,
+        metadata: Map<String, Value>,
+    ) -> VectorStoreFuture<'_, ()>;
+
+    /// IDを指定して取得
+    fn get(&self, collection: String, id: String) -> VectorStoreFuture<'_, Option<VectorRecord>>;
+
+    /// 類似ベクトルを検索（類似度の降順、filterを指定した場合は条件を満たす点のみ）
+    fn query(
+        &self,
+        collection: String,
+        embedding: Vec<f32>,
+        limit: usize,
+        filter: Option<MetadataFilter>,
+    ) -> VectorStoreFuture<'_, Vec<VectorMatch>>;
+
+    /// IDを指定して削除
+    fn delete(&self, collection: String, ids: Vec<String>) -> VectorStoreFuture<'_, ()>;
+
+    /// コレクションの件数
+    fn count(&self, collection: String) -> VectorStoreFuture<'_, usize>;
+
+    /// コレクション内の全IDを取得
+    fn list_ids(&self, collection: String) -> VectorStoreFuture<'_, Vec<String>>;
+
+    /// 存在するコレクション名の一覧
+    fn list_collections(&self) -> VectorStoreFuture<'_, Vec<String>>;
+
+    /// コレクションを削除（存在しない場合は何もしない）
+    fn delete_collection(&self, collection: String) -> VectorStoreFuture<'_, ()>;
+}
+
+// ========== バックエンドの選択 ==========
+
+static VECTOR_BACKEND: OnceLock<VectorBackend> = OnceLock::new();
+static VECTOR_STORE_DIR: OnceLock<PathBuf> = OnceLock::new();
+static HNSW_STORE: OnceLock<Arc<HnswVectorStore>> = OnceLock::new();
+
+/// 使用するバックエンドを取得（環境変数VECTOR_STORE_BACKEND、デフォルトはchromadb）
+pub fn vector_backend() -> VectorBackend {
+    *VECTOR_BACKEND.get_or_init(|| {
+        let backend = match std::env::var("VECTOR_STORE_BACKEND")
+            .map(|s| s.trim().to_lowercase())
+            .as_deref()
+        {
+            Ok("hnsw") => VectorBackend::Hnsw,
+            _ => VectorBackend::ChromaDB,
+        };
+        eprintln!("🔧 ベクトルストアのバックエンド: {:?}", backend);
+        backend
+    })
+}
+
+/// HNSWインデックスの保存先を設定（app.dbと同じディレクトリ配下）
+pub fn init_vector_store(data_dir: PathBuf) {
+    if VECTOR_STORE_DIR.set(data_dir.clone()).is_err() {
+        eprintln!("⚠️ ベクトルインデックスの保存先は既に設定されています");
+        return;
+    }
+    eprintln!("✅ ベクトルインデックスの保存先: {}", data_dir.display());
+}
+
+/// 保存先が未設定の場合のデフォルト（データベースディレクトリ/vector_indexes）
+fn default_vector_store_dir() -> Result<PathBuf, String> {
+    let db_dir_name = if cfg!(debug_assertions) {
+        "network-mock-local-dev"
+    } else {
+        "network-mock-local"
+    };
+    dirs::data_dir()
+        .map(|dir| dir.join(db_dir_name).join("vector_indexes"))
+        .ok_or_else(|| "データディレクトリを取得できませんでした".to_string())
+}
+
+/// ベクトルインデックス・埋め込みレジストリの保存先
+pub(crate) fn vector_store_dir() -> Result<PathBuf, String> {
+    match VECTOR_STORE_DIR.get() {
+        Some(dir) => Ok(dir.clone()),
+        None => default_vector_store_dir(),
+    }
+}
+
+fn hnsw_store() -> Result<Arc<HnswVectorStore>, String> {
+    if let Some(store) = HNSW_STORE.get() {
+        return Ok(store.clone());
+    }
+    let data_dir = vector_store_dir()?;
+    Ok(HNSW_STORE
+        .get_or_init(|| Arc::new(HnswVectorStore::new(data_dir)))
+        .clone())
+}
+
+/// 選択されているバックエンドのベクトルストアを取得
+/// コレクション名は論理名で指定する（埋め込みレジストリで物理コレクションに変換し、次元数を確認する）
+pub fn vector_store() -> Result<Arc<dyn VectorStore>, String> {
+    Ok(Arc::new(RegistryVectorStore::new(raw_vector_store()?)))
+}
+
+/// レジストリを介さずにバックエンドのベクトルストアを取得（物理コレクション名で操作する）
+pub(crate) fn raw_vector_store() -> Result<Arc<dyn VectorStore>, String> {
+    match vector_backend() {
+        VectorBackend::Hnsw => Ok(hnsw_store()?),
+        VectorBackend::ChromaDB => Ok(Arc::new(ChromaVectorStore)),
+    }
+}
+
+/// HNSWバックエンドが選択されている場合のみベクトルストアを返す
+/// chromadb.rsの各関数はこれがSomeの場合、ChromaDBサーバーを使わずにこのモジュールへ委譲する
+pub fn native_vector_store() -> Result<Option<Arc<dyn VectorStore>>, String> {
+    let store = vector_store()?;
+    Ok(if store.backend() == VectorBackend::Hnsw { Some(store) } else { None })
+}
+
+// ========== HNSWバックエンド ==========
+
+/// 追記ログの1行（起動時に再生してインデックスを復元する）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op")]
+enum IndexLogEntry {
+    #[serde(rename = "upsert")]
+    Upsert {
+        id: String,
+        embedding: Vec<f32>,
+        metadata: Map<String, Value>,
+    },
+    #[serde(rename = "delete")]
+    Delete { id: String },
+}
+
+// ベクトル検索インデックスのラッパー
+// hnsw_rsは削除に対応していないため、削除・更新された点は墓標として残し、検索時に除外する
+struct VectorIndex {
+    hnsw: Hnsw<'static, f32, DistCosine>,
+    dimension: Option<usize>,
+    id_to_index: HashMap<String, usize>, // エンティティID -> インデックス番号
+    index_to_id: HashMap<usize, String>, // インデックス番号 -> エンティティID（有効な点のみ）
+    embeddings: HashMap<usize, Vec<f32>>, // インデックス番号 -> 埋め込み（再構築・取得用）
+    metadata: HashMap<String, Map<String, Value>>, // エンティティID -> メタデータ
+    next_index: usize,
+    log_path: PathBuf,
+    log_entries: usize,
+}
+
+fn new_hnsw(capacity: usize) -> Hnsw<'static, f32, DistCosine> {
+    Hnsw::new(
+        MAX_NB_CONNECTION,
+        capacity.max(1_000),
+        NB_LAYERS,
+        EF_CONSTRUCTION,
+        DistCosine {},
+    )
+}
+
+impl VectorIndex {
+    /// ログファイルからインデックスを復元（ファイルがなければ空のインデックス）
+    fn load(log_path: PathBuf) -> Result<Self, String> {
+        let mut records: HashMap<String, (Vec<f32>, Map<String, Value>)> = HashMap::new();
+        let mut order: Vec<String> = Vec::new();
+        let mut log_entries = 0;
+
+        if log_path.exists() {
+            let file = File::open(&log_path)
+                .map_err(|e| format!("ベクトルインデックスの読み込みに失敗しました: {}", e))?;
+            for (line_no, line) in BufReader::new(file).lines().enumerate() {
+                let line = line
+                    .map_err(|e| format!("ベクトルインデックスの読み込みに失敗しました: {}", e))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                log_entries += 1;
+                match serde_json::from_str::<IndexLogEntry>(&line) {
+                    Ok(IndexLogEntry::Upsert { id, embedding, metadata }) => {
+                        if !records.contains_key(&id) {
+                            order.push(id.clone());
+                        }
+                        records.insert(id, (embedding, metadata));
+                    }
+                    Ok(IndexLogEntry::Delete { id }) => {
+                        records.remove(&id);
+                    }
+                    Err(e) => {
+                        // 書き込み途中で終了した場合など、壊れた行は読み飛ばす
+                        eprintln!(
+                            "⚠️ [vector_search] {} の {}行目を読み飛ばしました: {}",
+                            log_path.display(),
+                            line_no + 1,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut index = VectorIndex {
+            hnsw: new_hnsw(records.len()),
+            dimension: None,
+            id_to_index: HashMap::new(),
+            index_to_id: HashMap::new(),
+            embeddings: HashMap::new(),
+            metadata: HashMap::new(),
+            next_index: 0,
+            log_path,
+            log_entries,
+        };
+        for id in order {
+            if let Some((embedding, metadata)) = records.remove(&id) {
+                index.insert_point(id, embedding, metadata);
+            }
+        }
+
+        // 上書き・削除が多く溜まったログは起動時に詰め直す
+        if index.log_entries > index.len() * 2 + REBUILD_TOMBSTONE_THRESHOLD {
+            index.compact_log()?;
+        }
+
+        Ok(index)
+    }
+
+    fn len(&self) -> usize {
+        self.index_to_id.len()
+    }
+
+    fn check_dimension(&self, embedding: &[f32]) -> Result<(), String> {
+        if embedding.is_empty() {
+            return Err("埋め込みベクトルが空です".to_string());
+        }
+        match self.dimension {
+            Some(dim) if dim != embedding.len() => Err(format!(
+                "埋め込みの次元数が一致しません（コレクション: {}次元, 入力: {}次元）",
+                dim,
+                embedding.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// メモリ上のインデックスに点を追加（既存IDは墓標化してから追加）
+    fn insert_point(&mut self, id: String, embedding: Vec<f32>, metadata: Map<String, Value>) {
+        if let Some(old_index) = self.id_to_index.remove(&id) {
+            self.index_to_id.remove(&old_index);
+            self.embeddings.remove(&old_index);
+        }
+        let index = self.next_index;
+        self.next_index += 1;
+        self.hnsw.insert((&embedding, index));
+        if self.dimension.is_none() {
+            self.dimension = Some(embedding.len());
+        }
+        self.id_to_index.insert(id.clone(), index);
+        self.index_to_id.insert(index, id.clone());
+        self.embeddings.insert(index, embedding);
+        self.metadata.insert(id, metadata);
+    }
+
+    fn remove_point(&mut self, id: &str) -> bool {
+        match self.id_to_index.remove(id) {
+            Some(index) => {
+                self.index_to_id.remove(&index);
+                self.embeddings.remove(&index);
+                self.metadata.remove(id);
+                if self.index_to_id.is_empty() {
+                    self.dimension = None;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn append_log(&mut self, entry: &IndexLogEntry) -> Result<(), String> {
+        if let Some(parent) = self.log_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("ベクトルインデックスの保存先の作成に失敗しました: {}", e))?;
+        }
+        let line = serde_json::to_string(entry)
+            .map_err(|e| format!("ベクトルインデックスのシリアライズに失敗しました: {}", e))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_path)
+            .map_err(|e| format!("ベクトルインデックスの保存に失敗しました: {}", e))?;
+        writeln!(file, "{}", line)
+            .map_err(|e| format!("ベクトルインデックスの保存に失敗しました: {}", e))?;
+        self.log_entries += 1;
+        Ok(())
+    }
+
+    fn upsert(&mut self, id: String, embedding: Vec<f32>, metadata: Map<String, Value>) -> Result<(), String> {
+        self.check_dimension(&embedding)?;
+        // 先にログへ書き込み、失敗した場合はメモリ上のインデックスを変更しない
+        self.append_log(&IndexLogEntry::Upsert {
+            id: id.clone(),
+            embedding: embedding.clone(),
+            metadata: metadata.clone(),
+        })?;
+        self.insert_point(id, embedding, metadata);
+        self.rebuild_if_needed()
+    }
+
+    fn delete(&mut self, id: &str) -> Result<(), String> {
+        if !self.id_to_index.contains_key(id) {
+            return Ok(());
+        }
+        self.append_log(&IndexLogEntry::Delete { id: id.to_string() })?;
+        self.remove_point(id);
+        self.rebuild_if_needed()
+    }
+
+    /// 墓標が増えすぎた場合、有効な点だけでHNSWグラフとログを作り直す
+    fn rebuild_if_needed(&mut self) -> Result<(), String> {
+        let tombstones = self.next_index - self.len();
+        if tombstones <= REBUILD_TOMBSTONE_THRESHOLD || tombstones <= self.len() {
+            return Ok(());
+        }
+        let mut live: Vec<(usize, String)> = self.index_to_id.drain().collect();
+        live.sort_by_key(|(index, _)| *index);
+        let mut embeddings = std::mem::take(&mut self.embeddings);
+        let mut metadata = std::mem::take(&mut self.metadata);
+        self.hnsw = new_hnsw(live.len());
+        self.id_to_index.clear();
+        self.next_index = 0;
+        for (old_index, id) in live {
+            if let Some(embedding) = embeddings.remove(&old_index) {
+                let meta = metadata.remove(&id).unwrap_or_default();
+                self.insert_point(id, embedding, meta);
+            }
+        }
+        self.compact_log()
+    }
+
+    /// 現在の有効な点だけを書き出したログで置き換える
+    fn compact_log(&mut self) -> Result<(), String> {
+        let tmp_path = self.log_path.with_extension("jsonl.tmp");
+        if let Some(parent) = self.log_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("ベクトルインデックスの保存先の作成に失敗しました: {}", e))?;
+        }
+        {
+            let file = File::create(&tmp_path)
+                .map_err(|e| format!("ベクトルインデックスの書き出しに失敗しました: {}", e))?;
+            let mut writer = BufWriter::new(file);
+            let mut live: Vec<(&usize, &String)> = self.index_to_id.iter().collect();
+            live.sort_by_key(|(index, _)| **index);
+            for (index, id) in live {
+                let entry = IndexLogEntry::Upsert {
+                    id: id.clone(),
+                    embedding: self.embeddings.get(index).cloned().unwrap_or_default(),
+                    metadata: self.metadata.get(id).cloned().unwrap_or_default(),
+                };
+                let line = serde_json::to_string(&entry)
+                    .map_err(|e| format!("ベクトルインデックスのシリアライズに失敗しました: {}", e))?;
+                writeln!(writer, "{}", line)
+                    .map_err(|e| format!("ベクトルインデックスの書き出しに失敗しました: {}", e))?;
+            }
+            writer.flush()
+                .map_err(|e| format!("ベクトルインデックスの書き出しに失敗しました: {}", e))?;
+        }
+        fs::rename(&tmp_path, &self.log_path)
+            .map_err(|e| format!("ベクトルインデックスの置き換えに失敗しました: {}", e))?;
+        self.log_entries = self.len();
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Option<VectorRecord> {
+        let index = self.id_to_index.get(id)?;
+        Some(VectorRecord {
+            id: id.to_string(),
+            embedding: self.embeddings.get(index).cloned().unwrap_or_default(),
+            metadata: self.metadata.get(id).cloned().unwrap_or_default(),
+        })
+    }
+
+    fn search(&self, query: &[f32], limit: usize, filter: Option<&MetadataFilter>) -> Result<Vec<VectorMatch>, String> {
+        if self.len() == 0 || limit == 0 {
+            return Ok(Vec::new());
+        }
+        self.check_dimension(query)?;
+
+        let filter = filter.filter(|f| !f.is_empty());
+        let matches_filter = |id: &String| match filter {
+            Some(filter) => self.metadata.get(id).map(|m| filter.matches(m)).unwrap_or(false),
+            None => true,
+        };
+
+        if filter.is_some() {
+            let candidates: Vec<(&usize, &String)> = self.index_to_id
+                .iter()
+                .filter(|(_, id)| matches_filter(id))
+                .collect();
+            if candidates.len() <= EXACT_SEARCH_THRESHOLD {
+                let mut results: Vec<VectorMatch> = candidates
+                    .into_iter()
+                    .filter_map(|(index, id)| {
+                        let embedding = self.embeddings.get(index)?;
+                        Some(VectorMatch {
+                            id: id.clone(),
+                            similarity: cosine_similarity(query, embedding).max(0.0_f32),
+                            metadata: self.metadata.get(id).cloned().unwrap_or_default(),
+                        })
+                    })
+                    .collect();
+                results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+                results.truncate(limit);
+                return Ok(results);
+            }
+        }
+
+        let is_candidate = |index: &usize| self.index_to_id.get(index).map(|id| matches_filter(id)).unwrap_or(false);
+        let neighbours = self.hnsw.search_filter(
+            query,
+            limit,
+            EF_SEARCH.max(limit),
+            Some(&is_candidate),
+        );
+        Ok(neighbours
+            .into_iter()
+            .filter_map(|n| {
+                let id = self.index_to_id.get(&n.d_id)?;
+                Some(VectorMatch {
+                    id: id.clone(),
+                    // コサイン距離（1 - cos）を類似度に変換
+                    similarity: (1.0_f32 - n.distance).max(0.0_f32),
+                    metadata: self.metadata.get(id).cloned().unwrap_or_default(),
+                })
+            })
+            .collect())
+    }
+}
+
+/// HNSWによるインプロセスのベクトルストア
+/// コレクションごとに `<data_dir>/<collection>.jsonl` へ追記ログとして保存する
+pub struct HnswVectorStore {
+    data_dir: PathBuf,
+    indices: Mutex<HashMap<String, Arc<Mutex<VectorIndex>>>>,
+}
+
+/// コレクション名をファイル名として安全な形に変換
+fn collection_file_name(collection: &str) -> String {
+    let name: String = collection
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}.jsonl", name)
+}
+
+impl HnswVectorStore {
+    pub fn new(data_dir: PathBuf) -> Self {
+        HnswVectorStore {
+            data_dir,
+            indices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn collection_path(&self, collection: &str) -> PathBuf {
+        self.data_dir.join(collection_file_name(collection))
+    }
+
+    /// コレクションのインデックスを取得（未ロードならファイルから復元）
+    fn open_index(&self, collection: &str) -> Result<Arc<Mutex<VectorIndex>>, String> {
+        let mut indices = self.indices.lock()
+            .map_err(|e| format!("ベクトルインデックスのロック取得に失敗しました: {}", e))?;
+        if let Some(index) = indices.get(collection) {
+            return Ok(index.clone());
+        }
+        let index = Arc::new(Mutex::new(VectorIndex::load(self.collection_path(collection))?));
+        indices.insert(collection.to_string(), index.clone());
+        Ok(index)
+    }
+
+    fn with_index<T>(
+        &self,
+        collection: &str,
+        f: impl FnOnce(&mut VectorIndex) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let index = self.open_index(collection)?;
+        let mut guard = index.lock()
+            .map_err(|e| format!("ベクトルインデックスのロック取得に失敗しました: {}", e))?;
+        f(&mut guard)
+    }
+}
+
+impl VectorStore for HnswVectorStore {
+    fn backend(&self) -> VectorBackend {
+        VectorBackend::Hnsw
+    }
+
+    fn upsert(
+        &self,
+        collection: String,
+        id: String,
+        embedding: Vec<f32>,
+        metadata: Map<String, Value>,
+    ) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            self.with_index(&collection, |index| index.upsert(id, embedding, metadata))
+        })
+    }
+
+    fn get(&self, collection: String, id: String) -> VectorStoreFuture<'_, Option<VectorRecord>> {
+        Box::pin(async move { self.with_index(&collection, |index| Ok(index.get(&id))) })
+    }
+
+    fn query(
+        &self,
+        collection: String,
+        embedding: Vec<f32>,
+        limit: usize,
+        filter: Option<MetadataFilter>,
+    ) -> VectorStoreFuture<'_, Vec<VectorMatch>> {
+        Box::pin(async move {
+            self.with_index(&collection, |index| index.search(&embedding, limit, filter.as_ref()))
+        })
+    }
+
+    fn delete(&self, collection: String, ids: Vec<String>) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            self.with_index(&collection, |index| {
+                for id in &ids {
+                    index.delete(id)?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    fn count(&self, collection: String) -> VectorStoreFuture<'_, usize> {
+        Box::pin(async move { self.with_index(&collection, |index| Ok(index.len())) })
+    }
+
+    fn list_ids(&self, collection: String) -> VectorStoreFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            self.with_index(&collection, |index| {
+                let mut live: Vec<(&usize, &String)> = index.index_to_id.iter().collect();
+                live.sort_by_key(|(i, _)| **i);
+                Ok(live.into_iter().map(|(_, id)| id.clone()).collect())
+            })
+        })
+    }
+
+    fn list_collections(&self) -> VectorStoreFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let mut names: Vec<String> = Vec::new();
+            if self.data_dir.exists() {
+                let entries = fs::read_dir(&self.data_dir)
+                    .map_err(|e| format!("ベクトルインデックス一覧の取得に失敗しました: {}", e))?;
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
+                        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                            names.push(stem.to_string());
+                        }
+                    }
+                }
+            }
+            // まだファイルに書き出されていない、メモリ上の空でないコレクションも含める
+            let indices = self.indices.lock()
+                .map_err(|e| format!("ベクトルインデックスのロック取得に失敗しました: {}", e))?;
+            for (name, index) in indices.iter() {
+                let has_points = index.lock().map(|i| i.len() > 0).unwrap_or(false);
+                if has_points && !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            names.sort();
+            Ok(names)
+        })
+    }
+
+    fn delete_collection(&self, collection: String) -> VectorStoreFuture<'_, ()> {
+        Box::pin(async move {
+            {
+                let mut indices = self.indices.lock()
+                    .map_err(|e| format!("ベクトルインデックスのロック取得に失敗しました: {}", e))?;
+                indices.remove(&collection);
+            }
+            let path = self.collection_path(&collection);
+            if path.exists() {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("コレクション {} の削除に失敗しました: {}", collection, e))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// ========== バックエンド共通の高レベルAPI ==========
+// chromadb.rsの公開関数と同じ入出力で、任意のVectorStore上で動作する
+
+/// 組織IDからコレクション名を決定（空文字列の場合は"<prefix>_all"）
+pub(crate) fn org_collection_name(prefix: &str, organization_id: &str) -> String {
+    if organization_id.is_empty() {
+        format!("{}_all", prefix)
+    } else {
+        format!("{}_{}", prefix, organization_id)
+    }
+}
+
+/// 検索対象のコレクション一覧（組織未指定の場合は同じ種類の全コレクション）
+async fn search_target_collections(
+    store: &dyn VectorStore,
+    prefix: &str,
+    organization_id: Option<String>,
+) -> Result<Vec<String>, String> {
+    match organization_id {
+        Some(id) if !id.is_empty() => Ok(vec![org_collection_name(prefix, &id)]),
+        _ => {
+            let collection_prefix = format!("{}_", prefix);
+            let collections = store.list_collections().await?;
+            Ok(collections
+                .into_iter()
+                .filter(|name| name.starts_with(&collection_prefix))
+                .collect())
+        }
+    }
+}
+
+/// 複数コレクションを検索し、類似度順に上位limit件を返す
+/// 埋め込みの次元数がコレクションと一致しない場合はエラー（その他のコレクション単位のエラーは読み飛ばす）
+async fn query_collections(
+    store: &dyn VectorStore,
+    collections: Vec<String>,
+    query_embedding: &[f32],
+    limit: usize,
+    filter: Option<MetadataFilter>,
+) -> Result<Vec<VectorMatch>, String> {
+    for collection in &collections {
+        embedding_registry::check_query_dimension(collection, query_embedding.len()).await?;
+    }
+    let mut all_results = Vec::new();
+    for collection in collections {
+        match store.query(collection.clone(), query_embedding.to_vec(), limit, filter.clone()).await {
+            Ok(results) => all_results.extend(results),
+            Err(e) => {
+                eprintln!("[vector_search] ⚠️ コレクション '{}' の検索エラー: {}", collection, e);
+            }
+        }
+    }
+    all_results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+    all_results.truncate(limit);
+    Ok(all_results)
+}
+
+/// レコードを「combinedEmbedding + メタデータ」の形式に変換
+fn record_to_map(record: VectorRecord) -> HashMap<String, Value> {
+    let mut result_data = HashMap::new();
+    result_data.insert(
+        "combinedEmbedding".to_string(),
+        Value::Array(
+            record.embedding
+                .iter()
+                .filter_map(|&v| serde_json::Number::from_f64(v as f64).map(Value::Number))
+                .collect(),
+        ),
+    );
+    for (k, v) in record.metadata {
+        result_data.insert(k, v);
+    }
+    result_data
+}
+
+fn to_metadata_map(metadata: HashMap<String, Value>) -> Map<String, Value> {
+    metadata.into_iter().collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// エンティティ埋め込みを保存
 pub async fn save_entity_embedding(
-    _entity_id: String,
-    _organization_id: String,
-    _combined_embedding: Vec<f32>,
-    _metadata: HashMap<String, Value>,
+    store: &dyn VectorStore,
+    entity_id: String,
+    organization_id: String,
+    combined_embedding: Vec<f32>,
+    metadata: HashMap<String, Value>,
 ) -> Result<(), String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(())
+    let mut embedding_metadata = to_metadata_map(metadata);
+    embedding_metadata.insert("entityId".to_string(), Value::String(entity_id.clone()));
+    embedding_metadata.insert("organizationId".to_string(), Value::String(organization_id.clone()));
+
+    store.upsert(
+        org_collection_name(COLLECTION_ENTITIES, &organization_id),
+        entity_id,
+        combined_embedding,
+        embedding_metadata,
+    ).await
+        .map_err(|e| format!("エンティティ埋め込みの保存に失敗しました: {}", e))
 }
 
-/**
- * エンティティ埋め込みを取得
- * 注意: 現在はChromaDBを使用するため、この実装は使用されていません
- */
-#[allow(dead_code)]
+/// エンティティ埋め込みを取得
 pub async fn get_entity_embedding(
-    _entity_id: String,
-) -> Result<Option<(Vec<f32>, HashMap<String, Value>)>, String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(None)
+    store: &dyn VectorStore,
+    entity_id: String,
+    organization_id: String,
+) -> Result<Option<HashMap<String, Value>>, String> {
+    let record = store.get(org_collection_name(COLLECTION_ENTITIES, &organization_id), entity_id).await
+        .map_err(|e| format!("エンティティ埋め込みの取得に失敗しました: {}", e))?;
+    Ok(record.map(record_to_map))
 }
 
-/**
- * 類似エンティティを検索
- * 注意: 現在はChromaDBを使用するため、この実装は使用されていません
- */
-#[allow(dead_code)]
+/// 類似エンティティを検索（組織横断検索対応）
 pub async fn find_similar_entities(
-    _query_embedding: Vec<f32>,
-    _limit: usize,
-    _organization_id: Option<String>,
+    store: &dyn VectorStore,
+    query_embedding: Vec<f32>,
+    limit: usize,
+    organization_id: Option<String>,
+    filter: Option<EntitySearchFilter>,
 ) -> Result<Vec<(String, f32)>, String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(vec![])
+    let filter = filter.map(|f| f.to_metadata_filter()).transpose()?;
+    let collections = search_target_collections(store, COLLECTION_ENTITIES, organization_id).await?;
+    Ok(query_collections(store, collections, &query_embedding, limit, filter).await?
+        .into_iter()
+        .map(|m| (m.id, m.similarity))
+        .collect())
 }
 
-/**
- * リレーション埋め込みを保存
- * 注意: 現在はChromaDBを使用するため、この実装は使用されていません
- */
-#[allow(dead_code)]
+/// エンティティコレクションの件数を取得
+pub async fn count_entities(
+    store: &dyn VectorStore,
+    organization_id: Option<String>,
+) -> Result<usize, String> {
+    let org_id = match organization_id {
+        Some(id) if !id.is_empty() => id,
+        _ => return Err("organizationIdが指定されていません".to_string()),
+    };
+    store.count(org_collection_name(COLLECTION_ENTITIES, &org_id)).await
+        .map_err(|e| format!("コレクションの件数取得に失敗しました: {}", e))
+}
+
+/// リレーション埋め込みを保存
 pub async fn save_relation_embedding(
-    _relation_id: String,
-    _organization_id: String,
-    _combined_embedding: Vec<f32>,
-    _metadata: HashMap<String, Value>,
+    store: &dyn VectorStore,
+    relation_id: String,
+    organization_id: String,
+    combined_embedding: Vec<f32>,
+    metadata: HashMap<String, Value>,
 ) -> Result<(), String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(())
+    let mut embedding_metadata = to_metadata_map(metadata);
+    embedding_metadata.insert("relationId".to_string(), Value::String(relation_id.clone()));
+    embedding_metadata.insert("organizationId".to_string(), Value::String(organization_id.clone()));
+
+    store.upsert(
+        org_collection_name(COLLECTION_RELATIONS, &organization_id),
+        relation_id,
+        combined_embedding,
+        embedding_metadata,
+    ).await
+        .map_err(|e| format!("リレーション埋め込みの保存に失敗しました: {}", e))
 }
 
-/**
- * 類似リレーションを検索
- * 注意: 現在はChromaDBを使用するため、この実装は使用されていません
- */
-#[allow(dead_code)]
+/// リレーション埋め込みを取得
+pub async fn get_relation_embedding(
+    store: &dyn VectorStore,
+    relation_id: String,
+    organization_id: String,
+) -> Result<Option<HashMap<String, Value>>, String> {
+    let record = store.get(org_collection_name(COLLECTION_RELATIONS, &organization_id), relation_id).await
+        .map_err(|e| format!("リレーション埋め込みの取得に失敗しました: {}", e))?;
+    Ok(record.map(record_to_map))
+}
+
+/// 類似リレーションを検索（組織横断検索対応）
 pub async fn find_similar_relations(
-    _query_embedding: Vec<f32>,
-    _limit: usize,
-    _organization_id: Option<String>,
+    store: &dyn VectorStore,
+    query_embedding: Vec<f32>,
+    limit: usize,
+    organization_id: Option<String>,
+    filter: Option<RelationSearchFilter>,
 ) -> Result<Vec<(String, f32)>, String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(vec![])
+    let filter = filter.map(|f| f.to_metadata_filter()).transpose()?;
+    let collections = search_target_collections(store, COLLECTION_RELATIONS, organization_id).await?;
+    Ok(query_collections(store, collections, &query_embedding, limit, filter).await?
+        .into_iter()
+        .map(|m| (m.id, m.similarity))
+        .collect())
 }
 
-/**
- * トピック埋め込みを保存
- * 注意: 現在はChromaDBを使用するため、この実装は使用されていません
- */
-#[allow(dead_code)]
+/// トピック埋め込みを保存
 pub async fn save_topic_embedding(
-    _topic_id: String,
-    _meeting_note_id: String,
-    _organization_id: String,
-    _combined_embedding: Vec<f32>,
-    _metadata: HashMap<String, Value>,
+    store: &dyn VectorStore,
+    topic_id: String,
+    meeting_note_id: Option<String>,
+    organization_id: String,
+    combined_embedding: Vec<f32>,
+    metadata: HashMap<String, Value>,
+    regulation_id: Option<String>,
 ) -> Result<(), String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(())
+    let mut embedding_metadata = to_metadata_map(metadata);
+    embedding_metadata.insert("topicId".to_string(), Value::String(topic_id.clone()));
+    embedding_metadata.insert("organizationId".to_string(), Value::String(organization_id.clone()));
+
+    // タイトルが空の場合はcontentSummaryまたはtopicIdから補完（ChromaDB版と同じ）
+    let title_is_empty = embedding_metadata.get("title")
+        .and_then(|v| v.as_str())
+        .map(|s| s.is_empty())
+        .unwrap_or(true);
+    if title_is_empty {
+        let content_summary = embedding_metadata.get("contentSummary")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let fallback_title = if content_summary.is_empty() {
+            format!("トピック {}", topic_id)
+        } else if content_summary.chars().count() > 50 {
+            format!("{}...", content_summary.chars().take(50).collect::<String>())
+        } else {
+            content_summary
+        };
+        embedding_metadata.insert("title".to_string(), Value::String(fallback_title));
+    }
+
+    if let Some(meeting_note_id) = meeting_note_id {
+        embedding_metadata.insert("meetingNoteId".to_string(), Value::String(meeting_note_id));
+    }
+    if let Some(regulation_id) = regulation_id {
+        embedding_metadata.insert("regulationId".to_string(), Value::String(regulation_id));
+    }
+    if let Some(seconds) = topic_date_timestamp(embedding_metadata.get("topicDate")) {
+        embedding_metadata.insert(TOPIC_DATE_TIMESTAMP_KEY.to_string(), Value::from(seconds));
+    }
+
+    store.upsert(
+        org_collection_name(COLLECTION_TOPICS, &organization_id),
+        topic_id,
+        combined_embedding,
+        embedding_metadata,
+    ).await
+        .map_err(|e| format!("トピック埋め込みの保存に失敗しました: {}", e))
 }
 
-/**
- * 類似トピックを検索
- * 注意: 現在はChromaDBを使用するため、この実装は使用されていません
- */
-#[allow(dead_code)]
+/// トピック埋め込みを取得
+pub async fn get_topic_embedding(
+    store: &dyn VectorStore,
+    topic_id: String,
+    organization_id: String,
+) -> Result<Option<HashMap<String, Value>>, String> {
+    let record = store.get(org_collection_name(COLLECTION_TOPICS, &organization_id), topic_id).await
+        .map_err(|e| format!("トピック埋め込みの取得に失敗しました: {}", e))?;
+    Ok(record.map(record_to_map))
+}
+
+/// 類似トピックを検索（組織横断検索対応）
 pub async fn find_similar_topics(
-    _query_embedding: Vec<f32>,
-    _limit: usize,
-    _organization_id: Option<String>,
-) -> Result<Vec<(String, String, f32)>, String> {
-    // ChromaDBを使用するため、この実装は使用されていません
-    Ok(vec![])
+    store: &dyn VectorStore,
+    query_embedding: Vec<f32>,
+    limit: usize,
+    organization_id: Option<String>,
+    filter: Option<TopicSearchFilter>,
+) -> Result<Vec<TopicSearchResult>, String> {
+    let filter = filter.map(|f| f.to_metadata_filter()).transpose()?;
+    let collections = search_target_collections(store, COLLECTION_TOPICS, organization_id).await?;
+    let metadata_str = |m: &Map<String, Value>, key: &str| {
+        m.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+    };
+    Ok(query_collections(store, collections, &query_embedding, limit, filter).await?
+        .into_iter()
+        .map(|m| TopicSearchResult {
+            meeting_note_id: metadata_str(&m.metadata, "meetingNoteId"),
+            regulation_id: metadata_str(&m.metadata, "regulationId"),
+            title: metadata_str(&m.metadata, "title").unwrap_or_default(),
+            content_summary: metadata_str(&m.metadata, "contentSummary").unwrap_or_default(),
+            organization_id: metadata_str(&m.metadata, "organizationId"),
+            similarity: m.similarity,
+            topic_id: m.id,
+        })
+        .collect())
+}
+
+/// システム設計ドキュメントの埋め込みを保存
+pub async fn save_design_doc_embedding(
+    store: &dyn VectorStore,
+    section_id: String,
+    combined_embedding: Vec<f32>,
+    metadata: HashMap<String, Value>,
+) -> Result<(), String> {
+    let mut embedding_metadata = to_metadata_map(metadata);
+    embedding_metadata.insert("sectionId".to_string(), Value::String(section_id.clone()));
+
+    store.upsert(COLLECTION_DESIGN_DOCS.to_string(), section_id, combined_embedding, embedding_metadata).await
+        .map_err(|e| format!("システム設計ドキュメント埋め込みの保存に失敗しました: {}", e))
+}
+
+/// 類似システム設計ドキュメントを検索
+/// section_idを指定した場合はそのセクションとの類似度のみを返す（タグはChromaDB版と同様に未対応）
+pub async fn find_similar_design_docs(
+    store: &dyn VectorStore,
+    query_embedding: Vec<f32>,
+    limit: usize,
+    section_id: Option<String>,
+    _tags: Option<Vec<String>>,
+) -> Result<Vec<(String, f32)>, String> {
+    if let Some(sid) = section_id {
+        let record = store.get(COLLECTION_DESIGN_DOCS.to_string(), sid).await
+            .map_err(|e| format!("類似システム設計ドキュメントの検索に失敗しました: {}", e))?;
+        return Ok(record
+            .map(|r| vec![(r.id, cosine_similarity(&query_embedding, &r.embedding))])
+            .unwrap_or_default());
+    }
+
+    let results = store.query(COLLECTION_DESIGN_DOCS.to_string(), query_embedding, limit, None).await
+        .map_err(|e| format!("類似システム設計ドキュメントの検索に失敗しました: {}", e))?;
+    Ok(results.into_iter().map(|m| (m.id, m.similarity)).collect())
+}
+
+/// システム設計ドキュメントのメタデータを取得
+pub async fn get_design_doc_metadata(
+    store: &dyn VectorStore,
+    section_id: String,
+) -> Result<HashMap<String, Value>, String> {
+    let record = store.get(COLLECTION_DESIGN_DOCS.to_string(), section_id).await
+        .map_err(|e| format!("システム設計ドキュメントメタデータの取得に失敗しました: {}", e))?;
+    match record {
+        Some(record) => Ok(record.metadata.into_iter().collect()),
+        None => Err("メタデータが見つかりませんでした".to_string()),
+    }
+}
+
+/// システム設計ドキュメントコレクション内の全セクションIDを取得
+pub async fn list_design_doc_section_ids(store: &dyn VectorStore) -> Result<Vec<String>, String> {
+    store.list_ids(COLLECTION_DESIGN_DOCS.to_string()).await
+        .map_err(|e| format!("システム設計ドキュメント一覧の取得に失敗しました: {}", e))
+}
+
+/// トピック埋め込みを削除
+pub async fn delete_topic_embedding(
+    store: &dyn VectorStore,
+    topic_id: String,
+    organization_id: String,
+) -> Result<(), String> {
+    store.delete(org_collection_name(COLLECTION_TOPICS, &organization_id), vec![topic_id]).await
+        .map_err(|e| format!("トピック埋め込みの削除に失敗しました: {}", e))
+}
+
+/// エンティティ埋め込みを削除
+pub async fn delete_entity_embedding(
+    store: &dyn VectorStore,
+    entity_id: String,
+    organization_id: String,
+) -> Result<(), String> {
+    store.delete(org_collection_name(COLLECTION_ENTITIES, &organization_id), vec![entity_id]).await
+        .map_err(|e| format!("エンティティ埋め込みの削除に失敗しました: {}", e))
+}
+
+/// リレーション埋め込みを削除
+pub async fn delete_relation_embedding(
+    store: &dyn VectorStore,
+    relation_id: String,
+    organization_id: String,
+) -> Result<(), String> {
+    store.delete(org_collection_name(COLLECTION_RELATIONS, &organization_id), vec![relation_id]).await
+        .map_err(|e| format!("リレーション埋め込みの削除に失敗しました: {}", e))
+}
+
+/// 組織に関連するコレクションを削除
+pub async fn delete_organization_collections(
+    store: &dyn VectorStore,
+    organization_id: String,
+) -> Result<(), String> {
+    for prefix in [COLLECTION_TOPICS, COLLECTION_ENTITIES, COLLECTION_RELATIONS] {
+        let collection_name = org_collection_name(prefix, &organization_id);
+        if let Err(e) = store.delete_collection(collection_name.clone()).await {
+            eprintln!("⚠️ [delete_organization_collections] コレクション削除エラー（続行します）: {} - {}", collection_name, e);
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,550 @@
+/**
+ * YAMLの構文・スキーマ検証モジュール
+ * graphvizYamlFilesのyamlContentを解析し、yamlSchemaで宣言されたスキーマに照らして検証する
+ *
+ * - yamlSchemaは組み込みスキーマ名（topology / device / links / intent）か、JSON Schema（JSONまたはYAML）
+ * - 対応するキーワード: type, enum, required, properties, additionalProperties, items,
+ *   minItems, maxItems, minLength, maxLength, minimum, maximum
+ * - エラーにはYAML上の行・列（1始まり）とキーのパスを含める
+ */
+
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+use yaml_rust2::Yaml;
+
+/// 1回の検証で返すエラーの上限
+const MAX_ERRORS: usize = 100;
+
+/// エラーメッセージに含めるエラーの件数
+const MAX_ERRORS_IN_MESSAGE: usize = 5;
+
+/// 検証エラー
+#[derive(Debug, Clone, Serialize)]
+pub struct YamlValidationIssue {
+    /// キーのパス（例: connections[0].from.device、ルートは空文字）
+    pub path: String,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// 検証結果
+#[derive(Debug, Clone, Serialize)]
+pub struct YamlValidationReport {
+    pub valid: bool,
+    pub errors: Vec<YamlValidationIssue>,
+    /// 適用したスキーマ（組み込みスキーマ名、JSON Schemaの場合は "custom"、未指定の場合はnull）
+    pub schema: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum YamlNodeValue {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Sequence(Vec<YamlNode>),
+    Mapping(Vec<(String, YamlNode)>),
+}
+
+/// 位置情報付きのYAMLノード
+#[derive(Debug, Clone)]
+struct YamlNode {
+    value: YamlNodeValue,
+    line: usize,
+    column: usize,
+}
+
+enum Frame {
+    Sequence { node: YamlNode, anchor: usize },
+    /// keyは値を待っているキー、first_keyは最初のキーの位置
+    Mapping { node: YamlNode, anchor: usize, key: Option<YamlNode>, first_key: Option<(usize, usize)> },
+}
+
+/// パーサーのイベントから位置情報付きのツリーを組み立てる
+#[derive(Default)]
+struct TreeBuilder {
+    stack: Vec<Frame>,
+    anchors: HashMap<usize, YamlNode>,
+    root: Option<YamlNode>,
+}
+
+impl TreeBuilder {
+    fn insert(&mut self, node: YamlNode, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+        match self.stack.last_mut() {
+            Some(Frame::Sequence { node: parent, .. }) => {
+                if let YamlNodeValue::Sequence(items) = &mut parent.value {
+                    items.push(node);
+                }
+            }
+            Some(Frame::Mapping { node: parent, key, first_key, .. }) => match key.take() {
+                Some(k) => {
+                    if let YamlNodeValue::Mapping(entries) = &mut parent.value {
+                        entries.push((scalar_text(&k), node));
+                    }
+                }
+                None => {
+                    first_key.get_or_insert((node.line, node.column));
+                    *key = Some(node);
+                }
+            },
+            None => {
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            }
+        }
+    }
+}
+
+impl MarkedEventReceiver for TreeBuilder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        let at = |value| YamlNode { value, line: mark.line(), column: mark.col() + 1 };
+        match event {
+            Event::Scalar(text, style, anchor, tag) => {
+                let is_str_tag = tag.as_ref().is_some_and(|t| t.suffix == "str");
+                let value = if style != TScalarStyle::Plain || is_str_tag {
+                    YamlNodeValue::String(text)
+                } else {
+                    match Yaml::from_str(&text) {
+                        Yaml::Null => YamlNodeValue::Null,
+                        Yaml::Boolean(b) => YamlNodeValue::Bool(b),
+                        Yaml::Integer(i) => YamlNodeValue::Integer(i),
+                        Yaml::Real(r) => r.parse().map(YamlNodeValue::Float).unwrap_or(YamlNodeValue::String(text)),
+                        _ => YamlNodeValue::String(text),
+                    }
+                };
+                self.insert(at(value), anchor);
+            }
+            Event::SequenceStart(anchor, _) => {
+                self.stack.push(Frame::Sequence { node: at(YamlNodeValue::Sequence(Vec::new())), anchor });
+            }
+            Event::MappingStart(anchor, _) => {
+                self.stack.push(Frame::Mapping {
+                    node: at(YamlNodeValue::Mapping(Vec::new())),
+                    anchor,
+                    key: None,
+                    first_key: None,
+                });
+            }
+            Event::SequenceEnd => {
+                if let Some(Frame::Sequence { node, anchor }) = self.stack.pop() {
+                    self.insert(node, anchor);
+                }
+            }
+            Event::MappingEnd => {
+                // ブロック形式のマッピングの開始位置は最初のキーの後ろになるため、最初のキーの位置を使う
+                if let Some(Frame::Mapping { mut node, anchor, first_key, .. }) = self.stack.pop() {
+                    if let Some((line, column)) = first_key {
+                        node.line = line;
+                        node.column = column;
+                    }
+                    self.insert(node, anchor);
+                }
+            }
+            Event::Alias(id) => {
+                let mut node = self.anchors.get(&id).cloned().unwrap_or_else(|| at(YamlNodeValue::Null));
+                node.line = mark.line();
+                node.column = mark.col() + 1;
+                self.insert(node, 0);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// マッピングのキーなどに使う文字列表現
+fn scalar_text(node: &YamlNode) -> String {
+    match &node.value {
+        YamlNodeValue::Null => "null".to_string(),
+        YamlNodeValue::Bool(b) => b.to_string(),
+        YamlNodeValue::Integer(i) => i.to_string(),
+        YamlNodeValue::Float(f) => f.to_string(),
+        YamlNodeValue::String(s) => s.clone(),
+        YamlNodeValue::Sequence(_) | YamlNodeValue::Mapping(_) => to_json(node).to_string(),
+    }
+}
+
+/// YAMLを位置情報付きで解析（空のドキュメントはnull）
+fn parse_yaml(content: &str) -> Result<YamlNode, YamlValidationIssue> {
+    let mut builder = TreeBuilder::default();
+    let mut parser = Parser::new_from_str(content);
+    parser.load(&mut builder, false).map_err(|e| YamlValidationIssue {
+        path: String::new(),
+        message: format!("YAMLの構文エラー: {}", e.info()),
+        line: Some(e.marker().line()),
+        column: Some(e.marker().col() + 1),
+    })?;
+    Ok(builder.root.unwrap_or(YamlNode { value: YamlNodeValue::Null, line: 1, column: 1 }))
+}
+
+fn to_json(node: &YamlNode) -> JsonValue {
+    match &node.value {
+        YamlNodeValue::Null => JsonValue::Null,
+        YamlNodeValue::Bool(b) => json!(b),
+        YamlNodeValue::Integer(i) => json!(i),
+        YamlNodeValue::Float(f) => serde_json::Number::from_f64(*f).map(JsonValue::Number).unwrap_or(JsonValue::Null),
+        YamlNodeValue::String(s) => json!(s),
+        YamlNodeValue::Sequence(items) => JsonValue::Array(items.iter().map(to_json).collect()),
+        YamlNodeValue::Mapping(entries) => {
+            JsonValue::Object(entries.iter().map(|(k, v)| (k.clone(), to_json(v))).collect())
+        }
+    }
+}
+
+/// 組み込みスキーマ（フロントエンドのyamlSchemas.tsと同じ定義）
+fn builtin_schema(name: &str) -> Option<JsonValue> {
+    let metadata = json!({
+        "type": "object",
+        "properties": { "createdAt": { "type": "string" }, "updatedAt": { "type": "string" } }
+    });
+    let endpoint = json!({
+        "type": "object",
+        "required": ["device", "port"],
+        "properties": { "device": { "type": "string" }, "port": { "type": "string" } }
+    });
+    match name {
+        "topology" => Some(json!({
+            "type": "object",
+            "required": ["id", "type", "label"],
+            "properties": {
+                "id": { "type": "string" },
+                "label": { "type": "string" },
+                "description": { "type": "string" },
+                "type": { "type": "string", "enum": ["topology"] },
+                "layers": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "label", "level"],
+                        "properties": {
+                            "id": { "type": "string" },
+                            "label": { "type": "string" },
+                            "level": { "type": "number" }
+                        }
+                    }
+                },
+                "metadata": metadata
+            }
+        })),
+        "device" => Some(json!({
+            "type": "object",
+            "required": ["id", "type", "label"],
+            "properties": {
+                "id": { "type": "string" },
+                "type": { "type": "string", "enum": ["server", "switch", "router", "firewall"] },
+                "label": { "type": "string" },
+                "model": { "type": "string" },
+                "location": {
+                    "type": "object",
+                    "properties": { "rack": { "type": "string" }, "unit": { "type": "string" } }
+                },
+                "ports": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "label"],
+                        "properties": {
+                            "id": { "type": "string" },
+                            "label": { "type": "string" },
+                            "speed": { "type": "string" },
+                            "role": { "type": "string" },
+                            "mac": { "type": "string" }
+                        }
+                    }
+                },
+                "metadata": metadata
+            }
+        })),
+        "links" => Some(json!({
+            "type": "object",
+            "required": ["id", "type", "label", "connections"],
+            "properties": {
+                "id": { "type": "string" },
+                "type": { "type": "string", "enum": ["links"] },
+                "label": { "type": "string" },
+                "network": { "type": "string" },
+                "connections": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id", "from", "to"],
+                        "properties": {
+                            "id": { "type": "string" },
+                            "from": endpoint,
+                            "to": endpoint,
+                            "network": { "type": "string" },
+                            "status": { "type": "string", "enum": ["active", "inactive", "planned"] }
+                        }
+                    }
+                },
+                "metadata": metadata
+            }
+        })),
+        "intent" => Some(json!({
+            "type": "object",
+            "required": ["id", "type", "label", "rules"],
+            "properties": {
+                "id": { "type": "string" },
+                "type": { "type": "string", "enum": ["intent"] },
+                "label": { "type": "string" },
+                "rules": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["name", "description", "applies_to"],
+                        "properties": {
+                            "name": { "type": "string" },
+                            "description": { "type": "string" },
+                            "applies_to": { "type": "string" },
+                            "validation": {
+                                "type": "object",
+                                "properties": {
+                                    "type": { "type": "string" },
+                                    "min": { "type": "number" },
+                                    "max": { "type": "number" },
+                                    "target_devices": { "type": "string" }
+                                }
+                            }
+                        }
+                    }
+                },
+                "metadata": metadata
+            }
+        })),
+        _ => None,
+    }
+}
+
+/// yamlSchemaの値からスキーマを取得（組み込みスキーマ名、JSON、YAMLの順に解釈）
+fn resolve_schema(yaml_schema: &str) -> Result<(String, JsonValue), String> {
+    let trimmed = yaml_schema.trim();
+    if let Some(schema) = builtin_schema(trimmed) {
+        return Ok((trimmed.to_string(), schema));
+    }
+    let schema = match serde_json::from_str::<JsonValue>(trimmed) {
+        Ok(schema) => schema,
+        Err(_) => parse_yaml(trimmed)
+            .map(|node| to_json(&node))
+            .map_err(|e| format!("スキーマの解析に失敗しました: {}", format_issue(&e)))?,
+    };
+    if !schema.is_object() {
+        return Err(format!("スキーマが見つかりません: {}（組み込みスキーマ: topology, device, links, intent）", trimmed));
+    }
+    Ok(("custom".to_string(), schema))
+}
+
+fn type_name(node: &YamlNode) -> &'static str {
+    match node.value {
+        YamlNodeValue::Null => "null",
+        YamlNodeValue::Bool(_) => "boolean",
+        YamlNodeValue::Integer(_) => "integer",
+        YamlNodeValue::Float(_) => "number",
+        YamlNodeValue::String(_) => "string",
+        YamlNodeValue::Sequence(_) => "array",
+        YamlNodeValue::Mapping(_) => "object",
+    }
+}
+
+fn matches_type(node: &YamlNode, expected: &str) -> bool {
+    match (expected, &node.value) {
+        ("number", YamlNodeValue::Integer(_) | YamlNodeValue::Float(_)) => true,
+        ("integer", YamlNodeValue::Float(f)) => f.fract() == 0.0,
+        _ => type_name(node) == expected,
+    }
+}
+
+fn numeric_value(node: &YamlNode) -> Option<f64> {
+    match node.value {
+        YamlNodeValue::Integer(i) => Some(i as f64),
+        YamlNodeValue::Float(f) => Some(f),
+        _ => None,
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+struct Validator {
+    errors: Vec<YamlValidationIssue>,
+}
+
+impl Validator {
+    fn push(&mut self, node: &YamlNode, path: &str, message: String) {
+        if self.errors.len() < MAX_ERRORS {
+            self.errors.push(YamlValidationIssue {
+                path: path.to_string(),
+                message,
+                line: Some(node.line),
+                column: Some(node.column),
+            });
+        }
+    }
+
+    fn validate(&mut self, node: &YamlNode, schema: &JsonValue, path: &str) {
+        let Some(schema) = schema.as_object() else {
+            return;
+        };
+
+        if let Some(expected) = schema.get("type") {
+            let expected: Vec<&str> = match expected {
+                JsonValue::String(t) => vec![t.as_str()],
+                JsonValue::Array(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+                _ => Vec::new(),
+            };
+            if !expected.is_empty() && !expected.iter().any(|t| matches_type(node, t)) {
+                self.push(node, path, format!("型が一致しません: 期待値={}, 実際={}", expected.join(" | "), type_name(node)));
+                return;
+            }
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+            let value = to_json(node);
+            if !allowed.contains(&value) {
+                let allowed = allowed.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+                self.push(node, path, format!("許可されていない値です: {}（許可: {}）", value, allowed));
+            }
+        }
+
+        match &node.value {
+            YamlNodeValue::Mapping(entries) => self.validate_mapping(node, entries, schema, path),
+            YamlNodeValue::Sequence(items) => {
+                let count = items.len() as u64;
+                if let Some(min) = schema.get("minItems").and_then(|v| v.as_u64()).filter(|min| count < *min) {
+                    self.push(node, path, format!("要素数が少なすぎます: {}件（最小{}件）", count, min));
+                }
+                if let Some(max) = schema.get("maxItems").and_then(|v| v.as_u64()).filter(|max| count > *max) {
+                    self.push(node, path, format!("要素数が多すぎます: {}件（最大{}件）", count, max));
+                }
+                if let Some(item_schema) = schema.get("items") {
+                    for (i, item) in items.iter().enumerate() {
+                        self.validate(item, item_schema, &format!("{}[{}]", path, i));
+                    }
+                }
+            }
+            YamlNodeValue::String(s) => {
+                let length = s.chars().count() as u64;
+                if let Some(min) = schema.get("minLength").and_then(|v| v.as_u64()).filter(|min| length < *min) {
+                    self.push(node, path, format!("文字数が少なすぎます: {}文字（最小{}文字）", length, min));
+                }
+                if let Some(max) = schema.get("maxLength").and_then(|v| v.as_u64()).filter(|max| length > *max) {
+                    self.push(node, path, format!("文字数が多すぎます: {}文字（最大{}文字）", length, max));
+                }
+            }
+            _ => {
+                if let Some(value) = numeric_value(node) {
+                    if let Some(min) = schema.get("minimum").and_then(|v| v.as_f64()).filter(|min| value < *min) {
+                        self.push(node, path, format!("値が小さすぎます: {}（最小{}）", value, min));
+                    }
+                    if let Some(max) = schema.get("maximum").and_then(|v| v.as_f64()).filter(|max| value > *max) {
+                        self.push(node, path, format!("値が大きすぎます: {}（最大{}）", value, max));
+                    }
+                }
+            }
+        }
+    }
+
+    fn validate_mapping(
+        &mut self,
+        node: &YamlNode,
+        entries: &[(String, YamlNode)],
+        schema: &serde_json::Map<String, JsonValue>,
+        path: &str,
+    ) {
+        let mut seen = HashSet::new();
+        for (key, value) in entries {
+            if !seen.insert(key.as_str()) {
+                self.push(value, &child_path(path, key), format!("キーが重複しています: {}", key));
+            }
+        }
+
+        if let Some(required) = schema.get("required").and_then(|r| r.as_array()) {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !seen.contains(key) {
+                    self.push(node, path, format!("必須のキーがありません: {}", key));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        let additional = schema.get("additionalProperties");
+        for (key, value) in entries {
+            let key_path = child_path(path, key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(property_schema) => self.validate(value, property_schema, &key_path),
+                None => match additional {
+                    Some(JsonValue::Bool(false)) => {
+                        self.push(value, &key_path, format!("定義されていないキーです: {}", key));
+                    }
+                    Some(additional_schema @ JsonValue::Object(_)) => self.validate(value, additional_schema, &key_path),
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+fn format_issue(issue: &YamlValidationIssue) -> String {
+    let location = match (issue.line, issue.column) {
+        (Some(line), Some(column)) => format!("{}行目 {}列目", line, column),
+        _ => String::new(),
+    };
+    let path = if issue.path.is_empty() { String::new() } else { format!(" {}", issue.path) };
+    format!("{}{}: {}", location, path, issue.message)
+}
+
+/// YAMLを構文チェックし、yamlSchemaが指定されていればスキーマで検証する
+/// （スキーマ自体が解析できない場合はErr）
+pub fn validate_yaml(yaml_content: &str, yaml_schema: Option<&str>) -> Result<YamlValidationReport, String> {
+    let schema = match yaml_schema.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(yaml_schema) => Some(resolve_schema(yaml_schema)?),
+        None => None,
+    };
+
+    let errors = match parse_yaml(yaml_content) {
+        Ok(root) => {
+            let mut validator = Validator { errors: Vec::new() };
+            if let Some((_, schema)) = schema.as_ref() {
+                validator.validate(&root, schema, "");
+            }
+            validator.errors
+        }
+        Err(issue) => vec![issue],
+    };
+
+    Ok(YamlValidationReport {
+        valid: errors.is_empty(),
+        errors,
+        schema: schema.map(|(name, _)| name),
+    })
+}
+
+/// 検証に失敗した場合、エラー位置を含むメッセージを返す（保存前のチェック用）
+pub(crate) fn ensure_valid_yaml(yaml_content: &str, yaml_schema: Option<&str>) -> Result<(), String> {
+    let report = validate_yaml(yaml_content, yaml_schema)?;
+    if report.valid {
+        return Ok(());
+    }
+    let mut lines: Vec<String> = report
+        .errors
+        .iter()
+        .take(MAX_ERRORS_IN_MESSAGE)
+        .map(|issue| format!("- {}", format_issue(issue)))
+        .collect();
+    if report.errors.len() > MAX_ERRORS_IN_MESSAGE {
+        lines.push(format!("- ほか{}件", report.errors.len() - MAX_ERRORS_IN_MESSAGE));
+    }
+    Err(format!("YAMLの検証に失敗しました:\n{}", lines.join("\n")))
+}
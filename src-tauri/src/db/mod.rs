@@ -1,14 +1,54 @@
+pub mod outbox;
 pub mod write_job;
 pub mod write_worker;
 
 use std::sync::Arc;
 use async_channel::Sender;
+use crate::database::pool::DatabasePool;
 
+pub use outbox::{DeadWriteJob, WriteOutbox};
 pub use write_job::WriteJob;
 pub use write_worker::WriteWorker;
 
 // 書き込みキュー状態
 #[derive(Clone)]
 pub struct WriteQueueState {
-    pub tx: Arc<Sender<WriteJob>>,
+    /// ワーカーへの通知（保存したジョブのアウトボックスID）
+    pub tx: Arc<Sender<i64>>,
+    /// ジョブの保存先（データベース未初期化の場合はNone）
+    pub outbox: Option<Arc<WriteOutbox>>,
+}
+
+impl WriteQueueState {
+    /// ジョブをアウトボックスに保存してからワーカーに通知
+    pub async fn enqueue(&self, job: WriteJob) -> Result<(), String> {
+        let outbox = self.outbox.as_ref()
+            .ok_or_else(|| "書き込みキューが初期化されていません".to_string())?;
+        let id = outbox.enqueue(&job)
+            .map_err(|e| format!("書き込みジョブの保存に失敗しました: {}", e))?;
+        // ジョブは保存済みのため、通知に失敗しても次回起動時に処理される
+        if let Err(e) = self.tx.send(id).await {
+            eprintln!("[DB-WRITER] ワーカーへの通知に失敗しました: {}", e);
+        }
+        Ok(())
+    }
+
+    /// ワーカーに待機中のジョブを確認させる
+    pub fn wake(&self, id: i64) {
+        let _ = self.tx.try_send(id);
+    }
+}
+
+/// アウトボックスを使う書き込みワーカーを起動
+pub fn start_write_worker(pool: DatabasePool) -> WriteQueueState {
+    let (tx, rx) = async_channel::unbounded::<i64>();
+    let outbox = Arc::new(WriteOutbox::new(pool.clone()));
+    let worker = WriteWorker::new(pool, outbox.clone());
+    tauri::async_runtime::spawn(async move {
+        worker.run(rx).await;
+    });
+    WriteQueueState {
+        tx: Arc::new(tx),
+        outbox: Some(outbox),
+    }
 }
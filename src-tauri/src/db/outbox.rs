@@ -0,0 +1,318 @@
+/**
+ * 書き込みジョブのアウトボックス
+ * WriteJobを受け付ける前にSQLite（writeOutboxテーブル）へ保存し、アプリが強制終了されても書き込みを失わないようにする
+ *
+ * - ジョブは処理に成功した時点でアウトボックスから削除される（処理中に終了した場合は次回起動時に再実行）
+ * - 失敗したジョブは指数バックオフで再試行し、MAX_ATTEMPTS回失敗したらデッドレター（writeDeadLetters）へ移す
+ * - ジョブの順序キー（writeOutboxKeys）が重なるジョブは登録順に処理し、先頭のジョブが再試行待ちの間は後続のジョブも待機する
+ *   （別レコードのジョブは待たないため、1件の失敗がすべての書き込みを止めることはない）
+ * - 処理に成功したジョブの排他キーごとに最後のアウトボックスIDを記録し（writeKeyWatermarks）、
+ *   より新しい書き込みで上書き済みのデッドレターは再実行しない
+ */
+
+use crate::database::get_timestamp;
+use crate::database::pool::DatabasePool;
+use crate::db::write_job::{OrderingKey, WriteJob};
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// デッドレターへ移すまでの最大試行回数
+pub const MAX_ATTEMPTS: i64 = 5;
+
+/// 再試行の待ち時間（BACKOFF_BASE_SECS * 2^(試行回数-1)、最大BACKOFF_MAX_SECS）
+const BACKOFF_BASE_SECS: i64 = 2;
+const BACKOFF_MAX_SECS: i64 = 300;
+
+/// アウトボックスから取り出したジョブ
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub job_type: String,
+    pub payload: String,
+    pub attempts: i64,
+}
+
+/// 失敗したジョブの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureOutcome {
+    /// 指定秒数後に再試行する
+    Retry { delay_secs: i64 },
+    /// デッドレターへ移した
+    DeadLettered,
+}
+
+/// デッドレターのジョブ
+#[derive(Debug, Clone, Serialize)]
+pub struct DeadWriteJob {
+    pub id: i64,
+    #[serde(rename = "jobType")]
+    pub job_type: String,
+    #[serde(rename = "jobKey")]
+    pub job_key: String,
+    /// WriteJobのJSON
+    pub payload: serde_json::Value,
+    pub attempts: i64,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "failedAt")]
+    pub failed_at: String,
+    /// 同じレコードへのより新しい書き込みがある（再実行すると古い内容で上書きするため再実行できない）
+    pub superseded: bool,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn backoff_secs(attempts: i64) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    (BACKOFF_BASE_SECS * 2_i64.pow(exponent)).min(BACKOFF_MAX_SECS)
+}
+
+// 順序キーが重なる古いジョブ（どちらかが排他キーとして持つもの）が残っていない（=先頭の）ジョブだけを対象にする条件
+const HEAD_OF_KEY: &str =
+    "NOT EXISTS (SELECT 1 FROM writeOutboxKeys mine
+                 JOIN writeOutboxKeys older ON older.jobKey = mine.jobKey AND older.outboxId < mine.outboxId
+                 WHERE mine.outboxId = o.id AND (mine.exclusive = 1 OR older.exclusive = 1))";
+
+/// ジョブの順序キーを保存
+fn insert_keys(conn: &Connection, outbox_id: i64, keys: &[OrderingKey]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO writeOutboxKeys (outboxId, jobKey, exclusive) VALUES (?1, ?2, ?3)",
+    )?;
+    for key in keys {
+        stmt.execute(params![outbox_id, key.key, key.exclusive])?;
+    }
+    Ok(())
+}
+
+/// デッドレターのジョブの順序キー（復元できないジョブは保存時のjobKeyを排他キーにする）
+fn dead_letter_keys(job_key: &str, payload: &str) -> Vec<OrderingKey> {
+    match serde_json::from_str::<WriteJob>(payload) {
+        Ok(job) => job.ordering_keys(),
+        Err(_) => vec![OrderingKey { key: job_key.to_string(), exclusive: true }],
+    }
+}
+
+/// デッドレターのジョブより新しい書き込みが、いずれかの排他キーで処理済みまたは待機中か
+fn is_superseded(conn: &Connection, outbox_id: Option<i64>, keys: &[OrderingKey]) -> Result<bool> {
+    let Some(outbox_id) = outbox_id else {
+        return Ok(false);
+    };
+    for key in keys.iter().filter(|key| key.exclusive) {
+        let newer: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM writeKeyWatermarks WHERE jobKey = ?1 AND lastOutboxId > ?2)
+                 OR EXISTS (SELECT 1 FROM writeOutboxKeys WHERE jobKey = ?1 AND outboxId > ?2)",
+            params![key.key, outbox_id],
+            |row| row.get(0),
+        )?;
+        if newer {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[derive(Clone)]
+pub struct WriteOutbox {
+    pool: DatabasePool,
+}
+
+impl WriteOutbox {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    /// ジョブをアウトボックスに保存（保存できた時点でジョブを受け付けたものとする）
+    pub fn enqueue(&self, job: &WriteJob) -> Result<i64> {
+        let conn = self.pool.get_connection()?;
+        let payload = serde_json::to_string(job).context("Failed to serialize write job")?;
+        let now = get_timestamp();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            r#"INSERT INTO writeOutbox (jobType, jobKey, payload, attempts, nextAttemptAt, createdAt, updatedAt)
+               VALUES (?1, ?2, ?3, 0, ?4, ?5, ?5)"#,
+            params![job.job_type(), job.ordering_key(), payload, now_secs(), now],
+        )?;
+        let outbox_id = tx.last_insert_rowid();
+        insert_keys(&tx, outbox_id, &job.ordering_keys())?;
+        tx.commit()?;
+        Ok(outbox_id)
+    }
+
+    /// 実行時刻を迎えたジョブを登録順に取得
+    pub fn due_entries(&self, limit: usize) -> Result<Vec<OutboxEntry>> {
+        let conn = self.pool.get_connection()?;
+        let mut stmt = conn.prepare(&format!(
+            r#"SELECT id, jobType, payload, attempts FROM writeOutbox o
+               WHERE nextAttemptAt <= ?1 AND {}
+               ORDER BY id LIMIT ?2"#,
+            HEAD_OF_KEY
+        ))?;
+        let rows = stmt.query_map(params![now_secs(), limit as i64], |row| {
+            Ok(OutboxEntry {
+                id: row.get(0)?,
+                job_type: row.get(1)?,
+                payload: row.get(2)?,
+                attempts: row.get(3)?,
+            })
+        })?;
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row?);
+        }
+        Ok(entries)
+    }
+
+    /// 次に実行時刻を迎えるジョブまでの待ち時間（ジョブが無い場合はNone）
+    pub fn next_due_in(&self) -> Result<Option<Duration>> {
+        let conn = self.pool.get_connection()?;
+        let next: Option<i64> = conn.query_row(
+            &format!("SELECT MIN(nextAttemptAt) FROM writeOutbox o WHERE {}", HEAD_OF_KEY),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(next.map(|at| Duration::from_secs((at - now_secs()).max(0) as u64)))
+    }
+
+    /// 未処理のジョブ件数
+    pub fn pending_count(&self) -> Result<i64> {
+        let conn = self.pool.get_connection()?;
+        Ok(conn.query_row("SELECT COUNT(*) FROM writeOutbox", [], |row| row.get(0))?)
+    }
+
+    /// 処理に成功したジョブを削除し、排他キーごとに処理済みのアウトボックスIDを記録
+    pub fn complete(&self, id: i64) -> Result<()> {
+        let conn = self.pool.get_connection()?;
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            r#"INSERT INTO writeKeyWatermarks (jobKey, lastOutboxId)
+               SELECT jobKey, outboxId FROM writeOutboxKeys WHERE outboxId = ?1 AND exclusive = 1
+               ON CONFLICT(jobKey) DO UPDATE SET lastOutboxId = MAX(lastOutboxId, excluded.lastOutboxId)"#,
+            params![id],
+        )?;
+        tx.execute("DELETE FROM writeOutboxKeys WHERE outboxId = ?1", params![id])?;
+        tx.execute("DELETE FROM writeOutbox WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 処理に失敗したジョブを再試行待ちにする（試行回数が上限に達した場合はデッドレターへ移す）
+    pub fn fail(&self, entry: &OutboxEntry, error: &str) -> Result<FailureOutcome> {
+        let attempts = entry.attempts + 1;
+        if attempts >= MAX_ATTEMPTS {
+            self.dead_letter(entry.id, attempts, error)?;
+            return Ok(FailureOutcome::DeadLettered);
+        }
+
+        let delay_secs = backoff_secs(attempts);
+        let conn = self.pool.get_connection()?;
+        conn.execute(
+            r#"UPDATE writeOutbox
+               SET attempts = ?1, nextAttemptAt = ?2, lastError = ?3, updatedAt = ?4
+               WHERE id = ?5"#,
+            params![attempts, now_secs() + delay_secs, error, get_timestamp(), entry.id],
+        )?;
+        Ok(FailureOutcome::Retry { delay_secs })
+    }
+
+    /// ジョブをアウトボックスからデッドレターへ移す
+    pub fn dead_letter(&self, id: i64, attempts: i64, error: &str) -> Result<()> {
+        let conn = self.pool.get_connection()?;
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            r#"INSERT INTO writeDeadLetters (outboxId, jobType, jobKey, payload, attempts, lastError, createdAt, failedAt)
+               SELECT id, jobType, jobKey, payload, ?1, ?2, createdAt, ?3 FROM writeOutbox WHERE id = ?4"#,
+            params![attempts, error, get_timestamp(), id],
+        )?;
+        tx.execute("DELETE FROM writeOutboxKeys WHERE outboxId = ?1", params![id])?;
+        tx.execute("DELETE FROM writeOutbox WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// デッドレターの一覧（新しい順）
+    pub fn list_dead_letters(&self) -> Result<Vec<DeadWriteJob>> {
+        let conn = self.pool.get_connection()?;
+        let mut stmt = conn.prepare(
+            r#"SELECT id, jobType, jobKey, payload, attempts, lastError, createdAt, failedAt, outboxId
+               FROM writeDeadLetters ORDER BY id DESC"#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let job_key: String = row.get(2)?;
+            let payload: String = row.get(3)?;
+            let outbox_id: Option<i64> = row.get(8)?;
+            Ok((
+                DeadWriteJob {
+                    id: row.get(0)?,
+                    job_type: row.get(1)?,
+                    job_key: job_key.clone(),
+                    payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::String(payload.clone())),
+                    attempts: row.get(4)?,
+                    last_error: row.get(5)?,
+                    created_at: row.get(6)?,
+                    failed_at: row.get(7)?,
+                    superseded: false,
+                },
+                dead_letter_keys(&job_key, &payload),
+                outbox_id,
+            ))
+        })?;
+        let mut jobs = Vec::new();
+        for row in rows {
+            let (mut job, keys, outbox_id) = row?;
+            job.superseded = is_superseded(&conn, outbox_id, &keys)?;
+            jobs.push(job);
+        }
+        Ok(jobs)
+    }
+
+    /// デッドレターのジョブをアウトボックスの末尾に戻す（試行回数はリセット）
+    /// 同じレコードへのより新しい書き込みがある場合は、古い内容で上書きしないようエラーにする
+    pub fn retry_dead_letter(&self, id: i64) -> Result<i64> {
+        let conn = self.pool.get_connection()?;
+        let tx = conn.unchecked_transaction()?;
+        let found: Option<(String, String, String, Option<i64>)> = tx.query_row(
+            "SELECT jobType, jobKey, payload, outboxId FROM writeDeadLetters WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        ).optional()?;
+        let (job_type, job_key, payload, original_outbox_id) = found
+            .ok_or_else(|| anyhow::anyhow!("Dead write job not found: {}", id))?;
+        let keys = dead_letter_keys(&job_key, &payload);
+        if is_superseded(&tx, original_outbox_id, &keys)? {
+            return Err(anyhow::anyhow!(
+                "Dead write job {} is superseded by a newer write to the same record; discard it instead",
+                id
+            ));
+        }
+        let now = get_timestamp();
+        tx.execute(
+            r#"INSERT INTO writeOutbox (jobType, jobKey, payload, attempts, nextAttemptAt, createdAt, updatedAt)
+               VALUES (?1, ?2, ?3, 0, ?4, ?5, ?5)"#,
+            params![job_type, job_key, payload, now_secs(), now],
+        )?;
+        let outbox_id = tx.last_insert_rowid();
+        insert_keys(&tx, outbox_id, &keys)?;
+        tx.execute("DELETE FROM writeDeadLetters WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(outbox_id)
+    }
+
+    /// デッドレターのジョブを破棄
+    pub fn discard_dead_letter(&self, id: i64) -> Result<()> {
+        let conn = self.pool.get_connection()?;
+        let deleted = conn.execute("DELETE FROM writeDeadLetters WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(anyhow::anyhow!("Dead write job not found: {}", id));
+        }
+        Ok(())
+    }
+}
@@ -3,10 +3,13 @@
  * データベースへの書き込み操作を表すenum
  */
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+// アウトボックス（writeOutboxテーブル）にJSONとして保存されるため、フィールド名を変更する場合は既存データとの互換性に注意
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
 pub enum WriteJob {
     // エンティティ操作
     UpsertEntity {
@@ -65,3 +68,93 @@ pub enum WriteJob {
         error: Option<String>, // エラーメッセージ（失敗時）
     },
 }
+
+impl WriteJob {
+    /// ジョブの種類名（アウトボックス・デッドレターの表示用）
+    pub fn job_type(&self) -> &'static str {
+        match self {
+            WriteJob::UpsertEntity { .. } => "UpsertEntity",
+            WriteJob::DeleteEntities { .. } => "DeleteEntities",
+            WriteJob::UpsertRelation { .. } => "UpsertRelation",
+            WriteJob::DeleteRelations { .. } => "DeleteRelations",
+            WriteJob::UpsertTopic { .. } => "UpsertTopic",
+            WriteJob::DeleteTopics { .. } => "DeleteTopics",
+            WriteJob::UpsertOrganization { .. } => "UpsertOrganization",
+            WriteJob::DeleteOrganization { .. } => "DeleteOrganization",
+            WriteJob::DeleteMeetingNote { .. } => "DeleteMeetingNote",
+            WriteJob::UpdateChromaSyncStatus { .. } => "UpdateChromaSyncStatus",
+        }
+    }
+
+    /// 順序を保証するキー
+    ///
+    /// 同じキーを持つジョブは登録順に処理され、失敗したジョブが後続に追い越されない。
+    /// レコード単位の書き込みはレコードIDを排他キーに、組織を共有キーにするため、別レコードのジョブは互いに待たない。
+    /// 議事録の削除は組織内のトピック・エンティティ・リレーションにも及ぶため組織を、
+    /// 組織の削除は子組織にも及ぶためすべてのデータを排他キーにする
+    pub fn ordering_keys(&self) -> Vec<OrderingKey> {
+        let records = |kind: &str, ids: &[&String], organization_id: &str| {
+            let mut keys = vec![OrderingKey::shared(DATA_KEY), OrderingKey::shared(format!("org:{}", organization_id))];
+            keys.extend(ids.iter().map(|id| OrderingKey::exclusive(format!("{}:{}", kind, id))));
+            keys
+        };
+        match self {
+            WriteJob::UpsertEntity { entity_id, organization_id, .. } => records("entity", &[entity_id], organization_id),
+            WriteJob::DeleteEntities { entity_ids, organization_id } => {
+                records("entity", &entity_ids.iter().collect::<Vec<_>>(), organization_id)
+            }
+            WriteJob::UpsertRelation { relation_id, organization_id, .. } => records("relation", &[relation_id], organization_id),
+            WriteJob::DeleteRelations { relation_ids, organization_id } => {
+                records("relation", &relation_ids.iter().collect::<Vec<_>>(), organization_id)
+            }
+            WriteJob::UpsertTopic { topic_id, organization_id, .. } => records("topic", &[topic_id], organization_id),
+            WriteJob::DeleteTopics { topic_ids, organization_id } => {
+                records("topic", &topic_ids.iter().collect::<Vec<_>>(), organization_id)
+            }
+            WriteJob::UpsertOrganization { organization_id, .. } => vec![
+                OrderingKey::shared(DATA_KEY),
+                OrderingKey::exclusive(format!("org:{}", organization_id)),
+            ],
+            WriteJob::DeleteOrganization { .. } => vec![OrderingKey::exclusive(DATA_KEY)],
+            WriteJob::DeleteMeetingNote { meeting_note_id, organization_id } => vec![
+                OrderingKey::shared(DATA_KEY),
+                OrderingKey::exclusive(format!("org:{}", organization_id)),
+                OrderingKey::exclusive(format!("meetingNote:{}", meeting_note_id)),
+            ],
+            WriteJob::UpdateChromaSyncStatus { entity_type, entity_id, .. } => {
+                vec![OrderingKey::exclusive(format!("chromaSync:{}:{}", entity_type, entity_id))]
+            }
+        }
+    }
+
+    /// アウトボックス・デッドレターに表示する代表のキー（最後の排他キー）
+    pub fn ordering_key(&self) -> String {
+        self.ordering_keys()
+            .into_iter()
+            .filter(|key| key.exclusive)
+            .last()
+            .map(|key| key.key)
+            .unwrap_or_else(|| DATA_KEY.to_string())
+    }
+}
+
+/// すべてのデータの書き込みに共通するキー
+const DATA_KEY: &str = "data";
+
+/// ジョブの順序を保証するキー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderingKey {
+    pub key: String,
+    /// 排他キーは同じキーの古いジョブすべてを待ち、共有キーは同じキーの古い排他ジョブだけを待つ
+    pub exclusive: bool,
+}
+
+impl OrderingKey {
+    fn exclusive(key: impl Into<String>) -> Self {
+        Self { key: key.into(), exclusive: true }
+    }
+
+    fn shared(key: impl Into<String>) -> Self {
+        Self { key: key.into(), exclusive: false }
+    }
+}
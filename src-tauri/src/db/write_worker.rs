@@ -1,37 +1,130 @@
 /**
  * 書き込み専用ワーカー
  * すべてのデータベース書き込み操作を1本の通路に集約
+ *
+ * ジョブはアウトボックス（writeOutbox）から取り出して処理する
+ * チャネルは「新しいジョブが保存された」ことを知らせるだけで、ジョブ本体はSQLiteに残る
  */
 
 use async_channel::Receiver;
 use crate::database::pool::DatabasePool;
+use crate::db::outbox::{FailureOutcome, OutboxEntry, WriteOutbox};
 use crate::db::write_job::WriteJob;
 use anyhow::{Context, Result};
 use rusqlite::params;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 use serde_json::Value;
 
+/// 1回に取り出すジョブ数
+const BATCH_SIZE: usize = 32;
+
+/// 待機中のジョブが無い場合にアウトボックスを確認する間隔
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
 pub struct WriteWorker {
     pool: DatabasePool,
+    outbox: Arc<WriteOutbox>,
 }
 
 impl WriteWorker {
-    pub fn new(pool: DatabasePool) -> Self {
-        Self { pool }
+    pub fn new(pool: DatabasePool, outbox: Arc<WriteOutbox>) -> Self {
+        Self { pool, outbox }
     }
 
-    pub async fn run(&self, rx: Receiver<WriteJob>) {
+    /// ワーカーを起動（rxには保存済みジョブのアウトボックスIDが通知される）
+    pub async fn run(&self, rx: Receiver<i64>) {
         eprintln!("[DB-WRITER] 書き込みワーカーを起動しました");
-        
-        while let Ok(job) = rx.recv().await {
-            if let Err(e) = self.handle_job(&job).await {
+
+        // 前回終了時に残っていたジョブを再実行
+        match self.outbox.pending_count() {
+            Ok(0) => {}
+            Ok(count) => eprintln!("[DB-WRITER] 未処理のジョブを再実行します: {}件", count),
+            Err(e) => eprintln!("[DB-WRITER] アウトボックスの確認に失敗しました: {e:#}"),
+        }
+
+        loop {
+            self.drain_due_jobs().await;
+
+            let wait = match self.outbox.next_due_in() {
+                Ok(Some(delay)) => delay.min(IDLE_POLL_INTERVAL),
+                Ok(None) => IDLE_POLL_INTERVAL,
+                Err(e) => {
+                    eprintln!("[DB-WRITER] アウトボックスの確認に失敗しました: {e:#}");
+                    IDLE_POLL_INTERVAL
+                }
+            };
+
+            tokio::select! {
+                received = rx.recv() => {
+                    if received.is_err() {
+                        break;
+                    }
+                }
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+
+        eprintln!("[DB-WRITER] 書き込みワーカーを停止しました");
+    }
+
+    /// 実行時刻を迎えたジョブが無くなるまで処理
+    async fn drain_due_jobs(&self) {
+        loop {
+            let entries = match self.outbox.due_entries(BATCH_SIZE) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    eprintln!("[DB-WRITER] アウトボックスからの取得に失敗しました: {e:#}");
+                    return;
+                }
+            };
+            if entries.is_empty() {
+                return;
+            }
+
+            for entry in &entries {
+                self.process_entry(entry).await;
+            }
+        }
+    }
+
+    async fn process_entry(&self, entry: &OutboxEntry) {
+        let job: WriteJob = match serde_json::from_str(&entry.payload) {
+            Ok(job) => job,
+            Err(e) => {
+                // 復元できないジョブは再試行しても成功しないため、すぐにデッドレターへ移す
+                eprintln!("[DB-WRITER] ジョブの復元に失敗しました (id={}, type={}): {}", entry.id, entry.job_type, e);
+                let message = format!("Failed to deserialize write job: {}", e);
+                if let Err(e) = self.outbox.dead_letter(entry.id, entry.attempts + 1, &message) {
+                    eprintln!("[DB-WRITER] デッドレターへの移動に失敗しました: {e:#}");
+                }
+                return;
+            }
+        };
+
+        let result = match self.handle_job(&job).await {
+            Ok(()) => self.outbox.complete(entry.id),
+            Err(e) => {
                 eprintln!("[DB-WRITER] ジョブ処理エラー: {e:#}");
                 eprintln!("[DB-WRITER] 失敗したジョブ: {:?}", job);
-                // TODO: エラーログ・監視への通知
+                match self.outbox.fail(entry, &format!("{e:#}")) {
+                    Ok(FailureOutcome::Retry { delay_secs }) => {
+                        eprintln!("[DB-WRITER] {}秒後に再試行します (id={}, 試行回数={})", delay_secs, entry.id, entry.attempts + 1);
+                        Ok(())
+                    }
+                    Ok(FailureOutcome::DeadLettered) => {
+                        eprintln!("[DB-WRITER] ⚠️ 再試行の上限に達したためデッドレターへ移しました (id={}, type={})", entry.id, entry.job_type);
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                }
             }
+        };
+
+        if let Err(e) = result {
+            eprintln!("[DB-WRITER] アウトボックスの更新に失敗しました (id={}): {e:#}", entry.id);
         }
-        
-        eprintln!("[DB-WRITER] 書き込みワーカーを停止しました");
     }
 
     async fn handle_job(&self, job: &WriteJob) -> Result<()> {
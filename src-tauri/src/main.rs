@@ -9,7 +9,7 @@ mod db;
 use std::sync::Arc;
 use async_channel;
 use tauri::Manager;
-use db::{WriteJob, WriteQueueState};
+use db::WriteQueueState;
 
 fn main() {
     // ログシステムの初期化（リリースビルドではINFOレベル）
@@ -57,11 +57,18 @@ fn main() {
             #[cfg(debug_assertions)]
             eprintln!("ℹ️  Rust APIサーバーの起動をスキップしました（Supabase専用）");
             
-            // WriteQueueStateを初期化（Supabase専用のため、ダミーのチャネルを作成）
-            // 注意: 実際には書き込みワーカーは動作しないが、コマンドのState管理のために必要
-            let (_tx, _rx) = async_channel::unbounded::<WriteJob>();
-            let write_queue_state = WriteQueueState {
-                tx: Arc::new(_tx),
+            // WriteQueueStateを初期化
+            // SQLiteが初期化されている場合はアウトボックス付きの書き込みワーカーを起動し、
+            // 初期化されていない場合（Supabase専用）はコマンドのState管理のためにダミーのチャネルを作成
+            let write_queue_state = match database::get_db() {
+                Some(db) => db::start_write_worker(db.get_pool()),
+                None => {
+                    let (_tx, _rx) = async_channel::unbounded::<i64>();
+                    WriteQueueState {
+                        tx: Arc::new(_tx),
+                        outbox: None,
+                    }
+                }
             };
             app.manage(write_queue_state);
             
@@ -87,6 +94,19 @@ fn main() {
             commands::db::collection_get,
             // クエリ操作コマンド（SQLite削除のため無効化、後方互換性のため残す）
             commands::db::query_get,
+            // 全文検索コマンド
+            commands::db::full_text_search,
+            commands::db::rebuild_full_text_search_index,
+            commands::db::hybrid_search,
+            commands::db::get_graph_neighborhood,
+            commands::db::find_graph_shortest_path,
+            commands::db::get_graph_connected_components,
+            commands::db::get_graph_centrality,
+            commands::db::find_duplicate_entities,
+            commands::db::merge_entities,
+            commands::db::list_entity_merge_audit,
+            commands::db::export_knowledge_graph,
+            commands::db::export_knowledge_graph_to_file,
             // データエクスポート/インポートコマンド（SQLite削除のため無効化、後方互換性のため残す）
             commands::db::export_database_data,
             commands::db::import_database_data,
@@ -102,6 +122,11 @@ fn main() {
             commands::app::diagnose_database,
             commands::app::get_table_schema,
             commands::app::update_chroma_sync_status,
+            commands::app::list_dead_write_jobs,
+            commands::app::retry_dead_write_job,
+            commands::app::discard_dead_write_job,
+            commands::app::get_schema_migration_status,
+            commands::app::run_schema_migrations,
             // 組織管理コマンド
             commands::organization::create_org,
             commands::organization::update_org,
@@ -137,7 +162,32 @@ fn main() {
             // commands::organization_company_display::delete_org_company_display_by_ids,
             // commands::organization_company_display::delete_all_org_company_displays_by_org,
             // commands::organization_company_display::delete_all_org_company_displays_by_company,
-            // ChromaDBコマンドは削除されました（Supabase専用のため）
+            // ベクトル検索コマンド（デフォルトはChromaDBバックエンド。環境変数VECTOR_STORE_BACKEND=hnswでHNSWに切り替え）
+            commands::chromadb::chromadb_save_entity_embedding,
+            commands::chromadb::chromadb_get_entity_embedding,
+            commands::chromadb::chromadb_find_similar_entities,
+            commands::chromadb::chromadb_count_entities,
+            commands::chromadb::chromadb_save_relation_embedding,
+            commands::chromadb::chromadb_get_relation_embedding,
+            commands::chromadb::chromadb_find_similar_relations,
+            commands::chromadb::chromadb_save_topic_embedding,
+            commands::chromadb::chromadb_get_topic_embedding,
+            commands::chromadb::chromadb_find_similar_topics,
+            commands::chromadb::chromadb_save_design_doc_embedding,
+            commands::chromadb::chromadb_find_similar_design_docs,
+            commands::chromadb::chromadb_get_design_doc_metadata,
+            commands::chromadb::chromadb_list_design_doc_section_ids,
+            commands::chromadb::chromadb_delete_topic_embedding,
+            commands::chromadb::chromadb_delete_entity_embedding,
+            commands::chromadb::chromadb_delete_relation_embedding,
+            commands::chromadb::chromadb_clear_data_dir,
+            commands::chromadb::chromadb_delete_organization_collections,
+            commands::chromadb::start_chroma_sync,
+            commands::chromadb::cancel_chroma_sync,
+            commands::chromadb::get_chroma_sync_pending_counts,
+            commands::chromadb::generate_embeddings,
+            commands::chromadb::list_embedding_collections,
+            commands::chromadb::reembed_collection,
             // 後方互換性のため、コマンドは残していますが、TypeScript側からは呼び出されません
             // システム設計ドキュメントセクション管理コマンド
             commands::design_doc::create_design_doc_section_cmd,
@@ -146,6 +196,13 @@ fn main() {
             commands::design_doc::get_all_design_doc_sections_cmd,
             commands::design_doc::get_all_design_doc_sections_lightweight_cmd,
             commands::design_doc::delete_design_doc_section_cmd,
+            commands::design_doc::list_design_doc_section_revisions_cmd,
+            commands::design_doc::get_design_doc_section_revision_cmd,
+            commands::design_doc::diff_design_doc_section_revisions_cmd,
+            commands::design_doc::restore_design_doc_section_revision_cmd,
+            commands::design_doc::merge_design_doc_section_cmd,
+            commands::design_doc::export_design_doc_site_cmd,
+            commands::design_doc::check_design_doc_consistency_cmd,
             // システム設計ドキュメントセクション関係管理コマンド
             commands::design_doc::create_design_doc_section_relation_cmd,
             commands::design_doc::update_design_doc_section_relation_cmd,
@@ -164,6 +221,7 @@ fn main() {
             commands::fs::open_url,
             // PlantUMLコマンド
             commands::plantuml::render_plantuml,
+            commands::plantuml::render_plantuml_batch,
             commands::plantuml::check_java_installed,
             // Agentシステムコマンド
             commands::agent_system::save_task_command,
@@ -174,14 +232,36 @@ fn main() {
             commands::agent_system::get_task_execution_command,
             commands::agent_system::get_task_executions_command,
             commands::agent_system::get_all_task_executions_command,
+            commands::agent_system::execute_task_command,
+            commands::agent_system::cancel_task_execution_command,
+            commands::agent_system::run_task_workflow_command,
             commands::agent_system::save_task_chain_command,
             commands::agent_system::get_task_chain_command,
             commands::agent_system::get_all_task_chains_command,
             commands::agent_system::delete_task_chain_command,
+            commands::agent_system::run_task_chain_command,
+            commands::agent_system::cancel_task_chain_run_command,
+            commands::agent_system::get_task_chain_run_command,
+            commands::agent_system::list_task_chain_runs_command,
+            commands::agent_system::get_task_chain_run_executions_command,
             commands::agent_system::save_agent_command,
             commands::agent_system::get_agent_command,
             commands::agent_system::get_all_agents_command,
             commands::agent_system::delete_agent_command,
+            commands::agent_system::list_agent_prompt_versions_command,
+            commands::agent_system::get_agent_prompt_version_command,
+            commands::agent_system::diff_agent_prompt_versions_command,
+            commands::agent_system::rollback_agent_prompt_command,
+            commands::agent_system::pin_task_prompt_version_command,
+            commands::agent_system::run_prompt_evaluation_command,
+            commands::agent_system::get_prompt_evaluation_command,
+            commands::agent_system::list_prompt_evaluations_command,
+            commands::agent_system::send_a2a_message_command,
+            commands::agent_system::get_a2a_message_command,
+            commands::agent_system::wait_for_a2a_response_command,
+            commands::agent_system::get_a2a_inbox_command,
+            commands::agent_system::mark_a2a_messages_read_command,
+            commands::agent_system::get_a2a_task_threads_command,
             commands::agent_system::save_mcp_tool_command,
             commands::agent_system::get_mcp_tool_command,
             commands::agent_system::get_all_mcp_tools_command,
@@ -197,8 +277,15 @@ fn main() {
             commands::graphviz::get_graphviz_yaml_file_cmd,
             commands::graphviz::get_all_graphviz_yaml_files_cmd,
             commands::graphviz::delete_graphviz_yaml_file_cmd,
+            commands::graphviz::validate_graphviz_yaml_cmd,
+            commands::graphviz::list_graphviz_yaml_versions_cmd,
+            commands::graphviz::diff_graphviz_yaml_versions_cmd,
+            commands::graphviz::rollback_graphviz_yaml_file_cmd,
             commands::graphviz::create_graphviz_dot_file_cmd,
             commands::graphviz::get_graphviz_dot_file_cmd,
+            commands::graphviz::generate_graphviz_dot_cmd,
+            commands::graphviz::generate_and_save_graphviz_dot_cmd,
+            commands::graphviz::render_graphviz,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");